@@ -0,0 +1,58 @@
+use std::fs;
+use std::path::PathBuf;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+
+use eza::fs::filter::{sort_files_by, Collation, SortCase, SortField};
+use eza::fs::{Dir, File};
+
+/// How many files get sorted in each benchmark.
+const COUNT: usize = 20_000;
+
+/// Makes a directory full of files with names that need natural sorting,
+/// with a directory every so often, and reads them all in.
+fn listing() -> Vec<File<'static>> {
+    let dir = std::env::temp_dir().join(format!("eza-sort-bench-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let files = (0..COUNT)
+        .map(|n| {
+            let path = dir.join(format!("File{}-{}.txt", (n * 7919) % COUNT, n % 3));
+            if n % 10 == 0 {
+                fs::create_dir_all(&path).unwrap();
+            } else {
+                fs::write(&path, n.to_string()).unwrap();
+            }
+            path
+        })
+        .collect::<Vec<PathBuf>>()
+        .into_iter()
+        .map(|path| File::from_args(path, None::<&Dir>, None, false, false).unwrap())
+        .collect();
+
+    fs::remove_dir_all(&dir).unwrap();
+    files
+}
+
+pub fn criterion_benchmark(c: &mut Criterion) {
+    let files = listing();
+    let fields = [
+        ("name", SortField::Name(SortCase::AaBbCc)),
+        ("name-natural", SortField::NameNatural(Collation::Codepoint)),
+        ("size", SortField::Size),
+        ("modified", SortField::ModifiedDate),
+    ];
+
+    for (name, field) in fields {
+        c.bench_function(&format!("sort {COUNT} files by {name}"), |b| {
+            b.iter_batched_ref(
+                || files.iter().collect::<Vec<_>>(),
+                |files| sort_files_by(black_box(files), field, false, true),
+                BatchSize::LargeInput,
+            );
+        });
+    }
+}
+
+criterion_group!(benches, criterion_benchmark);
+criterion_main!(benches);
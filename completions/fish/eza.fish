@@ -1,16 +1,37 @@
 # Meta-stuff
 complete -c eza -s v -l version -d "Show version of eza"
 complete -c eza -l help -d "Show list of command-line options"
+complete -c eza -l generate-completions -d "Print a completion script for a shell" -x -a "bash zsh fish nushell powershell"
+complete -c eza -l debug-timing -d "Print how long each part of the listing took"
+complete -c eza -l io-timeout -d "Give up on filesystem calls that take longer than this" -x
+complete -c eza -l sftp -d "List sftp://host/path arguments on other computers"
+complete -c eza -l print-ls-colors -d "Print the file name colours as an LS_COLORS string"
 
 # Display options
 complete -c eza -s 1 -l oneline -d "Display one entry per line"
+complete -c eza -s 0 -l null -d "End each entry with a NUL byte"
 complete -c eza -s l -l long -d "Display extended file metadata as a table"
 complete -c eza -s G -l grid -d "Display entries in a grid"
 complete -c eza -s x -l across -d "Sort the grid across, rather than downwards"
+complete -c eza -l grid-order -d "Fill the grid by rows or by columns" -x -a "
+  rows\t'Fill each row before the next, like --across'
+  columns\t'Fill each column before the next'
+"
+complete -c eza -l thumbnails -d "Display images as a grid of previews" -x -a "
+  auto\t'Guess from the terminal'
+  kitty\t'The kitty graphics protocol'
+  sixel\t'Sixel graphics'
+  symbols\t'Coloured block characters'
+"
 complete -c eza -s R -l recurse -d "Recurse into directories"
 complete -c eza -s T -l tree -d "Recurse into directories as a tree"
 complete -c eza -s X -l dereference -d "Dereference symbolic links when displaying file information"
+complete -c eza -l no-dereference-owner -d "Keep symbolic links' own owners and permissions with --dereference"
 complete -c eza -s F -l classify -d "Display type indicator by file names"
+complete -c eza -l classify-indicators -d "Which type indicators to use" -x -a "
+  basic\t'The indicators ls -F uses'
+  extended\t'Also mark broken links, setuid and setgid files, and doors'
+"
 complete -c eza -l color \
     -l colour -d "When to use terminal colours" -x -a "
     always\t'Always use colour'
@@ -18,6 +39,13 @@ complete -c eza -l color \
     automatic\t'Use colour if standard output is a terminal'
     never\t'Never use colour'
 "
+complete -c eza -l theme -d "Use the colours from a theme file" -x
+complete -c eza -l color-theme \
+    -l colour-theme -d "Which background to pick default colours for" -x -a "
+    dark\t'Pick colours for a dark background'
+    light\t'Pick colours for a light background'
+    auto\t'Ask the terminal which background it has'
+"
 complete -c eza -l color-scale \
     -l colour-scale -d "Highlight levels 'field' distinctly" -x -a "
     all\t''
@@ -30,6 +58,21 @@ complete -c eza -l color-scale-mode \
     fixed\t'Highlight based on fixed colors'
     gradient\t'Highlight based \'field\' in relation to other files'
 "
+complete -c eza -l no-color-scale-floor \
+    -l no-colour-scale-floor -d "Colour sizes by fixed thresholds in --color-scale"
+complete -c eza -l color-scale-time \
+    -l colour-scale-time -d "Shade every timestamp by one time field" -x -a "
+    modified\t'Shade by when the contents changed'
+    changed\t'Shade by when the inode changed'
+    accessed\t'Shade by when the file was read'
+    created\t'Shade by when the file was created'
+"
+complete -c eza -l color-depth \
+    -l colour-depth -d "How many colours to draw gradients with" -x -a "
+    8\t'Keep the theme colours, without gradients'
+    256\t'Round gradients to the 256-colour palette'
+    truecolor\t'Draw gradients with 24-bit colours'
+"
 complete -c eza -l icons -d "When to display icons" -x -a "
   always\t'Always display icons'
   auto\t'Display icons if standard output is a terminal'
@@ -37,26 +80,81 @@ complete -c eza -l icons -d "When to display icons" -x -a "
   never\t'Never display icons'
 "
 complete -c eza -l no-quotes -d "Don't quote file names with spaces"
+complete -c eza -s N -l literal -d "Print file names as they are, without escaping"
+complete -c eza -l quoting-style -d "How to quote file names" -x -a "
+    literal\t'Print names as they are'
+    shell\t'Quote names for the shell'
+    c\t'Show names as C strings'
+    escape\t'Escape names with backslashes'
+"
 complete -c eza -l hyperlink -d "Display entries as hyperlinks"
+complete -c eza -l hardlink-groups -d "Mark files hard linked to another listed file, colouring each group alike"
+complete -c eza -l clones -d "Mark files that share data blocks with a clone"
+complete -c eza -l highlight-new -d "Mark files added or changed since the directory was last listed"
+complete -c eza -l suggest-broken-links -d "Suggest a similarly-named file for broken links"
+complete -c eza -l security-audit -d "Flag risky permissions"
+complete -c eza -l audit-exit-code -d "Exit code for when --security-audit flags anything" -x
+complete -c eza -l in-use -d "Mark files that processes have open"
+complete -c eza -l truncate-names -d "Shorten names that are too wide" -x -a "
+  middle\t'Cut out the middle of the name'
+  end\t'Cut out the end of the name, keeping the extension'
+"
+complete -c eza -l total -d "Show a footer with counts and sizes after each listing"
+complete -c eza -l dir-header -d "Line to print before each directory's files" -x
+complete -c eza -l dir-footer -d "Line to print after each directory's files" -x
+complete -c eza -l compare -d "List two directories against each other"
+complete -c eza -l stat -d "Show everything about each file, one field per line"
+complete -c eza -l which -d "List every program called NAME along \$PATH" -x -a "(__fish_complete_command)"
+complete -c eza -l watch -d "List the files again whenever they change"
+complete -c eza -l exec -d "With --watch, run a command after each change" -x -a "(__fish_complete_command)"
+complete -c eza -l snapshot-out -d "Save the files' details to a file instead of listing them" -r -F
+complete -c eza -l snapshot-in -d "List the files saved with --snapshot-out" -r -F
+complete -c eza -l format -d "How to write the listing" -x -a "
+  text\t'Lay the listing out for reading'
+  jsonl\t'One JSON object per file, per line'
+  html\t'A standalone HTML page, in colour'
+"
+complete -c eza -l fields -d "Which fields to write as JSON" -x -a "path name type size inode links blocks mode uid user gid group modified accessed changed created target git"
+complete -c eza -l format-template -d "Write a line per file filled in from a template" -x
+complete -c eza -l summary -d "Add the listing up by extension" -x -a "
+  ext\t'One line per file extension'
+"
+complete -c eza -l summary-sort -d "Which column to sort the summary by" -x -a "
+  ext\t'Sort by extension'
+  count\t'Most files first'
+  size\t'Biggest combined size first'
+  largest\t'Biggest single file first'
+"
+complete -c eza -l paging -d "When to send the output through a pager" -x -a "
+  always\t'Always use a pager'
+  auto\t'Use a pager if the output is too long for the terminal'
+  automatic\t'Use a pager if the output is too long for the terminal'
+  never\t'Never use a pager'
+"
 complete -c eza -l absolute -d "Display entries with their absolute path" -x -a "
   on\t'Show absolute path for listed entries'
   follow\t'Show absolute path with followed symlinks'
   off\t'Do not show the absolute path'
 "
+complete -c eza -l relative-to -d "Display entries with their path relative to a directory" -x -a "(__fish_complete_directories)"
 complete -c eza -l smart-group -d "Only show group if it has a different name from owner"
 
 # Filtering and sorting options
 complete -c eza -l group-directories-first -d "Sort directories before other files"
 complete -c eza -l git-ignore -d "Ignore files mentioned in '.gitignore'"
+complete -c eza -l git-ignore-debug -d "Report the rule that hid each ignored file"
+complete -c eza -l only-modified-git -d "List only files with changes that Git knows about"
 complete -c eza -s a -l all -d "Show hidden and 'dot' files. Use this twice to also show the '.' and '..' directories"
 complete -c eza -s A -l almost-all -d "Equivalent to --all; included for compatibility with `ls -A`"
 complete -c eza -s d -l list-dirs -d "List directories like regular files"
 complete -c eza -s L -l level -d "Limit the depth of recursion" -x -a "1 2 3 4 5 6 7 8 9"
+complete -c eza -l follow-symlinks -d "Recurse into symlinked directories, stopping at loops"
 complete -c eza -s w -l width -d "Limits column output of grid, 0 implies auto-width"
 complete -c eza -s r -l reverse -d "Reverse the sort order"
 complete -c eza -s s -l sort -d "Which field to sort by" -x -a "
     accessed\t'Sort by file accessed time'
     age\t'Sort by file modified time (newest first)'
+    blocks\t'Sort by blocks used on disk'
     changed\t'Sort by changed time'
     created\t'Sort by file modified time'
     date\t'Sort by file modified time'
@@ -67,26 +165,75 @@ complete -c eza -s s -l sort -d "Which field to sort by" -x -a "
     filename\t'Sort by filename'
     Filename\t'Sort by filename (uppercase first)'
     inode\t'Sort by file inode'
+    links\t'Sort by number of hard links'
     modified\t'Sort by file modified time'
     name\t'Sort by filename'
     Name\t'Sort by filename (uppercase first)'
+    name-natural\t'Sort by filename, with numbers in order'
     newest\t'Sort by file modified time (newest first)'
-    none\t'Do not sort files at all'
+  none\t'Do not sort files at all'
     oldest\t'Sort by file modified time'
     size\t'Sort by file size'
     time\t'Sort by file modified time'
     type\t'Sort by file type'
 "
+complete -c eza -l collate -d "How --sort=name-natural compares letters" -x -a "
+    locale\t'Use Unicode collation for the locale'
+    codepoint\t'Compare Unicode code points'
+"
+complete -c eza -l sort-levels -d "Sort some levels of a tree differently" -x
+complete -c eza -l sort-dirs-first-per-level -d "List directories first only at these tree levels" -x
 
 complete -c eza -s I -l ignore-glob -d "Ignore files that match these glob patterns" -r
+complete -c eza -l ignore-glob-file -d "Ignore files that match the patterns in this file" -r -F
+complete -c eza -l exclude-vcs -d "Ignore version control directories, such as .git"
+complete -c eza -l ignore-case -d "Match glob patterns regardless of case"
+complete -c eza -l case-sensitive -d "Match glob patterns only with the same case"
 complete -c eza -s D -l only-dirs -d "List only directories"
 complete -c eza -s f -l only-files -d "List only files"
+complete -c eza -l only-symlinks -d "List only symbolic links"
+complete -c eza -l only-executables -d "List only executable files"
+complete -c eza -l dangling-only -d "List only symbolic links whose target is missing"
+complete -c eza -l type -d "List only the given kinds of file" -x -a "
+    f\t'Regular files'
+    d\t'Directories'
+    l\t'Symbolic links'
+    x\t'Executable files'
+    p\t'Named pipes'
+    s\t'Sockets'
+    b\t'Block devices'
+    c\t'Character devices'
+    file\t'Regular files'
+    dir\t'Directories'
+    symlink\t'Symbolic links'
+    executable\t'Executable files'
+    pipe\t'Named pipes'
+    socket\t'Sockets'
+    block-device\t'Block devices'
+    char-device\t'Character devices'
+"
+complete -c eza -l min-size -d "List only files at least this big" -x
+complete -c eza -l max-size -d "List only files at most this big" -x
+complete -c eza -l tag -d "List only files with this Finder tag" -x
+complete -c eza -l newer-than -d "List only files modified after this one" -r -F
+complete -c eza -l older-than -d "List only files modified before this one" -r -F
+complete -c eza -l reference-time -d "Which timestamp to compare with --newer-than and --older-than" -x -a "
+    modified\t'Compare modified times'
+    changed\t'Compare changed times'
+    accessed\t'Compare accessed times'
+    created\t'Compare created times'
+"
 
 # Long view options
 complete -c eza -s b -l binary -d "List file sizes with binary prefixes"
 complete -c eza -s B -l bytes -d "List file sizes in bytes, without any prefixes"
 complete -c eza -s g -l group -d "List each file's group"
 complete -c eza -s h -l header -d "Add a header row to each column"
+complete -c eza -l header-mode -d "How often to print the header row" -x -a "
+  once\t'Show the header once per directory'
+  repeat\t'Repeat the header every screenful'
+"
+complete -c eza -l header-units -d "Add a row of units and sort arrows under the header"
 complete -c eza -s H -l links -d "List each file's number of hard links"
 complete -c eza -s i -l inode -d "List each file's inode number"
 complete -c eza -s S -l blocksize -d "List each file's size of allocated file system blocks"
@@ -98,11 +245,13 @@ complete -c eza -s t -l time -d "Which timestamp field to list" -x -a "
 "
 complete -c eza -s m -l modified -d "Use the modified timestamp field"
 complete -c eza -s n -l numeric -d "List numeric user and group IDs."
+complete -c eza -l numeric-ids -d "Which of the user and group columns show IDs" -x -a "uid gid both"
 complete -c eza -l changed -d "Use the changed timestamp field"
 complete -c eza -s u -l accessed -d "Use the accessed timestamp field"
 complete -c eza -s U -l created -d "Use the created timestamp field"
 complete -c eza -l time-style -d "How to format timestamps" -x -a "
     default\t'Use the default time style'
+    locale\t'Use the default time style, with the day and month in the locale\'s order'
     iso\t'Display brief ISO timestamps'
     long-iso\t'Display longer ISO timestamps, up to the minute'
     full-iso\t'Display full ISO timestamps, up to the nanosecond'
@@ -110,18 +259,56 @@ complete -c eza -l time-style -d "How to format timestamps" -x -a "
     +FORMAT\t'Use custom time style'
 "
 complete -c eza -l total-size -d "Show recursive directory size (unix only)"
+complete -c eza -l size-on-disk -d "Show directory disk usage in tree view"
+complete -c eza -l show-counts -d "Show file counts and sizes of directories in tree view"
+complete -c eza -l prune -d "Leave out directories that end up empty in tree view"
+complete -c eza -l tree-style -d "How to draw the branches in tree view" -x -a "
+  unicode\t'Box drawing lines (default)'
+  ascii\t'Plain ASCII characters'
+  rounded\t'Box drawing lines with rounded corners'
+  bold\t'Heavy box drawing lines'
+  double\t'Double box drawing lines'
+  none\t'Only indentation'
+"
+complete -c eza -l ascii-depth-guides -d "Number every Nth level's branch line with its depth in tree view" -x
+complete -c eza -l align-sections -d "Line the columns up across every directory in a recursive listing"
+complete -c eza -l group-by -d "Split the listing into sections" -x -a "
+  none\t'One section (default)'
+  mtime-bucket\t'By when each file was modified'
+"
+complete -c eza -l time-buckets -d "Which sections to split the listing into by modification time" -x
 complete -c eza -l no-permissions -d "Suppress the permissions field"
 complete -c eza -s o -l octal-permissions -d "List each file's permission in octal format"
 complete -c eza -l no-filesize -d "Suppress the filesize field"
 complete -c eza -l no-user -d "Suppress the user field"
+complete -c eza -l columns -d "Show exactly these columns, in this order" -x
 complete -c eza -l no-time -d "Suppress the time field"
 complete -c eza -s M -l mounts -d "Show mount details"
 complete -c eza -l stdin -d "When piping to eza. Read file names from stdin"
+complete -c eza -l frecency -d "Look up z:keyword arguments with zoxide or in a list of visited directories"
+complete -c eza -l unique -d "List each path only once"
+complete -c eza -l common-names -d "Mark files with the same name as one under another argument"
 
 # Optional extras
-complete -c eza -l git -d "List each file's Git status, if tracked"
+complete -c eza -l git -d "List each file's Git status, if tracked" -x -a "
+  sync\t'Wait for the Git status before listing'
+  async\t'Fill in the Git status after listing'
+"
+complete -c eza -l git-ignore-submodules -d "Choose which changes in submodules to ignore" -x -a "
+  all\t'Never show submodules as changed'
+  dirty\t'Only show submodules whose commit has moved'
+  untracked\t'Ignore untracked files in submodules'
+  none\t'Show any change in submodules'
+"
+complete -c eza -l git-status-porcelain-compat -d "Show Git statuses with the letters from git status --short"
+complete -c eza -l git-detail -d "Split the Git column into staged and unstaged, with rename scores"
+complete -c eza -l git-age-since -d "Show when each file changed since a Git reference" -x
 complete -c eza -l no-git -d "Suppress Git status"
 complete -c eza -l git-repos -d "List each git-repos status and branch name"
 complete -c eza -l git-repos-no-status -d "List each git-repos branch name (much faster)"
+complete -c eza -l git-repos-behind -d "Show how many commits each git-repos branch is behind its upstream"
 complete -c eza -s '@' -l extended -d "List each file's extended attributes and sizes"
+complete -c eza -l xattr-values -d "Preview extended attribute values"
 complete -c eza -s Z -l context -d "List each file's security context"
+complete -c eza -l target -d "List where each symlink finally resolves to"
+complete -c eza -l quota -d "List how much of its quota each file's owner is using"
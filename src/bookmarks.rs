@@ -0,0 +1,110 @@
+//! Short names for directories, for arguments like `@proj`.
+//!
+//! Bookmarks are given names in the `bookmarks` section of `config.yml` in
+//! eza’s configuration directory. An argument that starts with `@` and isn’t
+//! the name of an actual file gets replaced by the directory its bookmark
+//! points to, before anything is listed, and anything after the name is
+//! looked up inside that directory, so `@proj/src` lists the `src`
+//! directory of the `proj` bookmark.
+
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use log::*;
+
+/// What arguments that should be looked up start with.
+pub const PREFIX: char = '@';
+
+/// The bookmarks from the configuration file, in the order they were
+/// written.
+#[derive(PartialEq, Eq, Debug, Clone, Default)]
+pub struct Bookmarks {
+    pub entries: Vec<(String, PathBuf)>,
+}
+
+impl Bookmarks {
+    /// Replaces every `@` argument that isn’t the name of an actual file
+    /// with the path its bookmark points to. Arguments that don’t name a
+    /// bookmark are left as they are, so they get reported as missing.
+    pub fn resolve_args(&self, args: &[&OsStr]) -> Vec<OsString> {
+        args.iter().map(|arg| self.resolve_arg(arg)).collect()
+    }
+
+    fn resolve_arg(&self, arg: &OsStr) -> OsString {
+        let Some(rest) = arg.to_str().and_then(|arg| arg.strip_prefix(PREFIX)) else {
+            return arg.to_os_string();
+        };
+        if Path::new(arg).exists() {
+            return arg.to_os_string();
+        }
+
+        let (name, subpath) = rest.split_once('/').unwrap_or((rest, ""));
+        let Some((_, path)) = self.entries.iter().rev().find(|(n, _)| n == name) else {
+            debug!("No bookmark called {name:?}");
+            return arg.to_os_string();
+        };
+
+        let path = if subpath.is_empty() {
+            path.clone()
+        } else {
+            path.join(subpath)
+        };
+        debug!("Resolved {arg:?} to {path:?}");
+        path.into_os_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bookmarks() -> Bookmarks {
+        Bookmarks {
+            entries: vec![
+                (String::from("proj"), PathBuf::from("/code/project")),
+                (String::from("notes"), PathBuf::from("/home/me/notes")),
+            ],
+        }
+    }
+
+    fn resolve(arg: &str) -> OsString {
+        bookmarks().resolve_arg(OsStr::new(arg))
+    }
+
+    #[test]
+    fn bookmark() {
+        assert_eq!(resolve("@proj"), OsString::from("/code/project"));
+    }
+
+    #[test]
+    fn inside_a_bookmark() {
+        assert_eq!(
+            resolve("@proj/src/main"),
+            OsString::from("/code/project/src/main")
+        );
+        assert_eq!(resolve("@notes/"), OsString::from("/home/me/notes"));
+    }
+
+    #[test]
+    fn unknown_bookmark() {
+        assert_eq!(resolve("@nothing"), OsString::from("@nothing"));
+    }
+
+    #[test]
+    fn not_a_bookmark() {
+        assert_eq!(resolve("proj"), OsString::from("proj"));
+        assert_eq!(resolve("dir/@proj"), OsString::from("dir/@proj"));
+    }
+
+    #[test]
+    fn later_bookmarks_win() {
+        let mut bookmarks = bookmarks();
+        bookmarks
+            .entries
+            .push((String::from("proj"), PathBuf::from("/elsewhere")));
+        assert_eq!(
+            bookmarks.resolve_arg(OsStr::new("@proj")),
+            OsString::from("/elsewhere")
+        );
+    }
+}
@@ -0,0 +1,53 @@
+//! Files that eza keeps in its cache directory between runs, such as the
+//! snapshots for `--highlight-new` and the previews for `--thumbnails`.
+//!
+//! Each one gets named after a hash of whatever it’s a cache of, and gets
+//! written somewhere else first, so that another eza running at the same
+//! time never reads one that’s only half there.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::fs::walk::write_atomically;
+
+/// The name to keep the cached copy of something under, from whatever
+/// identifies it, such as its full path.
+pub fn file_name(key: &[u8]) -> String {
+    format!("{:016x}", fnv1a(key))
+}
+
+/// A hash that stays the same from one build of eza to the next, unlike the
+/// standard library’s, so that files named after one can still be found
+/// after updating.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Writes the file next to where it goes and moves it into place, creating
+/// the cache directory if it isn’t there yet.
+pub fn save(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    write_atomically(path, contents)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stable_hash() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(file_name(b"a"), "af63dc4c8601ec8c");
+    }
+}
@@ -0,0 +1,143 @@
+//! Leaving out arguments that are the same path as an earlier one, for
+//! `--unique`, and marking files that have the same name as a file under
+//! another argument, for `--common-names`.
+//!
+//! Paths count as the same once they’ve been made absolute and had their
+//! `.` and `..` components worked out, so `src`, `./src/`, and `lib/../src`
+//! all get listed once. Symlinks don’t get followed, as a link to a
+//! directory and the directory itself get listed differently.
+//!
+//! Files under different directory arguments have the same name when their
+//! paths relative to those directories are the same, so listing two copies
+//! of a project recursively marks `src/main.rs` in both, but not every
+//! `mod.rs`. A file given as an argument itself goes by its file name.
+//! Which files those are gets worked out once, before anything is listed,
+//! by reading the directories down to as many levels as the listing goes,
+//! and then carried to the listing in the file name options.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use crate::fs::walk::{is_hidden, walk};
+
+/// The paths of the files that have the same name as a file under another
+/// argument.
+#[derive(Debug, Default)]
+pub struct CommonNames(HashSet<PathBuf>);
+
+impl CommonNames {
+    /// Whether the file at the given path has the same name as a file
+    /// under another argument.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.0.contains(path)
+    }
+}
+
+/// The paths with any that are the same as an earlier one left out.
+pub fn unique<'a>(paths: &[&'a OsStr]) -> Vec<&'a OsStr> {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let mut seen = HashSet::new();
+
+    paths
+        .iter()
+        .filter(|path| seen.insert(path_clean::clean(cwd.join(path))))
+        .copied()
+        .collect()
+}
+
+/// Reads the arguments down to the given number of levels, or all the way
+/// down if there’s no limit, and finds which files under them have the same
+/// name as one under another argument. Files starting with a dot get left
+/// out unless they’re wanted.
+pub fn find_common(paths: &[&OsStr], levels: Option<usize>, dotfiles: bool) -> CommonNames {
+    let found = unique(paths)
+        .into_iter()
+        .map(|path| {
+            let path = Path::new(path);
+            let mut files = Vec::new();
+            if levels != Some(0) && path.is_dir() {
+                walk(path, levels, dotfiles, &mut |_, entries| {
+                    let entries = entries.into_iter().flatten();
+                    for entry in entries.filter(|entry| dotfiles || !is_hidden(entry)) {
+                        let entry = entry.path();
+                        let relative = entry.strip_prefix(path).unwrap_or(&entry);
+                        files.push((relative.to_path_buf(), entry.clone()));
+                    }
+                });
+            } else if let Some(name) = path.file_name() {
+                files.push((PathBuf::from(name), path.to_path_buf()));
+            }
+            files
+        })
+        .collect::<Vec<_>>();
+
+    CommonNames(common(&found))
+}
+
+/// The paths of the files whose relative path is shared with a file found
+/// under another argument, given each argument’s files as pairs of their
+/// relative paths and their actual ones.
+fn common(found: &[Vec<(PathBuf, PathBuf)>]) -> HashSet<PathBuf> {
+    let mut arguments: HashMap<&Path, HashSet<usize>> = HashMap::new();
+    for (index, files) in found.iter().enumerate() {
+        for (relative, _) in files {
+            arguments.entry(relative).or_default().insert(index);
+        }
+    }
+
+    found
+        .iter()
+        .flatten()
+        .filter(|(relative, _)| arguments[relative.as_path()].len() > 1)
+        .map(|(_, path)| path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn files(root: &str, relatives: &[&str]) -> Vec<(PathBuf, PathBuf)> {
+        relatives
+            .iter()
+            .map(|relative| (PathBuf::from(relative), Path::new(root).join(relative)))
+            .collect()
+    }
+
+    #[test]
+    fn same_paths() {
+        let paths = ["src", "./src/", "lib/../src", "lib", "/"].map(OsStr::new);
+        assert_eq!(unique(&paths), ["src", "lib", "/"].map(OsStr::new));
+    }
+
+    #[test]
+    fn common_relative_paths() {
+        let found = [
+            files("a", &["src", "src/main.rs", "src/mod.rs", "README"]),
+            files("b", &["src", "src/main.rs", "lib/mod.rs"]),
+            files("c", &["README"]),
+        ];
+
+        let mut common = common(&found).into_iter().collect::<Vec<_>>();
+        common.sort();
+        assert_eq!(
+            common,
+            [
+                "a/README",
+                "a/src",
+                "a/src/main.rs",
+                "b/src",
+                "b/src/main.rs",
+                "c/README"
+            ]
+            .map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn nothing_in_common() {
+        let found = [files("a", &["x", "y"]), files("b", &["z"])];
+        assert!(common(&found).is_empty());
+    }
+}
@@ -0,0 +1,390 @@
+//! Finding frequently used directories by name, for `--frecency`.
+//!
+//! With `--frecency`, an argument such as `z:proj` that isn’t the name of
+//! an actual file gets replaced by the directory that best matches `proj`
+//! before anything is listed, so a deep directory can be listed without
+//! typing its whole path. The words after the `z:` get matched the same way
+//! `zoxide` matches them: each has to appear in the path, in order and
+//! ignoring case, and the last one has to be in the directory’s own name.
+//!
+//! If zoxide is installed, its database gets asked first. Otherwise, or if
+//! it has nothing that matches, eza looks through its own list of the
+//! directories it’s listed, which gets kept in the same format as `z`’s:
+//! one `path|rank|time` line per directory. Each listing adds one to the
+//! rank of the directories that were listed, and directories that were
+//! listed recently count for more than ones that were listed a long time
+//! ago, so the match is the most *frecent* one.
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::*;
+
+use crate::fs::walk::write_atomically;
+
+/// What arguments that should be looked up start with.
+pub const PREFIX: &str = "z:";
+
+/// Once the ranks add up to more than this, they all get scaled down, and
+/// directories that haven’t been listed for a while drop off the list.
+const MAX_TOTAL_RANK: f64 = 10_000.0;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+const WEEK: u64 = 7 * DAY;
+
+/// Where to look directories up, and where to count visits to them.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Frecency {
+    /// The file with eza’s own list of directories, if there’s anywhere
+    /// to keep it.
+    pub store: Option<PathBuf>,
+}
+
+impl Frecency {
+    /// Replaces every `z:` argument that isn’t the name of an actual file
+    /// with the directory that best matches it. Arguments that don’t match
+    /// anything are left as they are, so they get reported as missing.
+    pub fn resolve_args(&self, args: &[&OsStr]) -> Vec<OsString> {
+        args.iter().map(|arg| self.resolve_arg(arg)).collect()
+    }
+
+    fn resolve_arg(&self, arg: &OsStr) -> OsString {
+        let Some(query) = arg.to_str().and_then(|arg| arg.strip_prefix(PREFIX)) else {
+            return arg.to_os_string();
+        };
+        if Path::new(arg).exists() {
+            return arg.to_os_string();
+        }
+
+        if let Some(dir) = self.resolve(query) {
+            debug!("Resolved {arg:?} to {dir:?}");
+            dir.into_os_string()
+        } else {
+            debug!("Nothing matches {arg:?}");
+            arg.to_os_string()
+        }
+    }
+
+    /// Finds the directory that best matches the words in the query.
+    fn resolve(&self, query: &str) -> Option<PathBuf> {
+        let keywords = query.split_whitespace().collect::<Vec<_>>();
+        if keywords.is_empty() {
+            return None;
+        }
+
+        zoxide_query(&keywords).or_else(|| {
+            let store = Store::parse(&fs::read_to_string(self.store.as_ref()?).ok()?);
+            store
+                .best_match(&keywords, now(), Path::is_dir)
+                .map(Path::to_path_buf)
+        })
+    }
+
+    /// Counts a visit to each of the arguments that’s a directory. Failing
+    /// to save the list shouldn’t stop anything getting listed, so it only
+    /// gets logged.
+    pub fn record(&self, args: &[&OsStr]) {
+        let Some(store_path) = &self.store else {
+            return;
+        };
+
+        let dirs = args
+            .iter()
+            .filter_map(|arg| fs::canonicalize(arg).ok())
+            .filter(|path| path.is_dir())
+            .collect::<Vec<_>>();
+        if dirs.is_empty() {
+            return;
+        }
+
+        let mut store = fs::read_to_string(store_path)
+            .map(|text| Store::parse(&text))
+            .unwrap_or_default();
+        let now = now();
+        for dir in &dirs {
+            store.visit(dir, now);
+        }
+
+        if let Err(e) = save(store_path, &store.to_text()) {
+            warn!("Failed to save {store_path:?}: {e}");
+        }
+    }
+}
+
+/// Asks zoxide for the best match, if it’s installed.
+fn zoxide_query(keywords: &[&str]) -> Option<PathBuf> {
+    let output = Command::new("zoxide")
+        .arg("query")
+        .arg("--")
+        .args(keywords)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() => {
+            let path = String::from_utf8(output.stdout).ok()?;
+            let path = path.trim_end_matches(['\n', '\r']);
+            (!path.is_empty()).then(|| PathBuf::from(path))
+        }
+        Ok(_) => None,
+        Err(e) => {
+            debug!("Not asking zoxide: {e}");
+            None
+        }
+    }
+}
+
+/// Writes the list over the store, creating its directory if it isn’t there
+/// yet, so another eza reading the list never sees it half-written.
+fn save(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    write_atomically(path, text.as_bytes())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// A directory that’s been listed before.
+#[derive(PartialEq, Debug)]
+struct Entry {
+    path: PathBuf,
+
+    /// How many times it’s been listed, scaled down with everything else
+    /// whenever the ranks get too big.
+    rank: f64,
+
+    /// When it was last listed, in seconds since the Unix epoch.
+    time: u64,
+}
+
+impl Entry {
+    /// How good a match this is, with recent visits counting for more.
+    fn score(&self, now: u64) -> f64 {
+        let age = now.saturating_sub(self.time);
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+
+        self.rank * weight
+    }
+}
+
+/// eza’s own list of directories.
+#[derive(PartialEq, Debug, Default)]
+struct Store {
+    entries: Vec<Entry>,
+}
+
+impl Store {
+    /// Reads the list from its text, skipping any lines that don’t make
+    /// sense. Paths can have `|` in them, so the line is split from the end.
+    fn parse(text: &str) -> Self {
+        let entries = text
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.rsplitn(3, '|');
+                let time = parts.next()?.parse().ok()?;
+                let rank = parts.next()?.parse().ok()?;
+                let path = PathBuf::from(parts.next()?);
+                Some(Entry { path, rank, time })
+            })
+            .collect();
+
+        Self { entries }
+    }
+
+    fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}|{}|{}\n", entry.path.display(), entry.rank, entry.time))
+            .collect()
+    }
+
+    /// Counts a visit to the directory, scaling everything down if the ranks
+    /// have got too big.
+    fn visit(&mut self, path: &Path, now: u64) {
+        match self.entries.iter_mut().find(|entry| entry.path == path) {
+            Some(entry) => {
+                entry.rank += 1.0;
+                entry.time = now;
+            }
+            None => self.entries.push(Entry {
+                path: path.to_path_buf(),
+                rank: 1.0,
+                time: now,
+            }),
+        }
+
+        let total = self.entries.iter().map(|entry| entry.rank).sum::<f64>();
+        if total > MAX_TOTAL_RANK {
+            let factor = 0.9 * MAX_TOTAL_RANK / total;
+            for entry in &mut self.entries {
+                entry.rank *= factor;
+            }
+            self.entries.retain(|entry| entry.rank >= 1.0);
+        }
+    }
+
+    /// The highest-scoring directory that matches every keyword, and that
+    /// still exists according to the given check.
+    fn best_match(
+        &self,
+        keywords: &[&str],
+        now: u64,
+        exists: impl Fn(&Path) -> bool,
+    ) -> Option<&Path> {
+        self.entries
+            .iter()
+            .filter(|entry| matches_keywords(&entry.path, keywords))
+            .filter(|entry| exists(&entry.path))
+            .max_by(|a, b| a.score(now).total_cmp(&b.score(now)))
+            .map(|entry| entry.path.as_path())
+    }
+}
+
+/// Whether every keyword appears in the path, in order and ignoring case,
+/// with the last one in the final component.
+fn matches_keywords(path: &Path, keywords: &[&str]) -> bool {
+    let path = path.to_string_lossy().to_lowercase();
+    let keywords = keywords
+        .iter()
+        .map(|keyword| keyword.to_lowercase())
+        .collect::<Vec<_>>();
+
+    let Some(last) = keywords.last() else {
+        return false;
+    };
+    let name = path.rsplit(std::path::is_separator).next().unwrap_or(&path);
+    if !name.contains(last.as_str()) {
+        return false;
+    }
+
+    let mut rest = path.as_str();
+    for keyword in &keywords {
+        match rest.find(keyword.as_str()) {
+            Some(index) => rest = &rest[index + keyword.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NOW: u64 = 1_700_000_000;
+
+    fn store() -> Store {
+        Store::parse(
+            "/home/me/projects/eza|10|1699990000\n\
+             /home/me/projects/eza/src|3|1699999000\n\
+             /srv/old-projects|50|1600000000\n\
+             nonsense\n",
+        )
+    }
+
+    #[test]
+    fn parses() {
+        let store = store();
+        assert_eq!(store.entries.len(), 3);
+        assert_eq!(
+            store.entries[1],
+            Entry {
+                path: PathBuf::from("/home/me/projects/eza/src"),
+                rank: 3.0,
+                time: 1_699_999_000,
+            }
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let store = store();
+        assert_eq!(Store::parse(&store.to_text()), store);
+    }
+
+    #[test]
+    fn keywords_in_order() {
+        let path = Path::new("/home/me/Projects/eza");
+        assert!(!matches_keywords(path, &["proj"]));
+        assert!(matches_keywords(path, &["eza"]));
+        assert!(matches_keywords(path, &["proj", "eza"]));
+        assert!(!matches_keywords(path, &["eza", "proj"]));
+        assert!(!matches_keywords(path, &["home"]));
+    }
+
+    #[test]
+    fn recent_visits_win() {
+        // The old directory has been listed more, but not for years.
+        let store = store();
+        assert_eq!(
+            store.best_match(&["proj"], NOW, |_| true),
+            Some(Path::new("/srv/old-projects"))
+        );
+        assert_eq!(
+            store.best_match(&["eza"], NOW, |_| true),
+            Some(Path::new("/home/me/projects/eza"))
+        );
+    }
+
+    #[test]
+    fn missing_directories_are_skipped() {
+        let store = store();
+        assert_eq!(
+            store.best_match(&["eza"], NOW, |path| !path.ends_with("src")),
+            Some(Path::new("/home/me/projects/eza"))
+        );
+        assert_eq!(store.best_match(&["eza"], NOW, |_| false), None);
+    }
+
+    #[test]
+    fn visits() {
+        let mut store = store();
+        store.visit(Path::new("/home/me/projects/eza/src"), NOW);
+        store.visit(Path::new("/tmp"), NOW);
+        assert_eq!(
+            store.entries[1],
+            Entry {
+                path: PathBuf::from("/home/me/projects/eza/src"),
+                rank: 4.0,
+                time: NOW,
+            }
+        );
+        assert_eq!(
+            store.entries[3],
+            Entry {
+                path: PathBuf::from("/tmp"),
+                rank: 1.0,
+                time: NOW,
+            }
+        );
+    }
+
+    #[test]
+    fn ranks_get_scaled_down() {
+        let mut store = Store::parse("/a|9999|0\n/b|1|0\n");
+        store.visit(Path::new("/a"), NOW);
+        assert_eq!(store.entries.len(), 1);
+        assert!(store.entries[0].rank < 9000.0);
+    }
+}
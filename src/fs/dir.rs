@@ -2,12 +2,23 @@ use crate::fs::feature::git::GitCache;
 use crate::fs::fields::GitStatus;
 use std::fs;
 use std::io;
+use std::iter::Enumerate;
 use std::path::{Path, PathBuf};
 use std::slice::Iter as SliceIter;
+#[cfg(unix)]
+use std::sync::Arc;
 
 use log::*;
 
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+use crate::fs::feature::uring;
+use crate::fs::filter::HiddenNames;
+use crate::fs::metadata::Metadata;
+#[cfg(unix)]
+use crate::fs::metadata::Source;
 use crate::fs::File;
+use crate::timeout::Deadline;
+use crate::timing::{self, Phase};
 
 /// A **Dir** provides a cached list of the file paths in a directory that’s
 /// being listed.
@@ -19,8 +30,65 @@ pub struct Dir {
     /// A vector of the files that have been read from this directory.
     contents: Vec<PathBuf>,
 
+    /// The metadata of each of those files, if it got looked up along with
+    /// the rest of the directory’s. Files without any get looked up one at a
+    /// time when they’re listed.
+    metadata: Vec<Option<Metadata>>,
+
     /// The path that was read.
     pub path: PathBuf,
+
+    /// The identities of this directory and of every directory it was
+    /// reached through while recursing, outermost first. Following a symlink
+    /// to any of these would go round in circles.
+    ancestry: Vec<DirId>,
+
+    /// The deadline that reading this directory and looking up its files
+    /// share, so a hung mount only gets waited on once.
+    pub deadline: Deadline,
+
+    /// Where the directory was read from, if it isn’t on this computer.
+    #[cfg(unix)]
+    source: Option<Arc<dyn Source>>,
+}
+
+/// The identity of a directory on disk, which is the same no matter which
+/// path or symlink it was reached through.
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum DirId {
+    /// The device and inode numbers of the directory.
+    #[cfg(unix)]
+    Inode(u64, u64),
+
+    /// The fully-resolved path, which stands in for the inode numbers on
+    /// platforms that don’t expose them, and for directories that aren’t on
+    /// this computer.
+    Path(PathBuf),
+}
+
+impl DirId {
+    /// Look up the identity of whatever the given path points to, following
+    /// any symlinks along the way.
+    fn of(path: &Path) -> Option<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let metadata = fs::metadata(path).ok()?;
+            Some(Self::Inode(metadata.dev(), metadata.ino()))
+        }
+
+        #[cfg(not(unix))]
+        {
+            let canonical = fs::canonicalize(path).ok()?;
+            Some(Self::Path(canonical))
+        }
+    }
+
+    /// Look up the identity of a directory that isn’t on this computer.
+    #[cfg(unix)]
+    fn of_remote(source: &dyn Source, path: &Path) -> Option<Self> {
+        source.canonicalize(path).ok().map(Self::Path)
+    }
 }
 
 impl Dir {
@@ -35,12 +103,148 @@ impl Dir {
     pub fn read_dir(path: PathBuf) -> io::Result<Self> {
         info!("Reading directory {:?}", &path);
 
-        let contents = fs::read_dir(&path)?
-            .map(|result| result.map(|entry| entry.path()))
-            .collect::<Result<_, _>>()?;
+        let deadline = Deadline::default();
+        let contents = timing::time(Phase::ReadDir, || {
+            let dir = path.clone();
+            deadline.io(move || {
+                fs::read_dir(dir)?
+                    .map(|result| result.map(|entry| entry.path()))
+                    .collect::<Result<Vec<_>, _>>()
+            })
+        })?;
 
         info!("Read directory success {:?}", &path);
-        Ok(Self { contents, path })
+        let metadata = Self::look_up_all(&path, &contents, &deadline);
+        let ancestry = DirId::of(&path).into_iter().collect();
+        Ok(Self {
+            contents,
+            metadata,
+            path,
+            ancestry,
+            deadline,
+            #[cfg(unix)]
+            source: None,
+        })
+    }
+
+    /// Like `read_dir`, for a directory that’s read from a source other than
+    /// this computer’s filesystem. Its files’ metadata comes along with it.
+    #[cfg(unix)]
+    pub fn read_from(source: &Arc<dyn Source>, path: PathBuf) -> io::Result<Self> {
+        info!("Reading directory {:?} from {:?}", &path, source);
+
+        let files = timing::time(Phase::ReadDir, || source.read_dir(&path))?;
+        let (contents, metadata) = files
+            .into_iter()
+            .map(|(path, fields)| (path, Some(Metadata::remote(fields, source))))
+            .unzip();
+        let ancestry = DirId::of_remote(source.as_ref(), &path)
+            .into_iter()
+            .collect();
+        Ok(Self {
+            contents,
+            metadata,
+            path,
+            ancestry,
+            deadline: Deadline::default(),
+            source: Some(Arc::clone(source)),
+        })
+    }
+
+    /// Where this directory was read from, if it isn’t this computer’s
+    /// filesystem.
+    #[cfg(unix)]
+    pub fn source(&self) -> Option<&Arc<dyn Source>> {
+        self.source.as_ref()
+    }
+
+    /// Whether this directory is on this computer’s filesystem.
+    #[cfg(unix)]
+    pub fn is_local(&self) -> bool {
+        self.source.is_none()
+    }
+
+    #[cfg(not(unix))]
+    pub fn is_local(&self) -> bool {
+        true
+    }
+
+    /// The path to show for this directory, such as in the heading above
+    /// its files, which says where it is if it isn’t on this computer.
+    pub fn display_path(&self) -> String {
+        #[cfg(unix)]
+        if let Some(source) = &self.source {
+            return source.display(&self.path);
+        }
+
+        self.path.display().to_string()
+    }
+
+    /// Looks up the metadata of all the directory’s files at once, if the
+    /// `io_uring` backend is on and there are enough of them for it to be
+    /// worth it.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    fn look_up_all(
+        path: &Path,
+        contents: &[PathBuf],
+        deadline: &Deadline,
+    ) -> Vec<Option<Metadata>> {
+        if !uring::is_enabled() || contents.len() < uring::MIN_ENTRIES {
+            return Vec::new();
+        }
+
+        let dir = path.to_path_buf();
+        let paths = contents.to_vec();
+        let stats = timing::time(Phase::Stat, || {
+            deadline.run(move || uring::symlink_metadata_all(&dir, &paths))
+        });
+
+        stats
+            .unwrap_or_default()
+            .into_iter()
+            .map(|stat| stat.map(Metadata::Stat))
+            .collect()
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+    fn look_up_all(
+        _path: &Path,
+        _contents: &[PathBuf],
+        _deadline: &Deadline,
+    ) -> Vec<Option<Metadata>> {
+        Vec::new()
+    }
+
+    /// Read one of this directory’s subdirectories while recursing, keeping
+    /// track of the directories above it so that symlink loops can be
+    /// noticed with `is_ancestor`.
+    pub fn read_subdir(&self, path: PathBuf) -> io::Result<Self> {
+        #[cfg(unix)]
+        let mut subdir = match &self.source {
+            Some(source) => Self::read_from(source, path)?,
+            None => Self::read_dir(path)?,
+        };
+        #[cfg(not(unix))]
+        let mut subdir = Self::read_dir(path)?;
+        let mut ancestry = self.ancestry.clone();
+        ancestry.append(&mut subdir.ancestry);
+        subdir.ancestry = ancestry;
+        Ok(subdir)
+    }
+
+    /// Whether the given path resolves to this directory or to one that was
+    /// recursed through to get here, meaning that descending into it would
+    /// loop forever.
+    pub fn is_ancestor(&self, path: &Path) -> bool {
+        #[cfg(unix)]
+        let id = match &self.source {
+            Some(source) => DirId::of_remote(source.as_ref(), path),
+            None => DirId::of(path),
+        };
+        #[cfg(not(unix))]
+        let id = DirId::of(path);
+
+        id.is_some_and(|id| self.ancestry.contains(&id))
     }
 
     /// Produce an iterator of IO results of trying to read all the files in
@@ -53,10 +257,22 @@ impl Dir {
         deref_links: bool,
         total_size: bool,
     ) -> Files<'dir, 'ig> {
+        // Only bother reading the `.hidden` file if there is one, and it’s
+        // on this computer.
+        let hidden = if !dots.shows_dotfiles()
+            && self.is_local()
+            && self.contains(&self.join(HiddenNames::FILE_NAME.as_ref()))
+        {
+            HiddenNames::read(&self.path)
+        } else {
+            HiddenNames::default()
+        };
+
         Files {
-            inner: self.contents.iter(),
+            inner: self.contents.iter().enumerate(),
             dir: self,
             dotfiles: dots.shows_dotfiles(),
+            hidden,
             dots: dots.dots(),
             git,
             git_ignoring,
@@ -80,7 +296,7 @@ impl Dir {
 #[allow(clippy::struct_excessive_bools)]
 pub struct Files<'dir, 'ig> {
     /// The internal iterator over the paths that have been read already.
-    inner: SliceIter<'dir, PathBuf>,
+    inner: Enumerate<SliceIter<'dir, PathBuf>>,
 
     /// The directory that begat those paths.
     dir: &'dir Dir,
@@ -88,6 +304,10 @@ pub struct Files<'dir, 'ig> {
     /// Whether to include dotfiles in the list.
     dotfiles: bool,
 
+    /// The names that the directory’s `.hidden` file hides along with the
+    /// dotfiles.
+    hidden: HiddenNames,
+
     /// Whether the `.` or `..` directories should be produced first, before
     /// any files have been listed.
     dots: DotsNext,
@@ -117,9 +337,9 @@ impl<'dir, 'ig> Files<'dir, 'ig> {
     /// varies depending on the dotfile visibility flag)
     fn next_visible_file(&mut self) -> Option<Result<File<'dir>, (PathBuf, io::Error)>> {
         loop {
-            if let Some(path) = self.inner.next() {
+            if let Some((index, path)) = self.inner.next() {
                 let filename = File::filename(path);
-                if !self.dotfiles && filename.starts_with('.') {
+                if !self.dotfiles && (filename.starts_with('.') || self.hidden.hides(&filename)) {
                     continue;
                 }
 
@@ -133,18 +353,31 @@ impl<'dir, 'ig> Files<'dir, 'ig> {
                 if self.git_ignoring {
                     let git_status = self.git.map(|g| g.get(path, false)).unwrap_or_default();
                     if git_status.unstaged == GitStatus::Ignored {
+                        if let Some(git) = self.git {
+                            git.report_ignored(path);
+                        }
                         continue;
                     }
                 }
 
-                let file = File::from_args(
-                    path.clone(),
-                    self.dir,
-                    filename,
-                    self.deref_links,
-                    self.total_size,
-                )
-                .map_err(|e| (path.clone(), e));
+                let file = match self.dir.metadata.get(index) {
+                    Some(Some(metadata)) => Ok(File::with_metadata(
+                        path.clone(),
+                        self.dir,
+                        filename,
+                        metadata.clone(),
+                        self.deref_links,
+                        self.total_size,
+                    )),
+                    _ => File::from_args(
+                        path.clone(),
+                        self.dir,
+                        filename,
+                        self.deref_links,
+                        self.total_size,
+                    )
+                    .map_err(|e| (path.clone(), e)),
+                };
 
                 // Windows has its own concept of hidden files, when dotfiles are
                 // hidden Windows hidden files should also be filtered out
@@ -153,6 +386,12 @@ impl<'dir, 'ig> Files<'dir, 'ig> {
                     continue;
                 }
 
+                // The same goes for files that the Finder hides on macOS.
+                #[cfg(target_os = "macos")]
+                if !self.dotfiles && file.as_ref().is_ok_and(File::is_finder_hidden) {
+                    continue;
+                }
+
                 return Some(file);
             }
 
@@ -44,6 +44,16 @@ impl DirAction {
         }
     }
 
+    /// How many levels of directories get read, counting the arguments
+    /// themselves, or `None` if there’s no limit.
+    pub fn levels(self) -> Option<usize> {
+        match self {
+            Self::AsFile => Some(0),
+            Self::List => Some(1),
+            Self::Recurse(o) => o.max_depth.map(|depth| depth + 1),
+        }
+    }
+
     /// Whether to treat directories as regular files or not.
     pub fn treat_dirs_as_files(self) -> bool {
         match self {
@@ -64,6 +74,15 @@ pub struct RecurseOptions {
     /// The maximum number of times that recursion should descend to, if one
     /// is specified.
     pub max_depth: Option<usize>,
+
+    /// The only levels to list, when `--level` was given a list of them
+    /// rather than just the deepest. The levels in between still get
+    /// descended through, but aren’t shown.
+    pub only_levels: Option<Levels>,
+
+    /// Whether to descend through symlinks that point to directories, rather
+    /// than only into directories themselves.
+    pub follow_symlinks: bool,
 }
 
 impl RecurseOptions {
@@ -74,4 +93,46 @@ impl RecurseOptions {
             Some(d) => d <= depth,
         }
     }
+
+    /// Returns whether the files at the given depth should be shown, where
+    /// the files directly inside a listed directory are at depth 1.
+    pub fn shows_level(self, depth: usize) -> bool {
+        self.only_levels
+            .map_or(true, |levels| levels.contains(depth))
+    }
+}
+
+/// A set of levels, as given to `--level` in a list such as `1,3` or `2-4`.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct Levels(u64);
+
+impl Levels {
+    /// The deepest level that can be put in the set.
+    pub const DEEPEST: usize = 63;
+
+    /// The set of every level.
+    pub fn all() -> Self {
+        Self(!1)
+    }
+
+    /// Adds a level to the set. Levels start at 1, and only go up to
+    /// `DEEPEST`, so this returns `false` for anything outside that.
+    pub fn insert(&mut self, level: usize) -> bool {
+        if level == 0 || level > Self::DEEPEST {
+            return false;
+        }
+
+        self.0 |= 1 << level;
+        true
+    }
+
+    /// Whether the given level is in the set.
+    pub fn contains(self, level: usize) -> bool {
+        level <= Self::DEEPEST && self.0 & (1 << level) != 0
+    }
+
+    /// The deepest level in the set, if it has any.
+    pub fn deepest(self) -> Option<usize> {
+        (self.0 != 0).then(|| 63 - self.0.leading_zeros() as usize)
+    }
 }
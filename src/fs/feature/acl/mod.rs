@@ -0,0 +1,196 @@
+//! Access control lists, which grant or deny users and groups permissions
+//! beyond the ones in a file’s mode.
+//!
+//! Unix systems have two flavours of these. POSIX ACLs add entries for
+//! named users and groups to the usual owner, group, and other bits, and
+//! Linux keeps them in extended attributes. `NFSv4` ACLs are closer to the
+//! ones on Windows: ordered lists of entries that allow or deny rights much
+//! finer than reading, writing, and executing, which can also be inherited
+//! by new files. FreeBSD has these natively on ZFS and UFS, and reads them
+//! through its `acl_*` functions, while on Linux, the NFS client exposes the
+//! server’s ACL as the `system.nfs4_acl` attribute, in the form that gets
+//! sent over the network.
+//!
+//! Windows has its own access control lists, in files’ security
+//! descriptors, which get read by the `windows` module.
+
+#[cfg(windows)]
+mod windows;
+#[cfg(windows)]
+pub use self::windows::{security, Security};
+
+#[cfg(any(target_os = "linux", test))]
+mod nfs4;
+
+#[cfg(unix)]
+use std::path::Path;
+
+/// Which flavour of access control list a file has, if it has one that
+/// gives any permissions beyond its mode.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum AclKind {
+    Posix,
+    Nfs4,
+}
+
+/// Which flavour of access control list the file at `path` has. ACLs that
+/// say nothing more than the file’s mode already does don’t count.
+#[cfg(unix)]
+pub fn kind(path: &Path, follow_symlinks: bool) -> Option<AclKind> {
+    os::kind(path, follow_symlinks)
+}
+
+/// The entries of the file’s `NFSv4` access control list, written the way
+/// `getfacl` on FreeBSD writes them, or nothing if it doesn’t have one.
+#[cfg(unix)]
+pub fn nfs4_entries(path: &Path, follow_symlinks: bool) -> Vec<String> {
+    os::nfs4_entries(path, follow_symlinks)
+}
+
+#[cfg(target_os = "linux")]
+mod os {
+    use std::path::Path;
+
+    use super::{nfs4, AclKind};
+    use crate::fs::feature::xattr;
+
+    pub fn kind(path: &Path, follow_symlinks: bool) -> Option<AclKind> {
+        let nfs4 = xattr::attribute(path, xattr::NFS4_ACL, follow_symlinks)
+            .and_then(|value| nfs4::parse(&value));
+        if nfs4.is_some_and(|entries| !nfs4::is_trivial(&entries)) {
+            return Some(AclKind::Nfs4);
+        }
+
+        // The kernel only keeps a POSIX ACL when it has more in it than the
+        // file’s mode, so any that are there count.
+        [xattr::POSIX_ACL_ACCESS, xattr::POSIX_ACL_DEFAULT]
+            .into_iter()
+            .any(|name| xattr::attribute(path, name, follow_symlinks).is_some())
+            .then_some(AclKind::Posix)
+    }
+
+    pub fn nfs4_entries(path: &Path, follow_symlinks: bool) -> Vec<String> {
+        xattr::attribute(path, xattr::NFS4_ACL, follow_symlinks)
+            .and_then(|value| nfs4::parse(&value))
+            .unwrap_or_default()
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+}
+
+#[cfg(target_os = "freebsd")]
+mod os {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+    use std::ptr;
+
+    use libc::{c_char, c_int, c_void, ssize_t};
+
+    use super::AclKind;
+
+    const ACL_TYPE_ACCESS: c_int = 0x2;
+    const ACL_TYPE_NFS4: c_int = 0x4;
+
+    #[allow(non_camel_case_types)]
+    type acl_t = *mut c_void;
+
+    extern "C" {
+        fn acl_get_file(path: *const c_char, kind: c_int) -> acl_t;
+        fn acl_get_link_np(path: *const c_char, kind: c_int) -> acl_t;
+        fn acl_is_trivial_np(acl: acl_t, trivial: *mut c_int) -> c_int;
+        fn acl_to_text_np(acl: acl_t, len: *mut ssize_t, flags: c_int) -> *mut c_char;
+        fn acl_free(object: *mut c_void) -> c_int;
+    }
+
+    /// An ACL read from a file, which gets freed when it’s dropped.
+    struct Acl(acl_t);
+
+    impl Acl {
+        fn get(path: &Path, kind: c_int, follow_symlinks: bool) -> Option<Self> {
+            let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+
+            // SAFETY: the path is null-terminated, and a null result means
+            // the file has no ACL of this flavour, or its filesystem doesn’t
+            // support them.
+            let acl = unsafe {
+                if follow_symlinks {
+                    acl_get_file(path.as_ptr(), kind)
+                } else {
+                    acl_get_link_np(path.as_ptr(), kind)
+                }
+            };
+            (!acl.is_null()).then_some(Self(acl))
+        }
+
+        fn is_trivial(&self) -> bool {
+            let mut trivial = 0;
+            // SAFETY: the ACL is valid until it gets dropped.
+            let result = unsafe { acl_is_trivial_np(self.0, &mut trivial) };
+            result != 0 || trivial != 0
+        }
+
+        fn to_text(&self) -> Option<String> {
+            // SAFETY: the ACL is valid until it gets dropped, and the text
+            // is null-terminated and gets freed once it’s been copied.
+            unsafe {
+                let text = acl_to_text_np(self.0, ptr::null_mut(), 0);
+                if text.is_null() {
+                    return None;
+                }
+                let string = CStr::from_ptr(text).to_string_lossy().into_owned();
+                acl_free(text.cast());
+                Some(string)
+            }
+        }
+    }
+
+    impl Drop for Acl {
+        fn drop(&mut self) {
+            // SAFETY: the ACL came from `acl_get_file` or `acl_get_link_np`,
+            // and only gets freed once.
+            unsafe { acl_free(self.0) };
+        }
+    }
+
+    pub fn kind(path: &Path, follow_symlinks: bool) -> Option<AclKind> {
+        [
+            (ACL_TYPE_NFS4, AclKind::Nfs4),
+            (ACL_TYPE_ACCESS, AclKind::Posix),
+        ]
+        .into_iter()
+        .find(|(kind, _)| {
+            Acl::get(path, *kind, follow_symlinks).is_some_and(|acl| !acl.is_trivial())
+        })
+        .map(|(_, kind)| kind)
+    }
+
+    pub fn nfs4_entries(path: &Path, follow_symlinks: bool) -> Vec<String> {
+        Acl::get(path, ACL_TYPE_NFS4, follow_symlinks)
+            .and_then(|acl| acl.to_text())
+            .map(|text| {
+                text.lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "freebsd"))))]
+mod os {
+    use std::path::Path;
+
+    use super::AclKind;
+
+    pub fn kind(_path: &Path, _follow_symlinks: bool) -> Option<AclKind> {
+        None
+    }
+
+    pub fn nfs4_entries(_path: &Path, _follow_symlinks: bool) -> Vec<String> {
+        Vec::new()
+    }
+}
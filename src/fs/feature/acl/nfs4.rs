@@ -0,0 +1,205 @@
+//! Decoding `NFSv4` access control lists from the form they’re sent over the
+//! network in, which is what Linux’s `system.nfs4_acl` attribute holds.
+
+use std::fmt;
+
+/// Whether an entry allows or denies its rights, or has them logged.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Nfs4EntryType {
+    Allow,
+    Deny,
+    Audit,
+    Alarm,
+}
+
+/// One entry of an `NFSv4` access control list.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Nfs4Entry {
+    pub entry_type: Nfs4EntryType,
+
+    /// How the entry gets inherited, and whether it names a group.
+    pub flags: u32,
+
+    /// Which rights the entry is about.
+    pub access_mask: u32,
+
+    /// Who the entry applies to: `OWNER@`, `GROUP@`, or `EVERYONE@`, or a
+    /// user or group name, usually followed by their NFS domain.
+    pub who: String,
+}
+
+const IDENTIFIER_GROUP: u32 = 0x40;
+
+/// The letters `getfacl` on FreeBSD uses for each right, in the order it
+/// writes them.
+const ACCESS_LETTERS: [(u32, char); 14] = [
+    (0x0000_0001, 'r'), // read data
+    (0x0000_0002, 'w'), // write data
+    (0x0000_0020, 'x'), // execute
+    (0x0000_0004, 'p'), // append data
+    (0x0000_0040, 'D'), // delete child
+    (0x0001_0000, 'd'), // delete
+    (0x0000_0080, 'a'), // read attributes
+    (0x0000_0100, 'A'), // write attributes
+    (0x0000_0008, 'R'), // read named attributes
+    (0x0000_0010, 'W'), // write named attributes
+    (0x0002_0000, 'c'), // read ACL
+    (0x0004_0000, 'C'), // write ACL
+    (0x0008_0000, 'o'), // write owner
+    (0x0010_0000, 's'), // synchronize
+];
+
+/// The letters for each flag, in the same way.
+const FLAG_LETTERS: [(u32, char); 7] = [
+    (0x01, 'f'), // file inherit
+    (0x02, 'd'), // directory inherit
+    (0x08, 'i'), // inherit only
+    (0x04, 'n'), // no propagate inherit
+    (0x10, 'S'), // successful access
+    (0x20, 'F'), // failed access
+    (0x80, 'I'), // inherited
+];
+
+/// Parse an `NFSv4` access control list, which is the number of entries
+/// followed by each one’s type, flags, and access mask, and the length and
+/// bytes of who it applies to, padded to four bytes. Everything is
+/// big-endian.
+pub fn parse(value: &[u8]) -> Option<Vec<Nfs4Entry>> {
+    fn word(rest: &mut &[u8]) -> Option<u32> {
+        let bytes = rest.get(..4)?.try_into().ok()?;
+        *rest = &rest[4..];
+        Some(u32::from_be_bytes(bytes))
+    }
+
+    let mut rest = value;
+    let count = word(&mut rest)?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let entry_type = match word(&mut rest)? {
+            0 => Nfs4EntryType::Allow,
+            1 => Nfs4EntryType::Deny,
+            2 => Nfs4EntryType::Audit,
+            3 => Nfs4EntryType::Alarm,
+            _ => return None,
+        };
+        let flags = word(&mut rest)?;
+        let access_mask = word(&mut rest)?;
+        let length = usize::try_from(word(&mut rest)?).ok()?;
+
+        let padded = length.checked_add(3)? & !3;
+        let who = String::from_utf8_lossy(rest.get(..length)?).into_owned();
+        rest = rest.get(padded..)?;
+
+        entries.push(Nfs4Entry {
+            entry_type,
+            flags,
+            access_mask,
+            who,
+        });
+    }
+
+    Some(entries)
+}
+
+/// Whether the list only has entries for the owner, group, and everyone
+/// else, none of which get inherited, in which case it says no more than the
+/// file’s mode does. NFS servers make one of these up for files that don’t
+/// have an ACL of their own.
+pub fn is_trivial(entries: &[Nfs4Entry]) -> bool {
+    entries.iter().all(|entry| {
+        entry.flags == 0 && matches!(entry.who.as_str(), "OWNER@" | "GROUP@" | "EVERYONE@")
+    })
+}
+
+impl fmt::Display for Nfs4Entry {
+    /// Writes the entry the way `getfacl` on FreeBSD does, such as
+    /// `user:alice@example.com:rw-p----------:fd-----:allow`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.who.as_str() {
+            "OWNER@" => write!(f, "owner@")?,
+            "GROUP@" => write!(f, "group@")?,
+            "EVERYONE@" => write!(f, "everyone@")?,
+            who if self.flags & IDENTIFIER_GROUP != 0 => write!(f, "group:{who}")?,
+            who => write!(f, "user:{who}")?,
+        }
+
+        let letters = |bits: u32, letters: &[(u32, char)]| {
+            letters
+                .iter()
+                .map(|(bit, letter)| if bits & bit == 0 { '-' } else { *letter })
+                .collect::<String>()
+        };
+        let entry_type = match self.entry_type {
+            Nfs4EntryType::Allow => "allow",
+            Nfs4EntryType::Deny => "deny",
+            Nfs4EntryType::Audit => "audit",
+            Nfs4EntryType::Alarm => "alarm",
+        };
+
+        write!(
+            f,
+            ":{}:{}:{entry_type}",
+            letters(self.access_mask, &ACCESS_LETTERS),
+            letters(self.flags, &FLAG_LETTERS)
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn encode(entries: &[(u32, u32, u32, &str)]) -> Vec<u8> {
+        let mut value = u32::try_from(entries.len()).unwrap().to_be_bytes().to_vec();
+        for (entry_type, flags, access_mask, who) in entries {
+            value.extend(entry_type.to_be_bytes());
+            value.extend(flags.to_be_bytes());
+            value.extend(access_mask.to_be_bytes());
+            value.extend(u32::try_from(who.len()).unwrap().to_be_bytes());
+            value.extend(who.as_bytes());
+            value.resize((value.len() + 3) & !3, 0);
+        }
+        value
+    }
+
+    #[test]
+    fn trivial() {
+        let value = encode(&[
+            (0, 0, 0x0016_0187, "OWNER@"),
+            (0, 0, 0x0012_0081, "GROUP@"),
+            (0, 0, 0x0012_0081, "EVERYONE@"),
+        ]);
+        let entries = parse(&value).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert!(is_trivial(&entries));
+        assert_eq!(
+            entries[0].to_string(),
+            "owner@:rw-p--aA--cC-s:-------:allow"
+        );
+    }
+
+    #[test]
+    fn named() {
+        let value = encode(&[
+            (1, 0x03, 0x02, "alice@example.com"),
+            (0, 0x40, 0x21, "staff@example.com"),
+        ]);
+        let entries = parse(&value).unwrap();
+        assert!(!is_trivial(&entries));
+        assert_eq!(
+            entries[0].to_string(),
+            "user:alice@example.com:-w------------:fd-----:deny"
+        );
+        assert_eq!(
+            entries[1].to_string(),
+            "group:staff@example.com:r-x-----------:-------:allow"
+        );
+    }
+
+    #[test]
+    fn truncated() {
+        let value = encode(&[(0, 0, 0x01, "OWNER@")]);
+        assert_eq!(parse(&value[..value.len() - 4]), None);
+        assert_eq!(parse(&[0, 0]), None);
+    }
+}
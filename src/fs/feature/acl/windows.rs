@@ -0,0 +1,202 @@
+//! Reading who owns a file, and what the current user may do with it, from
+//! its security descriptor on Windows.
+//!
+//! Windows has no Unix-style permission bits or numeric user IDs: instead,
+//! every file has an owner SID and a discretionary access control list
+//! (DACL) of entries that grant or deny rights to users and groups.
+
+use std::ffi::OsString;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::path::Path;
+use std::ptr;
+use std::sync::OnceLock;
+
+use log::*;
+
+use windows_sys::Win32::Foundation::{CloseHandle, LocalFree, ERROR_SUCCESS, HANDLE, PSID};
+use windows_sys::Win32::Security::Authorization::{
+    BuildTrusteeWithSidW, GetEffectiveRightsFromAclW, GetNamedSecurityInfoW, SE_FILE_OBJECT,
+    TRUSTEE_W,
+};
+use windows_sys::Win32::Security::{
+    EqualSid, GetTokenInformation, IsWellKnownSid, LookupAccountSidW, TokenUser,
+    WinBuiltinAdministratorsSid, WinLocalSystemSid, ACL, DACL_SECURITY_INFORMATION,
+    OWNER_SECURITY_INFORMATION, PSECURITY_DESCRIPTOR, SID_NAME_USE, TOKEN_QUERY, TOKEN_USER,
+};
+use windows_sys::Win32::Storage::FileSystem::{
+    FILE_GENERIC_EXECUTE, FILE_GENERIC_READ, FILE_GENERIC_WRITE,
+};
+use windows_sys::Win32::System::Threading::{GetCurrentProcess, OpenProcessToken};
+
+use crate::fs::fields as f;
+
+/// The parts of a file’s security descriptor that get displayed.
+pub struct Security {
+    pub owner: Option<f::Owner>,
+    pub access: Option<f::AccessRights>,
+}
+
+/// Read the owner and the current user’s effective access rights for the
+/// file at the given path. Returns `None` if the security descriptor can’t
+/// be read at all, such as when the user isn’t allowed to see it.
+pub fn security(path: &Path) -> Option<Security> {
+    let wide_path = path
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<_>>();
+
+    let mut owner: PSID = ptr::null_mut();
+    let mut dacl: *mut ACL = ptr::null_mut();
+    let mut descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+
+    // SAFETY: the path is null-terminated, and the owner and DACL pointers
+    // point into the descriptor, which stays alive until it’s freed below.
+    let result = unsafe {
+        GetNamedSecurityInfoW(
+            wide_path.as_ptr(),
+            SE_FILE_OBJECT,
+            OWNER_SECURITY_INFORMATION | DACL_SECURITY_INFORMATION,
+            &mut owner,
+            ptr::null_mut(),
+            &mut dacl,
+            ptr::null_mut(),
+            &mut descriptor,
+        )
+    };
+
+    if result != ERROR_SUCCESS {
+        debug!("Error reading security info for {:?}: {}", path, result);
+        return None;
+    }
+
+    let security = Security {
+        owner: owner_of(owner),
+        access: current_user_sid().and_then(|sid| effective_access(dacl, sid)),
+    };
+
+    // SAFETY: the descriptor was allocated by `GetNamedSecurityInfoW`.
+    unsafe { LocalFree(descriptor) };
+    Some(security)
+}
+
+/// Look up the `DOMAIN\User` name of the account with the given SID.
+fn owner_of(sid: PSID) -> Option<f::Owner> {
+    if sid.is_null() {
+        return None;
+    }
+
+    let mut name = [0_u16; 256];
+    let mut domain = [0_u16; 256];
+    let mut name_len = name.len() as u32;
+    let mut domain_len = domain.len() as u32;
+    let mut sid_use: SID_NAME_USE = 0;
+
+    // SAFETY: the buffer lengths passed in match the buffers themselves.
+    let found = unsafe {
+        LookupAccountSidW(
+            ptr::null(),
+            sid,
+            name.as_mut_ptr(),
+            &mut name_len,
+            domain.as_mut_ptr(),
+            &mut domain_len,
+            &mut sid_use,
+        )
+    } != 0;
+
+    if !found {
+        return None;
+    }
+
+    let name = OsString::from_wide(&name[..name_len as usize]);
+    let domain = OsString::from_wide(&domain[..domain_len as usize]);
+    let name = if domain.is_empty() {
+        name.to_string_lossy().into_owned()
+    } else {
+        format!("{}\\{}", domain.to_string_lossy(), name.to_string_lossy())
+    };
+
+    // SAFETY: both SIDs are valid for the duration of these calls.
+    let is_you = current_user_sid().is_some_and(|you| unsafe { EqualSid(sid, you) } != 0);
+    let is_admin = unsafe {
+        IsWellKnownSid(sid, WinLocalSystemSid) != 0
+            || IsWellKnownSid(sid, WinBuiltinAdministratorsSid) != 0
+    };
+
+    Some(f::Owner {
+        name,
+        is_you,
+        is_admin,
+    })
+}
+
+/// Work out which rights the DACL grants to the user with the given SID,
+/// taking both allowing and denying entries into account.
+fn effective_access(dacl: *mut ACL, sid: PSID) -> Option<f::AccessRights> {
+    // A missing DACL grants everyone full access.
+    if dacl.is_null() {
+        return Some(f::AccessRights {
+            read: true,
+            write: true,
+            execute: true,
+        });
+    }
+
+    // SAFETY: an all-zero TRUSTEE_W is valid, and is filled in below.
+    let mut trustee: TRUSTEE_W = unsafe { std::mem::zeroed() };
+    let mut mask = 0_u32;
+
+    // SAFETY: the trustee borrows the SID, which outlives it, and the DACL
+    // was read from a descriptor that is still allocated.
+    let result = unsafe {
+        BuildTrusteeWithSidW(&mut trustee, sid);
+        GetEffectiveRightsFromAclW(dacl, &trustee, &mut mask)
+    };
+
+    if result != ERROR_SUCCESS {
+        debug!("Error getting effective rights: {}", result);
+        return None;
+    }
+
+    let has = |rights| mask & rights == rights;
+    Some(f::AccessRights {
+        read: has(FILE_GENERIC_READ),
+        write: has(FILE_GENERIC_WRITE),
+        execute: has(FILE_GENERIC_EXECUTE),
+    })
+}
+
+/// The SID of the user running eza, read once from the process token.
+fn current_user_sid() -> Option<PSID> {
+    // The token information is kept in a buffer of `u64`s so that the
+    // `TOKEN_USER` at the start of it is suitably aligned.
+    static TOKEN_USER_BUFFER: OnceLock<Option<Vec<u64>>> = OnceLock::new();
+
+    let buffer = TOKEN_USER_BUFFER.get_or_init(|| {
+        let mut token: HANDLE = 0;
+
+        // SAFETY: the token handle is closed again once it’s been queried.
+        unsafe {
+            if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+                return None;
+            }
+
+            let mut len = 0_u32;
+            GetTokenInformation(token, TokenUser, ptr::null_mut(), 0, &mut len);
+
+            let mut buffer = vec![0_u64; (len as usize + 7) / 8];
+            let ok =
+                GetTokenInformation(token, TokenUser, buffer.as_mut_ptr().cast(), len, &mut len);
+            CloseHandle(token);
+
+            (ok != 0).then_some(buffer)
+        }
+    });
+
+    // SAFETY: the buffer holds a `TOKEN_USER` whose SID points further into
+    // the same buffer, which lives for the rest of the program.
+    buffer
+        .as_ref()
+        .map(|buffer| unsafe { (*buffer.as_ptr().cast::<TOKEN_USER>()).User.Sid })
+}
@@ -0,0 +1,281 @@
+//! Flagging files with permissions that could let someone else tamper with
+//! them, for `--security-audit`.
+//!
+//! Four things get flagged: files and directories that anyone can write to,
+//! programs that run as their owner or group, files in the user’s home
+//! directory that someone else owns, and shared temporary directories that
+//! anyone can delete anyone else’s files from because they’re missing the
+//! sticky bit. A world-writable directory with the sticky bit set is how
+//! shared directories are meant to be, so it doesn’t get flagged. Symlinks
+//! always have every permission bit set, so they never get flagged either.
+//!
+//! Which directories count as home and as temporary get looked up once,
+//! when the options are read, by their full paths. Whether anything at all
+//! got flagged gets remembered, so eza can exit with a different code when
+//! it did.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::fs::File;
+
+/// The shared temporary directories that should have the sticky bit set,
+/// along with whatever `$TMPDIR` is.
+const TEMP_DIRS: &[&str] = &["/tmp", "/var/tmp", "/dev/shm"];
+
+/// How much a finding matters, which picks the colour it gets shown in.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Severity {
+    /// Worth knowing about, but often done on purpose.
+    Warning,
+
+    /// Lets other users change or delete things, or run things as someone
+    /// else.
+    Critical,
+}
+
+/// Something risky about a file’s permissions.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Finding {
+    /// Anyone can write to it.
+    WorldWritable,
+
+    /// It runs as the user that owns it.
+    Setuid,
+
+    /// It runs as the group that owns it.
+    Setgid,
+
+    /// It’s a shared temporary directory without the sticky bit.
+    NoStickyBit,
+
+    /// It’s in the user’s home directory, but the user with this ID owns it.
+    OtherOwner(u32),
+}
+
+impl Finding {
+    pub fn severity(self) -> Severity {
+        match self {
+            Self::WorldWritable | Self::Setuid | Self::NoStickyBit => Severity::Critical,
+            Self::Setgid | Self::OtherOwner(_) => Severity::Warning,
+        }
+    }
+
+    /// The short description shown next to the file’s name.
+    pub fn label(self) -> String {
+        match self {
+            Self::WorldWritable => String::from("world-writable"),
+            Self::Setuid => String::from("setuid"),
+            Self::Setgid => String::from("setgid"),
+            Self::NoStickyBit => String::from("no sticky bit"),
+            Self::OtherOwner(uid) => format!("owned by {}", user_name(uid)),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn user_name(uid: u32) -> String {
+    uzers::get_user_by_uid(uid).map_or_else(
+        || uid.to_string(),
+        |user| user.name().to_string_lossy().into_owned(),
+    )
+}
+
+#[cfg(not(unix))]
+fn user_name(uid: u32) -> String {
+    uid.to_string()
+}
+
+/// The directories and user that files get compared against.
+#[derive(Debug)]
+#[cfg_attr(not(unix), allow(dead_code))]
+struct Places {
+    /// The ID of the user running eza.
+    you: u32,
+
+    /// The full path of their home directory, if they have one.
+    home: Option<PathBuf>,
+
+    /// The full paths of the shared temporary directories.
+    temp_dirs: Vec<PathBuf>,
+}
+
+#[cfg(unix)]
+fn current_uid() -> u32 {
+    uzers::get_current_uid()
+}
+
+#[cfg(not(unix))]
+fn current_uid() -> u32 {
+    0
+}
+
+/// The places to check files against, and whether any have been flagged.
+#[derive(Debug)]
+pub struct Audit {
+    places: Places,
+    flagged: AtomicBool,
+}
+
+impl Audit {
+    /// Looks up the full paths of the given home directory and of the
+    /// shared temporary directories, along with the given one.
+    pub fn resolve(home: Option<PathBuf>, temp_dir: Option<PathBuf>) -> Self {
+        let places = Places {
+            you: current_uid(),
+            home: home.and_then(|home| fs::canonicalize(home).ok()),
+            temp_dirs: TEMP_DIRS
+                .iter()
+                .map(PathBuf::from)
+                .chain(temp_dir)
+                .filter_map(|dir| fs::canonicalize(dir).ok())
+                .collect(),
+        };
+
+        Self {
+            places,
+            flagged: AtomicBool::new(false),
+        }
+    }
+
+    /// Whether any file has been flagged so far.
+    pub fn flagged_any(&self) -> bool {
+        self.flagged.load(Ordering::Relaxed)
+    }
+
+    /// Everything risky about the given file’s permissions, most serious
+    /// first.
+    #[cfg(unix)]
+    pub fn check(&self, file: &File<'_>) -> Vec<Finding> {
+        if file.is_link() {
+            return Vec::new();
+        }
+
+        let metadata = &file.metadata;
+        let findings = match file.absolute_path() {
+            Some(path) => assess(
+                metadata.mode(),
+                metadata.is_dir(),
+                metadata.uid(),
+                path,
+                &self.places,
+            ),
+            None => Vec::new(),
+        };
+
+        if !findings.is_empty() {
+            self.flagged.store(true, Ordering::Relaxed);
+        }
+        findings
+    }
+
+    /// Without Unix permissions, there’s nothing to check.
+    #[cfg(not(unix))]
+    pub fn check(&self, _file: &File<'_>) -> Vec<Finding> {
+        Vec::new()
+    }
+}
+
+/// Everything risky about a file with the given mode and owner at the given
+/// full path.
+#[cfg_attr(not(unix), allow(dead_code))]
+fn assess(mode: u32, is_dir: bool, owner: u32, path: &Path, places: &Places) -> Vec<Finding> {
+    const SETUID: u32 = 0o4000;
+    const SETGID: u32 = 0o2000;
+    const STICKY: u32 = 0o1000;
+    const OTHER_WRITE: u32 = 0o002;
+
+    let mut findings = Vec::new();
+    let sticky = mode & STICKY != 0;
+
+    if is_dir && !sticky && places.temp_dirs.iter().any(|dir| dir == path) {
+        findings.push(Finding::NoStickyBit);
+    } else if mode & OTHER_WRITE != 0 && !(is_dir && sticky) {
+        findings.push(Finding::WorldWritable);
+    }
+
+    // The set-group-ID bit on a directory only makes new files in it belong
+    // to its group, which is harmless.
+    if !is_dir && mode & SETUID != 0 {
+        findings.push(Finding::Setuid);
+    }
+    if !is_dir && mode & SETGID != 0 {
+        findings.push(Finding::Setgid);
+    }
+
+    let in_home = places
+        .home
+        .as_ref()
+        .is_some_and(|home| path.starts_with(home));
+    if in_home && owner != places.you {
+        findings.push(Finding::OtherOwner(owner));
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn places() -> Places {
+        Places {
+            you: 1000,
+            home: Some(PathBuf::from("/home/me")),
+            temp_dirs: vec![PathBuf::from("/tmp")],
+        }
+    }
+
+    fn assess_at(mode: u32, is_dir: bool, owner: u32, path: &str) -> Vec<Finding> {
+        assess(mode, is_dir, owner, Path::new(path), &places())
+    }
+
+    #[test]
+    fn safe_files() {
+        assert_eq!(assess_at(0o644, false, 1000, "/home/me/notes"), vec![]);
+        assert_eq!(assess_at(0o755, true, 0, "/usr/bin"), vec![]);
+        assert_eq!(assess_at(0o1777, true, 0, "/tmp"), vec![]);
+        assert_eq!(assess_at(0o2775, true, 1000, "/home/me/shared"), vec![]);
+    }
+
+    #[test]
+    fn world_writable() {
+        assert_eq!(
+            assess_at(0o666, false, 1000, "/home/me/notes"),
+            vec![Finding::WorldWritable]
+        );
+        assert_eq!(
+            assess_at(0o777, true, 0, "/srv/upload"),
+            vec![Finding::WorldWritable]
+        );
+    }
+
+    #[test]
+    fn temp_dir_without_sticky_bit() {
+        assert_eq!(
+            assess_at(0o777, true, 0, "/tmp"),
+            vec![Finding::NoStickyBit]
+        );
+        assert_eq!(assess_at(0o755, true, 1000, "/tmp/build"), vec![]);
+    }
+
+    #[test]
+    fn set_id_programs() {
+        assert_eq!(
+            assess_at(0o6755, false, 0, "/usr/bin/tool"),
+            vec![Finding::Setuid, Finding::Setgid]
+        );
+        assert_eq!(Finding::Setuid.severity(), Severity::Critical);
+        assert_eq!(Finding::Setgid.severity(), Severity::Warning);
+    }
+
+    #[test]
+    fn someone_elses_file_at_home() {
+        assert_eq!(
+            assess_at(0o644, false, 0, "/home/me/.bashrc"),
+            vec![Finding::OtherOwner(0)]
+        );
+        assert_eq!(assess_at(0o644, false, 0, "/home/someone/.bashrc"), vec![]);
+    }
+}
@@ -1,15 +1,21 @@
 //! Getting the Git status of files and directories.
 
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ffi::OsStr;
 #[cfg(target_family = "unix")]
 use std::os::unix::ffi::OsStrExt;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::thread::{self, JoinHandle};
 
+use chrono::{DateTime, NaiveDateTime, Utc};
 use log::*;
 
+use crate::fs::feature::gitignore::{self, IgnoreRules};
 use crate::fs::fields as f;
+use crate::timeout::{self, Deadline};
+use crate::timing::{self, Phase};
 
 /// A **Git cache** is assembled based on the user’s input arguments.
 ///
@@ -21,19 +27,193 @@ pub struct GitCache {
 
     /// Paths that we’ve confirmed do not have Git repositories underneath them.
     misses: Vec<PathBuf>,
+
+    /// Repositories found inside the directories being listed, rather than
+    /// from the input arguments, while recursing or listing a tree.
+    nested: RwLock<Vec<Arc<GitRepo>>>,
+
+    /// The directories that have already been checked for a repository of
+    /// their own, so each only gets checked once.
+    visited: Mutex<HashSet<PathBuf>>,
+
+    /// The ignore rules, read separately from libgit2 so that the rule that
+    /// hid a file can be reported.
+    ignores: IgnoreRules,
+
+    /// Whether to report why each ignored file was hidden.
+    reporting_ignored: bool,
+
+    /// Which changes inside submodules count towards their statuses.
+    submodules: f::SubmoduleIgnore,
+
+    /// The reference that files get compared with for `--git-age-since`,
+    /// if any.
+    since: Option<String>,
 }
 
 impl GitCache {
     pub fn has_anything_for(&self, index: &Path) -> bool {
-        self.repos.iter().any(|e| e.has_path(index))
+        self.with_repo(index, |_| ()).is_some()
+    }
+
+    /// Whether the given directory is in a repository, or has one anywhere
+    /// inside it, counting the nested ones that have been found so far.
+    pub fn has_anything_under(&self, dir: &Path) -> bool {
+        let inside = |repo: &GitRepo| repo.has_path(dir) || repo.original_path.starts_with(dir);
+        self.repos.iter().any(inside) || self.nested.read().unwrap().iter().any(|r| inside(r))
     }
 
     pub fn get(&self, index: &Path, prefix_lookup: bool) -> f::Git {
-        self.repos
-            .iter()
-            .find(|repo| repo.has_path(index))
-            .map(|repo| repo.search(index, prefix_lookup))
+        timing::time(Phase::Git, || {
+            self.with_repo(index, |repo| {
+                repo.query(self.submodules, self.since.as_deref(), |statuses| {
+                    statuses.status(index, prefix_lookup)
+                })
+            })
+            .unwrap_or_default()
+        })
+    }
+
+    /// Where the file at the given path was renamed from, if Git has tracked
+    /// a rename for it.
+    pub fn rename(&self, index: &Path) -> Option<f::GitRename> {
+        timing::time(Phase::Git, || {
+            self.with_repo(index, |repo| {
+                repo.query(self.submodules, self.since.as_deref(), |statuses| {
+                    statuses.rename(index)
+                })
+            })
+            .flatten()
+        })
+    }
+
+    /// Whether the file or directory at the given path has changed since
+    /// the reference being compared with.
+    pub fn changed_since(&self, index: &Path, prefix_lookup: bool) -> f::ChangedSince {
+        timing::time(Phase::Git, || {
+            self.with_repo(index, |repo| {
+                repo.query(self.submodules, self.since.as_deref(), |statuses| {
+                    statuses.changed_since(index, prefix_lookup)
+                })
+            })
             .unwrap_or_default()
+        })
+    }
+
+    /// Runs the function with the repository that the given path is in. When
+    /// repositories are nested inside one another, this is the innermost.
+    fn with_repo<T>(&self, index: &Path, f: impl FnOnce(&GitRepo) -> T) -> Option<T> {
+        let outer = self
+            .repos
+            .iter()
+            .filter_map(|repo| Some((repo.path_depth(index)?, repo)))
+            .max_by_key(|(depth, _)| *depth);
+
+        let nested = self
+            .nested
+            .read()
+            .unwrap()
+            .iter()
+            .filter_map(|repo| Some((repo.path_depth(index)?, Arc::clone(repo))))
+            .max_by_key(|(depth, _)| *depth);
+
+        match (outer, nested) {
+            (Some((outer_depth, repo)), Some((nested_depth, _))) if outer_depth >= nested_depth => {
+                Some(f(repo))
+            }
+            (_, Some((_, repo))) => Some(f(&repo)),
+            (Some((_, repo)), None) => Some(f(repo)),
+            (None, None) => None,
+        }
+    }
+
+    /// Checks whether a directory that’s being listed is the working
+    /// directory of a repository that hasn’t been found yet, such as one
+    /// nested inside another repository, or in a directory that isn’t in a
+    /// repository at all, so that the files in it get their own statuses.
+    pub fn discover_nested(&self, dir: &Path) {
+        if !self.visited.lock().unwrap().insert(dir.to_path_buf()) {
+            return;
+        }
+
+        // A submodule has a `.git` file pointing into its parent’s `.git`
+        // directory, rather than a `.git` directory of its own.
+        let dot_git = dir.join(".git");
+        if !dot_git.exists() || (self.submodules == f::SubmoduleIgnore::All && dot_git.is_file()) {
+            return;
+        }
+
+        let flags = git2::RepositoryOpenFlags::NO_SEARCH;
+        let Ok(repo) = GitRepo::discover(dir.to_path_buf(), flags) else {
+            return;
+        };
+
+        let mut nested = self.nested.write().unwrap();
+        if self.repos.iter().any(|e| e.has_workdir(&repo.workdir))
+            || nested.iter().any(|e| e.has_workdir(&repo.workdir))
+        {
+            debug!("Skipping {:?} because we already queried it", dir);
+            return;
+        }
+
+        debug!("Discovered nested Git repo in {:?}", dir);
+        nested.push(Arc::new(repo));
+    }
+
+    /// Set which changes inside submodules count towards their statuses.
+    pub fn ignore_submodules(&mut self, submodules: f::SubmoduleIgnore) {
+        self.submodules = submodules;
+    }
+
+    /// Compare every file with the given reference, such as a tag, as well
+    /// as getting its status.
+    pub fn compare_with(&mut self, reference: &str) {
+        self.since = Some(reference.to_owned());
+    }
+
+    /// Start reporting which rule hid each ignored file.
+    pub fn report_ignored_files(&mut self) {
+        self.reporting_ignored = true;
+    }
+
+    /// Report the rule that hid the given file, if reporting was asked for.
+    pub fn report_ignored(&self, path: &Path) {
+        if self.reporting_ignored {
+            gitignore::report(path, self.ignores.matching_rule(path));
+        }
+    }
+
+    /// Starts querying every repository’s statuses on a background thread
+    /// each, so that the listing can be printed before they’ve finished.
+    pub fn query_in_background(&self) {
+        for repo in &self.repos {
+            repo.query_in_background(self.submodules, self.since.clone());
+        }
+    }
+
+    /// Whether the status for the given path can be looked up without
+    /// having to wait for a background query to finish.
+    pub fn is_ready(&self, index: &Path) -> bool {
+        self.with_repo(index, GitRepo::is_ready).unwrap_or(true)
+    }
+
+    /// Whether querying the repository the given path is in took longer
+    /// than `--io-timeout` allows, so its statuses aren’t known.
+    pub fn has_timed_out(&self, index: &Path) -> bool {
+        self.with_repo(index, GitRepo::has_timed_out)
+            .unwrap_or(false)
+    }
+
+    /// Whether any repository is still being queried in the background.
+    pub fn is_pending(&self) -> bool {
+        self.repos.iter().any(|repo| !repo.is_ready())
+    }
+
+    /// Blocks until every background query has finished.
+    pub fn wait(&self) {
+        for repo in &self.repos {
+            repo.wait();
+        }
     }
 }
 
@@ -47,6 +227,12 @@ impl FromIterator<PathBuf> for GitCache {
         let mut git = Self {
             repos: Vec::with_capacity(iter.size_hint().0),
             misses: Vec::new(),
+            nested: RwLock::default(),
+            visited: Mutex::default(),
+            ignores: IgnoreRules::default(),
+            reporting_ignored: false,
+            submodules: f::SubmoduleIgnore::default(),
+            since: None,
         };
 
         if let Ok(path) = env::var("GIT_DIR") {
@@ -124,14 +310,21 @@ enum GitContents {
     /// repository out of the `Before` variant.
     Processing,
 
+    /// The statuses are being queried on a background thread, which hands
+    /// them back when it gets joined.
+    Pending { thread: JoinHandle<Git> },
+
     /// The data we’ve extracted from the repository, but only after we’ve
     /// actually done so.
     After { statuses: Git },
+
+    /// Querying the repository took too long, and got abandoned.
+    TimedOut,
 }
 
 impl GitRepo {
-    /// Searches through this repository for a path (to a file or directory,
-    /// depending on the prefix-lookup flag) and returns its Git status.
+    /// Looks something up in this repository’s statuses, such as the status
+    /// of a file or directory, giving the default if they couldn’t be had.
     ///
     /// Actually querying the `git2` repository for the mapping of paths to
     /// Git statuses is only done once, and gets cached so we don’t need to
@@ -140,23 +333,100 @@ impl GitRepo {
     /// The temporary `Processing` enum variant is used after the `git2`
     /// repository is moved out, but before the results have been moved in!
     /// See <https://stackoverflow.com/q/45985827/3484614>
-    fn search(&self, index: &Path, prefix_lookup: bool) -> f::Git {
+    fn query<T: Default>(
+        &self,
+        submodules: f::SubmoduleIgnore,
+        since: Option<&str>,
+        f: impl FnOnce(&Git) -> T,
+    ) -> T {
         use std::mem::replace;
 
         let mut contents = self.contents.lock().unwrap();
-        if let GitContents::After { ref statuses } = *contents {
-            debug!("Git repo {:?} has been found in cache", &self.workdir);
-            return statuses.status(index, prefix_lookup);
+        match *contents {
+            GitContents::After { ref statuses } => {
+                debug!("Git repo {:?} has been found in cache", &self.workdir);
+                return f(statuses);
+            }
+            GitContents::TimedOut => return T::default(),
+            _ => {}
         }
 
-        debug!("Querying Git repo {:?} for the first time", &self.workdir);
-        let repo = replace(&mut *contents, GitContents::Processing).inner_repo();
-        let statuses = repo_to_statuses(&repo, &self.workdir);
-        let result = statuses.status(index, prefix_lookup);
+        let statuses = match replace(&mut *contents, GitContents::Processing) {
+            GitContents::Pending { thread } => {
+                debug!("Waiting for Git repo {:?} to be queried", &self.workdir);
+                timeout::join(thread)
+            }
+            before => {
+                debug!("Querying Git repo {:?} for the first time", &self.workdir);
+                let repo = before.inner_repo();
+                let workdir = self.workdir.clone();
+                let since = since.map(String::from);
+                Deadline::default()
+                    .run(move || repo_to_statuses(&repo, &workdir, submodules, since.as_deref()))
+            }
+        };
+
+        let Some(statuses) = statuses else {
+            warn!("Gave up querying Git repo {:?}", &self.workdir);
+            let _processing = replace(&mut *contents, GitContents::TimedOut);
+            return T::default();
+        };
+
+        let result = f(&statuses);
         let _processing = replace(&mut *contents, GitContents::After { statuses });
         result
     }
 
+    /// Moves the `git2` repository onto a new thread that queries it, if it
+    /// hasn’t been queried already.
+    fn query_in_background(&self, submodules: f::SubmoduleIgnore, since: Option<String>) {
+        use std::mem::replace;
+
+        let mut contents = self.contents.lock().unwrap();
+        if let GitContents::Before { .. } = *contents {
+            debug!("Querying Git repo {:?} in the background", &self.workdir);
+            let repo = replace(&mut *contents, GitContents::Processing).inner_repo();
+            let workdir = self.workdir.clone();
+            let thread = thread::spawn(move || {
+                repo_to_statuses(&repo, &workdir, submodules, since.as_deref())
+            });
+            let _processing = replace(&mut *contents, GitContents::Pending { thread });
+        }
+    }
+
+    /// Whether this repository can be searched without blocking on its
+    /// background thread.
+    fn is_ready(&self) -> bool {
+        match *self.contents.lock().unwrap() {
+            GitContents::Pending { ref thread } => thread.is_finished(),
+            _ => true,
+        }
+    }
+
+    /// Whether querying this repository got abandoned for taking too long.
+    fn has_timed_out(&self) -> bool {
+        matches!(*self.contents.lock().unwrap(), GitContents::TimedOut)
+    }
+
+    /// Blocks until this repository’s background thread, if any, has
+    /// finished, and caches what it found.
+    fn wait(&self) {
+        use std::mem::replace;
+
+        let mut contents = self.contents.lock().unwrap();
+        if let GitContents::Pending { .. } = *contents {
+            if let GitContents::Pending { thread } =
+                replace(&mut *contents, GitContents::Processing)
+            {
+                let queried = match timeout::join(thread) {
+                    Some(statuses) => GitContents::After { statuses },
+                    None => GitContents::TimedOut,
+                };
+                let _processing = replace(&mut *contents, queried);
+            }
+        }
+    }
+
     /// Whether this repository has the given working directory.
     fn has_workdir(&self, path: &Path) -> bool {
         self.workdir == path
@@ -164,8 +434,18 @@ impl GitRepo {
 
     /// Whether this repository cares about the given path at all.
     fn has_path(&self, path: &Path) -> bool {
-        path.starts_with(&self.original_path)
-            || self.extra_paths.iter().any(|e| path.starts_with(e))
+        self.path_depth(path).is_some()
+    }
+
+    /// If this repository cares about the given path, how many components
+    /// long the path it was found through is, so that the most specific of
+    /// several repositories can be picked.
+    fn path_depth(&self, path: &Path) -> Option<usize> {
+        std::iter::once(&self.original_path)
+            .chain(&self.extra_paths)
+            .filter(|e| path.starts_with(e))
+            .map(|e| e.components().count())
+            .max()
     }
 
     /// Open a Git repository. Depending on the flags, the path is either
@@ -215,11 +495,33 @@ impl GitContents {
 /// mapping of files to their Git status.
 /// We will have already used the working directory at this point, so it gets
 /// passed in rather than deriving it from the `Repository` again.
-fn repo_to_statuses(repo: &git2::Repository, workdir: &Path) -> Git {
+fn repo_to_statuses(
+    repo: &git2::Repository,
+    workdir: &Path,
+    submodules: f::SubmoduleIgnore,
+    since: Option<&str>,
+) -> Git {
     let mut statuses = Vec::new();
+    let mut renames = Vec::new();
+
+    // These are the files libgit2 includes without any options, along with
+    // renames, which it has to be asked to look for. It follows each
+    // submodule’s configuration, so when the submodules are to be treated
+    // differently, they get left out here and looked up on their own below,
+    // as libgit2 can only be told to ignore them differently by changing the
+    // configuration itself.
+    let submodule_ignore = submodules.to_git2();
+    let mut options = git2::StatusOptions::new();
+    options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(true)
+        .renames_head_to_index(true)
+        .renames_index_to_workdir(true)
+        .exclude_submodules(submodule_ignore.is_some());
 
     info!("Getting Git statuses for repo with workdir {:?}", workdir);
-    match repo.statuses(None) {
+    match repo.statuses(Some(&mut options)) {
         Ok(es) => {
             for e in es.iter() {
                 #[cfg(target_family = "unix")]
@@ -228,6 +530,16 @@ fn repo_to_statuses(repo: &git2::Repository, workdir: &Path) -> Git {
                 // https://github.com/ogham/exa/issues/698
                 #[cfg(not(target_family = "unix"))]
                 let path = workdir.join(Path::new(e.path().unwrap()));
+
+                // A rename gets listed under the path it was renamed from,
+                // but the file that’s there now is at the path it was
+                // renamed to, so it gets listed under both.
+                if let Some(rename) = rename_of(repo, workdir, &e) {
+                    let renamed_path = workdir.join(&rename.to);
+                    statuses.push((renamed_path.clone(), e.status()));
+                    renames.push((renamed_path, rename));
+                }
+
                 let elem = (path, e.status());
                 statuses.push(elem);
             }
@@ -240,7 +552,203 @@ fn repo_to_statuses(repo: &git2::Repository, workdir: &Path) -> Git {
         }
     }
 
-    Git { statuses }
+    if let Some(ignore) = submodule_ignore {
+        statuses.extend(submodule_statuses(repo, workdir, ignore));
+    }
+
+    let changes = since.and_then(|reference| changes_since(repo, workdir, reference));
+
+    Git {
+        statuses,
+        renames,
+        changes,
+    }
+}
+
+/// The files that are different in the working directory from how they
+/// were in the given reference’s tree, found with a single diff between
+/// the two, along with when each was last modified. Staged and unstaged
+/// changes both count, as do untracked files, but ignored ones don’t.
+fn changes_since(
+    repo: &git2::Repository,
+    workdir: &Path,
+    reference: &str,
+) -> Option<Vec<(PathBuf, Option<NaiveDateTime>)>> {
+    let tree = match repo
+        .revparse_single(reference)
+        .and_then(|object| object.peel_to_tree())
+    {
+        Ok(tree) => tree,
+        Err(e) => {
+            warn!(
+                "Couldn't find {:?} in Git repo {:?}: {}",
+                reference,
+                workdir,
+                e.message()
+            );
+            return None;
+        }
+    };
+
+    let mut options = git2::DiffOptions::new();
+    options.include_untracked(true).recurse_untracked_dirs(true);
+
+    info!("Comparing Git repo {:?} with {:?}", workdir, reference);
+    let diff = match repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options)) {
+        Ok(diff) => diff,
+        Err(e) => {
+            error!("Error comparing with {:?}: {:?}", reference, e);
+            return None;
+        }
+    };
+
+    let changes = diff
+        .deltas()
+        .filter_map(|delta| {
+            let path = workdir.join(delta.new_file().path()?);
+            let modified = std::fs::symlink_metadata(&path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(|time| DateTime::<Utc>::from(time).naive_utc());
+            Some((path, modified))
+        })
+        .collect();
+
+    Some(changes)
+}
+
+/// Where the file in a status entry was renamed from and to, if Git has
+/// tracked a rename for it, either in the index or in the working directory
+/// or both, and how similar each rename’s contents are.
+fn rename_of(
+    repo: &git2::Repository,
+    workdir: &Path,
+    entry: &git2::StatusEntry<'_>,
+) -> Option<f::GitRename> {
+    let is_rename = |delta: &git2::DiffDelta<'_>| delta.status() == git2::Delta::Renamed;
+    let staged = entry.head_to_index().filter(is_rename);
+    let unstaged = entry.index_to_workdir().filter(is_rename);
+
+    let from = staged.as_ref().or(unstaged.as_ref())?.old_file().path()?;
+    let to = unstaged.as_ref().or(staged.as_ref())?.new_file().path()?;
+
+    let blob = |id| repo.find_blob(id).ok().map(|blob| blob.content().to_vec());
+    let staged_similarity = staged.as_ref().and_then(|delta| {
+        let old = blob(delta.old_file().id())?;
+        let new = blob(delta.new_file().id())?;
+        Some(similarity(&old, &new))
+    });
+    let unstaged_similarity = unstaged.as_ref().and_then(|delta| {
+        let old = blob(delta.old_file().id())?;
+        let new = std::fs::read(workdir.join(delta.new_file().path()?)).ok()?;
+        Some(similarity(&old, &new))
+    });
+
+    Some(f::GitRename {
+        from: from.to_path_buf(),
+        to: to.to_path_buf(),
+        staged_similarity,
+        unstaged_similarity,
+    })
+}
+
+/// How similar the contents of a file are to what they were before it was
+/// renamed, as a percentage, worked out the same way as the score that
+/// `git diff -M` shows. The contents get split into lines, with long lines
+/// cut every 64 bytes, and the bytes in the lines that the two have in common
+/// count as kept, out of the size of the larger of the two.
+fn similarity(old: &[u8], new: &[u8]) -> u8 {
+    fn chunks(contents: &[u8]) -> HashMap<&[u8], usize> {
+        let mut chunks = HashMap::new();
+        let mut rest = contents;
+        while !rest.is_empty() {
+            let end = rest
+                .iter()
+                .take(64)
+                .position(|&b| b == b'\n')
+                .map_or(rest.len().min(64), |newline| newline + 1);
+            *chunks.entry(&rest[..end]).or_insert(0) += end;
+            rest = &rest[end..];
+        }
+        chunks
+    }
+
+    let larger = old.len().max(new.len());
+    if larger == 0 {
+        return 100;
+    }
+
+    let new_chunks = chunks(new);
+    let kept: usize = chunks(old)
+        .into_iter()
+        .map(|(chunk, bytes)| bytes.min(new_chunks.get(chunk).copied().unwrap_or(0)))
+        .sum();
+
+    u8::try_from(kept * 100 / larger).unwrap_or(100)
+}
+
+/// Looks up the status of each of a repository’s submodules, ignoring the
+/// changes inside them that the given setting says to.
+fn submodule_statuses(
+    repo: &git2::Repository,
+    workdir: &Path,
+    ignore: git2::SubmoduleIgnore,
+) -> Vec<(PathBuf, git2::Status)> {
+    let submodules = match repo.submodules() {
+        Ok(submodules) => submodules,
+        Err(e) => {
+            error!("Error looking up Git submodules: {:?}", e);
+            return Vec::new();
+        }
+    };
+
+    submodules
+        .iter()
+        .filter_map(|submodule| {
+            let status = repo.submodule_status(submodule.name()?, ignore).ok()?;
+            let status = submodule_to_status(status);
+            (!status.is_empty()).then(|| (workdir.join(submodule.path()), status))
+        })
+        .collect()
+}
+
+/// Converts a submodule’s status into the file status that `git status`
+/// would show for it: changes to what the index or working directory hold
+/// for the submodule are additions or deletions, and anything else that’s
+/// changed inside it counts as a modification.
+fn submodule_to_status(status: git2::SubmoduleStatus) -> git2::Status {
+    use git2::{Status, SubmoduleStatus as S};
+
+    let mapping = [
+        (S::INDEX_ADDED, Status::INDEX_NEW),
+        (S::INDEX_DELETED, Status::INDEX_DELETED),
+        (S::INDEX_MODIFIED, Status::INDEX_MODIFIED),
+        (S::WD_ADDED, Status::WT_NEW),
+        (S::WD_DELETED, Status::WT_DELETED),
+        (S::WD_MODIFIED, Status::WT_MODIFIED),
+        (S::WD_INDEX_MODIFIED, Status::WT_MODIFIED),
+        (S::WD_WD_MODIFIED, Status::WT_MODIFIED),
+        (S::WD_UNTRACKED, Status::WT_MODIFIED),
+    ];
+
+    mapping
+        .iter()
+        .filter(|(from, _)| status.contains(*from))
+        .fold(Status::empty(), |acc, (_, to)| acc | *to)
+}
+
+impl f::SubmoduleIgnore {
+    /// The libgit2 setting to look submodules up with, or `None` to leave
+    /// them to the configuration.
+    fn to_git2(self) -> Option<git2::SubmoduleIgnore> {
+        match self {
+            Self::Config => None,
+            Self::None => Some(git2::SubmoduleIgnore::None),
+            Self::Untracked => Some(git2::SubmoduleIgnore::Untracked),
+            Self::Dirty => Some(git2::SubmoduleIgnore::Dirty),
+            Self::All => Some(git2::SubmoduleIgnore::All),
+        }
+    }
 }
 
 // The `repo.statuses` call above takes a long time. exa debug output:
@@ -254,9 +762,55 @@ fn repo_to_statuses(repo: &git2::Repository, workdir: &Path) -> Git {
 /// Container of Git statuses for all the files in this folder’s Git repository.
 struct Git {
     statuses: Vec<(PathBuf, git2::Status)>,
+
+    /// The renames Git has tracked, by the path each file was renamed to.
+    renames: Vec<(PathBuf, f::GitRename)>,
+
+    /// The files that have changed since the reference being compared with,
+    /// and when each was last modified, if there’s a reference and it could
+    /// be found.
+    changes: Option<Vec<(PathBuf, Option<NaiveDateTime>)>>,
 }
 
 impl Git {
+    /// Where the file at the given path was renamed from, if it was.
+    fn rename(&self, file: &Path) -> Option<f::GitRename> {
+        let path = reorient(file);
+
+        self.renames
+            .iter()
+            .find(|(renamed_path, _)| *renamed_path == path)
+            .map(|(_, rename)| rename.clone())
+    }
+
+    /// Whether the file at the given path has changed since the reference
+    /// being compared with. With a “prefix lookup”, a directory has changed
+    /// if anything inside it has.
+    fn changed_since(&self, index: &Path, prefix_lookup: bool) -> f::ChangedSince {
+        let Some(changes) = &self.changes else {
+            return f::ChangedSince::Unknown;
+        };
+
+        let path = reorient(index);
+        let mut times = changes
+            .iter()
+            .filter(|(changed, _)| {
+                if prefix_lookup {
+                    changed.starts_with(&path)
+                } else {
+                    *changed == path
+                }
+            })
+            .map(|(_, modified)| *modified)
+            .peekable();
+
+        if times.peek().is_none() {
+            f::ChangedSince::Unchanged
+        } else {
+            f::ChangedSince::Changed(times.flatten().max())
+        }
+    }
+
     /// Get either the file or directory status for the given path.
     /// “Prefix lookup” means that it should report an aggregate status of all
     /// paths starting with the given prefix (in other words, a directory).
@@ -347,13 +901,15 @@ fn reorient(path: &Path) -> PathBuf {
 }
 
 /// The character to display if the file has been modified, but not staged.
+/// A renamed file that’s also been changed counts as renamed, as it does to
+/// `git status`.
 fn working_tree_status(status: git2::Status) -> f::GitStatus {
     #[rustfmt::skip]
     return match status {
         s if s.contains(git2::Status::WT_NEW)         => f::GitStatus::New,
+        s if s.contains(git2::Status::WT_RENAMED)     => f::GitStatus::Renamed,
         s if s.contains(git2::Status::WT_MODIFIED)    => f::GitStatus::Modified,
         s if s.contains(git2::Status::WT_DELETED)     => f::GitStatus::Deleted,
-        s if s.contains(git2::Status::WT_RENAMED)     => f::GitStatus::Renamed,
         s if s.contains(git2::Status::WT_TYPECHANGE)  => f::GitStatus::TypeChange,
         s if s.contains(git2::Status::IGNORED)        => f::GitStatus::Ignored,
         s if s.contains(git2::Status::CONFLICTED)     => f::GitStatus::Conflicted,
@@ -367,9 +923,9 @@ fn index_status(status: git2::Status) -> f::GitStatus {
     #[rustfmt::skip]
     return match status {
         s if s.contains(git2::Status::INDEX_NEW)         => f::GitStatus::New,
+        s if s.contains(git2::Status::INDEX_RENAMED)     => f::GitStatus::Renamed,
         s if s.contains(git2::Status::INDEX_MODIFIED)    => f::GitStatus::Modified,
         s if s.contains(git2::Status::INDEX_DELETED)     => f::GitStatus::Deleted,
-        s if s.contains(git2::Status::INDEX_RENAMED)     => f::GitStatus::Renamed,
         s if s.contains(git2::Status::INDEX_TYPECHANGE)  => f::GitStatus::TypeChange,
         _                                                => f::GitStatus::NotModified,
     };
@@ -402,16 +958,55 @@ fn current_branch(repo: &git2::Repository) -> Option<String> {
     None
 }
 
+/// How many commits the current branch is behind its upstream branch.
+///
+/// This only compares the local branch with its remote-tracking branch, so
+/// it never touches the network: the count is as of the last `git fetch`.
+/// The result is cached per repository, as several listed directories can
+/// belong to the same one.
+fn commits_behind(repo: &git2::Repository) -> Option<usize> {
+    static BEHIND: OnceLock<Mutex<HashMap<PathBuf, Option<usize>>>> = OnceLock::new();
+
+    let cache = BEHIND.get_or_init(Mutex::default);
+    if let Some(behind) = cache.lock().unwrap().get(repo.path()) {
+        return *behind;
+    }
+
+    let behind = (|| {
+        let head = repo.head().ok()?;
+        let local = head.target()?;
+        let upstream = git2::Branch::wrap(head).upstream().ok()?;
+        let upstream = upstream.get().target()?;
+
+        match repo.graph_ahead_behind(local, upstream) {
+            Ok((_, behind)) => Some(behind),
+            Err(e) => {
+                error!("Error comparing branch with its upstream: {e:?}");
+                None
+            }
+        }
+    })();
+
+    cache
+        .lock()
+        .unwrap()
+        .insert(repo.path().to_path_buf(), behind);
+    behind
+}
+
 impl f::SubdirGitRepo {
-    pub fn from_path(dir: &Path, status: bool) -> Self {
+    pub fn from_path(dir: &Path, status: bool, behind: bool) -> Self {
         let path = &reorient(dir);
 
         if let Ok(repo) = git2::Repository::open(path) {
             let branch = current_branch(&repo);
+            let behind = if behind { commits_behind(&repo) } else { None };
+
             if !status {
                 return Self {
                     status: None,
                     branch,
+                    behind,
                 };
             }
             match repo.statuses(None) {
@@ -420,11 +1015,13 @@ impl f::SubdirGitRepo {
                         return Self {
                             status: Some(f::SubdirGitRepoStatus::GitDirty),
                             branch,
+                            behind,
                         };
                     }
                     return Self {
                         status: Some(f::SubdirGitRepoStatus::GitClean),
                         branch,
+                        behind,
                     };
                 }
                 Err(e) => {
@@ -439,6 +1036,154 @@ impl f::SubdirGitRepo {
                 None
             },
             branch: None,
+            behind: None,
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use git2::SubmoduleStatus as S;
+
+    #[test]
+    fn similar_contents() {
+        assert_eq!(similarity(b"", b""), 100);
+        assert_eq!(similarity(b"a\nb\nc\nd\n", b"a\nb\nc\nd\n"), 100);
+        assert_eq!(similarity(b"a\nb\nc\nd\n", b"a\nb\nc\ne\n"), 75);
+        assert_eq!(similarity(b"a\nb\n", b"a\nb\nc\nd\n"), 50);
+        assert_eq!(similarity(b"one\n", b"two\n"), 0);
+    }
+
+    #[test]
+    fn long_lines_get_cut() {
+        let old = [b'x'; 200];
+        let mut new = old;
+        new[199] = b'y';
+        assert_eq!(similarity(&old, &new), 96);
+    }
+
+    #[test]
+    fn clean_submodule() {
+        let status = S::IN_HEAD | S::IN_INDEX | S::IN_CONFIG | S::IN_WD;
+        assert_eq!(submodule_to_status(status), git2::Status::empty());
+    }
+
+    #[test]
+    fn submodule_with_new_commits() {
+        let status = S::IN_HEAD | S::IN_INDEX | S::IN_WD | S::WD_MODIFIED;
+        assert_eq!(submodule_to_status(status), git2::Status::WT_MODIFIED);
+    }
+
+    #[test]
+    fn submodule_added_to_index() {
+        let status = S::IN_INDEX | S::IN_WD | S::INDEX_ADDED | S::WD_UNTRACKED;
+        assert_eq!(
+            submodule_to_status(status),
+            git2::Status::INDEX_NEW | git2::Status::WT_MODIFIED
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn newest_change_inside_a_directory() {
+        let old = NaiveDateTime::from_timestamp_opt(1_000_000, 0);
+        let new = NaiveDateTime::from_timestamp_opt(2_000_000, 0);
+        let git = Git {
+            statuses: Vec::new(),
+            renames: Vec::new(),
+            changes: Some(vec![
+                (PathBuf::from("/nowhere/src/old.rs"), old),
+                (PathBuf::from("/nowhere/src/new.rs"), new),
+            ]),
+        };
+
+        let dir = Path::new("/nowhere/src");
+        let file = Path::new("/nowhere/src/old.rs");
+        let other = Path::new("/nowhere/README.md");
+        assert_eq!(git.changed_since(dir, true), f::ChangedSince::Changed(new));
+        assert_eq!(
+            git.changed_since(file, false),
+            f::ChangedSince::Changed(old)
+        );
+        assert_eq!(git.changed_since(other, false), f::ChangedSince::Unchanged);
+    }
+
+    /// A cache made from the given directory, as when it’s listed.
+    fn cache_for(dir: &Path) -> GitCache {
+        std::iter::once(dir.to_path_buf()).collect()
+    }
+
+    /// The working directory of the repository a path is in, if any.
+    fn workdir_of(git: &GitCache, path: &Path) -> Option<PathBuf> {
+        git.with_repo(path, |repo| repo.workdir.clone())
+    }
+
+    #[test]
+    fn nested_repository_is_found() {
+        let dir = tempfile::tempdir().unwrap();
+        let outer = dir.path().to_path_buf();
+        let inner = outer.join("vendor/inner");
+        std::fs::create_dir_all(&inner).unwrap();
+        git2::Repository::init(&outer).unwrap();
+        git2::Repository::init(&inner).unwrap();
+
+        let git = cache_for(&outer);
+        let file = inner.join("lib.rs");
+        assert_eq!(
+            workdir_of(&git, &file).map(|w| w.canonicalize().unwrap()),
+            Some(outer.canonicalize().unwrap())
+        );
+
+        git.discover_nested(&inner);
+        assert_eq!(
+            workdir_of(&git, &file).map(|w| w.canonicalize().unwrap()),
+            Some(inner.canonicalize().unwrap())
+        );
+        assert_eq!(
+            workdir_of(&git, &outer.join("README.md")).map(|w| w.canonicalize().unwrap()),
+            Some(outer.canonicalize().unwrap())
+        );
+    }
+
+    #[test]
+    fn nested_repository_outside_any_other() {
+        let dir = tempfile::tempdir().unwrap();
+        let inner = dir.path().join("project");
+        std::fs::create_dir(&inner).unwrap();
+        git2::Repository::init(&inner).unwrap();
+
+        let git = cache_for(dir.path());
+        assert!(!git.has_anything_for(dir.path()));
+        assert!(!git.has_anything_under(dir.path()));
+
+        git.discover_nested(&inner);
+        assert!(git.has_anything_for(&inner.join("main.rs")));
+        assert!(!git.has_anything_for(&dir.path().join("notes.txt")));
+        assert!(git.has_anything_under(dir.path()));
+    }
+
+    #[test]
+    fn no_repository_anywhere() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        std::fs::create_dir(&sub).unwrap();
+
+        let git = cache_for(dir.path());
+        git.discover_nested(&sub);
+        assert!(!git.has_anything_under(dir.path()));
+    }
+
+    #[test]
+    fn path_depths() {
+        let dir = tempfile::tempdir().unwrap();
+        git2::Repository::init(dir.path()).unwrap();
+
+        let git = cache_for(dir.path());
+        let repo = &git.repos[0];
+        let depth = dir.path().components().count();
+        assert_eq!(repo.path_depth(dir.path()), Some(depth));
+        assert_eq!(repo.path_depth(&dir.path().join("a/b.rs")), Some(depth));
+        assert_eq!(repo.path_depth(Path::new("/elsewhere")), None);
+    }
+}
@@ -0,0 +1,460 @@
+//! Reading `.gitignore` rules without going through libgit2.
+//!
+//! This is what `--git-ignore` falls back on when eza is built without the
+//! `git` feature, and it’s also how `--git-ignore-debug` finds out which
+//! rule was responsible for hiding a file, as libgit2 only says *whether* a
+//! path is ignored.
+//!
+//! Rules are read from the same places Git reads them from, from lowest to
+//! highest precedence: the file named by `core.excludesFile` (or the default
+//! `git/ignore` in the user’s configuration directory), the repository’s
+//! `.git/info/exclude`, and then every `.gitignore` between the root of the
+//! working tree and the file.
+//!
+//! The same rules can also be read from a file given with
+//! `--ignore-glob-file`, which applies whether or not there’s a repository.
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use glob::{MatchOptions, Pattern};
+use log::*;
+
+/// A cache of the ignore rules read so far, keyed by the directory they
+/// apply to, so each file of rules only gets read once per run.
+#[derive(Default)]
+pub struct IgnoreRules {
+    /// The rules from `.gitignore` files, keyed by their directory.
+    directories: Mutex<HashMap<PathBuf, Arc<Vec<Rule>>>>,
+
+    /// The global and `info/exclude` rules, keyed by the repository root.
+    repositories: Mutex<HashMap<PathBuf, Arc<Vec<Rule>>>>,
+}
+
+/// The rules from a file given with `--ignore-glob-file`, which hide files
+/// wherever they are, rather than only inside a repository. As with a
+/// `.gitignore`, patterns containing a slash are relative to the directory
+/// the file is in.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct IgnoreFile {
+    rules: Vec<Rule>,
+}
+
+/// One line of a `.gitignore`-style file.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct Rule {
+    /// The glob that paths get matched against.
+    pattern: Pattern,
+
+    /// Whether the line started with `!`, re-including matching paths.
+    negated: bool,
+
+    /// Whether the line ended with `/`, so it only matches directories.
+    directories_only: bool,
+
+    /// Whether the pattern contains a slash, which makes it match paths
+    /// relative to `base` rather than just file names.
+    anchored: bool,
+
+    /// The directory that anchored patterns are relative to.
+    base: PathBuf,
+
+    /// Where the rule came from, for reporting.
+    source: PathBuf,
+    line_number: usize,
+    line: String,
+}
+
+/// The rule responsible for a path being ignored.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct IgnoreMatch {
+    pub source: PathBuf,
+    pub line_number: usize,
+    pub line: String,
+}
+
+impl fmt::Display for IgnoreMatch {
+    /// Formats the rule the same way `git check-ignore --verbose` does.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}",
+            self.source.display(),
+            self.line_number,
+            self.line
+        )
+    }
+}
+
+impl IgnoreRules {
+    /// Find the rule that causes Git to ignore the given path, if any. A
+    /// path is also ignored if any of the directories above it are, as Git
+    /// can’t re-include anything inside an ignored directory.
+    pub fn matching_rule(&self, path: &Path) -> Option<IgnoreMatch> {
+        let path = absolute(path)?;
+        let root = repository_root(&path)?;
+        let relative = path.strip_prefix(&root).ok()?;
+
+        let mut parent = root.clone();
+        for component in relative.components() {
+            let current = parent.join(component);
+
+            if component.as_os_str() == ".git" && parent == root {
+                return Some(IgnoreMatch {
+                    source: root.join(".git"),
+                    line_number: 0,
+                    line: String::from(".git"),
+                });
+            }
+
+            let is_dir = current != path || path.is_dir();
+            let rule = self
+                .rules_for(&root, &parent)
+                .iter()
+                .flat_map(|rules| rules.iter())
+                .filter(|rule| rule.matches(&current, is_dir))
+                .last()
+                .filter(|rule| !rule.negated)
+                .map(Rule::to_match);
+
+            if rule.is_some() {
+                return rule;
+            }
+
+            parent = current;
+        }
+
+        None
+    }
+
+    /// Every set of rules that applies to files in the given directory, in
+    /// increasing order of precedence.
+    fn rules_for(&self, root: &Path, dir: &Path) -> Vec<Arc<Vec<Rule>>> {
+        let mut all = vec![self.repository_rules(root)];
+
+        let mut current = root.to_path_buf();
+        all.push(self.gitignore_rules(&current));
+        if let Ok(relative) = dir.strip_prefix(root) {
+            for component in relative.components() {
+                current.push(component);
+                all.push(self.gitignore_rules(&current));
+            }
+        }
+
+        all
+    }
+
+    fn repository_rules(&self, root: &Path) -> Arc<Vec<Rule>> {
+        let mut repositories = self.repositories.lock().unwrap();
+        let rules = repositories.entry(root.to_path_buf()).or_insert_with(|| {
+            let mut rules = Vec::new();
+            if let Some(excludes) = global_excludes_file(root) {
+                rules.extend(read_rules(&excludes, root));
+            }
+            rules.extend(read_rules(&root.join(".git/info/exclude"), root));
+            Arc::new(rules)
+        });
+        Arc::clone(rules)
+    }
+
+    fn gitignore_rules(&self, dir: &Path) -> Arc<Vec<Rule>> {
+        let mut directories = self.directories.lock().unwrap();
+        let rules = directories
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| Arc::new(read_rules(&dir.join(".gitignore"), dir)));
+        Arc::clone(rules)
+    }
+}
+
+impl IgnoreFile {
+    /// Read the rules from the file at the given path, which has to exist.
+    pub fn read(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let base = absolute(path)
+            .as_deref()
+            .and_then(Path::parent)
+            .map_or_else(PathBuf::new, Path::to_path_buf);
+
+        Ok(Self::parse(&contents, &base, path))
+    }
+
+    fn parse(contents: &str, base: &Path, source: &Path) -> Self {
+        let rules = contents
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| Rule::parse(line, base, source, index + 1))
+            .collect();
+
+        Self { rules }
+    }
+
+    /// Whether the file at the given path gets hidden, which is up to the
+    /// last rule that matches it.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        absolute(path).is_some_and(|path| self.matches(&path, is_dir))
+    }
+
+    fn matches(&self, absolute_path: &Path, is_dir: bool) -> bool {
+        self.rules
+            .iter()
+            .filter(|rule| rule.matches(absolute_path, is_dir))
+            .last()
+            .is_some_and(|rule| !rule.negated)
+    }
+}
+
+impl Rule {
+    /// Parse a line of an ignore file, returning nothing for blank lines
+    /// and comments.
+    fn parse(original: &str, base: &Path, source: &Path, line_number: usize) -> Option<Self> {
+        let mut line = original;
+
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+
+        // Trailing spaces are ignored unless they’re escaped.
+        if !line.ends_with("\\ ") {
+            line = line.trim_end_matches(' ');
+        }
+
+        // A leading backslash escapes a `!` or `#` that’s part of the name.
+        let negated = line.starts_with('!');
+        if negated || line.starts_with("\\!") || line.starts_with("\\#") {
+            line = &line[1..];
+        }
+
+        let directories_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        if line.is_empty() {
+            return None;
+        }
+
+        let anchored = line.contains('/');
+        let line = line.trim_start_matches('/');
+
+        let pattern = match Pattern::new(line) {
+            Ok(pattern) => pattern,
+            Err(e) => {
+                debug!(
+                    "Skipping bad ignore pattern {:?} in {:?}: {}",
+                    line, source, e
+                );
+                return None;
+            }
+        };
+
+        Some(Self {
+            pattern,
+            negated,
+            directories_only,
+            anchored,
+            base: base.to_path_buf(),
+            source: source.to_path_buf(),
+            line_number,
+            line: original.trim_end().to_string(),
+        })
+    }
+
+    fn matches(&self, path: &Path, is_dir: bool) -> bool {
+        const OPTIONS: MatchOptions = MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+
+        if self.directories_only && !is_dir {
+            return false;
+        }
+
+        if self.anchored {
+            path.strip_prefix(&self.base)
+                .is_ok_and(|relative| self.pattern.matches_path_with(relative, OPTIONS))
+        } else {
+            path.file_name()
+                .is_some_and(|name| self.pattern.matches_with(&name.to_string_lossy(), OPTIONS))
+        }
+    }
+
+    fn to_match(&self) -> IgnoreMatch {
+        IgnoreMatch {
+            source: self.source.clone(),
+            line_number: self.line_number,
+            line: self.line.clone(),
+        }
+    }
+}
+
+/// Print the rule that hid a file to standard error, in the same format as
+/// `git check-ignore --verbose --non-matching`, which leaves the rule empty
+/// when it can’t say which one it was.
+pub fn report(path: &Path, rule: Option<IgnoreMatch>) {
+    match rule {
+        Some(rule) => eprintln!("{rule}\t{}", path.display()),
+        None => eprintln!("::\t{}", path.display()),
+    }
+}
+
+/// Read all the rules from an ignore file, which may well not exist.
+fn read_rules(source: &Path, base: &Path) -> Vec<Rule> {
+    let Ok(contents) = fs::read_to_string(source) else {
+        return Vec::new();
+    };
+
+    debug!("Reading ignore rules from {:?}", source);
+    IgnoreFile::parse(&contents, base, source).rules
+}
+
+/// Turn a path into an absolute one without resolving the final component,
+/// so that a symlink keeps its own name rather than its target’s.
+fn absolute(path: &Path) -> Option<PathBuf> {
+    let name = path.file_name()?;
+    let parent = match path.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    };
+
+    Some(parent.canonicalize().ok()?.join(name))
+}
+
+/// The root of the working tree that contains the given path, if any.
+fn repository_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// The file named by `core.excludesFile`, or Git’s default location for it.
+/// The repository’s own configuration takes precedence over the global one.
+fn global_excludes_file(root: &Path) -> Option<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let config_home = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".config")));
+
+    let configs = [
+        config_home.as_ref().map(|dir| dir.join("git/config")),
+        home.as_ref().map(|home| home.join(".gitconfig")),
+        Some(root.join(".git/config")),
+    ];
+
+    let configured = configs
+        .iter()
+        .flatten()
+        .filter_map(|config| fs::read_to_string(config).ok())
+        .filter_map(|contents| excludes_file_setting(&contents))
+        .last();
+
+    match configured {
+        Some(path) => match (path.strip_prefix("~/"), &home) {
+            (Some(rest), Some(home)) => Some(home.join(rest)),
+            _ => Some(PathBuf::from(path)),
+        },
+        None => config_home.map(|dir| dir.join("git/ignore")),
+    }
+}
+
+/// Pick the `excludesFile` setting out of the `[core]` section of a Git
+/// configuration file. Keys are case-insensitive, and later settings win.
+fn excludes_file_setting(contents: &str) -> Option<String> {
+    let mut in_core = false;
+    let mut setting = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            in_core = line
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .trim()
+                .eq_ignore_ascii_case("core");
+        } else if in_core {
+            if let Some((key, value)) = line.split_once('=') {
+                if key.trim().eq_ignore_ascii_case("excludesfile") {
+                    setting = Some(value.trim().trim_matches('"').to_string());
+                }
+            }
+        }
+    }
+
+    setting
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn rule(line: &str) -> Rule {
+        Rule::parse(line, Path::new("/repo"), Path::new("/repo/.gitignore"), 1).unwrap()
+    }
+
+    #[test]
+    fn comments_and_blanks() {
+        let source = Path::new("/repo/.gitignore");
+        assert!(Rule::parse("# comment", Path::new("/repo"), source, 1).is_none());
+        assert!(Rule::parse("   ", Path::new("/repo"), source, 1).is_none());
+    }
+
+    #[test]
+    fn file_names_at_any_depth() {
+        let rule = rule("*.log");
+        assert!(rule.matches(Path::new("/repo/debug.log"), false));
+        assert!(rule.matches(Path::new("/repo/src/deep/debug.log"), false));
+        assert!(!rule.matches(Path::new("/repo/debug.txt"), false));
+    }
+
+    #[test]
+    fn anchored_to_base() {
+        let rule = rule("/target");
+        assert!(rule.matches(Path::new("/repo/target"), true));
+        assert!(!rule.matches(Path::new("/repo/src/target"), true));
+    }
+
+    #[test]
+    fn directories_only() {
+        let rule = rule("build/");
+        assert!(rule.matches(Path::new("/repo/src/build"), true));
+        assert!(!rule.matches(Path::new("/repo/src/build"), false));
+    }
+
+    #[test]
+    fn negation() {
+        let rule = rule("!keep.log");
+        assert!(rule.negated);
+        assert!(rule.matches(Path::new("/repo/keep.log"), false));
+    }
+
+    #[test]
+    fn ignore_file_last_rule_wins() {
+        let source = Path::new("/team/.ezaignore");
+        let file = IgnoreFile::parse("*.log\n!keep.log\n/dist/\n", Path::new("/team"), source);
+        let matches = |path: &str, is_dir| file.matches(Path::new(path), is_dir);
+
+        assert!(matches("/team/src/debug.log", false));
+        assert!(!matches("/team/keep.log", false));
+        assert!(matches("/team/dist", true));
+        assert!(!matches("/team/src/dist", true));
+    }
+
+    #[test]
+    fn core_excludes_file() {
+        let config = "[user]\n\tname = someone\n[core]\n\texcludesFile = \"~/.gitignore_global\"\n";
+        assert_eq!(
+            excludes_file_setting(config),
+            Some(String::from("~/.gitignore_global"))
+        );
+    }
+
+    #[test]
+    fn excludes_file_outside_core() {
+        let config = "[alias]\n\texcludesfile = nope\n";
+        assert_eq!(excludes_file_setting(config), None);
+    }
+}
@@ -0,0 +1,308 @@
+//! Finding out which files processes have open, for `--in-use`.
+//!
+//! Nothing keeps a list of which processes are using a file, so every
+//! process gets asked what it has open, the first time a file gets looked
+//! up, and the files get remembered by their device and inode numbers. This
+//! is slow when there are a lot of processes, which is why it’s not on by
+//! default.
+//!
+//! On Linux, each process’s open file descriptors, its program, its working
+//! directory, and the files it has mapped into memory all count, and get
+//! found by following the links in `/proc`. On macOS, `libproc` gets asked
+//! for each process’s open files and its program. Processes belonging to
+//! other users can only be looked at by root, and nothing gets found on
+//! other platforms.
+
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+use log::*;
+
+use crate::fs::File;
+
+/// The names of the processes using each file, by device and inode.
+type Users = HashMap<(u64, u64), BTreeSet<String>>;
+
+/// How many process names to show before leaving the rest out.
+const MAX_NAMES: usize = 3;
+
+/// What every process had open when eza first looked.
+#[derive(Debug, Default)]
+pub struct OpenFiles {
+    users: OnceLock<Users>,
+}
+
+impl OpenFiles {
+    /// Asks every process what it has open, which only gets done once.
+    #[cfg_attr(not(unix), allow(dead_code))]
+    fn users(&self) -> &Users {
+        self.users.get_or_init(|| {
+            let started = std::time::Instant::now();
+            let users = scan_processes();
+            debug!(
+                "Found {} files in use in {:?}",
+                users.len(),
+                started.elapsed()
+            );
+            users
+        })
+    }
+
+    /// The names of the processes that have the given file open, if any do.
+    #[cfg(unix)]
+    pub fn users_of(&self, file: &File<'_>) -> Option<&BTreeSet<String>> {
+        let key = (file.metadata.dev(), file.metadata.ino());
+        self.users().get(&key)
+    }
+
+    /// Without device and inode numbers, there’s nothing to look files up by.
+    #[cfg(not(unix))]
+    pub fn users_of(&self, _file: &File<'_>) -> Option<&BTreeSet<String>> {
+        None
+    }
+}
+
+/// The short description shown next to the file’s name, such as
+/// `in use by cargo, nvim`.
+pub fn label(names: &BTreeSet<String>) -> String {
+    let mut label = String::from("in use by ");
+    let shown = names.iter().take(MAX_NAMES).cloned().collect::<Vec<_>>();
+    label.push_str(&shown.join(", "));
+
+    if names.len() > MAX_NAMES {
+        label.push_str(&format!(" and {} more", names.len() - MAX_NAMES));
+    }
+    label
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn scan_processes() -> Users {
+    use std::fs;
+    use std::os::unix::fs::MetadataExt;
+    use std::path::PathBuf;
+
+    let mut users = Users::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return users;
+    };
+
+    let own_pid = std::process::id().to_string();
+    for entry in entries.flatten() {
+        let file_name = entry.file_name();
+        let Some(pid) = file_name.to_str() else {
+            continue;
+        };
+        if pid == own_pid || !pid.bytes().all(|b| b.is_ascii_digit()) {
+            continue;
+        }
+
+        let dir = entry.path();
+        let Ok(name) = fs::read_to_string(dir.join("comm")) else {
+            continue;
+        };
+        let name = name.trim_end();
+
+        // Getting the metadata of a link in `/proc` gets the metadata of
+        // the file that’s open, even if it’s since been renamed.
+        let mut paths = vec![dir.join("exe"), dir.join("cwd")];
+        if let Ok(fds) = fs::read_dir(dir.join("fd")) {
+            paths.extend(fds.flatten().map(|fd| fd.path()));
+        }
+        if let Ok(maps) = fs::read_to_string(dir.join("maps")) {
+            paths.extend(mapped_paths(&maps).into_iter().map(PathBuf::from));
+        }
+
+        for path in paths {
+            if let Ok(metadata) = fs::metadata(&path) {
+                users
+                    .entry((metadata.dev(), metadata.ino()))
+                    .or_default()
+                    .insert(name.to_string());
+            }
+        }
+    }
+
+    users
+}
+
+/// The files mapped into a process’s memory, from its `maps` file, where
+/// each line has the address range, permissions, offset, device, and inode,
+/// followed by the file’s path if it has one. Files that have since been
+/// deleted can’t be found again by their paths, so they get left out.
+#[cfg_attr(not(any(target_os = "linux", target_os = "android")), allow(dead_code))]
+fn mapped_paths(maps: &str) -> BTreeSet<&str> {
+    maps.lines()
+        .filter_map(|line| line.find('/').map(|start| &line[start..]))
+        .filter(|path| !path.ends_with(" (deleted)"))
+        .collect()
+}
+
+#[cfg(target_os = "macos")]
+#[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn scan_processes() -> Users {
+    use std::ffi::{c_int, OsStr};
+    use std::fs;
+    use std::mem::{size_of, MaybeUninit};
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::MetadataExt;
+    use std::ptr;
+
+    const PROC_PIDLISTFDS: c_int = 1;
+    const PROC_PIDFDVNODEINFO: c_int = 1;
+    const PROX_FDTYPE_VNODE: u32 = 1;
+    const PROC_PIDPATHINFO_MAXSIZE: usize = 4 * libc::PATH_MAX as usize;
+
+    /// One of a process’s open file descriptors.
+    #[repr(C)]
+    #[derive(Default, Copy, Clone)]
+    struct FdInfo {
+        fd: i32,
+        fd_type: u32,
+    }
+
+    /// How a file descriptor was opened.
+    #[repr(C)]
+    #[allow(dead_code)]
+    struct FileInfo {
+        open_flags: u32,
+        status: u32,
+        offset: i64,
+        file_type: i32,
+        guard_flags: u32,
+    }
+
+    /// A file descriptor that has a file open.
+    #[repr(C)]
+    struct VnodeFdInfo {
+        file: FileInfo,
+        vnode: libc::vnode_info,
+    }
+
+    let mut users = Users::new();
+
+    let count = unsafe { libc::proc_listallpids(ptr::null_mut(), 0) };
+    if count <= 0 {
+        return users;
+    }
+
+    // Leave room for processes that start in between.
+    let mut pids = vec![0 as c_int; count as usize + 64];
+    let count = unsafe {
+        libc::proc_listallpids(
+            pids.as_mut_ptr().cast(),
+            (pids.len() * size_of::<c_int>()) as c_int,
+        )
+    };
+    pids.truncate(count.max(0) as usize);
+
+    let own_pid = std::process::id() as c_int;
+    for pid in pids.into_iter().filter(|&pid| pid > 0 && pid != own_pid) {
+        let mut name = [0_u8; 256];
+        let length = unsafe { libc::proc_name(pid, name.as_mut_ptr().cast(), name.len() as u32) };
+        if length <= 0 {
+            continue;
+        }
+        let name = String::from_utf8_lossy(&name[..length as usize]).into_owned();
+
+        let mut add = |dev: u64, ino: u64| {
+            users.entry((dev, ino)).or_default().insert(name.clone());
+        };
+
+        let mut path = vec![0_u8; PROC_PIDPATHINFO_MAXSIZE];
+        let length =
+            unsafe { libc::proc_pidpath(pid, path.as_mut_ptr().cast(), path.len() as u32) };
+        if length > 0 {
+            let path = OsStr::from_bytes(&path[..length as usize]);
+            if let Ok(metadata) = fs::metadata(path) {
+                add(metadata.dev(), metadata.ino());
+            }
+        }
+
+        let size = unsafe { libc::proc_pidinfo(pid, PROC_PIDLISTFDS, 0, ptr::null_mut(), 0) };
+        if size <= 0 {
+            continue;
+        }
+
+        let mut fds = vec![FdInfo::default(); size as usize / size_of::<FdInfo>() + 16];
+        let size = unsafe {
+            libc::proc_pidinfo(
+                pid,
+                PROC_PIDLISTFDS,
+                0,
+                fds.as_mut_ptr().cast(),
+                (fds.len() * size_of::<FdInfo>()) as c_int,
+            )
+        };
+        fds.truncate(size.max(0) as usize / size_of::<FdInfo>());
+
+        for fd in fds.iter().filter(|fd| fd.fd_type == PROX_FDTYPE_VNODE) {
+            let mut info = MaybeUninit::<VnodeFdInfo>::zeroed();
+            let size = unsafe {
+                libc::proc_pidfdinfo(
+                    pid,
+                    fd.fd,
+                    PROC_PIDFDVNODEINFO,
+                    info.as_mut_ptr().cast(),
+                    size_of::<VnodeFdInfo>() as c_int,
+                )
+            };
+            if size as usize != size_of::<VnodeFdInfo>() {
+                continue;
+            }
+
+            let info = unsafe { info.assume_init() };
+            let stat = info.vnode.vi_stat;
+
+            // The device number gets converted the same way as the standard
+            // library converts `st_dev`, so the two can be compared.
+            add(stat.vst_dev as i32 as u64, stat.vst_ino);
+        }
+    }
+
+    users
+}
+
+/// There’s no way to ask processes what they have open on other platforms.
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+fn scan_processes() -> Users {
+    Users::new()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn names(names: &[&str]) -> BTreeSet<String> {
+        names.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn labels() {
+        assert_eq!(label(&names(&["nvim"])), "in use by nvim");
+        assert_eq!(
+            label(&names(&["nvim", "cargo", "bash"])),
+            "in use by bash, cargo, nvim"
+        );
+        assert_eq!(
+            label(&names(&["nvim", "cargo", "bash", "rustc", "zsh"])),
+            "in use by bash, cargo, nvim and 2 more"
+        );
+    }
+
+    #[test]
+    fn mapped_files() {
+        let maps = "\
+55d0c0a00000-55d0c0a28000 r--p 00000000 fd:01 1835 /usr/bin/my program
+55d0c0a28000-55d0c0b00000 r-xp 00028000 fd:01 1835 /usr/bin/my program
+55d0c2000000-55d0c2021000 rw-p 00000000 00:00 0    [heap]
+7f1e2c000000-7f1e2c200000 r--p 00000000 fd:01 4021 /usr/lib/libc.so.6
+7f1e2d000000-7f1e2d001000 rw-s 00000000 00:05 9001 /tmp/scratch (deleted)
+7ffd1a000000-7ffd1a021000 rw-p 00000000 00:00 0    [stack]
+";
+
+        assert_eq!(
+            mapped_paths(maps),
+            BTreeSet::from(["/usr/bin/my program", "/usr/lib/libc.so.6"])
+        );
+    }
+}
@@ -0,0 +1,709 @@
+//! Saving the files in a listing to be shown again later, for
+//! `--snapshot-out` and `--snapshot-in`, such as to take a listing on a
+//! remote machine and look through it back at home.
+//!
+//! These aren’t the snapshots that `--highlight-new` keeps, which only have
+//! each file’s size and modification time. A saved listing has each file’s
+//! type and permissions, size and blocks, inode and number of links, owner
+//! and group, all four of its times, and where it points to if it’s a
+//! symlink, along with the type and permissions of what it points to.
+//!
+//! A listing that’s been loaded is somewhere files can be read from, the
+//! same way as a computer at the other end of an SFTP connection, so it gets
+//! shown with whichever view options are given, as far as they can be with
+//! only those details.
+//!
+//! The file starts with a line saying what it is, and then has a section
+//! for the files given as arguments, and one for each directory that got
+//! read, each starting with a line of its own. Each file gets a line of
+//! fields separated by spaces, with any text percent-encoded so it can’t
+//! have spaces or line breaks in it, and a `-` for anything not known.
+
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs::{self, DirEntry, Metadata};
+use std::io::{self, ErrorKind};
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use uzers::{Groups, Users, UsersCache};
+
+use crate::fs::metadata::{Fields, Source};
+use crate::fs::walk::{walk, write_atomically};
+
+/// The first line of every saved listing, which gets checked when loading
+/// one, so other files don’t get shown as empty listings.
+const HEADER: &str = "eza-snapshot 3";
+
+/// The characters that get percent-encoded in names and paths.
+const ENCODED: &AsciiSet = &CONTROLS.add(b' ').add(b'%');
+
+/// Files that get listed together: either the ones given as arguments, or
+/// the ones in a directory.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Section {
+    /// The directory the files are in, or `None` for the arguments.
+    pub dir: Option<String>,
+
+    /// The files, in the order they were read.
+    pub entries: Vec<Entry>,
+}
+
+/// A listing that’s been saved, or that’s about to be.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct Listing {
+    pub sections: Vec<Section>,
+}
+
+/// One file in a saved listing.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Entry {
+    /// The file’s name, or its whole path if it was given as an argument.
+    pub name: String,
+    pub fields: Fields,
+
+    /// The names of the file’s owner and group, if they’re known.
+    pub user: Option<String>,
+    pub group: Option<String>,
+
+    /// Where the file points to, if it’s a symlink and it could be read.
+    pub target: Option<String>,
+
+    /// The type and permission bits of what the file points to, if it’s a
+    /// symlink that isn’t broken.
+    pub target_mode: Option<u32>,
+}
+
+impl Listing {
+    /// Reads the given paths, and the directories among them down to the
+    /// given number of levels, or all the way down if there’s no limit.
+    /// Directories starting with a dot only get gone into if dotfiles are
+    /// wanted, though their files get saved either way. Anything that can’t
+    /// be read gets returned with its error, rather than stopping the rest.
+    pub fn take(
+        paths: &[&OsStr],
+        levels: Option<usize>,
+        dotfiles: bool,
+    ) -> (Self, Vec<(PathBuf, io::Error)>) {
+        let mut taker = Taker {
+            users: UsersCache::new(),
+            sections: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let mut arguments = Vec::new();
+        let mut dirs = Vec::new();
+        for path in paths.iter().map(Path::new) {
+            match taker.entry(path.to_string_lossy().into_owned(), path) {
+                Ok(entry) => arguments.push(entry),
+                Err(e) => {
+                    taker.errors.push((path.to_path_buf(), e));
+                    continue;
+                }
+            }
+
+            if levels != Some(0) && fs::metadata(path).is_ok_and(|m| m.is_dir()) {
+                dirs.push(path);
+            }
+        }
+
+        taker.sections.push(Section {
+            dir: None,
+            entries: arguments,
+        });
+
+        for dir in dirs {
+            walk(dir, levels, dotfiles, &mut |path, children| {
+                taker.add_dir(path, children);
+            });
+        }
+
+        let listing = Self {
+            sections: taker.sections,
+        };
+        (listing, taker.errors)
+    }
+
+    /// Writes the listing to the given file. A listing that fails to be
+    /// written never leaves a half-written file, or replaces an older one.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        write_atomically(path, self.to_text().as_bytes())
+    }
+
+    /// Reads a listing from a file that `save` wrote.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Self::parse(&text).map_err(|line| {
+            let message = if line == 1 {
+                String::from("not a listing saved with --snapshot-out")
+            } else {
+                format!("line {line} of the saved listing can't be read")
+            };
+            io::Error::new(ErrorKind::InvalidData, message)
+        })
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = format!("{HEADER}\n");
+        for section in &self.sections {
+            match &section.dir {
+                Some(dir) => text.push_str(&format!("dir {}\n", text_field(Some(dir)))),
+                None => text.push_str("files\n"),
+            }
+
+            for entry in &section.entries {
+                text.push_str(&entry_line(entry));
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// Parses a saved listing, returning the number of the first line that
+    /// isn’t right if there’s one that isn’t.
+    fn parse(text: &str) -> Result<Self, usize> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line));
+        if lines.next().map(|(_, line)| line) != Some(HEADER) {
+            return Err(1);
+        }
+
+        let mut sections: Vec<Section> = Vec::new();
+        for (number, line) in lines {
+            if line == "files" {
+                sections.push(Section {
+                    dir: None,
+                    entries: Vec::new(),
+                });
+            } else if let Some(dir) = line.strip_prefix("dir ") {
+                let dir = parse_text(dir).ok().flatten().ok_or(number)?;
+                sections.push(Section {
+                    dir: Some(dir),
+                    entries: Vec::new(),
+                });
+            } else {
+                let section = sections.last_mut().ok_or(number)?;
+                section
+                    .entries
+                    .push(parse_entry(line).map_err(|BadField| number)?);
+            }
+        }
+
+        Ok(Self { sections })
+    }
+}
+
+/// A listing that’s been loaded, which files can be read from as though it
+/// were the filesystem they were saved from. Only the directories that got
+/// read when it was saved can be read again.
+#[derive(Debug, Default)]
+pub struct Saved {
+    /// The paths that were given as arguments, in order.
+    arguments: Vec<PathBuf>,
+
+    /// Every file in the listing, by its path.
+    files: HashMap<PathBuf, Entry>,
+
+    /// The files in each directory that got read, not including `.` and
+    /// `..`.
+    dirs: HashMap<PathBuf, Vec<PathBuf>>,
+
+    /// The type and permission bits of the files that symlinks point to,
+    /// by the paths they point to.
+    targets: HashMap<PathBuf, u32>,
+
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+impl Saved {
+    /// The paths that were given as arguments when the listing was saved.
+    pub fn arguments(&self) -> &[PathBuf] {
+        &self.arguments
+    }
+
+    fn add(&mut self, path: PathBuf, entry: Entry) {
+        if let (Some(uid), Some(user)) = (entry.fields.uid, &entry.user) {
+            self.users.entry(uid).or_insert_with(|| user.clone());
+        }
+        if let (Some(gid), Some(group)) = (entry.fields.gid, &entry.group) {
+            self.groups.entry(gid).or_insert_with(|| group.clone());
+        }
+
+        // Targets are relative to the directory the link is in, the same
+        // way they get looked up when the link is listed.
+        if let (Some(target), Some(mode)) = (&entry.target, entry.target_mode) {
+            let dir = path.parent().unwrap_or(Path::new(""));
+            self.targets.insert(dir.join(target), mode);
+        }
+
+        // A directory’s `.` is the directory itself, which is already there
+        // if it was listed in its parent or given as an argument.
+        self.files.entry(path).or_insert(entry);
+    }
+
+    fn entry(&self, path: &Path) -> io::Result<&Entry> {
+        self.files.get(path).ok_or_else(not_saved)
+    }
+}
+
+impl From<Listing> for Saved {
+    fn from(listing: Listing) -> Self {
+        let mut saved = Self::default();
+        for section in listing.sections {
+            let Some(dir) = section.dir.map(PathBuf::from) else {
+                for entry in section.entries {
+                    let path = PathBuf::from(&entry.name);
+                    saved.arguments.push(path.clone());
+                    saved.add(path, entry);
+                }
+                continue;
+            };
+
+            let mut children = Vec::new();
+            for entry in section.entries {
+                let path = dir.join(&entry.name);
+                if entry.name != "." && entry.name != ".." {
+                    children.push(path.clone());
+                }
+                saved.add(path, entry);
+            }
+            saved.dirs.insert(dir, children);
+        }
+        saved
+    }
+}
+
+impl Source for Saved {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(PathBuf, Fields)>> {
+        let children = self.dirs.get(path).ok_or_else(not_saved)?;
+        children
+            .iter()
+            .map(|child| Ok((child.clone(), self.entry(child)?.fields.clone())))
+            .collect()
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Fields> {
+        self.entry(path).map(|entry| entry.fields.clone())
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Fields> {
+        match (self.files.get(path), self.targets.get(path)) {
+            (Some(entry), _) if entry.target.is_none() => Ok(entry.fields.clone()),
+            (_, Some(&mode)) => Ok(Fields {
+                mode: Some(mode),
+                ..Fields::default()
+            }),
+            (Some(entry), None) => match entry.target_mode {
+                Some(mode) => Ok(Fields {
+                    mode: Some(mode),
+                    ..Fields::default()
+                }),
+                None => Err(ErrorKind::NotFound.into()),
+            },
+            (None, None) => Err(ErrorKind::NotFound.into()),
+        }
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        let entry = self.entry(path)?;
+        let target = entry.target.as_ref().ok_or(ErrorKind::InvalidInput)?;
+        Ok(PathBuf::from(target))
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        // Where the listing was taken isn’t known, so the paths in it are
+        // as good as it gets.
+        self.entry(path).map(|_| path.to_path_buf())
+    }
+
+    fn user_name(&self, uid: u32) -> Option<String> {
+        self.users.get(&uid).cloned()
+    }
+
+    fn group_name(&self, gid: u32) -> Option<String> {
+        self.groups.get(&gid).cloned()
+    }
+
+    fn current_ids(&self) -> Option<(u32, u32)> {
+        None
+    }
+
+    fn display(&self, path: &Path) -> String {
+        path.display().to_string()
+    }
+}
+
+/// The error for a file or directory that isn’t in the saved listing.
+fn not_saved() -> io::Error {
+    io::Error::new(ErrorKind::NotFound, "not in the saved listing")
+}
+
+/// The state kept while reading the files to save.
+struct Taker {
+    users: UsersCache,
+    sections: Vec<Section>,
+    errors: Vec<(PathBuf, io::Error)>,
+}
+
+impl Taker {
+    /// Adds a section for a directory that’s been read, including `.` and
+    /// `..`. The walk goes into the directories inside it afterwards.
+    fn add_dir(&mut self, path: &Path, children: io::Result<Vec<DirEntry>>) {
+        let children = match children {
+            Ok(children) => children,
+            Err(e) => {
+                self.errors.push((path.to_path_buf(), e));
+                return;
+            }
+        };
+
+        let mut entries = Vec::new();
+        for (name, dot) in [(".", path.to_path_buf()), ("..", path.join(".."))] {
+            if let Ok(entry) = self.entry(String::from(name), &dot) {
+                entries.push(entry);
+            }
+        }
+
+        for child in children {
+            let name = child.file_name().to_string_lossy().into_owned();
+            match self.entry(name, &child.path()) {
+                Ok(entry) => entries.push(entry),
+                Err(e) => self.errors.push((child.path(), e)),
+            }
+        }
+
+        self.sections.push(Section {
+            dir: Some(path.to_string_lossy().into_owned()),
+            entries,
+        });
+    }
+
+    /// Reads the file at the path, without following it if it’s a symlink.
+    fn entry(&self, name: String, path: &Path) -> io::Result<Entry> {
+        let metadata = fs::symlink_metadata(path)?;
+        let (target, target_mode) = if metadata.file_type().is_symlink() {
+            let target = fs::read_link(path)
+                .ok()
+                .map(|target| target.to_string_lossy().into_owned());
+            (target, fs::metadata(path).ok().map(|target| target.mode()))
+        } else {
+            (None, None)
+        };
+
+        let user = self
+            .users
+            .get_user_by_uid(metadata.uid())
+            .map(|user| user.name().to_string_lossy().into_owned());
+        let group = self
+            .users
+            .get_group_by_gid(metadata.gid())
+            .map(|group| group.name().to_string_lossy().into_owned());
+
+        Ok(Entry {
+            name,
+            fields: fields(&metadata),
+            user,
+            group,
+            target,
+            target_mode,
+        })
+    }
+}
+
+/// All the fields of a file’s metadata that get saved.
+fn fields(metadata: &Metadata) -> Fields {
+    Fields {
+        mode: Some(metadata.mode()),
+        size: Some(metadata.size()),
+        uid: Some(metadata.uid()),
+        gid: Some(metadata.gid()),
+        nlink: Some(metadata.nlink()),
+        ino: Some(metadata.ino()),
+        blocks: Some(metadata.blocks()),
+        accessed: metadata.accessed().ok(),
+        modified: metadata.modified().ok(),
+        changed: since_epoch(metadata.ctime(), metadata.ctime_nsec()),
+        created: metadata.created().ok(),
+    }
+}
+
+#[allow(clippy::cast_sign_loss)]
+fn since_epoch(secs: i64, nanos: i64) -> Option<SystemTime> {
+    DateTime::<Utc>::from_timestamp(secs, nanos as u32).map(SystemTime::from)
+}
+
+/// The line for one file: its mode in octal, its size and blocks, its inode
+/// and number of links, its owner and group IDs, its accessed, modified,
+/// changed, and created times, the names of its owner and group, its name,
+/// its symlink target, and the mode of what that points to in octal.
+fn entry_line(entry: &Entry) -> String {
+    let fields = &entry.fields;
+    [
+        fields.mode.map(|mode| format!("{mode:o}")),
+        fields.size.map(|size| size.to_string()),
+        fields.blocks.map(|blocks| blocks.to_string()),
+        fields.ino.map(|inode| inode.to_string()),
+        fields.nlink.map(|links| links.to_string()),
+        fields.uid.map(|uid| uid.to_string()),
+        fields.gid.map(|gid| gid.to_string()),
+        fields.accessed.map(time_field),
+        fields.modified.map(time_field),
+        fields.changed.map(time_field),
+        fields.created.map(time_field),
+    ]
+    .into_iter()
+    .map(|number| number.unwrap_or_else(|| String::from("-")))
+    .chain([
+        text_field(entry.user.as_deref()),
+        text_field(entry.group.as_deref()),
+        text_field(Some(&entry.name)),
+        text_field(entry.target.as_deref()),
+        entry
+            .target_mode
+            .map_or_else(|| String::from("-"), |mode| format!("{mode:o}")),
+    ])
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// A field in a file’s line that can’t be read.
+struct BadField;
+
+fn parse_entry(line: &str) -> Result<Entry, BadField> {
+    let fields = line.split(' ').collect::<Vec<_>>();
+    let [mode, size, blocks, inode, links, user_id, group_id, accessed, modified, changed, created, user, group, name, target, target_mode] =
+        fields[..]
+    else {
+        return Err(BadField);
+    };
+
+    let octal = |mode: &str| u32::from_str_radix(mode, 8).ok();
+    Ok(Entry {
+        name: parse_text(name)?.ok_or(BadField)?,
+        fields: Fields {
+            mode: parse_number(mode, octal)?,
+            size: parse_number(size, |size| size.parse().ok())?,
+            blocks: parse_number(blocks, |blocks| blocks.parse().ok())?,
+            ino: parse_number(inode, |inode| inode.parse().ok())?,
+            nlink: parse_number(links, |links| links.parse().ok())?,
+            uid: parse_number(user_id, |id| id.parse().ok())?,
+            gid: parse_number(group_id, |id| id.parse().ok())?,
+            accessed: parse_number(accessed, parse_time)?,
+            modified: parse_number(modified, parse_time)?,
+            changed: parse_number(changed, parse_time)?,
+            created: parse_number(created, parse_time)?,
+        },
+        user: parse_text(user)?,
+        group: parse_text(group)?,
+        target: parse_text(target)?,
+        target_mode: parse_number(target_mode, octal)?,
+    })
+}
+
+/// A time, as the seconds since the epoch and the nanoseconds after that.
+fn time_field(time: SystemTime) -> String {
+    let time = DateTime::<Utc>::from(time);
+    format!("{}.{:09}", time.timestamp(), time.timestamp_subsec_nanos())
+}
+
+fn parse_time(field: &str) -> Option<SystemTime> {
+    let (secs, nanos) = field.split_once('.')?;
+    DateTime::<Utc>::from_timestamp(secs.parse().ok()?, nanos.parse().ok()?).map(SystemTime::from)
+}
+
+/// Text that can go in a field: percent-encoded, with a `-` for nothing,
+/// and the text `-` itself encoded so it can’t be mistaken for that.
+fn text_field(text: Option<&str>) -> String {
+    match text {
+        None => String::from("-"),
+        Some("-") => String::from("%2D"),
+        Some(text) => utf8_percent_encode(text, ENCODED).to_string(),
+    }
+}
+
+/// The text in a field, which is `None` for a `-`.
+fn parse_text(field: &str) -> Result<Option<String>, BadField> {
+    if field == "-" {
+        return Ok(None);
+    }
+
+    let text = percent_decode_str(field)
+        .decode_utf8()
+        .map_err(|_| BadField)?;
+    Ok(Some(text.into_owned()))
+}
+
+/// The number in a field, which is `None` for a `-`.
+fn parse_number<T>(field: &str, parse: impl Fn(&str) -> Option<T>) -> Result<Option<T>, BadField> {
+    if field == "-" {
+        Ok(None)
+    } else {
+        parse(field).map(Some).ok_or(BadField)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn entry(name: &str, target: Option<&str>) -> Entry {
+        Entry {
+            name: String::from(name),
+            fields: Fields {
+                size: Some(1234),
+                blocks: Some(8),
+                ino: Some(42),
+                nlink: Some(1),
+                uid: Some(1000),
+                mode: Some(if target.is_some() {
+                    0o120_777
+                } else {
+                    0o100_644
+                }),
+                accessed: Some(UNIX_EPOCH + Duration::new(1_700_000_000, 5)),
+                ..Fields::default()
+            },
+            user: Some(String::from("alice")),
+            group: None,
+            target: target.map(String::from),
+            target_mode: target.map(|_| 0o40_755),
+        }
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            entry_line(&entry("two words", None)),
+            "100644 1234 8 42 1 1000 - 1700000000.000000005 - - - alice - two%20words - -"
+        );
+        assert_eq!(
+            entry_line(&entry("-", Some("50%\n"))),
+            "120777 1234 8 42 1 1000 - 1700000000.000000005 - - - alice - %2D 50%25%0A 40755"
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let listing = Listing {
+            sections: vec![
+                Section {
+                    dir: None,
+                    entries: vec![entry("src/main.rs", None)],
+                },
+                Section {
+                    dir: Some(String::from("my files")),
+                    entries: vec![entry("-", Some("→ elsewhere")), entry("a b", None)],
+                },
+                Section {
+                    dir: Some(String::from("empty")),
+                    entries: vec![],
+                },
+            ],
+        };
+
+        assert_eq!(Listing::parse(&listing.to_text()), Ok(listing));
+    }
+
+    #[test]
+    fn not_a_listing() {
+        assert_eq!(Listing::parse(""), Err(1));
+        assert_eq!(Listing::parse("files\n"), Err(1));
+        assert_eq!(Listing::parse("eza-snapshot 2\nfiles\n"), Err(1));
+    }
+
+    #[test]
+    fn bad_lines() {
+        let entry = "100644 1 - - - - - - - - - - - name - -";
+        assert_eq!(Listing::parse(&format!("{HEADER}\n{entry}\n")), Err(2));
+        assert_eq!(
+            Listing::parse(&format!("{HEADER}\nfiles\n{entry} extra\n")),
+            Err(3)
+        );
+        assert_eq!(
+            Listing::parse(&format!(
+                "{HEADER}\nfiles\n9 big - - - - - - - - - - - name - -\n"
+            )),
+            Err(3)
+        );
+        assert_eq!(
+            Listing::parse(&format!(
+                "{HEADER}\nfiles\n100644 1 - - - - - 1700000000 - - - - - name - -\n"
+            )),
+            Err(3)
+        );
+        assert!(Listing::parse(&format!("{HEADER}\nfiles\n{entry}\n")).is_ok());
+    }
+
+    fn saved() -> Saved {
+        Saved::from(Listing {
+            sections: vec![
+                Section {
+                    dir: None,
+                    entries: vec![entry("top", None), entry("link", Some("top"))],
+                },
+                Section {
+                    dir: Some(String::from("top")),
+                    entries: vec![
+                        entry(".", None),
+                        entry("..", None),
+                        entry("up", Some("../top")),
+                        entry("file", None),
+                    ],
+                },
+            ],
+        })
+    }
+
+    #[test]
+    fn reads_like_a_filesystem() {
+        let saved = saved();
+        assert_eq!(
+            saved.arguments(),
+            [PathBuf::from("top"), PathBuf::from("link")]
+        );
+
+        let children = saved.read_dir(Path::new("top")).unwrap();
+        let paths = children
+            .into_iter()
+            .map(|(path, _)| path)
+            .collect::<Vec<_>>();
+        assert_eq!(paths, [PathBuf::from("top/up"), PathBuf::from("top/file")]);
+
+        assert_eq!(
+            saved.read_link(Path::new("top/up")).unwrap(),
+            PathBuf::from("../top")
+        );
+        assert_eq!(saved.user_name(1000), Some(String::from("alice")));
+        assert!(saved.symlink_metadata(Path::new("top/..")).is_ok());
+    }
+
+    #[test]
+    fn follows_links_as_far_as_was_saved() {
+        let saved = saved();
+        assert_eq!(
+            saved.metadata(Path::new("top/../top")).unwrap().mode,
+            Some(0o40_755)
+        );
+        assert_eq!(
+            saved.metadata(Path::new("top/file")).unwrap().size,
+            Some(1234)
+        );
+        assert_eq!(
+            saved.metadata(Path::new("elsewhere")).unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn only_saved_directories_can_be_read() {
+        let error = saved().read_dir(Path::new("top/file")).unwrap_err();
+        assert_eq!(error.to_string(), "not in the saved listing");
+    }
+}
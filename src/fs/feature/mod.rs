@@ -1,5 +1,24 @@
 pub mod xattr;
 
+pub mod acl;
+pub mod audit;
+pub mod gitignore;
+pub mod in_use;
+#[cfg(unix)]
+pub mod listing_snapshot;
+#[cfg(unix)]
+pub mod quota;
+pub mod reflink;
+#[cfg(windows)]
+pub mod reparse;
+#[cfg(unix)]
+pub mod sftp;
+pub mod snapshot;
+pub mod special_dirs;
+pub mod trash;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+pub mod uring;
+
 #[cfg(feature = "git")]
 pub mod git;
 
@@ -8,16 +27,25 @@ pub mod git {
     use std::iter::FromIterator;
     use std::path::{Path, PathBuf};
 
+    use crate::fs::feature::gitignore::{self, IgnoreRules};
     use crate::fs::fields as f;
 
-    pub struct GitCache;
+    /// Without libgit2, the only thing left to ask about is whether files
+    /// are ignored, which gets worked out from the ignore files directly.
+    pub struct GitCache {
+        ignores: IgnoreRules,
+        reporting_ignored: bool,
+    }
 
     impl FromIterator<PathBuf> for GitCache {
         fn from_iter<I>(_iter: I) -> Self
         where
             I: IntoIterator<Item = PathBuf>,
         {
-            Self
+            Self {
+                ignores: IgnoreRules::default(),
+                reporting_ignored: false,
+            }
         }
     }
 
@@ -26,13 +54,62 @@ pub mod git {
             false
         }
 
-        pub fn get(&self, _index: &Path, _prefix_lookup: bool) -> f::Git {
-            unreachable!();
+        pub fn get(&self, index: &Path, _prefix_lookup: bool) -> f::Git {
+            let unstaged = if self.ignores.matching_rule(index).is_some() {
+                f::GitStatus::Ignored
+            } else {
+                f::GitStatus::NotModified
+            };
+
+            f::Git {
+                staged: f::GitStatus::NotModified,
+                unstaged,
+            }
+        }
+
+        pub fn rename(&self, _index: &Path) -> Option<f::GitRename> {
+            None
+        }
+
+        pub fn changed_since(&self, _index: &Path, _prefix_lookup: bool) -> f::ChangedSince {
+            f::ChangedSince::Unknown
+        }
+
+        pub fn compare_with(&mut self, _reference: &str) {}
+
+        pub fn report_ignored_files(&mut self) {
+            self.reporting_ignored = true;
+        }
+
+        pub fn report_ignored(&self, path: &Path) {
+            if self.reporting_ignored {
+                gitignore::report(path, self.ignores.matching_rule(path));
+            }
+        }
+
+        pub fn discover_nested(&self, _dir: &Path) {}
+
+        pub fn ignore_submodules(&mut self, _submodules: f::SubmoduleIgnore) {}
+
+        pub fn query_in_background(&self) {}
+
+        pub fn is_ready(&self, _index: &Path) -> bool {
+            true
+        }
+
+        pub fn has_timed_out(&self, _index: &Path) -> bool {
+            false
+        }
+
+        pub fn is_pending(&self) -> bool {
+            false
         }
+
+        pub fn wait(&self) {}
     }
 
     impl f::SubdirGitRepo {
-        pub fn from_path(_dir: &Path, _status: bool) -> Self {
+        pub fn from_path(_dir: &Path, _status: bool, _behind: bool) -> Self {
             panic!("Tried to get subdir Git status, but Git support is disabled")
         }
     }
@@ -0,0 +1,121 @@
+//! Finding out how much of a filesystem a user is allowed to take up.
+//!
+//! Filesystems with quotas turned on keep count of how much space each user
+//! is using on them, along with the limits the administrator has given
+//! them. On Linux, `quotactl` reads those counts, given the block device the
+//! filesystem was mounted from, which gets found by looking through the
+//! mounts for the one with the same device ID as the file.
+//!
+//! Only the superuser can look up other users’ quotas, so for everyone else,
+//! only the files they own have one shown. Lookups get cached by device and
+//! user, as a directory full of one user’s files would otherwise ask the
+//! same question for each of them.
+
+use crate::fs::fields as f;
+
+/// The quota of the user on the filesystem with the given device ID.
+#[cfg(target_os = "linux")]
+pub fn usage(dev: u64, uid: u32) -> f::Quota {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use once_cell::sync::Lazy;
+
+    static CACHE: Lazy<Mutex<HashMap<(u64, u32), f::Quota>>> =
+        Lazy::new(|| Mutex::new(HashMap::new()));
+
+    if let Some(quota) = CACHE.lock().unwrap().get(&(dev, uid)) {
+        return *quota;
+    }
+
+    let quota = linux::usage(dev, uid);
+    CACHE.lock().unwrap().insert((dev, uid), quota);
+    quota
+}
+
+/// Quotas can only be looked up on Linux.
+#[cfg(not(target_os = "linux"))]
+pub fn usage(_dev: u64, _uid: u32) -> f::Quota {
+    f::Quota::None
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::ffi::CString;
+    use std::os::unix::fs::MetadataExt;
+
+    use log::*;
+
+    use crate::fs::fields as f;
+    use crate::fs::mounts::all_mounts;
+
+    /// Limits are counted in blocks of this many bytes, whatever the
+    /// filesystem’s own block size is.
+    const QUOTA_BLOCK_SIZE: u64 = 1024;
+
+    pub fn usage(dev: u64, uid: u32) -> f::Quota {
+        let Some(device) = device_for(dev) else {
+            return f::Quota::None;
+        };
+
+        // SAFETY: `dqblk` is plain data, for which all zeroes is valid.
+        let mut dqblk: libc::dqblk = unsafe { std::mem::zeroed() };
+        let cmd = libc::QCMD(libc::Q_GETQUOTA, libc::USRQUOTA);
+        let id = libc::c_int::from_ne_bytes(uid.to_ne_bytes());
+
+        // SAFETY: the device name is a valid C string, and `dqblk` is the
+        // structure that `Q_GETQUOTA` fills in.
+        let result = unsafe {
+            libc::quotactl(
+                cmd,
+                device.as_ptr(),
+                id,
+                std::ptr::addr_of_mut!(dqblk).cast(),
+            )
+        };
+
+        if result != 0 {
+            debug!(
+                "No quota for user {} on {:?}: {}",
+                uid,
+                device,
+                std::io::Error::last_os_error()
+            );
+            return f::Quota::None;
+        }
+
+        if dqblk.dqb_valid & libc::QIF_SPACE == 0 {
+            return f::Quota::None;
+        }
+
+        f::Quota::Usage {
+            used: dqblk.dqb_curspace,
+            limit: limit(&dqblk),
+        }
+    }
+
+    /// The limit the user is held to, in bytes: the soft limit if there is
+    /// one, as that’s the one they get warned about going over, or else the
+    /// hard limit. Zero means there’s no limit.
+    fn limit(dqblk: &libc::dqblk) -> Option<u64> {
+        if dqblk.dqb_valid & libc::QIF_BLIMITS == 0 {
+            return None;
+        }
+
+        [dqblk.dqb_bsoftlimit, dqblk.dqb_bhardlimit]
+            .into_iter()
+            .find(|blocks| *blocks != 0)
+            .map(|blocks| blocks.saturating_mul(QUOTA_BLOCK_SIZE))
+    }
+
+    /// The block device that the filesystem with the given device ID was
+    /// mounted from.
+    fn device_for(dev: u64) -> Option<CString> {
+        let mount = all_mounts().iter().find_map(|(dest, mount)| {
+            let metadata = std::fs::metadata(dest).ok()?;
+            (metadata.dev() == dev).then_some(mount)
+        })?;
+
+        CString::new(mount.source.as_bytes()).ok()
+    }
+}
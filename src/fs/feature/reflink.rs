@@ -0,0 +1,161 @@
+//! Finding out whether a file shares its data blocks with another file.
+//!
+//! Copy-on-write filesystems can *clone* a file, or *reflink* it: the copy
+//! gets new metadata, but points at the same blocks on disk as the original,
+//! until one of them is written to. A clone takes up no extra space, but
+//! looks just like a real copy to `stat`, so it has to be asked about some
+//! other way.
+//!
+//! On Linux, the `FIEMAP` ioctl lists a file’s extents, with a flag on each
+//! one that’s shared with another file, which Btrfs, XFS, and bcachefs all
+//! set. On macOS, APFS sets a flag on files that may share blocks with a
+//! clone. Only the first few extents of a file get looked at, so this stays
+//! cheap for files in thousands of pieces, and anything that can’t be asked
+//! is taken not to be a clone.
+
+use std::path::Path;
+
+/// Whether the file at the path shares any data blocks with another file.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub fn shares_blocks(path: &Path) -> bool {
+    use std::fs::File;
+    use std::os::unix::io::AsRawFd;
+
+    /// `_IOWR('f', 11, struct fiemap)`.
+    const FS_IOC_FIEMAP: u32 = 0xC020_660B;
+
+    /// The extent’s blocks are shared with another file.
+    const FIEMAP_EXTENT_SHARED: u32 = 0x2000;
+
+    /// How many extents to ask about.
+    const EXTENT_COUNT: usize = 32;
+
+    #[repr(C)]
+    #[derive(Default, Copy, Clone)]
+    struct Extent {
+        logical: u64,
+        physical: u64,
+        length: u64,
+        reserved64: [u64; 2],
+        flags: u32,
+        reserved: [u32; 3],
+    }
+
+    #[repr(C)]
+    struct ExtentMap {
+        start: u64,
+        length: u64,
+        flags: u32,
+        mapped_extents: u32,
+        extent_count: u32,
+        reserved: u32,
+        extents: [Extent; EXTENT_COUNT],
+    }
+
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+
+    let mut map = ExtentMap {
+        start: 0,
+        length: u64::MAX,
+        flags: 0,
+        mapped_extents: 0,
+        extent_count: EXTENT_COUNT as u32,
+        reserved: 0,
+        extents: [Extent::default(); EXTENT_COUNT],
+    };
+
+    // The request’s type is different between C libraries, hence the cast.
+    #[allow(clippy::cast_lossless, clippy::cast_possible_wrap)]
+    let result = unsafe {
+        libc::ioctl(
+            file.as_raw_fd(),
+            FS_IOC_FIEMAP as _,
+            std::ptr::addr_of_mut!(map),
+        )
+    };
+    if result != 0 {
+        return false;
+    }
+
+    let mapped = (map.mapped_extents as usize).min(EXTENT_COUNT);
+    map.extents[..mapped]
+        .iter()
+        .any(|extent| extent.flags & FIEMAP_EXTENT_SHARED != 0)
+}
+
+/// Whether the file at the path shares any data blocks with another file.
+#[cfg(target_os = "macos")]
+pub fn shares_blocks(path: &Path) -> bool {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    /// The file may share blocks with a clone of it.
+    const EF_MAY_SHARE_BLOCKS: u64 = 0x0000_0001;
+
+    /// What `getattrlist` writes back: its length, which attributes it
+    /// managed to get, then the extended flags themselves.
+    #[repr(C, packed(4))]
+    struct Buffer {
+        length: u32,
+        returned: libc::attribute_set_t,
+        ext_flags: u64,
+    }
+
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return false;
+    };
+
+    let mut request = libc::attrlist {
+        bitmapcount: libc::ATTR_BIT_MAP_COUNT,
+        reserved: 0,
+        commonattr: libc::ATTR_CMN_RETURNED_ATTRS,
+        volattr: 0,
+        dirattr: 0,
+        fileattr: 0,
+        forkattr: libc::ATTR_CMNEXT_EXT_FLAGS,
+    };
+
+    let mut buffer = std::mem::MaybeUninit::<Buffer>::zeroed();
+    let result = unsafe {
+        libc::getattrlist(
+            path.as_ptr(),
+            std::ptr::addr_of_mut!(request).cast(),
+            buffer.as_mut_ptr().cast(),
+            std::mem::size_of::<Buffer>(),
+            libc::FSOPT_NOFOLLOW | libc::FSOPT_ATTR_CMN_EXTENDED,
+        )
+    };
+    if result != 0 {
+        return false;
+    }
+
+    let buffer = unsafe { buffer.assume_init() };
+    let returned = buffer.returned.forkattr;
+    let ext_flags = buffer.ext_flags;
+    returned & libc::ATTR_CMNEXT_EXT_FLAGS != 0 && ext_flags & EF_MAY_SHARE_BLOCKS != 0
+}
+
+/// There’s no way to ask about clones on other platforms.
+#[cfg(not(any(target_os = "linux", target_os = "android", target_os = "macos")))]
+pub fn shares_blocks(_path: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fresh_file_is_not_a_clone() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), b"not shared with anything").unwrap();
+        assert!(!shares_blocks(file.path()));
+    }
+
+    #[test]
+    fn missing_file_is_not_a_clone() {
+        assert!(!shares_blocks(Path::new("/this/file/does/not/exist")));
+    }
+}
@@ -0,0 +1,236 @@
+//! Telling the kinds of reparse point apart on Windows.
+//!
+//! NTFS marks files that should be handled by something other than their
+//! own contents with a reparse point, tagged with what kind it is. Symbolic
+//! links and directory junctions both lead to other paths, and the standard
+//! library treats them both as symlinks, but app execution aliases — the
+//! stubs in `WindowsApps` that apps from the Store get run through — are a
+//! kind of their own, which it sees as empty regular files. The tag, and the
+//! data after it, get read with `FSCTL_GET_REPARSE_POINT`.
+
+use std::ffi::OsString;
+use std::io;
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use log::*;
+
+use windows_sys::Win32::Foundation::{CloseHandle, INVALID_HANDLE_VALUE};
+use windows_sys::Win32::Storage::FileSystem::{
+    CreateFileW, FILE_FLAG_BACKUP_SEMANTICS, FILE_FLAG_OPEN_REPARSE_POINT, FILE_SHARE_DELETE,
+    FILE_SHARE_READ, FILE_SHARE_WRITE, OPEN_EXISTING,
+};
+use windows_sys::Win32::System::IO::DeviceIoControl;
+
+use crate::fs::fields as f;
+
+const IO_REPARSE_TAG_MOUNT_POINT: u32 = 0xA000_0003;
+const IO_REPARSE_TAG_SYMLINK: u32 = 0xA000_000C;
+const IO_REPARSE_TAG_APPEXECLINK: u32 = 0x8000_001B;
+
+const FSCTL_GET_REPARSE_POINT: u32 = 0x0009_00A8;
+const MAXIMUM_REPARSE_DATA_BUFFER_SIZE: usize = 16 * 1024;
+
+/// The version of the app execution alias data that this knows how to read.
+const APPEXECLINK_VERSION: u32 = 3;
+
+/// What kind of reparse point the file at the given path is. This should
+/// only be asked of files whose attributes say they are one.
+pub fn kind(path: &Path) -> Option<f::ReparseKind> {
+    match read(path) {
+        Ok(data) => tag(&data).map(kind_of),
+        Err(e) => {
+            debug!("Error reading reparse point {:?}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// The path of the program that the app execution alias at the given path
+/// runs.
+pub fn app_exec_target(path: &Path) -> io::Result<PathBuf> {
+    let data = read(path)?;
+    app_exec_target_of(&data).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "app execution alias has no target",
+        )
+    })
+}
+
+/// Reads the reparse point’s tag and data.
+fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let wide_path = path
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<_>>();
+
+    // SAFETY: the path is null-terminated. Opening the reparse point itself
+    // needs no access rights, and doesn’t follow it.
+    let handle = unsafe {
+        CreateFileW(
+            wide_path.as_ptr(),
+            0,
+            FILE_SHARE_READ | FILE_SHARE_WRITE | FILE_SHARE_DELETE,
+            ptr::null(),
+            OPEN_EXISTING,
+            FILE_FLAG_OPEN_REPARSE_POINT | FILE_FLAG_BACKUP_SEMANTICS,
+            0,
+        )
+    };
+
+    if handle == INVALID_HANDLE_VALUE {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut data = vec![0_u8; MAXIMUM_REPARSE_DATA_BUFFER_SIZE];
+    let mut returned = 0_u32;
+
+    // SAFETY: the handle is open, and the buffer is as long as it says.
+    let succeeded = unsafe {
+        DeviceIoControl(
+            handle,
+            FSCTL_GET_REPARSE_POINT,
+            ptr::null(),
+            0,
+            data.as_mut_ptr().cast(),
+            data.len() as u32,
+            &mut returned,
+            ptr::null_mut(),
+        )
+    } != 0;
+    let error = io::Error::last_os_error();
+
+    // SAFETY: the handle was opened above, and isn’t used after this.
+    unsafe { CloseHandle(handle) };
+
+    if !succeeded {
+        return Err(error);
+    }
+
+    data.truncate(returned as usize);
+    Ok(data)
+}
+
+/// The tag at the start of the reparse data.
+fn tag(data: &[u8]) -> Option<u32> {
+    Some(u32::from_le_bytes(data.get(..4)?.try_into().ok()?))
+}
+
+fn kind_of(tag: u32) -> f::ReparseKind {
+    match tag {
+        IO_REPARSE_TAG_SYMLINK => f::ReparseKind::Symlink,
+        IO_REPARSE_TAG_MOUNT_POINT => f::ReparseKind::Junction,
+        IO_REPARSE_TAG_APPEXECLINK => f::ReparseKind::AppExecLink,
+        _ => f::ReparseKind::Other,
+    }
+}
+
+/// An app execution alias’s data is a version number followed by a list of
+/// null-terminated UTF-16 strings: the ID of the app’s package, the ID of
+/// the app itself, and then the path of the program that gets run.
+fn app_exec_target_of(data: &[u8]) -> Option<PathBuf> {
+    if tag(data)? != IO_REPARSE_TAG_APPEXECLINK {
+        return None;
+    }
+
+    // The tag is followed by the length of the data and two reserved bytes.
+    let body = data.get(8..)?;
+    let version = u32::from_le_bytes(body.get(..4)?.try_into().ok()?);
+    if version != APPEXECLINK_VERSION {
+        return None;
+    }
+
+    let wide = body[4..]
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .collect::<Vec<_>>();
+
+    let target = wide.split(|&c| c == 0).nth(2)?;
+    (!target.is_empty()).then(|| PathBuf::from(OsString::from_wide(target)))
+}
+
+/// Turns a path in its verbatim `\\?\` form, which is how junctions store
+/// their targets, back into the form it would usually be written in.
+pub fn strip_verbatim(path: PathBuf) -> PathBuf {
+    match path.to_str() {
+        Some(s) if s.starts_with(r"\\?\UNC\") => PathBuf::from(format!(r"\\{}", &s[8..])),
+        Some(s) if s.starts_with(r"\\?\") => PathBuf::from(&s[4..]),
+        _ => path,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn app_exec_data(strings: &[&str]) -> Vec<u8> {
+        let mut body = APPEXECLINK_VERSION.to_le_bytes().to_vec();
+        for string in strings {
+            for unit in string.encode_utf16().chain(Some(0)) {
+                body.extend(unit.to_le_bytes());
+            }
+        }
+
+        let mut data = IO_REPARSE_TAG_APPEXECLINK.to_le_bytes().to_vec();
+        data.extend((body.len() as u16).to_le_bytes());
+        data.extend([0, 0]);
+        data.extend(body);
+        data
+    }
+
+    #[test]
+    fn kinds() {
+        assert_eq!(kind_of(IO_REPARSE_TAG_SYMLINK), f::ReparseKind::Symlink);
+        assert_eq!(
+            kind_of(IO_REPARSE_TAG_MOUNT_POINT),
+            f::ReparseKind::Junction
+        );
+        assert_eq!(
+            kind_of(IO_REPARSE_TAG_APPEXECLINK),
+            f::ReparseKind::AppExecLink
+        );
+        assert_eq!(kind_of(0x9000_001A), f::ReparseKind::Other);
+    }
+
+    #[test]
+    fn app_exec_target() {
+        let data = app_exec_data(&[
+            "Microsoft.WindowsTerminal_8wekyb3d8bbwe",
+            "Microsoft.WindowsTerminal_8wekyb3d8bbwe!App",
+            r"C:\Program Files\WindowsApps\Terminal\wt.exe",
+            "0",
+        ]);
+
+        assert_eq!(
+            app_exec_target_of(&data),
+            Some(PathBuf::from(
+                r"C:\Program Files\WindowsApps\Terminal\wt.exe"
+            ))
+        );
+    }
+
+    #[test]
+    fn app_exec_truncated() {
+        let data = app_exec_data(&["Package", "App"]);
+        assert_eq!(app_exec_target_of(&data), None);
+    }
+
+    #[test]
+    fn verbatim() {
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"\\?\C:\Users\me")),
+            PathBuf::from(r"C:\Users\me")
+        );
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"\\?\UNC\server\share")),
+            PathBuf::from(r"\\server\share")
+        );
+        assert_eq!(
+            strip_verbatim(PathBuf::from(r"C:\Users")),
+            PathBuf::from(r"C:\Users")
+        );
+    }
+}
@@ -0,0 +1,878 @@
+//! Reading directories on another computer over SFTP, for the `sftp://`
+//! paths that `--sftp` lets through.
+//!
+//! Rather than linking an SSH library, this runs the system’s own `ssh` with
+//! its `sftp` subsystem, the same way the `sftp` command does, and speaks
+//! version 3 of the protocol over its input and output. That way, whatever’s
+//! set up in `~/.ssh/config`, from keys in an agent to jump hosts, works the
+//! same as it does everywhere else, and a password prompt goes to the
+//! terminal, which `ssh` reads from itself.
+//!
+//! Version 3 is the one OpenSSH’s server speaks, and it only has each file’s
+//! size, owner, permissions, and access and modification times. The link
+//! counts and the owners’ names get picked out of the `ls -l` style lines
+//! that servers send along with each directory entry, and everything else
+//! is unknown.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use log::*;
+
+use crate::fs::metadata::{Fields, Source};
+
+/// The version of the protocol that gets asked for.
+const VERSION: u32 = 3;
+
+const SSH_FXP_INIT: u8 = 1;
+const SSH_FXP_VERSION: u8 = 2;
+const SSH_FXP_CLOSE: u8 = 4;
+const SSH_FXP_LSTAT: u8 = 7;
+const SSH_FXP_OPENDIR: u8 = 11;
+const SSH_FXP_READDIR: u8 = 12;
+const SSH_FXP_REALPATH: u8 = 16;
+const SSH_FXP_STAT: u8 = 17;
+const SSH_FXP_READLINK: u8 = 19;
+const SSH_FXP_STATUS: u8 = 101;
+const SSH_FXP_HANDLE: u8 = 102;
+const SSH_FXP_NAME: u8 = 104;
+const SSH_FXP_ATTRS: u8 = 105;
+
+const SSH_FILEXFER_ATTR_SIZE: u32 = 0x1;
+const SSH_FILEXFER_ATTR_UIDGID: u32 = 0x2;
+const SSH_FILEXFER_ATTR_PERMISSIONS: u32 = 0x4;
+const SSH_FILEXFER_ATTR_ACMODTIME: u32 = 0x8;
+const SSH_FILEXFER_ATTR_EXTENDED: u32 = 0x8000_0000;
+
+const SSH_FX_OK: u32 = 0;
+const SSH_FX_EOF: u32 = 1;
+const SSH_FX_NO_SUCH_FILE: u32 = 2;
+const SSH_FX_PERMISSION_DENIED: u32 = 3;
+const SSH_FX_OP_UNSUPPORTED: u32 = 8;
+
+/// The longest message that gets read. Servers keep theirs well under this,
+/// so anything longer means what’s coming back isn’t SFTP at all.
+const MAX_MESSAGE: usize = 256 * 1024;
+
+/// Whether the argument is an `sftp://` URL, rather than a local path.
+pub fn is_url(arg: &OsStr) -> bool {
+    arg.as_bytes().starts_with(b"sftp://")
+}
+
+/// Where an `sftp://` URL leads: a file on a host, which can be logged in to
+/// as a particular user or on a particular port.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Location {
+    pub user: Option<String>,
+    pub host: String,
+    pub port: Option<u16>,
+
+    /// The path on the host. Relative paths, from URLs such as
+    /// `sftp://host/~/notes`, start from the home directory.
+    pub path: PathBuf,
+}
+
+impl Location {
+    /// Parses a URL of the form `sftp://[user@]host[:port]/path`, where the
+    /// path can start with `/~` to be in the home directory.
+    pub fn parse(url: &OsStr) -> io::Result<Self> {
+        let invalid = || io::Error::new(io::ErrorKind::InvalidInput, "not a valid sftp:// URL");
+
+        let rest = url
+            .as_bytes()
+            .strip_prefix(b"sftp://")
+            .ok_or_else(invalid)?;
+        let (authority, path) = match rest.iter().position(|&b| b == b'/') {
+            Some(slash) => rest.split_at(slash),
+            None => (rest, &b""[..]),
+        };
+        let authority = std::str::from_utf8(authority).map_err(|_| invalid())?;
+
+        let (user, host_port) = match authority.rsplit_once('@') {
+            Some((user, host_port)) => {
+                let user = percent_decode(user.as_bytes()).ok_or_else(invalid)?;
+                (
+                    Some(String::from_utf8(user).map_err(|_| invalid())?),
+                    host_port,
+                )
+            }
+            None => (None, authority),
+        };
+
+        let (host, port) = match host_port.strip_prefix('[') {
+            Some(bracketed) => match bracketed.split_once(']').ok_or_else(invalid)? {
+                (host, "") => (host, None),
+                (host, after) => (host, Some(after.strip_prefix(':').ok_or_else(invalid)?)),
+            },
+            None => match host_port.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (host_port, None),
+            },
+        };
+        let port = port.map(str::parse).transpose().map_err(|_| invalid())?;
+
+        // Anything starting with a dash would be taken as an option by `ssh`.
+        let is_option = |part: &str| part.is_empty() || part.starts_with('-');
+        if is_option(host) || user.as_deref().is_some_and(is_option) {
+            return Err(invalid());
+        }
+
+        let path = percent_decode(path).ok_or_else(invalid)?;
+        let path = match path.strip_prefix(b"/~") {
+            Some(in_home) if in_home.is_empty() || in_home.starts_with(b"/") => {
+                let in_home = OsStr::from_bytes(&in_home[1.min(in_home.len())..]);
+                Path::new(".").join(in_home)
+            }
+            _ if path.is_empty() => PathBuf::from("."),
+            _ => PathBuf::from(OsString::from_vec(path)),
+        };
+
+        Ok(Self {
+            user,
+            host: host.into(),
+            port,
+            path,
+        })
+    }
+
+    /// The start of the URLs of the files on the host, such as
+    /// `sftp://user@host:2222`, which is the same for every one of them.
+    pub fn prefix(&self) -> String {
+        let mut prefix = String::from("sftp://");
+        if let Some(user) = &self.user {
+            prefix.push_str(user);
+            prefix.push('@');
+        }
+        if self.host.contains(':') {
+            prefix.push_str(&format!("[{}]", self.host));
+        } else {
+            prefix.push_str(&self.host);
+        }
+        if let Some(port) = self.port {
+            prefix.push_str(&format!(":{port}"));
+        }
+        prefix
+    }
+}
+
+/// Decodes the `%XX` escapes in part of a URL, or returns `None` if there’s
+/// one that isn’t followed by two hex digits.
+fn percent_decode(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+
+    while let Some((&byte, after)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(after.get(..2)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &after[2..];
+        } else {
+            decoded.push(byte);
+            rest = after;
+        }
+    }
+
+    Some(decoded)
+}
+
+/// A connection to the SFTP server on a host, which its files get read
+/// through.
+pub struct Session {
+    /// The start of the URLs that paths get shown with.
+    prefix: String,
+
+    /// The streams to and from the server, which one request at a time gets
+    /// sent down.
+    channel: Mutex<Channel>,
+
+    /// The names of users and groups, as seen in the long names of the files
+    /// that have been listed so far.
+    names: Mutex<Names>,
+
+    /// The IDs that own the home directory, which are probably the ones
+    /// being logged in as.
+    current_ids: Option<(u32, u32)>,
+
+    /// The `ssh` process, if the session is over one.
+    child: Option<Child>,
+}
+
+struct Channel {
+    reader: Box<dyn Read + Send>,
+    writer: Box<dyn Write + Send>,
+    next_id: u32,
+}
+
+#[derive(Default)]
+struct Names {
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+}
+
+/// A reply to a request, without the ID it was sent back with.
+struct Reply {
+    kind: u8,
+    body: Vec<u8>,
+}
+
+impl Session {
+    /// Runs `ssh` to connect to the location’s host, and starts talking SFTP
+    /// to it.
+    pub fn connect(location: &Location) -> io::Result<Self> {
+        let mut command = Command::new("ssh");
+        if let Some(port) = location.port {
+            command.arg("-p").arg(port.to_string());
+        }
+        let destination = match &location.user {
+            Some(user) => format!("{user}@{}", location.host),
+            None => location.host.clone(),
+        };
+        command
+            .args(["-s", "--", &destination, "sftp"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit());
+
+        debug!("Running {command:?}");
+        let mut child = command
+            .spawn()
+            .map_err(|e| io::Error::new(e.kind(), format!("couldn’t run ssh: {e}")))?;
+
+        let reader = child.stdout.take().expect("piped stdout");
+        let writer = child.stdin.take().expect("piped stdin");
+        Self::start(
+            location.prefix(),
+            Box::new(BufReader::new(reader)),
+            Box::new(BufWriter::new(writer)),
+            Some(child),
+        )
+    }
+
+    /// Says hello to the server at the other end of the streams, and makes
+    /// sure it speaks a version of the protocol that can be understood.
+    fn start(
+        prefix: String,
+        reader: Box<dyn Read + Send>,
+        writer: Box<dyn Write + Send>,
+        child: Option<Child>,
+    ) -> io::Result<Self> {
+        let channel = Channel {
+            reader,
+            writer,
+            next_id: 0,
+        };
+        let mut session = Self {
+            prefix,
+            channel: Mutex::new(channel),
+            names: Mutex::default(),
+            current_ids: None,
+            child,
+        };
+
+        let channel = session.channel.get_mut().unwrap();
+        channel.send(SSH_FXP_INIT, &VERSION.to_be_bytes())?;
+        let reply = channel.receive()?;
+        if reply.kind != SSH_FXP_VERSION {
+            return Err(invalid_data("the server didn’t start with its version"));
+        }
+        let version = Cursor(&reply.body).u32()?;
+        if version < VERSION {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!("the server only speaks version {version} of SFTP"),
+            ));
+        }
+
+        let home = session.symlink_metadata(Path::new("."))?;
+        session.current_ids = home.uid.zip(home.gid);
+        Ok(session)
+    }
+
+    /// Sends a request with the arguments, and waits for its reply.
+    fn request(&self, kind: u8, args: &[u8]) -> io::Result<Reply> {
+        let mut channel = self.channel.lock().unwrap();
+
+        let id = channel.next_id;
+        channel.next_id = id.wrapping_add(1);
+
+        let mut message = id.to_be_bytes().to_vec();
+        message.extend_from_slice(args);
+        channel.send(kind, &message)?;
+
+        let mut reply = channel.receive()?;
+        if reply.body.len() < 4 || reply.body[..4] != id.to_be_bytes() {
+            return Err(invalid_data("the server replied to the wrong request"));
+        }
+        reply.body.drain(..4);
+        Ok(reply)
+    }
+
+    /// Asks for the attributes of the file at the path.
+    fn attributes(&self, kind: u8, path: &Path) -> io::Result<Fields> {
+        let reply = self.request(kind, &path_arg(path))?;
+        match reply.kind {
+            SSH_FXP_ATTRS => attributes(&mut Cursor(&reply.body)),
+            _ => Err(reply.into_error()),
+        }
+    }
+
+    /// Asks for the path that the server gives back for the one sent.
+    fn path(&self, kind: u8, path: &Path) -> io::Result<PathBuf> {
+        let reply = self.request(kind, &path_arg(path))?;
+        if reply.kind != SSH_FXP_NAME {
+            return Err(reply.into_error());
+        }
+
+        let mut body = Cursor(&reply.body);
+        if body.u32()? == 0 {
+            return Err(invalid_data("the server sent back no path"));
+        }
+        Ok(PathBuf::from(OsStr::from_bytes(body.bytes()?)))
+    }
+
+    /// Picks the link count and the owners’ names out of a long name.
+    fn read_long_name(&self, long_name: &[u8], fields: &mut Fields) {
+        let long_name = String::from_utf8_lossy(long_name);
+        let mut columns = long_name.split_whitespace();
+        let (Some(permissions), Some(links), Some(user), Some(group)) =
+            (columns.next(), columns.next(), columns.next(), columns.next())
+        else {
+            return;
+        };
+        if permissions.len() < 10 {
+            return;
+        }
+
+        fields.nlink = links.parse().ok();
+
+        // Servers that don’t know a name use the number instead.
+        let mut names = self.names.lock().unwrap();
+        if let Some(uid) = fields.uid.filter(|uid| user != uid.to_string()) {
+            names.users.entry(uid).or_insert_with(|| user.into());
+        }
+        if let Some(gid) = fields.gid.filter(|gid| group != gid.to_string()) {
+            names.groups.entry(gid).or_insert_with(|| group.into());
+        }
+    }
+}
+
+impl Source for Session {
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(PathBuf, Fields)>> {
+        let reply = self.request(SSH_FXP_OPENDIR, &path_arg(path))?;
+        if reply.kind != SSH_FXP_HANDLE {
+            return Err(reply.into_error());
+        }
+        let mut handle = Vec::new();
+        put_bytes(&mut handle, Cursor(&reply.body).bytes()?);
+
+        let mut files = Vec::new();
+        let result = loop {
+            let reply = match self.request(SSH_FXP_READDIR, &handle) {
+                Ok(reply) => reply,
+                Err(e) => break Err(e),
+            };
+            match reply.kind {
+                SSH_FXP_NAME => {
+                    if let Err(e) = self.read_names(&reply.body, path, &mut files) {
+                        break Err(e);
+                    }
+                }
+                SSH_FXP_STATUS if Cursor(&reply.body).u32().ok() == Some(SSH_FX_EOF) => {
+                    break Ok(files);
+                }
+                _ => break Err(reply.into_error()),
+            }
+        };
+
+        if let Err(e) = self.request(SSH_FXP_CLOSE, &handle) {
+            debug!("Error closing {path:?}: {e}");
+        }
+        result
+    }
+
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Fields> {
+        self.attributes(SSH_FXP_LSTAT, path)
+    }
+
+    fn metadata(&self, path: &Path) -> io::Result<Fields> {
+        self.attributes(SSH_FXP_STAT, path)
+    }
+
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf> {
+        self.path(SSH_FXP_READLINK, path)
+    }
+
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        // Servers don’t all check that the file exists.
+        self.symlink_metadata(path)?;
+        self.path(SSH_FXP_REALPATH, path)
+    }
+
+    fn user_name(&self, uid: u32) -> Option<String> {
+        self.names.lock().unwrap().users.get(&uid).cloned()
+    }
+
+    fn group_name(&self, gid: u32) -> Option<String> {
+        self.names.lock().unwrap().groups.get(&gid).cloned()
+    }
+
+    fn current_ids(&self) -> Option<(u32, u32)> {
+        self.current_ids
+    }
+
+    fn display(&self, path: &Path) -> String {
+        if path.is_absolute() {
+            return format!("{}{}", self.prefix, path.display());
+        }
+
+        let in_home = path.strip_prefix(".").unwrap_or(path);
+        if in_home.as_os_str().is_empty() {
+            format!("{}/~", self.prefix)
+        } else {
+            format!("{}/~/{}", self.prefix, in_home.display())
+        }
+    }
+}
+
+impl Session {
+    /// Adds the files in a reply to a `READDIR` request, other than `.` and
+    /// `..`, to the ones read so far.
+    fn read_names(
+        &self,
+        body: &[u8],
+        dir: &Path,
+        files: &mut Vec<(PathBuf, Fields)>,
+    ) -> io::Result<()> {
+        let mut body = Cursor(body);
+        for _ in 0..body.u32()? {
+            let name = body.bytes()?;
+            let long_name = body.bytes()?;
+            let mut fields = attributes(&mut body)?;
+
+            if name == b"." || name == b".." {
+                continue;
+            }
+            self.read_long_name(long_name, &mut fields);
+            files.push((dir.join(OsStr::from_bytes(name)), fields));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Session")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // Closing the server’s input is what tells it to finish.
+        if let Ok(channel) = self.channel.get_mut() {
+            channel.writer = Box::new(io::sink());
+        }
+        if let Some(child) = &mut self.child {
+            if let Err(e) = child.wait() {
+                debug!("Error waiting for ssh: {e}");
+            }
+        }
+    }
+}
+
+impl Channel {
+    fn send(&mut self, kind: u8, body: &[u8]) -> io::Result<()> {
+        let length = u32::try_from(body.len() + 1).map_err(|_| invalid_data("message too long"))?;
+        self.writer.write_all(&length.to_be_bytes())?;
+        self.writer.write_all(&[kind])?;
+        self.writer.write_all(body)?;
+        self.writer.flush()
+    }
+
+    fn receive(&mut self) -> io::Result<Reply> {
+        let mut length = [0; 4];
+        self.reader
+            .read_exact(&mut length)
+            .map_err(|e| match e.kind() {
+                io::ErrorKind::UnexpectedEof => io::Error::new(
+                    io::ErrorKind::ConnectionAborted,
+                    "the connection was closed",
+                ),
+                _ => e,
+            })?;
+
+        let length = u32::from_be_bytes(length) as usize;
+        if length == 0 || length > MAX_MESSAGE {
+            return Err(invalid_data(
+                "the server sent something that isn’t SFTP (does the shell print anything at login?)",
+            ));
+        }
+
+        let mut message = vec![0; length];
+        self.reader.read_exact(&mut message)?;
+        let body = message.split_off(1);
+        Ok(Reply {
+            kind: message[0],
+            body,
+        })
+    }
+}
+
+impl Reply {
+    /// The error that a reply stands for, when it isn’t the one that was
+    /// expected.
+    fn into_error(self) -> io::Error {
+        if self.kind != SSH_FXP_STATUS {
+            return invalid_data("the server sent an unexpected reply");
+        }
+
+        let mut body = Cursor(&self.body);
+        let Ok(code) = body.u32() else {
+            return invalid_data("the server sent an unexpected reply");
+        };
+        let message = body
+            .bytes()
+            .map(|message| String::from_utf8_lossy(message).into_owned())
+            .unwrap_or_default();
+
+        let kind = match code {
+            SSH_FX_NO_SUCH_FILE => io::ErrorKind::NotFound,
+            SSH_FX_PERMISSION_DENIED => io::ErrorKind::PermissionDenied,
+            SSH_FX_OP_UNSUPPORTED => io::ErrorKind::Unsupported,
+            SSH_FX_OK | SSH_FX_EOF => return invalid_data("the server sent an unexpected reply"),
+            _ => io::ErrorKind::Other,
+        };
+
+        if message.is_empty() {
+            kind.into()
+        } else {
+            io::Error::new(kind, message)
+        }
+    }
+}
+
+/// Reads the parts of a message one after another.
+struct Cursor<'a>(&'a [u8]);
+
+impl<'a> Cursor<'a> {
+    fn take(&mut self, count: usize) -> io::Result<&'a [u8]> {
+        if self.0.len() < count {
+            return Err(invalid_data(
+                "the server sent a message that ended too soon",
+            ));
+        }
+        let (taken, rest) = self.0.split_at(count);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> io::Result<u64> {
+        let bytes = self.take(8)?;
+        Ok(u64::from_be_bytes(bytes.try_into().unwrap()))
+    }
+
+    fn bytes(&mut self) -> io::Result<&'a [u8]> {
+        let length = self.u32()? as usize;
+        self.take(length)
+    }
+}
+
+/// Reads a set of file attributes, which only has the ones its flags say.
+fn attributes(body: &mut Cursor<'_>) -> io::Result<Fields> {
+    let flags = body.u32()?;
+    let mut fields = Fields::default();
+
+    if flags & SSH_FILEXFER_ATTR_SIZE != 0 {
+        fields.size = Some(body.u64()?);
+    }
+    if flags & SSH_FILEXFER_ATTR_UIDGID != 0 {
+        fields.uid = Some(body.u32()?);
+        fields.gid = Some(body.u32()?);
+    }
+    if flags & SSH_FILEXFER_ATTR_PERMISSIONS != 0 {
+        fields.mode = Some(body.u32()?);
+    }
+    if flags & SSH_FILEXFER_ATTR_ACMODTIME != 0 {
+        fields.accessed = Some(time(body.u32()?));
+        fields.modified = Some(time(body.u32()?));
+    }
+    if flags & SSH_FILEXFER_ATTR_EXTENDED != 0 {
+        for _ in 0..body.u32()? {
+            body.bytes()?;
+            body.bytes()?;
+        }
+    }
+
+    Ok(fields)
+}
+
+fn time(secs: u32) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(secs.into())
+}
+
+/// A path as an argument to a request. The server starts relative paths
+/// from the home directory, but not all of them take an empty one to mean
+/// the home directory itself.
+fn path_arg(path: &Path) -> Vec<u8> {
+    let bytes = match path.as_os_str().as_bytes() {
+        b"" => b".",
+        bytes => bytes,
+    };
+
+    let mut arg = Vec::new();
+    put_bytes(&mut arg, bytes);
+    arg
+}
+
+fn put_bytes(message: &mut Vec<u8>, bytes: &[u8]) {
+    message.extend_from_slice(&(bytes.len() as u32).to_be_bytes());
+    message.extend_from_slice(bytes);
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::os::unix::net::UnixStream;
+    use std::thread;
+
+    fn location(url: &str) -> io::Result<Location> {
+        Location::parse(OsStr::new(url))
+    }
+
+    #[test]
+    fn absolute_path() {
+        let location = location("sftp://example.com/var/log").unwrap();
+        assert_eq!(location.user, None);
+        assert_eq!(location.host, "example.com");
+        assert_eq!(location.port, None);
+        assert_eq!(location.path, Path::new("/var/log"));
+        assert_eq!(location.prefix(), "sftp://example.com");
+    }
+
+    #[test]
+    fn user_and_port() {
+        let location = location("sftp://ben%40work@example.com:2222/srv").unwrap();
+        assert_eq!(location.user.as_deref(), Some("ben@work"));
+        assert_eq!(location.port, Some(2222));
+        assert_eq!(location.prefix(), "sftp://ben@work@example.com:2222");
+    }
+
+    #[test]
+    fn ipv6_host() {
+        let location = location("sftp://[::1]:22/").unwrap();
+        assert_eq!(location.host, "::1");
+        assert_eq!(location.port, Some(22));
+        assert_eq!(location.path, Path::new("/"));
+        assert_eq!(location.prefix(), "sftp://[::1]:22");
+    }
+
+    #[test]
+    fn home_paths() {
+        assert_eq!(location("sftp://host").unwrap().path, Path::new("."));
+        assert_eq!(location("sftp://host/~").unwrap().path, Path::new("."));
+        assert_eq!(location("sftp://host/~/").unwrap().path, Path::new("."));
+        assert_eq!(
+            location("sftp://host/~/a%20b").unwrap().path,
+            Path::new("./a b")
+        );
+        assert_eq!(location("sftp://host/~a").unwrap().path, Path::new("/~a"));
+    }
+
+    #[test]
+    fn invalid_urls() {
+        assert!(location("sftp:///path").is_err());
+        assert!(location("sftp://-oProxyCommand=x/path").is_err());
+        assert!(location("sftp://-l@host/path").is_err());
+        assert!(location("sftp://host:port/path").is_err());
+        assert!(location("sftp://[::1/path").is_err());
+        assert!(location("sftp://host/%zz").is_err());
+    }
+
+    /// A file on the pretend server, as its path, its long name, and its
+    /// attributes.
+    type Entry = (&'static str, &'static str, u32, u64);
+
+    const UID: u32 = 1000;
+
+    #[rustfmt::skip]
+    const FILES: &[Entry] = &[
+        (".",              "",                                               0o040_755, 4096),
+        ("/srv",           "",                                               0o040_755, 4096),
+        ("/srv/notes.txt", "-rw-r--r--    1 ben      staff      12 Jan  1 notes.txt", 0o100_644, 12),
+        ("/srv/latest",    "lrwxrwxrwx    2 1000     1000        9 Jan  1 latest",    0o120_777, 9),
+    ];
+
+    /// Answers requests the way a real server would, from the files above,
+    /// until the other end closes.
+    fn serve(mut stream: UnixStream) {
+        let read = |stream: &mut UnixStream| -> Option<(u8, Vec<u8>)> {
+            let mut length = [0; 4];
+            stream.read_exact(&mut length).ok()?;
+            let mut message = vec![0; u32::from_be_bytes(length) as usize];
+            stream.read_exact(&mut message).ok()?;
+            let body = message.split_off(1);
+            Some((message[0], body))
+        };
+        let write = |stream: &mut UnixStream, kind: u8, body: &[u8]| {
+            stream
+                .write_all(&(body.len() as u32 + 1).to_be_bytes())
+                .unwrap();
+            stream.write_all(&[kind]).unwrap();
+            stream.write_all(body).unwrap();
+        };
+        let attrs = |reply: &mut Vec<u8>, mode: u32, size: u64| {
+            let flags = SSH_FILEXFER_ATTR_SIZE
+                | SSH_FILEXFER_ATTR_UIDGID
+                | SSH_FILEXFER_ATTR_PERMISSIONS
+                | SSH_FILEXFER_ATTR_ACMODTIME;
+            reply.extend_from_slice(&flags.to_be_bytes());
+            reply.extend_from_slice(&size.to_be_bytes());
+            reply.extend_from_slice(&UID.to_be_bytes());
+            reply.extend_from_slice(&UID.to_be_bytes());
+            reply.extend_from_slice(&mode.to_be_bytes());
+            reply.extend_from_slice(&1_000_000_u32.to_be_bytes());
+            reply.extend_from_slice(&2_000_000_u32.to_be_bytes());
+        };
+        let status = |reply: &mut Vec<u8>, code: u32| {
+            reply.extend_from_slice(&code.to_be_bytes());
+            put_bytes(reply, b"no such file");
+            put_bytes(reply, b"");
+        };
+
+        let (kind, _) = read(&mut stream).unwrap();
+        assert_eq!(kind, SSH_FXP_INIT);
+        write(&mut stream, SSH_FXP_VERSION, &VERSION.to_be_bytes());
+
+        let mut listed = false;
+        while let Some((kind, body)) = read(&mut stream) {
+            let mut request = Cursor(&body);
+            let id = request.u32().unwrap();
+            let arg = request.bytes().unwrap();
+            let file = FILES.iter().find(|file| file.0.as_bytes() == arg);
+
+            let mut reply = id.to_be_bytes().to_vec();
+            let kind = match (kind, file) {
+                (SSH_FXP_LSTAT | SSH_FXP_STAT, Some(&(_, _, mode, size))) => {
+                    attrs(&mut reply, mode, size);
+                    SSH_FXP_ATTRS
+                }
+                (SSH_FXP_OPENDIR, Some(_)) => {
+                    put_bytes(&mut reply, b"handle");
+                    SSH_FXP_HANDLE
+                }
+                (SSH_FXP_READDIR, _) if !listed => {
+                    listed = true;
+                    reply.extend_from_slice(&3_u32.to_be_bytes());
+                    for &(path, long_name, mode, size) in &FILES[1..] {
+                        let name = if path == "/srv" { "." } else { &path[5..] };
+                        put_bytes(&mut reply, name.as_bytes());
+                        put_bytes(&mut reply, long_name.as_bytes());
+                        attrs(&mut reply, mode, size);
+                    }
+                    SSH_FXP_NAME
+                }
+                (SSH_FXP_READDIR, _) => {
+                    status(&mut reply, SSH_FX_EOF);
+                    SSH_FXP_STATUS
+                }
+                (SSH_FXP_READLINK, Some(_)) => {
+                    reply.extend_from_slice(&1_u32.to_be_bytes());
+                    put_bytes(&mut reply, b"notes.txt");
+                    put_bytes(&mut reply, b"");
+                    reply.extend_from_slice(&0_u32.to_be_bytes());
+                    SSH_FXP_NAME
+                }
+                (SSH_FXP_CLOSE, _) => {
+                    status(&mut reply, SSH_FX_OK);
+                    SSH_FXP_STATUS
+                }
+                _ => {
+                    status(&mut reply, SSH_FX_NO_SUCH_FILE);
+                    SSH_FXP_STATUS
+                }
+            };
+            write(&mut stream, kind, &reply);
+        }
+    }
+
+    fn session() -> Session {
+        let (client, server) = UnixStream::pair().unwrap();
+        thread::spawn(move || serve(server));
+
+        let reader = Box::new(client.try_clone().unwrap());
+        Session::start("sftp://host".into(), reader, Box::new(client), None).unwrap()
+    }
+
+    #[test]
+    fn home_directory_owner() {
+        assert_eq!(session().current_ids(), Some((UID, UID)));
+    }
+
+    #[test]
+    fn metadata() {
+        let fields = session()
+            .symlink_metadata(Path::new("/srv/notes.txt"))
+            .unwrap();
+        assert_eq!(fields.mode, Some(0o100_644));
+        assert_eq!(fields.size, Some(12));
+        assert_eq!(fields.uid, Some(UID));
+        assert_eq!(fields.modified, Some(time(2_000_000)));
+        assert_eq!(fields.nlink, None);
+        assert_eq!(fields.changed, None);
+    }
+
+    #[test]
+    fn missing_file() {
+        let error = session()
+            .symlink_metadata(Path::new("/nowhere"))
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn directory() {
+        let session = session();
+        let files = session.read_dir(Path::new("/srv")).unwrap();
+        let paths: Vec<_> = files.iter().map(|(path, _)| path.as_path()).collect();
+        assert_eq!(
+            paths,
+            [Path::new("/srv/notes.txt"), Path::new("/srv/latest")]
+        );
+
+        assert_eq!(files[0].1.nlink, Some(1));
+        assert_eq!(files[1].1.nlink, Some(2));
+        assert_eq!(session.user_name(UID).as_deref(), Some("ben"));
+        assert_eq!(session.group_name(UID).as_deref(), Some("staff"));
+    }
+
+    #[test]
+    fn link() {
+        let target = session().read_link(Path::new("/srv/latest")).unwrap();
+        assert_eq!(target, Path::new("notes.txt"));
+    }
+
+    #[test]
+    fn display() {
+        let session = session();
+        assert_eq!(session.display(Path::new("/srv")), "sftp://host/srv");
+        assert_eq!(session.display(Path::new(".")), "sftp://host/~");
+        assert_eq!(session.display(Path::new("./a/b")), "sftp://host/~/a/b");
+    }
+}
@@ -0,0 +1,228 @@
+//! Noticing which files are new since the last listing, for
+//! `--highlight-new`.
+//!
+//! Each time a directory gets listed, a snapshot of its files — each one’s
+//! name, modification time, and size — gets saved in eza’s cache directory.
+//! The next listing of the same directory compares its files against that
+//! snapshot before replacing it, and marks the ones that weren’t there
+//! before, and the ones whose time or size has changed since.
+//!
+//! The snapshot has every file in the directory, not just the ones that got
+//! shown, so that showing hidden files with `--all` doesn’t make them all look
+//! new. A directory that has never been listed before has nothing to compare
+//! against, so nothing in it gets marked.
+//!
+//! Snapshots get named after a hash of the directory’s full path, with the
+//! path itself on the first line so that two directories with the same hash
+//! can’t be mixed up.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+
+use log::*;
+
+use crate::cache;
+use crate::fs::{Dir, DotFilter, File};
+
+/// The snapshots that get compared against and saved during a run, for
+/// when `--highlight-new` is on.
+#[derive(Debug)]
+pub struct Snapshots {
+    /// Where snapshots get kept.
+    cache_dir: PathBuf,
+
+    /// The snapshots from the previous listings, by the path of the
+    /// directory as it was given. `None` means the directory has no
+    /// snapshot yet.
+    previous: Mutex<HashMap<PathBuf, Option<Snapshot>>>,
+}
+
+/// How a file has changed since the directory was last listed.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Change {
+    /// The file wasn’t there before.
+    Added,
+
+    /// The file’s modification time or size is different.
+    Modified,
+}
+
+/// When a file was last modified, and how big it was.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+struct Stamp {
+    secs: u64,
+    nanos: u32,
+    size: u64,
+}
+
+impl Stamp {
+    fn of(file: &File<'_>) -> Self {
+        let modified = file
+            .metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .unwrap_or_default();
+
+        Self {
+            secs: modified.as_secs(),
+            nanos: modified.subsec_nanos(),
+            size: file.metadata.len(),
+        }
+    }
+}
+
+/// A directory’s files, by name.
+#[derive(PartialEq, Eq, Debug, Default)]
+struct Snapshot {
+    files: HashMap<String, Stamp>,
+}
+
+impl Snapshot {
+    fn parse(text: &str) -> Self {
+        let files = text
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.splitn(4, ' ');
+                let secs = parts.next()?.parse().ok()?;
+                let nanos = parts.next()?.parse().ok()?;
+                let size = parts.next()?.parse().ok()?;
+                let name = parts.next()?.to_owned();
+                Some((name, Stamp { secs, nanos, size }))
+            })
+            .collect();
+
+        Self { files }
+    }
+
+    fn to_text(&self) -> String {
+        let mut lines = self
+            .files
+            .iter()
+            .map(|(name, stamp)| format!("{} {} {} {name}\n", stamp.secs, stamp.nanos, stamp.size))
+            .collect::<Vec<_>>();
+        lines.sort_unstable();
+        lines.concat()
+    }
+
+    fn change(&self, name: &str, stamp: Stamp) -> Option<Change> {
+        match self.files.get(name) {
+            None => Some(Change::Added),
+            Some(old) if *old != stamp => Some(Change::Modified),
+            Some(_) => None,
+        }
+    }
+}
+
+impl Snapshots {
+    /// Keeps snapshots in the given directory.
+    pub fn new(cache_dir: PathBuf) -> Self {
+        Self {
+            cache_dir,
+            previous: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Saves a snapshot of the directory’s files, after keeping hold of the
+    /// one from the last time it was listed for its files to be compared
+    /// against.
+    pub fn record(&self, dir: &Dir) {
+        self.load(&dir.path);
+
+        let files = dir
+            .files(DotFilter::Dotfiles, None, false, false, false)
+            .flatten()
+            .filter(|file| !file.name.contains('\n'))
+            .map(|file| (file.name.clone(), Stamp::of(&file)))
+            .collect();
+        let snapshot = Snapshot { files };
+
+        let Some((path, canonical)) = snapshot_path(&self.cache_dir, &dir.path) else {
+            return;
+        };
+        let text = format!("{}\n{}", canonical.display(), snapshot.to_text());
+        if let Err(e) = cache::save(&path, text.as_bytes()) {
+            warn!("Failed to save snapshot {path:?}: {e}");
+        }
+    }
+
+    /// How the file has changed since its directory was last listed, if it
+    /// has.
+    pub fn change(&self, file: &File<'_>) -> Option<Change> {
+        let dir = file.path.parent()?;
+        self.load(dir);
+
+        let previous = self.previous.lock().unwrap();
+        previous
+            .get(dir)?
+            .as_ref()?
+            .change(&file.name, Stamp::of(file))
+    }
+
+    /// Reads the directory’s last snapshot into memory, unless that’s
+    /// already been done, so it stays around after a new one replaces it on
+    /// disk.
+    fn load(&self, dir: &Path) {
+        let mut previous = self.previous.lock().unwrap();
+        if previous.contains_key(dir) {
+            return;
+        }
+
+        let snapshot = snapshot_path(&self.cache_dir, dir).and_then(|(path, canonical)| {
+            let text = fs::read_to_string(path).ok()?;
+            let (first, rest) = text.split_once('\n')?;
+            (Path::new(first) == canonical).then(|| Snapshot::parse(rest))
+        });
+        previous.insert(dir.to_path_buf(), snapshot);
+    }
+}
+
+/// Where the directory’s snapshot gets kept, along with the full path that
+/// its name comes from.
+fn snapshot_path(cache_dir: &Path, dir: &Path) -> Option<(PathBuf, PathBuf)> {
+    let canonical = fs::canonicalize(dir).ok()?;
+    let name = cache::file_name(canonical.to_string_lossy().as_bytes());
+    Some((cache_dir.join(name), canonical))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn stamp(secs: u64, size: u64) -> Stamp {
+        Stamp {
+            secs,
+            nanos: 0,
+            size,
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        let mut snapshot = Snapshot::default();
+        snapshot.files.insert("a file".into(), stamp(100, 5));
+        snapshot.files.insert("other".into(), stamp(200, 0));
+        assert_eq!(Snapshot::parse(&snapshot.to_text()), snapshot);
+    }
+
+    #[test]
+    fn changes() {
+        let snapshot = Snapshot::parse("100 0 5 same\n100 0 5 touched\n100 0 5 grown\n");
+        assert_eq!(snapshot.change("same", stamp(100, 5)), None);
+        assert_eq!(
+            snapshot.change("touched", stamp(101, 5)),
+            Some(Change::Modified)
+        );
+        assert_eq!(
+            snapshot.change("grown", stamp(100, 6)),
+            Some(Change::Modified)
+        );
+        assert_eq!(
+            snapshot.change("brand new", stamp(100, 5)),
+            Some(Change::Added)
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! Recognising directories that mean something to the user: their home
+//! directory, the XDG user directories such as Downloads and Pictures, and
+//! the roots of projects.
+//!
+//! The user directories get read from `user-dirs.dirs` in the config
+//! directory, which `xdg-user-dirs-update` writes, so a Downloads directory
+//! that’s been renamed or translated is still recognised. Any that aren’t
+//! listed there get their usual names inside the home directory. They all
+//! get looked up once, along with the options, by their full paths, and then
+//! compared against the full path of each directory being listed.
+//!
+//! A project root is any directory with a version control directory or a
+//! package manifest in it, which has to be checked for separately for each
+//! directory.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::*;
+
+/// Files that only appear at the root of a project.
+const PROJECT_MARKERS: &[&str] = &[
+    ".git",
+    ".hg",
+    "Cargo.toml",
+    "go.mod",
+    "package.json",
+    "pyproject.toml",
+];
+
+/// A directory that gets its own icon and colour.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub enum SpecialDir {
+    Home,
+    Desktop,
+    Documents,
+    Downloads,
+    Music,
+    Pictures,
+    Public,
+    Templates,
+    Videos,
+    Project,
+}
+
+impl SpecialDir {
+    /// The user directory with this key in `user-dirs.dirs`, along with its
+    /// name when it hasn’t been given a different one.
+    fn from_key(key: &str) -> Option<(Self, &'static str)> {
+        match key {
+            "XDG_DESKTOP_DIR" => Some((Self::Desktop, "Desktop")),
+            "XDG_DOCUMENTS_DIR" => Some((Self::Documents, "Documents")),
+            "XDG_DOWNLOAD_DIR" => Some((Self::Downloads, "Downloads")),
+            "XDG_MUSIC_DIR" => Some((Self::Music, "Music")),
+            "XDG_PICTURES_DIR" => Some((Self::Pictures, "Pictures")),
+            "XDG_PUBLICSHARE_DIR" => Some((Self::Public, "Public")),
+            "XDG_TEMPLATES_DIR" => Some((Self::Templates, "Templates")),
+            "XDG_VIDEOS_DIR" => Some((Self::Videos, "Videos")),
+            _ => None,
+        }
+    }
+}
+
+/// The user directories, and the home directory, by their full paths.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct UserDirs(HashMap<PathBuf, SpecialDir>);
+
+impl UserDirs {
+    /// Looks the user directories up. Without a home directory, there are
+    /// none to look up.
+    pub fn resolve(home: Option<PathBuf>, config_home: Option<PathBuf>) -> Self {
+        let Some(home) = home else {
+            return Self::default();
+        };
+
+        let config_home = config_home.unwrap_or_else(|| home.join(".config"));
+        let text = fs::read_to_string(config_home.join("user-dirs.dirs")).unwrap_or_default();
+
+        let dirs = user_dirs(&text, &home)
+            .into_iter()
+            .chain([(home.clone(), SpecialDir::Home)])
+            .filter_map(|(path, dir)| Some((fs::canonicalize(path).ok()?, dir)))
+            .inspect(|(path, dir)| debug!("Found {dir:?} directory {path:?}"))
+            .collect();
+        Self(dirs)
+    }
+
+    /// Which special directory is at the given full path, if any. The path
+    /// of the directory is only checked for project files if it isn’t a
+    /// user directory.
+    pub fn lookup(&self, absolute_path: &Path, path: &Path) -> Option<SpecialDir> {
+        if let Some(dir) = self.0.get(absolute_path) {
+            return Some(*dir);
+        }
+
+        PROJECT_MARKERS
+            .iter()
+            .any(|marker| fs::symlink_metadata(path.join(marker)).is_ok())
+            .then_some(SpecialDir::Project)
+    }
+}
+
+/// Reads the user directories out of a `user-dirs.dirs` file, which has
+/// lines such as `XDG_DOWNLOAD_DIR="$HOME/Downloads"`, filling in the ones
+/// that aren’t there with their usual names. Directories that are set to the
+/// home directory itself have been turned off.
+fn user_dirs(text: &str, home: &Path) -> Vec<(PathBuf, SpecialDir)> {
+    let mut set = HashMap::new();
+
+    for line in text.lines() {
+        let Some((key, value)) = line.trim().split_once('=') else {
+            continue;
+        };
+        let Some((dir, _)) = SpecialDir::from_key(key) else {
+            continue;
+        };
+
+        let value = value.trim_matches('"');
+        let path = if let Some(rest) = value.strip_prefix("$HOME") {
+            home.join(rest.trim_start_matches('/'))
+        } else if value.starts_with('/') {
+            PathBuf::from(value)
+        } else {
+            continue;
+        };
+        set.insert(dir, path);
+    }
+
+    let keys = [
+        "XDG_DESKTOP_DIR",
+        "XDG_DOCUMENTS_DIR",
+        "XDG_DOWNLOAD_DIR",
+        "XDG_MUSIC_DIR",
+        "XDG_PICTURES_DIR",
+        "XDG_PUBLICSHARE_DIR",
+        "XDG_TEMPLATES_DIR",
+        "XDG_VIDEOS_DIR",
+    ];
+
+    keys.into_iter()
+        .filter_map(SpecialDir::from_key)
+        .map(|(dir, name)| {
+            let path = set.remove(&dir).unwrap_or_else(|| home.join(name));
+            (path, dir)
+        })
+        .filter(|(path, _)| path != home)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn find(dirs: &[(PathBuf, SpecialDir)], dir: SpecialDir) -> Option<&Path> {
+        dirs.iter()
+            .find(|(_, d)| *d == dir)
+            .map(|(path, _)| path.as_path())
+    }
+
+    #[test]
+    fn defaults() {
+        let dirs = user_dirs("", Path::new("/home/me"));
+        assert_eq!(dirs.len(), 8);
+        assert_eq!(
+            find(&dirs, SpecialDir::Downloads),
+            Some(Path::new("/home/me/Downloads"))
+        );
+    }
+
+    #[test]
+    fn from_file() {
+        let text = "# written by xdg-user-dirs-update\n\
+                    XDG_DOWNLOAD_DIR=\"$HOME/Téléchargements\"\n\
+                    XDG_MUSIC_DIR=\"/srv/music\"\n\
+                    XDG_DESKTOP_DIR=\"$HOME/\"\n";
+        let dirs = user_dirs(text, Path::new("/home/me"));
+        assert_eq!(
+            find(&dirs, SpecialDir::Downloads),
+            Some(Path::new("/home/me/Téléchargements"))
+        );
+        assert_eq!(
+            find(&dirs, SpecialDir::Music),
+            Some(Path::new("/srv/music"))
+        );
+        assert_eq!(find(&dirs, SpecialDir::Desktop), None);
+    }
+}
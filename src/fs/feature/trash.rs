@@ -0,0 +1,251 @@
+//! Reading where files in the trash came from, and when they were deleted.
+//!
+//! On Linux and the BSDs, the trash follows the freedesktop.org
+//! specification: deleted files are kept in a `files` directory, next to an
+//! `info` directory holding a `.trashinfo` file for each of them. On
+//! Windows, each `$R` file in a `$Recycle.Bin` folder has a binary `$I` file
+//! next to it with the same information. macOS keeps it in the trash’s
+//! `.DS_Store` file, which isn’t read.
+
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::prelude::*;
+use percent_encoding::percent_decode_str;
+
+/// What’s known about a file that was moved to the trash.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct TrashInfo {
+    /// Where the file was before it was deleted.
+    pub original_path: PathBuf,
+
+    /// When the file was deleted, in UTC.
+    pub deleted: Option<NaiveDateTime>,
+}
+
+/// Whether the given directory is one that trashed files get kept in, with
+/// information about them that can be read.
+pub fn is_trash_dir(dir: &Path) -> bool {
+    freedesktop_trash_root(dir).is_some() || is_recycle_bin(dir)
+}
+
+/// Read what’s known about a file in a trash directory.
+pub fn info_for(path: &Path) -> Option<TrashInfo> {
+    let dir = path.parent()?;
+    let name = path.file_name()?;
+
+    if let Some(root) = freedesktop_trash_root(dir) {
+        let mut info_name = name.to_os_string();
+        info_name.push(".trashinfo");
+
+        let contents = fs::read_to_string(dir.parent()?.join("info").join(info_name)).ok()?;
+        let mut info = parse_trashinfo(&contents)?;
+
+        // Trash directories at the top of other filesystems store paths
+        // relative to the top of that filesystem.
+        if info.original_path.is_relative() {
+            if let Some(top) = root.parent() {
+                info.original_path = top.join(&info.original_path);
+            }
+        }
+
+        Some(info)
+    } else if is_recycle_bin(dir) {
+        let name = name.to_str()?.strip_prefix("$R")?;
+        parse_recycle_bin_info(&fs::read(dir.join(format!("$I{name}"))).ok()?)
+    } else {
+        None
+    }
+}
+
+/// If the directory is the `files` directory of a freedesktop.org trash,
+/// return the directory that the trash is kept in: the `Trash` in the
+/// user’s data directory, or a `.Trash` or `.Trash-UID` at the top of a
+/// filesystem.
+fn freedesktop_trash_root(dir: &Path) -> Option<&Path> {
+    if dir.file_name() != Some(OsStr::new("files")) || !dir.with_file_name("info").is_dir() {
+        return None;
+    }
+
+    dir.ancestors().skip(1).take(2).find(|ancestor| {
+        ancestor
+            .file_name()
+            .and_then(OsStr::to_str)
+            .is_some_and(|name| name == "Trash" || name.starts_with(".Trash"))
+    })
+}
+
+/// Whether the directory is inside a Windows recycle bin.
+fn is_recycle_bin(dir: &Path) -> bool {
+    dir.components().any(|component| {
+        component
+            .as_os_str()
+            .to_str()
+            .is_some_and(|name| name.eq_ignore_ascii_case("$Recycle.Bin"))
+    })
+}
+
+/// Parse the `[Trash Info]` group of a `.trashinfo` file, which looks like
+/// this:
+///
+/// ```text
+/// [Trash Info]
+/// Path=/home/user/foo%20bar.txt
+/// DeletionDate=2004-08-31T22:32:08
+/// ```
+///
+/// The path is percent-encoded, and the date is in local time.
+fn parse_trashinfo(contents: &str) -> Option<TrashInfo> {
+    let mut in_group = false;
+    let mut original_path = None;
+    let mut deleted = None;
+
+    for line in contents.lines() {
+        let line = line.trim();
+
+        if line.starts_with('[') {
+            in_group = line == "[Trash Info]";
+        } else if let Some((key, value)) = line.split_once('=').filter(|_| in_group) {
+            match key.trim() {
+                "Path" => original_path = Some(decode_path(value.trim())),
+                "DeletionDate" => {
+                    deleted = NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%dT%H:%M:%S")
+                        .ok()
+                        .and_then(|local| Local.from_local_datetime(&local).earliest())
+                        .map(|time| time.naive_utc());
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Some(TrashInfo {
+        original_path: original_path?,
+        deleted,
+    })
+}
+
+fn decode_path(encoded: &str) -> PathBuf {
+    let bytes = percent_decode_str(encoded).collect::<Vec<u8>>();
+
+    #[cfg(unix)]
+    let path = {
+        use std::os::unix::ffi::OsStringExt;
+        OsString::from_vec(bytes)
+    };
+
+    #[cfg(not(unix))]
+    let path = OsString::from(String::from_utf8_lossy(&bytes).into_owned());
+
+    PathBuf::from(path)
+}
+
+/// Parse a recycle bin `$I` file. Both versions start with a version
+/// number, the file’s size, and the time it was deleted. Version 1, from
+/// Windows Vista, follows that with the path in a fixed 520-byte field,
+/// while version 2, from Windows 10, has the path’s length first.
+fn parse_recycle_bin_info(bytes: &[u8]) -> Option<TrashInfo> {
+    let read_u64 = |at: usize| {
+        bytes
+            .get(at..at + 8)
+            .and_then(|field| field.try_into().ok())
+            .map(u64::from_le_bytes)
+    };
+
+    let path_bytes = match read_u64(0)? {
+        1 => bytes.get(24..)?,
+        2 => {
+            let length = u32::from_le_bytes(bytes.get(24..28)?.try_into().ok()?) as usize;
+            bytes.get(28..28 + length * 2)?
+        }
+        _ => return None,
+    };
+
+    let path = path_bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect::<Vec<u16>>();
+
+    Some(TrashInfo {
+        original_path: PathBuf::from(String::from_utf16_lossy(&path)),
+        deleted: read_u64(16).and_then(filetime_to_utc),
+    })
+}
+
+/// Convert a Windows `FILETIME`, which counts 100-nanosecond intervals
+/// since the start of 1601, into a time in UTC.
+fn filetime_to_utc(filetime: u64) -> Option<NaiveDateTime> {
+    const SECONDS_BEFORE_UNIX_EPOCH: i64 = 11_644_473_600;
+
+    let seconds = (filetime / 10_000_000) as i64 - SECONDS_BEFORE_UNIX_EPOCH;
+    let nanoseconds = (filetime % 10_000_000) as u32 * 100;
+    DateTime::from_timestamp(seconds, nanoseconds).map(|time| time.naive_utc())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn trashinfo() {
+        let info = parse_trashinfo(
+            "[Trash Info]\nPath=/home/user/foo%20bar.txt\nDeletionDate=2004-08-31T22:32:08\n",
+        )
+        .unwrap();
+
+        assert_eq!(info.original_path, PathBuf::from("/home/user/foo bar.txt"));
+        assert!(info.deleted.is_some());
+    }
+
+    #[test]
+    fn trashinfo_other_groups() {
+        let info = parse_trashinfo("[Other]\nPath=/nope\n[Trash Info]\nPath=/yes\n").unwrap();
+        assert_eq!(info.original_path, PathBuf::from("/yes"));
+        assert_eq!(info.deleted, None);
+    }
+
+    #[test]
+    fn trashinfo_without_path() {
+        assert_eq!(
+            parse_trashinfo("[Trash Info]\nDeletionDate=2004-08-31T22:32:08\n"),
+            None
+        );
+    }
+
+    fn recycle_bin_info(version: u64, path: &str) -> Vec<u8> {
+        let units = path.encode_utf16().chain(Some(0)).collect::<Vec<u16>>();
+
+        let mut bytes = Vec::new();
+        bytes.extend(version.to_le_bytes());
+        bytes.extend(1234_u64.to_le_bytes());
+        bytes.extend(132_223_104_000_000_000_u64.to_le_bytes());
+        if version == 2 {
+            bytes.extend((units.len() as u32).to_le_bytes());
+        }
+        bytes.extend(units.iter().flat_map(|unit| unit.to_le_bytes()));
+        bytes
+    }
+
+    #[test]
+    fn recycle_bin_v1() {
+        let info = parse_recycle_bin_info(&recycle_bin_info(1, r"C:\Users\me\a.txt")).unwrap();
+        assert_eq!(info.original_path, PathBuf::from(r"C:\Users\me\a.txt"));
+    }
+
+    #[test]
+    fn recycle_bin_v2() {
+        let info = parse_recycle_bin_info(&recycle_bin_info(2, r"D:\b.txt")).unwrap();
+        assert_eq!(info.original_path, PathBuf::from(r"D:\b.txt"));
+        assert_eq!(
+            info.deleted,
+            NaiveDate::from_ymd_opt(2020, 1, 1).and_then(|day| day.and_hms_opt(0, 0, 0))
+        );
+    }
+
+    #[test]
+    fn recycle_bin_truncated() {
+        assert_eq!(parse_recycle_bin_info(&[2, 0, 0, 0]), None);
+    }
+}
@@ -0,0 +1,547 @@
+//! Looking up the metadata of a directory’s files in batches with `io_uring`,
+//! for `EZA_IO_URING`.
+//!
+//! Listing a directory normally takes one `lstat` per file, each of which
+//! waits for the one before it to finish. On a cold cache over a network
+//! filesystem or a spinning disk, that waiting is most of the time a listing
+//! takes. Handing the kernel a `statx` for every file at once lets it work
+//! on them all together, and the listing waits for them only once.
+//!
+//! There’s no `io_uring` operation for reading directories, so the directory
+//! itself still gets read with `getdents64` the usual way, and only the
+//! lookups of its files get batched.
+//!
+//! Everything here falls back to the usual lookups: if the kernel is too old
+//! for `io_uring` or its `statx` operation, if it’s been forbidden by a seccomp
+//! filter, or if a file can’t be looked up this way for any other reason,
+//! that file gets looked up one at a time as it would have been anyway.
+
+use std::cell::RefCell;
+use std::ffi::CString;
+use std::fs;
+use std::io;
+use std::mem;
+use std::os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use log::*;
+
+use crate::fs::metadata::{Stat, Timestamp};
+
+/// Whether `EZA_IO_URING` turned the backend on.
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// How many files a directory needs to have before batching them is worth
+/// setting up. Smaller ones don’t have enough waiting to save.
+pub const MIN_ENTRIES: usize = 32;
+
+/// How many lookups can be handed to the kernel at once.
+const ENTRIES: u32 = 256;
+
+const IORING_OP_STATX: u8 = 21;
+const IORING_ENTER_GETEVENTS: u32 = 1;
+const IORING_OFF_SQ_RING: libc::off_t = 0;
+const IORING_OFF_CQ_RING: libc::off_t = 0x800_0000;
+const IORING_OFF_SQES: libc::off_t = 0x1000_0000;
+
+const AT_SYMLINK_NOFOLLOW: u32 = 0x100;
+const STATX_BASIC_STATS: u32 = 0x7ff;
+const STATX_BTIME: u32 = 0x800;
+
+/// Turns the backend on for the rest of the run.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether directories’ files should be looked up in batches.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+thread_local! {
+    /// Each thread that reads directories gets its own ring, set up the first
+    /// time it’s needed.
+    static RING: RefCell<State> = RefCell::new(State::Untried);
+}
+
+enum State {
+    Untried,
+    Ready(Ring),
+
+    /// The ring couldn’t be set up, or the kernel doesn’t know how to
+    /// `statx` with it, so there’s no point trying again.
+    Unavailable,
+}
+
+/// Looks up the metadata of every one of the paths, which all have to be in
+/// the given directory, without following symlinks. Any that couldn’t be
+/// looked up this way are left as `None`, as are all of them if `io_uring`
+/// can’t be used at all.
+pub fn symlink_metadata_all(dir: &Path, paths: &[PathBuf]) -> Vec<Option<Stat>> {
+    let names = paths
+        .iter()
+        .map(|path| CString::new(path.file_name()?.as_bytes()).ok())
+        .collect::<Vec<_>>();
+
+    let stats = fs::File::open(dir).and_then(|dir| {
+        RING.with(|ring| {
+            let mut state = ring.borrow_mut();
+            if let State::Untried = *state {
+                *state = match Ring::new() {
+                    Ok(ring) => State::Ready(ring),
+                    Err(e) => {
+                        debug!("Couldn’t set up io_uring: {e}");
+                        State::Unavailable
+                    }
+                };
+            }
+
+            let State::Ready(ring) = &mut *state else {
+                return Err(io::Error::from(io::ErrorKind::Unsupported));
+            };
+
+            let result = ring.statx_all(dir.as_raw_fd(), &names);
+            if let Err(e) = &result {
+                debug!("Giving up on io_uring: {e}");
+                if let State::Ready(ring) = mem::replace(&mut *state, State::Unavailable) {
+                    // Lookups that never finished could still write to the
+                    // ring, so it has to stay mapped.
+                    mem::forget(ring);
+                }
+            }
+            result
+        })
+    });
+
+    match stats {
+        Ok(stats) => stats,
+        Err(e) => {
+            debug!("Looking up the files in {dir:?} one at a time: {e}");
+            vec![None; paths.len()]
+        }
+    }
+}
+
+/// An `io_uring` instance, with its queues mapped into memory.
+struct Ring {
+    fd: OwnedFd,
+    params: Params,
+    submissions: Mmap,
+    completions: Mmap,
+    entries: Mmap,
+}
+
+impl Ring {
+    fn new() -> io::Result<Self> {
+        let mut params = Params::default();
+        // SAFETY: the kernel fills in the parameters, which are laid out
+        // the way it expects.
+        let fd =
+            unsafe { libc::syscall(libc::SYS_io_uring_setup, ENTRIES, ptr::addr_of_mut!(params)) };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let fd = RawFd::try_from(fd).map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+        // SAFETY: the descriptor was just returned to us, and nothing else
+        // owns it.
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        let submissions = Mmap::new(
+            &fd,
+            params.sq_off.array as usize + params.sq_entries as usize * mem::size_of::<u32>(),
+            IORING_OFF_SQ_RING,
+        )?;
+        let completions = Mmap::new(
+            &fd,
+            params.cq_off.cqes as usize + params.cq_entries as usize * mem::size_of::<Cqe>(),
+            IORING_OFF_CQ_RING,
+        )?;
+        let entries = Mmap::new(
+            &fd,
+            params.sq_entries as usize * mem::size_of::<Sqe>(),
+            IORING_OFF_SQES,
+        )?;
+
+        Ok(Self {
+            fd,
+            params,
+            submissions,
+            completions,
+            entries,
+        })
+    }
+
+    /// Looks up every one of the names in the directory, a queue’s worth at a
+    /// time. Fails if the ring stops working, or the kernel turns out not to
+    /// support `statx` with it.
+    fn statx_all(
+        &mut self,
+        dir: RawFd,
+        names: &[Option<CString>],
+    ) -> io::Result<Vec<Option<Stat>>> {
+        let mut stats = Vec::with_capacity(names.len());
+        for chunk in names.chunks(self.params.sq_entries as usize) {
+            let mut buffers = vec![Statx::default(); chunk.len()];
+            match self.statx_chunk(dir, chunk, &mut buffers) {
+                Ok(results) => {
+                    stats.extend(results.into_iter().zip(&buffers).map(|(result, buffer)| {
+                        match result {
+                            Some(0) => Some(buffer.stat()),
+                            _ => None,
+                        }
+                    }));
+                }
+                Err(e) => {
+                    // The kernel could still be writing to the buffers.
+                    mem::forget(buffers);
+                    return Err(e);
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Submits a lookup for every name that has one, and waits for them
+    /// all to come back, returning each one’s result.
+    fn statx_chunk(
+        &mut self,
+        dir: RawFd,
+        names: &[Option<CString>],
+        buffers: &mut [Statx],
+    ) -> io::Result<Vec<Option<i32>>> {
+        let sq = &self.params.sq_off;
+        let mut count = 0;
+
+        // SAFETY: the offsets come from the kernel and point into the
+        // mappings, and the buffers and names outlive the lookups, as this
+        // waits for every one of them before returning.
+        unsafe {
+            let tail = &*self.submissions.at::<AtomicU32>(sq.tail);
+            let mask = *self.submissions.at::<u32>(sq.ring_mask);
+            let array = self.submissions.at::<u32>(sq.array);
+            let entries = self.entries.ptr.cast::<Sqe>();
+
+            let mut next = tail.load(Ordering::Relaxed);
+            for (index, (name, buffer)) in names.iter().zip(buffers.iter_mut()).enumerate() {
+                let Some(name) = name else { continue };
+                let slot = next & mask;
+                entries.add(slot as usize).write(Sqe {
+                    opcode: IORING_OP_STATX,
+                    fd: dir,
+                    off: ptr::addr_of_mut!(*buffer) as u64,
+                    addr: name.as_ptr() as u64,
+                    len: STATX_BASIC_STATS | STATX_BTIME,
+                    op_flags: AT_SYMLINK_NOFOLLOW,
+                    user_data: index as u64,
+                    ..Sqe::default()
+                });
+                array.add(slot as usize).write(slot);
+                next = next.wrapping_add(1);
+                count += 1;
+            }
+            tail.store(next, Ordering::Release);
+        }
+
+        let mut unsubmitted = count;
+        while unsubmitted > 0 {
+            unsubmitted -= self.enter(unsubmitted, count)?;
+        }
+
+        let cq = &self.params.cq_off;
+        let mut results = vec![None; names.len()];
+        let mut reaped = 0;
+
+        // SAFETY: as above, the offsets point into the completion mapping.
+        unsafe {
+            let head = &*self.completions.at::<AtomicU32>(cq.head);
+            let tail = &*self.completions.at::<AtomicU32>(cq.tail);
+            let mask = *self.completions.at::<u32>(cq.ring_mask);
+            let completions = self.completions.at::<Cqe>(cq.cqes);
+
+            while reaped < count {
+                let mut next = head.load(Ordering::Relaxed);
+                let end = tail.load(Ordering::Acquire);
+                if next == end {
+                    self.enter(0, 1)?;
+                    continue;
+                }
+
+                while next != end {
+                    let completion = completions.add((next & mask) as usize).read();
+                    if completion.res == -libc::EINVAL {
+                        return Err(io::Error::from_raw_os_error(libc::EINVAL));
+                    }
+                    let index = usize::try_from(completion.user_data).ok();
+                    if let Some(result) = index.and_then(|i| results.get_mut(i)) {
+                        *result = Some(completion.res);
+                    }
+                    next = next.wrapping_add(1);
+                    reaped += 1;
+                }
+                head.store(next, Ordering::Release);
+            }
+        }
+
+        Ok(results)
+    }
+
+    /// Submits up to the given number of lookups, and waits until at least
+    /// the given number have finished, returning how many got submitted.
+    fn enter(&self, submit: u32, wait: u32) -> io::Result<u32> {
+        loop {
+            // SAFETY: no signal mask gets passed, so there’s nothing for the
+            // kernel to read besides the ring.
+            let submitted = unsafe {
+                libc::syscall(
+                    libc::SYS_io_uring_enter,
+                    self.fd.as_raw_fd(),
+                    submit,
+                    wait,
+                    IORING_ENTER_GETEVENTS,
+                    ptr::null::<libc::sigset_t>(),
+                    0_usize,
+                )
+            };
+
+            if submitted >= 0 {
+                return u32::try_from(submitted)
+                    .map_err(|_| io::Error::from(io::ErrorKind::InvalidData));
+            }
+
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+}
+
+/// A region of the ring that’s been mapped into memory.
+struct Mmap {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl Mmap {
+    fn new(fd: &OwnedFd, len: usize, offset: libc::off_t) -> io::Result<Self> {
+        // SAFETY: a fresh mapping doesn’t alias anything.
+        let ptr = unsafe {
+            libc::mmap(
+                ptr::null_mut(),
+                len,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED | libc::MAP_POPULATE,
+                fd.as_raw_fd(),
+                offset,
+            )
+        };
+
+        if ptr == libc::MAP_FAILED {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(Self { ptr, len })
+        }
+    }
+
+    /// A pointer to whatever’s at the given offset into the mapping.
+    unsafe fn at<T>(&self, offset: u32) -> *mut T {
+        self.ptr.cast::<u8>().add(offset as usize).cast()
+    }
+}
+
+impl Drop for Mmap {
+    fn drop(&mut self) {
+        // SAFETY: the mapping is only ever unmapped here.
+        unsafe {
+            libc::munmap(self.ptr, self.len);
+        }
+    }
+}
+
+// The structures that get shared with the kernel, laid out as in
+// `linux/io_uring.h` and `linux/stat.h`.
+
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct SqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    flags: u32,
+    dropped: u32,
+    array: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct CqringOffsets {
+    head: u32,
+    tail: u32,
+    ring_mask: u32,
+    ring_entries: u32,
+    overflow: u32,
+    cqes: u32,
+    flags: u32,
+    resv1: u32,
+    user_addr: u64,
+}
+
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct Params {
+    sq_entries: u32,
+    cq_entries: u32,
+    flags: u32,
+    sq_thread_cpu: u32,
+    sq_thread_idle: u32,
+    features: u32,
+    wq_fd: u32,
+    resv: [u32; 3],
+    sq_off: SqringOffsets,
+    cq_off: CqringOffsets,
+}
+
+/// A submission queue entry.
+#[repr(C)]
+#[derive(Default)]
+#[allow(dead_code)]
+struct Sqe {
+    opcode: u8,
+    flags: u8,
+    ioprio: u16,
+    fd: i32,
+    off: u64,
+    addr: u64,
+    len: u32,
+    op_flags: u32,
+    user_data: u64,
+    buf_index: u16,
+    personality: u16,
+    splice_fd_in: i32,
+    addr3: u64,
+    pad: u64,
+}
+
+/// A completion queue entry.
+#[repr(C)]
+#[allow(dead_code)]
+struct Cqe {
+    user_data: u64,
+    res: i32,
+    flags: u32,
+}
+
+#[repr(C)]
+#[derive(Default, Copy, Clone)]
+#[allow(dead_code)]
+struct StatxTimestamp {
+    tv_sec: i64,
+    tv_nsec: u32,
+    reserved: i32,
+}
+
+#[repr(C)]
+#[derive(Default, Copy, Clone)]
+#[allow(dead_code)]
+struct Statx {
+    mask: u32,
+    blksize: u32,
+    attributes: u64,
+    nlink: u32,
+    uid: u32,
+    gid: u32,
+    mode: u16,
+    spare0: u16,
+    ino: u64,
+    size: u64,
+    blocks: u64,
+    attributes_mask: u64,
+    atime: StatxTimestamp,
+    btime: StatxTimestamp,
+    ctime: StatxTimestamp,
+    mtime: StatxTimestamp,
+    rdev_major: u32,
+    rdev_minor: u32,
+    dev_major: u32,
+    dev_minor: u32,
+    spare: [u64; 14],
+}
+
+impl StatxTimestamp {
+    fn timestamp(self) -> Timestamp {
+        Timestamp {
+            secs: self.tv_sec,
+            nanos: self.tv_nsec,
+        }
+    }
+}
+
+impl Statx {
+    fn stat(&self) -> Stat {
+        Stat {
+            mode: u32::from(self.mode),
+            uid: self.uid,
+            gid: self.gid,
+            nlink: u64::from(self.nlink),
+            ino: self.ino,
+            dev: libc::makedev(self.dev_major, self.dev_minor),
+            rdev: libc::makedev(self.rdev_major, self.rdev_minor),
+            size: self.size,
+            blocks: self.blocks,
+            accessed: self.atime.timestamp(),
+            modified: self.mtime.timestamp(),
+            changed: self.ctime.timestamp(),
+            created: (self.mask & STATX_BTIME != 0).then(|| self.btime.timestamp()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::fs::metadata::Metadata;
+
+    #[test]
+    fn layouts() {
+        assert_eq!(mem::size_of::<Params>(), 120);
+        assert_eq!(mem::size_of::<Sqe>(), 64);
+        assert_eq!(mem::size_of::<Cqe>(), 16);
+        assert_eq!(mem::size_of::<Statx>(), 256);
+    }
+
+    #[test]
+    fn same_as_lstat() {
+        let dir = Path::new("src/fs");
+        let mut paths = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        paths.push(dir.join("nothing-here"));
+
+        let stats = symlink_metadata_all(dir, &paths);
+        assert_eq!(stats.len(), paths.len());
+        assert_eq!(stats.last(), Some(&None));
+
+        // Kernels and sandboxes without io_uring leave everything to `lstat`.
+        for (path, stat) in paths.iter().zip(stats).filter_map(|(p, s)| Some((p, s?))) {
+            let expected = Metadata::from(fs::symlink_metadata(path).unwrap());
+            let actual = Metadata::Stat(stat);
+            assert_eq!(actual.kind(), expected.kind(), "{path:?}");
+            assert_eq!(actual.len(), expected.len(), "{path:?}");
+            assert_eq!(actual.ino(), expected.ino(), "{path:?}");
+            assert_eq!(actual.dev(), expected.dev(), "{path:?}");
+            assert_eq!(actual.mode(), expected.mode(), "{path:?}");
+            assert_eq!(actual.modified().ok(), expected.modified().ok(), "{path:?}");
+        }
+    }
+}
@@ -1,4 +1,5 @@
-//! Extended attribute support for `NetBSD`, `Darwin`, and `Linux` systems.
+//! Extended attribute support for `NetBSD`, `FreeBSD`, `Darwin`, `Linux`,
+//! `Solaris`, `illumos`, and `AIX` systems.
 
 #![allow(trivial_casts)] // for ARM
 
@@ -11,7 +12,10 @@ pub const ENABLED: bool = cfg!(any(
     target_os = "macos",
     target_os = "linux",
     target_os = "netbsd",
-    target_os = "freebsd"
+    target_os = "freebsd",
+    target_os = "solaris",
+    target_os = "illumos",
+    target_os = "aix"
 ));
 
 #[derive(Debug)]
@@ -29,7 +33,10 @@ pub trait FileAttributes {
     target_os = "macos",
     target_os = "linux",
     target_os = "netbsd",
-    target_os = "freebsd"
+    target_os = "freebsd",
+    target_os = "solaris",
+    target_os = "illumos",
+    target_os = "aix"
 ))]
 impl FileAttributes for Path {
     fn attributes(&self) -> io::Result<Vec<Attribute>> {
@@ -45,7 +52,10 @@ impl FileAttributes for Path {
     target_os = "macos",
     target_os = "linux",
     target_os = "netbsd",
-    target_os = "freebsd"
+    target_os = "freebsd",
+    target_os = "solaris",
+    target_os = "illumos",
+    target_os = "aix"
 )))]
 impl FileAttributes for Path {
     fn attributes(&self) -> io::Result<Vec<Attribute>> {
@@ -61,7 +71,8 @@ impl FileAttributes for Path {
     target_os = "macos",
     target_os = "linux",
     target_os = "netbsd",
-    target_os = "freebsd"
+    target_os = "freebsd",
+    target_os = "aix"
 ))]
 mod extended_attrs {
     use super::Attribute;
@@ -152,6 +163,63 @@ mod extended_attrs {
         }
     }
 
+    #[cfg(target_os = "aix")]
+    mod os {
+        use libc::{c_char, c_void, size_t, ssize_t};
+
+        // The libc crate doesn’t declare the functions for the extended
+        // attributes that JFS2 keeps, but they work the same way as Linux’s.
+        extern "C" {
+            fn listea(path: *const c_char, list: *mut c_char, size: size_t) -> ssize_t;
+            fn llistea(path: *const c_char, list: *mut c_char, size: size_t) -> ssize_t;
+            fn getea(
+                path: *const c_char,
+                name: *const c_char,
+                value: *mut c_void,
+                size: size_t,
+            ) -> ssize_t;
+            fn lgetea(
+                path: *const c_char,
+                name: *const c_char,
+                value: *mut c_void,
+                size: size_t,
+            ) -> ssize_t;
+        }
+
+        // Wrapper around listea and llistea for handling symbolic links
+        pub(super) fn list_xattr(
+            follow_symlinks: bool,
+            path: *const c_char,
+            namebuf: *mut c_char,
+            size: size_t,
+        ) -> ssize_t {
+            if follow_symlinks {
+                // SAFETY: Calling C function
+                unsafe { listea(path, namebuf, size) }
+            } else {
+                // SAFETY: Calling C function
+                unsafe { llistea(path, namebuf, size) }
+            }
+        }
+
+        // Wrapper around getea and lgetea for handling symbolic links
+        pub(super) fn get_xattr(
+            follow_symlinks: bool,
+            path: *const c_char,
+            name: *const c_char,
+            value: *mut c_void,
+            size: size_t,
+        ) -> ssize_t {
+            if follow_symlinks {
+                // SAFETY: Calling C function
+                unsafe { getea(path, name, value, size) }
+            } else {
+                // SAFETY: Calling C function
+                unsafe { lgetea(path, name, value, size) }
+            }
+        }
+    }
+
     #[cfg(any(target_os = "netbsd", target_os = "freebsd"))]
     mod os {
         use libc::{
@@ -260,7 +328,7 @@ mod extended_attrs {
 
     // Split attribute name list.  Each attribute name is null terminated in the
     // list.
-    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    #[cfg(any(target_os = "macos", target_os = "linux", target_os = "aix"))]
     fn split_attribute_list(buffer: &[u8]) -> Vec<OsString> {
         buffer[..buffer.len() - 1] // Skip trailing null
             .split(|&c| c == 0)
@@ -344,7 +412,7 @@ mod extended_attrs {
     }
 
     // Get the attribute value `name` on `path`
-    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    #[cfg(any(target_os = "macos", target_os = "linux", target_os = "aix"))]
     fn get_attribute(
         path: &CStr,
         name: &CStr,
@@ -357,6 +425,10 @@ mod extended_attrs {
             size: size_t,
         ) -> ssize_t,
     ) -> io::Result<Option<Vec<u8>>> {
+        // AIX says an attribute isn’t there with its own error number
+        #[cfg(target_os = "aix")]
+        use libc::ENOATTR as ENODATA;
+        #[cfg(not(target_os = "aix"))]
         use libc::ENODATA;
 
         get_loop(|buf, size| {
@@ -424,8 +496,21 @@ mod extended_attrs {
         })
     }
 
-    // Get a vector of all attribute names and values on `path`
+    // Get the value of the single attribute `name` on `path`
     #[cfg(any(target_os = "macos", target_os = "linux"))]
+    pub fn attribute(
+        path: &Path,
+        name: &str,
+        follow_symlinks: bool,
+    ) -> io::Result<Option<Vec<u8>>> {
+        let path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        let name = CString::new(name).map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        get_attribute(&path, &name, follow_symlinks, os::get_xattr)
+    }
+
+    // Get a vector of all attribute names and values on `path`
+    #[cfg(any(target_os = "macos", target_os = "linux", target_os = "aix"))]
     pub fn attributes(path: &Path, follow_symlinks: bool) -> io::Result<Vec<Attribute>> {
         let path = CString::new(path.as_os_str().as_bytes())
             .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
@@ -523,6 +608,123 @@ mod extended_attrs {
     }
 }
 
+// Solaris and illumos keep a file’s extended attributes as files in a
+// hidden directory attached to it, which `openat` opens when given
+// `O_XATTR`, rather than behind functions like `getxattr`.
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+mod extended_attrs {
+    use super::Attribute;
+    use libc::{c_int, ELOOP, O_NOFOLLOW, O_NONBLOCK, O_RDONLY, O_XATTR, _PC_XATTR_EXISTS};
+    use std::ffi::{CStr, CString};
+    use std::fs::File;
+    use std::io::{self, Read};
+    use std::os::fd::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    // Attributes that the system gives every file to hold its flags, such as
+    // whether it’s immutable, which `ls -@` leaves out too.
+    const SYSTEM_ATTRIBUTES: &[&[u8]] = &[b"SUNWattr_ro", b"SUNWattr_rw"];
+
+    // Open `name` relative to the directory `dir`
+    fn open_at(dir: c_int, name: &CStr, flags: c_int) -> io::Result<OwnedFd> {
+        // SAFETY: Calling C function
+        let fd = unsafe { libc::openat(dir, name.as_ptr(), flags | libc::O_CLOEXEC) };
+        if fd == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            // SAFETY: The descriptor was just opened, and nothing else owns it
+            Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+        }
+    }
+
+    // Get the names of the attributes in the attribute directory `dir`
+    fn list_attributes(dir: &OwnedFd) -> io::Result<Vec<CString>> {
+        // The directory stream takes ownership of the descriptor it’s given,
+        // so it gets a copy, leaving `dir` open for reading the values.
+        let fd = dir.try_clone()?.into_raw_fd();
+
+        // SAFETY: Calling C function
+        let stream = unsafe { libc::fdopendir(fd) };
+        if stream.is_null() {
+            let error = io::Error::last_os_error();
+            // SAFETY: The stream didn’t take the descriptor, so it’s still ours
+            unsafe { libc::close(fd) };
+            return Err(error);
+        }
+
+        let mut names = Vec::new();
+        loop {
+            // SAFETY: Calling C function with the stream opened above
+            let entry = unsafe { libc::readdir(stream) };
+            if entry.is_null() {
+                break;
+            }
+
+            // SAFETY: The entry stays valid until the next call to readdir,
+            // and its name is null terminated
+            let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) };
+            let bytes = name.to_bytes();
+            if bytes != b"." && bytes != b".." && !SYSTEM_ATTRIBUTES.contains(&bytes) {
+                names.push(name.to_owned());
+            }
+        }
+
+        // SAFETY: Calling C function, which closes the stream and descriptor
+        unsafe { libc::closedir(stream) };
+        Ok(names)
+    }
+
+    // Get the value of the attribute `name` in the attribute directory `dir`
+    fn get_attribute(dir: &OwnedFd, name: &CStr) -> io::Result<Option<Vec<u8>>> {
+        let mut value = Vec::new();
+        File::from(open_at(dir.as_raw_fd(), name, O_RDONLY)?).read_to_end(&mut value)?;
+        Ok((!value.is_empty()).then_some(value))
+    }
+
+    // Get a vector of all attribute names and values on `path`
+    pub fn attributes(path: &Path, follow_symlinks: bool) -> io::Result<Vec<Attribute>> {
+        let path = CString::new(path.as_os_str().as_bytes())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+
+        // Most files don’t have any attributes, and asking first saves
+        // opening each one of them to find that out.
+        // SAFETY: Calling C function
+        if unsafe { libc::pathconf(path.as_ptr(), _PC_XATTR_EXISTS) } <= 0 {
+            return Ok(Vec::new());
+        }
+
+        let flags = if follow_symlinks {
+            O_RDONLY | O_NONBLOCK
+        } else {
+            O_RDONLY | O_NONBLOCK | O_NOFOLLOW
+        };
+
+        let file = match open_at(libc::AT_FDCWD, &path, flags) {
+            // Symbolic links can’t have attributes of their own
+            Err(e) if e.raw_os_error() == Some(ELOOP) && !follow_symlinks => {
+                return Ok(Vec::new());
+            }
+            result => result?,
+        };
+
+        let dot = CStr::from_bytes_with_nul(b".\0").unwrap();
+        let dir = open_at(file.as_raw_fd(), dot, O_RDONLY | O_XATTR)?;
+
+        let mut attrs = Vec::new();
+        for attr_name in list_attributes(&dir)? {
+            if let Ok(name) = attr_name.to_str() {
+                attrs.push(Attribute {
+                    name: name.to_string(),
+                    value: get_attribute(&dir, &attr_name)?,
+                });
+            }
+        }
+
+        Ok(attrs)
+    }
+}
+
 const ATTRIBUTE_VALUE_MAX_HEX_LENGTH: usize = 16;
 
 // Display for an attribute.  Attribute values that have a custom display are
@@ -551,6 +753,256 @@ impl Display for Attribute {
     }
 }
 
+impl Attribute {
+    /// A preview of the attribute’s value for `--xattr-values`, cut down to
+    /// at most `limit` characters, along with how big the value is. Text is
+    /// shown quoted and escaped, and anything else as hex bytes. Attributes
+    /// with a custom display are shown as they normally are.
+    pub fn preview(&self, limit: usize) -> String {
+        let Some(value) = &self.value else {
+            return self.to_string();
+        };
+
+        if custom_attr_display(self).is_some() || custom_value_display(value).is_some() {
+            return self.to_string();
+        }
+
+        let preview = if let Ok(text) = str::from_utf8(value) {
+            let text = text.trim_end_matches(char::from(0));
+            if let Some((end, _)) = text.char_indices().nth(limit) {
+                format!("{:?}…", &text[..end])
+            } else {
+                format!("{text:?}")
+            }
+        } else {
+            let hex = value
+                .iter()
+                .take(limit)
+                .map(|byte| format!("{byte:02x}"))
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            if value.len() > limit {
+                format!("[{hex} …]")
+            } else {
+                format!("[{hex}]")
+            }
+        };
+
+        format!("{}: {} ({} bytes)", self.name, preview, value.len())
+    }
+}
+
+/// The value of the single attribute `name` on `path`, if it has one and it
+/// can be read.
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+pub fn attribute(path: &Path, name: &str, follow_symlinks: bool) -> Option<Vec<u8>> {
+    extended_attrs::attribute(path, name, follow_symlinks)
+        .ok()
+        .flatten()
+}
+
+/// Whether the Finder has been told not to show the file at `path`, through
+/// the invisible bit in its `com.apple.FinderInfo` attribute.
+#[cfg(target_os = "macos")]
+pub fn is_finder_invisible(path: &Path, follow_symlinks: bool) -> bool {
+    extended_attrs::attribute(path, "com.apple.FinderInfo", follow_symlinks)
+        .ok()
+        .flatten()
+        .is_some_and(|value| finder_info_is_invisible(&value))
+}
+
+// com.apple.FinderInfo is 32 bytes, starting with a FileInfo record whose
+// big-endian fdFlags field, at offset 8, has the kIsInvisible bit set for
+// files that the Finder hides
+#[cfg(any(target_os = "macos", test))]
+fn finder_info_is_invisible(value: &[u8]) -> bool {
+    const IS_INVISIBLE: u16 = 0x4000;
+
+    value
+        .get(8..10)
+        .is_some_and(|flags| u16::from_be_bytes([flags[0], flags[1]]) & IS_INVISIBLE != 0)
+}
+
+/// One of the colours that a tag can have. These are the seven that the
+/// Finder offers, in the order it numbers them.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+pub enum TagColour {
+    Grey,
+    Green,
+    Purple,
+    Blue,
+    Yellow,
+    Red,
+    Orange,
+}
+
+/// A tag that the user has given a file.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Tag {
+    pub name: String,
+    pub colour: Option<TagColour>,
+}
+
+/// The tags on the file at `path`: the Finder’s tags on macOS, and the
+/// `user.xdg.tags` attribute used by some file managers on Linux.
+#[allow(unused_variables)]
+pub fn tags(path: &Path, follow_symlinks: bool) -> Vec<Tag> {
+    #[cfg(target_os = "macos")]
+    let tags = extended_attrs::attribute(path, FINDER_TAGS, follow_symlinks)
+        .ok()
+        .flatten()
+        .map(|value| parse_finder_tags(&value));
+
+    #[cfg(target_os = "linux")]
+    let tags = extended_attrs::attribute(path, XDG_TAGS, follow_symlinks)
+        .ok()
+        .flatten()
+        .map(|value| parse_xdg_tags(&value));
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    let tags = None;
+
+    tags.unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+const FINDER_TAGS: &str = "com.apple.metadata:_kMDItemUserTags";
+
+#[cfg(target_os = "linux")]
+const XDG_TAGS: &str = "user.xdg.tags";
+
+// _kMDItemUserTags is a binary plist holding an array of strings, each
+// being the tag’s name, optionally followed by a newline and the number of
+// its colour, where 0 means it has none
+#[cfg(any(target_os = "macos", test))]
+fn parse_finder_tags(value: &[u8]) -> Vec<Tag> {
+    let reader = std::io::Cursor::new(value);
+    let Ok(plist::Value::Array(entries)) = plist::Value::from_reader(reader) else {
+        return Vec::new();
+    };
+
+    entries
+        .iter()
+        .filter_map(plist::Value::as_string)
+        .map(|entry| {
+            let (name, colour) = entry.split_once('\n').unwrap_or((entry, ""));
+            let colour = match colour {
+                "1" => Some(TagColour::Grey),
+                "2" => Some(TagColour::Green),
+                "3" => Some(TagColour::Purple),
+                "4" => Some(TagColour::Blue),
+                "5" => Some(TagColour::Yellow),
+                "6" => Some(TagColour::Red),
+                "7" => Some(TagColour::Orange),
+                _ => None,
+            };
+
+            Tag {
+                name: name.to_string(),
+                colour,
+            }
+        })
+        .collect()
+}
+
+// user.xdg.tags is a comma-separated list of tag names, which don’t have
+// colours
+#[cfg(any(target_os = "linux", test))]
+fn parse_xdg_tags(value: &[u8]) -> Vec<Tag> {
+    String::from_utf8_lossy(value)
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| Tag {
+            name: name.to_string(),
+            colour: None,
+        })
+        .collect()
+}
+
+/// The attributes that Linux keeps a file’s POSIX access control lists in:
+/// the one that applies to the file itself, and, for directories, the one
+/// that new files inside it start off with.
+pub const POSIX_ACL_ACCESS: &str = "system.posix_acl_access";
+pub const POSIX_ACL_DEFAULT: &str = "system.posix_acl_default";
+
+/// The attribute that Linux’s NFS client shows a file’s `NFSv4` access control
+/// list as.
+pub const NFS4_ACL: &str = "system.nfs4_acl";
+
+/// Whether the attribute with this name holds an access control list.
+pub fn is_acl(name: &str) -> bool {
+    name == POSIX_ACL_ACCESS || name == POSIX_ACL_DEFAULT || name == NFS4_ACL
+}
+
+/// Who an entry in an access control list applies to.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum AclTag {
+    /// The user that owns the file.
+    Owner,
+
+    /// Some other user, by ID.
+    User(u32),
+
+    /// The group that owns the file.
+    OwningGroup,
+
+    /// Some other group, by ID.
+    Group(u32),
+
+    /// The most that any named user or group, or the owning group, may do.
+    Mask,
+
+    /// Everyone else.
+    Other,
+}
+
+/// One entry of a POSIX access control list.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct AclEntry {
+    pub tag: AclTag,
+
+    /// The read, write, and execute bits, in that order from most to least
+    /// significant, the same as in a permission mode.
+    pub permissions: u16,
+}
+
+/// Parse the value of a POSIX access control list attribute, which is a
+/// version number followed by eight bytes for each entry: its tag, its
+/// permissions, and the user or group ID it applies to, all little-endian.
+pub fn parse_posix_acl(value: &[u8]) -> Option<Vec<AclEntry>> {
+    const VERSION: u32 = 2;
+
+    let version = u32::from_le_bytes(value.get(..4)?.try_into().ok()?);
+    let entries = &value[4..];
+    if version != VERSION || entries.len() % 8 != 0 {
+        return None;
+    }
+
+    entries
+        .chunks_exact(8)
+        .map(|entry| {
+            let tag = u16::from_le_bytes([entry[0], entry[1]]);
+            let permissions = u16::from_le_bytes([entry[2], entry[3]]) & 0o7;
+            let id = u32::from_le_bytes([entry[4], entry[5], entry[6], entry[7]]);
+
+            let tag = match tag {
+                0x01 => AclTag::Owner,
+                0x02 => AclTag::User(id),
+                0x04 => AclTag::OwningGroup,
+                0x08 => AclTag::Group(id),
+                0x10 => AclTag::Mask,
+                0x20 => AclTag::Other,
+                _ => return None,
+            };
+
+            Some(AclEntry { tag, permissions })
+        })
+        .collect()
+}
+
 struct AttributeDisplay {
     pub attribute: &'static str,
     pub display: fn(&Attribute) -> Option<String>,
@@ -706,3 +1158,129 @@ fn plist_value_display(value: &[u8]) -> Option<String> {
         .map(|s| format!("<plist version=\"1.0\">{}</plist>", s.replace('\n', "")))
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        finder_info_is_invisible, parse_finder_tags, parse_posix_acl, parse_xdg_tags, AclEntry,
+        AclTag, Attribute, Tag, TagColour,
+    };
+
+    fn attribute(value: &[u8]) -> Attribute {
+        Attribute {
+            name: String::from("user.test"),
+            value: Some(value.to_vec()),
+        }
+    }
+
+    #[test]
+    fn preview_text() {
+        assert_eq!(
+            attribute(b"hello\n").preview(32),
+            "user.test: \"hello\\n\" (6 bytes)"
+        );
+    }
+
+    #[test]
+    fn preview_long_text() {
+        assert_eq!(
+            attribute("héllo world".as_bytes()).preview(5),
+            "user.test: \"héllo\"… (12 bytes)"
+        );
+    }
+
+    #[test]
+    fn preview_binary() {
+        assert_eq!(
+            attribute(&[0xff, 0x00, 0x10]).preview(2),
+            "user.test: [ff 00 …] (3 bytes)"
+        );
+    }
+
+    #[test]
+    fn invisible_finder_info() {
+        let mut value = [0_u8; 32];
+        value[8] = 0x40;
+        assert!(finder_info_is_invisible(&value));
+    }
+
+    #[test]
+    fn visible_finder_info() {
+        let mut value = [0_u8; 32];
+        value[9] = 0x40;
+        assert!(!finder_info_is_invisible(&value));
+        assert!(!finder_info_is_invisible(&[]));
+    }
+
+    #[test]
+    fn finder_tags() {
+        let value = plist::Value::Array(vec![
+            plist::Value::String(String::from("Important\n6")),
+            plist::Value::String(String::from("Work")),
+        ]);
+        let mut bytes = Vec::new();
+        value.to_writer_binary(&mut bytes).unwrap();
+
+        assert_eq!(
+            parse_finder_tags(&bytes),
+            vec![
+                Tag {
+                    name: String::from("Important"),
+                    colour: Some(TagColour::Red),
+                },
+                Tag {
+                    name: String::from("Work"),
+                    colour: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn finder_tags_not_a_plist() {
+        assert_eq!(parse_finder_tags(b"nope"), Vec::new());
+    }
+
+    #[test]
+    fn xdg_tags() {
+        let names = parse_xdg_tags(b"work, urgent,,")
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["work", "urgent"]);
+    }
+
+    #[test]
+    fn posix_acl() {
+        let mut value = 2_u32.to_le_bytes().to_vec();
+        for (tag, permissions, id) in [(0x01, 6, u32::MAX), (0x02, 4, 1000), (0x20, 0, u32::MAX)] {
+            value.extend(u16::to_le_bytes(tag));
+            value.extend(u16::to_le_bytes(permissions));
+            value.extend(u32::to_le_bytes(id));
+        }
+
+        assert_eq!(
+            parse_posix_acl(&value),
+            Some(vec![
+                AclEntry {
+                    tag: AclTag::Owner,
+                    permissions: 6,
+                },
+                AclEntry {
+                    tag: AclTag::User(1000),
+                    permissions: 4,
+                },
+                AclEntry {
+                    tag: AclTag::Other,
+                    permissions: 0,
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn posix_acl_bad_version() {
+        assert_eq!(parse_posix_acl(&[1, 0, 0, 0]), None);
+        assert_eq!(parse_posix_acl(&[2, 0]), None);
+    }
+}
@@ -14,6 +14,13 @@
 #![allow(non_camel_case_types)]
 #![allow(clippy::struct_excessive_bools)]
 
+use std::path::PathBuf;
+
+use chrono::NaiveDateTime;
+
+#[cfg(unix)]
+use crate::fs::feature::acl::AclKind;
+
 /// The type of a file’s group ID.
 pub type gid_t = u32;
 
@@ -49,6 +56,7 @@ pub enum Type {
     Socket,
     CharDevice,
     BlockDevice,
+    Door,
     Special,
 }
 
@@ -80,6 +88,7 @@ pub struct Permissions {
 }
 
 /// The file's `FileAttributes` field, available only on Windows.
+#[cfg(windows)]
 #[derive(Copy, Clone)]
 #[rustfmt::skip]
 pub struct Attributes {
@@ -88,7 +97,27 @@ pub struct Attributes {
     pub readonly:        bool,
     pub hidden:          bool,
     pub system:          bool,
-    pub reparse_point:   bool,
+    pub reparse:         Option<ReparseKind>,
+}
+
+/// Which kind of reparse point a file is on Windows, for the kinds that get
+/// shown differently from one another.
+#[cfg(windows)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum ReparseKind {
+    /// A symbolic link, to either a file or a directory.
+    Symlink,
+
+    /// A directory junction, which can only lead to a directory on a local
+    /// volume.
+    Junction,
+
+    /// An app execution alias, which runs an app installed from the Store.
+    AppExecLink,
+
+    /// Anything else, such as a placeholder for a file kept in the cloud,
+    /// which gets treated like the file it stands in for.
+    Other,
 }
 
 /// The three pieces of information that are displayed as a single column in
@@ -101,7 +130,36 @@ pub struct PermissionsPlus {
     pub permissions: Permissions,
     #[cfg(windows)]
     pub attributes: Attributes,
+    #[cfg(windows)]
+    pub access: Option<AccessRights>,
     pub xattrs: bool,
+    #[cfg(unix)]
+    pub acl: Option<AclKind>,
+    #[cfg(target_os = "macos")]
+    pub finder_hidden: bool,
+}
+
+/// What the current user is allowed to do with a file on Windows, as
+/// granted by the access control list in its security descriptor.
+#[cfg(windows)]
+#[derive(Copy, Clone)]
+pub struct AccessRights {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+/// The account that owns a file on Windows, named as `DOMAIN\User`.
+#[cfg(windows)]
+pub struct Owner {
+    pub name: String,
+
+    /// Whether this is the user running eza.
+    pub is_you: bool,
+
+    /// Whether this is the local system account or the administrators
+    /// group, which are the Windows counterparts to root.
+    pub is_admin: bool,
 }
 
 /// The permissions encoded as octal values
@@ -125,6 +183,18 @@ pub struct Links {
     pub multiple: bool,
 }
 
+/// Where a regular file with more than one hard link is kept, which every
+/// link to the same file shares, so the links to it in a listing can be
+/// picked out.
+#[derive(PartialEq, Eq, Hash, Debug, Copy, Clone)]
+pub struct HardLink {
+    /// The device and inode the links point to.
+    pub id: (u64, u64),
+
+    /// How many links there are to the file.
+    pub count: nlink_t,
+}
+
 /// A file’s inode. Every directory entry on a Unix filesystem has an inode,
 /// including directories and links, so this is applicable to everything exa
 /// can deal with.
@@ -246,6 +316,65 @@ impl Default for Git {
     }
 }
 
+/// Where a file that Git has tracked a rename for was renamed from, as paths
+/// inside the repository’s working directory, along with how similar its
+/// contents are to what they were before each rename, as a percentage.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct GitRename {
+    pub from: PathBuf,
+    pub to: PathBuf,
+
+    /// The similarity between the file in the last commit and the staged
+    /// file, if the rename has been staged.
+    pub staged_similarity: Option<u8>,
+
+    /// The similarity between the staged file and the file in the working
+    /// directory, if the rename hasn’t been staged.
+    pub unstaged_similarity: Option<u8>,
+}
+
+/// Whether a file has changed since the Git reference given to
+/// `--git-age-since`, going by a diff between the reference’s tree and the
+/// working directory, and if so, when it was last modified.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub enum ChangedSince {
+    /// The file isn’t in a repository, or the reference couldn’t be found
+    /// in the one it’s in.
+    #[default]
+    Unknown,
+
+    /// The file is the same as it was in the reference.
+    Unchanged,
+
+    /// The file has changed since the reference, or is new. A directory has
+    /// changed if anything inside it has, and the time is the latest time
+    /// any of those files were modified.
+    Changed(Option<NaiveDateTime>),
+}
+
+/// Which changes inside a Git submodule count towards its status, going by
+/// the same settings as Git’s own `--ignore-submodules` option.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub enum SubmoduleIgnore {
+    /// Use whatever each submodule’s `ignore` setting in the configuration
+    /// says, which usually counts everything.
+    #[default]
+    Config,
+
+    /// Count any change to a submodule, including untracked files in it.
+    None,
+
+    /// Count changes to files the submodule tracks, but not untracked files.
+    Untracked,
+
+    /// Only count a submodule as changed if its checked-out commit has moved.
+    Dirty,
+
+    /// Never count a submodule as changed, and don’t look for statuses
+    /// inside it when recursing.
+    All,
+}
+
 pub enum SecurityContextType<'a> {
     SELinux(&'a str),
     None,
@@ -267,6 +396,10 @@ pub enum SubdirGitRepoStatus {
 pub struct SubdirGitRepo {
     pub status: Option<SubdirGitRepoStatus>,
     pub branch: Option<String>,
+
+    /// How many commits the branch is behind its upstream, going by the
+    /// remote-tracking refs from the last fetch.
+    pub behind: Option<usize>,
 }
 
 impl Default for SubdirGitRepo {
@@ -274,10 +407,43 @@ impl Default for SubdirGitRepo {
         Self {
             status: Some(SubdirGitRepoStatus::NoRepo),
             branch: None,
+            behind: None,
         }
     }
 }
 
+/// Where a symlink ends up once every link along the way has been
+/// followed, rather than just the first.
+#[derive(PartialEq, Eq, Debug)]
+pub enum LinkChain {
+    /// This file isn’t a symlink.
+    NotALink,
+
+    /// The chain ends at this path after following this many links. The
+    /// path might not exist, in which case the link is broken.
+    Resolved {
+        path: PathBuf,
+        links: usize,
+        exists: bool,
+    },
+
+    /// The chain leads back to a link it had already been through, after
+    /// following this many links.
+    Loop { links: usize },
+}
+
+/// How much space the owner of a file is using on its filesystem, when the
+/// filesystem keeps count with quotas.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Quota {
+    /// The filesystem doesn’t have quotas, or the owner’s couldn’t be read.
+    None,
+
+    /// The owner is using this many bytes, out of the limit they’ve been
+    /// given, if there is one.
+    Usage { used: u64, limit: Option<u64> },
+}
+
 /// The user file flags on the file. This will only ever be a number;
 /// looking up the flags is done in the `display` module.
 pub struct Flags(pub flag_t);
@@ -1,10 +1,12 @@
 //! Files, and methods and fields to access their metadata.
 
+#[cfg(unix)]
+use std::borrow::Cow;
 #[cfg(unix)]
 use std::collections::HashMap;
 use std::io;
 #[cfg(unix)]
-use std::os::unix::fs::{FileTypeExt, MetadataExt, PermissionsExt};
+use std::os::unix::fs::MetadataExt;
 #[cfg(windows)]
 use std::os::windows::fs::MetadataExt;
 use std::path::{Path, PathBuf};
@@ -21,24 +23,47 @@ use log::*;
 use once_cell::sync::Lazy;
 
 use crate::fs::dir::Dir;
+use crate::fs::feature::acl;
+#[cfg(unix)]
+use crate::fs::feature::quota;
+use crate::fs::feature::reflink;
+#[cfg(windows)]
+use crate::fs::feature::reparse;
+use crate::fs::feature::special_dirs::{SpecialDir, UserDirs};
+use crate::fs::feature::trash::{self, TrashInfo};
 use crate::fs::feature::xattr;
-use crate::fs::feature::xattr::{Attribute, FileAttributes};
+use crate::fs::feature::xattr::{Attribute, FileAttributes, Tag};
 use crate::fs::fields as f;
 use crate::fs::fields::SecurityContextType;
+use crate::fs::metadata::{Field, Kind, Metadata};
 use crate::fs::recursive_size::RecursiveSize;
+use crate::timeout::Deadline;
+use crate::timing::{self, Phase};
 
 use super::mounts::all_mounts;
 use super::mounts::MountedFs;
 
-// Maps (device_id, inode) => (size_in_bytes, size_in_blocks)
+// Maps (device_id, inode) => totals of everything under that directory
 // Mutex::new is const but HashMap::new is not const requiring us to use lazy
 // initialization.
 // TODO: Replace with std::sync::LazyLock when it is stable.
-#[allow(clippy::type_complexity)]
 #[cfg(unix)]
-static DIRECTORY_SIZE_CACHE: Lazy<Mutex<HashMap<(u64, u64), (u64, u64)>>> =
+static DIRECTORY_SIZE_CACHE: Lazy<Mutex<HashMap<(u64, u64), SubtreeTotals>>> =
     Lazy::new(|| Mutex::new(HashMap::new()));
 
+/// What everything underneath a directory adds up to.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct SubtreeTotals {
+    /// The combined size of every file, in bytes.
+    pub size: u64,
+
+    /// The combined number of 512-byte blocks allocated to every file.
+    pub blocks: u64,
+
+    /// How many things that aren’t directories there are, at any depth.
+    pub files: u64,
+}
+
 /// A **File** is a wrapper around one of Rust’s `PathBuf` values, along with
 /// associated data about the file.
 ///
@@ -72,7 +97,7 @@ pub struct File<'dir> {
     /// This too is queried multiple times, and is *not* cached by the OS, as
     /// it could easily change between invocations — but exa is so short-lived
     /// it’s better to just cache it.
-    pub metadata: std::fs::Metadata,
+    pub metadata: Metadata,
 
     /// A reference to the directory that contains this file, if any.
     ///
@@ -106,6 +131,17 @@ pub struct File<'dir> {
 
     /// The absolute value of this path, used to look up mount points.
     absolute_path: OnceLock<Option<PathBuf>>,
+
+    /// Where this file came from, if it’s in the trash.
+    trash_info: OnceLock<Option<TrashInfo>>,
+
+    /// Which of the user’s special directories this is, if it’s one.
+    special_dir: OnceLock<Option<SpecialDir>>,
+
+    /// The owner and access rights from this file’s security descriptor,
+    /// which both the user and permissions columns need.
+    #[cfg(windows)]
+    security: OnceLock<Option<acl::Security>>,
 }
 
 impl<'dir> File<'dir> {
@@ -121,11 +157,47 @@ impl<'dir> File<'dir> {
         FN: Into<Option<String>>,
     {
         let parent_dir = parent_dir.into();
-        let name = filename.into().unwrap_or_else(|| File::filename(&path));
-        let ext = File::ext(&path);
 
         debug!("Statting file {:?}", &path);
-        let metadata = std::fs::symlink_metadata(&path)?;
+        let deadline = parent_dir.map_or_else(Deadline::default, |dir| dir.deadline.clone());
+        let metadata = match timing::time(Phase::Stat, || {
+            File::symlink_metadata(&path, parent_dir, &deadline)
+        }) {
+            // The file’s name is known even if nothing else about it is, so
+            // it still gets listed, with `?` for everything else.
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+                debug!("Timed out statting file {:?}", &path);
+                Metadata::TimedOut
+            }
+            result => result?,
+        };
+        Ok(File::with_metadata(
+            path,
+            parent_dir,
+            filename,
+            metadata,
+            deref_links,
+            total_size,
+        ))
+    }
+
+    /// Like `from_args`, for a file whose metadata has already been looked
+    /// up, such as along with the rest of its directory’s.
+    pub fn with_metadata<PD, FN>(
+        path: PathBuf,
+        parent_dir: PD,
+        filename: FN,
+        metadata: Metadata,
+        deref_links: bool,
+        total_size: bool,
+    ) -> File<'dir>
+    where
+        PD: Into<Option<&'dir Dir>>,
+        FN: Into<Option<String>>,
+    {
+        let parent_dir = parent_dir.into();
+        let name = filename.into().unwrap_or_else(|| File::filename(&path));
+        let ext = File::ext(&path);
         let is_all_all = false;
         let extended_attributes = OnceLock::new();
         let absolute_path = OnceLock::new();
@@ -146,13 +218,17 @@ impl<'dir> File<'dir> {
             recursive_size,
             extended_attributes,
             absolute_path,
+            trash_info: OnceLock::new(),
+            special_dir: OnceLock::new(),
+            #[cfg(windows)]
+            security: OnceLock::new(),
         };
 
         if total_size {
             file.recursive_size = file.recursive_directory_size();
         }
 
-        Ok(file)
+        file
     }
 
     fn new_aa(
@@ -164,7 +240,7 @@ impl<'dir> File<'dir> {
         let ext = File::ext(&path);
 
         debug!("Statting file {:?}", &path);
-        let metadata = std::fs::symlink_metadata(&path)?;
+        let metadata = File::symlink_metadata(&path, Some(parent_dir), &parent_dir.deadline)?;
         let is_all_all = true;
         let parent_dir = Some(parent_dir);
         let extended_attributes = OnceLock::new();
@@ -185,6 +261,10 @@ impl<'dir> File<'dir> {
             deref_links: false,
             extended_attributes,
             absolute_path,
+            trash_info: OnceLock::new(),
+            special_dir: OnceLock::new(),
+            #[cfg(windows)]
+            security: OnceLock::new(),
             recursive_size,
         };
 
@@ -207,6 +287,49 @@ impl<'dir> File<'dir> {
         File::new_aa(path, parent_dir, "..", total_size)
     }
 
+    /// Gets the metadata of the file at the path without following it if
+    /// it’s a link, from wherever its directory was read from. Taking longer
+    /// than `--io-timeout` allows is an error of its own kind.
+    fn symlink_metadata(
+        path: &Path,
+        parent_dir: Option<&Dir>,
+        deadline: &Deadline,
+    ) -> io::Result<Metadata> {
+        #[cfg(unix)]
+        if let Some(source) = parent_dir.and_then(Dir::source) {
+            return source
+                .symlink_metadata(path)
+                .map(|fields| Metadata::remote(fields, source));
+        }
+
+        #[cfg(not(unix))]
+        let _ = parent_dir;
+
+        let path = path.to_path_buf();
+        deadline.io(move || std::fs::symlink_metadata(path).map(Metadata::from))
+    }
+
+    /// Whether this file is on this computer’s filesystem, rather than read
+    /// from elsewhere. The things that only this computer’s filesystem knows
+    /// about, such as extended attributes and mount points, only get looked
+    /// up for files that are.
+    #[cfg(unix)]
+    pub fn is_local(&self) -> bool {
+        self.metadata.source().is_none()
+    }
+
+    #[cfg(not(unix))]
+    pub fn is_local(&self) -> bool {
+        true
+    }
+
+    /// The deadline that calls to look things up about this file share
+    /// with the rest of its directory.
+    fn deadline(&self) -> Deadline {
+        self.parent_dir
+            .map_or_else(Deadline::default, |dir| dir.deadline.clone())
+    }
+
     /// A file’s name is derived from its string. This needs to handle directories
     /// such as `/` or `..`, which have no `file_name` component. So instead, just
     /// use the last component as the name.
@@ -236,12 +359,16 @@ impl<'dir> File<'dir> {
 
     /// Read the extended attributes of a file path.
     fn gather_extended_attributes(&self) -> Vec<Attribute> {
-        if xattr::ENABLED {
-            let attributes = if self.deref_links {
-                self.path.attributes()
-            } else {
-                self.path.symlink_attributes()
-            };
+        if xattr::ENABLED && self.is_local() {
+            let path = self.path.clone();
+            let deref_links = self.deref_links;
+            let attributes = self.deadline().io(move || {
+                if deref_links {
+                    path.attributes()
+                } else {
+                    path.symlink_attributes()
+                }
+            });
             match attributes {
                 Ok(xattrs) => xattrs,
                 Err(e) => {
@@ -261,7 +388,62 @@ impl<'dir> File<'dir> {
     /// Get the extended attributes of a file path on demand.
     pub fn extended_attributes(&self) -> &Vec<Attribute> {
         self.extended_attributes
-            .get_or_init(|| self.gather_extended_attributes())
+            .get_or_init(|| timing::time(Phase::Xattr, || self.gather_extended_attributes()))
+    }
+
+    /// Whether the Finder hides this file, either because it has the
+    /// `hidden` flag set with `chflags`, or because it’s marked as invisible
+    /// in its Finder info. These get treated like dotfiles.
+    #[cfg(target_os = "macos")]
+    pub fn is_finder_hidden(&self) -> bool {
+        use std::os::macos::fs::MetadataExt;
+
+        let flags = self.metadata.as_std().map_or(0, MetadataExt::st_flags);
+        flags & libc::UF_HIDDEN != 0
+            || (self.is_local() && xattr::is_finder_invisible(&self.path, self.deref_links))
+    }
+
+    /// Which flavour of access control list this file has, if it has one
+    /// that gives any permissions beyond its mode.
+    #[cfg(unix)]
+    pub fn acl_kind(&self) -> Option<acl::AclKind> {
+        if !self.is_local() {
+            return None;
+        }
+
+        // Linux keeps them in extended attributes, which have already been
+        // listed, so there’s no need to ask for them if there aren’t any.
+        #[cfg(target_os = "linux")]
+        if !self
+            .extended_attributes()
+            .iter()
+            .any(|a| xattr::is_acl(&a.name))
+        {
+            return None;
+        }
+
+        timing::time(Phase::Xattr, || acl::kind(&self.path, self.deref_links))
+    }
+
+    /// The entries of this file’s `NFSv4` access control list, if it has one.
+    #[cfg(unix)]
+    pub fn nfs4_acl(&self) -> Vec<String> {
+        if !self.is_local() {
+            return Vec::new();
+        }
+
+        timing::time(Phase::Xattr, || {
+            acl::nfs4_entries(&self.path, self.deref_links)
+        })
+    }
+
+    /// The tags that the user has given this file.
+    pub fn tags(&self) -> Vec<Tag> {
+        if !self.is_local() {
+            return Vec::new();
+        }
+
+        xattr::tags(&self.path, self.deref_links)
     }
 
     /// Whether this file is a directory on the filesystem.
@@ -293,9 +475,30 @@ impl<'dir> File<'dir> {
     /// if a `File` is a directory or not! For that, just use `is_directory()`.
     pub fn to_dir(&self) -> io::Result<Dir> {
         trace!("to_dir: reading dir");
+        match self.parent_dir {
+            Some(parent) => parent.read_subdir(self.path.clone()),
+            None => self.read_as_dir(),
+        }
+    }
+
+    /// Reads this file as a directory on its own, from wherever it came from.
+    fn read_as_dir(&self) -> io::Result<Dir> {
+        #[cfg(unix)]
+        if let Some(source) = self.metadata.source() {
+            return Dir::read_from(source, self.path.clone());
+        }
+
         Dir::read_dir(self.path.clone())
     }
 
+    /// Whether descending into this file would lead back to its own parent
+    /// directory or to one above it, as happens with a symlink such as
+    /// `loop -> ..`.
+    pub fn is_loop(&self) -> bool {
+        self.parent_dir
+            .is_some_and(|dir| dir.is_ancestor(&self.path))
+    }
+
     /// Whether this file is a regular file on the filesystem — that is, not a
     /// directory, a link, or anything else treated specially.
     pub fn is_file(&self) -> bool {
@@ -308,36 +511,123 @@ impl<'dir> File<'dir> {
     #[cfg(unix)]
     pub fn is_executable_file(&self) -> bool {
         let bit = modes::USER_EXECUTE;
-        self.is_file() && (self.metadata.permissions().mode() & bit) == bit
+        self.is_file() && (self.metadata.mode() & bit) == bit
+    }
+
+    /// Whether this file is a regular file with its setuid bit set, so it
+    /// runs as the user that owns it.
+    #[cfg(unix)]
+    pub fn is_setuid_file(&self) -> bool {
+        self.is_file() && (self.metadata.mode() & modes::SETUID) == modes::SETUID
+    }
+
+    /// Whether this file is a regular file with its setgid bit set, so it
+    /// runs as the group that owns it.
+    #[cfg(unix)]
+    pub fn is_setgid_file(&self) -> bool {
+        self.is_file() && (self.metadata.mode() & modes::SETGID) == modes::SETGID
+    }
+
+    /// Whether this file is a door, which Solaris and illumos use for calling
+    /// procedures in other processes.
+    #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+    pub fn is_door(&self) -> bool {
+        const S_IFMT: u32 = 0o170_000;
+        const S_IFDOOR: u32 = 0o150_000;
+        (self.metadata.mode() & S_IFMT) == S_IFDOOR
     }
 
-    /// Whether this file is a symlink on the filesystem.
+    /// Whether this file is a symlink on the filesystem. On Windows, this
+    /// includes junctions and app execution aliases, which lead to other
+    /// files too.
     pub fn is_link(&self) -> bool {
-        self.metadata.file_type().is_symlink()
+        #[cfg(windows)]
+        if self.reparse_kind() == Some(f::ReparseKind::AppExecLink) {
+            return true;
+        }
+
+        self.metadata.is_symlink()
+    }
+
+    /// Which kind of reparse point this file is, if it’s one at all.
+    #[cfg(windows)]
+    pub fn reparse_kind(&self) -> Option<f::ReparseKind> {
+        const FILE_ATTRIBUTE_REPARSE_POINT: u32 = 0x400;
+
+        let bits = self
+            .metadata
+            .as_std()
+            .map_or(0, MetadataExt::file_attributes);
+        if bits & FILE_ATTRIBUTE_REPARSE_POINT == 0 {
+            return None;
+        }
+
+        reparse::kind(&self.path)
+    }
+
+    /// Reads where this link leads. Junctions keep the paths they lead to in
+    /// their verbatim form, and app execution aliases can’t be read as links
+    /// at all, so on Windows, those are read from their reparse data.
+    #[cfg(unix)]
+    pub fn read_link(&self) -> io::Result<PathBuf> {
+        match self.metadata.source() {
+            Some(source) => source.read_link(&self.path),
+            None => std::fs::read_link(&self.path),
+        }
+    }
+
+    #[cfg(windows)]
+    pub fn read_link(&self) -> io::Result<PathBuf> {
+        match self.reparse_kind() {
+            Some(f::ReparseKind::AppExecLink) => reparse::app_exec_target(&self.path),
+            Some(f::ReparseKind::Junction) => {
+                std::fs::read_link(&self.path).map(reparse::strip_verbatim)
+            }
+            _ => std::fs::read_link(&self.path),
+        }
     }
 
     /// Whether this file is a named pipe on the filesystem.
     #[cfg(unix)]
     pub fn is_pipe(&self) -> bool {
-        self.metadata.file_type().is_fifo()
+        self.metadata.kind() == Kind::Pipe
     }
 
     /// Whether this file is a char device on the filesystem.
     #[cfg(unix)]
     pub fn is_char_device(&self) -> bool {
-        self.metadata.file_type().is_char_device()
+        self.metadata.kind() == Kind::CharDevice
     }
 
     /// Whether this file is a block device on the filesystem.
     #[cfg(unix)]
     pub fn is_block_device(&self) -> bool {
-        self.metadata.file_type().is_block_device()
+        self.metadata.kind() == Kind::BlockDevice
     }
 
     /// Whether this file is a socket on the filesystem.
     #[cfg(unix)]
     pub fn is_socket(&self) -> bool {
-        self.metadata.file_type().is_socket()
+        self.metadata.kind() == Kind::Socket
+    }
+
+    /// Where this file was before it was moved to the trash, and when that
+    /// happened, if it’s in a trash directory.
+    pub fn trash_info(&self) -> Option<&TrashInfo> {
+        self.trash_info
+            .get_or_init(|| self.is_local().then(|| trash::info_for(&self.path))?)
+            .as_ref()
+    }
+
+    /// Which of the user’s special directories this is, such as their home
+    /// directory or their Downloads directory, if it’s one.
+    pub fn special_dir(&self, user_dirs: &UserDirs) -> Option<SpecialDir> {
+        *self.special_dir.get_or_init(|| {
+            if !self.is_directory() || !self.is_local() {
+                return None;
+            }
+            user_dirs.lookup(self.absolute_path()?, &self.path)
+        })
     }
 
     /// Determine the full path resolving all symbolic links on demand.
@@ -350,19 +640,36 @@ impl<'dir> File<'dir> {
                     // (including the last one) to exist
                     self.path
                         .parent()
-                        .and_then(|parent| std::fs::canonicalize(parent).ok())
+                        .and_then(|parent| self.canonicalize(parent).ok())
                         .map(|p| p.join(self.name.clone()))
                 } else {
-                    std::fs::canonicalize(&self.path).ok()
+                    self.canonicalize(&self.path).ok()
                 }
             })
             .as_ref()
     }
 
+    /// Resolves a path in the same place as this file, which is somewhere
+    /// other than this computer’s filesystem if this file is.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf> {
+        #[cfg(unix)]
+        if let Some(source) = self.metadata.source() {
+            return source.canonicalize(path);
+        }
+
+        std::fs::canonicalize(path)
+    }
+
     /// Whether this file is a mount point
     pub fn is_mount_point(&self) -> bool {
-        cfg!(any(target_os = "linux", target_os = "macos"))
-            && self.is_directory()
+        cfg!(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )) && self.is_directory()
+            && self.is_local()
             && self
                 .absolute_path()
                 .is_some_and(|p| all_mounts().contains_key(p))
@@ -370,7 +677,14 @@ impl<'dir> File<'dir> {
 
     /// The filesystem device and type for a mount point
     pub fn mount_point_info(&self) -> Option<&MountedFs> {
-        if cfg!(any(target_os = "linux", target_os = "macos")) {
+        if cfg!(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        )) && self.is_local()
+        {
             return self.absolute_path().and_then(|p| all_mounts().get(p));
         }
         None
@@ -391,6 +705,38 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// Assuming this file is a broken symlink, looks through the directory
+    /// its target would be in for a file with a similar name, in case the
+    /// target was renamed. This reads the whole directory, so it’s only done
+    /// when asked for.
+    pub fn suggest_link_target(&self) -> Option<String> {
+        let path = self.read_link().ok()?;
+        let absolute_path = self.reorient_target_path(&path);
+        let missing = absolute_path.file_name()?.to_string_lossy().into_owned();
+        let dir = match absolute_path.parent() {
+            Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+            Some(parent) => parent,
+            None => return None,
+        };
+
+        #[cfg(unix)]
+        if let Some(source) = self.metadata.source() {
+            let names = source
+                .read_dir(dir)
+                .ok()?
+                .into_iter()
+                .map(|(path, _)| File::filename(&path));
+            return closest_name(&missing, names);
+        }
+
+        let names = std::fs::read_dir(dir)
+            .ok()?
+            .filter_map(Result::ok)
+            .map(|entry| entry.file_name().to_string_lossy().into_owned());
+
+        closest_name(&missing, names)
+    }
+
     /// Again assuming this file is a symlink, follows that link and returns
     /// the result of following it.
     ///
@@ -407,7 +753,7 @@ impl<'dir> File<'dir> {
         // we actually look up and turn into a `File` — which needs to be
         // absolute to be accessible from any directory.
         debug!("Reading link {:?}", &self.path);
-        let path = match std::fs::read_link(&self.path) {
+        let path = match self.read_link() {
             Ok(p) => p,
             Err(e) => return FileTarget::Err(e),
         };
@@ -416,7 +762,7 @@ impl<'dir> File<'dir> {
 
         // Use plain `metadata` instead of `symlink_metadata` - we *want* to
         // follow links.
-        match std::fs::metadata(&absolute_path) {
+        match self.target_metadata(&absolute_path) {
             Ok(metadata) => {
                 let ext = File::ext(&path);
                 let name = File::filename(&path);
@@ -432,10 +778,15 @@ impl<'dir> File<'dir> {
                     deref_links: self.deref_links,
                     extended_attributes,
                     absolute_path: absolute_path_cell,
+                    trash_info: OnceLock::new(),
+                    special_dir: OnceLock::new(),
+                    #[cfg(windows)]
+                    security: OnceLock::new(),
                     recursive_size: RecursiveSize::None,
                 };
                 FileTarget::Ok(Box::new(file))
             }
+            Err(e) if e.kind() == io::ErrorKind::TimedOut => FileTarget::Err(e),
             Err(e) => {
                 error!("Error following link {:?}: {:#?}", &path, e);
                 FileTarget::Broken(path)
@@ -443,6 +794,21 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// The metadata of whatever’s at the path, following any symlinks, from
+    /// the same place as this file.
+    fn target_metadata(&self, path: &Path) -> io::Result<Metadata> {
+        #[cfg(unix)]
+        if let Some(source) = self.metadata.source() {
+            return source
+                .metadata(path)
+                .map(|fields| Metadata::remote(fields, source));
+        }
+
+        let path = path.to_path_buf();
+        self.deadline()
+            .io(move || std::fs::metadata(path).map(Metadata::from))
+    }
+
     /// Assuming this file is a symlink, follows that link and any further
     /// links recursively, returning the result from following the trail.
     ///
@@ -464,6 +830,38 @@ impl<'dir> File<'dir> {
         target
     }
 
+    /// Follows this file, if it’s a symlink, through every link in the chain
+    /// to wherever it finally ends up, stopping if it comes back round to a
+    /// link it’s already been through. This looks at the file on disk rather
+    /// than its metadata, so it works the same when links are dereferenced.
+    pub fn link_chain(&self) -> f::LinkChain {
+        #[cfg(unix)]
+        if let Some(source) = self.metadata.source() {
+            return follow_links(
+                &self.path,
+                |path| {
+                    let metadata = Metadata::remote(source.symlink_metadata(path)?, source);
+                    Ok(metadata.is_symlink().then(|| normalize(path)))
+                },
+                |path| source.read_link(path),
+                &|path| source.canonicalize(path),
+            );
+        }
+
+        follow_links(
+            &self.path,
+            |path| {
+                let metadata = std::fs::symlink_metadata(path)?;
+                Ok(metadata
+                    .file_type()
+                    .is_symlink()
+                    .then(|| link_id(path, &metadata)))
+            },
+            |path| std::fs::read_link(path),
+            &|path| std::fs::canonicalize(path),
+        )
+    }
+
     /// This file’s number of hard links.
     ///
     /// It also reports whether this is both a regular file, and a file with
@@ -481,12 +879,54 @@ impl<'dir> File<'dir> {
         }
     }
 
+    /// Whether this is a regular file that shares data blocks with a clone
+    /// of it, or with the file it was cloned from, rather than being a copy
+    /// with blocks of its own.
+    pub fn is_clone(&self) -> bool {
+        self.is_file() && self.is_local() && reflink::shares_blocks(&self.path)
+    }
+
+    /// Where this file is kept and how many links there are to it, if it’s
+    /// a regular file with more than one. Without an inode number, there’s
+    /// no telling which other files it’s linked to.
+    #[cfg(unix)]
+    pub fn hard_link(&self) -> Option<f::HardLink> {
+        let links = self.links();
+        let known = self.metadata.has(Field::Inode);
+        (links.multiple && known).then(|| f::HardLink {
+            id: (self.metadata.dev(), self.metadata.ino()),
+            count: links.count,
+        })
+    }
+
+    /// Windows doesn’t expose file IDs or link counts on stable Rust.
+    #[cfg(windows)]
+    pub fn hard_link(&self) -> Option<f::HardLink> {
+        None
+    }
+
     /// This file’s inode.
     #[cfg(unix)]
     pub fn inode(&self) -> f::Inode {
         f::Inode(self.metadata.ino())
     }
 
+    /// The device that this file is on, rather than the one it refers to if
+    /// it’s a device file itself.
+    #[cfg(unix)]
+    pub fn device(&self) -> f::DeviceIDs {
+        let device_id = self.metadata.dev();
+
+        // See the note in `size` about these casts.
+        #[allow(trivial_numeric_casts)]
+        #[allow(clippy::unnecessary_cast, clippy::useless_conversion)]
+        f::DeviceIDs {
+            // SAFETY: Calling libc function to decompose the device_id
+            major: unsafe { libc::major(device_id.try_into().unwrap()) } as u32,
+            minor: unsafe { libc::minor(device_id.try_into().unwrap()) } as u32,
+        }
+    }
+
     /// This actual size the file takes up on disk, in bytes.
     #[cfg(unix)]
     pub fn blocksize(&self) -> f::Blocksize {
@@ -499,7 +939,7 @@ impl<'dir> File<'dir> {
             self.recursive_size.map_or(f::Blocksize::None, |_, blocks| {
                 f::Blocksize::Some(blocks * 512)
             })
-        } else if self.is_file() {
+        } else if self.is_file() && self.metadata.has(Field::Blocks) {
             // Note that metadata.blocks returns the number of blocks
             // for 512 byte blocks according to the POSIX standard
             // even though the physical block size may be different.
@@ -510,29 +950,64 @@ impl<'dir> File<'dir> {
         }
     }
 
-    /// The ID of the user that own this file. If dereferencing links, the links
-    /// may be broken, in which case `None` will be returned.
+    /// The metadata that this file’s owner, group, and permissions come
+    /// from. For a symlink, that’s the link’s own, from `lstat`, unless links
+    /// are being dereferenced and their owners aren’t being kept, as they
+    /// are with `--no-dereference-owner`, in which case it’s the metadata of
+    /// whatever’s at the end of the chain of links. If the chain is broken,
+    /// there’s none.
     #[cfg(unix)]
-    pub fn user(&self) -> Option<f::User> {
-        if self.is_link() && self.deref_links {
-            return match self.link_target_recurse() {
-                FileTarget::Ok(f) => f.user(),
-                _ => None,
+    pub fn owner_metadata(&self, keep_link_owners: bool) -> Option<Cow<'_, Metadata>> {
+        if self.is_link() && self.deref_links && !keep_link_owners {
+            return match self.target_metadata(&self.path) {
+                Ok(metadata) => Some(Cow::Owned(metadata)),
+                Err(e) => {
+                    debug!("Error following link {:?}: {e}", &self.path);
+                    None
+                }
             };
         }
-        Some(f::User(self.metadata.uid()))
+        Some(Cow::Borrowed(&self.metadata))
+    }
+
+    /// The ID of the user that own this file. If dereferencing links, the links
+    /// may be broken, in which case `None` will be returned.
+    #[cfg(unix)]
+    pub fn user(&self, keep_link_owners: bool) -> Option<f::User> {
+        self.owner_metadata(keep_link_owners)
+            .filter(|metadata| metadata.has(Field::Owner))
+            .map(|metadata| f::User(metadata.uid()))
+    }
+
+    /// The account that owns this file, looked up from the owner SID in its
+    /// security descriptor.
+    #[cfg(windows)]
+    pub fn owner(&self) -> Option<&f::Owner> {
+        self.security().and_then(|security| security.owner.as_ref())
+    }
+
+    /// What the current user may do with this file, according to its access
+    /// control list.
+    #[cfg(windows)]
+    pub fn access_rights(&self) -> Option<f::AccessRights> {
+        self.security().and_then(|security| security.access)
+    }
+
+    /// The parts of this file’s security descriptor that get displayed,
+    /// read the first time they’re needed.
+    #[cfg(windows)]
+    fn security(&self) -> Option<&acl::Security> {
+        self.security
+            .get_or_init(|| acl::security(&self.path))
+            .as_ref()
     }
 
     /// The ID of the group that owns this file.
     #[cfg(unix)]
-    pub fn group(&self) -> Option<f::Group> {
-        if self.is_link() && self.deref_links {
-            return match self.link_target_recurse() {
-                FileTarget::Ok(f) => f.group(),
-                _ => None,
-            };
-        }
-        Some(f::Group(self.metadata.gid()))
+    pub fn group(&self, keep_link_owners: bool) -> Option<f::Group> {
+        self.owner_metadata(keep_link_owners)
+            .filter(|metadata| metadata.has(Field::Owner))
+            .map(|metadata| f::Group(metadata.gid()))
     }
 
     /// This file’s size, if it’s a regular file.
@@ -570,10 +1045,10 @@ impl<'dir> File<'dir> {
                 major: unsafe { libc::major(device_id.try_into().unwrap()) } as u32,
                 minor: unsafe { libc::minor(device_id.try_into().unwrap()) } as u32,
             })
-        } else if self.is_file() {
+        } else if self.is_file() && self.metadata.has(Field::Size) {
             f::Size::Some(self.metadata.len())
         } else {
-            // symlink
+            // symlink, or a file whose size isn’t known
             f::Size::None
         }
     }
@@ -597,40 +1072,60 @@ impl<'dir> File<'dir> {
     #[cfg(unix)]
     fn recursive_directory_size(&self) -> RecursiveSize {
         if self.is_directory() {
-            let key = (self.metadata.dev(), self.metadata.ino());
-            if let Some(size) = DIRECTORY_SIZE_CACHE.lock().unwrap().get(&key) {
-                return RecursiveSize::Some(size.0, size.1);
-            }
-            Dir::read_dir(self.path.clone()).map_or(RecursiveSize::Unknown, |dir| {
-                let mut size = 0;
-                let mut blocks = 0;
-                for file in dir
-                    .files(super::DotFilter::Dotfiles, None, false, false, true)
-                    .flatten()
-                {
-                    match file.recursive_directory_size() {
-                        RecursiveSize::Some(bytes, blks) => {
-                            size += bytes;
-                            blocks += blks;
-                        }
-                        RecursiveSize::Unknown => {}
-                        RecursiveSize::None => {
-                            size += file.metadata.size();
-                            blocks += file.metadata.blocks();
-                        }
-                    }
-                }
-                DIRECTORY_SIZE_CACHE
-                    .lock()
-                    .unwrap()
-                    .insert(key, (size, blocks));
-                RecursiveSize::Some(size, blocks)
-            })
+            self.subtree_totals()
+                .map_or(RecursiveSize::Unknown, |totals| {
+                    RecursiveSize::Some(totals.size, totals.blocks)
+                })
         } else {
             RecursiveSize::None
         }
     }
 
+    /// Walk everything underneath this directory, adding up sizes and
+    /// counting files. Returns `None` if this isn’t a directory, or it can’t
+    /// be read. The totals of every directory passed through get cached, so
+    /// a tree only gets walked once no matter how many of its directories
+    /// are asked about.
+    #[cfg(unix)]
+    pub fn subtree_totals(&self) -> Option<SubtreeTotals> {
+        if !self.is_directory() || !self.is_local() {
+            return None;
+        }
+
+        let key = (self.metadata.dev(), self.metadata.ino());
+        if let Some(totals) = DIRECTORY_SIZE_CACHE.lock().unwrap().get(&key) {
+            return Some(*totals);
+        }
+
+        let dir = Dir::read_dir(self.path.clone()).ok()?;
+        let mut totals = SubtreeTotals::default();
+        for file in dir
+            .files(super::DotFilter::Dotfiles, None, false, false, true)
+            .flatten()
+        {
+            if file.is_directory() {
+                if let Some(inner) = file.subtree_totals() {
+                    totals.size += inner.size;
+                    totals.blocks += inner.blocks;
+                    totals.files += inner.files;
+                }
+            } else {
+                totals.size += file.metadata.len();
+                totals.blocks += file.metadata.blocks();
+                totals.files += 1;
+            }
+        }
+
+        DIRECTORY_SIZE_CACHE.lock().unwrap().insert(key, totals);
+        Some(totals)
+    }
+
+    /// Windows can’t cache totals by inode, so it doesn’t walk directories.
+    #[cfg(windows)]
+    pub fn subtree_totals(&self) -> Option<SubtreeTotals> {
+        None
+    }
+
     /// Windows version always returns None.  The metadata for
     /// `volume_serial_number` and `file_index` are marked unstable so we can
     /// not cache the sizes.  Without caching we could end up walking the
@@ -704,7 +1199,7 @@ impl<'dir> File<'dir> {
     /// make it difficult to get any info about a dir by it's size, so this may be it.
     fn is_empty_directory(&self) -> bool {
         trace!("is_empty_directory: reading dir");
-        match Dir::read_dir(self.path.clone()) {
+        match self.read_as_dir() {
             // . & .. are skipped, if the returned iterator has .next(), it's not empty
             Ok(has_files) => has_files
                 .files(super::DotFilter::Dotfiles, None, false, false, false)
@@ -737,7 +1232,10 @@ impl<'dir> File<'dir> {
                 _ => None,
             };
         }
-        NaiveDateTime::from_timestamp_opt(self.metadata.ctime(), self.metadata.ctime_nsec() as u32)
+        self.metadata
+            .changed()
+            .map(|st| DateTime::<Utc>::from(st).naive_utc())
+            .ok()
     }
 
     #[cfg(windows)]
@@ -795,13 +1293,20 @@ impl<'dir> File<'dir> {
         } else if self.is_socket() {
             f::Type::Socket
         } else {
+            #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+            if self.is_door() {
+                return f::Type::Door;
+            }
+
             f::Type::Special
         }
     }
 
     #[cfg(windows)]
     pub fn type_char(&self) -> f::Type {
-        if self.is_file() {
+        if self.is_link() {
+            f::Type::Link
+        } else if self.is_file() {
             f::Type::File
         } else if self.is_directory() {
             f::Type::Directory
@@ -812,17 +1317,13 @@ impl<'dir> File<'dir> {
 
     /// This file’s permissions, with flags for each bit.
     #[cfg(unix)]
-    pub fn permissions(&self) -> Option<f::Permissions> {
-        if self.is_link() && self.deref_links {
-            // If the chain of links is broken, we instead fall through and
-            // return the permissions of the original link, as would have been
-            // done if we were not dereferencing.
-            return match self.link_target_recurse() {
-                FileTarget::Ok(f) => f.permissions(),
-                _ => None,
-            };
+    pub fn permissions(&self, keep_link_owners: bool) -> Option<f::Permissions> {
+        let metadata = self.owner_metadata(keep_link_owners)?;
+        if !metadata.has(Field::Mode) {
+            return None;
         }
-        let bits = self.metadata.mode();
+
+        let bits = metadata.mode();
         let has_bit = |bit| bits & bit == bit;
 
         Some(f::Permissions {
@@ -846,7 +1347,10 @@ impl<'dir> File<'dir> {
 
     #[cfg(windows)]
     pub fn attributes(&self) -> f::Attributes {
-        let bits = self.metadata.file_attributes();
+        let bits = self
+            .metadata
+            .as_std()
+            .map_or(0, MetadataExt::file_attributes);
         let has_bit = |bit| bits & bit == bit;
 
         // https://docs.microsoft.com/en-us/windows/win32/fileio/file-attribute-constants
@@ -856,7 +1360,17 @@ impl<'dir> File<'dir> {
             readonly: has_bit(0x1),
             hidden: has_bit(0x2),
             system: has_bit(0x4),
-            reparse_point: has_bit(0x400),
+            reparse: self.reparse_kind(),
+        }
+    }
+
+    /// How much space the owner of this file is using on the filesystem
+    /// it’s on, if the filesystem has quotas.
+    #[cfg(unix)]
+    pub fn quota(&self, keep_link_owners: bool) -> f::Quota {
+        match self.owner_metadata(keep_link_owners) {
+            Some(metadata) if self.is_local() => quota::usage(self.metadata.dev(), metadata.uid()),
+            _ => f::Quota::None,
         }
     }
 
@@ -907,12 +1421,87 @@ impl<'dir> File<'dir> {
         use std::os::netbsd::fs::MetadataExt;
         #[cfg(target_os = "openbsd")]
         use std::os::openbsd::fs::MetadataExt;
-        f::Flags(self.metadata.st_flags())
+        f::Flags(self.metadata.as_std().map_or(0, MetadataExt::st_flags))
     }
 
     #[cfg(windows)]
     pub fn flags(&self) -> f::Flags {
-        f::Flags(self.metadata.file_attributes())
+        f::Flags(
+            self.metadata
+                .as_std()
+                .map_or(0, MetadataExt::file_attributes),
+        )
+    }
+
+    /// The attributes that `lsattr` lists, from the `FS_IOC_GETFLAGS` ioctl.
+    /// Only regular files and directories get asked, as opening anything
+    /// else can have side effects, and files that can’t be opened, or are
+    /// on filesystems without attributes, have none. The flags that only say
+    /// how the filesystem has laid the file out, such as ext4’s extents, are
+    /// left out, as they’re set on nearly every file and can’t be changed.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn flags(&self) -> f::Flags {
+        use std::os::unix::fs::OpenOptionsExt;
+        use std::os::unix::io::AsRawFd;
+
+        // `_IOR('f', 1, long)`, where which bits mean “read” depend on the
+        // architecture.
+        #[cfg(any(
+            target_arch = "mips",
+            target_arch = "mips64",
+            target_arch = "powerpc",
+            target_arch = "powerpc64",
+            target_arch = "sparc",
+            target_arch = "sparc64"
+        ))]
+        const IOC_READ: u32 = 0x4000_0000;
+        #[cfg(not(any(
+            target_arch = "mips",
+            target_arch = "mips64",
+            target_arch = "powerpc",
+            target_arch = "powerpc64",
+            target_arch = "sparc",
+            target_arch = "sparc64"
+        )))]
+        const IOC_READ: u32 = 0x8000_0000;
+        const FS_IOC_GETFLAGS: u32 =
+            IOC_READ | ((std::mem::size_of::<libc::c_long>() as u32) << 16) | (0x66 << 8) | 1;
+
+        // Indexed directories, huge files, extents, inodes holding extended
+        // attributes, blocks past the end of the file, and inline data.
+        const LAYOUT_FLAGS: f::flag_t =
+            0x0000_1000 | 0x0004_0000 | 0x0008_0000 | 0x0020_0000 | 0x0040_0000 | 0x1000_0000;
+
+        if !self.is_file() && !self.is_directory() || !self.is_local() {
+            return f::Flags(0);
+        }
+
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .custom_flags(libc::O_NONBLOCK | libc::O_NOFOLLOW)
+            .open(&self.path);
+        let Ok(file) = file else {
+            return f::Flags(0);
+        };
+
+        // The kernel only ever writes an `int`, whatever the request says,
+        // and the request’s type is different between C libraries.
+        let mut flags: libc::c_int = 0;
+        #[allow(clippy::cast_lossless, clippy::cast_possible_wrap)]
+        let result = unsafe {
+            libc::ioctl(
+                file.as_raw_fd(),
+                FS_IOC_GETFLAGS as _,
+                std::ptr::addr_of_mut!(flags),
+            )
+        };
+
+        #[allow(clippy::cast_sign_loss)]
+        f::Flags(if result == 0 {
+            flags as f::flag_t & !LAYOUT_FLAGS
+        } else {
+            0
+        })
     }
 
     #[cfg(not(any(
@@ -921,7 +1510,9 @@ impl<'dir> File<'dir> {
         target_os = "netbsd",
         target_os = "openbsd",
         target_os = "dragonfly",
-        target_os = "windows"
+        target_os = "windows",
+        target_os = "linux",
+        target_os = "android"
     )))]
     pub fn flags(&self) -> f::Flags {
         f::Flags(0)
@@ -986,6 +1577,207 @@ mod modes {
     pub const SETUID: Mode = libc::S_ISUID as Mode;
 }
 
+/// Picks the name that’s fewest edits away from the missing one, as long as
+/// it’s close enough to be a likely replacement: no more edits than half the
+/// missing name’s length. Ties go to whichever name sorts first.
+fn closest_name(missing: &str, names: impl Iterator<Item = String>) -> Option<String> {
+    let most_edits = missing.chars().count() / 2;
+
+    names
+        .filter(|name| name != missing)
+        .map(|name| (edit_distance(missing, &name), name))
+        .filter(|(edits, _)| *edits <= most_edits)
+        .min()
+        .map(|(_, name)| name)
+}
+
+/// Follows the chain of links that starts at the path. Each file along the
+/// way gets looked at with `link_id`, which fails if there’s nothing there,
+/// and otherwise returns something that tells it apart from other links if
+/// it’s a link, so coming back round to one can be noticed.
+fn follow_links<Id: PartialEq>(
+    start: &Path,
+    link_id: impl Fn(&Path) -> io::Result<Option<Id>>,
+    read_link: impl Fn(&Path) -> io::Result<PathBuf>,
+    canonicalize: &dyn Fn(&Path) -> io::Result<PathBuf>,
+) -> f::LinkChain {
+    let mut path = start.to_path_buf();
+    let mut seen = Vec::new();
+
+    loop {
+        let id = match link_id(&path) {
+            Ok(id) => id,
+            Err(_) if seen.is_empty() => return f::LinkChain::NotALink,
+            Err(_) => {
+                let path = absolute_as_far_as_exists(&path, canonicalize);
+                let links = seen.len();
+                return f::LinkChain::Resolved {
+                    path,
+                    links,
+                    exists: false,
+                };
+            }
+        };
+
+        let Some(id) = id else {
+            if seen.is_empty() {
+                return f::LinkChain::NotALink;
+            }
+
+            let path = canonicalize(&path).unwrap_or(path);
+            let links = seen.len();
+            return f::LinkChain::Resolved {
+                path,
+                links,
+                exists: true,
+            };
+        };
+
+        if seen.contains(&id) {
+            return f::LinkChain::Loop { links: seen.len() };
+        }
+        seen.push(id);
+
+        // A link that can’t be read can’t be followed any further.
+        let Ok(target) = read_link(&path) else {
+            let path = absolute_as_far_as_exists(&path, canonicalize);
+            let links = seen.len() - 1;
+            return f::LinkChain::Resolved { path, links, exists: false };
+        };
+
+        path = match path.parent() {
+            Some(parent) if target.is_relative() => parent.join(target),
+            _ => target,
+        };
+    }
+}
+
+/// Makes a path that might not exist absolute, resolving as much of it as
+/// does exist with `canonicalize`, so a broken link’s target gets shown with
+/// the same kind of path as a working one’s.
+fn absolute_as_far_as_exists(
+    path: &Path,
+    canonicalize: &dyn Fn(&Path) -> io::Result<PathBuf>,
+) -> PathBuf {
+    let mut missing = Vec::new();
+    let mut ancestor = path;
+
+    loop {
+        // The parent of a bare file name is the empty path, which is the
+        // current directory.
+        let existing = if ancestor.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            ancestor
+        };
+
+        if let Ok(resolved) = canonicalize(existing) {
+            return missing
+                .iter()
+                .rev()
+                .fold(resolved, |path, name| path.join(name));
+        }
+
+        match (ancestor.parent(), ancestor.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                ancestor = parent;
+            }
+            _ => {
+                return std::env::current_dir()
+                    .map_or_else(|_| path.to_path_buf(), |dir| dir.join(path))
+            }
+        }
+    }
+}
+
+/// Something that tells links apart, so a chain of them can notice coming
+/// back round to one it’s already been through. On Unix, this is the link’s
+/// own device and inode number, which stay the same however it was reached.
+#[cfg(unix)]
+fn link_id(_path: &Path, metadata: &std::fs::Metadata) -> (u64, u64) {
+    (metadata.dev(), metadata.ino())
+}
+
+/// Elsewhere, links get told apart by their paths.
+#[cfg(not(unix))]
+fn link_id(path: &Path, _metadata: &std::fs::Metadata) -> PathBuf {
+    normalize(path)
+}
+
+/// The path with any `.` and `..` components taken out.
+fn normalize(path: &Path) -> PathBuf {
+    use std::path::Component;
+
+    let mut normal = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normal.pop();
+            }
+            component => normal.push(component),
+        }
+    }
+    normal
+}
+
+/// The Levenshtein distance between two strings: how many characters have
+/// to be inserted, deleted, or replaced to turn one into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let replace = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = replace.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod suggestion_test {
+    use super::{closest_name, edit_distance};
+
+    fn names(names: &[&str]) -> impl Iterator<Item = String> {
+        names
+            .iter()
+            .map(|name| (*name).to_string())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn renamed_file() {
+        let found = closest_name("foo.txt", names(&["bar.rs", "foo_v2.txt", "other"]));
+        assert_eq!(found, Some("foo_v2.txt".to_string()));
+    }
+
+    #[test]
+    fn closest_wins() {
+        let found = closest_name("config.yaml", names(&["config.yml", "config.yaml.bak"]));
+        assert_eq!(found, Some("config.yml".to_string()));
+    }
+
+    #[test]
+    fn nothing_close() {
+        assert_eq!(closest_name("a.c", names(&["b.h", "main.rs"])), None);
+    }
+}
+
 #[cfg(test)]
 mod ext_test {
     use super::File;
@@ -1043,3 +1835,90 @@ mod filename_test {
         assert_eq!("/", File::filename(Path::new("/")));
     }
 }
+
+#[cfg(test)]
+#[cfg(unix)]
+mod link_owner_test {
+    use super::File;
+    use std::fs;
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    #[test]
+    fn links_have_their_own_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let target = dir.join("target");
+        fs::write(&target, "").unwrap();
+        fs::set_permissions(&target, fs::Permissions::from_mode(0o640)).unwrap();
+        symlink(&target, dir.join("link")).unwrap();
+        symlink(dir.join("missing"), dir.join("broken")).unwrap();
+
+        let file =
+            |name: &str, deref| File::from_args(dir.join(name), None, None, deref, false).unwrap();
+
+        let own = file("link", false).permissions(false).unwrap();
+        assert!(own.other_read);
+        let followed = file("link", true).permissions(false).unwrap();
+        assert!(followed.group_read && !followed.other_read);
+        assert_eq!(
+            file("link", true).user(false).map(|user| user.0),
+            file("target", false).user(false).map(|user| user.0)
+        );
+
+        // With --no-dereference-owner, a followed link keeps its own.
+        let kept = file("link", true).permissions(true).unwrap();
+        assert!(kept.other_read);
+
+        assert!(file("broken", false).permissions(false).is_some());
+        assert!(file("broken", true).permissions(false).is_none());
+        assert!(file("broken", true).permissions(true).is_some());
+        assert!(file("broken", true).user(false).is_none());
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod link_chain_test {
+    use super::{absolute_as_far_as_exists, File};
+    use crate::fs::fields as f;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+    use std::path::Path;
+
+    #[test]
+    fn missing_file_here() {
+        let cwd = fs::canonicalize(".").unwrap();
+        assert_eq!(
+            absolute_as_far_as_exists(Path::new("no-such-file"), &|p| fs::canonicalize(p)),
+            cwd.join("no-such-file")
+        );
+    }
+
+    #[test]
+    fn missing_directories() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = fs::canonicalize(dir.path()).unwrap();
+        assert_eq!(
+            absolute_as_far_as_exists(&dir.join("a/b/c"), &|p| fs::canonicalize(p)),
+            dir.join("a").join("b").join("c")
+        );
+    }
+
+    #[test]
+    fn broken_chain() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = fs::canonicalize(dir.path()).unwrap();
+        symlink("nowhere", dir.join("first")).unwrap();
+        symlink("first", dir.join("second")).unwrap();
+
+        let file = File::from_args(dir.join("second"), None, None, false, false).unwrap();
+        assert_eq!(
+            file.link_chain(),
+            f::LinkChain::Resolved {
+                path: dir.join("nowhere"),
+                links: 2,
+                exists: false,
+            }
+        );
+    }
+}
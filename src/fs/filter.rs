@@ -1,12 +1,24 @@
 //! Filtering and sorting the list of files before displaying them.
 
 use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fs;
 use std::iter::FromIterator;
-#[cfg(unix)]
-use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use icu_collator::{Collator, CollatorOptions};
+use icu_locid::Locale;
+use log::*;
 
+use crate::fs::dir_action::Levels;
+use crate::fs::feature::git::GitCache;
+use crate::fs::feature::gitignore::IgnoreFile;
+use crate::fs::fields as f;
 use crate::fs::DotFilter;
-use crate::fs::File;
+use crate::fs::{File, FileTarget};
+use crate::options::vars;
+use crate::timing::{self, Phase};
 
 /// Flags used to manage the **file filter** process
 #[derive(PartialEq, Eq, Debug, Clone)]
@@ -15,12 +27,156 @@ pub enum FileFilterFlags {
     /// files first, or files starting with Z, or the most-recently-changed
     /// ones, depending on the sort field.
     Reverse,
+}
+
+/// A **kind** of filesystem entry that a listing can be restricted to,
+/// using `--type` or one of the `--only-*` shorthands.
+///
+/// Giving more than one kind shows entries matching *any* of them, the same
+/// way `fd -t` combines its type filters.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum FileKind {
+    /// Regular files.
+    File,
+
+    /// Directories, not including symlinks that point to them.
+    Directory,
+
+    /// Symbolic links, whether or not their target exists.
+    Symlink,
+
+    /// Symbolic links whose target doesn’t exist.
+    BrokenSymlink,
+
+    /// Regular files that are executable by the current user.
+    Executable,
 
-    /// Whether to only show directories.
-    OnlyDirs,
+    /// Named pipes (FIFOs).
+    Pipe,
 
-    /// Whether to only show files.
-    OnlyFiles,
+    /// Unix domain sockets.
+    Socket,
+
+    /// Block devices.
+    BlockDevice,
+
+    /// Character devices.
+    CharDevice,
+}
+
+impl FileKind {
+    /// Whether the given file is of this kind.
+    pub fn matches(self, file: &File<'_>) -> bool {
+        #[rustfmt::skip]
+        return match self {
+            Self::File         => file.is_file(),
+            Self::Directory    => file.is_directory(),
+            Self::Symlink      => file.is_link(),
+            Self::BrokenSymlink => file.is_link()
+                                && matches!(file.link_target(), FileTarget::Broken(_)),
+            #[cfg(unix)]
+            Self::Executable   => file.is_executable_file(),
+            #[cfg(unix)]
+            Self::Pipe         => file.is_pipe(),
+            #[cfg(unix)]
+            Self::Socket       => file.is_socket(),
+            #[cfg(unix)]
+            Self::BlockDevice  => file.is_block_device(),
+            #[cfg(unix)]
+            Self::CharDevice   => file.is_char_device(),
+            #[cfg(windows)]
+            Self::Executable | Self::Pipe | Self::Socket | Self::BlockDevice | Self::CharDevice => false,
+        };
+    }
+}
+
+/// The range of sizes that files have to fall within to be listed, using
+/// `--min-size` and `--max-size`. Both ends are inclusive.
+///
+/// Directories aren’t affected unless `--total-size` gives them a size, so
+/// that the files inside them can still be found, but anything else without
+/// a size, such as a symlink or a device, gets hidden as soon as a limit is
+/// given.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct SizeFilter {
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl SizeFilter {
+    /// Whether the given file should be listed.
+    pub fn allows(self, file: &File<'_>) -> bool {
+        if self.min.is_none() && self.max.is_none() {
+            return true;
+        }
+
+        match file.size() {
+            f::Size::Some(size) => {
+                self.min.map_or(true, |min| size >= min) && self.max.map_or(true, |max| size <= max)
+            }
+            _ => file.is_directory(),
+        }
+    }
+}
+
+/// Which of a file’s timestamps gets compared with a reference file’s, using
+/// `--reference-time`.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub enum TimeField {
+    /// The time it was last modified.
+    #[default]
+    Modified,
+
+    /// The time its metadata was last changed.
+    Changed,
+
+    /// The time it was last accessed.
+    Accessed,
+
+    /// The time it was created.
+    Created,
+}
+
+impl TimeField {
+    /// This timestamp of the given file, if it has one.
+    pub fn of(self, file: &File<'_>) -> Option<NaiveDateTime> {
+        match self {
+            Self::Modified => file.modified_time(),
+            Self::Changed => file.changed_time(),
+            Self::Accessed => file.accessed_time(),
+            Self::Created => file.created_time(),
+        }
+    }
+}
+
+/// The times that files have to be newer or older than to be listed, taken
+/// from reference files with `--newer-than` and `--older-than`, the way that
+/// `find -newer` works. Both ends are exclusive, so a file with the same
+/// time as the reference file isn’t listed.
+///
+/// As with sizes, directories aren’t affected, so that the files inside them
+/// can still be found.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct TimeFilter {
+    pub field: TimeField,
+    pub newer_than: Option<NaiveDateTime>,
+    pub older_than: Option<NaiveDateTime>,
+}
+
+impl TimeFilter {
+    /// Whether the given file should be listed.
+    pub fn allows(self, file: &File<'_>) -> bool {
+        if (self.newer_than.is_none() && self.older_than.is_none()) || file.is_directory() {
+            return true;
+        }
+
+        self.field.of(file).is_some_and(|time| self.contains(time))
+    }
+
+    fn contains(self, time: NaiveDateTime) -> bool {
+        self.newer_than.map_or(true, |newer| time > newer)
+            && self.older_than.map_or(true, |older| time < older)
+    }
 }
 
 /// The **file filter** processes a list of files before displaying them to
@@ -46,9 +202,25 @@ pub struct FileFilter {
     /// The metadata field to sort by.
     pub sort_field: SortField,
 
+    /// Sort settings for some levels of a tree that override the ones
+    /// above, with later ones taking precedence.
+    pub level_sorts: Vec<LevelSort>,
+
     // Flags that the file filtering process follow
     pub flags: Vec<FileFilterFlags>,
 
+    /// The kinds of file to show. If this is empty, every kind is shown.
+    pub only_kinds: Vec<FileKind>,
+
+    /// The sizes of file to show.
+    pub size_filter: SizeFilter,
+
+    /// The times of file to show.
+    pub time_filter: TimeFilter,
+
+    /// The tag that files need to have to be listed, if any.
+    pub tag: Option<String>,
+
     /// Which invisible “dot” files to include when listing a directory.
     ///
     /// Files starting with a single “.” are used to determine “system” or
@@ -68,30 +240,29 @@ pub struct FileFilter {
 
     /// Whether to ignore Git-ignored patterns.
     pub git_ignore: GitIgnore,
+
+    /// Whether to report the rule that caused each Git-ignored file to be
+    /// hidden.
+    pub git_ignore_debug: bool,
+
+    /// Whether to only show files with changes that Git knows about.
+    pub only_modified_git: bool,
 }
 
 impl FileFilter {
     /// Remove every file in the given vector that does *not* pass the
     /// filter predicate for files found inside a directory.
-    pub fn filter_child_files(&self, files: &mut Vec<File<'_>>) {
-        use FileFilterFlags::{OnlyDirs, OnlyFiles};
-
-        files.retain(|f| !self.ignore_patterns.is_ignored(&f.name));
-
-        match (
-            self.flags.contains(&OnlyDirs),
-            self.flags.contains(&OnlyFiles),
-        ) {
-            (true, false) => {
-                // On pass -'-only-dirs' flag only
-                files.retain(File::is_directory);
-            }
-            (false, true) => {
-                // On pass -'-only-files' flag only
-                files.retain(File::is_file);
-            }
-            _ => {}
+    pub fn filter_child_files(&self, files: &mut Vec<File<'_>>, git: Option<&GitCache>) {
+        files.retain(|f| !self.ignore_patterns.ignores(f));
+
+        if !self.only_kinds.is_empty() {
+            files.retain(|f| self.only_kinds.iter().any(|kind| kind.matches(f)));
         }
+
+        files.retain(|f| self.size_filter.allows(f));
+        files.retain(|f| self.time_filter.allows(f));
+        files.retain(|f| self.has_tag(f));
+        files.retain(|f| self.has_git_changes(f, git));
     }
 
     /// Remove every file in the given vector that does *not* pass the
@@ -103,8 +274,35 @@ impl FileFilter {
     /// dotfile, because it’s been directly specified. But running
     /// `exa -I='*.ogg' music/*` should filter out the ogg files obtained
     /// from the glob, even though the globbing is done by the shell!
-    pub fn filter_argument_files(&self, files: &mut Vec<File<'_>>) {
-        files.retain(|f| !self.ignore_patterns.is_ignored(&f.name));
+    pub fn filter_argument_files(&self, files: &mut Vec<File<'_>>, git: Option<&GitCache>) {
+        files.retain(|f| !self.ignore_patterns.ignores(f));
+        files.retain(|f| self.size_filter.allows(f));
+        files.retain(|f| self.time_filter.allows(f));
+        files.retain(|f| self.has_tag(f));
+        files.retain(|f| self.has_git_changes(f, git));
+    }
+
+    /// Whether the given file has changes that Git would report, if only
+    /// those are being shown. A directory has changes if anything inside it
+    /// does, and files outside of any repository have none.
+    fn has_git_changes(&self, file: &File<'_>, git: Option<&GitCache>) -> bool {
+        if !self.only_modified_git {
+            return true;
+        }
+
+        git.is_some_and(|git| is_changed(git.get(&file.path, file.is_directory())))
+    }
+
+    /// Whether the given file has the tag being filtered by, ignoring case
+    /// the same way the Finder does.
+    fn has_tag(&self, file: &File<'_>) -> bool {
+        let Some(wanted) = &self.tag else {
+            return true;
+        };
+
+        file.tags()
+            .iter()
+            .any(|tag| tag.name.to_lowercase() == wanted.to_lowercase())
     }
 
     /// Sort the files in the given vector based on the sort field option.
@@ -112,24 +310,131 @@ impl FileFilter {
     where
         F: AsRef<File<'a>>,
     {
-        files.sort_by(|a, b| self.sort_field.compare_files(a.as_ref(), b.as_ref()));
+        let reverse = self.flags.contains(&FileFilterFlags::Reverse);
+        sort_files_by(files, self.sort_field, reverse, self.list_dirs_first);
+    }
 
-        if self.flags.contains(&FileFilterFlags::Reverse) {
-            files.reverse();
+    /// Sort the files at the given level of a tree, where the files directly
+    /// inside a listed directory are at level 1, using any settings that
+    /// `level_sorts` has for that level in place of the usual ones.
+    pub fn sort_files_at_level<'a, F>(&self, files: &mut [F], level: usize)
+    where
+        F: AsRef<File<'a>>,
+    {
+        let mut field = self.sort_field;
+        let mut reverse = self.flags.contains(&FileFilterFlags::Reverse);
+        let mut dirs_first = self.list_dirs_first;
+
+        for sort in self
+            .level_sorts
+            .iter()
+            .filter(|sort| sort.levels.contains(level))
+        {
+            field = sort.field.unwrap_or(field);
+            reverse = sort.reverse.unwrap_or(reverse);
+            dirs_first = sort.dirs_first.unwrap_or(dirs_first);
+        }
+
+        sort_files_by(files, field, reverse, dirs_first);
+    }
+}
+
+/// Sort the files by the given field, optionally reversing them and listing
+/// directories first.
+///
+/// Rather than moving the files around while comparing them, this sorts a
+/// list of their positions, and only puts the files themselves in order at
+/// the end. Files can be large — a tree’s entries also carry their children
+/// — so an unstable sort over plain indices avoids the buffer of files that
+/// a stable sort would need, which adds up for listings with millions of
+/// entries. Anything a comparison needs that costs more than reading a
+/// field, such as whether a symlink points to a directory, or a name in
+/// lowercase, gets worked out once per file beforehand.
+pub fn sort_files_by<'a, F>(files: &mut [F], field: SortField, reverse: bool, dirs_first: bool)
+where
+    F: AsRef<File<'a>>,
+{
+    timing::time(Phase::Sort, || {
+        let keys = files
+            .iter()
+            .map(|file| SortKey::new(file.as_ref(), field, dirs_first))
+            .collect::<Vec<_>>();
+
+        let mut order = (0..files.len()).collect::<Vec<_>>();
+        order.sort_unstable_by(|&a, &b| {
+            let (a_key, b_key) = (&keys[a], &keys[b]);
+
+            // Files that compare equal keep the order they were in, or the
+            // opposite order when reversed, which is what a stable sort
+            // followed by a reversal would leave them in.
+            let by_field = match (field, &a_key.lowercase_name, &b_key.lowercase_name) {
+                (SortField::NameNatural(collation), Some(a_lower), Some(b_lower)) => {
+                    let a_name = &files[a].as_ref().name;
+                    let b_name = &files[b].as_ref().name;
+                    collation.compare_lowered(a_name, a_lower, b_name, b_lower)
+                }
+                _ => field.compare_files(files[a].as_ref(), files[b].as_ref()),
+            }
+            .then(a.cmp(&b));
+
+            b_key.directory.cmp(&a_key.directory).then(if reverse {
+                by_field.reverse()
+            } else {
+                by_field
+            })
+        });
+
+        permute(files, order);
+    });
+}
+
+/// The parts of a file that get compared while sorting that are worth
+/// working out before the sort, instead of every time it gets compared.
+struct SortKey {
+    /// Whether the file points to a directory, if directories go first.
+    directory: bool,
+
+    /// The file’s name in lowercase, if it’s being sorted naturally.
+    lowercase_name: Option<String>,
+}
+
+impl SortKey {
+    fn new(file: &File<'_>, field: SortField, dirs_first: bool) -> Self {
+        Self {
+            directory: dirs_first && file.points_to_directory(),
+            lowercase_name: matches!(field, SortField::NameNatural(_))
+                .then(|| file.name.to_lowercase()),
         }
+    }
+}
 
-        if self.list_dirs_first {
-            // This relies on the fact that `sort_by` is *stable*: it will keep
-            // adjacent elements next to each other.
-            files.sort_by(|a, b| {
-                b.as_ref()
-                    .points_to_directory()
-                    .cmp(&a.as_ref().points_to_directory())
-            });
+/// Rearranges the items so the one at each position is the one that was
+/// at that position in `order`, by following each cycle of the
+/// permutation and swapping items into place, so nothing gets copied.
+fn permute<T>(items: &mut [T], mut order: Vec<usize>) {
+    for start in 0..items.len() {
+        let mut current = start;
+        while order[current] != start {
+            let next = order[current];
+            items.swap(current, next);
+            order[current] = current;
+            current = next;
         }
+        order[current] = current;
     }
 }
 
+/// Sort settings that apply to the files at some levels of a tree, such as
+/// sorting by size at the deepest levels, or only listing directories first
+/// at the top one. Settings that are `None` are left as they are.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct LevelSort {
+    pub levels: Levels,
+    pub field: Option<SortField>,
+    pub reverse: Option<bool>,
+    pub dirs_first: Option<bool>,
+}
+
 /// User-supplied field to sort by.
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub enum SortField {
@@ -151,6 +456,15 @@ pub enum SortField {
     #[cfg(unix)]
     FileInode,
 
+    /// The number of blocks the file takes up on disk, which can be far
+    /// fewer than its size suggests for sparse or compressed files.
+    #[cfg(unix)]
+    Blocks,
+
+    /// The number of hard links to the file.
+    #[cfg(unix)]
+    Links,
+
     /// The time the file was modified (the “mtime”).
     ///
     /// As this is stored as a Unix timestamp, rather than a local time
@@ -200,6 +514,10 @@ pub enum SortField {
     /// The file's name, however if the name of the file begins with `.`
     /// ignore the leading `.` and then sort as Name
     NameMixHidden(SortCase),
+
+    /// The file name, sorted naturally and case-insensitively, with the
+    /// letters in between the numbers compared using the given collation.
+    NameNatural(Collation),
 }
 
 /// Whether a field should be sorted case-sensitively or case-insensitively.
@@ -221,6 +539,120 @@ pub enum SortCase {
     AaBbCc,
 }
 
+/// How the letters in file names get compared by `--sort=name-natural`,
+/// picked with `--collate`.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Collation {
+    /// Compare them with the Unicode Collation Algorithm, tailored to the
+    /// `LC_COLLATE` locale, so ‘ä’ sorts next to ‘a’ in German, but after
+    /// ‘z’ in Swedish. Locales without tailoring, including “C”, use the
+    /// Unicode default order, which still puts ‘Ä’ next to ‘A’.
+    Locale,
+
+    /// Compare them by their Unicode code points.
+    Codepoint,
+}
+
+thread_local! {
+    /// The collator for the user’s locale. Building one loads its tables,
+    /// so this happens once per thread rather than once per comparison.
+    static COLLATOR: Collator = locale_collator(collate_locale().as_deref());
+}
+
+/// The name of the locale to collate in, using the same precedence as the
+/// C library: `LC_ALL`, then `LC_COLLATE`, then `LANG`.
+fn collate_locale() -> Option<String> {
+    [vars::LC_ALL, vars::LC_COLLATE, vars::LANG]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Builds a collator for a locale name such as `de_DE.UTF-8` or
+/// `sv_SE@euro`, falling back to the Unicode default order for names that
+/// aren’t a language, such as “C” or “POSIX”.
+fn locale_collator(name: Option<&str>) -> Collator {
+    let locale = name
+        .and_then(|name| name.split(|c| c == '.' || c == '@').next())
+        .and_then(|tag| tag.replace('_', "-").parse::<Locale>().ok())
+        .unwrap_or_default();
+
+    Collator::try_new(&(&locale).into(), CollatorOptions::new())
+        .or_else(|_| Collator::try_new(&(&Locale::default()).into(), CollatorOptions::new()))
+        .expect("the root collation is built in")
+}
+
+impl Collation {
+    /// Compares two pieces of text that don’t contain any digits.
+    fn compare(self, a: &str, b: &str) -> Ordering {
+        match self {
+            Self::Locale => COLLATOR.with(|collator| collator.compare(a, b)),
+            Self::Codepoint => a.cmp(b),
+        }
+    }
+
+    /// Compares two file names naturally, so `file9` comes before `file10`,
+    /// and ignoring case unless the names are otherwise the same.
+    pub fn compare_names(self, a: &str, b: &str) -> Ordering {
+        self.compare_lowered(a, &a.to_lowercase(), b, &b.to_lowercase())
+    }
+
+    /// Compares two file names naturally, given them in lowercase as well,
+    /// so sorting can lowercase each name once rather than every time it
+    /// gets compared.
+    fn compare_lowered(self, a: &str, a_lower: &str, b: &str, b_lower: &str) -> Ordering {
+        self.compare_chunks(a_lower, b_lower)
+            .then_with(|| self.compare_chunks(a, b))
+            .then_with(|| a.cmp(b))
+    }
+
+    fn compare_chunks(self, a: &str, b: &str) -> Ordering {
+        let mut a_chunks = natural_chunks(a);
+        let mut b_chunks = natural_chunks(b);
+
+        loop {
+            let order = match (a_chunks.next(), b_chunks.next()) {
+                (None, None) => return Ordering::Equal,
+                (None, Some(_)) => return Ordering::Less,
+                (Some(_), None) => return Ordering::Greater,
+                (Some(a), Some(b)) => match (is_number(a), is_number(b)) {
+                    (true, true) => {
+                        let a = a.trim_start_matches('0');
+                        let b = b.trim_start_matches('0');
+                        a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+                    }
+                    (true, false) => Ordering::Less,
+                    (false, true) => Ordering::Greater,
+                    (false, false) => self.compare(a, b),
+                },
+            };
+
+            if order != Ordering::Equal {
+                return order;
+            }
+        }
+    }
+}
+
+/// Splits a name into runs of digits and runs of everything else.
+fn natural_chunks(name: &str) -> impl Iterator<Item = &str> {
+    let mut rest = name;
+    std::iter::from_fn(move || {
+        let digits = is_number(rest);
+        let end = rest
+            .find(|c: char| c.is_ascii_digit() != digits)
+            .unwrap_or(rest.len());
+
+        let (chunk, tail) = rest.split_at(end);
+        rest = tail;
+        (!chunk.is_empty()).then_some(chunk)
+    })
+}
+
+fn is_number(chunk: &str) -> bool {
+    chunk.starts_with(|c: char| c.is_ascii_digit())
+}
+
 impl SortField {
     /// Compares two files to determine the order they should be listed in,
     /// depending on the search field.
@@ -244,6 +676,10 @@ impl SortField {
 
             #[cfg(unix)]
             Self::FileInode     => a.metadata.ino().cmp(&b.metadata.ino()),
+            #[cfg(unix)]
+            Self::Blocks        => a.metadata.blocks().cmp(&b.metadata.blocks()),
+            #[cfg(unix)]
+            Self::Links         => a.metadata.nlink().cmp(&b.metadata.nlink()),
             Self::ModifiedDate  => a.modified_time().cmp(&b.modified_time()),
             Self::AccessedDate  => a.accessed_time().cmp(&b.accessed_time()),
             Self::ChangedDate   => a.changed_time().cmp(&b.changed_time()),
@@ -273,6 +709,8 @@ impl SortField {
                 Self::strip_dot(&a.name),
                 Self::strip_dot(&b.name)
             ),
+
+            Self::NameNatural(collation) => collation.compare_names(&a.name, &b.name),
         };
     }
 
@@ -287,9 +725,22 @@ impl SortField {
 /// The **ignore patterns** are a list of globs that are tested against
 /// each filename, and if any of them match, that file isn’t displayed.
 /// This lets a user hide, say, text files by ignoring `*.txt`.
-#[derive(PartialEq, Eq, Default, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct IgnorePatterns {
     patterns: Vec<glob::Pattern>,
+
+    /// Whether letters in the patterns only match letters of the same case.
+    case_sensitive: bool,
+
+    /// Rules read from a file with `--ignore-glob-file`, which hide files as
+    /// well as the patterns do.
+    file: Option<IgnoreFile>,
+}
+
+impl Default for IgnorePatterns {
+    fn default() -> Self {
+        Self::empty()
+    }
 }
 
 impl FromIterator<glob::Pattern> for IgnorePatterns {
@@ -298,11 +749,22 @@ impl FromIterator<glob::Pattern> for IgnorePatterns {
         I: IntoIterator<Item = glob::Pattern>,
     {
         let patterns = iter.into_iter().collect();
-        Self { patterns }
+        Self {
+            patterns,
+            case_sensitive: Self::CASE_SENSITIVE_BY_DEFAULT,
+            file: None,
+        }
     }
 }
 
 impl IgnorePatterns {
+    /// Whether patterns match case-sensitively when neither `--ignore-case`
+    /// nor `--case-sensitive` is given. This follows the filesystems that
+    /// each platform usually has: macOS and Windows don’t tell names apart
+    /// by case, so patterns don’t either.
+    pub const CASE_SENSITIVE_BY_DEFAULT: bool =
+        !cfg!(any(target_os = "macos", target_os = "windows"));
+
     /// Create a new list from the input glob strings, turning the inputs that
     /// are valid glob patterns into an `IgnorePatterns`. The inputs that
     /// don’t parse correctly are returned separately.
@@ -328,22 +790,144 @@ impl IgnorePatterns {
             }
         }
 
-        (Self { patterns }, errors)
+        let patterns = Self {
+            patterns,
+            case_sensitive: Self::CASE_SENSITIVE_BY_DEFAULT,
+            file: None,
+        };
+
+        (patterns, errors)
     }
 
     /// Create a new empty set of patterns that matches nothing.
     pub fn empty() -> Self {
         Self {
             patterns: Vec::new(),
+            case_sensitive: Self::CASE_SENSITIVE_BY_DEFAULT,
+            file: None,
+        }
+    }
+
+    /// Sets whether letters in the patterns only match letters of the same
+    /// case.
+    pub fn with_case_sensitivity(self, case_sensitive: bool) -> Self {
+        Self {
+            case_sensitive,
+            ..self
         }
     }
 
+    /// Adds patterns for the directories that version control systems keep
+    /// their own data in, for `--exclude-vcs`.
+    pub fn with_vcs_dirs(mut self) -> Self {
+        self.patterns.extend(
+            VCS_DIRS
+                .iter()
+                .filter_map(|name| glob::Pattern::new(&glob::Pattern::escape(name)).ok()),
+        );
+        self
+    }
+
+    /// Adds the rules from an ignore file to the patterns.
+    pub fn with_file(self, file: IgnoreFile) -> Self {
+        Self {
+            file: Some(file),
+            ..self
+        }
+    }
+
+    /// Whether the given file should be hidden, either because its name
+    /// matches one of the patterns, or because the ignore file says so.
+    fn ignores(&self, file: &File<'_>) -> bool {
+        self.is_ignored(&file.name)
+            || self
+                .file
+                .as_ref()
+                .is_some_and(|rules| rules.is_ignored(&file.path, file.is_directory()))
+    }
+
     /// Test whether the given file should be hidden from the results.
     fn is_ignored(&self, file: &str) -> bool {
-        self.patterns.iter().any(|p| p.matches(file))
+        let options = glob::MatchOptions {
+            case_sensitive: self.case_sensitive,
+            ..glob::MatchOptions::new()
+        };
+
+        self.patterns.iter().any(|p| p.matches_with(file, options))
     }
 }
 
+/// The names of the directories, and a few files, that version control
+/// systems keep a checkout’s history and settings in, rather than anything
+/// that’s been checked out.
+const VCS_DIRS: &[&str] = &[
+    ".git",
+    ".hg",
+    ".svn",
+    ".jj",
+    ".bzr",
+    ".pijul",
+    ".sl",
+    "_darcs",
+    "CVS",
+    "RCS",
+    "SCCS",
+    ".fslckout",
+    "_FOSSIL_",
+];
+
+/// The names of the files that a directory’s `.hidden` file hides, which
+/// file managers following the freedesktop.org convention, such as the GTK
+/// file chooser, leave out of their listings the same way as dotfiles.
+///
+/// The file has one name per line, with no globs or paths, and each name
+/// only hides a file in the same directory as the `.hidden` file itself.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct HiddenNames {
+    names: HashSet<String>,
+}
+
+impl HiddenNames {
+    /// The name of the file that lists the names to hide.
+    pub const FILE_NAME: &'static str = ".hidden";
+
+    /// Reads the `.hidden` file in the given directory. A directory without
+    /// one, or with one that can’t be read, hides nothing.
+    pub fn read(dir: &Path) -> Self {
+        let path = dir.join(Self::FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(contents) => Self::parse(&contents),
+            Err(e) => {
+                debug!("Couldn't read {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Collects the names from the contents of a `.hidden` file. Names can
+    /// start or end with spaces, so only line endings get removed.
+    fn parse(contents: &str) -> Self {
+        let names = contents
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect();
+        Self { names }
+    }
+
+    /// Whether a file with the given name gets hidden.
+    pub fn hides(&self, name: &str) -> bool {
+        self.names.contains(name)
+    }
+}
+
+/// Whether a Git status counts as a change for `--only-modified-git`.
+/// Ignored files don’t, as `git status` doesn’t list them either.
+fn is_changed(status: f::Git) -> bool {
+    let changed = |s| !matches!(s, f::GitStatus::NotModified | f::GitStatus::Ignored);
+    changed(status.staged) || changed(status.unstaged)
+}
+
 /// Whether to ignore or display files that Git would ignore.
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub enum GitIgnore {
@@ -388,4 +972,268 @@ mod test_ignores {
         assert!(pats.is_ignored("nothing"));
         assert!(pats.is_ignored("test.mp3"));
     }
+
+    #[test]
+    fn case_sensitive() {
+        let (pats, _) = IgnorePatterns::parse_from_iter(vec!["*.mp3"]);
+        let pats = pats.with_case_sensitivity(true);
+        assert!(pats.is_ignored("test.mp3"));
+        assert!(!pats.is_ignored("TEST.MP3"));
+    }
+
+    #[test]
+    fn case_insensitive() {
+        let (pats, _) = IgnorePatterns::parse_from_iter(vec!["*.mp3", "[a-c]*"]);
+        let pats = pats.with_case_sensitivity(false);
+        assert!(pats.is_ignored("TEST.MP3"));
+        assert!(pats.is_ignored("Bach.flac"));
+        assert!(!pats.is_ignored("Debussy.flac"));
+    }
+}
+
+#[cfg(test)]
+mod test_permute {
+    use super::permute;
+
+    #[test]
+    fn cycles() {
+        let mut items = vec!['a', 'b', 'c', 'd', 'e'];
+        permute(&mut items, vec![2, 0, 1, 4, 3]);
+        assert_eq!(items, vec!['c', 'a', 'b', 'e', 'd']);
+    }
+
+    #[test]
+    fn reversal() {
+        let mut items = vec![1, 2, 3, 4];
+        permute(&mut items, vec![3, 2, 1, 0]);
+        assert_eq!(items, vec![4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn unchanged() {
+        let mut items = vec![1, 2, 3];
+        permute(&mut items, vec![0, 1, 2]);
+        assert_eq!(items, vec![1, 2, 3]);
+
+        let mut empty: Vec<u8> = Vec::new();
+        permute(&mut empty, Vec::new());
+        assert!(empty.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod test_collation {
+    use super::*;
+
+    fn sorted(names: &[&'static str]) -> Vec<&'static str> {
+        let mut names = names.to_vec();
+        names.sort_by(|a, b| Collation::Codepoint.compare_names(a, b));
+        names
+    }
+
+    #[test]
+    fn numbers_naturally() {
+        assert_eq!(
+            sorted(&["file10", "file9", "file1"]),
+            vec!["file1", "file9", "file10"]
+        );
+    }
+
+    #[test]
+    fn leading_zeroes() {
+        assert_eq!(sorted(&["a010", "a9", "a08"]), vec!["a08", "a9", "a010"]);
+    }
+
+    #[test]
+    fn ignoring_case() {
+        assert_eq!(
+            sorted(&["banana", "Apple", "apple", "Cherry"]),
+            vec!["Apple", "apple", "banana", "Cherry"]
+        );
+    }
+
+    #[test]
+    fn numbers_before_letters() {
+        assert_eq!(sorted(&["b", "2", "a1"]), vec!["2", "a1", "b"]);
+    }
+
+    fn collated(locale: Option<&str>, names: &[&'static str]) -> Vec<&'static str> {
+        let collator = locale_collator(locale);
+        let mut names = names.to_vec();
+        names.sort_by(|a, b| collator.compare(a, b));
+        names
+    }
+
+    #[test]
+    fn umlauts_in_german() {
+        assert_eq!(
+            collated(
+                Some("de_DE.UTF-8"),
+                &["Zebra", "Öl", "Apfel", "Äpfel", "Ofen"]
+            ),
+            vec!["Apfel", "Äpfel", "Ofen", "Öl", "Zebra"]
+        );
+    }
+
+    #[test]
+    fn umlauts_in_the_c_locale() {
+        assert_eq!(
+            collated(Some("C"), &["Zebra", "Öl", "Äpfel", "Ofen"]),
+            vec!["Äpfel", "Ofen", "Öl", "Zebra"]
+        );
+        assert_eq!(
+            collated(None, &["Zebra", "Öl", "Äpfel", "Ofen"]),
+            vec!["Äpfel", "Ofen", "Öl", "Zebra"]
+        );
+    }
+
+    #[test]
+    fn umlauts_in_swedish() {
+        assert_eq!(
+            collated(Some("sv_SE.UTF-8"), &["Öl", "Zebra", "Äpple", "Apa"]),
+            vec!["Apa", "Zebra", "Äpple", "Öl"]
+        );
+    }
+
+    #[test]
+    fn umlauts_by_codepoint() {
+        assert_eq!(
+            sorted(&["Zebra", "Öl", "Äpfel"]),
+            vec!["Zebra", "Äpfel", "Öl"]
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_git_changes {
+    use super::*;
+    use crate::fs::fields::GitStatus;
+
+    fn git(staged: GitStatus, unstaged: GitStatus) -> f::Git {
+        f::Git { staged, unstaged }
+    }
+
+    #[test]
+    fn untouched() {
+        assert!(!is_changed(git(
+            GitStatus::NotModified,
+            GitStatus::NotModified
+        )));
+    }
+
+    #[test]
+    fn ignored() {
+        assert!(!is_changed(git(GitStatus::NotModified, GitStatus::Ignored)));
+    }
+
+    #[test]
+    fn staged_or_unstaged() {
+        assert!(is_changed(git(GitStatus::Modified, GitStatus::NotModified)));
+        assert!(is_changed(git(GitStatus::NotModified, GitStatus::New)));
+        assert!(is_changed(git(GitStatus::Renamed, GitStatus::Modified)));
+    }
+}
+
+#[cfg(test)]
+mod test_times {
+    use super::*;
+
+    fn at(seconds: i64) -> NaiveDateTime {
+        NaiveDateTime::from_timestamp_opt(seconds, 0).unwrap()
+    }
+
+    #[test]
+    fn newer_than() {
+        let filter = TimeFilter {
+            newer_than: Some(at(100)),
+            ..TimeFilter::default()
+        };
+
+        assert!(filter.contains(at(101)));
+        assert!(!filter.contains(at(100)));
+        assert!(!filter.contains(at(99)));
+    }
+
+    #[test]
+    fn between() {
+        let filter = TimeFilter {
+            field: TimeField::Changed,
+            newer_than: Some(at(100)),
+            older_than: Some(at(200)),
+        };
+
+        assert!(filter.contains(at(150)));
+        assert!(!filter.contains(at(200)));
+        assert!(!filter.contains(at(50)));
+    }
+}
+
+#[cfg(test)]
+mod test_hidden_names {
+    use super::*;
+
+    #[test]
+    fn one_name_per_line() {
+        let hidden = HiddenNames::parse("snap\nbuild output\r\n\n Desktop \n");
+        assert!(hidden.hides("snap"));
+        assert!(hidden.hides("build output"));
+        assert!(hidden.hides(" Desktop "));
+        assert!(!hidden.hides("Desktop"));
+        assert!(!hidden.hides(""));
+    }
+
+    #[test]
+    fn no_globs_or_paths() {
+        let hidden = HiddenNames::parse("*.log\nsrc/target\n");
+        assert!(hidden.hides("*.log"));
+        assert!(!hidden.hides("debug.log"));
+        assert!(!hidden.hides("target"));
+    }
+
+    #[test]
+    fn missing_file() {
+        let hidden = HiddenNames::read(Path::new("/this/directory/does/not/exist"));
+        assert_eq!(hidden, HiddenNames::default());
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod test_kinds {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn broken_symlinks() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        fs::write(dir.join("target"), "").unwrap();
+        symlink(dir.join("target"), dir.join("working")).unwrap();
+        symlink(dir.join("missing"), dir.join("dangling")).unwrap();
+
+        let file = |name: &str| File::from_args(dir.join(name), None, None, false, false).unwrap();
+
+        assert!(FileKind::BrokenSymlink.matches(&file("dangling")));
+        assert!(!FileKind::BrokenSymlink.matches(&file("working")));
+        assert!(!FileKind::BrokenSymlink.matches(&file("target")));
+        assert!(FileKind::Symlink.matches(&file("dangling")));
+        assert!(FileKind::Symlink.matches(&file("working")));
+    }
+
+    #[test]
+    fn directory_sizes() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        fs::create_dir(dir.join("small")).unwrap();
+        fs::write(dir.join("small").join("file"), [0; 10]).unwrap();
+
+        let small = |total_size| File::from_args(dir.join("small"), None, None, false, total_size);
+        let filter = SizeFilter {
+            min: Some(1000),
+            max: None,
+        };
+
+        assert!(filter.allows(&small(false).unwrap()));
+        assert!(!filter.allows(&small(true).unwrap()));
+    }
 }
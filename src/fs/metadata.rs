@@ -0,0 +1,512 @@
+//! The metadata that files get listed with.
+//!
+//! This usually comes from the standard library, one `lstat` at a time, but
+//! it can also come from elsewhere, such as the `io_uring` backend that looks
+//! up a whole directory’s worth in one go, a computer on the other end of
+//! an SFTP connection, or a listing saved with `--snapshot-out`. Either way,
+//! the rest of eza sees the same methods, named after the ones on
+//! `std::fs::Metadata`.
+
+#[cfg(unix)]
+use std::fmt;
+use std::fs;
+use std::io;
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::sync::Arc;
+use std::time::SystemTime;
+#[cfg(unix)]
+use std::time::{Duration, UNIX_EPOCH};
+
+/// A file’s metadata, and where it came from.
+#[derive(Debug, Clone)]
+pub enum Metadata {
+    /// Looked up by the standard library.
+    Std(fs::Metadata),
+
+    /// Looked up along with the rest of its directory’s entries.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    Stat(Stat),
+
+    /// Read from somewhere other than this computer’s filesystem.
+    #[cfg(unix)]
+    Remote(Remote),
+
+    /// Nothing, as looking it up took longer than `--io-timeout` allows.
+    /// The file still gets listed, by the name it was found by.
+    TimedOut,
+}
+
+/// The metadata of a file that isn’t on this computer, along with where it
+/// came from, so its link target or its directory’s files can be read from
+/// the same place.
+#[cfg(unix)]
+#[derive(Debug, Clone)]
+pub struct Remote {
+    pub fields: Fields,
+    pub source: Arc<dyn Source>,
+}
+
+/// What’s known about a file that isn’t on this computer. Not everywhere
+/// has every field, so any of them can be missing.
+#[cfg(unix)]
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Fields {
+    /// The file’s type and permission bits, as in `st_mode`.
+    pub mode: Option<u32>,
+    pub size: Option<u64>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub nlink: Option<u64>,
+    pub ino: Option<u64>,
+
+    /// The number of 512-byte blocks allocated to the file.
+    pub blocks: Option<u64>,
+
+    pub accessed: Option<SystemTime>,
+    pub modified: Option<SystemTime>,
+    pub changed: Option<SystemTime>,
+    pub created: Option<SystemTime>,
+}
+
+/// Somewhere other than this computer’s filesystem that files can be read
+/// from. Paths are the ones used there.
+#[cfg(unix)]
+pub trait Source: fmt::Debug + Send + Sync {
+    /// The files in the directory at the path, not including `.` and `..`,
+    /// each with its metadata.
+    fn read_dir(&self, path: &Path) -> io::Result<Vec<(PathBuf, Fields)>>;
+
+    /// The metadata of the file at the path, without following it if it’s
+    /// a symlink.
+    fn symlink_metadata(&self, path: &Path) -> io::Result<Fields>;
+
+    /// The metadata of the file at the path, following any symlinks.
+    fn metadata(&self, path: &Path) -> io::Result<Fields>;
+
+    /// Where the symlink at the path points to.
+    fn read_link(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// The absolute path to the file at the path, with every symlink in it
+    /// followed.
+    fn canonicalize(&self, path: &Path) -> io::Result<PathBuf>;
+
+    /// The name of the user with the ID, if there is one.
+    fn user_name(&self, uid: u32) -> Option<String>;
+
+    /// The name of the group with the ID, if there is one.
+    fn group_name(&self, gid: u32) -> Option<String>;
+
+    /// The IDs of the user and their primary group that the files are being
+    /// read as, if they’re known.
+    fn current_ids(&self) -> Option<(u32, u32)>;
+
+    /// How to show the path, such as in the heading above a directory’s
+    /// files, so it can’t be mistaken for a local one.
+    fn display(&self, path: &Path) -> String;
+}
+
+/// The fields of a file’s metadata that not everywhere has.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Field {
+    Mode,
+    Size,
+    Owner,
+    Links,
+    Inode,
+    Blocks,
+    Accessed,
+    Modified,
+    Changed,
+    Created,
+}
+
+/// The fields of a `stat` result that didn’t come from the standard library.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Stat {
+    /// The file’s type and permission bits, as in `st_mode`.
+    pub mode: u32,
+    pub uid: u32,
+    pub gid: u32,
+    pub nlink: u64,
+    pub ino: u64,
+    pub dev: u64,
+    pub rdev: u64,
+    pub size: u64,
+
+    /// The number of 512-byte blocks allocated to the file.
+    pub blocks: u64,
+
+    pub accessed: Timestamp,
+    pub modified: Timestamp,
+    pub changed: Timestamp,
+
+    /// When the file was created, if the filesystem keeps track.
+    pub created: Option<Timestamp>,
+}
+
+/// A point in time, as a number of seconds and nanoseconds since the epoch.
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct Timestamp {
+    pub secs: i64,
+    pub nanos: u32,
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+impl Timestamp {
+    fn system_time(self) -> SystemTime {
+        since_epoch(self.secs, self.nanos)
+    }
+}
+
+/// What kind of thing a file is.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Kind {
+    File,
+    Directory,
+    Symlink,
+    #[cfg(unix)]
+    Pipe,
+    #[cfg(unix)]
+    CharDevice,
+    #[cfg(unix)]
+    BlockDevice,
+    #[cfg(unix)]
+    Socket,
+    Other,
+}
+
+impl Kind {
+    /// The kind of file that the type bits of a mode say it is.
+    #[cfg(unix)]
+    #[allow(trivial_numeric_casts)]
+    fn of_mode(mode: u32) -> Self {
+        // The `libc::mode_t` type’s actual type varies.
+        type Mode = u32;
+
+        match mode & libc::S_IFMT as Mode {
+            m if m == libc::S_IFREG as Mode => Self::File,
+            m if m == libc::S_IFDIR as Mode => Self::Directory,
+            m if m == libc::S_IFLNK as Mode => Self::Symlink,
+            m if m == libc::S_IFIFO as Mode => Self::Pipe,
+            m if m == libc::S_IFCHR as Mode => Self::CharDevice,
+            m if m == libc::S_IFBLK as Mode => Self::BlockDevice,
+            m if m == libc::S_IFSOCK as Mode => Self::Socket,
+            _ => Self::Other,
+        }
+    }
+
+    /// The kind of file that the standard library says it is, where there
+    /// are no modes to go by.
+    #[cfg(not(unix))]
+    fn of_type(file_type: fs::FileType) -> Self {
+        if file_type.is_file() {
+            Self::File
+        } else if file_type.is_dir() {
+            Self::Directory
+        } else if file_type.is_symlink() {
+            Self::Symlink
+        } else {
+            Self::Other
+        }
+    }
+}
+
+impl From<fs::Metadata> for Metadata {
+    fn from(metadata: fs::Metadata) -> Self {
+        Self::Std(metadata)
+    }
+}
+
+impl Metadata {
+    /// The standard library’s metadata, for the things that only it has,
+    /// such as the flags on BSD systems and attributes on Windows.
+    #[cfg(any(
+        windows,
+        target_os = "macos",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "dragonfly"
+    ))]
+    pub fn as_std(&self) -> Option<&fs::Metadata> {
+        match self {
+            Self::Std(metadata) => Some(metadata),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(_) => None,
+            #[cfg(unix)]
+            Self::Remote(_) => None,
+            Self::TimedOut => None,
+        }
+    }
+
+    /// Metadata read from the source.
+    #[cfg(unix)]
+    pub fn remote(fields: Fields, source: &Arc<dyn Source>) -> Self {
+        Self::Remote(Remote {
+            fields,
+            source: Arc::clone(source),
+        })
+    }
+
+    /// Where this came from, if it isn’t this computer’s filesystem.
+    #[cfg(unix)]
+    pub fn source(&self) -> Option<&Arc<dyn Source>> {
+        match self {
+            Self::Remote(remote) => Some(&remote.source),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(_) => None,
+            Self::Std(_) | Self::TimedOut => None,
+        }
+    }
+
+    /// Whether the field is known. Only metadata that didn’t come from this
+    /// computer, or that never came back, can be missing any, and the
+    /// methods for the ones that are missing return zeroes.
+    pub fn has(&self, field: Field) -> bool {
+        match self {
+            #[cfg(unix)]
+            Self::Remote(Remote { fields, .. }) => match field {
+                Field::Mode => fields.mode.is_some(),
+                Field::Size => fields.size.is_some(),
+                Field::Owner => fields.uid.is_some(),
+                Field::Links => fields.nlink.is_some(),
+                Field::Inode => fields.ino.is_some(),
+                Field::Blocks => fields.blocks.is_some(),
+                Field::Accessed => fields.accessed.is_some(),
+                Field::Modified => fields.modified.is_some(),
+                Field::Changed => fields.changed.is_some(),
+                Field::Created => fields.created.is_some(),
+            },
+            Self::TimedOut => false,
+            Self::Std(_) => true,
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(_) => true,
+        }
+    }
+
+    pub fn kind(&self) -> Kind {
+        match self {
+            #[cfg(unix)]
+            Self::Std(metadata) => Kind::of_mode(metadata.mode()),
+            #[cfg(not(unix))]
+            Self::Std(metadata) => Kind::of_type(metadata.file_type()),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => Kind::of_mode(stat.mode),
+            #[cfg(unix)]
+            Self::Remote(remote) => remote.fields.mode.map_or(Kind::Other, Kind::of_mode),
+            Self::TimedOut => Kind::Other,
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.kind() == Kind::Directory
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.kind() == Kind::File
+    }
+
+    pub fn is_symlink(&self) -> bool {
+        self.kind() == Kind::Symlink
+    }
+
+    /// The size of the file in bytes.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u64 {
+        match self {
+            Self::Std(metadata) => metadata.len(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.size,
+            #[cfg(unix)]
+            Self::Remote(remote) => remote.fields.size.unwrap_or(0),
+            Self::TimedOut => 0,
+        }
+    }
+
+    pub fn modified(&self) -> io::Result<SystemTime> {
+        match self {
+            Self::Std(metadata) => metadata.modified(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => Ok(stat.modified.system_time()),
+            #[cfg(unix)]
+            Self::Remote(remote) => remote.fields.modified.ok_or_else(not_known),
+            Self::TimedOut => Err(not_known()),
+        }
+    }
+
+    pub fn accessed(&self) -> io::Result<SystemTime> {
+        match self {
+            Self::Std(metadata) => metadata.accessed(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => Ok(stat.accessed.system_time()),
+            #[cfg(unix)]
+            Self::Remote(remote) => remote.fields.accessed.ok_or_else(not_known),
+            Self::TimedOut => Err(not_known()),
+        }
+    }
+
+    pub fn created(&self) -> io::Result<SystemTime> {
+        match self {
+            Self::Std(metadata) => metadata.created(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.created.map(Timestamp::system_time).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Unsupported,
+                    "creation time is not available for the filesystem",
+                )
+            }),
+            #[cfg(unix)]
+            Self::Remote(remote) => remote.fields.created.ok_or_else(not_known),
+            Self::TimedOut => Err(not_known()),
+        }
+    }
+}
+
+/// The error for a time that isn’t known, as it wasn’t read along with the
+/// rest of a file’s metadata.
+fn not_known() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "time is not known")
+}
+
+/// The fields that only Unix systems have, each of which is the same as the
+/// method on `std::os::unix::fs::MetadataExt` with the same name.
+#[cfg(unix)]
+impl Metadata {
+    pub fn mode(&self) -> u32 {
+        match self {
+            Self::Std(metadata) => metadata.mode(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.mode,
+            Self::Remote(remote) => remote.fields.mode.unwrap_or(0),
+            Self::TimedOut => 0,
+        }
+    }
+
+    pub fn uid(&self) -> u32 {
+        match self {
+            Self::Std(metadata) => metadata.uid(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.uid,
+            Self::Remote(remote) => remote.fields.uid.unwrap_or(0),
+            Self::TimedOut => 0,
+        }
+    }
+
+    pub fn gid(&self) -> u32 {
+        match self {
+            Self::Std(metadata) => metadata.gid(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.gid,
+            Self::Remote(remote) => remote.fields.gid.unwrap_or(0),
+            Self::TimedOut => 0,
+        }
+    }
+
+    pub fn nlink(&self) -> u64 {
+        match self {
+            Self::Std(metadata) => metadata.nlink(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.nlink,
+            Self::Remote(remote) => remote.fields.nlink.unwrap_or(0),
+            Self::TimedOut => 0,
+        }
+    }
+
+    pub fn ino(&self) -> u64 {
+        match self {
+            Self::Std(metadata) => metadata.ino(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.ino,
+            Self::Remote(remote) => remote.fields.ino.unwrap_or(0),
+            Self::TimedOut => 0,
+        }
+    }
+
+    pub fn dev(&self) -> u64 {
+        match self {
+            Self::Std(metadata) => metadata.dev(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.dev,
+            Self::Remote(_) | Self::TimedOut => 0,
+        }
+    }
+
+    pub fn rdev(&self) -> u64 {
+        match self {
+            Self::Std(metadata) => metadata.rdev(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.rdev,
+            Self::Remote(_) | Self::TimedOut => 0,
+        }
+    }
+
+    pub fn blocks(&self) -> u64 {
+        match self {
+            Self::Std(metadata) => metadata.blocks(),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => stat.blocks,
+            Self::Remote(remote) => remote.fields.blocks.unwrap_or(0),
+            Self::TimedOut => 0,
+        }
+    }
+
+    /// When the file’s metadata was last changed.
+    pub fn changed(&self) -> io::Result<SystemTime> {
+        match self {
+            Self::Std(metadata) => Ok(since_epoch(metadata.ctime(), metadata.ctime_nsec() as u32)),
+            #[cfg(all(target_os = "linux", feature = "io-uring"))]
+            Self::Stat(stat) => Ok(stat.changed.system_time()),
+            Self::Remote(remote) => remote.fields.changed.ok_or_else(not_known),
+            Self::TimedOut => Err(not_known()),
+        }
+    }
+}
+
+/// The time that’s the number of seconds and nanoseconds after the epoch,
+/// where the seconds can be negative for times before it.
+#[cfg(unix)]
+fn since_epoch(secs: i64, nanos: u32) -> SystemTime {
+    let nanos = Duration::from_nanos(u64::from(nanos));
+    match u64::try_from(secs) {
+        Ok(secs) => UNIX_EPOCH + Duration::from_secs(secs) + nanos,
+        Err(_) => UNIX_EPOCH - Duration::from_secs(secs.unsigned_abs()) + nanos,
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn kinds_of_modes() {
+        assert_eq!(Kind::of_mode(0o100_644), Kind::File);
+        assert_eq!(Kind::of_mode(0o040_755), Kind::Directory);
+        assert_eq!(Kind::of_mode(0o120_777), Kind::Symlink);
+        assert_eq!(Kind::of_mode(0o010_600), Kind::Pipe);
+        assert_eq!(Kind::of_mode(0o020_620), Kind::CharDevice);
+        assert_eq!(Kind::of_mode(0o060_660), Kind::BlockDevice);
+        assert_eq!(Kind::of_mode(0o140_755), Kind::Socket);
+    }
+
+    #[test]
+    fn directory() {
+        let metadata = Metadata::from(fs::symlink_metadata("src").unwrap());
+        assert!(metadata.is_dir());
+        assert!(!metadata.is_file());
+    }
+    #[test]
+    fn timed_out() {
+        let metadata = Metadata::TimedOut;
+        assert_eq!(metadata.kind(), Kind::Other);
+        assert!(!metadata.has(Field::Mode));
+        assert!(!metadata.has(Field::Modified));
+        assert!(metadata.modified().is_err());
+    }
+}
@@ -8,5 +8,7 @@ pub mod dir_action;
 pub mod feature;
 pub mod fields;
 pub mod filter;
+pub mod metadata;
 pub mod mounts;
 pub mod recursive_size;
+pub mod walk;
@@ -0,0 +1,47 @@
+use crate::fs::mounts::{Error, MountedFs};
+use libc::{c_char, getmntinfo, MNT_NOWAIT};
+use std::ffi::{CStr, OsStr};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::PathBuf;
+use std::slice;
+
+// NetBSD replaced `statfs` with `statvfs`, but kept the same field names.
+#[cfg(not(target_os = "netbsd"))]
+use libc::statfs;
+#[cfg(target_os = "netbsd")]
+use libc::statvfs as statfs;
+
+/// Get a list of all mounted filesystems
+pub fn mounts() -> Result<Vec<MountedFs>, Error> {
+    let mut mntbuf: *mut statfs = std::ptr::null_mut();
+
+    // SAFETY:
+    // Calling external "C" function getmntinfo. It points `mntbuf` at an
+    // array of `count` entries that it manages itself, and which stays
+    // valid until the next call. `MNT_NOWAIT` returns cached information
+    // rather than waiting on filesystems that might not be responding.
+    let count = unsafe { getmntinfo(&mut mntbuf, MNT_NOWAIT) };
+    if count <= 0 || mntbuf.is_null() {
+        return Err(Error::IOError(io::Error::last_os_error()));
+    }
+
+    // SAFETY: getmntinfo succeeded, so the buffer holds `count` entries.
+    let entries = unsafe { slice::from_raw_parts(mntbuf, count as usize) };
+    Ok(entries.iter().map(mounted_fs).collect())
+}
+
+fn mounted_fs(mnt: &statfs) -> MountedFs {
+    // SAFETY: Converting null terminated "C" string
+    let mount_point = unsafe { CStr::from_ptr(mnt.f_mntonname.as_ptr().cast::<c_char>()) };
+    // SAFETY: Converting null terminated "C" string
+    let fstype = unsafe { CStr::from_ptr(mnt.f_fstypename.as_ptr().cast::<c_char>()) };
+    // SAFETY: Converting null terminated "C" string
+    let source = unsafe { CStr::from_ptr(mnt.f_mntfromname.as_ptr().cast::<c_char>()) };
+
+    MountedFs {
+        dest: PathBuf::from(OsStr::from_bytes(mount_point.to_bytes())),
+        fstype: fstype.to_string_lossy().into(),
+        source: source.to_string_lossy().into(),
+    }
+}
@@ -1,16 +1,97 @@
 use crate::fs::mounts::{Error, MountedFs};
-use proc_mounts::MountList;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
 
-/// Get a list of all mounted filesystems
+/// Get a list of all mounted filesystems, as seen from eza’s own mount
+/// namespace
 pub fn mounts() -> Result<Vec<MountedFs>, Error> {
-    Ok(MountList::new()
-        .map_err(Error::IOError)?
-        .0
-        .iter()
-        .map(|mount| MountedFs {
-            dest: mount.dest.clone(),
-            fstype: mount.fstype.clone(),
-            source: mount.source.to_string_lossy().into(),
-        })
-        .collect())
+    let contents = fs::read_to_string("/proc/self/mountinfo").map_err(Error::IOError)?;
+    Ok(contents.lines().filter_map(parse_mountinfo_line).collect())
+}
+
+/// Parse one line of `/proc/self/mountinfo`, which looks like this:
+///
+/// ```text
+/// 36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 - ext3 /dev/root rw,errors=continue
+/// ```
+///
+/// The fifth field is the mount point. After that comes a variable number
+/// of optional fields, ended by a lone `-`, and then the filesystem type and
+/// the mount source.
+fn parse_mountinfo_line(line: &str) -> Option<MountedFs> {
+    let mut fields = line.split(' ');
+    let dest = fields.nth(4)?;
+
+    let mut fields = fields.skip_while(|field| *field != "-").skip(1);
+    let fstype = fields.next()?;
+    let source = fields.next()?;
+
+    Some(MountedFs {
+        dest: PathBuf::from(OsString::from_vec(unescape(dest))),
+        fstype: String::from_utf8_lossy(&unescape(fstype)).into_owned(),
+        source: String::from_utf8_lossy(&unescape(source)).into_owned(),
+    })
+}
+
+/// The kernel escapes spaces, tabs, newlines, and backslashes in each field
+/// as a backslash followed by three octal digits, such as `\040` for a space.
+fn unescape(field: &str) -> Vec<u8> {
+    let bytes = field.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let octal = bytes
+            .get(index + 1..index + 4)
+            .filter(|_| bytes[index] == b'\\')
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u8::from_str_radix(digits, 8).ok());
+
+        if let Some(byte) = octal {
+            result.push(byte);
+            index += 4;
+        } else {
+            result.push(bytes[index]);
+            index += 1;
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn optional_fields() {
+        let line = "36 35 98:0 /mnt1 /mnt/parent rw,noatime master:1 shared:2 - ext3 /dev/root rw,errors=continue";
+        let mount = parse_mountinfo_line(line).unwrap();
+        assert_eq!(mount.dest, PathBuf::from("/mnt/parent"));
+        assert_eq!(mount.fstype, "ext3");
+        assert_eq!(mount.source, "/dev/root");
+    }
+
+    #[test]
+    fn no_optional_fields() {
+        let line = "22 1 0:21 / /proc rw,nosuid,nodev,noexec,relatime - proc proc rw";
+        let mount = parse_mountinfo_line(line).unwrap();
+        assert_eq!(mount.dest, PathBuf::from("/proc"));
+        assert_eq!(mount.fstype, "proc");
+        assert_eq!(mount.source, "proc");
+    }
+
+    #[test]
+    fn escaped_spaces() {
+        let line = r"50 22 0:45 / /media/My\040Drive rw - vfat /dev/sdb1 rw";
+        let mount = parse_mountinfo_line(line).unwrap();
+        assert_eq!(mount.dest, PathBuf::from("/media/My Drive"));
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(parse_mountinfo_line("22 1 0:21 / /proc rw").is_none());
+    }
 }
@@ -2,15 +2,25 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::OnceLock;
 
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+mod bsd;
 #[cfg(target_os = "linux")]
 mod linux;
-#[cfg(target_os = "macos")]
-mod macos;
 
+#[cfg(any(
+    target_os = "macos",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+use bsd::mounts;
 #[cfg(target_os = "linux")]
 use linux::mounts;
-#[cfg(target_os = "macos")]
-use macos::mounts;
 
 /// Details of a mounted filesystem.
 #[derive(Clone)]
@@ -23,9 +33,6 @@ pub struct MountedFs {
 #[derive(Debug)]
 #[non_exhaustive]
 pub enum Error {
-    #[cfg(target_os = "macos")]
-    GetFSStatError(i32),
-    #[cfg(target_os = "linux")]
     IOError(std::io::Error),
 }
 
@@ -33,14 +40,8 @@ impl std::error::Error for Error {}
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        // Allow unreachable_patterns for windows build
-        #[allow(unreachable_patterns)]
         match self {
-            #[cfg(target_os = "macos")]
-            Error::GetFSStatError(err) => write!(f, "getfsstat failed: {err}"),
-            #[cfg(target_os = "linux")]
-            Error::IOError(err) => write!(f, "failed to read /proc/mounts: {err}"),
-            _ => write!(f, "Unknown error"),
+            Error::IOError(err) => write!(f, "failed to list mounted filesystems: {err}"),
         }
     }
 }
@@ -49,7 +50,7 @@ impl std::fmt::Display for Error {
 //
 // The map contains a mapping from the mounted directory path to the
 // corresponding mount information. If there's an error retrieving the mount
-// list or if we're not running on Linux or Mac, the map will be empty.
+// list or if we're not running on Linux, macOS, or a BSD, the map will be empty.
 //
 // Initialise this at application start so we don't have to look the details
 // up for every directory. Ideally this would only be done if the --mounts
@@ -63,7 +64,13 @@ pub(super) fn all_mounts() -> &'static HashMap<PathBuf, MountedFs> {
         #[allow(unused_mut)]
         let mut mount_map: HashMap<PathBuf, MountedFs> = HashMap::new();
 
-        #[cfg(any(target_os = "linux", target_os = "macos"))]
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "macos",
+            target_os = "freebsd",
+            target_os = "netbsd",
+            target_os = "openbsd"
+        ))]
         if let Ok(mounts) = mounts() {
             for mount in mounts {
                 mount_map.insert(mount.dest.clone(), mount);
@@ -0,0 +1,126 @@
+//! Going through directories and the ones inside them without listing them,
+//! for the options that need to look at files ahead of time or on their own:
+//! `--watch`, `--common-names`, and `--snapshot-out`.
+//!
+//! Also, writing files that other eza processes might be reading at the same
+//! time, such as caches and saved listings.
+
+use std::fs::{self, DirEntry};
+use std::io;
+use std::path::Path;
+
+use log::*;
+
+/// Reads the directory, and then the directories inside it, down to the
+/// given number of levels, or all the way down if there’s no limit. Each
+/// directory gets passed to `visit` along with its entries once it’s been
+/// read, before any of the directories inside it, which get gone into in
+/// order of their names. Directories starting with a dot only get gone into
+/// when dotfiles are wanted, and symlinks to directories never get followed.
+pub fn walk<F>(dir: &Path, levels: Option<usize>, dotfiles: bool, visit: &mut F)
+where
+    F: FnMut(&Path, io::Result<Vec<DirEntry>>),
+{
+    let entries = fs::read_dir(dir).and_then(Iterator::collect::<io::Result<Vec<_>>>);
+    let mut subdirs = entries.as_ref().map_or_else(
+        |_| Vec::new(),
+        |entries| {
+            entries
+                .iter()
+                .filter(|entry| dotfiles || !is_hidden(entry))
+                .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_dir()))
+                .map(DirEntry::path)
+                .collect()
+        },
+    );
+
+    visit(dir, entries);
+
+    let levels = levels.map(|levels| levels - 1);
+    if levels == Some(0) {
+        return;
+    }
+
+    subdirs.sort();
+    for subdir in subdirs {
+        walk(&subdir, levels, dotfiles, visit);
+    }
+}
+
+/// Whether the entry’s name starts with a dot.
+pub fn is_hidden(entry: &DirEntry) -> bool {
+    entry.file_name().to_string_lossy().starts_with('.')
+}
+
+/// Writes the file next to where it goes and then moves it into place, so
+/// anything reading it never sees it half-written, and a write that fails
+/// leaves the old one alone. The file it gets written to first is named
+/// after this process, so two runs of eza saving the same file at once
+/// don’t write over each other’s.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut temporary = path.as_os_str().to_owned();
+    temporary.push(format!(".{}.tmp", std::process::id()));
+    let temporary = Path::new(&temporary);
+
+    let result = fs::write(temporary, contents).and_then(|()| fs::rename(temporary, path));
+    if result.is_err() {
+        if let Err(e) = fs::remove_file(temporary) {
+            debug!("Couldn't remove {:?}: {}", temporary, e);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn walked(root: &Path, levels: Option<usize>, dotfiles: bool) -> Vec<PathBuf> {
+        let mut dirs = Vec::new();
+        walk(root, levels, dotfiles, &mut |dir, entries| {
+            assert!(entries.is_ok());
+            dirs.push(dir.strip_prefix(root).unwrap().to_path_buf());
+        });
+        dirs
+    }
+
+    #[test]
+    fn directories_in_order() {
+        let root = tempfile::tempdir().unwrap();
+        for dir in ["b/inner", "a", ".hidden/inner"] {
+            fs::create_dir_all(root.path().join(dir)).unwrap();
+        }
+        fs::write(root.path().join("c"), "").unwrap();
+
+        let all = ["", "a", "b", "b/inner"].map(PathBuf::from);
+        assert_eq!(walked(root.path(), None, false), all);
+        assert_eq!(walked(root.path(), Some(2), false), all[..3]);
+        assert_eq!(walked(root.path(), Some(1), false), all[..1]);
+        assert_eq!(
+            walked(root.path(), None, true),
+            ["", ".hidden", ".hidden/inner", "a", "b", "b/inner"].map(PathBuf::from)
+        );
+    }
+
+    #[test]
+    fn writes_over_the_old_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("saved");
+        write_atomically(&path, b"old").unwrap();
+        write_atomically(&path, b"new").unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), b"new");
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn failed_writes_leave_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("in the way");
+        fs::create_dir_all(path.join("not empty")).unwrap();
+
+        assert!(write_atomically(&path, b"contents").is_err());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+}
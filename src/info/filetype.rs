@@ -389,6 +389,18 @@ const EXTENSION_TYPES: Map<&'static str, FileType> = phf_map! {
 };
 
 impl FileType {
+    /// Every file name and extension with a type of its own, with the
+    /// extensions given with a leading dot.
+    pub(crate) fn all_names() -> impl Iterator<Item = (String, Self)> {
+        let names = FILENAME_TYPES
+            .entries()
+            .map(|(name, file_type)| ((*name).to_string(), file_type.clone()));
+        let exts = EXTENSION_TYPES
+            .entries()
+            .map(|(ext, file_type)| (format!(".{ext}"), file_type.clone()));
+        names.chain(exts)
+    }
+
     /// Lookup the file type based on the file's name, by the file name
     /// lowercase extension, or if the file could be compiled from related
     /// source code.
@@ -1,4 +1,12 @@
 #[allow(unused)]
+pub mod bookmarks;
+#[allow(unused)]
+pub mod cache;
+#[allow(unused)]
+pub mod duplicates;
+#[allow(unused)]
+pub mod frecency;
+#[allow(unused)]
 pub mod fs;
 #[allow(unused)]
 pub mod info;
@@ -9,4 +17,14 @@ pub mod options;
 #[allow(unused)]
 pub mod output;
 #[allow(unused)]
+pub mod progress;
+#[allow(unused)]
 pub mod theme;
+#[allow(unused)]
+pub mod timeout;
+#[allow(unused)]
+pub mod timing;
+#[allow(unused)]
+pub mod watch;
+#[allow(unused)]
+pub mod which;
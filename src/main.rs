@@ -21,29 +21,61 @@
 #![allow(clippy::upper_case_acronyms)]
 #![allow(clippy::wildcard_imports)]
 
+#[cfg(unix)]
+use std::cell::RefCell;
+#[cfg(unix)]
+use std::collections::HashMap;
 use std::env;
 use std::ffi::{OsStr, OsString};
-use std::io::{self, stdin, ErrorKind, IsTerminal, Read, Write};
-use std::path::{Component, PathBuf};
-use std::process::exit;
+use std::io::{self, stdin, BufWriter, ErrorKind, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{exit, Child, ChildStdin, Command, Stdio};
+#[cfg(unix)]
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::thread;
 
 use nu_ansi_term::{AnsiStrings as ANSIStrings, Style};
 
+use crate::fs::dir_action::RecurseOptions;
 use crate::fs::feature::git::GitCache;
+#[cfg(unix)]
+use crate::fs::feature::listing_snapshot;
+#[cfg(unix)]
+use crate::fs::feature::sftp;
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+use crate::fs::feature::uring;
 use crate::fs::filter::GitIgnore;
-use crate::fs::{Dir, File};
+#[cfg(unix)]
+use crate::fs::metadata::{Metadata, Source};
+use crate::fs::{Dir, DotFilter, File};
 use crate::options::stdin::FilesInput;
-use crate::options::{vars, Options, OptionsResult, Vars};
-use crate::output::{details, escape, file_name, grid, grid_details, lines, Mode, View};
+use crate::options::{vars, ListingSnapshot, Options, OptionsError, OptionsResult, Vars};
+use crate::output::section::Section;
+use crate::output::table::{SharedWidths, SizeFormat};
+use crate::output::total::{self, Totals};
+use crate::output::{
+    compare, details, escape, file_name, grid, grid_details, html, json, lines, stat_view, summary,
+    template, thumbnails, Mode, OutputFormat, Paging, View,
+};
 use crate::theme::Theme;
 use log::*;
 
+mod bookmarks;
+mod cache;
+mod duplicates;
+mod frecency;
 mod fs;
 mod info;
 mod logger;
 mod options;
 mod output;
+mod progress;
 mod theme;
+mod timeout;
+mod timing;
+mod watch;
+mod which;
 
 fn main() {
     #[cfg(unix)]
@@ -56,9 +88,32 @@ fn main() {
     let stdout_istty = io::stdout().is_terminal();
 
     let mut input = String::new();
+    let bookmarked: Vec<OsString>;
+    let resolved: Vec<OsString>;
+    let found: Vec<OsString>;
     let args: Vec<_> = env::args_os().skip(1).collect();
     match Options::parse(args.iter().map(std::convert::AsRef::as_ref), &LiveVars) {
-        OptionsResult::Ok(options, mut input_paths) => {
+        OptionsResult::Ok(mut options, mut input_paths) => {
+            // Colours get printed whether or not they’re going to a
+            // terminal, as they’re for other programs to use.
+            if options.print_ls_colors {
+                println!("{}", options.theme.to_theme(true).to_ls_colors());
+                exit(exits::SUCCESS);
+            }
+
+            // `--which` lists the programs it finds instead of any paths.
+            if let Some(name) = &options.which {
+                let duplicates;
+                (found, duplicates) =
+                    which::find_all(name, &env::var_os(vars::PATH).unwrap_or_default());
+                if found.is_empty() {
+                    eprintln!("eza: {}: not found in PATH", name.to_string_lossy());
+                    exit(exits::RUNTIME_ERROR);
+                }
+                input_paths = found.iter().map(OsString::as_os_str).collect();
+                options.view.file_style.which_duplicates = Some(Arc::new(duplicates));
+            }
+
             // List the current directory by default.
             // (This has to be done here, otherwise git_options won’t see it.)
             if input_paths.is_empty() {
@@ -81,12 +136,85 @@ fn main() {
                 }
             }
 
+            // Arguments like `@proj` and `z:proj` get replaced before
+            // anything else looks at them, and the directories that end up
+            // being listed get counted as visited.
+            if !options.bookmarks.entries.is_empty() {
+                bookmarked = options.bookmarks.resolve_args(&input_paths);
+                input_paths = bookmarked.iter().map(OsString::as_os_str).collect();
+            }
+
+            if let Some(frecency) = &options.frecency {
+                resolved = frecency.resolve_args(&input_paths);
+                input_paths = resolved.iter().map(OsString::as_os_str).collect();
+                frecency.record(&input_paths);
+            }
+
+            if options.unique {
+                input_paths = duplicates::unique(&input_paths);
+
+                // `--compare` had two arguments when the options were
+                // parsed, but they might have turned out to be the same.
+                if options.view.compare && input_paths.len() != 2 {
+                    eprintln!("eza: {}", OptionsError::CompareNeedsTwo(input_paths.len()));
+                    exit(exits::OPTIONS_ERROR);
+                }
+            }
+
+            if options.debug_timing {
+                timing::enable();
+            }
+
+            if let Some(limit) = options.io_timeout {
+                if let Err(e) = timeout::set(limit) {
+                    eprintln!("eza: Couldn't start the threads for --io-timeout: {e}");
+                    exit(exits::RUNTIME_ERROR);
+                }
+            }
+
+            if options.io_uring {
+                #[cfg(all(target_os = "linux", feature = "io-uring"))]
+                uring::enable();
+                #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+                warn!("EZA_IO_URING is set, but this build of eza can’t use io_uring");
+            }
+
+            if let Some(common_names) = &mut options.view.file_style.common_names {
+                *common_names = Arc::new(duplicates::find_common(
+                    &input_paths,
+                    options.dir_action.levels(),
+                    options.filter.dot_filter != DotFilter::JustFiles,
+                ));
+            }
+
+            let audit_exit_code = options.audit_exit_code;
+            let audit = options.view.file_style.security_audit.clone();
+
             let git = git_options(&options, &input_paths);
-            let writer = io::stdout();
+            let html = options.view.format == OutputFormat::Html;
+            // A listing that gets redrawn has nothing to page through.
+            let writer = if html {
+                Output::Html(Vec::new())
+            } else if options.watch.is_some() {
+                Output::Stdout(io::stdout())
+            } else {
+                Output::new(options.view.paging, stdout_istty)
+            };
+
+            // A pager has the terminal to itself, so there’s nowhere to show
+            // how far the listing has got.
+            if options.dir_action.recurse_options().is_some()
+                && options.watch.is_none()
+                && !matches!(writer, Output::Pager(..))
+            {
+                progress::start();
+            }
             let git_repos = git_repos(&options, &input_paths);
 
             let console_width = options.view.width.actual_terminal_width();
-            let theme = options.theme.to_theme(stdout_istty);
+            // An HTML page gets its colours from the escape sequences, so
+            // they’re on unless they’ve been turned off.
+            let theme = options.theme.to_theme(stdout_istty || html);
             let exa = Exa {
                 options,
                 writer,
@@ -95,13 +223,33 @@ fn main() {
                 console_width,
                 git,
                 git_repos,
+                shared_widths: None,
+                numerics: OnceLock::new(),
+                #[cfg(unix)]
+                sessions: RefCell::default(),
+                #[cfg(unix)]
+                saved: None,
             };
 
             info!("matching on exa.run");
-            match exa.run() {
+            let result = match exa.options.watch.clone() {
+                Some(watch) => exa.watch(&watch, stdout_istty),
+                None => exa.run(),
+            };
+            timing::report();
+
+            match result {
                 Ok(exit_status) => {
                     trace!("exa.run: exit Ok(exit_status)");
-                    exit(exit_status);
+                    match audit_exit_code {
+                        Some(code)
+                            if exit_status == exits::SUCCESS
+                                && audit.as_ref().is_some_and(|audit| audit.flagged_any()) =>
+                        {
+                            exit(code);
+                        }
+                        _ => exit(exit_status),
+                    }
                 }
 
                 Err(e) if e.kind() == ErrorKind::BrokenPipe => {
@@ -125,6 +273,10 @@ fn main() {
             print!("{version_str}");
         }
 
+        OptionsResult::Completions(completions) => {
+            print!("{completions}");
+        }
+
         OptionsResult::InvalidOptions(error) => {
             eprintln!("eza: {error}");
 
@@ -143,7 +295,7 @@ pub struct Exa<'args> {
     pub options: Options,
 
     /// The output handle that we write to.
-    pub writer: io::Stdout,
+    pub writer: Output,
 
     /// List of the free command-line arguments that should correspond to file
     /// names (anything that isn’t an option).
@@ -164,6 +316,165 @@ pub struct Exa<'args> {
     pub git: Option<GitCache>,
 
     pub git_repos: bool,
+
+    /// How wide each column of every directory’s table has to be, if they
+    /// should all be lined up with each other.
+    pub shared_widths: Option<SharedWidths>,
+
+    /// How to format the numbers in footers and summaries, which is loaded
+    /// the first time one gets rendered and shared by every directory after.
+    pub numerics: OnceLock<locale::Numeric>,
+
+    /// The connections to the hosts in `sftp://` arguments, one for each
+    /// host, so listing several directories there only logs in once.
+    #[cfg(unix)]
+    pub sessions: RefCell<HashMap<String, Arc<dyn Source>>>,
+
+    /// The listing given to `--snapshot-in`, once it’s been loaded, which
+    /// gets listed in place of any paths.
+    #[cfg(unix)]
+    pub saved: Option<Arc<listing_snapshot::Saved>>,
+}
+
+/// Where the listing gets written: to stdout, to a pager, or into a buffer
+/// until it’s known whether the listing is long enough to need a pager, or
+/// until the whole listing can be turned into an HTML page.
+pub enum Output {
+    Stdout(io::Stdout),
+
+    /// The start of the listing, and how many more lines can be written
+    /// before it no longer fits on the screen and needs a pager.
+    Buffer(Vec<u8>, usize),
+
+    Pager(Child, BufWriter<ChildStdin>),
+    Html(Vec<u8>),
+}
+
+impl Output {
+    fn new(paging: Paging, stdout_istty: bool) -> Self {
+        match paging {
+            Paging::Always => Self::pager(),
+            Paging::Auto(height) if stdout_istty => match height.actual_terminal_height() {
+                Some(height) => Self::Buffer(Vec::new(), height),
+                None => Self::Stdout(io::stdout()),
+            },
+            Paging::Auto(_) | Paging::Never => Self::Stdout(io::stdout()),
+        }
+    }
+
+    /// Start the user’s pager, or `less` if they haven’t picked one, falling
+    /// back to stdout if it can’t be started.
+    fn pager() -> Self {
+        let pager = env::var(vars::PAGER)
+            .ok()
+            .filter(|pager| !pager.trim().is_empty())
+            .unwrap_or_else(|| String::from("less"));
+
+        // The pager is run by the shell, as Git does, so it can have
+        // arguments with quotes in them.
+        #[cfg(unix)]
+        let mut command = {
+            let mut command = Command::new("sh");
+            command.arg("-c").arg(&pager);
+            command
+        };
+
+        #[cfg(windows)]
+        let mut command = {
+            let mut words = pager.split_whitespace();
+            let mut command = Command::new(words.next().unwrap_or("less"));
+            command.args(words);
+            command
+        };
+
+        command.stdin(Stdio::piped());
+
+        // Like Git, tell `less` to pass colours through, unless the user has
+        // already configured it.
+        if env::var_os(vars::LESS).is_none() {
+            command.env(vars::LESS, "R");
+        }
+
+        match command.spawn() {
+            Ok(mut child) => match child.stdin.take() {
+                Some(stdin) => Self::Pager(child, BufWriter::new(stdin)),
+                None => Self::Stdout(io::stdout()),
+            },
+            Err(e) => {
+                warn!("Failed to start pager {pager:?}: {e}");
+                Self::Stdout(io::stdout())
+            }
+        }
+    }
+
+    /// Write out anything that was held back, and wait for the pager to be
+    /// closed so the shell doesn’t get the terminal back too early.
+    fn finish(self) -> io::Result<()> {
+        progress::finish();
+
+        match self {
+            Self::Stdout(mut stdout) => stdout.flush(),
+
+            // The listing was finished before it filled the screen.
+            Self::Buffer(buffer, _) => {
+                let mut stdout = io::stdout();
+                stdout.write_all(&buffer)?;
+                stdout.flush()
+            }
+
+            Self::Pager(mut child, mut stdin) => {
+                let flushed = stdin.flush();
+                drop(stdin);
+                child.wait()?;
+                flushed
+            }
+
+            Self::Html(buffer) => {
+                let mut stdout = io::stdout();
+                stdout.write_all(html::page(&buffer).as_bytes())?;
+                stdout.flush()
+            }
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Stdout(stdout) => {
+                progress::finish();
+                return stdout.write(buf);
+            }
+            Self::Buffer(buffer, lines_left) => {
+                let lines = buf.split(|&byte| byte == b'\n').count() - 1;
+                buffer.extend_from_slice(buf);
+                if lines < *lines_left {
+                    *lines_left -= lines;
+                    return Ok(buf.len());
+                }
+            }
+            Self::Html(buffer) => return buffer.write(buf),
+            Self::Pager(_, stdin) => {
+                progress::finish();
+                return stdin.write(buf);
+            }
+        }
+
+        // The listing no longer fits on the screen, so what’s been held back
+        // gets handed to the pager, and the rest gets written straight to it.
+        if let Self::Buffer(buffer, _) = std::mem::replace(self, Self::pager()) {
+            self.write_all(&buffer)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Stdout(stdout) => stdout.flush(),
+            Self::Buffer(..) | Self::Html(..) => Ok(()),
+            Self::Pager(_, stdin) => stdin.flush(),
+        }
+    }
 }
 
 /// The “real” environment variables type.
@@ -180,7 +491,20 @@ impl Vars for LiveVars {
 /// listed before they’re actually listed, if the options demand it.
 fn git_options(options: &Options, args: &[&OsStr]) -> Option<GitCache> {
     if options.should_scan_for_git() {
-        Some(args.iter().map(PathBuf::from).collect())
+        let mut git: GitCache = timing::time(timing::Phase::Git, || {
+            args.iter().map(PathBuf::from).collect()
+        });
+        if options.filter.git_ignore_debug {
+            git.report_ignored_files();
+        }
+        git.ignore_submodules(options.git_submodules());
+        if let Some(reference) = options.git_since() {
+            git.compare_with(reference);
+        }
+        if options.should_query_git_in_background() {
+            git.query_in_background();
+        }
+        Some(git)
     } else {
         None
     }
@@ -188,7 +512,7 @@ fn git_options(options: &Options, args: &[&OsStr]) -> Option<GitCache> {
 
 #[cfg(not(feature = "git"))]
 fn git_repos(_options: &Options, _args: &[&OsStr]) -> bool {
-    return false;
+    false
 }
 
 #[cfg(feature = "git")]
@@ -247,20 +571,79 @@ impl<'args> Exa<'args> {
     ///
     /// Will return `Err` if printing to stderr fails.
     pub fn run(mut self) -> io::Result<i32> {
+        let exit_status = self.list();
+        self.writer.finish()?;
+        exit_status
+    }
+
+    /// Lists the paths again each time anything in them changes, clearing
+    /// the terminal first, and running the `--exec` command after each
+    /// listing but the first, until eza gets interrupted.
+    fn watch(mut self, watch: &watch::Options, clear: bool) -> io::Result<i32> {
+        let levels = self.options.dir_action.levels();
+        let dotfiles = self.options.filter.dot_filter != DotFilter::JustFiles;
+
+        let mut snapshot = watch::Snapshot::take(&self.input_paths, levels, dotfiles);
+        let mut changed = Vec::new();
+
+        loop {
+            if clear {
+                write!(self.writer, "\x1B[2J\x1B[H")?;
+            }
+            self.list()?;
+            self.writer.flush()?;
+
+            if let Some(command) = watch.exec.as_ref().filter(|_| !changed.is_empty()) {
+                watch::exec(command, &changed);
+            }
+
+            changed = loop {
+                thread::sleep(watch::INTERVAL);
+                let later = watch::Snapshot::take(&self.input_paths, levels, dotfiles);
+                let paths = snapshot.changes(&later);
+                if !paths.is_empty() {
+                    snapshot = later;
+                    break paths;
+                }
+            };
+
+            // Git statuses are likely to have changed along with the files.
+            self.git = git_options(&self.options, &self.input_paths);
+        }
+    }
+
+    fn list(&mut self) -> io::Result<i32> {
         debug!("Running with options: {:#?}", self.options);
 
+        #[cfg(unix)]
+        match self.options.listing_snapshot.clone() {
+            Some(ListingSnapshot::Save(path)) => return self.save_listing(&path),
+            Some(ListingSnapshot::Load(path)) => {
+                let listing = listing_snapshot::Listing::load(&path)
+                    .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+                self.saved = Some(Arc::new(listing.into()));
+            }
+            None => {}
+        }
+
+        if self.options.view.compare {
+            return self.print_comparison();
+        }
+
+        if self.options.view.stat {
+            return self.print_stats();
+        }
+
+        if matches!(self.options.view.mode, Mode::Details(ref opts) if opts.align_sections) {
+            self.shared_widths = Some(self.measure());
+        }
+
         let mut files = Vec::new();
         let mut dirs = Vec::new();
         let mut exit_status = 0;
 
-        for file_path in &self.input_paths {
-            match File::from_args(
-                PathBuf::from(file_path),
-                None,
-                None,
-                self.options.view.deref_links,
-                self.options.view.total_size,
-            ) {
+        for (file_path, file) in self.argument_files() {
+            match file {
                 Err(e) => {
                     exit_status = 2;
                     writeln!(io::stderr(), "{file_path:?}: {e}")?;
@@ -291,75 +674,372 @@ impl<'args> Exa<'args> {
         let no_files = files.is_empty();
         let is_only_dir = dirs.len() == 1 && no_files;
 
-        self.options.filter.filter_argument_files(&mut files);
+        self.options
+            .filter
+            .filter_argument_files(&mut files, self.git.as_ref());
         self.print_files(None, files)?;
 
-        self.print_dirs(dirs, no_files, is_only_dir, exit_status)
+        let mut first = no_files;
+        self.print_dirs(dirs, &mut first, is_only_dir, exit_status, 1)
+    }
+
+    /// Reads the files given as arguments, each along with the path to
+    /// report it by if it can’t be read. A listing loaded with
+    /// `--snapshot-in` has the arguments it was saved with instead.
+    fn argument_files(&self) -> Vec<(OsString, io::Result<File<'static>>)> {
+        #[cfg(unix)]
+        if let Some(saved) = &self.saved {
+            let source: Arc<dyn Source> = saved.clone();
+            return saved
+                .arguments()
+                .iter()
+                .map(|path| {
+                    let file = self.source_file(&source, path.clone(), None);
+                    (path.clone().into_os_string(), file)
+                })
+                .collect();
+        }
+
+        self.input_paths
+            .iter()
+            .map(|path| (path.to_os_string(), self.argument_file(path)))
+            .collect()
     }
 
+    /// Reads the file that a command-line argument names, which is on
+    /// another computer when it’s an `sftp://` URL and `--sftp` is on.
+    fn argument_file(&self, file_path: &OsStr) -> io::Result<File<'static>> {
+        #[cfg(unix)]
+        if self.options.sftp && sftp::is_url(file_path) {
+            let location = sftp::Location::parse(file_path)?;
+            let source = self.session(&location)?;
+            let home = (location.path == Path::new(".")).then(|| String::from("~"));
+            return self.source_file(&source, location.path, home);
+        }
+
+        File::from_args(
+            PathBuf::from(file_path),
+            None,
+            None,
+            self.options.view.deref_links,
+            self.options.view.total_size,
+        )
+    }
+
+    /// Reads a file from somewhere other than this computer’s filesystem.
+    #[cfg(unix)]
+    fn source_file(
+        &self,
+        source: &Arc<dyn Source>,
+        path: PathBuf,
+        name: Option<String>,
+    ) -> io::Result<File<'static>> {
+        let fields = source.symlink_metadata(&path)?;
+        Ok(File::with_metadata(
+            path,
+            None,
+            name,
+            Metadata::remote(fields, source),
+            self.options.view.deref_links,
+            self.options.view.total_size,
+        ))
+    }
+
+    /// The connection to the host that a location is on, logging in the
+    /// first time it’s needed.
+    #[cfg(unix)]
+    fn session(&self, location: &sftp::Location) -> io::Result<Arc<dyn Source>> {
+        let mut sessions = self.sessions.borrow_mut();
+        if let Some(session) = sessions.get(&location.prefix()) {
+            return Ok(Arc::clone(session));
+        }
+
+        let session: Arc<dyn Source> = Arc::new(sftp::Session::connect(location)?);
+        sessions.insert(location.prefix(), Arc::clone(&session));
+        Ok(session)
+    }
+
+    /// Saves the details of the files that would have been listed, instead
+    /// of listing them.
+    #[cfg(unix)]
+    fn save_listing(&self, path: &Path) -> io::Result<i32> {
+        let (listing, errors) = listing_snapshot::Listing::take(
+            &self.input_paths,
+            self.options.dir_action.levels(),
+            self.options.filter.dot_filter != DotFilter::JustFiles,
+        );
+
+        let mut exit_status = exits::SUCCESS;
+        for (path, e) in errors {
+            exit_status = 2;
+            writeln!(io::stderr(), "{path:?}: {e}")?;
+        }
+
+        listing
+            .save(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+        Ok(exit_status)
+    }
+
+    /// Reads the files in a directory that pass the filters, reporting any
+    /// that can’t be read.
+    fn dir_children<'dir>(&self, dir: &'dir Dir) -> io::Result<Vec<File<'dir>>> {
+        let (children, errors) = self.read_children(dir);
+        for (path, e) in errors {
+            writeln!(io::stderr(), "[{}: {}]", path.display(), e)?;
+        }
+
+        Ok(children)
+    }
+
+    /// Reads the files in a directory that pass the filters, along with the
+    /// errors from any that can’t be read.
+    fn read_children<'dir>(&self, dir: &'dir Dir) -> (Vec<File<'dir>>, Vec<(PathBuf, io::Error)>) {
+        let mut children = Vec::new();
+        let mut errors = Vec::new();
+        let git_ignore = self.options.filter.git_ignore == GitIgnore::CheckAndIgnore;
+        for file in dir.files(
+            self.options.filter.dot_filter,
+            self.git.as_ref(),
+            git_ignore,
+            self.options.view.deref_links,
+            self.options.view.total_size,
+        ) {
+            match file {
+                Ok(file) => children.push(file),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        self.options
+            .filter
+            .filter_child_files(&mut children, self.git.as_ref());
+        (children, errors)
+    }
+
+    /// Goes through everything that’s going to be listed without printing
+    /// any of it, measuring how wide each column of the tables has to be so
+    /// that every directory’s table can be lined up, for `--align-sections`.
+    /// The files and directories get read again as they’re listed, which is
+    /// when any errors get reported.
+    fn measure(&self) -> SharedWidths {
+        let mut shared = SharedWidths::default();
+        let mut files = Vec::new();
+        let mut dirs = Vec::new();
+
+        for (_, file) in self.argument_files() {
+            let Ok(f) = file else {
+                continue;
+            };
+
+            if f.points_to_directory() && !self.options.dir_action.treat_dirs_as_files() {
+                dirs.extend(f.to_dir());
+            } else {
+                files.push(f);
+            }
+        }
+
+        self.options
+            .filter
+            .filter_argument_files(&mut files, self.git.as_ref());
+        self.measure_files(None, files, &mut shared);
+        self.measure_dirs(&dirs, 1, &mut shared);
+        shared
+    }
+
+    fn measure_dirs(&self, dirs: &[Dir], depth: usize, shared: &mut SharedWidths) {
+        let Some(recurse_opts) = self.options.dir_action.recurse_options() else {
+            return;
+        };
+
+        for dir in dirs {
+            let (children, _) = self.read_children(dir);
+            let child_dirs = if recurse_opts.is_too_deep(depth) {
+                Vec::new()
+            } else {
+                children
+                    .iter()
+                    .filter(|f| is_recursable(f, recurse_opts) && !f.is_loop())
+                    .filter_map(|f| f.to_dir().ok())
+                    .collect()
+            };
+
+            if recurse_opts.shows_level(depth) {
+                self.measure_files(Some(dir), children, shared);
+            }
+            self.measure_dirs(&child_dirs, depth + 1, shared);
+        }
+    }
+
+    fn measure_files(&self, dir: Option<&Dir>, files: Vec<File<'_>>, shared: &mut SharedWidths) {
+        let Mode::Details(ref opts) = self.options.view.mode else {
+            return;
+        };
+        if files.is_empty() {
+            return;
+        }
+
+        let r = details::Render {
+            dir,
+            files,
+            theme: &self.theme,
+            file_style: &self.options.view.file_style,
+            opts,
+            recurse: self.options.dir_action.recurse_options(),
+            filter: &self.options.filter,
+            git_ignoring: self.options.filter.git_ignore == GitIgnore::CheckAndIgnore,
+            git: self.git.as_ref(),
+            git_repos: self.git_repos,
+            shared_widths: None,
+            total: false,
+            numerics: self.numerics.get_or_init(total::load_numerics),
+        };
+        r.measure(shared);
+    }
+
+    /// Lists the contents of the two directories given to `--compare`
+    /// against each other.
+    fn print_comparison(&mut self) -> io::Result<i32> {
+        let mut dirs = Vec::new();
+        for file_path in &self.input_paths {
+            match Dir::read_dir(PathBuf::from(file_path)) {
+                Ok(dir) => dirs.push(dir),
+                Err(e) => {
+                    writeln!(io::stderr(), "{file_path:?}: {e}")?;
+                    return Ok(2);
+                }
+            }
+        }
+
+        let left = self.dir_children(&dirs[0])?;
+        let right = self.dir_children(&dirs[1])?;
+        let r = compare::Render {
+            left,
+            right,
+            theme: &self.theme,
+            file_style: &self.options.view.file_style,
+            filter: &self.options.filter,
+        };
+        r.render(&mut self.writer)?;
+
+        Ok(exits::SUCCESS)
+    }
+
+    /// Shows everything about each of the files given to `--stat`, without
+    /// listing the contents of directories.
+    fn print_stats(&mut self) -> io::Result<i32> {
+        let mut files = Vec::new();
+        let mut exit_status = exits::SUCCESS;
+
+        for (file_path, file) in self.argument_files() {
+            match file {
+                Ok(file) => files.push(file),
+                Err(e) => {
+                    exit_status = 2;
+                    writeln!(io::stderr(), "{file_path:?}: {e}")?;
+                }
+            }
+        }
+
+        let r = stat_view::Render {
+            files,
+            theme: &self.theme,
+            file_style: &self.options.view.file_style,
+            git: self.git.as_ref(),
+            keep_link_owners: self.options.view.keep_link_owners,
+        };
+        r.render(&mut self.writer)?;
+
+        Ok(exit_status)
+    }
+
+    /// Prints each directory in turn, along with the directories inside it
+    /// when recursing. The directories are at the given depth, where the
+    /// ones given on the command-line are at depth 1, and `first` keeps
+    /// track of whether anything has been printed yet, as levels that
+    /// `--level` skips don’t print anything.
     fn print_dirs(
         &mut self,
         dir_files: Vec<Dir>,
-        mut first: bool,
+        first: &mut bool,
         is_only_dir: bool,
         exit_status: i32,
+        depth: usize,
     ) -> io::Result<i32> {
         let View {
             file_style: file_name::Options { quote_style, .. },
             ..
         } = self.options.view;
+        let recurse_opts = self.options.dir_action.recurse_options();
+        let per_file = self.options.view.format.is_per_file();
+        let null_terminated = self.options.view.null_terminated;
+
+        let sections = self.options.view.sections.clone();
+        let headings = !per_file && !null_terminated;
+
         for dir in dir_files {
-            // Put a gap between directories, or between the list of files and
-            // the first directory.
-            if first {
-                first = false;
-            } else {
-                writeln!(&mut self.writer)?;
+            let shown = recurse_opts.map_or(true, |r| r.shows_level(depth));
+
+            let mut children = self.dir_children(&dir)?;
+            progress::add_directory(children.len());
+            if let Some(snapshots) = &self.options.view.file_style.highlight_new {
+                snapshots.record(&dir);
             }
+            self.options.filter.sort_files(&mut children);
 
-            if !is_only_dir {
-                let mut bits = Vec::new();
-                escape(
-                    dir.path.display().to_string(),
-                    &mut bits,
-                    Style::default(),
-                    Style::default(),
+            // Only count the files up when there’s a template to use them.
+            let section =
+                (sections.header.is_some() || sections.footer.is_some()).then(|| Section {
+                    path: &dir.path,
+                    totals: Totals::of(&children),
+                    theme: &self.theme,
+                    size_format: size_format(&self.options.view.mode),
+                    numerics: self.numerics.get_or_init(total::load_numerics),
                     quote_style,
-                );
-                writeln!(&mut self.writer, "{}:", ANSIStrings(&bits))?;
-            }
-
-            let mut children = Vec::new();
-            let git_ignore = self.options.filter.git_ignore == GitIgnore::CheckAndIgnore;
-            for file in dir.files(
-                self.options.filter.dot_filter,
-                self.git.as_ref(),
-                git_ignore,
-                self.options.view.deref_links,
-                self.options.view.total_size,
-            ) {
-                match file {
-                    Ok(file) => children.push(file),
-                    Err((path, e)) => writeln!(io::stderr(), "[{}: {}]", path.display(), e)?,
+                });
+
+            // JSON Lines, templates, and null-terminated lists have no
+            // headings or gaps, as every entry has its path.
+            if shown && headings {
+                // Put a gap between directories, or between the list of
+                // files and the first directory.
+                if *first {
+                    *first = false;
+                } else {
+                    writeln!(&mut self.writer)?;
+                }
+
+                // A custom header gets printed even when there’s only the
+                // one directory, as it can say more than just its path.
+                if let (Some(header), Some(section)) = (&sections.header, &section) {
+                    writeln!(&mut self.writer, "{}", section.render(header).strings())?;
+                } else if !is_only_dir {
+                    let mut bits = Vec::new();
+                    escape(
+                        dir.display_path(),
+                        &mut bits,
+                        Style::default(),
+                        Style::default(),
+                        quote_style,
+                    );
+                    writeln!(&mut self.writer, "{}:", ANSIStrings(&bits))?;
                 }
             }
 
-            self.options.filter.filter_child_files(&mut children);
-            self.options.filter.sort_files(&mut children);
+            let footer = section
+                .zip(sections.footer.as_ref())
+                .filter(|_| shown && headings)
+                .map(|(section, footer)| section.render(footer));
 
-            if let Some(recurse_opts) = self.options.dir_action.recurse_options() {
-                let depth = dir
-                    .path
-                    .components()
-                    .filter(|&c| c != Component::CurDir)
-                    .count()
-                    + 1;
+            if let Some(recurse_opts) = recurse_opts {
                 if !recurse_opts.tree && !recurse_opts.is_too_deep(depth) {
                     let mut child_dirs = Vec::new();
-                    for child_dir in children
-                        .iter()
-                        .filter(|f| f.is_directory() && !f.is_all_all)
-                    {
+                    for child_dir in children.iter().filter(|f| is_recursable(f, recurse_opts)) {
+                        if child_dir.is_loop() {
+                            writeln!(io::stderr(), "{}: [loop]", child_dir.path.display())?;
+                            continue;
+                        }
+
                         match child_dir.to_dir() {
                             Ok(d) => child_dirs.push(d),
                             Err(e) => {
@@ -368,16 +1048,23 @@ impl<'args> Exa<'args> {
                         }
                     }
 
-                    self.print_files(Some(&dir), children)?;
-                    match self.print_dirs(child_dirs, false, false, exit_status) {
-                        Ok(_) => (),
-                        Err(e) => return Err(e),
+                    if shown {
+                        self.print_files(Some(&dir), children)?;
+                    }
+                    if let Some(footer) = footer {
+                        writeln!(&mut self.writer, "{}", footer.strings())?;
                     }
+                    self.print_dirs(child_dirs, first, false, exit_status, depth + 1)?;
                     continue;
                 }
             }
 
-            self.print_files(Some(&dir), children)?;
+            if shown {
+                self.print_files(Some(&dir), children)?;
+            }
+            if let Some(footer) = footer {
+                writeln!(&mut self.writer, "{}", footer.strings())?;
+            }
         }
 
         Ok(exit_status)
@@ -385,6 +1072,10 @@ impl<'args> Exa<'args> {
 
     /// Prints the list of files using whichever view is selected.
     fn print_files(&mut self, dir: Option<&Dir>, files: Vec<File<'_>>) -> io::Result<()> {
+        timing::time(timing::Phase::Render, || self.render_files(dir, files))
+    }
+
+    fn render_files(&mut self, dir: Option<&Dir>, files: Vec<File<'_>>) -> io::Result<()> {
         if files.is_empty() {
             return Ok(());
         }
@@ -393,107 +1084,203 @@ impl<'args> Exa<'args> {
         let View {
             ref mode,
             ref file_style,
+            total,
             ..
         } = self.options.view;
 
-        match (mode, self.console_width) {
-            (Mode::Grid(ref opts), Some(console_width)) => {
-                let filter = &self.options.filter;
-                let r = grid::Render {
-                    files,
-                    theme,
-                    file_style,
-                    opts,
-                    console_width,
-                    filter,
-                };
-                r.render(&mut self.writer)
-            }
-
-            (Mode::Grid(_), None) | (Mode::Lines, _) => {
-                let filter = &self.options.filter;
-                let r = lines::Render {
-                    files,
-                    theme,
-                    file_style,
-                    filter,
-                };
-                r.render(&mut self.writer)
-            }
-
-            (Mode::Details(ref opts), _) => {
-                let filter = &self.options.filter;
-                let recurse = self.options.dir_action.recurse_options();
-
-                let git_ignoring = self.options.filter.git_ignore == GitIgnore::CheckAndIgnore;
-                let git = self.git.as_ref();
-                let git_repos = self.git_repos;
-                let r = details::Render {
-                    dir,
-                    files,
-                    theme,
-                    file_style,
-                    opts,
-                    recurse,
-                    filter,
-                    git_ignoring,
-                    git,
-                    git_repos,
-                };
-                r.render(&mut self.writer)
-            }
-
-            (Mode::GridDetails(ref opts), Some(console_width)) => {
-                let details = &opts.details;
-                let row_threshold = opts.row_threshold;
-
-                let filter = &self.options.filter;
-                let git_ignoring = self.options.filter.git_ignore == GitIgnore::CheckAndIgnore;
-                let git = self.git.as_ref();
-                let git_repos = self.git_repos;
-
-                let r = grid_details::Render {
-                    dir,
-                    files,
-                    theme,
-                    file_style,
-                    details,
-                    filter,
-                    row_threshold,
-                    git_ignoring,
-                    git,
-                    console_width,
-                    git_repos,
-                };
-                r.render(&mut self.writer)
-            }
-
-            (Mode::GridDetails(ref opts), None) => {
-                let opts = &opts.to_details_options();
-                let filter = &self.options.filter;
-                let recurse = self.options.dir_action.recurse_options();
-                let git_ignoring = self.options.filter.git_ignore == GitIgnore::CheckAndIgnore;
-                let git = self.git.as_ref();
-                let git_repos = self.git_repos;
-
-                let r = details::Render {
-                    dir,
-                    files,
-                    theme,
-                    file_style,
-                    opts,
-                    recurse,
-                    filter,
-                    git_ignoring,
-                    git,
-                    git_repos,
-                };
-                r.render(&mut self.writer)
+        if self.options.view.format == OutputFormat::JsonLines {
+            let r = json::Render {
+                files,
+                filter: &self.options.filter,
+                git: self.git.as_ref(),
+                opts: &self.options.view.json,
+                keep_link_owners: self.options.view.keep_link_owners,
+            };
+            return r.render(&mut self.writer);
+        }
+
+        if let Some(ref template) = self.options.view.template {
+            let r = template::Render {
+                files,
+                filter: &self.options.filter,
+                git: self.git.as_ref(),
+                template,
+                keep_link_owners: self.options.view.keep_link_owners,
+            };
+            return r.render(&mut self.writer);
+        }
+
+        let numerics = self.numerics.get_or_init(total::load_numerics);
+        if let Some(ref opts) = self.options.view.summary {
+            let r = summary::Render {
+                files,
+                theme,
+                file_style,
+                filter: &self.options.filter,
+                size_format: size_format(mode),
+                opts,
+                total,
+                numerics,
+            };
+            r.render(&mut self.writer)
+        } else {
+            match (mode, self.console_width) {
+                (Mode::Grid(ref opts), Some(console_width)) => {
+                    let filter = &self.options.filter;
+                    let r = grid::Render {
+                        files,
+                        theme,
+                        file_style,
+                        opts,
+                        console_width,
+                        filter,
+                        total,
+                        numerics,
+                    };
+                    r.render(&mut self.writer)
+                }
+
+                (Mode::Thumbnails(ref opts), Some(console_width)) => {
+                    let filter = &self.options.filter;
+                    let r = thumbnails::Render {
+                        files,
+                        theme,
+                        file_style,
+                        opts,
+                        console_width,
+                        filter,
+                        total,
+                        numerics,
+                    };
+                    r.render(&mut self.writer)
+                }
+
+                (Mode::Grid(_) | Mode::Thumbnails(_), None) | (Mode::Lines, _) => {
+                    let filter = &self.options.filter;
+                    let r = lines::Render {
+                        files,
+                        theme,
+                        file_style,
+                        filter,
+                        null_terminated: self.options.view.null_terminated,
+                        full_paths: self.options.dir_action.recurse_options().is_some(),
+                        total,
+                        numerics,
+                    };
+                    r.render(&mut self.writer)
+                }
+
+                (Mode::Details(ref opts), _) => {
+                    let filter = &self.options.filter;
+                    let recurse = self.options.dir_action.recurse_options();
+
+                    let git_ignoring = self.options.filter.git_ignore == GitIgnore::CheckAndIgnore;
+                    let git = self.git.as_ref();
+                    let git_repos = self.git_repos;
+                    let r = details::Render {
+                        dir,
+                        files,
+                        theme,
+                        file_style,
+                        opts,
+                        recurse,
+                        filter,
+                        git_ignoring,
+                        git,
+                        git_repos,
+                        shared_widths: self.shared_widths.as_ref(),
+                        total,
+                        numerics,
+                    };
+                    r.render(&mut self.writer)
+                }
+
+                (Mode::GridDetails(ref opts), Some(console_width)) => {
+                    let details = &opts.details;
+                    let row_threshold = opts.row_threshold;
+                    let grid = opts.grid;
+
+                    let filter = &self.options.filter;
+                    let git_ignoring = self.options.filter.git_ignore == GitIgnore::CheckAndIgnore;
+                    let git = self.git.as_ref();
+                    let git_repos = self.git_repos;
+
+                    let r = grid_details::Render {
+                        dir,
+                        files,
+                        theme,
+                        file_style,
+                        details,
+                        filter,
+                        row_threshold,
+                        grid,
+                        git_ignoring,
+                        git,
+                        console_width,
+                        git_repos,
+                        total,
+                        numerics,
+                    };
+                    r.render(&mut self.writer)
+                }
+
+                (Mode::GridDetails(ref opts), None) => {
+                    let opts = &opts.to_details_options();
+                    let filter = &self.options.filter;
+                    let recurse = self.options.dir_action.recurse_options();
+                    let git_ignoring = self.options.filter.git_ignore == GitIgnore::CheckAndIgnore;
+                    let git = self.git.as_ref();
+                    let git_repos = self.git_repos;
+
+                    let r = details::Render {
+                        dir,
+                        files,
+                        theme,
+                        file_style,
+                        opts,
+                        recurse,
+                        filter,
+                        git_ignoring,
+                        git,
+                        git_repos,
+                        shared_widths: None,
+                        total,
+                        numerics,
+                    };
+                    r.render(&mut self.writer)
+                }
             }
         }
     }
 }
 
+/// Whether a file is a directory that a recursive listing should go into.
+fn is_recursable(file: &File<'_>, recurse_opts: RecurseOptions) -> bool {
+    (file.is_directory()
+        || (recurse_opts.follow_symlinks && file.is_link() && file.points_to_directory()))
+        && !file.is_all_all
+}
+
+/// How sizes in footers get formatted. They follow the `--binary` and
+/// `--bytes` flags when there’s a table for them to apply to.
+fn size_format(mode: &Mode) -> SizeFormat {
+    match mode {
+        Mode::Details(details::Options {
+            table: Some(ref table),
+            ..
+        })
+        | Mode::GridDetails(grid_details::Options {
+            details:
+                details::Options {
+                    table: Some(ref table),
+                    ..
+                },
+            ..
+        }) => table.size_format,
+        _ => SizeFormat::default(),
+    }
+}
+
 mod exits {
 
     /// Exit code for when exa runs OK.
@@ -0,0 +1,114 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use log::*;
+
+use crate::bookmarks::Bookmarks;
+use crate::options::theme::{config_dir, config_mapping};
+use crate::options::vars::{self, Vars};
+
+/// The configuration file key that bookmarks go under.
+const BOOKMARKS_KEY: &str = "bookmarks";
+
+impl Bookmarks {
+    /// Reads the bookmarks out of `config.yml` in the configuration
+    /// directory. Not having one just means there are no bookmarks.
+    pub fn deduce<V: Vars>(vars: &V) -> Self {
+        let Some(path) = config_dir(vars).map(|dir| dir.join("config.yml")) else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => {
+                let home = vars.get(vars::HOME).map(PathBuf::from);
+                parse_bookmarks(&contents, home.as_deref())
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("Couldn't read {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Parse the `bookmarks` mapping out of the configuration file. Paths can
+/// start with `~` to mean the home directory, and names can’t have slashes
+/// in them, as anything after a slash is a path inside the bookmark.
+fn parse_bookmarks(contents: &str, home: Option<&Path>) -> Bookmarks {
+    let entries = config_mapping(contents, BOOKMARKS_KEY)
+        .into_iter()
+        .filter_map(|(name, path)| {
+            if name.is_empty() || name.contains('/') {
+                warn!("Skipping bookmark with bad name {:?}", name);
+                None
+            } else {
+                Some((name, expand_home(&path, home)))
+            }
+        })
+        .collect();
+
+    Bookmarks { entries }
+}
+
+/// Replaces a `~` at the start of a path with the home directory.
+fn expand_home(path: &str, home: Option<&Path>) -> PathBuf {
+    match (path.strip_prefix('~'), home) {
+        (Some(""), Some(home)) => home.to_path_buf(),
+        (Some(rest), Some(home)) if rest.starts_with('/') => home.join(&rest[1..]),
+        _ => PathBuf::from(path),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn parse(contents: &str) -> Vec<(String, PathBuf)> {
+        parse_bookmarks(contents, Some(Path::new("/home/me"))).entries
+    }
+
+    fn entry(name: &str, path: &str) -> (String, PathBuf) {
+        (String::from(name), PathBuf::from(path))
+    }
+
+    #[test]
+    fn block() {
+        let contents = "---\nbookmarks:  # short names\n  proj: ~/code/project\n  \"etc\": /etc\n\nother: 1\n  ignored: /nowhere\n";
+        assert_eq!(
+            parse(contents),
+            vec![entry("proj", "/home/me/code/project"), entry("etc", "/etc")]
+        );
+    }
+
+    #[test]
+    fn one_line() {
+        assert_eq!(
+            parse("bookmarks: {proj: ~/code/project, home: ~}"),
+            vec![
+                entry("proj", "/home/me/code/project"),
+                entry("home", "/home/me")
+            ]
+        );
+    }
+
+    #[test]
+    fn bad_names() {
+        assert_eq!(
+            parse("bookmarks:\n  a/b: /tmp\n  ~other: ~other\n"),
+            vec![entry("~other", "~other")]
+        );
+    }
+
+    #[test]
+    fn no_bookmarks() {
+        assert_eq!(parse("theme: dark\n"), vec![]);
+        assert_eq!(parse(""), vec![]);
+    }
+
+    #[test]
+    fn no_home() {
+        assert_eq!(expand_home("~/code", None), PathBuf::from("~/code"));
+    }
+}
@@ -0,0 +1,513 @@
+//! Generating shell completion scripts.
+//!
+//! Rather than being written by hand, these scripts are built from the same
+//! table of flags that the options parser uses, so a flag can’t be added to
+//! eza without also becoming completable. Each flag’s description is taken
+//! from its line in the `--help` text.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::options::flags;
+use crate::options::help;
+use crate::options::parser::{Arg, MatchedFlags, TakesValue, Values};
+use crate::options::OptionsError;
+
+/// Which shell to generate completions for.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    Nushell,
+    PowerShell,
+}
+
+/// A completion script for one shell, ready to be printed.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct Completions(pub Shell);
+
+impl Completions {
+    /// Determines whether to print a completion script, and for which shell,
+    /// based on the user’s command-line arguments. Like `--help`, this takes
+    /// priority over everything else, and doesn’t check for redundant flags.
+    pub fn deduce(matches: &MatchedFlags<'_>) -> Result<Option<Self>, OptionsError> {
+        let Some(word) = matches.get(&flags::GENERATE_COMPLETIONS)? else {
+            return Ok(None);
+        };
+
+        let shell = match word.to_str() {
+            Some("bash") => Shell::Bash,
+            Some("zsh") => Shell::Zsh,
+            Some("fish") => Shell::Fish,
+            Some("nushell" | "nu") => Shell::Nushell,
+            Some("powershell" | "pwsh") => Shell::PowerShell,
+            _ => {
+                return Err(OptionsError::BadArgument(
+                    &flags::GENERATE_COMPLETIONS,
+                    word.into(),
+                ))
+            }
+        };
+
+        Ok(Some(Self(shell)))
+    }
+}
+
+/// A flag from the options table, along with everything the completion
+/// scripts need to know about it.
+struct Completable {
+    short: Option<char>,
+    long: &'static str,
+    description: String,
+    values: Option<Values>,
+    takes_value: TakesValue,
+}
+
+impl Completable {
+    /// Every flag eza accepts, in the order they appear in the table.
+    fn all() -> Vec<Self> {
+        let descriptions = descriptions();
+
+        flags::ALL_ARGS
+            .0
+            .iter()
+            .map(|arg: &&Arg| {
+                let values = match arg.takes_value {
+                    TakesValue::Necessary(values) | TakesValue::Optional(values, _) => values,
+                    TakesValue::Forbidden => None,
+                };
+
+                Self {
+                    short: arg.short.map(char::from),
+                    long: arg.long,
+                    description: descriptions.get(arg.long).cloned().unwrap_or_default(),
+                    values,
+                    takes_value: arg.takes_value,
+                }
+            })
+            .collect()
+    }
+
+    /// The flag’s spellings, short one first, such as `-s` and `--sort`.
+    fn spellings(&self) -> Vec<String> {
+        let mut spellings = Vec::new();
+        if let Some(short) = self.short {
+            spellings.push(format!("-{short}"));
+        }
+        spellings.push(format!("--{}", self.long));
+        spellings
+    }
+}
+
+/// The column that descriptions start at in the help text’s option lines.
+const DESCRIPTION_COLUMN: usize = 29;
+
+/// Reads the description of each long flag out of the help text, whose
+/// option lines look like `  -s, --sort SORT_FIELD      which field to sort by`.
+/// Only the first line of a description gets used, which starts on the line
+/// after the flag when the flag is too long to leave room for it.
+///
+/// Every section of the help text gets read, even the ones for features that
+/// this build lacks, as their flags are still accepted.
+fn descriptions() -> HashMap<String, String> {
+    let mut descriptions = HashMap::new();
+    let mut lines = help::ALL_SECTIONS
+        .iter()
+        .flat_map(|section| section.lines())
+        .peekable();
+
+    while let Some(line) = lines.next() {
+        if !line.trim_start().starts_with('-') {
+            continue;
+        }
+
+        let (names, description) = match split_description(line) {
+            Some(split) => split,
+            None => (line, lines.peek().copied().unwrap_or_default()),
+        };
+
+        for name in names.trim().split(", ") {
+            let Some(long) = name.strip_prefix("--") else {
+                continue;
+            };
+
+            // This leaves `--git[=WHEN]` as `git[`, and `--colo[u]r` has
+            // to cover both of its spellings.
+            let long = long.split([' ', '=']).next().unwrap_or(long);
+            for spelling in [long.replace("[u]", ""), long.replace("[u]", "u")] {
+                descriptions
+                    .entry(spelling.trim_end_matches('[').to_string())
+                    .or_insert_with(|| description.trim().to_string());
+            }
+        }
+    }
+
+    descriptions
+}
+
+/// Splits an option line from the help text into its flags and the start of
+/// its description, if the description starts on the same line. The flags
+/// can come right up to the description with only one space between them,
+/// so it gets found by its column.
+fn split_description(line: &str) -> Option<(&str, &str)> {
+    let names = line.get(..DESCRIPTION_COLUMN)?;
+    let description = line.get(DESCRIPTION_COLUMN..)?;
+
+    if names.ends_with(' ') && !description.starts_with(' ') && !description.is_empty() {
+        Some((names, description))
+    } else {
+        None
+    }
+}
+
+impl fmt::Display for Completions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let flags = Completable::all();
+
+        match self.0 {
+            Shell::Bash => bash(f, &flags),
+            Shell::Zsh => zsh(f, &flags),
+            Shell::Fish => fish(f, &flags),
+            Shell::Nushell => nushell(f, &flags),
+            Shell::PowerShell => powershell(f, &flags),
+        }
+    }
+}
+
+fn bash(f: &mut fmt::Formatter<'_>, flags: &[Completable]) -> fmt::Result {
+    writeln!(f, "# shellcheck shell=bash")?;
+    writeln!(f, "_eza() {{")?;
+    writeln!(f, "    local cur=${{COMP_WORDS[COMP_CWORD]}}")?;
+    writeln!(f, "    local prev=${{COMP_WORDS[COMP_CWORD-1]}}")?;
+    writeln!(f)?;
+    writeln!(f, "    case \"$prev\" in")?;
+    for flag in flags {
+        if let Some(values) = flag.values {
+            writeln!(f, "        {})", flag.spellings().join("|"))?;
+            writeln!(
+                f,
+                "            mapfile -t COMPREPLY < <(compgen -W '{}' -- \"$cur\")",
+                values.join(" ")
+            )?;
+            writeln!(f, "            return")?;
+            writeln!(f, "            ;;")?;
+        }
+    }
+    writeln!(f, "    esac")?;
+    writeln!(f)?;
+    writeln!(f, "    case \"$cur\" in")?;
+    writeln!(f, "        -*)")?;
+    let all = flags
+        .iter()
+        .flat_map(Completable::spellings)
+        .collect::<Vec<_>>();
+    writeln!(
+        f,
+        "            mapfile -t COMPREPLY < <(compgen -W '{}' -- \"$cur\")",
+        all.join(" ")
+    )?;
+    writeln!(f, "            ;;")?;
+    writeln!(f, "        *)")?;
+    writeln!(
+        f,
+        "            mapfile -t COMPREPLY < <(compgen -f -- \"$cur\")"
+    )?;
+    writeln!(f, "            ;;")?;
+    writeln!(f, "    esac")?;
+    writeln!(f, "}} &&")?;
+    writeln!(f, "complete -o filenames -o bashdefault -F _eza eza")
+}
+
+fn zsh(f: &mut fmt::Formatter<'_>, flags: &[Completable]) -> fmt::Result {
+    /// Square brackets and colons have special meanings in a spec.
+    fn escape(text: &str) -> String {
+        text.replace('\'', "'\\''")
+            .replace('[', "\\[")
+            .replace(']', "\\]")
+            .replace(':', "\\:")
+    }
+
+    writeln!(f, "#compdef eza")?;
+    writeln!(f)?;
+    writeln!(f, "__eza() {{")?;
+    writeln!(f, "    _arguments -s -S \\")?;
+    for flag in flags {
+        let names = flag.short.map_or_else(
+            || format!("--{}", flag.long),
+            |short| format!("{{'-{short}',--{}}}", flag.long),
+        );
+
+        let argument = match (flag.takes_value, flag.values) {
+            (TakesValue::Forbidden, _) => String::new(),
+            (TakesValue::Necessary(_), Some(values)) => format!(":value:({})", values.join(" ")),
+            (TakesValue::Necessary(_), None) => String::from(":value: "),
+            (TakesValue::Optional(..), Some(values)) => format!("::value:({})", values.join(" ")),
+            (TakesValue::Optional(..), None) => String::from("::value: "),
+        };
+
+        writeln!(
+            f,
+            "        {names}'[{}]{}' \\",
+            escape(&flag.description),
+            argument
+        )?;
+    }
+    writeln!(f, "        '*:filename:_files'")?;
+    writeln!(f, "}}")?;
+    writeln!(f)?;
+    writeln!(f, "__eza")
+}
+
+fn fish(f: &mut fmt::Formatter<'_>, flags: &[Completable]) -> fmt::Result {
+    fn escape(text: &str) -> String {
+        text.replace('\\', "\\\\").replace('\'', "\\'")
+    }
+
+    for flag in flags {
+        write!(f, "complete -c eza")?;
+        match flag.short {
+            Some(short) if short.is_ascii_alphanumeric() => write!(f, " -s {short}")?,
+            Some(short) => write!(f, " -s '{short}'")?,
+            None => {}
+        }
+        write!(f, " -l {}", flag.long)?;
+        if !flag.description.is_empty() {
+            write!(f, " -d '{}'", escape(&flag.description))?;
+        }
+
+        match (flag.takes_value, flag.values) {
+            (TakesValue::Forbidden, _) => {}
+            (TakesValue::Necessary(_), Some(values)) => write!(f, " -x -a '{}'", values.join(" "))?,
+            (TakesValue::Necessary(_), None) => write!(f, " -r")?,
+            (TakesValue::Optional(..), Some(values)) => write!(f, " -a '{}'", values.join(" "))?,
+            (TakesValue::Optional(..), None) => {}
+        }
+
+        writeln!(f)?;
+    }
+
+    Ok(())
+}
+
+fn nushell(f: &mut fmt::Formatter<'_>, flags: &[Completable]) -> fmt::Result {
+    writeln!(f, "export extern \"eza\" [")?;
+    for flag in flags {
+        let mut signature = format!("--{}", flag.long);
+
+        // Nushell only allows letters and digits as short flags.
+        if let Some(short) = flag.short.filter(char::is_ascii_alphanumeric) {
+            signature.push_str(&format!("(-{short})"));
+        }
+
+        if let TakesValue::Necessary(_) = flag.takes_value {
+            signature.push_str(": string");
+        }
+
+        writeln!(f, "    {signature:<30} # {}", flag.description)?;
+    }
+    writeln!(f, "    ...args")?;
+    writeln!(f, "]")
+}
+
+fn powershell(f: &mut fmt::Formatter<'_>, flags: &[Completable]) -> fmt::Result {
+    fn quote(text: &str) -> String {
+        format!("'{}'", text.replace('\'', "''"))
+    }
+
+    writeln!(
+        f,
+        "Register-ArgumentCompleter -Native -CommandName 'eza' -ScriptBlock {{"
+    )?;
+    writeln!(
+        f,
+        "    param($wordToComplete, $commandAst, $cursorPosition)"
+    )?;
+    writeln!(f)?;
+    writeln!(f, "    $previous = $commandAst.CommandElements |")?;
+    writeln!(
+        f,
+        "        Where-Object {{ $_.Extent.EndOffset -lt $cursorPosition }} |"
+    )?;
+    writeln!(f, "        Select-Object -Last 1")?;
+    writeln!(f)?;
+    writeln!(f, "    $values = switch -CaseSensitive (\"$previous\") {{")?;
+    for flag in flags {
+        if let Some(values) = flag.values {
+            let values = values.iter().map(|v| quote(v)).collect::<Vec<_>>();
+            for spelling in flag.spellings() {
+                writeln!(
+                    f,
+                    "        {} {{ {} }}",
+                    quote(&spelling),
+                    values.join(", ")
+                )?;
+            }
+        }
+    }
+    writeln!(f, "    }}")?;
+    writeln!(f)?;
+    writeln!(f, "    if ($values) {{")?;
+    writeln!(
+        f,
+        "        $values | Where-Object {{ $_ -clike \"$wordToComplete*\" }} | ForEach-Object {{"
+    )?;
+    writeln!(
+        f,
+        "            [System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)"
+    )?;
+    writeln!(f, "        }}")?;
+    writeln!(f, "        return")?;
+    writeln!(f, "    }}")?;
+    writeln!(f)?;
+    writeln!(f, "    if (-not $wordToComplete.StartsWith('-')) {{")?;
+    writeln!(f, "        return")?;
+    writeln!(f, "    }}")?;
+    writeln!(f)?;
+    writeln!(f, "    @(")?;
+    for flag in flags {
+        let description = if flag.description.is_empty() {
+            flag.long.to_string()
+        } else {
+            flag.description.clone()
+        };
+
+        for spelling in flag.spellings() {
+            writeln!(
+                f,
+                "        @({}, {})",
+                quote(&spelling),
+                quote(&description)
+            )?;
+        }
+    }
+    writeln!(
+        f,
+        "    ) | Where-Object {{ $_[0] -clike \"$wordToComplete*\" }} | ForEach-Object {{"
+    )?;
+    writeln!(
+        f,
+        "        [System.Management.Automation.CompletionResult]::new($_[0], $_[0], 'ParameterName', $_[1])"
+    )?;
+    writeln!(f, "    }}")?;
+    writeln!(f, "}}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::options::{Options, OptionsResult};
+    use std::ffi::OsStr;
+
+    #[test]
+    fn generate() {
+        let args = vec![OsStr::new("--generate-completions"), OsStr::new("zsh")];
+        let opts = Options::parse(args, &None);
+        assert!(matches!(
+            opts,
+            OptionsResult::Completions(Completions(Shell::Zsh))
+        ));
+    }
+
+    #[test]
+    fn generate_for_nothing() {
+        let args = vec![OsStr::new("--generate-completions=tcsh")];
+        let opts = Options::parse(args, &None);
+        assert!(matches!(
+            opts,
+            OptionsResult::InvalidOptions(OptionsError::BadArgument(_, _))
+        ));
+    }
+
+    #[test]
+    fn every_flag_has_a_description() {
+        for flag in Completable::all() {
+            assert!(
+                !flag.description.is_empty(),
+                "--{} is missing from the help text",
+                flag.long
+            );
+        }
+    }
+
+    /// Each flag’s description has to be the first line of its own, and not
+    /// the rest of the one above it.
+    #[test]
+    fn descriptions_start_on_their_flags_lines() {
+        let lines = help::ALL_SECTIONS
+            .iter()
+            .flat_map(|section| section.lines())
+            .collect::<Vec<_>>();
+
+        let mentions = |line: &str, long: &str| {
+            let names = split_description(line).map_or(line, |(names, _)| names);
+            let flag = format!("--{long}");
+            [names.replace("[u]", ""), names.replace("[u]", "u")]
+                .iter()
+                .any(|names| {
+                    names.match_indices(&flag).any(|(i, _)| {
+                        !names[i + flag.len()..]
+                            .starts_with(|c: char| c.is_ascii_alphanumeric() || c == '-')
+                    })
+                })
+        };
+
+        for flag in Completable::all() {
+            let found = lines.iter().enumerate().any(|(i, line)| {
+                line.trim_start().starts_with('-')
+                    && mentions(line, flag.long)
+                    && match split_description(line) {
+                        Some((_, description)) => description.trim() == flag.description,
+                        None => lines.get(i + 1).map(|next| next.trim()) == Some(&flag.description),
+                    }
+            });
+
+            assert!(
+                found,
+                "--{} has the description {:?}",
+                flag.long, flag.description
+            );
+        }
+    }
+
+    #[test]
+    fn description_next_to_its_flag() {
+        let descriptions = descriptions();
+        assert!(descriptions["color-scale-time"].starts_with("shade every timestamp"));
+        assert!(descriptions["colour-scale-time"].starts_with("shade every timestamp"));
+    }
+
+    #[test]
+    fn colour_spellings() {
+        let descriptions = descriptions();
+        assert_eq!(descriptions.get("color"), descriptions.get("colour"));
+        assert!(descriptions.contains_key("colour-scale-mode"));
+    }
+
+    #[test]
+    fn every_flag_in_every_shell() {
+        for shell in [
+            Shell::Bash,
+            Shell::Zsh,
+            Shell::Fish,
+            Shell::Nushell,
+            Shell::PowerShell,
+        ] {
+            let script = Completions(shell).to_string();
+            for arg in flags::ALL_ARGS.0 {
+                assert!(
+                    script.contains(arg.long),
+                    "--{} is missing from the {shell:?} completions",
+                    arg.long
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn sort_values() {
+        let script = Completions(Shell::Bash).to_string();
+        assert!(script.contains("-s|--sort)"));
+        assert!(script.contains("compgen -W 'name Name size"));
+    }
+}
@@ -3,7 +3,7 @@
 use crate::options::parser::MatchedFlags;
 use crate::options::{flags, NumberSource, OptionsError};
 
-use crate::fs::dir_action::{DirAction, RecurseOptions};
+use crate::fs::dir_action::{DirAction, Levels, RecurseOptions};
 
 impl DirAction {
     /// Determine which action to perform when trying to list a directory.
@@ -23,6 +23,12 @@ impl DirAction {
                     &flags::RECURSE,
                     &flags::TREE,
                 ));
+            } else if !recurse && !tree && matches.has(&flags::FOLLOW_SYMLINKS)? {
+                return Err(OptionsError::Useless2(
+                    &flags::FOLLOW_SYMLINKS,
+                    &flags::RECURSE,
+                    &flags::TREE,
+                ));
             } else if recurse && as_file {
                 return Err(OptionsError::Conflict(&flags::RECURSE, &flags::LIST_DIRS));
             } else if tree && as_file {
@@ -49,25 +55,73 @@ impl RecurseOptions {
     /// flag’s value, and whether the `--tree` flag was passed, which was
     /// determined earlier. The maximum level should be a number, and this
     /// will fail with an `Err` if it isn’t.
+    ///
+    /// The level can also be a comma-separated list of levels and ranges of
+    /// levels, such as `1,3` or `2-4`, to only show those levels when
+    /// recursing. This doesn’t work for trees, as the rows connecting the
+    /// levels that are shown would be missing.
     pub fn deduce(matches: &MatchedFlags<'_>, tree: bool) -> Result<Self, OptionsError> {
-        if let Some(level) = matches.get(&flags::LEVEL)? {
-            let arg_str = level.to_string_lossy();
-            match arg_str.parse() {
+        let follow_symlinks = matches.has(&flags::FOLLOW_SYMLINKS)?;
+
+        let Some(level) = matches.get(&flags::LEVEL)? else {
+            return Ok(Self {
+                tree,
+                max_depth: None,
+                only_levels: None,
+                follow_symlinks,
+            });
+        };
+
+        let arg_str = level.to_string_lossy();
+        if !arg_str.contains([',', '-']) {
+            return match arg_str.parse() {
                 Ok(l) => Ok(Self {
                     tree,
                     max_depth: Some(l),
+                    only_levels: None,
+                    follow_symlinks,
                 }),
                 Err(e) => {
                     let source = NumberSource::Arg(&flags::LEVEL);
                     Err(OptionsError::FailedParse(arg_str.to_string(), source, e))
                 }
-            }
-        } else {
-            Ok(Self {
-                tree,
-                max_depth: None,
+            };
+        }
+
+        if tree {
+            return Err(OptionsError::TreeSkippedLevels);
+        }
+
+        let levels = Self::deduce_levels(&arg_str)?;
+        Ok(Self {
+            tree,
+            max_depth: levels.deepest(),
+            only_levels: Some(levels),
+            follow_symlinks,
+        })
+    }
+
+    fn deduce_levels(arg_str: &str) -> Result<Levels, OptionsError> {
+        let parse = |number: &str| {
+            number.trim().parse::<usize>().map_err(|e| {
+                let source = NumberSource::Arg(&flags::LEVEL);
+                OptionsError::FailedParse(number.to_string(), source, e)
             })
+        };
+
+        let mut levels = Levels::default();
+        for item in arg_str.split(',') {
+            let (first, last) = match item.split_once('-') {
+                Some((first, last)) => (parse(first)?, parse(last)?),
+                None => (parse(item)?, parse(item)?),
+            };
+
+            if first > last || !(first..=last).all(|level| levels.insert(level)) {
+                return Err(OptionsError::BadArgument(&flags::LEVEL, arg_str.into()));
+            }
         }
+
+        Ok(levels)
     }
 }
 
@@ -76,6 +130,7 @@ mod test {
     use super::*;
     use crate::options::flags;
     use crate::options::parser::Flag;
+    use std::ffi::OsString;
 
     macro_rules! test {
         ($name:ident: $type:ident <- $inputs:expr; $stricts:expr => $result:expr) => {
@@ -90,6 +145,7 @@ mod test {
                     &flags::LIST_DIRS,
                     &flags::TREE,
                     &flags::LEVEL,
+                    &flags::FOLLOW_SYMLINKS,
                 ];
                 for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| {
                     $type::deduce(mf, true)
@@ -109,19 +165,24 @@ mod test {
 
     // Recursing
     use self::DirAction::Recurse;
-    test!(rec_short:       DirAction <- ["-R"];                           Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None })));
-    test!(rec_long:        DirAction <- ["--recurse"];                    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None })));
-    test!(rec_lim_short:   DirAction <- ["-RL4"];                         Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(4) })));
-    test!(rec_lim_short_2: DirAction <- ["-RL=5"];                        Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(5) })));
-    test!(rec_lim_long:    DirAction <- ["--recurse", "--level", "666"];  Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(666) })));
-    test!(rec_lim_long_2:  DirAction <- ["--recurse", "--level=0118"];    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(118) })));
-    test!(tree:            DirAction <- ["--tree"];                       Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None })));
-    test!(rec_tree:        DirAction <- ["--recurse", "--tree"];          Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None })));
-    test!(rec_short_tree:  DirAction <- ["-TR"];                          Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None })));
+    test!(rec_short:       DirAction <- ["-R"];                           Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None, only_levels: None, follow_symlinks: false })));
+    test!(rec_long:        DirAction <- ["--recurse"];                    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: None, only_levels: None, follow_symlinks: false })));
+    test!(rec_lim_short:   DirAction <- ["-RL4"];                         Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(4), only_levels: None, follow_symlinks: false })));
+    test!(rec_lim_short_2: DirAction <- ["-RL=5"];                        Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(5), only_levels: None, follow_symlinks: false })));
+    test!(rec_lim_long:    DirAction <- ["--recurse", "--level", "666"];  Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(666), only_levels: None, follow_symlinks: false })));
+    test!(rec_lim_long_2:  DirAction <- ["--recurse", "--level=0118"];    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(118), only_levels: None, follow_symlinks: false })));
+    test!(tree:            DirAction <- ["--tree"];                       Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, only_levels: None, follow_symlinks: false })));
+    test!(rec_tree:        DirAction <- ["--recurse", "--tree"];          Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, only_levels: None, follow_symlinks: false })));
+    test!(rec_short_tree:  DirAction <- ["-TR"];                          Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, only_levels: None, follow_symlinks: false })));
+
+    // Following symlinks
+    test!(tree_follow:     DirAction <- ["--tree", "--follow-symlinks"];  Both => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, only_levels: None, follow_symlinks: true })));
+    test!(rec_follow:      DirAction <- ["-RL2", "--follow-symlinks"];    Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(2), only_levels: None, follow_symlinks: true })));
+    test!(just_follow:     DirAction <- ["--follow-symlinks"];        Complain => Err(OptionsError::Useless2(&flags::FOLLOW_SYMLINKS, &flags::RECURSE, &flags::TREE)));
 
     // Overriding --list-dirs, --recurse, and --tree
-    test!(dirs_recurse:    DirAction <- ["--list-dirs", "--recurse"];     Last => Ok(Recurse(RecurseOptions { tree: false, max_depth: None })));
-    test!(dirs_tree:       DirAction <- ["--list-dirs", "--tree"];        Last => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None })));
+    test!(dirs_recurse:    DirAction <- ["--list-dirs", "--recurse"];     Last => Ok(Recurse(RecurseOptions { tree: false, max_depth: None, only_levels: None, follow_symlinks: false })));
+    test!(dirs_tree:       DirAction <- ["--list-dirs", "--tree"];        Last => Ok(Recurse(RecurseOptions { tree: true,  max_depth: None, only_levels: None, follow_symlinks: false })));
     test!(just_level:      DirAction <- ["--level=4"];                    Last => Ok(DirAction::List));
 
     test!(dirs_recurse_2:  DirAction <- ["--list-dirs", "--recurse"]; Complain => Err(OptionsError::Conflict(&flags::RECURSE, &flags::LIST_DIRS)));
@@ -129,6 +190,22 @@ mod test {
     test!(just_level_2:    DirAction <- ["--level=4"];                Complain => Err(OptionsError::Useless2(&flags::LEVEL, &flags::RECURSE, &flags::TREE)));
 
     // Overriding levels
-    test!(overriding_1:    DirAction <- ["-RL=6", "-L=7"];                Last => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(7) })));
+    test!(overriding_1:    DirAction <- ["-RL=6", "-L=7"];                Last => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(7), only_levels: None, follow_symlinks: false })));
     test!(overriding_2:    DirAction <- ["-RL=6", "-L=7"];            Complain => Err(OptionsError::Duplicate(Flag::Short(b'L'), Flag::Short(b'L'))));
+
+    // Skipping levels
+    test!(level_list:      DirAction <- ["-R", "--level=1,3"];            Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(3), only_levels: Some(levels(&[1, 3])), follow_symlinks: false })));
+    test!(level_range:     DirAction <- ["-R", "--level=2-4"];            Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(4), only_levels: Some(levels(&[2, 3, 4])), follow_symlinks: false })));
+    test!(level_mixed:     DirAction <- ["-R", "--level=1,3-4"];          Both => Ok(Recurse(RecurseOptions { tree: false, max_depth: Some(4), only_levels: Some(levels(&[1, 3, 4])), follow_symlinks: false })));
+    test!(level_zero:      DirAction <- ["-R", "--level=0,2"];            Both => Err(OptionsError::BadArgument(&flags::LEVEL, OsString::from("0,2"))));
+    test!(level_backwards: DirAction <- ["-R", "--level=3-1"];            Both => Err(OptionsError::BadArgument(&flags::LEVEL, OsString::from("3-1"))));
+    test!(level_tree:      DirAction <- ["-T", "--level=1,3"];            Both => Err(OptionsError::TreeSkippedLevels));
+
+    fn levels(list: &[usize]) -> Levels {
+        let mut levels = Levels::default();
+        for &level in list {
+            levels.insert(level);
+        }
+        levels
+    }
 }
@@ -1,9 +1,12 @@
 use std::ffi::OsString;
 use std::fmt;
 use std::num::ParseIntError;
+use std::path::PathBuf;
 
 use crate::options::flags;
 use crate::options::parser::{Arg, Flag, ParseError};
+use crate::output::section::TemplateError;
+use crate::output::template;
 
 /// Something wrong with the combination of options the user has picked.
 #[derive(PartialEq, Eq, Debug)]
@@ -34,14 +37,49 @@ pub enum OptionsError {
     /// A very specific edge case where --tree can’t be used with --all twice.
     TreeAllAll,
 
+    /// `--level` was given a list of levels to skip between with `--tree`,
+    /// which can’t leave out the rows connecting them.
+    TreeSkippedLevels,
+
+    /// `--compare` was given some number of paths other than two.
+    CompareNeedsTwo(usize),
+
+    /// `--which` was given paths to list as well as a name to look for.
+    WhichWithPaths,
+
+    /// `--snapshot-in` was given paths to list as well as a saved listing.
+    SnapshotWithPaths,
+
+    /// An option was given that a listing loaded with `--snapshot-in` can’t
+    /// be shown with, as only what got saved about the files is known.
+    SnapshotUnsupported(Flag),
+
+    /// An option was given that `sftp://` paths can’t be listed with, as it
+    /// needs the files to be on this computer.
+    RemoteUnsupported(Flag),
+
     /// A numeric option was given that failed to be parsed as a number.
     FailedParse(String, NumberSource, ParseIntError),
 
     /// A glob ignore was given that failed to be parsed as a pattern.
     FailedGlobPattern(String),
+
+    /// The file of ignore patterns couldn’t be read.
+    FailedIgnoreFile(PathBuf, String),
+
+    /// The file given to compare times with couldn’t be read.
+    FailedReferenceFile(&'static Arg, PathBuf, String),
+
+    /// A directory header or footer template was given that failed to be
+    /// parsed.
+    FailedTemplate(NumberSource, TemplateError),
+
+    /// The template given to `--format-template` failed to be parsed.
+    FailedFormatTemplate(template::TemplateError),
 }
 
-/// The source of a string that failed to be parsed as a number.
+/// The source of a string that failed to be parsed as a number or a
+/// template.
 #[derive(PartialEq, Eq, Debug)]
 pub enum NumberSource {
     /// It came... from a command-line argument!
@@ -94,8 +132,18 @@ impl fmt::Display for OptionsError {
             Self::Useless(a, true, b)        => write!(f, "Option {a} is useless given option {b}"),
             Self::Useless2(a, b1, b2)        => write!(f, "Option {a} is useless without options {b1} or {b2}"),
             Self::TreeAllAll                 => write!(f, "Option --tree is useless given --all --all"),
+            Self::TreeSkippedLevels          => write!(f, "Option --level can only skip levels with --recurse, not --tree"),
+            Self::CompareNeedsTwo(n)         => write!(f, "Option --compare needs two directories, not {n}"),
+            Self::WhichWithPaths             => write!(f, "Option --which lists programs along $PATH, not the paths given"),
+            Self::SnapshotWithPaths          => write!(f, "Option --snapshot-in lists the files saved with --snapshot-out, not the paths given"),
+            Self::SnapshotUnsupported(a)     => write!(f, "Option {a} can't be used with --snapshot-in"),
+            Self::RemoteUnsupported(a)       => write!(f, "Option {a} can't be used with sftp:// paths"),
             Self::FailedParse(s, n, e)       => write!(f, "Value {s:?} not valid for {n}: {e}"),
             Self::FailedGlobPattern(ref e)   => write!(f, "Failed to parse glob pattern: {e}"),
+            Self::FailedIgnoreFile(path, e)  => write!(f, "Failed to read ignore file {}: {e}", path.display()),
+            Self::FailedReferenceFile(a, path, e) => write!(f, "Option {a} failed to read {}: {e}", path.display()),
+            Self::FailedTemplate(n, e)       => write!(f, "Template for {n} is not valid: {e}"),
+            Self::FailedFormatTemplate(e)    => write!(f, "Template for option --format-template is not valid: {e}"),
         };
     }
 }
@@ -1,10 +1,20 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::duplicates::CommonNames;
+use crate::fs::feature::audit::Audit;
+use crate::fs::feature::in_use::OpenFiles;
+use crate::fs::feature::snapshot::Snapshots;
+use crate::fs::feature::special_dirs::UserDirs;
 use crate::options::parser::MatchedFlags;
 use crate::options::vars::{self, Vars};
 use crate::options::{flags, NumberSource, OptionsError};
 
 use crate::output::file_name::{
-    Absolute, Classify, EmbedHyperlinks, Options, QuoteStyle, ShowIcons,
+    Absolute, Classify, EmbedHyperlinks, Indicators, Options, QuoteStyle, ShowIcons,
+    TruncatePosition, Truncation,
 };
+use crate::output::icons::{self, IconPack};
 
 impl Options {
     pub fn deduce<V: Vars>(
@@ -13,24 +23,96 @@ impl Options {
         is_a_tty: bool,
     ) -> Result<Self, OptionsError> {
         let classify = Classify::deduce(matches)?;
+        let indicators = Indicators::deduce(matches)?;
         let show_icons = ShowIcons::deduce(matches, vars)?;
+        let icon_pack = if show_icons == ShowIcons::Never {
+            None
+        } else {
+            IconPack::deduce(vars).map(Arc::new)
+        };
 
         let quote_style = QuoteStyle::deduce(matches)?;
         let embed_hyperlinks = EmbedHyperlinks::deduce(matches)?;
 
         let absolute = Absolute::deduce(matches)?;
+        let relative_to = deduce_relative_to(matches)?;
+        if relative_to.is_some() && absolute != Absolute::Off {
+            return Err(OptionsError::Conflict(
+                &flags::RELATIVE_TO,
+                &flags::ABSOLUTE,
+            ));
+        }
+
+        let hard_link_groups = matches.has(&flags::HARD_LINK_GROUPS)?;
+        let clones = matches.has(&flags::CLONES)?;
+        let highlight_new = if matches.has(&flags::HIGHLIGHT_NEW)? {
+            cache_dir(vars).map(|dir| Arc::new(Snapshots::new(dir.join("snapshots"))))
+        } else {
+            None
+        };
+        let suggest_broken_links = matches.has(&flags::SUGGEST_BROKEN_LINKS)?;
+        let security_audit = if matches.has(&flags::SECURITY_AUDIT)? {
+            Some(Arc::new(Audit::resolve(
+                vars.get(vars::HOME).map(PathBuf::from),
+                vars.get(vars::TMPDIR).map(PathBuf::from),
+            )))
+        } else {
+            None
+        };
+        let in_use = if matches.has(&flags::IN_USE)? {
+            Some(Arc::new(OpenFiles::default()))
+        } else {
+            None
+        };
+        // Which files have common names can only be found once the
+        // arguments are known, so they get filled in before listing.
+        let common_names = if matches.has(&flags::COMMON_NAMES)? {
+            Some(Arc::new(CommonNames::default()))
+        } else {
+            None
+        };
+        let truncate_names = Truncation::deduce(matches)?;
+        let user_dirs = Arc::new(UserDirs::resolve(
+            vars.get(vars::HOME).map(PathBuf::from),
+            vars.get(vars::XDG_CONFIG_HOME).map(PathBuf::from),
+        ));
 
         Ok(Self {
             classify,
+            indicators,
             show_icons,
+            icon_pack,
             quote_style,
             embed_hyperlinks,
             absolute,
+            relative_to,
             is_a_tty,
+            hard_link_groups,
+            clones,
+            highlight_new,
+            suggest_broken_links,
+            security_audit,
+            in_use,
+            common_names,
+            which_duplicates: None,
+            truncate_names,
+            user_dirs,
         })
     }
 }
 
+/// The directory that eza keeps things in between runs, such as the
+/// snapshots for `--highlight-new` and the previews for `--thumbnails`.
+pub(super) fn cache_dir<V: Vars>(vars: &V) -> Option<PathBuf> {
+    vars.get(vars::XDG_CACHE_HOME)
+        .map(PathBuf::from)
+        .or_else(|| {
+            vars.get(vars::HOME)
+                .map(|home| Path::new(&home).join(".cache"))
+        })
+        .map(|dir| dir.join("eza"))
+}
+
 impl Classify {
     fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
         let mode_opt = matches.get(&flags::CLASSIFY)?;
@@ -48,6 +130,31 @@ impl Classify {
     }
 }
 
+impl Indicators {
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        let Some(word) = matches.get(&flags::CLASSIFY_INDICATORS)? else {
+            return Ok(Self::Basic);
+        };
+
+        if matches.is_strict() && matches.get(&flags::CLASSIFY)?.is_none() {
+            return Err(OptionsError::Useless(
+                &flags::CLASSIFY_INDICATORS,
+                false,
+                &flags::CLASSIFY,
+            ));
+        }
+
+        match word.to_str() {
+            Some("basic") => Ok(Self::Basic),
+            Some("extended") => Ok(Self::Extended),
+            _ => Err(OptionsError::BadArgument(
+                &flags::CLASSIFY_INDICATORS,
+                word.into(),
+            )),
+        }
+    }
+}
+
 impl ShowIcons {
     pub fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
         enum AlwaysOrAuto {
@@ -92,14 +199,76 @@ impl ShowIcons {
 
         match mode {
             AlwaysOrAuto::Always => Ok(Self::Always(width)),
-            AlwaysOrAuto::Automatic => Ok(Self::Automatic(width)),
+            AlwaysOrAuto::Automatic => {
+                // Probing the terminal is a last resort, and only done when
+                // asked for, as it involves a round trip to the terminal.
+                let supported = terminal_icon_support(vars).unwrap_or_else(|| {
+                    vars.get(vars::EZA_ICONS_PROBE).is_none()
+                        || matches!(icons::probe_icon_width(), Some(1) | None)
+                });
+
+                if supported {
+                    Ok(Self::Automatic(width))
+                } else {
+                    Ok(Self::Never)
+                }
+            }
         }
     }
 }
 
+/// Guess whether the terminal can show icons from the variables it sets,
+/// returning `None` when there’s no way to tell.
+///
+/// The Linux and BSD consoles can’t load a font with icons in it, and
+/// neither can anything that calls itself `dumb`. Some other terminals come
+/// with the Nerd Font symbols built in, so they can always show them.
+fn terminal_icon_support<V: Vars>(vars: &V) -> Option<bool> {
+    let term = vars.get(vars::TERM).unwrap_or_default();
+    let term = term.to_string_lossy();
+    let program = vars.get(vars::TERM_PROGRAM).unwrap_or_default();
+
+    if matches!(&*term, "linux" | "dumb" | "cons25" | "wsvt25") || term.starts_with("vt") {
+        Some(false)
+    } else if matches!(&*term, "xterm-kitty" | "xterm-ghostty" | "wezterm")
+        || program == "WezTerm"
+        || program == "ghostty"
+    {
+        Some(true)
+    } else {
+        None
+    }
+}
+
 impl QuoteStyle {
     pub fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
-        if matches.has(&flags::NO_QUOTES)? {
+        if matches.is_strict() && matches.has(&flags::NO_QUOTES)? {
+            if matches.get(&flags::QUOTING_STYLE)?.is_some() {
+                return Err(OptionsError::Conflict(
+                    &flags::QUOTING_STYLE,
+                    &flags::NO_QUOTES,
+                ));
+            } else if matches.has(&flags::LITERAL)? {
+                return Err(OptionsError::Conflict(&flags::LITERAL, &flags::NO_QUOTES));
+            }
+        }
+
+        if let Some(word) = matches.get(&flags::QUOTING_STYLE)? {
+            return match word.to_str() {
+                Some("literal") => Ok(Self::Literal),
+                Some("shell") => Ok(Self::Shell),
+                Some("c") => Ok(Self::C),
+                Some("escape") => Ok(Self::Escape),
+                _ => Err(OptionsError::BadArgument(
+                    &flags::QUOTING_STYLE,
+                    word.into(),
+                )),
+            };
+        }
+
+        if matches.has(&flags::LITERAL)? {
+            Ok(Self::Literal)
+        } else if matches.has(&flags::NO_QUOTES)? {
             Ok(Self::NoQuotes)
         } else {
             Ok(Self::QuoteSpaces)
@@ -107,6 +276,50 @@ impl QuoteStyle {
     }
 }
 
+impl Truncation {
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Option<Self>, OptionsError> {
+        let Some(word) = matches.get(&flags::TRUNCATE_NAMES)? else {
+            return Ok(None);
+        };
+
+        let arg = word.to_string_lossy();
+        let (position, width) = match arg.split_once(':') {
+            Some((position, width)) => (position, Some(width)),
+            None => (arg.as_ref(), None),
+        };
+
+        let position = match position {
+            "middle" => TruncatePosition::Middle,
+            "end" => TruncatePosition::End,
+            _ => {
+                return Err(OptionsError::BadArgument(
+                    &flags::TRUNCATE_NAMES,
+                    word.into(),
+                ))
+            }
+        };
+
+        let width = match width {
+            None => Self::DEFAULT_WIDTH,
+            Some(width) => match width.parse() {
+                Ok(0) => {
+                    return Err(OptionsError::BadArgument(
+                        &flags::TRUNCATE_NAMES,
+                        word.into(),
+                    ))
+                }
+                Ok(width) => width,
+                Err(e) => {
+                    let source = NumberSource::Arg(&flags::TRUNCATE_NAMES);
+                    return Err(OptionsError::FailedParse(width.to_string(), source, e));
+                }
+            },
+        };
+
+        Ok(Some(Self { position, width }))
+    }
+}
+
 impl EmbedHyperlinks {
     fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
         let flagged = matches.has(&flags::HYPERLINK)?;
@@ -132,3 +345,62 @@ impl Absolute {
         }
     }
 }
+
+/// The directory given to `--relative-to`, made absolute so it can be
+/// compared with the files’ own absolute paths.
+fn deduce_relative_to(matches: &MatchedFlags<'_>) -> Result<Option<Arc<Path>>, OptionsError> {
+    let Some(base) = matches.get(&flags::RELATIVE_TO)? else {
+        return Ok(None);
+    };
+
+    let base = Path::new(base);
+    let base = match std::env::current_dir() {
+        Ok(cwd) => path_clean::clean(cwd.join(base)),
+        Err(_) => base.to_path_buf(),
+    };
+    Ok(Some(Arc::from(base)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::OsString;
+
+    struct MockVars {
+        term: &'static str,
+        program: &'static str,
+    }
+
+    impl Vars for MockVars {
+        fn get(&self, name: &'static str) -> Option<OsString> {
+            match name {
+                "TERM" if !self.term.is_empty() => Some(OsString::from(self.term)),
+                "TERM_PROGRAM" if !self.program.is_empty() => Some(OsString::from(self.program)),
+                _ => None,
+            }
+        }
+    }
+
+    fn support(term: &'static str, program: &'static str) -> Option<bool> {
+        terminal_icon_support(&MockVars { term, program })
+    }
+
+    #[test]
+    fn consoles_cannot() {
+        assert_eq!(support("linux", ""), Some(false));
+        assert_eq!(support("vt100", ""), Some(false));
+        assert_eq!(support("dumb", ""), Some(false));
+    }
+
+    #[test]
+    fn bundled_symbols_can() {
+        assert_eq!(support("xterm-kitty", ""), Some(true));
+        assert_eq!(support("xterm-256color", "WezTerm"), Some(true));
+    }
+
+    #[test]
+    fn everything_else_is_unknown() {
+        assert_eq!(support("xterm-256color", "iTerm.app"), None);
+        assert_eq!(support("", ""), None);
+    }
+}
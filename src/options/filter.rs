@@ -1,11 +1,20 @@
 //! Parsing the options for `FileFilter`.
 
+use std::path::Path;
+
+use chrono::NaiveDateTime;
+use number_prefix::{NumberPrefix, Prefix};
+
+use crate::fs::feature::gitignore::IgnoreFile;
+
+use crate::fs::dir_action::Levels;
 use crate::fs::filter::{
-    FileFilter, FileFilterFlags, GitIgnore, IgnorePatterns, SortCase, SortField,
+    Collation, FileFilter, FileFilterFlags, FileKind, GitIgnore, IgnorePatterns, LevelSort,
+    SizeFilter, SortCase, SortField, TimeField, TimeFilter,
 };
-use crate::fs::DotFilter;
+use crate::fs::{DotFilter, File};
 
-use crate::options::parser::MatchedFlags;
+use crate::options::parser::{Arg, MatchedFlags};
 use crate::options::{flags, OptionsError};
 
 impl FileFilter {
@@ -14,11 +23,7 @@ impl FileFilter {
         use FileFilterFlags as FFF;
         let mut filter_flags: Vec<FileFilterFlags> = vec![];
 
-        for (has, flag) in &[
-            (matches.has(&flags::REVERSE)?, FFF::Reverse),
-            (matches.has(&flags::ONLY_DIRS)?, FFF::OnlyDirs),
-            (matches.has(&flags::ONLY_FILES)?, FFF::OnlyFiles),
-        ] {
+        for (has, flag) in &[(matches.has(&flags::REVERSE)?, FFF::Reverse)] {
             if *has {
                 filter_flags.push(flag.clone());
             }
@@ -28,35 +33,244 @@ impl FileFilter {
         return Ok(Self {
             list_dirs_first:  matches.has(&flags::DIRS_FIRST)?,
             flags: filter_flags,
+            only_kinds:       FileKind::deduce(matches)?,
+            size_filter:      SizeFilter::deduce(matches)?,
+            time_filter:      TimeFilter::deduce(matches)?,
+            tag:              matches.get(&flags::TAG)?.map(|tag| tag.to_string_lossy().into_owned()),
             sort_field:       SortField::deduce(matches)?,
+            level_sorts:      LevelSort::deduce(matches)?,
             dot_filter:       DotFilter::deduce(matches)?,
             ignore_patterns:  IgnorePatterns::deduce(matches)?,
             git_ignore:       GitIgnore::deduce(matches)?,
+            git_ignore_debug: matches.has(&flags::GIT_IGNORE_DEBUG)?,
+            only_modified_git: matches.has(&flags::ONLY_MODIFIED_GIT)?,
         });
     }
 }
 
+impl FileKind {
+    /// Determines which kinds of file to list, from the `--type` argument’s
+    /// comma-separated list of kinds, and the `--only-dirs`, `--only-files`,
+    /// `--only-symlinks`, and `--only-executables` shorthands. These are all
+    /// combined, so `-D --type=l` lists directories and symlinks.
+    ///
+    /// An empty list means that no kinds were picked, so everything is shown.
+    /// Broken links can be anywhere down a recursive listing, so directories
+    /// keep being listed alongside them, the way `--min-size` leaves them.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Vec<Self>, OptionsError> {
+        let mut kinds = Vec::new();
+
+        for (arg, kind) in [
+            (&flags::ONLY_DIRS, Self::Directory),
+            (&flags::ONLY_FILES, Self::File),
+            (&flags::ONLY_SYMLINKS, Self::Symlink),
+            (&flags::ONLY_EXECUTABLES, Self::Executable),
+            (&flags::DANGLING_ONLY, Self::BrokenSymlink),
+        ] {
+            if matches.has(arg)? {
+                kinds.push(kind);
+            }
+        }
+
+        let recursing = matches.has(&flags::RECURSE)? || matches.has(&flags::TREE)?;
+        if recursing && kinds.contains(&Self::BrokenSymlink) && !kinds.contains(&Self::Directory) {
+            kinds.push(Self::Directory);
+        }
+
+        if let Some(words) = matches.get(&flags::TYPE)? {
+            for word in words.to_string_lossy().split(',') {
+                let kind = match word {
+                    "f" | "file" => Self::File,
+                    "d" | "dir" | "directory" => Self::Directory,
+                    "l" | "symlink" => Self::Symlink,
+                    "x" | "executable" => Self::Executable,
+                    "p" | "pipe" => Self::Pipe,
+                    "s" | "socket" => Self::Socket,
+                    "b" | "block-device" => Self::BlockDevice,
+                    "c" | "char-device" => Self::CharDevice,
+                    _ => return Err(OptionsError::BadArgument(&flags::TYPE, word.into())),
+                };
+
+                if !kinds.contains(&kind) {
+                    kinds.push(kind);
+                }
+            }
+        }
+
+        Ok(kinds)
+    }
+}
+
+impl SizeFilter {
+    /// Determines the smallest and largest sizes of file to list, from the
+    /// `--min-size` and `--max-size` arguments.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        Ok(Self {
+            min: Self::deduce_limit(matches, &flags::MIN_SIZE)?,
+            max: Self::deduce_limit(matches, &flags::MAX_SIZE)?,
+        })
+    }
+
+    fn deduce_limit(
+        matches: &MatchedFlags<'_>,
+        flag: &'static Arg,
+    ) -> Result<Option<u64>, OptionsError> {
+        let Some(word) = matches.get(flag)? else {
+            return Ok(None);
+        };
+
+        match word.to_str().and_then(parse_size) {
+            Some(bytes) => Ok(Some(bytes)),
+            None => Err(OptionsError::BadArgument(flag, word.into())),
+        }
+    }
+}
+
+impl TimeFilter {
+    /// Determines the times that files have to be newer or older than to be
+    /// listed, from the times of the files given to `--newer-than` and
+    /// `--older-than`, using the timestamp picked by `--reference-time`.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        let field = TimeField::deduce(matches)?;
+        let newer_than = Self::deduce_limit(matches, &flags::NEWER_THAN, field)?;
+        let older_than = Self::deduce_limit(matches, &flags::OLDER_THAN, field)?;
+
+        if matches.is_strict()
+            && newer_than.is_none()
+            && older_than.is_none()
+            && matches.get(&flags::REFERENCE_TIME)?.is_some()
+        {
+            return Err(OptionsError::Useless2(
+                &flags::REFERENCE_TIME,
+                &flags::NEWER_THAN,
+                &flags::OLDER_THAN,
+            ));
+        }
+
+        Ok(Self {
+            field,
+            newer_than,
+            older_than,
+        })
+    }
+
+    /// The time of the reference file given to the flag, if it was given.
+    /// Like `find -newer`, a symlink’s own time gets used rather than its
+    /// target’s.
+    fn deduce_limit(
+        matches: &MatchedFlags<'_>,
+        flag: &'static Arg,
+        field: TimeField,
+    ) -> Result<Option<NaiveDateTime>, OptionsError> {
+        let Some(path) = matches.get(flag)? else {
+            return Ok(None);
+        };
+
+        let path = Path::new(path);
+        let failed = |e: String| OptionsError::FailedReferenceFile(flag, path.to_path_buf(), e);
+        let file = File::from_args(path.to_path_buf(), None, None, false, false)
+            .map_err(|e| failed(e.to_string()))?;
+
+        match field.of(&file) {
+            Some(time) => Ok(Some(time)),
+            None => Err(failed(String::from("it has no such timestamp"))),
+        }
+    }
+}
+
+impl TimeField {
+    /// Determines which timestamp to compare, from the `--reference-time`
+    /// argument, which takes the same words as `--time`.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        let Some(word) = matches.get(&flags::REFERENCE_TIME)? else {
+            return Ok(Self::default());
+        };
+
+        match word.to_str() {
+            Some("mod" | "modified") => Ok(Self::Modified),
+            Some("ch" | "changed") => Ok(Self::Changed),
+            Some("acc" | "accessed") => Ok(Self::Accessed),
+            Some("cr" | "created") => Ok(Self::Created),
+            _ => Err(OptionsError::BadArgument(
+                &flags::REFERENCE_TIME,
+                word.into(),
+            )),
+        }
+    }
+}
+
+/// Parses a size such as `512`, `10M`, or `1.5GiB`, accepting the same
+/// prefixes that sizes get displayed with: `k`, `M`, `G`, and so on for
+/// powers of 1000, and `Ki`, `Mi`, `Gi`, and so on for powers of 1024.
+pub(super) fn parse_size(word: &str) -> Option<u64> {
+    let bytes = match word.parse::<NumberPrefix<f64>>().ok()? {
+        NumberPrefix::Standalone(n) => n,
+        NumberPrefix::Prefixed(prefix, n) => n * multiplier(prefix),
+    };
+
+    if bytes.is_sign_negative() || !bytes.is_finite() {
+        return None;
+    }
+
+    Some(bytes.round() as u64)
+}
+
+/// How many bytes one of the given prefix stands for.
+fn multiplier(prefix: Prefix) -> f64 {
+    #[rustfmt::skip]
+    let (base, power): (f64, i32) = match prefix {
+        Prefix::Kilo => (1000.0, 1),  Prefix::Kibi => (1024.0, 1),
+        Prefix::Mega => (1000.0, 2),  Prefix::Mebi => (1024.0, 2),
+        Prefix::Giga => (1000.0, 3),  Prefix::Gibi => (1024.0, 3),
+        Prefix::Tera => (1000.0, 4),  Prefix::Tebi => (1024.0, 4),
+        Prefix::Peta => (1000.0, 5),  Prefix::Pebi => (1024.0, 5),
+        Prefix::Exa => (1000.0, 6),   Prefix::Exbi => (1024.0, 6),
+        Prefix::Zetta => (1000.0, 7), Prefix::Zebi => (1024.0, 7),
+        Prefix::Yotta => (1000.0, 8), Prefix::Yobi => (1024.0, 8),
+    };
+
+    base.powi(power)
+}
+
 impl SortField {
     /// Determines which sort field to use based on the `--sort` argument.
     /// This argument’s value can be one of several flags, listed above.
     /// Returns the default sort field if none is given, or `Err` if the
     /// value doesn’t correspond to a sort field we know about.
     fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        if matches.is_strict()
+            && matches.get(&flags::COLLATE)?.is_some()
+            && !matches
+                .get(&flags::SORT)?
+                .is_some_and(|word| word == "name-natural")
+        {
+            return Err(OptionsError::Useless(&flags::COLLATE, false, &flags::SORT));
+        }
+
         let Some(word) = matches.get(&flags::SORT)? else {
             return Ok(Self::default());
         };
 
         // Get String because we can’t match an OsStr
-        let Some(word) = word.to_str() else {
-            return Err(OptionsError::BadArgument(&flags::SORT, word.into()));
-        };
+        match word.to_str() {
+            Some(word) => match Self::from_word(word, matches)? {
+                Some(field) => Ok(field),
+                None => Err(OptionsError::BadArgument(&flags::SORT, word.into())),
+            },
+            None => Err(OptionsError::BadArgument(&flags::SORT, word.into())),
+        }
+    }
 
+    /// The sort field that one of the words `--sort` takes stands for, or
+    /// `None` if it isn’t one of them.
+    fn from_word(word: &str, matches: &MatchedFlags<'_>) -> Result<Option<Self>, OptionsError> {
         let field = match word {
             "name" | "filename" => Self::Name(SortCase::AaBbCc),
             "Name" | "Filename" => Self::Name(SortCase::ABCabc),
             ".name" | ".filename" => Self::NameMixHidden(SortCase::AaBbCc),
             ".Name" | ".Filename" => Self::NameMixHidden(SortCase::ABCabc),
             "size" | "filesize" => Self::Size,
+            "name-natural" => Self::NameNatural(Collation::deduce(matches)?),
             "ext" | "extension" => Self::Extension(SortCase::AaBbCc),
             "Ext" | "Extension" => Self::Extension(SortCase::ABCabc),
 
@@ -76,14 +290,137 @@ impl SortField {
             "cr" | "created" => Self::CreatedDate,
             #[cfg(unix)]
             "inode" => Self::FileInode,
+            #[cfg(unix)]
+            "blocks" => Self::Blocks,
+            #[cfg(unix)]
+            "links" => Self::Links,
             "type" => Self::FileType,
             "none" => Self::Unsorted,
-            _ => {
-                return Err(OptionsError::BadArgument(&flags::SORT, word.into()));
+            _ => return Ok(None),
+        };
+
+        Ok(Some(field))
+    }
+}
+
+impl LevelSort {
+    /// Determines the sort settings for particular levels of a tree, from
+    /// `--sort-dirs-first-per-level`, which lists the only levels that get
+    /// directories first, and `--sort-levels`, which takes rules separated
+    /// by semicolons, such as `1:dirs-first;2+:size,reverse`. Each rule is
+    /// a list of levels, the same as `--level` takes but with `N+` for a
+    /// level and every one below it and `*` for all of them, followed by the
+    /// settings for those levels: a field that `--sort` takes, `reverse` or
+    /// `no-reverse`, and `dirs-first` or `no-dirs-first`.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Vec<Self>, OptionsError> {
+        let mut sorts = Vec::new();
+
+        if let Some(word) = matches.get(&flags::SORT_DIRS_FIRST_PER_LEVEL)? {
+            let levels = word.to_str().and_then(parse_levels).ok_or_else(|| {
+                OptionsError::BadArgument(&flags::SORT_DIRS_FIRST_PER_LEVEL, word.into())
+            })?;
+
+            sorts.push(Self::dirs_first(Levels::all(), false));
+            sorts.push(Self::dirs_first(levels, true));
+        }
+
+        if let Some(word) = matches.get(&flags::SORT_LEVELS)? {
+            let bad_argument = || OptionsError::BadArgument(&flags::SORT_LEVELS, word.into());
+            let spec = word.to_str().ok_or_else(bad_argument)?;
+
+            for rule in spec
+                .split(';')
+                .map(str::trim)
+                .filter(|rule| !rule.is_empty())
+            {
+                let (levels, settings) = rule.split_once(':').ok_or_else(bad_argument)?;
+                let mut sort = Self {
+                    levels: parse_levels(levels).ok_or_else(bad_argument)?,
+                    field: None,
+                    reverse: None,
+                    dirs_first: None,
+                };
+
+                for setting in settings.split(',').map(str::trim) {
+                    match setting {
+                        "reverse" => sort.reverse = Some(true),
+                        "no-reverse" => sort.reverse = Some(false),
+                        "dirs-first" => sort.dirs_first = Some(true),
+                        "no-dirs-first" => sort.dirs_first = Some(false),
+                        _ => {
+                            let field = SortField::from_word(setting, matches)?;
+                            sort.field = Some(field.ok_or_else(bad_argument)?);
+                        }
+                    }
+                }
+
+                sorts.push(sort);
             }
+        }
+
+        if !sorts.is_empty() && matches.is_strict() && !matches.has(&flags::TREE)? {
+            let flag = if matches.get(&flags::SORT_LEVELS)?.is_some() {
+                &flags::SORT_LEVELS
+            } else {
+                &flags::SORT_DIRS_FIRST_PER_LEVEL
+            };
+            return Err(OptionsError::Useless(flag, false, &flags::TREE));
+        }
+
+        Ok(sorts)
+    }
+
+    fn dirs_first(levels: Levels, dirs_first: bool) -> Self {
+        Self {
+            levels,
+            field: None,
+            reverse: None,
+            dirs_first: Some(dirs_first),
+        }
+    }
+}
+
+/// Parses a comma-separated list of levels and ranges of levels, such as
+/// `1,3-4`, where `N+` is a level and every one below it, and `*` is all of
+/// them.
+fn parse_levels(spec: &str) -> Option<Levels> {
+    let mut levels = Levels::default();
+
+    for item in spec.split(',').map(str::trim) {
+        let (first, last) = if item == "*" {
+            (1, Levels::DEEPEST)
+        } else if let Some(first) = item.strip_suffix('+') {
+            (first.parse().ok()?, Levels::DEEPEST)
+        } else if let Some((first, last)) = item.split_once('-') {
+            (first.trim().parse().ok()?, last.trim().parse().ok()?)
+        } else {
+            let level = item.parse().ok()?;
+            (level, level)
+        };
+
+        if first > last || !(first..=last).all(|level| levels.insert(level)) {
+            return None;
+        }
+    }
+
+    Some(levels)
+}
+
+impl Collation {
+    /// Determines how `--sort=name-natural` compares letters, based on the
+    /// `--collate` argument. The locale is used by default.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        let Some(word) = matches.get(&flags::COLLATE)? else {
+            return Ok(Self::Locale);
         };
 
-        Ok(field)
+        if word == "locale" {
+            Ok(Self::Locale)
+        } else if word == "codepoint" {
+            Ok(Self::Codepoint)
+        } else {
+            Err(OptionsError::BadArgument(&flags::COLLATE, word.into()))
+        }
     }
 }
 
@@ -162,12 +499,33 @@ impl DotFilter {
 impl IgnorePatterns {
     /// Determines the set of glob patterns to use based on the
     /// `--ignore-glob` argument’s value. This is a list of strings
-    /// separated by pipe (`|`) characters, given in any order.
+    /// separated by pipe (`|`) characters, given in any order. Any rules
+    /// in the file given with `--ignore-glob-file` get added to them.
     pub fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        let mut patterns = Self::deduce_patterns(matches)?;
+        if matches.has(&flags::EXCLUDE_VCS)? {
+            patterns = patterns.with_vcs_dirs();
+        }
+
+        let Some(path) = matches.get(&flags::IGNORE_GLOB_FILE)? else {
+            return Ok(patterns);
+        };
+        let path = Path::new(path);
+        match IgnoreFile::read(path) {
+            Ok(file) => Ok(patterns.with_file(file)),
+            Err(e) => Err(OptionsError::FailedIgnoreFile(
+                path.to_path_buf(),
+                e.to_string(),
+            )),
+        }
+    }
+
+    fn deduce_patterns(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
         // If there are no inputs, we return a set of patterns that doesn’t
         // match anything, rather than, say, `None`.
+        let case_sensitive = Self::deduce_case_sensitivity(matches)?;
         let Some(inputs) = matches.get(&flags::IGNORE_GLOB)? else {
-            return Ok(Self::empty());
+            return Ok(Self::empty().with_case_sensitivity(case_sensitive));
         };
 
         // Awkwardly, though, a glob pattern can be invalid, and we need to
@@ -178,15 +536,34 @@ impl IgnorePatterns {
         // but we only use one. (TODO)
         match errors.pop() {
             Some(e) => Err(e.into()),
-            None => Ok(patterns),
+            None => Ok(patterns.with_case_sensitivity(case_sensitive)),
         }
     }
+
+    /// Whether the patterns should match case-sensitively, which is up to
+    /// whichever of `--ignore-case` and `--case-sensitive` comes last, or
+    /// the platform if neither is given.
+    fn deduce_case_sensitivity(matches: &MatchedFlags<'_>) -> Result<bool, OptionsError> {
+        let flag = matches
+            .has_where(|f| f.matches(&flags::IGNORE_CASE) || f.matches(&flags::CASE_SENSITIVE))?;
+
+        Ok(match flag {
+            Some(flag) => flag.matches(&flags::CASE_SENSITIVE),
+            None => Self::CASE_SENSITIVE_BY_DEFAULT,
+        })
+    }
 }
 
 impl GitIgnore {
     pub fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
         if matches.has(&flags::GIT_IGNORE)? {
             Ok(Self::CheckAndIgnore)
+        } else if matches.is_strict() && matches.has(&flags::GIT_IGNORE_DEBUG)? {
+            Err(OptionsError::Useless(
+                &flags::GIT_IGNORE_DEBUG,
+                false,
+                &flags::GIT_IGNORE,
+            ))
         } else {
             Ok(Self::Off)
         }
@@ -210,11 +587,31 @@ mod test {
 
                 static TEST_ARGS: &[&Arg] = &[
                     &flags::SORT,
+                    &flags::SORT_LEVELS,
+                    &flags::SORT_DIRS_FIRST_PER_LEVEL,
+                    &flags::COLLATE,
                     &flags::ALL,
                     &flags::ALMOST_ALL,
                     &flags::TREE,
+                    &flags::RECURSE,
                     &flags::IGNORE_GLOB,
+                    &flags::IGNORE_GLOB_FILE,
+                    &flags::EXCLUDE_VCS,
+                    &flags::IGNORE_CASE,
+                    &flags::CASE_SENSITIVE,
                     &flags::GIT_IGNORE,
+                    &flags::GIT_IGNORE_DEBUG,
+                    &flags::ONLY_DIRS,
+                    &flags::ONLY_FILES,
+                    &flags::ONLY_SYMLINKS,
+                    &flags::ONLY_EXECUTABLES,
+                    &flags::DANGLING_ONLY,
+                    &flags::TYPE,
+                    &flags::MIN_SIZE,
+                    &flags::MAX_SIZE,
+                    &flags::NEWER_THAN,
+                    &flags::OLDER_THAN,
+                    &flags::REFERENCE_TIME,
                 ];
                 for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| {
                     $type::deduce(mf)
@@ -242,13 +639,25 @@ mod test {
         test!(new:           SortField <- ["--sort", "old"];   Both => Ok(SortField::ModifiedAge));
         test!(newest:        SortField <- ["--sort=oldest"];   Both => Ok(SortField::ModifiedAge));
         test!(age:           SortField <- ["-sage"];           Both => Ok(SortField::ModifiedAge));
+        #[cfg(unix)]
+        test!(inode:         SortField <- ["--sort=inode"];    Both => Ok(SortField::FileInode));
+        #[cfg(unix)]
+        test!(blocks:        SortField <- ["--sort=blocks"];   Both => Ok(SortField::Blocks));
+        #[cfg(unix)]
+        test!(links:         SortField <- ["--sort", "links"]; Both => Ok(SortField::Links));
 
         test!(mix_hidden_lowercase:     SortField <- ["--sort", ".name"];  Both => Ok(SortField::NameMixHidden(SortCase::AaBbCc)));
         test!(mix_hidden_uppercase:     SortField <- ["--sort", ".Name"];  Both => Ok(SortField::NameMixHidden(SortCase::ABCabc)));
 
+        test!(natural:       SortField <- ["--sort=name-natural"];                        Both => Ok(SortField::NameNatural(Collation::Locale)));
+        test!(codepoint:     SortField <- ["--sort=name-natural", "--collate=codepoint"]; Both => Ok(SortField::NameNatural(Collation::Codepoint)));
+
         // Errors
         test!(error:         SortField <- ["--sort=colour"];   Both => Err(OptionsError::BadArgument(&flags::SORT, OsString::from("colour"))));
 
+        test!(bad_collate:   SortField <- ["--sort=name-natural", "--collate=klingon"];   Both => Err(OptionsError::BadArgument(&flags::COLLATE, OsString::from("klingon"))));
+        test!(useless:       SortField <- ["--sort=name", "--collate=codepoint"];     Complain => Err(OptionsError::Useless(&flags::COLLATE, false, &flags::SORT)));
+
         // Overriding
         test!(overridden:    SortField <- ["--sort=cr",       "--sort", "mod"];     Last => Ok(SortField::ModifiedDate));
         test!(overridden_2:  SortField <- ["--sort", "none",  "--sort=Extension"];  Last => Ok(SortField::Extension(SortCase::ABCabc)));
@@ -256,6 +665,57 @@ mod test {
         test!(overridden_4:  SortField <- ["--sort", "none",  "--sort=Extension"];  Complain => Err(OptionsError::Duplicate(Flag::Long("sort"), Flag::Long("sort"))));
     }
 
+    mod level_sorts {
+        use super::*;
+
+        fn sort(
+            levels: &str,
+            field: Option<SortField>,
+            reverse: Option<bool>,
+            dirs_first: Option<bool>,
+        ) -> LevelSort {
+            let levels = parse_levels(levels).unwrap();
+            LevelSort {
+                levels,
+                field,
+                reverse,
+                dirs_first,
+            }
+        }
+
+        fn some_rules() -> Vec<LevelSort> {
+            vec![
+                sort("1", None, None, Some(true)),
+                sort("2+", Some(SortField::Size), Some(true), None),
+            ]
+        }
+
+        fn dirs_first_at_1_and_3() -> Vec<LevelSort> {
+            vec![
+                sort("*", None, None, Some(false)),
+                sort("1,3", None, None, Some(true)),
+            ]
+        }
+
+        test!(empty:         LevelSort <- [];                                                     Both => Ok(vec![]));
+        test!(rules:         LevelSort <- ["--tree", "--sort-levels=1:dirs-first; 2+:size,reverse"]; Both => Ok(some_rules()));
+        test!(per_level:     LevelSort <- ["--tree", "--sort-dirs-first-per-level=1,3"];            Both => Ok(dirs_first_at_1_and_3()));
+        test!(bad_levels:    LevelSort <- ["--tree", "--sort-levels=0:size"];                       Both => Err(OptionsError::BadArgument(&flags::SORT_LEVELS, OsString::from("0:size"))));
+        test!(bad_field:     LevelSort <- ["--tree", "--sort-levels=1:colour"];                     Both => Err(OptionsError::BadArgument(&flags::SORT_LEVELS, OsString::from("1:colour"))));
+        test!(no_settings:   LevelSort <- ["--tree", "--sort-levels=1-2"];                          Both => Err(OptionsError::BadArgument(&flags::SORT_LEVELS, OsString::from("1-2"))));
+        test!(no_tree:       LevelSort <- ["--sort-dirs-first-per-level=1"];                        Complain => Err(OptionsError::Useless(&flags::SORT_DIRS_FIRST_PER_LEVEL, false, &flags::TREE)));
+
+        #[test]
+        fn levels() {
+            assert_eq!(
+                parse_levels("2+"),
+                parse_levels(&format!("2-{}", Levels::DEEPEST))
+            );
+            assert_eq!(parse_levels("*"), Some(Levels::all()));
+            assert_eq!(parse_levels("3-1"), None);
+        }
+    }
+
     mod dot_filters {
         use super::*;
 
@@ -295,11 +755,85 @@ mod test {
         test!(two:    IgnorePatterns <- ["--ignore-glob=*.ogg|*.MP3"];             Both => Ok(IgnorePatterns::from_iter(vec![ pat("*.ogg"), pat("*.MP3") ])));
         test!(loads:  IgnorePatterns <- ["-I*|?|.|*"];                             Both => Ok(IgnorePatterns::from_iter(vec![ pat("*"), pat("?"), pat("."), pat("*") ])));
 
+        // Version control directories
+        test!(vcs:      IgnorePatterns <- ["--exclude-vcs"];                       Both => Ok(IgnorePatterns::empty().with_vcs_dirs()));
+        test!(vcs_glob: IgnorePatterns <- ["--exclude-vcs", "-I*.ogg"];            Both => Ok(IgnorePatterns::from_iter(vec![ pat("*.ogg") ]).with_vcs_dirs()));
+
         // Overriding
         test!(overridden:   IgnorePatterns <- ["-I=*.ogg",    "-I", "*.mp3"];      Last => Ok(IgnorePatterns::from_iter(vec![ pat("*.mp3") ])));
         test!(overridden_2: IgnorePatterns <- ["-I", "*.OGG", "-I*.MP3"];          Last => Ok(IgnorePatterns::from_iter(vec![ pat("*.MP3") ])));
         test!(overridden_3: IgnorePatterns <- ["-I=*.ogg",    "-I", "*.mp3"];  Complain => Err(OptionsError::Duplicate(Flag::Short(b'I'), Flag::Short(b'I'))));
         test!(overridden_4: IgnorePatterns <- ["-I", "*.OGG", "-I*.MP3"];      Complain => Err(OptionsError::Duplicate(Flag::Short(b'I'), Flag::Short(b'I'))));
+
+        // Case sensitivity
+        test!(ignore_case:  IgnorePatterns <- ["-I*.ogg", "--ignore-case"];         Both => Ok(IgnorePatterns::from_iter(vec![ pat("*.ogg") ]).with_case_sensitivity(false)));
+        test!(sensitive:    IgnorePatterns <- ["-I*.ogg", "--case-sensitive"];      Both => Ok(IgnorePatterns::from_iter(vec![ pat("*.ogg") ]).with_case_sensitivity(true)));
+        test!(last_case:    IgnorePatterns <- ["--case-sensitive", "--ignore-case"]; Last => Ok(IgnorePatterns::empty().with_case_sensitivity(false)));
+        test!(both_cases:   IgnorePatterns <- ["--case-sensitive", "--ignore-case"]; Complain => Err(OptionsError::Duplicate(Flag::Long("case-sensitive"), Flag::Long("ignore-case"))));
+
+        // Ignore files
+        #[cfg(unix)]
+        test!(no_file:      IgnorePatterns <- ["--ignore-glob-file=/nonexistent/.ezaignore"]; Both => Err(OptionsError::FailedIgnoreFile(std::path::PathBuf::from("/nonexistent/.ezaignore"), String::from("No such file or directory (os error 2)"))));
+    }
+
+    mod file_kinds {
+        use super::*;
+
+        // Default behaviour
+        test!(empty:        FileKind <- [];                            Both => Ok(vec![]));
+
+        // Shorthands
+        test!(only_dirs:    FileKind <- ["--only-dirs"];               Both => Ok(vec![FileKind::Directory]));
+        test!(only_files:   FileKind <- ["-f"];                        Both => Ok(vec![FileKind::File]));
+        test!(only_links:   FileKind <- ["--only-symlinks"];           Both => Ok(vec![FileKind::Symlink]));
+        test!(only_execs:   FileKind <- ["--only-executables"];        Both => Ok(vec![FileKind::Executable]));
+        test!(dangling:     FileKind <- ["--dangling-only"];           Both => Ok(vec![FileKind::BrokenSymlink]));
+        test!(dangling_dirs: FileKind <- ["--dangling-only", "-D"];    Both => Ok(vec![FileKind::Directory, FileKind::BrokenSymlink]));
+        test!(dangling_recurse: FileKind <- ["--dangling-only", "-R"]; Both => Ok(vec![FileKind::BrokenSymlink, FileKind::Directory]));
+        test!(dangling_tree: FileKind <- ["--dangling-only", "-T"];   Both => Ok(vec![FileKind::BrokenSymlink, FileKind::Directory]));
+        test!(dirs_files:   FileKind <- ["-D", "-f"];                  Both => Ok(vec![FileKind::Directory, FileKind::File]));
+
+        // --type lists
+        test!(one_type:     FileKind <- ["--type=p"];                  Both => Ok(vec![FileKind::Pipe]));
+        test!(many_types:   FileKind <- ["--type", "s,b,c"];           Both => Ok(vec![FileKind::Socket, FileKind::BlockDevice, FileKind::CharDevice]));
+        test!(long_names:   FileKind <- ["--type=directory,symlink"];  Both => Ok(vec![FileKind::Directory, FileKind::Symlink]));
+        test!(combined:     FileKind <- ["-D", "--type=d,l"];          Both => Ok(vec![FileKind::Directory, FileKind::Symlink]));
+
+        // Errors
+        test!(bad_type:     FileKind <- ["--type=f,q"];                Both => Err(OptionsError::BadArgument(&flags::TYPE, OsString::from("q"))));
+
+        // Overriding
+        test!(overridden:   FileKind <- ["--type=f", "--type=x"];      Last => Ok(vec![FileKind::Executable]));
+        test!(overridden_2: FileKind <- ["--type=f", "--type=x"];  Complain => Err(OptionsError::Duplicate(Flag::Long("type"), Flag::Long("type"))));
+    }
+
+    mod size_filters {
+        use super::*;
+
+        test!(none:      SizeFilter <- [];                                     Both => Ok(SizeFilter::default()));
+        test!(bytes:     SizeFilter <- ["--min-size=512"];                     Both => Ok(SizeFilter { min: Some(512), max: None }));
+        test!(decimal:   SizeFilter <- ["--min-size", "10M"];                  Both => Ok(SizeFilter { min: Some(10_000_000), max: None }));
+        test!(binary:    SizeFilter <- ["--max-size=1.5Ki"];                   Both => Ok(SizeFilter { min: None, max: Some(1536) }));
+        test!(with_b:    SizeFilter <- ["--max-size=2GiB"];                    Both => Ok(SizeFilter { min: None, max: Some(2 << 30) }));
+        test!(range:     SizeFilter <- ["--min-size=1k", "--max-size=2k"];     Both => Ok(SizeFilter { min: Some(1000), max: Some(2000) }));
+
+        // Errors
+        test!(nonsense:  SizeFilter <- ["--min-size=big"];                     Both => Err(OptionsError::BadArgument(&flags::MIN_SIZE, OsString::from("big"))));
+        test!(negative:  SizeFilter <- ["--max-size=-1M"];                     Both => Err(OptionsError::BadArgument(&flags::MAX_SIZE, OsString::from("-1M"))));
+    }
+
+    mod time_filters {
+        use super::*;
+
+        test!(none:      TimeFilter <- [];                                     Both => Ok(TimeFilter::default()));
+        test!(field:     TimeField <- ["--reference-time=changed"];            Both => Ok(TimeField::Changed));
+        test!(short:     TimeField <- ["--reference-time", "cr"];              Both => Ok(TimeField::Created));
+
+        // Errors
+        test!(bad_field: TimeField <- ["--reference-time=tea"];                Both => Err(OptionsError::BadArgument(&flags::REFERENCE_TIME, OsString::from("tea"))));
+        test!(useless:   TimeFilter <- ["--reference-time=accessed"];      Complain => Err(OptionsError::Useless2(&flags::REFERENCE_TIME, &flags::NEWER_THAN, &flags::OLDER_THAN)));
+        #[cfg(unix)]
+        test!(missing:   TimeFilter <- ["--newer-than=/nonexistent/stamp"];   Both => Err(OptionsError::FailedReferenceFile(&flags::NEWER_THAN, std::path::PathBuf::from("/nonexistent/stamp"), String::from("No such file or directory (os error 2)"))));
     }
 
     mod git_ignores {
@@ -307,5 +841,8 @@ mod test {
 
         test!(off:  GitIgnore <- [];                Both => Ok(GitIgnore::Off));
         test!(on:   GitIgnore <- ["--git-ignore"];  Both => Ok(GitIgnore::CheckAndIgnore));
+        test!(debug:        GitIgnore <- ["--git-ignore", "--git-ignore-debug"];  Both => Ok(GitIgnore::CheckAndIgnore));
+        test!(just_debug:   GitIgnore <- ["--git-ignore-debug"];             Last => Ok(GitIgnore::Off));
+        test!(just_debug_2: GitIgnore <- ["--git-ignore-debug"];         Complain => Err(OptionsError::Useless(&flags::GIT_IGNORE_DEBUG, false, &flags::GIT_IGNORE)));
     }
 }
@@ -3,54 +3,124 @@ use crate::options::parser::{Arg, Args, TakesValue, Values};
 // exa options
 pub static VERSION: Arg = Arg { short: Some(b'v'), long: "version",  takes_value: TakesValue::Forbidden };
 pub static HELP:    Arg = Arg { short: Some(b'?'), long: "help",     takes_value: TakesValue::Forbidden };
+pub static GENERATE_COMPLETIONS: Arg = Arg { short: None, long: "generate-completions", takes_value: TakesValue::Necessary(Some(SHELLS)) };
+const SHELLS: Values = &["bash", "zsh", "fish", "nushell", "powershell"];
+pub static DEBUG_TIMING: Arg = Arg { short: None, long: "debug-timing", takes_value: TakesValue::Forbidden };
+pub static IO_TIMEOUT:   Arg = Arg { short: None, long: "io-timeout",   takes_value: TakesValue::Necessary(None) };
+pub static SFTP:         Arg = Arg { short: None, long: "sftp",         takes_value: TakesValue::Forbidden };
+pub static PRINT_LS_COLORS: Arg = Arg { short: None, long: "print-ls-colors", takes_value: TakesValue::Forbidden };
 
 // display options
 pub static ONE_LINE:    Arg = Arg { short: Some(b'1'), long: "oneline",     takes_value: TakesValue::Forbidden };
 pub static LONG:        Arg = Arg { short: Some(b'l'), long: "long",        takes_value: TakesValue::Forbidden };
 pub static GRID:        Arg = Arg { short: Some(b'G'), long: "grid",        takes_value: TakesValue::Forbidden };
 pub static ACROSS:      Arg = Arg { short: Some(b'x'), long: "across",      takes_value: TakesValue::Forbidden };
+pub static GRID_ORDER:  Arg = Arg { short: None,       long: "grid-order",  takes_value: TakesValue::Necessary(Some(GRID_ORDERS)) };
+const GRID_ORDERS: &[&str] = &["rows", "columns"];
+pub static THUMBNAILS:  Arg = Arg { short: None,       long: "thumbnails",  takes_value: TakesValue::Optional(Some(THUMBNAIL_PROTOCOLS), "auto") };
+const THUMBNAIL_PROTOCOLS: Values = &["auto", "kitty", "sixel", "symbols"];
 pub static RECURSE:     Arg = Arg { short: Some(b'R'), long: "recurse",     takes_value: TakesValue::Forbidden };
 pub static TREE:        Arg = Arg { short: Some(b'T'), long: "tree",        takes_value: TakesValue::Forbidden };
+pub static TREE_STYLE:  Arg = Arg { short: None,       long: "tree-style",  takes_value: TakesValue::Necessary(Some(TREE_STYLES)) };
+const TREE_STYLES: Values = &["unicode", "ascii", "rounded", "bold", "double", "none"];
+pub static DEPTH_GUIDES: Arg = Arg { short: None,     long: "ascii-depth-guides", takes_value: TakesValue::Optional(None, "1") };
 pub static CLASSIFY:    Arg = Arg { short: Some(b'F'), long: "classify",    takes_value: TakesValue::Optional(Some(WHEN), "auto") };
+pub static CLASSIFY_INDICATORS: Arg = Arg { short: None, long: "classify-indicators", takes_value: TakesValue::Necessary(Some(INDICATOR_SETS)) };
+const INDICATOR_SETS: Values = &["basic", "extended"];
 pub static DEREF_LINKS: Arg = Arg { short: Some(b'X'), long: "dereference", takes_value: TakesValue::Forbidden };
+pub static NO_DEREF_OWNER: Arg = Arg { short: None, long: "no-dereference-owner", takes_value: TakesValue::Forbidden };
 pub static WIDTH:       Arg = Arg { short: Some(b'w'), long: "width",       takes_value: TakesValue::Necessary(None) };
 pub static NO_QUOTES:   Arg = Arg { short: None,       long: "no-quotes",   takes_value: TakesValue::Forbidden };
+pub static LITERAL:     Arg = Arg { short: Some(b'N'), long: "literal",     takes_value: TakesValue::Forbidden };
+pub static QUOTING_STYLE: Arg = Arg { short: None,     long: "quoting-style", takes_value: TakesValue::Necessary(Some(QUOTING_STYLES)) };
+const QUOTING_STYLES: Values = &["literal", "shell", "c", "escape"];
+pub static SUMMARY:     Arg = Arg { short: None,       long: "summary",     takes_value: TakesValue::Necessary(Some(SUMMARY_GROUPS)) };
+const SUMMARY_GROUPS: Values = &["ext"];
+pub static SUMMARY_SORT: Arg = Arg { short: None,      long: "summary-sort", takes_value: TakesValue::Necessary(Some(SUMMARY_SORTS)) };
+const SUMMARY_SORTS: Values = &["ext", "count", "size", "largest"];
+pub static DIR_HEADER:  Arg = Arg { short: None,       long: "dir-header",  takes_value: TakesValue::Necessary(None) };
+pub static DIR_FOOTER:  Arg = Arg { short: None,       long: "dir-footer",  takes_value: TakesValue::Necessary(None) };
 pub static ABSOLUTE:    Arg = Arg { short: None,       long: "absolute",    takes_value: TakesValue::Optional(Some(ABSOLUTE_MODES), "on") };
 const ABSOLUTE_MODES: &[&str] = &["on", "follow", "off"];
+pub static RELATIVE_TO: Arg = Arg { short: None,     long: "relative-to", takes_value: TakesValue::Necessary(None) };
+pub static HARD_LINK_GROUPS: Arg = Arg { short: None, long: "hardlink-groups", takes_value: TakesValue::Forbidden };
+pub static CLONES:      Arg = Arg { short: None,       long: "clones",      takes_value: TakesValue::Forbidden };
+pub static HIGHLIGHT_NEW: Arg = Arg { short: None,     long: "highlight-new", takes_value: TakesValue::Forbidden };
+pub static TRUNCATE_NAMES: Arg = Arg { short: None, long: "truncate-names", takes_value: TakesValue::Necessary(None) };
+pub static SUGGEST_BROKEN_LINKS: Arg = Arg { short: None, long: "suggest-broken-links", takes_value: TakesValue::Forbidden };
+pub static SECURITY_AUDIT: Arg = Arg { short: None, long: "security-audit", takes_value: TakesValue::Forbidden };
+pub static AUDIT_EXIT_CODE: Arg = Arg { short: None, long: "audit-exit-code", takes_value: TakesValue::Necessary(None) };
+pub static IN_USE: Arg = Arg { short: None, long: "in-use", takes_value: TakesValue::Forbidden };
 
 pub static COLOR:  Arg = Arg { short: None, long: "color",  takes_value: TakesValue::Optional(Some(WHEN), "auto") };
 pub static COLOUR: Arg = Arg { short: None, long: "colour", takes_value: TakesValue::Optional(Some(WHEN), "auto") };
 const WHEN: &[&str] = &["always", "auto", "never"];
+pub static THEME:  Arg = Arg { short: None, long: "theme",  takes_value: TakesValue::Necessary(None) };
+pub static COLOR_THEME:  Arg = Arg { short: None, long: "color-theme",  takes_value: TakesValue::Necessary(Some(BACKGROUNDS)) };
+pub static COLOUR_THEME: Arg = Arg { short: None, long: "colour-theme", takes_value: TakesValue::Necessary(Some(BACKGROUNDS)) };
+const BACKGROUNDS: Values = &["dark", "light", "auto"];
 
 pub static COLOR_SCALE:  Arg = Arg { short: None, long: "color-scale",  takes_value: TakesValue::Optional(Some(SCALES), "all") };
 pub static COLOUR_SCALE: Arg = Arg { short: None, long: "colour-scale", takes_value: TakesValue::Optional(Some(SCALES), "all") };
 pub static COLOR_SCALE_MODE:  Arg = Arg { short: None, long: "color-scale-mode",  takes_value: TakesValue::Necessary(Some(COLOR_SCALE_MODES))};
 pub static COLOUR_SCALE_MODE: Arg = Arg { short: None, long: "colour-scale-mode", takes_value: TakesValue::Necessary(Some(COLOR_SCALE_MODES))};
+pub static NO_COLOR_SCALE_FLOOR:  Arg = Arg { short: None, long: "no-color-scale-floor",  takes_value: TakesValue::Forbidden };
+pub static NO_COLOUR_SCALE_FLOOR: Arg = Arg { short: None, long: "no-colour-scale-floor", takes_value: TakesValue::Forbidden };
+pub static COLOR_SCALE_TIME:  Arg = Arg { short: None, long: "color-scale-time",  takes_value: TakesValue::Necessary(Some(TIMES)) };
+pub static COLOUR_SCALE_TIME: Arg = Arg { short: None, long: "colour-scale-time", takes_value: TakesValue::Necessary(Some(TIMES)) };
+pub static COLOR_DEPTH:  Arg = Arg { short: None, long: "color-depth",  takes_value: TakesValue::Necessary(Some(COLOR_DEPTHS)) };
+pub static COLOUR_DEPTH: Arg = Arg { short: None, long: "colour-depth", takes_value: TakesValue::Necessary(Some(COLOR_DEPTHS)) };
 const SCALES: Values = &["all", "size", "age"];
 const COLOR_SCALE_MODES: Values = &["fixed", "gradient"];
+const COLOR_DEPTHS: Values = &["8", "256", "truecolor"];
 
 // filtering and sorting options
 pub static ALL:         Arg = Arg { short: Some(b'a'), long: "all",         takes_value: TakesValue::Forbidden };
 pub static ALMOST_ALL:  Arg = Arg { short: Some(b'A'), long: "almost-all",  takes_value: TakesValue::Forbidden };
 pub static LIST_DIRS:   Arg = Arg { short: Some(b'd'), long: "list-dirs",   takes_value: TakesValue::Forbidden };
 pub static LEVEL:       Arg = Arg { short: Some(b'L'), long: "level",       takes_value: TakesValue::Necessary(None) };
+pub static FOLLOW_SYMLINKS: Arg = Arg { short: None,   long: "follow-symlinks", takes_value: TakesValue::Forbidden };
 pub static REVERSE:     Arg = Arg { short: Some(b'r'), long: "reverse",     takes_value: TakesValue::Forbidden };
 pub static SORT:        Arg = Arg { short: Some(b's'), long: "sort",        takes_value: TakesValue::Necessary(Some(SORTS)) };
+pub static SORT_LEVELS: Arg = Arg { short: None,       long: "sort-levels", takes_value: TakesValue::Necessary(None) };
+pub static SORT_DIRS_FIRST_PER_LEVEL: Arg = Arg { short: None, long: "sort-dirs-first-per-level", takes_value: TakesValue::Necessary(None) };
 pub static IGNORE_GLOB: Arg = Arg { short: Some(b'I'), long: "ignore-glob", takes_value: TakesValue::Necessary(None) };
+pub static IGNORE_GLOB_FILE: Arg = Arg { short: None, long: "ignore-glob-file", takes_value: TakesValue::Necessary(None) };
+pub static EXCLUDE_VCS: Arg = Arg { short: None, long: "exclude-vcs", takes_value: TakesValue::Forbidden };
+pub static IGNORE_CASE:    Arg = Arg { short: None, long: "ignore-case",    takes_value: TakesValue::Forbidden };
+pub static CASE_SENSITIVE: Arg = Arg { short: None, long: "case-sensitive", takes_value: TakesValue::Forbidden };
 pub static GIT_IGNORE:  Arg = Arg { short: None, long: "git-ignore",           takes_value: TakesValue::Forbidden };
+pub static GIT_IGNORE_DEBUG: Arg = Arg { short: None, long: "git-ignore-debug", takes_value: TakesValue::Forbidden };
+pub static ONLY_MODIFIED_GIT: Arg = Arg { short: None, long: "only-modified-git", takes_value: TakesValue::Forbidden };
 pub static DIRS_FIRST:  Arg = Arg { short: None, long: "group-directories-first",  takes_value: TakesValue::Forbidden };
 pub static ONLY_DIRS:   Arg = Arg { short: Some(b'D'), long: "only-dirs", takes_value: TakesValue::Forbidden };
 pub static ONLY_FILES:  Arg = Arg { short: Some(b'f'), long: "only-files", takes_value: TakesValue::Forbidden };
+pub static ONLY_SYMLINKS:    Arg = Arg { short: None, long: "only-symlinks",    takes_value: TakesValue::Forbidden };
+pub static ONLY_EXECUTABLES: Arg = Arg { short: None, long: "only-executables", takes_value: TakesValue::Forbidden };
+pub static DANGLING_ONLY:    Arg = Arg { short: None, long: "dangling-only",    takes_value: TakesValue::Forbidden };
+pub static TYPE:        Arg = Arg { short: None, long: "type", takes_value: TakesValue::Necessary(Some(TYPES)) };
+pub static MIN_SIZE:    Arg = Arg { short: None, long: "min-size", takes_value: TakesValue::Necessary(None) };
+pub static MAX_SIZE:    Arg = Arg { short: None, long: "max-size", takes_value: TakesValue::Necessary(None) };
+pub static TAG:         Arg = Arg { short: None, long: "tag",      takes_value: TakesValue::Necessary(None) };
+pub static NEWER_THAN:  Arg = Arg { short: None, long: "newer-than", takes_value: TakesValue::Necessary(None) };
+pub static OLDER_THAN:  Arg = Arg { short: None, long: "older-than", takes_value: TakesValue::Necessary(None) };
+pub static REFERENCE_TIME: Arg = Arg { short: None, long: "reference-time", takes_value: TakesValue::Necessary(Some(TIMES)) };
 const SORTS: Values = &[ "name", "Name", "size", "extension",
                          "Extension", "modified", "changed", "accessed",
-                         "created", "inode", "type", "none" ];
+                         "created", "inode", "blocks", "links", "type", "name-natural", "none" ];
+pub static COLLATE:     Arg = Arg { short: None, long: "collate",  takes_value: TakesValue::Necessary(Some(COLLATIONS)) };
+const COLLATIONS: Values = &["locale", "codepoint"];
+const TYPES: Values = &["f", "d", "l", "x", "p", "s", "b", "c", "file", "dir", "directory", "symlink", "executable", "pipe", "socket", "block-device", "char-device"];
 
 // display options
 pub static BINARY:      Arg = Arg { short: Some(b'b'), long: "binary",      takes_value: TakesValue::Forbidden };
 pub static BYTES:       Arg = Arg { short: Some(b'B'), long: "bytes",       takes_value: TakesValue::Forbidden };
 pub static GROUP:       Arg = Arg { short: Some(b'g'), long: "group",       takes_value: TakesValue::Forbidden };
 pub static NUMERIC:     Arg = Arg { short: Some(b'n'), long: "numeric",     takes_value: TakesValue::Forbidden };
+pub static NUMERIC_IDS: Arg = Arg { short: None,       long: "numeric-ids", takes_value: TakesValue::Necessary(None) };
 pub static HEADER:      Arg = Arg { short: Some(b'h'), long: "header",      takes_value: TakesValue::Forbidden };
+pub static HEADER_MODE: Arg = Arg { short: None,       long: "header-mode", takes_value: TakesValue::Necessary(Some(HEADER_MODES)) };
+pub static HEADER_UNITS: Arg = Arg { short: None,      long: "header-units", takes_value: TakesValue::Forbidden };
 pub static ICONS:       Arg = Arg { short: None,       long: "icons",       takes_value: TakesValue::Optional(Some(WHEN), "auto")};
 pub static INODE:       Arg = Arg { short: Some(b'i'), long: "inode",       takes_value: TakesValue::Forbidden };
 pub static LINKS:       Arg = Arg { short: Some(b'H'), long: "links",       takes_value: TakesValue::Forbidden };
@@ -58,6 +128,27 @@ pub static MODIFIED:    Arg = Arg { short: Some(b'm'), long: "modified",    take
 pub static CHANGED:     Arg = Arg { short: None,       long: "changed",     takes_value: TakesValue::Forbidden };
 pub static BLOCKSIZE:   Arg = Arg { short: Some(b'S'), long: "blocksize",   takes_value: TakesValue::Forbidden };
 pub static TOTAL_SIZE:  Arg = Arg { short: None,       long: "total-size",  takes_value: TakesValue::Forbidden };
+pub static SIZE_ON_DISK: Arg = Arg { short: None,      long: "size-on-disk", takes_value: TakesValue::Forbidden };
+pub static SHOW_COUNTS: Arg = Arg { short: None,       long: "show-counts", takes_value: TakesValue::Forbidden };
+pub static PRUNE:       Arg = Arg { short: None,       long: "prune",       takes_value: TakesValue::Forbidden };
+pub static GROUP_BY:    Arg = Arg { short: None,       long: "group-by",    takes_value: TakesValue::Necessary(Some(GROUP_BYS)) };
+const GROUP_BYS: Values = &["none", "mtime-bucket"];
+pub static TIME_BUCKETS: Arg = Arg { short: None,      long: "time-buckets", takes_value: TakesValue::Necessary(None) };
+pub static ALIGN_SECTIONS: Arg = Arg { short: None,    long: "align-sections", takes_value: TakesValue::Forbidden };
+pub static TOTAL:       Arg = Arg { short: None,       long: "total",       takes_value: TakesValue::Forbidden };
+pub static COMPARE:     Arg = Arg { short: None,       long: "compare",     takes_value: TakesValue::Forbidden };
+pub static NULL:        Arg = Arg { short: Some(b'0'), long: "null",        takes_value: TakesValue::Forbidden };
+pub static FORMAT:      Arg = Arg { short: None,       long: "format",      takes_value: TakesValue::Necessary(Some(FORMATS)) };
+const FORMATS: Values = &["text", "jsonl", "html"];
+pub static FIELDS:      Arg = Arg { short: None,       long: "fields",      takes_value: TakesValue::Necessary(None) };
+pub static FORMAT_TEMPLATE: Arg = Arg { short: None,    long: "format-template", takes_value: TakesValue::Necessary(None) };
+pub static STAT:        Arg = Arg { short: None,       long: "stat",        takes_value: TakesValue::Forbidden };
+pub static WHICH:       Arg = Arg { short: None,       long: "which",       takes_value: TakesValue::Necessary(None) };
+pub static WATCH:       Arg = Arg { short: None,       long: "watch",       takes_value: TakesValue::Forbidden };
+pub static EXEC:        Arg = Arg { short: None,       long: "exec",        takes_value: TakesValue::Necessary(None) };
+pub static SNAPSHOT_OUT: Arg = Arg { short: None,      long: "snapshot-out", takes_value: TakesValue::Necessary(None) };
+pub static SNAPSHOT_IN: Arg = Arg { short: None,       long: "snapshot-in", takes_value: TakesValue::Necessary(None) };
+pub static PAGING:      Arg = Arg { short: None,       long: "paging",      takes_value: TakesValue::Optional(Some(WHEN), "auto") };
 pub static TIME:        Arg = Arg { short: Some(b't'), long: "time",        takes_value: TakesValue::Necessary(Some(TIMES)) };
 pub static ACCESSED:    Arg = Arg { short: Some(b'u'), long: "accessed",    takes_value: TakesValue::Forbidden };
 pub static CREATED:     Arg = Arg { short: Some(b'U'), long: "created",     takes_value: TakesValue::Forbidden };
@@ -65,40 +156,58 @@ pub static TIME_STYLE:  Arg = Arg { short: None,       long: "time-style",  take
 pub static HYPERLINK:   Arg = Arg { short: None,       long: "hyperlink",   takes_value: TakesValue::Forbidden };
 pub static MOUNTS:      Arg = Arg { short: Some(b'M'), long: "mounts",      takes_value: TakesValue::Forbidden };
 pub static SMART_GROUP: Arg = Arg { short: None,       long: "smart-group", takes_value: TakesValue::Forbidden };
+const HEADER_MODES: Values = &["once", "repeat"];
 const TIMES: Values = &["modified", "changed", "accessed", "created"];
-const TIME_STYLES: Values = &["default", "long-iso", "full-iso", "iso", "relative"];
+const TIME_STYLES: Values = &["default", "locale", "long-iso", "full-iso", "iso", "relative"];
 
 // suppressing columns
 pub static NO_PERMISSIONS: Arg = Arg { short: None, long: "no-permissions", takes_value: TakesValue::Forbidden };
 pub static NO_FILESIZE: Arg = Arg { short: None, long: "no-filesize", takes_value: TakesValue::Forbidden };
 pub static NO_USER: Arg = Arg { short: None, long: "no-user", takes_value: TakesValue::Forbidden };
+pub static COLUMNS: Arg = Arg { short: None, long: "columns", takes_value: TakesValue::Necessary(None) };
 pub static NO_TIME: Arg = Arg { short: None, long: "no-time", takes_value: TakesValue::Forbidden };
 
 // optional feature options
-pub static GIT:               Arg = Arg { short: None,       long: "git",                  takes_value: TakesValue::Forbidden };
+pub static GIT:               Arg = Arg { short: None,       long: "git",                  takes_value: TakesValue::Optional(Some(GIT_MODES), "sync") };
 pub static NO_GIT:            Arg = Arg { short: None,       long: "no-git",               takes_value: TakesValue::Forbidden };
 pub static GIT_REPOS:         Arg = Arg { short: None,       long: "git-repos",            takes_value: TakesValue::Forbidden };
 pub static GIT_REPOS_NO_STAT: Arg = Arg { short: None,       long: "git-repos-no-status",  takes_value: TakesValue::Forbidden };
+pub static GIT_IGNORE_SUBMODULES: Arg = Arg { short: None,  long: "git-ignore-submodules", takes_value: TakesValue::Optional(Some(SUBMODULE_IGNORES), "all") };
+pub static GIT_REPOS_BEHIND:  Arg = Arg { short: None,       long: "git-repos-behind",     takes_value: TakesValue::Forbidden };
+pub static GIT_PORCELAIN:     Arg = Arg { short: None,       long: "git-status-porcelain-compat", takes_value: TakesValue::Forbidden };
+pub static GIT_DETAIL:        Arg = Arg { short: None,       long: "git-detail",           takes_value: TakesValue::Forbidden };
+pub static GIT_AGE_SINCE:     Arg = Arg { short: None,       long: "git-age-since",        takes_value: TakesValue::Necessary(None) };
 pub static EXTENDED:          Arg = Arg { short: Some(b'@'), long: "extended",             takes_value: TakesValue::Forbidden };
+pub static XATTR_VALUES:      Arg = Arg { short: None,       long: "xattr-values",         takes_value: TakesValue::Optional(None, "32") };
 pub static OCTAL:             Arg = Arg { short: Some(b'o'), long: "octal-permissions",    takes_value: TakesValue::Forbidden };
 pub static SECURITY_CONTEXT:  Arg = Arg { short: Some(b'Z'), long: "context",              takes_value: TakesValue::Forbidden };
 pub static STDIN:             Arg = Arg { short: None,       long: "stdin",                takes_value: TakesValue::Forbidden };
 pub static FILE_FLAGS:        Arg = Arg { short: Some(b'O'), long: "flags",                takes_value: TakesValue::Forbidden };
+pub static FRECENCY:          Arg = Arg { short: None,       long: "frecency",             takes_value: TakesValue::Forbidden };
+pub static UNIQUE:            Arg = Arg { short: None,       long: "unique",               takes_value: TakesValue::Forbidden };
+pub static COMMON_NAMES:      Arg = Arg { short: None,       long: "common-names",         takes_value: TakesValue::Forbidden };
+pub static TARGET:            Arg = Arg { short: None,       long: "target",               takes_value: TakesValue::Forbidden };
+pub static QUOTA:             Arg = Arg { short: None,       long: "quota",                takes_value: TakesValue::Forbidden };
+const GIT_MODES: Values = &["sync", "async"];
+const SUBMODULE_IGNORES: Values = &["all", "dirty", "untracked", "none"];
 
 pub static ALL_ARGS: Args = Args(&[
-    &VERSION, &HELP,
+    &VERSION, &HELP, &GENERATE_COMPLETIONS, &DEBUG_TIMING, &IO_TIMEOUT, &SFTP, &PRINT_LS_COLORS,
 
-    &ONE_LINE, &LONG, &GRID, &ACROSS, &RECURSE, &TREE, &CLASSIFY, &DEREF_LINKS,
-    &COLOR, &COLOUR, &COLOR_SCALE, &COLOUR_SCALE, &COLOR_SCALE_MODE, &COLOUR_SCALE_MODE,
-    &WIDTH, &NO_QUOTES, &ABSOLUTE,
+    &ONE_LINE, &LONG, &GRID, &ACROSS, &GRID_ORDER, &THUMBNAILS, &RECURSE, &TREE, &CLASSIFY, &CLASSIFY_INDICATORS, &DEREF_LINKS, &NO_DEREF_OWNER,
+    &COLOR, &COLOUR, &THEME, &COLOR_THEME, &COLOUR_THEME, &COLOR_SCALE, &COLOUR_SCALE, &COLOR_SCALE_MODE, &COLOUR_SCALE_MODE,
+    &NO_COLOR_SCALE_FLOOR, &NO_COLOUR_SCALE_FLOOR, &COLOR_SCALE_TIME, &COLOUR_SCALE_TIME,
+    &COLOR_DEPTH, &COLOUR_DEPTH,
+    &WIDTH, &NO_QUOTES, &LITERAL, &QUOTING_STYLE, &DIR_HEADER, &DIR_FOOTER, &SUMMARY, &SUMMARY_SORT, &ABSOLUTE, &RELATIVE_TO, &HARD_LINK_GROUPS, &CLONES, &HIGHLIGHT_NEW, &SUGGEST_BROKEN_LINKS, &SECURITY_AUDIT, &AUDIT_EXIT_CODE, &IN_USE, &TRUNCATE_NAMES,
 
-    &ALL, &ALMOST_ALL, &LIST_DIRS, &LEVEL, &REVERSE, &SORT, &DIRS_FIRST,
-    &IGNORE_GLOB, &GIT_IGNORE, &ONLY_DIRS, &ONLY_FILES,
+    &ALL, &ALMOST_ALL, &LIST_DIRS, &LEVEL, &FOLLOW_SYMLINKS, &REVERSE, &SORT, &SORT_LEVELS, &SORT_DIRS_FIRST_PER_LEVEL, &COLLATE, &DIRS_FIRST,
+    &IGNORE_GLOB, &IGNORE_GLOB_FILE, &EXCLUDE_VCS, &IGNORE_CASE, &CASE_SENSITIVE, &GIT_IGNORE, &GIT_IGNORE_DEBUG, &ONLY_MODIFIED_GIT, &ONLY_DIRS, &ONLY_FILES, &ONLY_SYMLINKS, &ONLY_EXECUTABLES, &DANGLING_ONLY, &TYPE,
+    &MIN_SIZE, &MAX_SIZE, &TAG, &NEWER_THAN, &OLDER_THAN, &REFERENCE_TIME,
 
-    &BINARY, &BYTES, &GROUP, &NUMERIC, &HEADER, &ICONS, &INODE, &LINKS, &MODIFIED, &CHANGED,
-    &BLOCKSIZE, &TOTAL_SIZE, &TIME, &ACCESSED, &CREATED, &TIME_STYLE, &HYPERLINK, &MOUNTS,
-    &NO_PERMISSIONS, &NO_FILESIZE, &NO_USER, &NO_TIME, &SMART_GROUP,
+    &BINARY, &BYTES, &GROUP, &NUMERIC, &NUMERIC_IDS, &HEADER, &HEADER_MODE, &HEADER_UNITS, &ICONS, &INODE, &LINKS, &MODIFIED, &CHANGED,
+    &BLOCKSIZE, &TOTAL_SIZE, &SIZE_ON_DISK, &SHOW_COUNTS, &PRUNE, &TREE_STYLE, &DEPTH_GUIDES, &ALIGN_SECTIONS, &GROUP_BY, &TIME_BUCKETS, &TOTAL, &COMPARE, &STAT, &WHICH, &WATCH, &EXEC, &SNAPSHOT_OUT, &SNAPSHOT_IN, &FORMAT, &FIELDS, &FORMAT_TEMPLATE, &NULL, &PAGING, &TIME, &ACCESSED, &CREATED, &TIME_STYLE, &HYPERLINK, &MOUNTS,
+    &NO_PERMISSIONS, &NO_FILESIZE, &NO_USER, &COLUMNS, &NO_TIME, &SMART_GROUP,
 
-    &GIT, &NO_GIT, &GIT_REPOS, &GIT_REPOS_NO_STAT,
-    &EXTENDED, &OCTAL, &SECURITY_CONTEXT, &STDIN, &FILE_FLAGS
+    &GIT, &NO_GIT, &GIT_REPOS, &GIT_REPOS_NO_STAT, &GIT_REPOS_BEHIND, &GIT_IGNORE_SUBMODULES, &GIT_PORCELAIN, &GIT_DETAIL, &GIT_AGE_SINCE,
+    &EXTENDED, &XATTR_VALUES, &OCTAL, &SECURITY_CONTEXT, &STDIN, &FRECENCY, &UNIQUE, &COMMON_NAMES, &FILE_FLAGS, &TARGET, &QUOTA
 ]);
@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use crate::frecency::Frecency;
+use crate::options::parser::MatchedFlags;
+use crate::options::vars::{self, Vars};
+use crate::options::{flags, OptionsError};
+
+impl Frecency {
+    /// Determines whether `z:` arguments should be looked up, and where
+    /// eza’s own list of directories is kept.
+    pub fn deduce<V: Vars>(
+        matches: &MatchedFlags<'_>,
+        vars: &V,
+    ) -> Result<Option<Self>, OptionsError> {
+        if !matches.has(&flags::FRECENCY)? {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            store: data_dir(vars).map(|dir| dir.join("eza").join("frecency")),
+        }))
+    }
+}
+
+/// The directory that eza keeps its data in.
+fn data_dir<V: Vars>(vars: &V) -> Option<PathBuf> {
+    vars.get(vars::XDG_DATA_HOME)
+        .map(PathBuf::from)
+        .or_else(|| {
+            vars.get(vars::HOME)
+                .map(|home| Path::new(&home).join(".local").join("share"))
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::options::parser::Arg;
+    use crate::options::test::parse_for_test;
+    use crate::options::test::Strictnesses::*;
+    use std::ffi::OsString;
+
+    static TEST_ARGS: &[&Arg] = &[&flags::FRECENCY];
+
+    struct MockVars {
+        xdg_data_home: &'static str,
+        home: &'static str,
+    }
+
+    impl Vars for MockVars {
+        fn get(&self, name: &'static str) -> Option<OsString> {
+            match name {
+                "XDG_DATA_HOME" if !self.xdg_data_home.is_empty() => {
+                    Some(OsString::from(self.xdg_data_home))
+                }
+                "HOME" if !self.home.is_empty() => Some(OsString::from(self.home)),
+                _ => None,
+            }
+        }
+    }
+
+    macro_rules! test {
+        ($name:ident: $inputs:expr, $xdg:expr, $home:expr; $stricts:expr => $result:expr) => {
+            #[test]
+            fn $name() {
+                let vars = MockVars {
+                    xdg_data_home: $xdg,
+                    home: $home,
+                };
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| {
+                    Frecency::deduce(mf, &vars)
+                }) {
+                    assert_eq!(result, $result);
+                }
+            }
+        };
+    }
+
+    fn store(path: &str) -> Frecency {
+        Frecency {
+            store: Some(PathBuf::from(path)),
+        }
+    }
+
+    test!(off:      [], "/data", "/home/me";             Both => Ok(None));
+    test!(xdg:      ["--frecency"], "/data", "/home/me"; Both => Ok(Some(store("/data/eza/frecency"))));
+    test!(home:     ["--frecency"], "", "/home/me";      Both => Ok(Some(store("/home/me/.local/share/eza/frecency"))));
+    test!(nowhere:  ["--frecency"], "", "";              Both => Ok(Some(Frecency { store: None })));
+}
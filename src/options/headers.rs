@@ -0,0 +1,108 @@
+use std::fs;
+use std::io;
+
+use log::*;
+
+use crate::options::theme::{config_dir, config_mapping};
+use crate::options::vars::Vars;
+use crate::options::view::column_named;
+use crate::output::table::{Column, HeaderLabels};
+
+/// The configuration file key that the column headers go under.
+const HEADERS_KEY: &str = "headers";
+
+/// The configuration file key that the shorter column headers go under.
+const SHORT_HEADERS_KEY: &str = "short-headers";
+
+impl HeaderLabels {
+    /// Reads the column headers out of `config.yml` in the configuration
+    /// directory. Not having one just means the usual headers get used.
+    pub fn deduce<V: Vars>(vars: &V) -> Self {
+        let Some(path) = config_dir(vars).map(|dir| dir.join("config.yml")) else {
+            return Self::default();
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => parse_headers(&contents),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("Couldn't read {:?}: {}", path, e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Parse the `headers` and `short-headers` mappings out of the configuration
+/// file, both of which go from the names that `--columns` takes to the text
+/// to show above those columns. Only the full headers can rename the file
+/// name column, as it never gets any narrower than the names in it.
+fn parse_headers(contents: &str) -> HeaderLabels {
+    let mut labels = HeaderLabels::default();
+
+    for (key, label) in config_mapping(contents, HEADERS_KEY) {
+        if key == "name" {
+            labels.name = Some(label);
+        } else if let Some(column) = header_column(&key) {
+            labels.full.push((column, label));
+        }
+    }
+
+    for (key, label) in config_mapping(contents, SHORT_HEADERS_KEY) {
+        if let Some(column) = header_column(&key) {
+            labels.short.push((column, label));
+        }
+    }
+
+    labels
+}
+
+/// The column that a header is for, warning about names that aren’t one.
+fn header_column(key: &str) -> Option<Column> {
+    let column = column_named(key);
+    if column.is_none() {
+        warn!("Skipping header for unknown column {:?}", key);
+    }
+    column
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output::table::TimeType;
+
+    fn label(text: &str) -> String {
+        String::from(text)
+    }
+
+    #[test]
+    fn block() {
+        let contents = "headers:\n  size: Größe\n  modified: \"Geändert\"\n  name: Name  # same\nshort-headers: {size: Gr, perms: Rechte}\n";
+        assert_eq!(
+            parse_headers(contents),
+            HeaderLabels {
+                full: vec![
+                    (Column::FileSize, label("Größe")),
+                    (Column::Timestamp(TimeType::Modified), label("Geändert")),
+                ],
+                short: vec![
+                    (Column::FileSize, label("Gr")),
+                    (Column::Permissions, label("Rechte")),
+                ],
+                name: Some(label("Name")),
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_columns() {
+        let contents = "headers: {sizes: Größe}\nshort-headers:\n  name: N\n";
+        assert_eq!(parse_headers(contents), HeaderLabels::default());
+    }
+
+    #[test]
+    fn no_headers() {
+        assert_eq!(parse_headers("icon-pack: ascii\n"), HeaderLabels::default());
+        assert_eq!(parse_headers(""), HeaderLabels::default());
+    }
+}
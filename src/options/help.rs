@@ -10,23 +10,78 @@ static USAGE_PART1: &str = "Usage:
 META OPTIONS
   --help                     show list of command-line options
   -v, --version              show version of eza
+  --generate-completions SHELL
+                             print a completion script for a shell
+                             (bash, zsh, fish, nushell, powershell)
+  --debug-timing             print how long each part of the listing took
+  --io-timeout SECONDS       give up on filesystem calls that take longer than this
+  --sftp                     list sftp://host/path arguments on other computers
+  --print-ls-colors          print the file name colours as an LS_COLORS string
 
 DISPLAY OPTIONS
   -1, --oneline              display one entry per line
+  -0, --null                 end each entry with a NUL byte, for 'xargs -0'
   -l, --long                 display extended file metadata as a table
   -G, --grid                 display entries as a grid (default)
   -x, --across               sort the grid across, rather than downwards
+  --grid-order ORDER         fill the grid by 'rows' (like --across) or 'columns'
+  --thumbnails[=PROTOCOL]    display images as a grid of previews (auto, kitty, sixel, symbols)
   -R, --recurse              recurse into directories
   -T, --tree                 recurse into directories as a tree
   -X, --dereference          dereference symbolic links when displaying information
+  --no-dereference-owner     keep links' own owners and permissions with --dereference
   -F, --classify=WHEN        display type indicator by file names (always, auto, never)
+  --classify-indicators SET  which type indicators to use (basic, extended)
   --colo[u]r=WHEN            when to use terminal colours (always, auto, never)
+  --theme NAME               use the colours from the theme file NAME.yml
+  --colo[u]r-theme BG        which background to pick default colours for (dark, light, auto)
   --colo[u]r-scale           highlight levels of 'field' distinctly(all, age, size)
   --colo[u]r-scale-mode      use gradient or fixed colors in --color-scale (fixed, gradient)
+  --no-colo[u]r-scale-floor  colour sizes by fixed thresholds, not by the other files
+  --colo[u]r-scale-time TIME shade every timestamp by one time field (modified,
+                             changed, accessed, created)
+  --colo[u]r-depth DEPTH     how many colours to draw gradients with (8, 256, truecolor)
   --icons=WHEN               when to display icons (always, auto, never)
   --no-quotes                don't quote file names with spaces
+  -N, --literal              print file names as they are, without escaping
+  --quoting-style STYLE      how to quote file names (literal, shell, c, escape)
   --hyperlink                display entries as hyperlinks
+  --hardlink-groups          mark files that are hard links to the same file as
+                             another one listed, colouring each group alike
+  --clones                   mark files that share data blocks with a clone
+  --highlight-new            mark files added (+) or changed (~) since the last
+                             time their directory was listed with this option
+  --suggest-broken-links     suggest a similarly-named file for broken links
+  --security-audit           flag risky permissions, such as world-writable
+                             files and setuid programs
+  --audit-exit-code CODE     exit with CODE if --security-audit flags anything
+  --in-use                   mark files that processes have open (slow, as
+                             it looks at every process)
+  --truncate-names HOW       shorten names wider than 40 columns (middle, end),
+                             or N columns with 'middle:N' or 'end:N'
   --absolute                 display entries with their absolute path (on, follow, off)
+  --relative-to PATH         display entries with their path relative to PATH
+  --total                    show a footer with counts and sizes after each listing
+  --dir-header TEMPLATE      line to print before each directory's files, with
+                             fields like {path}, {count}, and {total_size}
+  --dir-footer TEMPLATE      line to print after each directory's files
+  --compare DIR1 DIR2        list two directories against each other, marking
+                             what's only in one and which files differ
+  --stat                     show everything about each file, one field per line
+  --which NAME               list every program called NAME along $PATH
+  --watch                    list the files again whenever they change
+  --exec CMD                 with --watch, run CMD after each change, with {}
+                             replaced by the changed paths
+  --snapshot-out FILE        save the files' details to FILE instead of listing them
+  --snapshot-in FILE         list the files saved in FILE with --snapshot-out
+  --format FORMAT            write the listing as text, JSON Lines, or HTML (text, jsonl, html)
+  --fields LIST              with --format=jsonl, which fields to write (name,size,mtime,...)
+  --format-template TEMPLATE write a line per file, filled in from a template
+                             like '{name}\t{size:>8|binary}\t{mtime}'
+  --summary ext              add the listing up by extension instead of listing files
+  --summary-sort FIELD       which column to sort the summary by (ext, count, size,
+                             largest)
+  --paging=WHEN              when to send the output through a pager (always, auto, never)
   -w, --width COLS           set screen width in columns
 
 
@@ -35,20 +90,49 @@ FILTERING AND SORTING OPTIONS
                              show the '.' and '..' directories
   -A, --almost-all           equivalent to --all; included for compatibility with `ls -A`
   -d, --list-dirs            list directories as files; don't list their contents
-  -L, --level DEPTH          limit the depth of recursion
+  -L, --level DEPTH          limit the depth of recursion, or pick levels
+                             to list with -R, like 1,3 or 2-4
+  --follow-symlinks          recurse into symlinked directories, stopping at loops
   -r, --reverse              reverse the sort order
   -s, --sort SORT_FIELD      which field to sort by
+  --collate HOW              how --sort=name-natural compares letters (locale, codepoint)
   --group-directories-first  list directories before other files
+  --sort-levels RULES        with --tree, sort settings for some levels, like
+                             '1:dirs-first;2+:size,reverse'
+  --sort-dirs-first-per-level LEVELS
+                             with --tree, list directories first only at these
+                             levels, like 1 or 1-2
   -D, --only-dirs            list only directories
   -f, --only-files           list only files
-  -I, --ignore-glob GLOBS    glob patterns (pipe-separated) of files to ignore";
+  --only-symlinks            list only symbolic links
+  --only-executables         list only executable files
+  --dangling-only            list only symbolic links whose target is missing
+  --type KINDS               list only the given kinds of file, comma-separated
+                             (f, d, l, x, p, s, b, c, or file, dir, symlink,
+                             executable, pipe, socket, block-device, char-device)
+  --min-size SIZE            list only files at least this big (like 10M or 4Ki)
+  --max-size SIZE            list only files at most this big
+                             (directories only get compared with --total-size)
+  --tag NAME                 list only files with this Finder tag
+  --newer-than FILE          list only files modified after this one
+  --older-than FILE          list only files modified before this one
+  --reference-time WORD      which timestamp to compare with those files
+                               (modified, changed, accessed, created)
+  -I, --ignore-glob GLOBS    glob patterns (pipe-separated) of files to ignore
+  --ignore-glob-file PATH    ignore files matching the patterns in PATH, one per line
+  --exclude-vcs              ignore version control directories, such as .git
+  --ignore-case              match glob patterns regardless of case
+  --case-sensitive           match glob patterns only with the same case";
 
 static GIT_FILTER_HELP: &str = "  \
-  --git-ignore               ignore files mentioned in '.gitignore'";
+  --git-ignore               ignore files mentioned in '.gitignore'
+  --git-ignore-debug         report the rule that hid each ignored file
+  --only-modified-git        list only files with changes that Git knows about";
 
 static USAGE_PART2: &str = "  \
-  Valid sort fields:         name, Name, extension, Extension, size, type,
-                             modified, accessed, created, inode, and none.
+  Valid sort fields:         name, Name, name-natural, extension, Extension,
+                             size, type, modified, accessed, created, inode,
+                             blocks, links, and none.
                              date, time, old, and new all refer to modified.
 
 LONG VIEW OPTIONS
@@ -57,40 +141,95 @@ LONG VIEW OPTIONS
   -g, --group                list each file's group
   --smart-group              only show group if it has a different name from owner
   -h, --header               add a header row to each column
+  --header-mode MODE         show the header once per directory, or repeat it
+                             every screenful (once, repeat)
+  --header-units             add a row of units and sort arrows under the header
   -H, --links                list each file's number of hard links
   -i, --inode                list each file's inode number
   -m, --modified             use the modified timestamp field
-  -M, --mounts               show mount details (Linux and Mac only)
+  -M, --mounts               show mount details (Linux, macOS and BSD only)
   -n, --numeric              list numeric user and group IDs
-  -O, --flags                list file flags (Mac, BSD, and Windows only)
+  --numeric-ids IDS          list only some IDs as numbers (uid, gid), or
+                             names with IDs (both)
+  -O, --flags                list file flags (Mac, BSD, Windows, and Linux)
+  --target                   list where each symlink finally resolves to
+  --quota                    list how much of its filesystem's quota each file's
+                             owner is using (Linux)
   -S, --blocksize            show size of allocated file system blocks
   -t, --time FIELD           which timestamp field to list (modified, accessed, created)
   -u, --accessed             use the accessed timestamp field
   -U, --created              use the created timestamp field
   --changed                  use the changed timestamp field
-  --time-style               how to format timestamps (default, iso, long-iso,
-                             full-iso, relative, or a custom style '+<FORMAT>'
-                             like '+%Y-%m-%d %H:%M')
+  --time-style               how to format timestamps (default, locale, iso,
+                             long-iso, full-iso, relative, or a custom style
+                             '+<FORMAT>' like '+%Y-%m-%d %H:%M')
   --total-size               show the size of a directory as the size of all
                              files and directories inside (unix only)
+  --size-on-disk             with --tree --total-size, show each directory's
+                             disk usage rather than its apparent size
+  --show-counts              with --tree, show how many files are in each
+                             directory and their total size
+  --prune                    with --tree, leave out directories that end up empty
+  --tree-style STYLE         with --tree, how to draw the branches (unicode,
+                             ascii, rounded, bold, double, none)
+  --ascii-depth-guides=N     with --tree, number every Nth level's branch line
+                             with its depth (1 by default)
+  --align-sections           with --recurse, line the columns up across every
+                             directory's listing
+  --group-by=mtime-bucket    split the listing into sections by when each file
+                             was modified (none, mtime-bucket)
+  --time-buckets LIST        which sections to split it into (today,yesterday,
+                             week,month,year)
   --no-permissions           suppress the permissions field
   -o, --octal-permissions    list each file's permission in octal format
   --no-filesize              suppress the filesize field
   --no-user                  suppress the user field
   --no-time                  suppress the time field
+  --columns LIST             show exactly these columns, in this order (size,perms,user,time,...)
   --stdin                    read file names from stdin, one per line or other separator 
-                             specified in environment";
+                             specified in environment
+  --frecency                 look up arguments like 'z:proj' with zoxide, or in
+                             a list of the directories eza has listed
+  --unique                   list each path only once, however it was written
+  --common-names             mark files with the same name as one under another
+                             argument";
 
 static GIT_VIEW_HELP: &str = "  \
-  --git                      list each file's Git status, if tracked or ignored
+  --git[=WHEN]               list each file's Git status, if tracked or ignored
+                             (sync, async: show a table that fits on screen
+                             before its statuses, then fill them in)
+  --git-ignore-submodules[=WHEN]
+                             which changes in submodules to ignore
+                             (all, dirty, untracked, none)
+  --git-status-porcelain-compat
+                             show Git statuses with the letters from
+                             'git status --short'
+  --git-detail               split the Git column into staged and unstaged,
+                             with a similarity score for renames (R086)
+  --git-age-since REF        show when each file last changed, if it has since
+                             the Git reference REF, such as a tag
   --no-git                   suppress Git status (always overrides --git,
                              --git-repos, --git-repos-no-status)
-  --git-repos                list root of git-tree status";
+  --git-repos                list root of git-tree status
+  --git-repos-no-status      list whether a directory is a Git repository, but not its status
+  --git-repos-behind         also show how far each repository is behind its upstream";
 static EXTENDED_HELP: &str = "  \
-  -@, --extended             list each file's extended attributes and sizes";
+  -@, --extended             list each file's extended attributes and sizes
+  --xattr-values[=N]         with -@, preview attribute values, up to N characters";
 static SECATTR_HELP: &str = "  \
   -Z, --context              list each file's security context";
 
+/// Every section of the help text, whether or not this build includes the
+/// features they describe.
+pub static ALL_SECTIONS: &[&str] = &[
+    USAGE_PART1,
+    GIT_FILTER_HELP,
+    USAGE_PART2,
+    GIT_VIEW_HELP,
+    EXTENDED_HELP,
+    SECATTR_HELP,
+];
+
 /// All the information needed to display the help text, which depends
 /// on which features are enabled and whether the user only wants to
 /// see one section’s help.
@@ -120,9 +259,7 @@ impl fmt::Display for HelpString {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
         write!(f, "{USAGE_PART1}")?;
 
-        if cfg!(feature = "git") {
-            write!(f, "\n{GIT_FILTER_HELP}")?;
-        }
+        write!(f, "\n{GIT_FILTER_HELP}")?;
 
         write!(f, "\n{USAGE_PART2}")?;
 
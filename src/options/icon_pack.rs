@@ -0,0 +1,94 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use log::*;
+
+use crate::options::theme::{config_dir, split_theme_line};
+use crate::options::vars::Vars;
+use crate::output::icons::IconPack;
+
+/// The configuration file key that the icon pack goes under.
+const ICON_PACK_KEY: &str = "icon-pack";
+
+impl IconPack {
+    /// Reads which icon pack to use out of `config.yml` in the
+    /// configuration directory, which can be one of the built-in ones, or
+    /// a file in the `icons` directory next to it. Not picking one, or
+    /// picking one that doesn’t exist, means the default gets used.
+    pub fn deduce<V: Vars>(vars: &V) -> Option<Self> {
+        let config_dir = config_dir(vars)?;
+        let path = config_dir.join("config.yml");
+
+        let name = match fs::read_to_string(&path) {
+            Ok(contents) => pack_name(&contents)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => {
+                warn!("Couldn't read {:?}: {}", path, e);
+                return None;
+            }
+        };
+
+        if let Some(pack) = Self::built_in(&name) {
+            return Some(pack);
+        }
+
+        let path = config_dir.join("icons").join(&name);
+        let path = if path.extension().is_some() {
+            path
+        } else {
+            path.with_extension("txt")
+        };
+        read_pack(&path, &name)
+    }
+}
+
+/// Reads an icon pack file, which gets put on top of the default pack.
+fn read_pack(path: &Path, name: &str) -> Option<IconPack> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Some(IconPack::over_default(&contents)),
+        Err(e) => {
+            let built_in = IconPack::built_in_names().collect::<Vec<_>>();
+            warn!(
+                "Couldn't read icon pack {:?} from {:?} ({}), and it's not one of {}",
+                name,
+                path,
+                e,
+                built_in.join(", ")
+            );
+            None
+        }
+    }
+}
+
+/// The name of the icon pack picked in the configuration file, if one was.
+fn pack_name(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .filter(|line| !line.starts_with([' ', '\t']))
+        .filter_map(|line| split_theme_line(line.trim()))
+        .find(|(key, _)| key == ICON_PACK_KEY)
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn picked() {
+        let contents = "bookmarks:\n  icon-pack: ~/nowhere\nicon-pack: ascii  # no font\n";
+        assert_eq!(pack_name(contents), Some(String::from("ascii")));
+        assert_eq!(
+            pack_name("\"icon-pack\": 'nerd-v2'"),
+            Some(String::from("nerd-v2"))
+        );
+    }
+
+    #[test]
+    fn not_picked() {
+        assert_eq!(pack_name("bookmarks: {proj: ~/code/project}\n"), None);
+        assert_eq!(pack_name("icon-pack:\n"), None);
+    }
+}
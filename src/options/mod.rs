@@ -68,17 +68,29 @@
 //! --grid --long` shouldn’t complain about `--long` being given twice when
 //! it’s clear what the user wants.
 
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+use std::time::Duration;
 
+use crate::bookmarks::Bookmarks;
+use crate::frecency::Frecency;
 use crate::fs::dir_action::DirAction;
+use crate::fs::fields::SubmoduleIgnore;
 use crate::fs::filter::{FileFilter, GitIgnore};
 use crate::options::stdin::FilesInput;
-use crate::output::{details, grid_details, Mode, View};
+use crate::output::template::Template;
+use crate::output::{details, grid_details, Mode, OutputFormat, Paging, View};
 use crate::theme::Options as ThemeOptions;
+use crate::watch;
 
+mod bookmarks;
 mod dir_action;
 mod file_name;
 mod filter;
+mod frecency;
+mod headers;
+mod icon_pack;
+mod thumbnails;
 #[rustfmt::skip] // this module becomes unreadable with rustfmt
 mod flags;
 mod theme;
@@ -90,8 +102,11 @@ pub use self::error::{NumberSource, OptionsError};
 mod help;
 use self::help::HelpString;
 
+mod completions;
+use self::completions::Completions;
+
 mod parser;
-use self::parser::MatchedFlags;
+use self::parser::{Arg, MatchedFlags};
 
 pub mod vars;
 pub use self::vars::Vars;
@@ -104,6 +119,7 @@ use self::version::VersionString;
 /// These **options** represent a parsed, error-checked versions of the
 /// user’s command-line options.
 #[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Options {
     /// The action to perform when encountering a directory rather than a
     /// regular file.
@@ -123,6 +139,62 @@ pub struct Options {
 
     /// Whether to read file names from stdin instead of the command-line
     pub stdin: FilesInput,
+
+    /// Whether to print how long each part of the listing took to stderr.
+    pub debug_timing: bool,
+
+    /// How long to wait for a filesystem call before giving up on it, if
+    /// there’s a limit at all.
+    pub io_timeout: Option<Duration>,
+
+    /// Whether arguments like `sftp://host/path` list files on other
+    /// computers, rather than being taken as local paths.
+    pub sftp: bool,
+
+    /// Whether to look up the metadata of large directories’ files in
+    /// batches with `io_uring`, where the build and the kernel allow it.
+    pub io_uring: bool,
+
+    /// Whether to print the file name colours as an `LS_COLORS` string,
+    /// rather than listing anything.
+    pub print_ls_colors: bool,
+
+    /// The directories that `@` arguments can name.
+    pub bookmarks: Bookmarks,
+
+    /// Where to look up `z:` arguments, if they should be looked up.
+    pub frecency: Option<Frecency>,
+
+    /// Whether to leave out arguments that are the same path as an earlier
+    /// one.
+    pub unique: bool,
+
+    /// The name of the program to look for along `$PATH`, listing each one
+    /// found instead of the paths given.
+    pub which: Option<OsString>,
+
+    /// The code to exit with if `--security-audit` flags any files, rather
+    /// than the usual one.
+    pub audit_exit_code: Option<i32>,
+
+    /// What to do when the listed files change, if they should be listed
+    /// again when they do.
+    pub watch: Option<watch::Options>,
+
+    /// Whether to save the files’ details to a file instead of listing
+    /// them, or to list the ones saved in a file instead of any paths.
+    pub listing_snapshot: Option<ListingSnapshot>,
+}
+
+/// Where a listing gets saved to or loaded from, with `--snapshot-out` and
+/// `--snapshot-in`.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum ListingSnapshot {
+    /// Read the files and save their details to this file.
+    Save(PathBuf),
+
+    /// List the files whose details were saved to this file.
+    Load(PathBuf),
 }
 
 impl Options {
@@ -157,7 +229,46 @@ impl Options {
             return OptionsResult::Version(version);
         }
 
+        match Completions::deduce(&flags) {
+            Ok(Some(completions)) => return OptionsResult::Completions(completions),
+            Ok(None) => {}
+            Err(oe) => return OptionsResult::InvalidOptions(oe),
+        }
+
         match Self::deduce(&flags, vars) {
+            Ok(options) if options.view.compare && frees.len() != 2 => {
+                OptionsResult::InvalidOptions(OptionsError::CompareNeedsTwo(frees.len()))
+            }
+            Ok(options) if options.which.is_some() && !frees.is_empty() => {
+                OptionsResult::InvalidOptions(OptionsError::WhichWithPaths)
+            }
+            Ok(options)
+                if matches!(options.listing_snapshot, Some(ListingSnapshot::Load(_)))
+                    && !frees.is_empty() =>
+            {
+                OptionsResult::InvalidOptions(OptionsError::SnapshotWithPaths)
+            }
+            Ok(options) if matches!(options.listing_snapshot, Some(ListingSnapshot::Load(_))) => {
+                match flags.last_where_any(|flag| {
+                    LOCAL_FLAGS
+                        .iter()
+                        .chain(SNAPSHOT_FLAGS)
+                        .any(|arg| flag.matches(arg))
+                }) {
+                    Some(flag) => {
+                        OptionsResult::InvalidOptions(OptionsError::SnapshotUnsupported(*flag))
+                    }
+                    None => OptionsResult::Ok(options, frees),
+                }
+            }
+            Ok(options) if options.sftp && frees.iter().any(|f| is_remote(f)) => {
+                match flags.last_where_any(|flag| LOCAL_FLAGS.iter().any(|arg| flag.matches(arg))) {
+                    Some(flag) => {
+                        OptionsResult::InvalidOptions(OptionsError::RemoteUnsupported(*flag))
+                    }
+                    None => OptionsResult::Ok(options, frees),
+                }
+            }
             Ok(options) => OptionsResult::Ok(options, frees),
             Err(oe) => OptionsResult::InvalidOptions(oe),
         }
@@ -167,7 +278,12 @@ impl Options {
     /// status column. It’s only worth trying to discover a repository if the
     /// results will end up being displayed.
     pub fn should_scan_for_git(&self) -> bool {
-        if self.filter.git_ignore == GitIgnore::CheckAndIgnore {
+        if self.filter.git_ignore == GitIgnore::CheckAndIgnore
+            || self.filter.only_modified_git
+            || self.view.stat
+            || self.view.format == OutputFormat::JsonLines
+            || self.view.template.as_ref().is_some_and(Template::uses_git)
+        {
             return true;
         }
 
@@ -183,7 +299,83 @@ impl Options {
                         ..
                     },
                 ..
-            }) => table.columns.git,
+            }) => table.columns.git || table.columns.git_since.is_some(),
+            _ => false,
+        }
+    }
+
+    /// The Git reference that files get compared with, if there’s a column
+    /// to show whether they’ve changed since it.
+    pub fn git_since(&self) -> Option<&str> {
+        match self.view.mode {
+            Mode::Details(details::Options {
+                table: Some(ref table),
+                ..
+            })
+            | Mode::GridDetails(grid_details::Options {
+                details:
+                    details::Options {
+                        table: Some(ref table),
+                        ..
+                    },
+                ..
+            }) => table.columns.git_since.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Which changes inside Git submodules count towards their statuses.
+    pub fn git_submodules(&self) -> SubmoduleIgnore {
+        match self.view.mode {
+            Mode::Details(details::Options {
+                table: Some(ref table),
+                ..
+            })
+            | Mode::GridDetails(grid_details::Options {
+                details:
+                    details::Options {
+                        table: Some(ref table),
+                        ..
+                    },
+                ..
+            }) => table.git_submodules,
+            _ => SubmoduleIgnore::default(),
+        }
+    }
+
+    /// Whether the Git statuses should be queried in the background while
+    /// the rest of the listing gets printed. There’s no point doing this when
+    /// the statuses are needed to filter files out.
+    pub fn should_query_git_in_background(&self) -> bool {
+        if self.filter.git_ignore == GitIgnore::CheckAndIgnore || self.filter.only_modified_git {
+            return false;
+        }
+
+        // An HTML page gets written all at once, so there’s nothing to
+        // redraw once the statuses come in.
+        if self.view.format == OutputFormat::Html {
+            return false;
+        }
+
+        // Nor is there when it goes through a pager, which would show the
+        // codes for moving back up over the table as text.
+        if self.view.paging != Paging::Never {
+            return false;
+        }
+
+        match self.view.mode {
+            Mode::Details(details::Options {
+                table: Some(ref table),
+                ..
+            })
+            | Mode::GridDetails(grid_details::Options {
+                details:
+                    details::Options {
+                        table: Some(ref table),
+                        ..
+                    },
+                ..
+            }) => table.git_async,
             _ => false,
         }
     }
@@ -191,21 +383,38 @@ impl Options {
     /// Determines the complete set of options based on the given command-line
     /// arguments, after they’ve been parsed.
     fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
-        if cfg!(not(feature = "git"))
-            && matches
-                .has_where_any(|f| f.matches(&flags::GIT) || f.matches(&flags::GIT_IGNORE))
-                .is_some()
-        {
+        if cfg!(not(feature = "git")) && matches.get(&flags::GIT)?.is_some() {
             return Err(OptionsError::Unsupported(String::from(
-                "Options --git and --git-ignore can't be used because `git` feature was disabled in this build of exa"
+                "Option --git can't be used because `git` feature was disabled in this build of exa"
             )));
         }
 
         let view = View::deduce(matches, vars)?;
-        let dir_action = DirAction::deduce(matches, matches!(view.mode, Mode::Details(_)))?;
+        let mut dir_action = DirAction::deduce(matches, matches!(view.mode, Mode::Details(_)))?;
+
+        // JSON Lines and templates have no tree to draw, so trees get
+        // listed recursively.
+        if let DirAction::Recurse(ref mut recurse) = dir_action {
+            if view.format.is_per_file() {
+                recurse.tree = false;
+            }
+        }
         let filter = FileFilter::deduce(matches)?;
         let theme = ThemeOptions::deduce(matches, vars)?;
         let stdin = FilesInput::deduce(matches, vars)?;
+        let debug_timing = matches.has(&flags::DEBUG_TIMING)?;
+        let io_timeout = deduce_io_timeout(matches)?;
+        let sftp = deduce_sftp(matches)?;
+        let io_uring = vars.get(vars::EZA_IO_URING).is_some();
+        let print_ls_colors = matches.has(&flags::PRINT_LS_COLORS)?;
+        let bookmarks = Bookmarks::deduce(vars);
+        let frecency = Frecency::deduce(matches, vars)?;
+        let unique = matches.has(&flags::UNIQUE)?;
+        let which = matches.get(&flags::WHICH)?.map(OsStr::to_os_string);
+        let audit_exit_code =
+            deduce_audit_exit_code(matches, view.file_style.security_audit.is_some())?;
+        let watch = deduce_watch(matches, &view)?;
+        let listing_snapshot = deduce_listing_snapshot(matches)?;
 
         Ok(Self {
             dir_action,
@@ -213,10 +422,162 @@ impl Options {
             view,
             theme,
             stdin,
+            debug_timing,
+            io_timeout,
+            sftp,
+            io_uring,
+            print_ls_colors,
+            bookmarks,
+            frecency,
+            unique,
+            which,
+            audit_exit_code,
+            watch,
+            listing_snapshot,
         })
     }
 }
 
+/// The exit code given to `--audit-exit-code`, which has to fit in the byte
+/// that a process gets to exit with.
+fn deduce_audit_exit_code(
+    matches: &MatchedFlags<'_>,
+    security_audit: bool,
+) -> Result<Option<i32>, OptionsError> {
+    let Some(word) = matches.get(&flags::AUDIT_EXIT_CODE)? else {
+        return Ok(None);
+    };
+
+    if matches.is_strict() && !security_audit {
+        return Err(OptionsError::Useless(
+            &flags::AUDIT_EXIT_CODE,
+            false,
+            &flags::SECURITY_AUDIT,
+        ));
+    }
+
+    let word = word.to_string_lossy();
+    match word.parse::<u8>() {
+        Ok(code) => Ok(Some(i32::from(code))),
+        Err(e) => Err(OptionsError::FailedParse(
+            word.into_owned(),
+            NumberSource::Arg(&flags::AUDIT_EXIT_CODE),
+            e,
+        )),
+    }
+}
+
+/// Whether to list the files again when they change, with `--watch`, and
+/// the command to run each time, given to `--exec`. An HTML page only gets
+/// written once the listing is done, which it never is when watching.
+fn deduce_watch(
+    matches: &MatchedFlags<'_>,
+    view: &View,
+) -> Result<Option<watch::Options>, OptionsError> {
+    let exec = matches
+        .get(&flags::EXEC)?
+        .map(|command| command.to_string_lossy().into_owned());
+
+    if !matches.has(&flags::WATCH)? {
+        if matches.is_strict() && exec.is_some() {
+            return Err(OptionsError::Useless(&flags::EXEC, false, &flags::WATCH));
+        }
+        return Ok(None);
+    }
+
+    if view.format == OutputFormat::Html {
+        return Err(OptionsError::Conflict(&flags::WATCH, &flags::FORMAT));
+    }
+
+    Ok(Some(watch::Options { exec }))
+}
+
+/// The options that need the files to be on this computer, looking at more
+/// than their metadata, so files read from elsewhere can’t be listed with.
+#[rustfmt::skip]
+static LOCAL_FLAGS: &[&Arg] = &[
+    &flags::THUMBNAILS, &flags::HARD_LINK_GROUPS, &flags::CLONES, &flags::HIGHLIGHT_NEW,
+    &flags::SECURITY_AUDIT, &flags::AUDIT_EXIT_CODE, &flags::IN_USE, &flags::HYPERLINK, &flags::MOUNTS,
+    &flags::GIT, &flags::GIT_REPOS, &flags::GIT_REPOS_NO_STAT, &flags::GIT_REPOS_BEHIND, &flags::GIT_DETAIL,
+    &flags::GIT_AGE_SINCE, &flags::GIT_IGNORE, &flags::GIT_IGNORE_DEBUG, &flags::ONLY_MODIFIED_GIT,
+    &flags::EXTENDED, &flags::XATTR_VALUES, &flags::SECURITY_CONTEXT, &flags::FILE_FLAGS, &flags::QUOTA,
+    &flags::TAG, &flags::TOTAL_SIZE, &flags::SIZE_ON_DISK, &flags::SHOW_COUNTS, &flags::COMMON_NAMES,
+    &flags::COMPARE, &flags::STAT, &flags::WATCH, &flags::EXEC, &flags::SNAPSHOT_OUT,
+];
+
+/// The options that need more of a file than a saved listing has, on top of
+/// the ones that need the files to be on this computer: what a symlink
+/// points to, other than its type and permissions, and where a file is.
+#[rustfmt::skip]
+static SNAPSHOT_FLAGS: &[&Arg] = &[
+    &flags::DEREF_LINKS, &flags::FOLLOW_SYMLINKS, &flags::ABSOLUTE,
+];
+
+/// Whether `sftp://` arguments list files on other computers, which only
+/// Unix builds can do.
+fn deduce_sftp(matches: &MatchedFlags<'_>) -> Result<bool, OptionsError> {
+    let sftp = matches.has(&flags::SFTP)?;
+    if sftp && cfg!(not(unix)) {
+        return Err(OptionsError::Unsupported(String::from(
+            "Option --sftp can only be used on Unix",
+        )));
+    }
+    Ok(sftp)
+}
+
+/// Whether an argument is an `sftp://` URL, which `--sftp` lists remotely.
+fn is_remote(argument: &OsStr) -> bool {
+    argument.to_string_lossy().starts_with("sftp://")
+}
+
+/// The file to save the listing to or load it from. A saved listing gets
+/// shown in place of the paths, so there’s nothing for `--watch` to watch.
+fn deduce_listing_snapshot(
+    matches: &MatchedFlags<'_>,
+) -> Result<Option<ListingSnapshot>, OptionsError> {
+    let save = matches.get(&flags::SNAPSHOT_OUT)?;
+    let load = matches.get(&flags::SNAPSHOT_IN)?;
+
+    let (flag, snapshot) = match (save, load) {
+        (None, None) => return Ok(None),
+        (Some(_), Some(_)) => {
+            return Err(OptionsError::Conflict(
+                &flags::SNAPSHOT_IN,
+                &flags::SNAPSHOT_OUT,
+            ))
+        }
+        (Some(path), None) => (&flags::SNAPSHOT_OUT, ListingSnapshot::Save(path.into())),
+        (None, Some(path)) => (&flags::SNAPSHOT_IN, ListingSnapshot::Load(path.into())),
+    };
+
+    if cfg!(not(unix)) {
+        return Err(OptionsError::Unsupported(format!(
+            "Option {flag} can only be used on Unix"
+        )));
+    }
+
+    if matches.has(&flags::WATCH)? {
+        return Err(OptionsError::Conflict(&flags::WATCH, flag));
+    }
+
+    Ok(Some(snapshot))
+}
+
+/// The number of seconds given to `--io-timeout`, which can have a
+/// fractional part, but has to be more than zero.
+fn deduce_io_timeout(matches: &MatchedFlags<'_>) -> Result<Option<Duration>, OptionsError> {
+    let Some(word) = matches.get(&flags::IO_TIMEOUT)? else {
+        return Ok(None);
+    };
+
+    word.to_str()
+        .and_then(|w| w.parse::<f64>().ok())
+        .filter(|seconds| *seconds > 0.0)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .map(Some)
+        .ok_or_else(|| OptionsError::BadArgument(&flags::IO_TIMEOUT, word.into()))
+}
+
 /// The result of the `Options::parse` function.
 ///
 /// NOTE: We disallow the `large_enum_variant` lint here, because we're not
@@ -240,6 +601,10 @@ pub enum OptionsResult<'args> {
 
     /// One of the arguments was `--version`, so display the version number.
     Version(VersionString),
+
+    /// One of the arguments was `--generate-completions`, so print a
+    /// completion script for that shell.
+    Completions(Completions),
 }
 
 #[cfg(test)]
@@ -260,6 +625,7 @@ pub mod test {
     ///
     /// It returns a vector with one or two elements in.
     /// These elements can then be tested with `assert_eq` or what have you.
+    #[allow(clippy::needless_pass_by_value)]
     pub fn parse_for_test<T, F>(
         inputs: &[&str],
         args: &'static [&'static Arg],
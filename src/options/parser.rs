@@ -443,6 +443,20 @@ impl<'a> MatchedFlags<'a> {
             .map(|tuple| &tuple.0)
     }
 
+    /// Returns the last argument that satisfies the predicate, whether or
+    /// not it was given a value, so flags that can take one can be weighed
+    /// against flags that can’t. Strict mode has no effect.
+    pub fn last_where_any<P>(&self, predicate: P) -> Option<&Flag>
+    where
+        P: Fn(&Flag) -> bool,
+    {
+        self.flags
+            .iter()
+            .rev()
+            .find(|tuple| predicate(&tuple.0))
+            .map(|tuple| &tuple.0)
+    }
+
     // This code could probably be better.
     // Both ‘has’ and ‘get’ immediately begin with a conditional, which makes
     // me think the functionality could be moved to inside Strictness.
@@ -1,23 +1,70 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::*;
+
 use crate::options::parser::MatchedFlags;
 use crate::options::{flags, vars, OptionsError, Vars};
 use crate::output::color_scale::ColorScaleOptions;
-use crate::theme::{Definitions, Options, UseColours};
+use crate::theme::{Background, Definitions, GitSymbols, Options, UseColours};
+
+/// The theme file key that picks the Git column’s symbols, rather than a
+/// colour.
+const GIT_SYMBOLS_KEY: &str = "git-symbols";
+
+/// The definitions read from a theme file, in the order they were written.
+type ThemePairs = Vec<(String, String)>;
 
 impl Options {
     pub fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
         let use_colours = UseColours::deduce(matches, vars)?;
         let colour_scale = ColorScaleOptions::deduce(matches, vars)?;
+        let background = Background::deduce(matches, vars)?;
+
+        // The theme file gets read even when there are no colours, as it
+        // can also pick the Git column’s symbols.
+        let theme = deduce_theme(matches, vars)?;
+        let git_symbols = GitSymbols::deduce(matches, &theme)?;
 
         let definitions = if use_colours == UseColours::Never {
             Definitions::default()
         } else {
-            Definitions::deduce(vars)
+            Definitions::deduce(vars, theme.as_ref())
         };
 
         Ok(Self {
             use_colours,
             colour_scale,
+            background,
             definitions,
+            git_symbols,
+        })
+    }
+}
+
+impl GitSymbols {
+    fn deduce(
+        matches: &MatchedFlags<'_>,
+        theme: &Option<ThemePairs>,
+    ) -> Result<Self, OptionsError> {
+        if matches.has(&flags::GIT_PORCELAIN)? {
+            return Ok(Self::Porcelain);
+        }
+
+        let value = theme
+            .iter()
+            .flatten()
+            .rev()
+            .find(|(key, _)| key == GIT_SYMBOLS_KEY)
+            .map(|(_, value)| value.as_str());
+
+        Ok(match value {
+            Some("porcelain") => Self::Porcelain,
+            Some("eza") | None => Self::Eza,
+            Some(other) => {
+                warn!("Unknown Git symbols {:?} in theme", other);
+                Self::Eza
+            }
         })
     }
 }
@@ -47,16 +94,234 @@ impl UseColours {
     }
 }
 
+impl Background {
+    /// Which background to pick the default colours for. Without one being
+    /// given, `COLORFGBG` gets checked, and if that doesn’t say either, the
+    /// terminal gets asked once it’s known that colours will be shown.
+    fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
+        let word = matches
+            .get_where(|f| f.matches(&flags::COLOR_THEME) || f.matches(&flags::COLOUR_THEME))?;
+
+        match word {
+            Some(word) if word == "dark" => Ok(Self::Dark),
+            Some(word) if word == "light" => Ok(Self::Light),
+            Some(word) if word != "auto" => {
+                Err(OptionsError::BadArgument(&flags::COLOR_THEME, word.into()))
+            }
+            _ => Ok(vars
+                .get(vars::COLORFGBG)
+                .and_then(|value| Self::from_colorfgbg(&value.to_string_lossy()))
+                .unwrap_or(Self::Automatic)),
+        }
+    }
+}
+
 impl Definitions {
-    fn deduce<V: Vars>(vars: &V) -> Self {
+    fn deduce<V: Vars>(vars: &V, theme: Option<&ThemePairs>) -> Self {
         let ls = vars
             .get(vars::LS_COLORS)
             .map(|e| e.to_string_lossy().to_string());
+        let theme = theme.map(|pairs| {
+            pairs
+                .iter()
+                .filter(|(key, _)| key != GIT_SYMBOLS_KEY)
+                .map(|(key, value)| format!("{key}={value}"))
+                .collect::<Vec<_>>()
+                .join(":")
+        });
         let exa = vars
             .get_with_fallback(vars::EZA_COLORS, vars::EXA_COLORS)
             .map(|e| e.to_string_lossy().to_string());
-        Self { ls, exa }
+        Self { ls, theme, exa }
+    }
+}
+
+/// Read the theme picked with `--theme` from the `themes` directory, or
+/// `theme.yml` if there is one and no theme was picked.
+fn deduce_theme<V: Vars>(
+    matches: &MatchedFlags<'_>,
+    vars: &V,
+) -> Result<Option<ThemePairs>, OptionsError> {
+    let config_dir = config_dir(vars);
+
+    let path = match matches.get(&flags::THEME)? {
+        Some(name) => {
+            let path = config_dir
+                .map(|dir| with_default_extension(dir.join("themes").join(name)))
+                .filter(|path| path.is_file());
+
+            match path {
+                Some(path) => path,
+                None => return Err(OptionsError::BadArgument(&flags::THEME, name.into())),
+            }
+        }
+        None => match config_dir.map(|dir| dir.join("theme.yml")) {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(None),
+        },
+    };
+
+    Ok(Some(read_theme(&path, &mut Vec::new())))
+}
+
+/// The directory that theme files and `config.yml` are read from.
+pub(super) fn config_dir<V: Vars>(vars: &V) -> Option<PathBuf> {
+    if let Some(dir) = vars.get(vars::EZA_CONFIG_DIR) {
+        return Some(PathBuf::from(dir));
     }
+
+    vars.get(vars::XDG_CONFIG_HOME)
+        .map(PathBuf::from)
+        .or_else(|| {
+            vars.get(vars::HOME)
+                .map(|home| Path::new(&home).join(".config"))
+        })
+        .map(|dir| dir.join("eza"))
+}
+
+/// Theme names can leave off the `.yml` extension.
+fn with_default_extension(path: PathBuf) -> PathBuf {
+    if path.extension().is_some() {
+        path
+    } else {
+        path.with_extension("yml")
+    }
+}
+
+/// Read the colour definitions out of a theme file, after those of the theme
+/// it extends, so that its own definitions take precedence. The paths of the
+/// files read so far are kept to stop a theme from extending itself.
+fn read_theme(path: &Path, seen: &mut Vec<PathBuf>) -> Vec<(String, String)> {
+    let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    if seen.contains(&canonical) {
+        warn!("Theme {:?} extends itself", path);
+        return Vec::new();
+    }
+    seen.push(canonical);
+
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) => {
+            warn!("Couldn't read theme {:?}: {}", path, e);
+            return Vec::new();
+        }
+    };
+
+    let (extends, pairs) = parse_theme(&contents);
+
+    let mut all = match extends {
+        Some(base) => {
+            let base = path.parent().unwrap_or(Path::new(".")).join(base);
+            read_theme(&with_default_extension(base), seen)
+        }
+        None => Vec::new(),
+    };
+
+    all.extend(pairs);
+    all
+}
+
+/// Parse a theme file, which is a YAML mapping from the same codes and globs
+/// that `EZA_COLORS` takes to their styles, such as `di: "1;34"`. A theme
+/// can name another one to build on with an `extends` key.
+fn parse_theme(contents: &str) -> (Option<String>, Vec<(String, String)>) {
+    let mut extends = None;
+    let mut pairs = Vec::new();
+
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line == "---" {
+            continue;
+        }
+
+        let Some((key, value)) = split_theme_line(line) else {
+            warn!("Skipping theme line {:?}", line);
+            continue;
+        };
+
+        if key == "extends" {
+            extends = Some(value);
+        } else if key.contains(['=', ':']) || value.contains(['=', ':']) {
+            warn!("Skipping theme definition {:?}", line);
+        } else {
+            pairs.push((key, value));
+        }
+    }
+
+    (extends, pairs)
+}
+
+/// Split a `key: value` line, either side of which may be quoted.
+pub(super) fn split_theme_line(line: &str) -> Option<(String, String)> {
+    let (key, rest) = if let quote @ ('"' | '\'') = line.chars().next()? {
+        let (key, rest) = line[1..].split_once(quote)?;
+        (key, rest.trim_start().strip_prefix(':')?)
+    } else {
+        let (key, rest) = line.split_once(':')?;
+        (key.trim_end(), rest)
+    };
+
+    let value = rest.trim();
+    let value = ['"', '\'']
+        .iter()
+        .find_map(|quote| value.strip_prefix(*quote)?.strip_suffix(*quote))
+        .unwrap_or_else(|| value.split(" #").next().unwrap_or(value).trim_end());
+
+    Some((key.to_string(), value.to_string()))
+}
+
+/// The `key: value` pairs in the configuration file’s mapping with the
+/// given name, which can either be written as a block of indented lines
+/// under it, or all on one line, as in `name: {key: value, other: value}`.
+pub(super) fn config_mapping(contents: &str, name: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut in_mapping = false;
+
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" {
+            continue;
+        }
+
+        if line.starts_with([' ', '\t']) {
+            if in_mapping {
+                pairs.extend(split_mapping_pair(trimmed, name));
+            }
+            continue;
+        }
+
+        in_mapping = false;
+        match split_theme_line(trimmed) {
+            Some((key, value)) if key == name => {
+                if let Some(inner) = value.strip_prefix('{').and_then(|v| v.strip_suffix('}')) {
+                    pairs.extend(
+                        inner
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|pair| !pair.is_empty())
+                            .filter_map(|pair| split_mapping_pair(pair, name)),
+                    );
+                } else if value.is_empty() || value.starts_with('#') {
+                    in_mapping = true;
+                } else {
+                    warn!("Skipping {} {:?}", name, value);
+                }
+            }
+            Some(_) => {}
+            None => warn!("Skipping configuration line {:?}", trimmed),
+        }
+    }
+
+    pairs
+}
+
+/// Split one `key: value` pair inside a mapping.
+fn split_mapping_pair(pair: &str, name: &str) -> Option<(String, String)> {
+    let split = split_theme_line(pair);
+    if split.is_none() {
+        warn!("Skipping {} line {:?}", name, pair);
+    }
+    split
 }
 
 #[cfg(test)]
@@ -74,6 +339,9 @@ mod terminal_test {
         &flags::COLOUR,
         &flags::COLOR_SCALE,
         &flags::COLOUR_SCALE,
+        &flags::COLOR_THEME,
+        &flags::COLOUR_THEME,
+        &flags::GIT_PORCELAIN,
     ];
 
     #[allow(unused_macro_rules)]
@@ -129,6 +397,7 @@ mod terminal_test {
         ls: &'static str,
         exa: &'static str,
         no_color: &'static str,
+        colorfgbg: &'static str,
     }
 
     impl MockVars {
@@ -137,6 +406,7 @@ mod terminal_test {
                 ls: "",
                 exa: "",
                 no_color: "",
+                colorfgbg: "",
             }
         }
         fn with_no_color() -> MockVars {
@@ -144,6 +414,15 @@ mod terminal_test {
                 ls: "",
                 exa: "",
                 no_color: "true",
+                colorfgbg: "",
+            }
+        }
+        fn with_colorfgbg(colorfgbg: &'static str) -> MockVars {
+            MockVars {
+                ls: "",
+                exa: "",
+                no_color: "",
+                colorfgbg,
             }
         }
     }
@@ -158,6 +437,8 @@ mod terminal_test {
                 Some(OsString::from(self.exa))
             } else if name == vars::NO_COLOR && !self.no_color.is_empty() {
                 Some(OsString::from(self.no_color))
+            } else if name == vars::COLORFGBG && !self.colorfgbg.is_empty() {
+                Some(OsString::from(self.colorfgbg))
             } else {
                 None
             }
@@ -192,4 +473,70 @@ mod terminal_test {
     test!(overridden_6:  UseColours <- ["--color=auto",  "--colour=never"], MockVars::empty();  Complain => err OptionsError::Duplicate(Flag::Long("color"),  Flag::Long("colour")));
     test!(overridden_7:  UseColours <- ["--colour=auto", "--color=never"], MockVars::empty();   Complain => err OptionsError::Duplicate(Flag::Long("colour"), Flag::Long("color")));
     test!(overridden_8:  UseColours <- ["--color=auto",  "--color=never"], MockVars::empty();   Complain => err OptionsError::Duplicate(Flag::Long("color"),  Flag::Long("color")));
+
+    // --colour-theme
+    test!(bg_empty:      Background <- [], MockVars::empty();                                Both => Ok(Background::Automatic));
+    test!(bg_dark:       Background <- ["--colour-theme=dark"], MockVars::empty();           Both => Ok(Background::Dark));
+    test!(bg_light:      Background <- ["--color-theme", "light"], MockVars::empty();        Both => Ok(Background::Light));
+    test!(bg_fgbg:       Background <- [], MockVars::with_colorfgbg("0;15");                  Both => Ok(Background::Light));
+    test!(bg_auto_fgbg:  Background <- ["--color-theme=auto"], MockVars::with_colorfgbg("15;0"); Both => Ok(Background::Dark));
+    test!(bg_flag_fgbg:  Background <- ["--color-theme=dark"], MockVars::with_colorfgbg("0;15"); Both => Ok(Background::Dark));
+    test!(bg_error:      Background <- ["--colour-theme=sepia"], MockVars::empty();          Both => err OptionsError::BadArgument(&flags::COLOR_THEME, OsString::from("sepia")));
+
+    fn theme_with(value: &str) -> ThemePairs {
+        vec![
+            (String::from("di"), String::from("34")),
+            (String::from(GIT_SYMBOLS_KEY), String::from(value)),
+        ]
+    }
+
+    // Git symbols
+    test!(git_eza:             GitSymbols <- [], None;                                                         Both => Ok(GitSymbols::Eza));
+    test!(git_flag:            GitSymbols <- ["--git-status-porcelain-compat"], None;                          Both => Ok(GitSymbols::Porcelain));
+    test!(git_theme:           GitSymbols <- [], Some(theme_with("porcelain"));                                Both => Ok(GitSymbols::Porcelain));
+    test!(git_theme_unknown:   GitSymbols <- [], Some(theme_with("emoji"));                                    Both => Ok(GitSymbols::Eza));
+    test!(git_flag_over_theme: GitSymbols <- ["--git-status-porcelain-compat"], Some(theme_with("eza"));      Both => Ok(GitSymbols::Porcelain));
+}
+
+#[cfg(test)]
+mod theme_file_test {
+    use super::*;
+
+    fn pair(key: &str, value: &str) -> (String, String) {
+        (key.to_string(), value.to_string())
+    }
+
+    #[test]
+    fn plain_and_quoted() {
+        let (extends, pairs) = parse_theme("di: 1;34\n\"*.rs\": \"38;5;208\"\n'ur': '33'\n");
+        assert_eq!(extends, None);
+        assert_eq!(
+            pairs,
+            vec![
+                pair("di", "1;34"),
+                pair("*.rs", "38;5;208"),
+                pair("ur", "33")
+            ]
+        );
+    }
+
+    #[test]
+    fn extends_and_comments() {
+        let contents = "---\n# Darker colours\nextends: base.yml\n\nda: 2  # dates\n";
+        let (extends, pairs) = parse_theme(contents);
+        assert_eq!(extends, Some(String::from("base.yml")));
+        assert_eq!(pairs, vec![pair("da", "2")]);
+    }
+
+    #[test]
+    fn priority_globs_need_quotes() {
+        let (_, pairs) = parse_theme("\"**/build/*#10\": 38;5;240\n");
+        assert_eq!(pairs, vec![pair("**/build/*#10", "38;5;240")]);
+    }
+
+    #[test]
+    fn unusable_lines() {
+        let (_, pairs) = parse_theme("just some words\n\"di=1\": 34\nfi: \"3:4\"\n");
+        assert!(pairs.is_empty());
+    }
 }
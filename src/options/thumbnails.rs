@@ -0,0 +1,115 @@
+use crate::options::parser::MatchedFlags;
+use crate::options::vars::{self, Vars};
+use crate::options::{file_name, flags, OptionsError};
+use crate::output::thumbnails::{Options, Protocol};
+
+impl Options {
+    /// Determines how previews should be drawn, and where they get kept.
+    pub fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
+        let protocol = match matches.get(&flags::THUMBNAILS)? {
+            None => Protocol::detect(vars),
+            Some(word) => match word.to_str() {
+                Some("auto") => Protocol::detect(vars),
+                Some("kitty") => Protocol::Kitty,
+                Some("sixel") => Protocol::Sixel,
+                Some("symbols") => Protocol::Symbols,
+                _ => return Err(OptionsError::BadArgument(&flags::THUMBNAILS, word.into())),
+            },
+        };
+
+        let cache = file_name::cache_dir(vars).map(|dir| dir.join("thumbnails"));
+        Ok(Self { protocol, cache })
+    }
+}
+
+impl Protocol {
+    /// Guesses which protocol the terminal understands from the variables
+    /// it sets, falling back to characters, which work everywhere.
+    fn detect<V: Vars>(vars: &V) -> Self {
+        let term = vars.get(vars::TERM).unwrap_or_default();
+        let term = term.to_string_lossy();
+        let program = vars.get(vars::TERM_PROGRAM).unwrap_or_default();
+
+        if matches!(&*term, "xterm-kitty" | "xterm-ghostty" | "wezterm")
+            || program == "WezTerm"
+            || program == "ghostty"
+        {
+            Self::Kitty
+        } else if matches!(&*term, "foot" | "foot-extra" | "mlterm" | "contour")
+            || term.contains("sixel")
+            || program == "iTerm.app"
+        {
+            Self::Sixel
+        } else {
+            Self::Symbols
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::options::parser::Arg;
+    use crate::options::test::parse_for_test;
+    use crate::options::test::Strictnesses::*;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    static TEST_ARGS: &[&Arg] = &[&flags::THUMBNAILS];
+
+    struct MockVars {
+        term: &'static str,
+        program: &'static str,
+    }
+
+    impl Vars for MockVars {
+        fn get(&self, name: &'static str) -> Option<OsString> {
+            match name {
+                "TERM" if !self.term.is_empty() => Some(OsString::from(self.term)),
+                "TERM_PROGRAM" if !self.program.is_empty() => Some(OsString::from(self.program)),
+                "HOME" => Some(OsString::from("/home/me")),
+                _ => None,
+            }
+        }
+    }
+
+    macro_rules! test {
+        ($name:ident: $inputs:expr, $term:expr, $program:expr; $stricts:expr => $result:expr) => {
+            #[test]
+            fn $name() {
+                let vars = MockVars {
+                    term: $term,
+                    program: $program,
+                };
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| {
+                    Options::deduce(mf, &vars).map(|options| options.protocol)
+                }) {
+                    assert_eq!(result, $result);
+                }
+            }
+        };
+    }
+
+    test!(kitty:     ["--thumbnails"], "xterm-kitty", "";          Both => Ok(Protocol::Kitty));
+    test!(wezterm:   ["--thumbnails"], "xterm-256color", "WezTerm"; Both => Ok(Protocol::Kitty));
+    test!(foot:      ["--thumbnails=auto"], "foot", "";            Both => Ok(Protocol::Sixel));
+    test!(unknown:   ["--thumbnails"], "xterm-256color", "";       Both => Ok(Protocol::Symbols));
+    test!(forced:    ["--thumbnails=sixel"], "xterm-kitty", "";    Both => Ok(Protocol::Sixel));
+    test!(bad:       ["--thumbnails=ascii"], "", "";               Both => Err(OptionsError::BadArgument(&flags::THUMBNAILS, OsString::from("ascii"))));
+
+    #[test]
+    fn cache() {
+        let vars = MockVars {
+            term: "",
+            program: "",
+        };
+        for result in parse_for_test(["--thumbnails"].as_ref(), TEST_ARGS, Both, |mf| {
+            Options::deduce(mf, &vars)
+        }) {
+            assert_eq!(
+                result.unwrap().cache,
+                Some(PathBuf::from("/home/me/.cache/eza/thumbnails"))
+            );
+        }
+    }
+}
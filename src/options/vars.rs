@@ -11,9 +11,47 @@ pub static LS_COLORS: &str = "LS_COLORS";
 /// characters.
 pub static COLUMNS: &str = "COLUMNS";
 
+/// Environment variable used to override the height of the terminal, in
+/// lines.
+pub static LINES: &str = "LINES";
+
 /// Environment variable used to datetime format.
 pub static TIME_STYLE: &str = "TIME_STYLE";
 
+/// Environment variables used, in order of precedence, to pick the locale
+/// that month names and date ordering are taken from.
+pub static LC_ALL: &str = "LC_ALL";
+pub static LC_TIME: &str = "LC_TIME";
+pub static LANG: &str = "LANG";
+
+/// Environment variable used, after `LC_ALL` and before `LANG`, to pick the
+/// locale that `--sort=name-natural` collates names in.
+pub static LC_COLLATE: &str = "LC_COLLATE";
+
+/// Environment variables that describe the terminal, used to guess whether
+/// it can show icons when they’re set to `auto`, and how it can be made to
+/// show images for `--thumbnails`.
+pub static TERM: &str = "TERM";
+pub static TERM_PROGRAM: &str = "TERM_PROGRAM";
+
+/// Environment variable that terminals which can show 24-bit colours set to
+/// `truecolor` or `24bit`, used to pick the colour depth when it isn’t
+/// given with `--color-depth`.
+pub static COLORTERM: &str = "COLORTERM";
+
+/// Environment variable that some terminals set to the numbers of their
+/// foreground and background colours, used to tell whether the background
+/// is dark or light when `--color-theme` is `auto`.
+pub static COLORFGBG: &str = "COLORFGBG";
+
+/// Environment variable used to pick the pager that `--paging` sends the
+/// output through.
+pub static PAGER: &str = "PAGER";
+
+/// Environment variable holding the options for `less`, which gets told to
+/// pass colours through when it isn’t set.
+pub static LESS: &str = "LESS";
+
 /// Environment variable used to disable colors.
 /// See: <https://no-color.org/>
 pub static NO_COLOR: &str = "NO_COLOR";
@@ -60,10 +98,54 @@ pub static EZA_OVERRIDE_GIT: &str = "EZA_OVERRIDE_GIT";
 pub static EXA_MIN_LUMINANCE: &str = "EXA_MIN_LUMINANCE";
 pub static EZA_MIN_LUMINANCE: &str = "EZA_MIN_LUMINANCE";
 
+/// Environment variable used to set the sizes that `--no-color-scale-floor`
+/// colours sizes between, as four comma-separated sizes such as `1k,1M,1G,1T`.
+pub static EZA_COLOR_SCALE_SIZES: &str = "EZA_COLOR_SCALE_SIZES";
+
 /// Environment variable used to automate the same behavior as `--icons=auto` if set.
 /// Any explicit use of `--icons=WHEN` overrides this behavior.
 pub static EZA_ICONS_AUTO: &str = "EZA_ICONS_AUTO";
 
+/// Environment variable used to make `--icons=auto` ask the terminal how wide
+/// it draws an icon before deciding whether to show them.
+pub static EZA_ICONS_PROBE: &str = "EZA_ICONS_PROBE";
+
+/// Environment variable used to look up the metadata of large directories’
+/// files in batches with `io_uring`, in builds with the `io-uring` feature.
+pub static EZA_IO_URING: &str = "EZA_IO_URING";
+
+/// Environment variable used to pick the directory that `theme.yml`,
+/// `config.yml`, and the `themes` directory are read from, instead of
+/// `$XDG_CONFIG_HOME/eza`.
+pub static EZA_CONFIG_DIR: &str = "EZA_CONFIG_DIR";
+
+/// Environment variables used to find the configuration directory when
+/// `EZA_CONFIG_DIR` isn’t set.
+pub static XDG_CONFIG_HOME: &str = "XDG_CONFIG_HOME";
+pub static HOME: &str = "HOME";
+
+/// Environment variable used to find where `--frecency` keeps its list of
+/// directories, falling back to `~/.local/share` when it isn’t set.
+pub static XDG_DATA_HOME: &str = "XDG_DATA_HOME";
+
+/// Environment variable used to find where `--highlight-new` keeps its
+/// snapshots of each directory, falling back to `~/.cache` when it isn’t set.
+pub static XDG_CACHE_HOME: &str = "XDG_CACHE_HOME";
+
+/// Environment variable naming a temporary directory, which
+/// `--security-audit` checks for the sticky bit alongside `/tmp`.
+pub static TMPDIR: &str = "TMPDIR";
+
+/// Environment variable used to find the directories that `--which` looks
+/// through for programs.
+pub static PATH: &str = "PATH";
+
+/// Environment variables used to set the templates for the line printed
+/// before and after each directory’s files, when `--dir-header` and
+/// `--dir-footer` aren’t given.
+pub static EZA_DIR_HEADER: &str = "EZA_DIR_HEADER";
+pub static EZA_DIR_FOOTER: &str = "EZA_DIR_FOOTER";
+
 pub static EZA_STDIN_SEPARATOR: &str = "EZA_STDIN_SEPARATOR";
 
 /// Environment variable used to choose how windows attributes are displayed.
@@ -147,7 +229,6 @@ impl MockVars {
             "COLUMNS" => self.columns = value.clone(),
             "NO_COLOR" => self.no_colors = value.clone(),
             _ => (),
-        };
-        ()
+        }
     }
 }
@@ -1,32 +1,265 @@
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
+use std::num::NonZeroUsize;
+
+use log::*;
 
 use crate::fs::feature::xattr;
-use crate::options::parser::MatchedFlags;
+use crate::fs::fields::SubmoduleIgnore;
+use crate::options::filter::parse_size;
+use crate::options::parser::{Arg, MatchedFlags};
 use crate::options::{flags, vars, NumberSource, OptionsError, Vars};
-use crate::output::color_scale::{ColorScaleMode, ColorScaleOptions};
+use crate::output::color_scale::{ColorDepth, ColorScaleMode, ColorScaleOptions, SizeThresholds};
+use crate::output::details::SubtreeSize;
 use crate::output::file_name::Options as FileStyle;
 use crate::output::grid_details::{self, RowThreshold};
+use crate::output::group::{self, Bucket};
+use crate::output::section::{self, Template};
+use crate::output::summary::{self, SummarySort};
+use crate::output::table::TimeType;
 use crate::output::table::{
-    Columns, FlagsFormat, GroupFormat, Options as TableOptions, SizeFormat, TimeTypes, UserFormat,
+    Column, Columns, FlagsFormat, GroupFormat, HeaderLabels, Options as TableOptions, SizeFormat,
+    TimeTypes, UserFormat,
 };
 use crate::output::time::TimeFormat;
-use crate::output::{details, grid, Mode, TerminalWidth, View};
+use crate::output::tree::TreeStyle;
+use crate::output::{
+    details, grid, json, template, thumbnails, Mode, OutputFormat, Paging, TerminalHeight,
+    TerminalWidth, View,
+};
 
 impl View {
     pub fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
         let mode = Mode::deduce(matches, vars)?;
         let deref_links = matches.has(&flags::DEREF_LINKS)?;
+        let keep_link_owners = matches.has(&flags::NO_DEREF_OWNER)?;
+        if matches.is_strict() && keep_link_owners && !deref_links {
+            return Err(OptionsError::Useless(
+                &flags::NO_DEREF_OWNER,
+                false,
+                &flags::DEREF_LINKS,
+            ));
+        }
         let total_size = matches.has(&flags::TOTAL_SIZE)?;
+        if matches.is_strict() && !total_size && matches.has(&flags::SIZE_ON_DISK)? {
+            return Err(OptionsError::Useless(
+                &flags::SIZE_ON_DISK,
+                false,
+                &flags::TOTAL_SIZE,
+            ));
+        }
+        if matches.is_strict() && !matches.has(&flags::TREE)? {
+            for flag in [&flags::SHOW_COUNTS, &flags::PRUNE] {
+                if matches.has(flag)? {
+                    return Err(OptionsError::Useless(flag, false, &flags::TREE));
+                }
+            }
+            for flag in [&flags::TREE_STYLE, &flags::DEPTH_GUIDES] {
+                if matches.get(flag)?.is_some() {
+                    return Err(OptionsError::Useless(flag, false, &flags::TREE));
+                }
+            }
+        }
+        if matches.is_strict() && matches.has(&flags::ALIGN_SECTIONS)? {
+            if !matches.has(&flags::RECURSE)? {
+                return Err(OptionsError::Useless(
+                    &flags::ALIGN_SECTIONS,
+                    false,
+                    &flags::RECURSE,
+                ));
+            } else if matches.has(&flags::TREE)? {
+                return Err(OptionsError::Useless(
+                    &flags::ALIGN_SECTIONS,
+                    true,
+                    &flags::TREE,
+                ));
+            }
+        }
+        let total = matches.has(&flags::TOTAL)?;
+        let compare = matches.has(&flags::COMPARE)?;
+        if matches.is_strict() && compare {
+            for flag in [&flags::RECURSE, &flags::TREE] {
+                if matches.has(flag)? {
+                    return Err(OptionsError::Useless(flag, true, &flags::COMPARE));
+                }
+            }
+        }
+        let stat = matches.has(&flags::STAT)?;
+        if matches.is_strict() && stat {
+            for flag in [&flags::RECURSE, &flags::TREE, &flags::COMPARE] {
+                if matches.has(flag)? {
+                    return Err(OptionsError::Useless(flag, true, &flags::STAT));
+                }
+            }
+        }
+        let paging = Paging::deduce(matches, vars)?;
+        let format = OutputFormat::deduce(matches)?;
+        let json = json::Options::deduce(matches)?;
+        let template = deduce_format_template(matches)?;
+        let null_terminated = matches!(mode, Mode::Lines) && matches.has(&flags::NULL)?;
         let width = TerminalWidth::deduce(matches, vars)?;
         let file_style = FileStyle::deduce(matches, vars, width.actual_terminal_width().is_some())?;
+        let sections = section::Options::deduce(matches, vars)?;
+        let summary = summary::Options::deduce(matches)?;
         Ok(Self {
             mode,
             width,
             file_style,
             deref_links,
+            keep_link_owners,
             total_size,
+            total,
+            compare,
+            stat,
+            paging,
+            format,
+            json,
+            template,
+            null_terminated,
+            sections,
+            summary,
+        })
+    }
+}
+
+impl json::Options {
+    /// Determines which fields to write from the comma-separated names given
+    /// to `--fields`, which only does anything for JSON Lines.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        let Some(list) = matches.get(&flags::FIELDS)? else {
+            return Ok(Self::default());
+        };
+
+        if matches.is_strict() && OutputFormat::deduce(matches)? != OutputFormat::JsonLines {
+            return Err(OptionsError::Useless(&flags::FIELDS, false, &flags::FORMAT));
+        }
+
+        let mut fields = Vec::new();
+        for name in list.to_string_lossy().split(',') {
+            let field = json::Field::from_name(name)
+                .ok_or_else(|| OptionsError::BadArgument(&flags::FIELDS, name.into()))?;
+            if !fields.contains(&field) {
+                fields.push(field);
+            }
+        }
+
+        Ok(Self { fields })
+    }
+}
+
+impl summary::Options {
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Option<Self>, OptionsError> {
+        let sort = SummarySort::deduce(matches)?;
+
+        let Some(word) = matches.get(&flags::SUMMARY)? else {
+            if matches.is_strict() && sort.is_some() {
+                return Err(OptionsError::Useless(
+                    &flags::SUMMARY_SORT,
+                    false,
+                    &flags::SUMMARY,
+                ));
+            }
+            return Ok(None);
+        };
+
+        if word != "ext" {
+            return Err(OptionsError::BadArgument(&flags::SUMMARY, word.into()));
+        }
+
+        if matches.is_strict() && matches.has(&flags::TREE)? {
+            return Err(OptionsError::Useless(&flags::TREE, true, &flags::SUMMARY));
+        }
+
+        Ok(Some(Self {
+            sort: sort.unwrap_or_default(),
+        }))
+    }
+}
+
+impl SummarySort {
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Option<Self>, OptionsError> {
+        let Some(word) = matches.get(&flags::SUMMARY_SORT)? else {
+            return Ok(None);
+        };
+
+        match word.to_str() {
+            Some("ext") => Ok(Some(Self::Extension)),
+            Some("count") => Ok(Some(Self::Count)),
+            Some("size") => Ok(Some(Self::Size)),
+            Some("largest") => Ok(Some(Self::Largest)),
+            _ => Err(OptionsError::BadArgument(&flags::SUMMARY_SORT, word.into())),
+        }
+    }
+}
+
+impl section::Options {
+    fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
+        Ok(Self {
+            header: Self::deduce_template(matches, vars, &flags::DIR_HEADER, vars::EZA_DIR_HEADER)?,
+            footer: Self::deduce_template(matches, vars, &flags::DIR_FOOTER, vars::EZA_DIR_FOOTER)?,
         })
     }
+
+    /// The template given with the flag, or in the environment variable if
+    /// the flag wasn’t given and the variable isn’t empty.
+    fn deduce_template<V: Vars>(
+        matches: &MatchedFlags<'_>,
+        vars: &V,
+        flag: &'static Arg,
+        var: &'static str,
+    ) -> Result<Option<Template>, OptionsError> {
+        let (input, source) = if let Some(input) = matches.get(flag)? {
+            (input.to_owned(), NumberSource::Arg(flag))
+        } else {
+            match vars.get(var) {
+                Some(input) if !input.is_empty() => (input, NumberSource::Env(var)),
+                _ => return Ok(None),
+            }
+        };
+
+        Template::parse(&input.to_string_lossy())
+            .map(Some)
+            .map_err(|e| OptionsError::FailedTemplate(source, e))
+    }
+}
+
+/// The template given to `--format-template`, if any.
+fn deduce_format_template(
+    matches: &MatchedFlags<'_>,
+) -> Result<Option<template::Template>, OptionsError> {
+    let Some(input) = matches.get(&flags::FORMAT_TEMPLATE)? else {
+        return Ok(None);
+    };
+
+    template::Template::parse(&input.to_string_lossy())
+        .map(Some)
+        .map_err(OptionsError::FailedFormatTemplate)
+}
+
+impl OutputFormat {
+    /// Determines the format from `--format`, or from `--format-template`,
+    /// which can’t be given along with it.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        if matches.get(&flags::FORMAT_TEMPLATE)?.is_some() {
+            if matches.get(&flags::FORMAT)?.is_some() {
+                return Err(OptionsError::Conflict(
+                    &flags::FORMAT_TEMPLATE,
+                    &flags::FORMAT,
+                ));
+            }
+            return Ok(Self::Template);
+        }
+
+        let Some(word) = matches.get(&flags::FORMAT)? else {
+            return Ok(Self::Text);
+        };
+
+        match word.to_str() {
+            Some("text") => Ok(Self::Text),
+            Some("jsonl") => Ok(Self::JsonLines),
+            Some("html") => Ok(Self::Html),
+            _ => Err(OptionsError::BadArgument(&flags::FORMAT, word.into())),
+        }
+    }
 }
 
 impl Mode {
@@ -39,11 +272,14 @@ impl Mode {
     /// This is complicated a little by the fact that `--grid` and `--tree`
     /// can also combine with `--long`, so care has to be taken to use the
     pub fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
-        let flag = matches.has_where_any(|f| {
+        let flag = matches.last_where_any(|f| {
             f.matches(&flags::LONG)
                 || f.matches(&flags::ONE_LINE)
+                || f.matches(&flags::NULL)
                 || f.matches(&flags::GRID)
                 || f.matches(&flags::TREE)
+                || f.matches(&flags::THUMBNAILS)
+                || f.matches(&flags::WHICH)
         });
 
         let Some(flag) = flag else {
@@ -52,7 +288,10 @@ impl Mode {
             return Ok(Self::Grid(grid));
         };
 
+        // The programs `--which` finds get listed with everything about
+        // them, as telling them apart is the point.
         if flag.matches(&flags::LONG)
+            || flag.matches(&flags::WHICH)
             || (flag.matches(&flags::TREE) && matches.has(&flags::LONG)?)
             || (flag.matches(&flags::GRID) && matches.has(&flags::LONG)?)
         {
@@ -68,6 +307,7 @@ impl Mode {
                 let grid_details = grid_details::Options {
                     details,
                     row_threshold,
+                    grid: grid::Options::deduce(matches)?,
                 };
                 return Ok(Self::GridDetails(grid_details));
             }
@@ -84,11 +324,16 @@ impl Mode {
             return Ok(Self::Details(details));
         }
 
-        if flag.matches(&flags::ONE_LINE) {
+        if flag.matches(&flags::ONE_LINE) || flag.matches(&flags::NULL) {
             let _ = matches.has(&flags::ONE_LINE)?;
             return Ok(Self::Lines);
         }
 
+        if flag.matches(&flags::THUMBNAILS) {
+            let thumbnails = thumbnails::Options::deduce(matches, vars)?;
+            return Ok(Self::Thumbnails(thumbnails));
+        }
+
         let grid = grid::Options::deduce(matches)?;
         Ok(Self::Grid(grid))
     }
@@ -102,19 +347,44 @@ impl Mode {
                 &flags::BYTES,
                 &flags::INODE,
                 &flags::LINKS,
-                &flags::HEADER,
                 &flags::BLOCKSIZE,
                 &flags::TIME,
                 &flags::GROUP,
-                &flags::NUMERIC,
                 &flags::MOUNTS,
+                &flags::HEADER,
+                &flags::HEADER_UNITS,
+                &flags::NUMERIC,
             ] {
                 if matches.has(option)? {
                     return Err(OptionsError::Useless(option, false, &flags::LONG));
                 }
             }
 
-            if matches.has(&flags::GIT)? && !matches.has(&flags::NO_GIT)? {
+            if matches.get(&flags::HEADER_MODE)?.is_some() {
+                return Err(OptionsError::Useless(
+                    &flags::HEADER_MODE,
+                    false,
+                    &flags::LONG,
+                ));
+            }
+
+            if matches.get(&flags::COLUMNS)?.is_some() {
+                return Err(OptionsError::Useless(&flags::COLUMNS, false, &flags::LONG));
+            }
+
+            if matches.get(&flags::NUMERIC_IDS)?.is_some() {
+                return Err(OptionsError::Useless(
+                    &flags::NUMERIC_IDS,
+                    false,
+                    &flags::LONG,
+                ));
+            }
+
+            if matches.get(&flags::GROUP_BY)?.is_some() {
+                return Err(OptionsError::Useless(&flags::GROUP_BY, false, &flags::LONG));
+            }
+
+            if matches.get(&flags::GIT)?.is_some() && !matches.has(&flags::NO_GIT)? {
                 return Err(OptionsError::Useless(&flags::GIT, false, &flags::LONG));
             } else if matches.has(&flags::LEVEL)?
                 && !matches.has(&flags::RECURSE)?
@@ -134,11 +404,21 @@ impl Mode {
 
 impl grid::Options {
     fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
-        let grid = grid::Options {
-            across: matches.has(&flags::ACROSS)?,
+        let across = match matches.get(&flags::GRID_ORDER)? {
+            None => matches.has(&flags::ACROSS)?,
+            Some(word) if word == "rows" => true,
+            Some(word) if word == "columns" => {
+                if matches.is_strict() && matches.has(&flags::ACROSS)? {
+                    return Err(OptionsError::Conflict(&flags::ACROSS, &flags::GRID_ORDER));
+                }
+                false
+            }
+            Some(word) => {
+                return Err(OptionsError::BadArgument(&flags::GRID_ORDER, word.into()));
+            }
         };
 
-        Ok(grid)
+        Ok(grid::Options { across })
     }
 }
 
@@ -147,33 +427,225 @@ impl details::Options {
         let details = details::Options {
             table: None,
             header: false,
+            header_units: false,
+            header_repeat: None,
             xattr: xattr::ENABLED && matches.has(&flags::EXTENDED)?,
+            xattr_values: Self::deduce_xattr_values(matches)?,
             secattr: xattr::ENABLED && matches.has(&flags::SECURITY_CONTEXT)?,
             mounts: matches.has(&flags::MOUNTS)?,
             color_scale: ColorScaleOptions::deduce(matches, vars)?,
+            subtree_size: Self::deduce_subtree_size(matches)?,
+            show_counts: matches.has(&flags::SHOW_COUNTS)?,
+            prune: matches.has(&flags::PRUNE)?,
+            tree_style: TreeStyle::deduce(matches)?,
+            depth_guides: Self::deduce_depth_guides(matches)?,
+            align_sections: false,
+            group_by: None,
         };
 
         Ok(details)
     }
 
+    /// Directories in a tree get their total size shown after their names
+    /// when `--total-size` is given, measured in disk usage if
+    /// `--size-on-disk` is given too.
+    fn deduce_subtree_size(
+        matches: &MatchedFlags<'_>,
+    ) -> Result<Option<SubtreeSize>, OptionsError> {
+        if !matches.has(&flags::TOTAL_SIZE)? {
+            Ok(None)
+        } else if matches.has(&flags::SIZE_ON_DISK)? {
+            Ok(Some(SubtreeSize::OnDisk))
+        } else {
+            Ok(Some(SubtreeSize::Apparent))
+        }
+    }
+
     fn deduce_long<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
         if matches.is_strict() {
             if matches.has(&flags::ACROSS)? && !matches.has(&flags::GRID)? {
                 return Err(OptionsError::Useless(&flags::ACROSS, true, &flags::LONG));
+            } else if matches.get(&flags::GRID_ORDER)?.is_some() && !matches.has(&flags::GRID)? {
+                return Err(OptionsError::Useless(
+                    &flags::GRID_ORDER,
+                    true,
+                    &flags::LONG,
+                ));
             } else if matches.has(&flags::ONE_LINE)? {
                 return Err(OptionsError::Useless(&flags::ONE_LINE, true, &flags::LONG));
+            } else if matches.has(&flags::NULL)? {
+                return Err(OptionsError::Useless(&flags::NULL, true, &flags::LONG));
             }
         }
 
         Ok(details::Options {
             table: Some(TableOptions::deduce(matches, vars)?),
-            header: matches.has(&flags::HEADER)?,
+            header: matches.has(&flags::HEADER)?
+                || matches.has(&flags::HEADER_UNITS)?
+                || matches.get(&flags::HEADER_MODE)?.is_some(),
+            header_units: matches.has(&flags::HEADER_UNITS)?,
+            header_repeat: Self::deduce_header_repeat(matches, vars)?,
             xattr: xattr::ENABLED && matches.has(&flags::EXTENDED)?,
+            xattr_values: Self::deduce_xattr_values(matches)?,
             secattr: xattr::ENABLED && matches.has(&flags::SECURITY_CONTEXT)?,
             mounts: matches.has(&flags::MOUNTS)?,
             color_scale: ColorScaleOptions::deduce(matches, vars)?,
+            subtree_size: None,
+            show_counts: matches.has(&flags::TREE)? && matches.has(&flags::SHOW_COUNTS)?,
+            prune: matches.has(&flags::TREE)? && matches.has(&flags::PRUNE)?,
+            tree_style: TreeStyle::deduce(matches)?,
+            depth_guides: if matches.has(&flags::TREE)? {
+                Self::deduce_depth_guides(matches)?
+            } else {
+                None
+            },
+            align_sections: matches.has(&flags::RECURSE)?
+                && !matches.has(&flags::TREE)?
+                && matches.has(&flags::ALIGN_SECTIONS)?,
+            group_by: group::Options::deduce(matches)?,
         })
     }
+
+    /// How many levels apart a tree’s branch lines get numbered with their
+    /// depths, if they get numbered at all.
+    fn deduce_depth_guides(
+        matches: &MatchedFlags<'_>,
+    ) -> Result<Option<NonZeroUsize>, OptionsError> {
+        let Some(word) = matches.get(&flags::DEPTH_GUIDES)? else {
+            return Ok(None);
+        };
+
+        let arg_str = word.to_string_lossy();
+        match arg_str.parse() {
+            Ok(every) => Ok(Some(every)),
+            Err(e) => {
+                let source = NumberSource::Arg(&flags::DEPTH_GUIDES);
+                Err(OptionsError::FailedParse(arg_str.to_string(), source, e))
+            }
+        }
+    }
+
+    /// How many characters of each extended attribute’s value to preview,
+    /// if they should be previewed at all.
+    fn deduce_xattr_values(matches: &MatchedFlags<'_>) -> Result<Option<usize>, OptionsError> {
+        let Some(word) = matches.get(&flags::XATTR_VALUES)? else {
+            return Ok(None);
+        };
+
+        if matches.is_strict() && !matches.has(&flags::EXTENDED)? {
+            return Err(OptionsError::Useless(
+                &flags::XATTR_VALUES,
+                false,
+                &flags::EXTENDED,
+            ));
+        }
+
+        let arg_str = word.to_string_lossy();
+        match arg_str.parse() {
+            Ok(limit) => Ok(Some(limit)),
+            Err(e) => {
+                let source = NumberSource::Arg(&flags::XATTR_VALUES);
+                Err(OptionsError::FailedParse(arg_str.to_string(), source, e))
+            }
+        }
+    }
+
+    fn deduce_header_repeat<V: Vars>(
+        matches: &MatchedFlags<'_>,
+        vars: &V,
+    ) -> Result<Option<TerminalHeight>, OptionsError> {
+        let Some(word) = matches.get(&flags::HEADER_MODE)? else {
+            return Ok(None);
+        };
+
+        if word == "once" {
+            Ok(None)
+        } else if word == "repeat" {
+            TerminalHeight::deduce(vars).map(Some)
+        } else {
+            Err(OptionsError::BadArgument(&flags::HEADER_MODE, word.into()))
+        }
+    }
+}
+
+impl group::Options {
+    /// Files only get split into sections in a long view, where the
+    /// sections are of a single directory’s files, rather than in a tree,
+    /// where they’d have to be split up from their directories, or in a
+    /// grid, where there are no lines to put the headings on.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Option<Self>, OptionsError> {
+        let buckets = Self::deduce_buckets(matches)?;
+
+        let group_by = match matches.get(&flags::GROUP_BY)? {
+            None => None,
+            Some(word) => match word.to_str() {
+                Some("none") => None,
+                Some("mtime-bucket") => Some(Self {
+                    buckets: buckets.unwrap_or_else(|| group::DEFAULT_BUCKETS.to_vec()),
+                }),
+                _ => return Err(OptionsError::BadArgument(&flags::GROUP_BY, word.into())),
+            },
+        };
+
+        if matches.is_strict() {
+            if group_by.is_none() && matches.get(&flags::TIME_BUCKETS)?.is_some() {
+                return Err(OptionsError::Useless(
+                    &flags::TIME_BUCKETS,
+                    false,
+                    &flags::GROUP_BY,
+                ));
+            }
+
+            for flag in [&flags::TREE, &flags::GRID] {
+                if group_by.is_some() && matches.has(flag)? {
+                    return Err(OptionsError::Useless(&flags::GROUP_BY, true, flag));
+                }
+            }
+        }
+
+        if matches.has(&flags::TREE)? || matches.has(&flags::GRID)? {
+            return Ok(None);
+        }
+
+        Ok(group_by)
+    }
+
+    /// The comma-separated buckets given to `--time-buckets`, which get put
+    /// in order from the most recent, whichever order they were given in.
+    fn deduce_buckets(matches: &MatchedFlags<'_>) -> Result<Option<Vec<Bucket>>, OptionsError> {
+        let Some(list) = matches.get(&flags::TIME_BUCKETS)? else {
+            return Ok(None);
+        };
+
+        let mut buckets = Vec::new();
+        for name in list.to_string_lossy().split(',') {
+            let bucket = Bucket::from_name(name.trim())
+                .ok_or_else(|| OptionsError::BadArgument(&flags::TIME_BUCKETS, name.into()))?;
+            buckets.push(bucket);
+        }
+
+        buckets.sort();
+        buckets.dedup();
+        Ok(Some(buckets))
+    }
+}
+
+impl TreeStyle {
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
+        let Some(word) = matches.get(&flags::TREE_STYLE)? else {
+            return Ok(Self::default());
+        };
+
+        match word.to_str() {
+            Some("unicode") => Ok(Self::Unicode),
+            Some("ascii") => Ok(Self::Ascii),
+            Some("rounded") => Ok(Self::Rounded),
+            Some("bold") => Ok(Self::Bold),
+            Some("double") => Ok(Self::Double),
+            Some("none") => Ok(Self::None),
+            _ => Err(OptionsError::BadArgument(&flags::TREE_STYLE, word.into())),
+        }
+    }
 }
 
 impl TerminalWidth {
@@ -207,6 +679,37 @@ impl TerminalWidth {
     }
 }
 
+impl TerminalHeight {
+    fn deduce<V: Vars>(vars: &V) -> Result<Self, OptionsError> {
+        if let Some(lines) = vars.get(vars::LINES).and_then(|s| s.into_string().ok()) {
+            match lines.parse() {
+                Ok(height) => Ok(Self::Set(height)),
+                Err(e) => {
+                    let source = NumberSource::Env(vars::LINES);
+                    Err(OptionsError::FailedParse(lines, source, e))
+                }
+            }
+        } else {
+            Ok(Self::Automatic)
+        }
+    }
+}
+
+impl Paging {
+    fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
+        let Some(word) = matches.get(&flags::PAGING)? else {
+            return Ok(Self::Never);
+        };
+
+        match word.to_str() {
+            Some("always") => Ok(Self::Always),
+            Some("auto" | "automatic") => TerminalHeight::deduce(vars).map(Self::Auto),
+            Some("never") => Ok(Self::Never),
+            _ => Err(OptionsError::BadArgument(&flags::PAGING, word.into())),
+        }
+    }
+}
+
 impl RowThreshold {
     fn deduce<V: Vars>(vars: &V) -> Result<Self, OptionsError> {
         if let Some(columns) = vars
@@ -233,19 +736,110 @@ impl TableOptions {
     fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
         let time_format = TimeFormat::deduce(matches, vars)?;
         let size_format = SizeFormat::deduce(matches)?;
-        let user_format = UserFormat::deduce(matches)?;
+        let (user_format, group_id_format) = UserFormat::deduce(matches)?;
         let group_format = GroupFormat::deduce(matches)?;
         let flags_format = FlagsFormat::deduce(vars);
         let columns = Columns::deduce(matches, vars)?;
+        let git_async = columns.git && Self::deduce_git_async(matches)?;
+        let git_submodules = Self::deduce_git_submodules(matches, &columns)?;
+        let git_repos_behind = Self::deduce_git_repos_behind(matches, &columns)?;
+        let git_detail = Self::deduce_git_detail(matches, &columns)?;
+        let headers = HeaderLabels::deduce(vars);
+        let keep_link_owners = matches.has(&flags::NO_DEREF_OWNER)?;
         Ok(Self {
             size_format,
             time_format,
             user_format,
+            group_id_format,
             group_format,
             flags_format,
             columns,
+            git_async,
+            git_submodules,
+            git_repos_behind,
+            git_detail,
+            headers,
+            keep_link_owners,
         })
     }
+
+    fn deduce_git_detail(
+        matches: &MatchedFlags<'_>,
+        columns: &Columns,
+    ) -> Result<bool, OptionsError> {
+        if !matches.has(&flags::GIT_DETAIL)? {
+            return Ok(false);
+        }
+
+        if matches.is_strict() && !columns.git {
+            return Err(OptionsError::Useless(
+                &flags::GIT_DETAIL,
+                false,
+                &flags::GIT,
+            ));
+        }
+
+        Ok(columns.git)
+    }
+
+    fn deduce_git_repos_behind(
+        matches: &MatchedFlags<'_>,
+        columns: &Columns,
+    ) -> Result<bool, OptionsError> {
+        if !matches.has(&flags::GIT_REPOS_BEHIND)? {
+            return Ok(false);
+        }
+
+        if matches.is_strict()
+            && !matches.has(&flags::GIT_REPOS)?
+            && !matches.has(&flags::GIT_REPOS_NO_STAT)?
+        {
+            return Err(OptionsError::Useless2(
+                &flags::GIT_REPOS_BEHIND,
+                &flags::GIT_REPOS,
+                &flags::GIT_REPOS_NO_STAT,
+            ));
+        }
+
+        Ok(columns.subdir_git_repos || columns.subdir_git_repos_no_stat)
+    }
+
+    fn deduce_git_submodules(
+        matches: &MatchedFlags<'_>,
+        columns: &Columns,
+    ) -> Result<SubmoduleIgnore, OptionsError> {
+        let Some(word) = matches.get(&flags::GIT_IGNORE_SUBMODULES)? else {
+            return Ok(SubmoduleIgnore::Config);
+        };
+
+        if matches.is_strict() && !columns.git {
+            return Err(OptionsError::Useless(
+                &flags::GIT_IGNORE_SUBMODULES,
+                false,
+                &flags::GIT,
+            ));
+        }
+
+        match word.to_string_lossy().as_ref() {
+            "all" => Ok(SubmoduleIgnore::All),
+            "dirty" => Ok(SubmoduleIgnore::Dirty),
+            "untracked" => Ok(SubmoduleIgnore::Untracked),
+            "none" => Ok(SubmoduleIgnore::None),
+            _ => Err(OptionsError::BadArgument(
+                &flags::GIT_IGNORE_SUBMODULES,
+                word.into(),
+            )),
+        }
+    }
+
+    fn deduce_git_async(matches: &MatchedFlags<'_>) -> Result<bool, OptionsError> {
+        match matches.get(&flags::GIT)? {
+            None => Ok(false),
+            Some(word) if word == "sync" => Ok(false),
+            Some(word) if word == "async" => Ok(true),
+            Some(word) => Err(OptionsError::BadArgument(&flags::GIT, word.into())),
+        }
+    }
 }
 
 impl Columns {
@@ -256,7 +850,8 @@ impl Columns {
             .get_with_fallback(vars::EXA_OVERRIDE_GIT, vars::EZA_OVERRIDE_GIT)
             .is_some();
 
-        let git = matches.has(&flags::GIT)? && !matches.has(&flags::NO_GIT)? && !no_git_env;
+        let git =
+            matches.get(&flags::GIT)?.is_some() && !matches.has(&flags::NO_GIT)? && !no_git_env;
         let subdir_git_repos =
             matches.has(&flags::GIT_REPOS)? && !matches.has(&flags::NO_GIT)? && !no_git_env;
         let subdir_git_repos_no_stat = !subdir_git_repos
@@ -271,11 +866,61 @@ impl Columns {
         let octal = matches.has(&flags::OCTAL)?;
         let security_context = xattr::ENABLED && matches.has(&flags::SECURITY_CONTEXT)?;
         let file_flags = matches.has(&flags::FILE_FLAGS)?;
+        let target = matches.has(&flags::TARGET)?;
+        let quota = matches.has(&flags::QUOTA)?;
+        let git_since = Self::deduce_git_since(matches, no_git_env)?;
 
         let permissions = !matches.has(&flags::NO_PERMISSIONS)?;
         let filesize = !matches.has(&flags::NO_FILESIZE)?;
         let user = !matches.has(&flags::NO_USER)?;
 
+        if let Some(order) = Self::deduce_order(matches, time_types)? {
+            let git_allowed = !matches.has(&flags::NO_GIT)? && !no_git_env;
+            let has = |column| order.contains(&column);
+
+            return Ok(Self {
+                time_types,
+                #[cfg(unix)]
+                inode: has(Column::Inode),
+                #[cfg(not(unix))]
+                inode: false,
+                #[cfg(unix)]
+                links: has(Column::HardLinks),
+                #[cfg(not(unix))]
+                links: false,
+                #[cfg(unix)]
+                blocksize: has(Column::Blocksize),
+                #[cfg(not(unix))]
+                blocksize: false,
+                #[cfg(unix)]
+                group: has(Column::Group),
+                #[cfg(not(unix))]
+                group: false,
+                git: has(Column::GitStatus) && git_allowed,
+                subdir_git_repos: has(Column::SubdirGitRepo(true)) && git_allowed,
+                subdir_git_repos_no_stat: has(Column::SubdirGitRepo(false)) && git_allowed,
+                #[cfg(unix)]
+                octal: has(Column::Octal),
+                #[cfg(not(unix))]
+                octal: false,
+                #[cfg(unix)]
+                security_context: has(Column::SecurityContext),
+                #[cfg(not(unix))]
+                security_context: false,
+                file_flags: has(Column::FileFlags),
+                target: has(Column::Target),
+                #[cfg(unix)]
+                quota: has(Column::Quota),
+                #[cfg(not(unix))]
+                quota: false,
+                git_since: git_since.filter(|_| has(Column::GitSince)),
+                permissions: has(Column::Permissions),
+                filesize: has(Column::FileSize),
+                user: has(Column::User),
+                order: Some(order),
+            });
+        }
+
         Ok(Self {
             time_types,
             inode,
@@ -288,11 +933,120 @@ impl Columns {
             octal,
             security_context,
             file_flags,
+            target,
+            quota,
+            git_since,
             permissions,
             filesize,
             user,
+            order: None,
         })
     }
+
+    /// The Git reference to compare each file with, unless Git has been
+    /// turned off.
+    fn deduce_git_since(
+        matches: &MatchedFlags<'_>,
+        no_git_env: bool,
+    ) -> Result<Option<String>, OptionsError> {
+        let Some(reference) = matches.get(&flags::GIT_AGE_SINCE)? else {
+            return Ok(None);
+        };
+
+        if matches.has(&flags::NO_GIT)? || no_git_env {
+            return Ok(None);
+        }
+
+        Ok(Some(reference.to_string_lossy().into_owned()))
+    }
+
+    /// Determine the exact list of columns from the comma-separated names
+    /// given to `--columns`, if it was given. The file name always comes
+    /// last, so `name` may only appear at the end of the list; `time` stands
+    /// for whichever timestamps were picked with `--time` and friends.
+    fn deduce_order(
+        matches: &MatchedFlags<'_>,
+        time_types: TimeTypes,
+    ) -> Result<Option<Vec<Column>>, OptionsError> {
+        let Some(list) = matches.get(&flags::COLUMNS)? else {
+            return Ok(None);
+        };
+
+        let list = list.to_string_lossy();
+        let names = list.split(',').collect::<Vec<_>>();
+        let mut order = Vec::new();
+
+        for (index, name) in names.iter().enumerate() {
+            let column = match *name {
+                "time" => {
+                    let TimeTypes {
+                        modified,
+                        changed,
+                        accessed,
+                        created,
+                    } = time_types;
+                    for (shown, time_type) in [
+                        (modified, TimeType::Modified),
+                        (changed, TimeType::Changed),
+                        (created, TimeType::Created),
+                        (accessed, TimeType::Accessed),
+                    ] {
+                        if shown {
+                            order.push(Column::Timestamp(time_type));
+                        }
+                    }
+                    continue;
+                }
+                "name" if index == names.len() - 1 => continue,
+                "name" => {
+                    return Err(OptionsError::Unsupported(String::from(
+                        "The name column must come last in --columns",
+                    )));
+                }
+                _ => column_named(name)
+                    .ok_or_else(|| OptionsError::BadArgument(&flags::COLUMNS, (*name).into()))?,
+            };
+
+            order.push(column);
+        }
+
+        Ok(Some(order))
+    }
+}
+
+/// The column with the given name, as used by `--columns` and by the
+/// `headers` section of the configuration file.
+pub(super) fn column_named(name: &str) -> Option<Column> {
+    Some(match name {
+        "permissions" | "perms" => Column::Permissions,
+        "size" => Column::FileSize,
+        #[cfg(unix)]
+        "octal" => Column::Octal,
+        #[cfg(unix)]
+        "inode" => Column::Inode,
+        #[cfg(unix)]
+        "links" => Column::HardLinks,
+        #[cfg(unix)]
+        "blocksize" | "blocks" => Column::Blocksize,
+        "user" => Column::User,
+        #[cfg(unix)]
+        "group" => Column::Group,
+        #[cfg(unix)]
+        "context" => Column::SecurityContext,
+        "flags" => Column::FileFlags,
+        "target" => Column::Target,
+        #[cfg(unix)]
+        "quota" => Column::Quota,
+        "modified" => Column::Timestamp(TimeType::Modified),
+        "changed" => Column::Timestamp(TimeType::Changed),
+        "accessed" => Column::Timestamp(TimeType::Accessed),
+        "created" => Column::Timestamp(TimeType::Created),
+        "git" => Column::GitStatus,
+        "git-since" => Column::GitSince,
+        "git-repos" => Column::SubdirGitRepo(true),
+        "git-repos-no-status" => Column::SubdirGitRepo(false),
+        _ => return None,
+    })
 }
 
 impl SizeFormat {
@@ -329,6 +1083,7 @@ impl TimeFormat {
 
         match word.to_string_lossy().as_ref() {
             "default" => Ok(Self::DefaultFormat),
+            "locale" => Ok(Self::LocaleFormat),
             "relative" => Ok(Self::Relative),
             "iso" => Ok(Self::ISOFormat),
             "long-iso" => Ok(Self::LongISO),
@@ -372,9 +1127,38 @@ impl TimeFormat {
 }
 
 impl UserFormat {
-    fn deduce(matches: &MatchedFlags<'_>) -> Result<Self, OptionsError> {
-        let flag = matches.has(&flags::NUMERIC)?;
-        Ok(if flag { Self::Numeric } else { Self::Name })
+    /// Determines how to show the user and group columns, in that order.
+    ///
+    /// `--numeric` shows both as numbers, but `--numeric-ids` can be given
+    /// a comma-separated list of `uid` and `gid` to only show one of them as
+    /// a number, and `both` to show the names of the others along with
+    /// their numbers.
+    fn deduce(matches: &MatchedFlags<'_>) -> Result<(Self, Self), OptionsError> {
+        let Some(word) = matches.get(&flags::NUMERIC_IDS)? else {
+            return Ok(if matches.has(&flags::NUMERIC)? {
+                (Self::Numeric, Self::Numeric)
+            } else {
+                (Self::Name, Self::Name)
+            });
+        };
+
+        let (mut numeric_user, mut numeric_group, mut both) = (false, false, false);
+        for id in word.to_string_lossy().split(',') {
+            match id {
+                "uid" => numeric_user = true,
+                "gid" => numeric_group = true,
+                "both" => both = true,
+                _ => return Err(OptionsError::BadArgument(&flags::NUMERIC_IDS, word.into())),
+            }
+        }
+
+        let format = |numeric| match (numeric, both) {
+            (true, _) => Self::Numeric,
+            (false, true) => Self::NameAndId,
+            (false, false) => Self::Name,
+        };
+
+        Ok((format(numeric_user), format(numeric_group)))
     }
 }
 
@@ -448,6 +1232,41 @@ impl TimeTypes {
     }
 }
 
+impl ColorDepth {
+    /// The depth given with `--color-depth`, or otherwise the one the
+    /// terminal says it has. Terminals that can show 24-bit colours say so
+    /// in `COLORTERM`, and just about all the others can show the 256-colour
+    /// palette, apart from the Linux console and older terminals.
+    fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
+        if let Some(word) = matches
+            .get(&flags::COLOR_DEPTH)?
+            .or(matches.get(&flags::COLOUR_DEPTH)?)
+        {
+            return match word.to_str() {
+                Some("8") => Ok(Self::Eight),
+                Some("256") => Ok(Self::Palette),
+                Some("truecolor" | "24bit") => Ok(Self::TrueColor),
+                _ => Err(OptionsError::BadArgument(
+                    &flags::COLOR_DEPTH,
+                    word.to_os_string(),
+                )),
+            };
+        }
+
+        let colorterm = vars.get(vars::COLORTERM).unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return Ok(Self::TrueColor);
+        }
+
+        let term = vars.get(vars::TERM).unwrap_or_default();
+        match term.to_str() {
+            Some("linux" | "ansi" | "vt100" | "vt220" | "xterm-color") => Ok(Self::Eight),
+            Some(term) if term.ends_with("-16color") => Ok(Self::Eight),
+            _ => Ok(Self::Palette),
+        }
+    }
+}
+
 impl ColorScaleOptions {
     pub fn deduce<V: Vars>(matches: &MatchedFlags<'_>, vars: &V) -> Result<Self, OptionsError> {
         let min_luminance =
@@ -480,18 +1299,18 @@ impl ColorScaleOptions {
             min_luminance,
             size: false,
             age: false,
+            time: None,
+            size_thresholds: None,
+            depth: ColorDepth::deduce(matches, vars)?,
         };
 
-        let words = if let Some(w) = matches
+        let words = matches
             .get(&flags::COLOR_SCALE)?
             .or(matches.get(&flags::COLOUR_SCALE)?)
-        {
-            w.to_os_string()
-        } else {
-            return Ok(options);
-        };
+            .map(OsStr::to_string_lossy)
+            .unwrap_or_default();
 
-        for word in words.to_string_lossy().split(',') {
+        for word in words.split(',').filter(|word| !word.is_empty()) {
             match word {
                 "all" => {
                     options.size = true;
@@ -506,8 +1325,77 @@ impl ColorScaleOptions {
             };
         }
 
+        let no_floor = matches
+            .has_where(|f| {
+                f.matches(&flags::NO_COLOR_SCALE_FLOOR) || f.matches(&flags::NO_COLOUR_SCALE_FLOOR)
+            })?
+            .is_some();
+
+        if no_floor && options.size {
+            options.size_thresholds = Some(Self::deduce_size_thresholds(vars));
+        } else if no_floor && matches.is_strict() {
+            return Err(OptionsError::Useless(
+                &flags::NO_COLOR_SCALE_FLOOR,
+                false,
+                &flags::COLOR_SCALE,
+            ));
+        }
+
+        if let Some(word) = matches
+            .get(&flags::COLOR_SCALE_TIME)?
+            .or(matches.get(&flags::COLOUR_SCALE_TIME)?)
+        {
+            let time = match word.to_str() {
+                Some("modified" | "mtime") => TimeType::Modified,
+                Some("changed" | "ctime") => TimeType::Changed,
+                Some("accessed" | "atime") => TimeType::Accessed,
+                Some("created" | "btime") => TimeType::Created,
+                _ => Err(OptionsError::BadArgument(
+                    &flags::COLOR_SCALE_TIME,
+                    word.to_os_string(),
+                ))?,
+            };
+
+            if options.age {
+                options.time = Some(time);
+            } else if matches.is_strict() {
+                return Err(OptionsError::Useless(
+                    &flags::COLOR_SCALE_TIME,
+                    false,
+                    &flags::COLOR_SCALE,
+                ));
+            }
+        }
+
         Ok(options)
     }
+
+    /// The thresholds from `EZA_COLOR_SCALE_SIZES`, which should be four
+    /// comma-separated sizes in increasing order. Anything else falls back
+    /// to the powers of 1024.
+    fn deduce_size_thresholds<V: Vars>(vars: &V) -> SizeThresholds {
+        let Some(var) = vars.get(vars::EZA_COLOR_SCALE_SIZES) else {
+            return SizeThresholds::default();
+        };
+
+        let sizes = var
+            .to_string_lossy()
+            .split(',')
+            .map(|size| parse_size(size.trim()))
+            .collect::<Option<Vec<_>>>();
+
+        match sizes.as_deref() {
+            Some(&[a, b, c, d]) if a < b && b < c && c < d => SizeThresholds([a, b, c, d]),
+            _ => {
+                warn!(
+                    "Ignoring invalid {}: {:?}",
+                    vars::EZA_COLOR_SCALE_SIZES,
+                    var
+                );
+                SizeThresholds::default()
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -530,18 +1418,65 @@ mod test {
         &flags::CREATED,
         &flags::ACCESSED,
         &flags::HEADER,
+        &flags::HEADER_MODE,
+        &flags::HEADER_UNITS,
         &flags::GROUP,
         &flags::INODE,
         &flags::GIT,
+        &flags::GIT_REPOS,
+        &flags::GIT_REPOS_NO_STAT,
+        &flags::GIT_REPOS_BEHIND,
+        &flags::GIT_IGNORE_SUBMODULES,
+        &flags::GIT_DETAIL,
+        &flags::GIT_AGE_SINCE,
+        &flags::NO_GIT,
         &flags::LINKS,
         &flags::BLOCKSIZE,
         &flags::LONG,
         &flags::LEVEL,
         &flags::GRID,
         &flags::ACROSS,
+        &flags::GRID_ORDER,
+        &flags::THUMBNAILS,
         &flags::ONE_LINE,
         &flags::TREE,
         &flags::NUMERIC,
+        &flags::NUMERIC_IDS,
+        &flags::COLUMNS,
+        &flags::TOTAL_SIZE,
+        &flags::SIZE_ON_DISK,
+        &flags::SHOW_COUNTS,
+        &flags::PRUNE,
+        &flags::TREE_STYLE,
+        &flags::DEPTH_GUIDES,
+        &flags::ALIGN_SECTIONS,
+        &flags::GROUP_BY,
+        &flags::TIME_BUCKETS,
+        &flags::COLOR_SCALE,
+        &flags::COLOUR_SCALE,
+        &flags::NO_COLOR_SCALE_FLOOR,
+        &flags::NO_COLOUR_SCALE_FLOOR,
+        &flags::COLOR_SCALE_TIME,
+        &flags::COLOUR_SCALE_TIME,
+        &flags::COLOR_DEPTH,
+        &flags::COLOUR_DEPTH,
+        &flags::RECURSE,
+        &flags::COMPARE,
+        &flags::STAT,
+        &flags::WHICH,
+        &flags::DEREF_LINKS,
+        &flags::NO_DEREF_OWNER,
+        &flags::FORMAT,
+        &flags::FIELDS,
+        &flags::FORMAT_TEMPLATE,
+        &flags::NULL,
+        &flags::DIR_HEADER,
+        &flags::DIR_FOOTER,
+        &flags::SUMMARY,
+        &flags::SUMMARY_SORT,
+        &flags::PAGING,
+        &flags::EXTENDED,
+        &flags::XATTR_VALUES,
     ];
 
     #[allow(unused_macro_rules)]
@@ -616,6 +1551,22 @@ mod test {
                 }
             }
         };
+
+        ($name:ident: $type:ident <- $inputs:expr, $vars:expr; $stricts:expr => like $pat:pat if $guard:expr) => {
+            /// Like above, but with a guard on the pattern.
+            #[test]
+            fn $name() {
+                for result in parse_for_test($inputs.as_ref(), TEST_ARGS, $stricts, |mf| {
+                    $type::deduce(mf, &$vars)
+                }) {
+                    println!("Testing {:?}", result);
+                    match result {
+                        $pat if $guard => assert!(true),
+                        _ => assert!(false),
+                    }
+                }
+            }
+        };
     }
 
     mod size_formats {
@@ -651,6 +1602,7 @@ mod test {
 
         // Individual settings
         test!(default:                TimeFormat <- ["--time-style=default"], None;               Both => like Ok(TimeFormat::DefaultFormat));
+        test!(locale:                 TimeFormat <- ["--time-style=locale"], None;                Both => like Ok(TimeFormat::LocaleFormat));
         test!(iso:                    TimeFormat <- ["--time-style", "iso"], None;                Both => like Ok(TimeFormat::ISOFormat));
         test!(relative:               TimeFormat <- ["--time-style", "relative"], None;           Both => like Ok(TimeFormat::Relative));
         test!(long_iso:               TimeFormat <- ["--time-style=long-iso"], None;              Both => like Ok(TimeFormat::LongISO));
@@ -725,6 +1677,7 @@ mod test {
         use super::*;
 
         use crate::output::grid::Options as GridOptions;
+        use crate::output::thumbnails::{Options as ThumbnailOptions, Protocol};
 
         // Default
         test!(empty:         Mode <- [], None;            Both => like Ok(Mode::Grid(_)));
@@ -735,13 +1688,127 @@ mod test {
         test!(across:        Mode <- ["--across"], None;  Both => like Ok(Mode::Grid(GridOptions { across: true,  .. })));
         test!(gracross:      Mode <- ["-xG"], None;       Both => like Ok(Mode::Grid(GridOptions { across: true,  .. })));
 
+        // Grid order
+        test!(order_rows:    Mode <- ["--grid-order=rows"], None;                Both => like Ok(Mode::Grid(GridOptions { across: true,  .. })));
+        test!(order_columns: Mode <- ["--grid-order=columns"], None;             Both => like Ok(Mode::Grid(GridOptions { across: false, .. })));
+        test!(order_same:    Mode <- ["-x", "--grid-order=rows"], None;          Both => like Ok(Mode::Grid(GridOptions { across: true,  .. })));
+        test!(order_wins:    Mode <- ["-x", "--grid-order=columns"], None;       Last => like Ok(Mode::Grid(GridOptions { across: false, .. })));
+        test!(order_clash:   Mode <- ["-x", "--grid-order=columns"], None;       Complain => err OptionsError::Conflict(&flags::ACROSS, &flags::GRID_ORDER));
+        test!(order_bad:     Mode <- ["--grid-order=diagonal"], None;            Both => err OptionsError::BadArgument(&flags::GRID_ORDER, OsString::from("diagonal")));
+        test!(order_details: Mode <- ["-lG", "--grid-order=rows"], None;         Both => like Ok(Mode::GridDetails(grid_details::Options { grid: GridOptions { across: true }, .. })));
+        test!(across_details: Mode <- ["-lGx"], None;                            Both => like Ok(Mode::GridDetails(grid_details::Options { grid: GridOptions { across: true }, .. })));
+        test!(order_long:    Mode <- ["-l", "--grid-order=rows"], None;          Complain => err OptionsError::Useless(&flags::GRID_ORDER, true, &flags::LONG));
+
+        // Thumbnails views
+        test!(thumbnails:    Mode <- ["--thumbnails=kitty"], None;   Both => like Ok(Mode::Thumbnails(ThumbnailOptions { protocol: Protocol::Kitty, .. })));
+        test!(thumbs_last:   Mode <- ["--thumbnails", "-1"], None;   Last => like Ok(Mode::Lines));
+        test!(thumbs_long:   Mode <- ["-l", "--thumbnails"], None;   Last => like Ok(Mode::Thumbnails(_)));
+
         // Lines views
         test!(lines:         Mode <- ["--oneline"], None;     Both => like Ok(Mode::Lines));
         test!(prima:         Mode <- ["-1"], None;            Both => like Ok(Mode::Lines));
+        test!(null:          Mode <- ["--null"], None;        Both => like Ok(Mode::Lines));
+        test!(zero:          Mode <- ["-0"], None;            Both => like Ok(Mode::Lines));
 
         // Details views
         test!(long:          Mode <- ["--long"], None;    Both => like Ok(Mode::Details(_)));
         test!(ell:           Mode <- ["-l"], None;        Both => like Ok(Mode::Details(_)));
+        test!(which:         Mode <- ["--which", "ls"], None;        Both => like Ok(Mode::Details(_)));
+        test!(which_lines:   Mode <- ["--which", "ls", "-1"], None;  Last => like Ok(Mode::Lines));
+
+        // Repeated headers
+        test!(header_once:   Mode <- ["-lh"], None;                 Both => like Ok(Mode::Details(details::Options { header: true, header_repeat: None, .. })));
+        test!(header_first:  Mode <- ["-hl"], None;                 Both => like Ok(Mode::Details(details::Options { header: true, header_repeat: None, .. })));
+        test!(header_repeat: Mode <- ["-lh", "--header-mode=repeat"], None;            Both => like Ok(Mode::Details(details::Options { header: true, header_repeat: Some(TerminalHeight::Automatic), .. })));
+        test!(header_units:  Mode <- ["-l", "--header-units"], None;           Both => like Ok(Mode::Details(details::Options { header: true, header_units: true, .. })));
+        test!(header_next:   Mode <- ["-l", "--header-mode", "repeat"], None;    Both => like Ok(Mode::Details(details::Options { header: true, header_repeat: Some(TerminalHeight::Automatic), .. })));
+        test!(header_path:   Mode <- ["-lh", "repeat"], None;                   Both => like Ok(Mode::Details(details::Options { header: true, header_repeat: None, .. })));
+        // Git modes
+        #[cfg(feature = "git")]
+        test!(git_sync:      Mode <- ["-l", "--git"], None;          Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_async: false, .. }), .. })));
+        #[cfg(feature = "git")]
+        test!(git_async:     Mode <- ["-l", "--git=async"], None;    Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_async: true, .. }), .. })));
+        #[cfg(feature = "git")]
+        test!(git_bad:       Mode <- ["-l", "--git=later"], None;    Both => err OptionsError::BadArgument(&flags::GIT, OsString::from("later")));
+        #[cfg(feature = "git")]
+        test!(submodules:    Mode <- ["-l", "--git", "--git-ignore-submodules"], None;       Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_submodules: SubmoduleIgnore::All, .. }), .. })));
+        #[cfg(feature = "git")]
+        test!(submodules_2:  Mode <- ["-l", "--git", "--git-ignore-submodules=dirty"], None; Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_submodules: SubmoduleIgnore::Dirty, .. }), .. })));
+        #[cfg(feature = "git")]
+        test!(submodules_bad: Mode <- ["-l", "--git", "--git-ignore-submodules=some"], None; Both => err OptionsError::BadArgument(&flags::GIT_IGNORE_SUBMODULES, OsString::from("some")));
+        test!(submodules_no_git: Mode <- ["-l", "--git-ignore-submodules"], None;             Complain => err OptionsError::Useless(&flags::GIT_IGNORE_SUBMODULES, false, &flags::GIT));
+        test!(async_no_git:  Mode <- ["-l"], None;                   Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_async: false, .. }), .. })));
+        #[cfg(feature = "git")]
+        test!(repos_behind:  Mode <- ["-l", "--git-repos", "--git-repos-behind"], None;           Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_repos_behind: true, .. }), .. })));
+        #[cfg(feature = "git")]
+        test!(no_st_behind:  Mode <- ["-l", "--git-repos-no-status", "--git-repos-behind"], None; Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_repos_behind: true, .. }), .. })));
+        test!(behind_alone:  Mode <- ["-l", "--git-repos-behind"], None;                          Last => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_repos_behind: false, .. }), .. })));
+        #[cfg(feature = "git")]
+        test!(git_detail:    Mode <- ["-l", "--git", "--git-detail"], None; Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { git_detail: true, .. }), .. })));
+        test!(link_owners:   Mode <- ["-lX", "--no-dereference-owner"], None; Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { keep_link_owners: true, .. }), .. })));
+        test!(detail_no_git: Mode <- ["-l", "--git-detail"], None;          Complain => err OptionsError::Useless(&flags::GIT_DETAIL, false, &flags::GIT));
+
+        test!(names:         Mode <- ["-l"], None;                   Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { user_format: UserFormat::Name,    group_id_format: UserFormat::Name, .. }), .. })));
+        test!(numeric:       Mode <- ["-ln"], None;                  Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { user_format: UserFormat::Numeric, group_id_format: UserFormat::Numeric, .. }), .. })));
+        test!(numeric_first: Mode <- ["-nl"], None;                  Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { user_format: UserFormat::Numeric, group_id_format: UserFormat::Numeric, .. }), .. })));
+        test!(numeric_uid:   Mode <- ["-l", "--numeric-ids=uid"], None;  Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { user_format: UserFormat::Numeric, group_id_format: UserFormat::Name, .. }), .. })));
+        test!(numeric_both:  Mode <- ["-l", "--numeric-ids=both"], None; Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { user_format: UserFormat::NameAndId, group_id_format: UserFormat::NameAndId, .. }), .. })));
+        test!(numeric_mixed: Mode <- ["-l", "--numeric-ids=gid,both"], None; Both => like Ok(Mode::Details(details::Options { table: Some(TableOptions { user_format: UserFormat::NameAndId, group_id_format: UserFormat::Numeric, .. }), .. })));
+        test!(numeric_bad:   Mode <- ["-l", "--numeric-ids=pid"], None;  Both => err OptionsError::BadArgument(&flags::NUMERIC_IDS, OsString::from("pid")));
+        test!(behind_strict: Mode <- ["-l", "--git-repos-behind"], None;                          Complain => err OptionsError::Useless2(&flags::GIT_REPOS_BEHIND, &flags::GIT_REPOS, &flags::GIT_REPOS_NO_STAT));
+
+        test!(header_bad:    Mode <- ["-l", "--header-mode=twice"], None;        Both => err OptionsError::BadArgument(&flags::HEADER_MODE, OsString::from("twice")));
+
+        // Subtree sizes
+        test!(tree_sizes:    Mode <- ["--tree", "--total-size"], None;                   Both => like Ok(Mode::Details(details::Options { subtree_size: Some(SubtreeSize::Apparent), .. })));
+        test!(tree_on_disk:  Mode <- ["--tree", "--total-size", "--size-on-disk"], None; Both => like Ok(Mode::Details(details::Options { subtree_size: Some(SubtreeSize::OnDisk), .. })));
+        test!(tree_no_sizes: Mode <- ["--tree"], None;                                   Both => like Ok(Mode::Details(details::Options { subtree_size: None, .. })));
+        test!(long_sizes:    Mode <- ["--long", "--total-size"], None;                   Both => like Ok(Mode::Details(details::Options { subtree_size: None, .. })));
+
+        // Extended attribute values
+        test!(xattr_values:  Mode <- ["-l@", "--xattr-values"], None;         Both => like Ok(Mode::Details(details::Options { xattr_values: Some(32), .. })));
+        test!(xattr_limit:   Mode <- ["-l@", "--xattr-values=8"], None;       Both => like Ok(Mode::Details(details::Options { xattr_values: Some(8), .. })));
+        test!(no_values:     Mode <- ["-l@"], None;                           Both => like Ok(Mode::Details(details::Options { xattr_values: None, .. })));
+        test!(values_alone:  Mode <- ["-l", "--xattr-values"], None;          Complain => err OptionsError::Useless(&flags::XATTR_VALUES, false, &flags::EXTENDED));
+
+        // Subtree counts
+        test!(tree_counts:   Mode <- ["--tree", "--show-counts"], None;                  Both => like Ok(Mode::Details(details::Options { show_counts: true, .. })));
+        test!(long_counts:   Mode <- ["--long", "--tree", "--show-counts"], None;        Both => like Ok(Mode::Details(details::Options { show_counts: true, .. })));
+        test!(tree_uncounted: Mode <- ["--tree"], None;                                  Both => like Ok(Mode::Details(details::Options { show_counts: false, .. })));
+
+        // Pruning
+        test!(tree_prune:    Mode <- ["--tree", "--prune"], None;                        Both => like Ok(Mode::Details(details::Options { prune: true, .. })));
+        test!(long_prune:    Mode <- ["--long", "--tree", "--prune"], None;              Both => like Ok(Mode::Details(details::Options { prune: true, .. })));
+        test!(long_no_prune: Mode <- ["--long", "--prune"], None;                        Last => like Ok(Mode::Details(details::Options { prune: false, .. })));
+
+        // Tree styles
+        test!(tree_unicode:  Mode <- ["--tree"], None;                                   Both => like Ok(Mode::Details(details::Options { tree_style: TreeStyle::Unicode, .. })));
+        test!(tree_ascii:    Mode <- ["--tree", "--tree-style=ascii"], None;             Both => like Ok(Mode::Details(details::Options { tree_style: TreeStyle::Ascii, .. })));
+        test!(long_rounded:  Mode <- ["--long", "--tree", "--tree-style", "rounded"], None; Both => like Ok(Mode::Details(details::Options { tree_style: TreeStyle::Rounded, .. })));
+        test!(tree_twice:    Mode <- ["--tree", "--tree-style=bold", "--tree-style=none"], None; Last => like Ok(Mode::Details(details::Options { tree_style: TreeStyle::None, .. })));
+        test!(tree_bad:      Mode <- ["--tree", "--tree-style=curly"], None;             Both => err OptionsError::BadArgument(&flags::TREE_STYLE, OsString::from("curly")));
+        test!(tree_guides:   Mode <- ["--tree", "--ascii-depth-guides"], None;           Both => like Ok(Mode::Details(details::Options { depth_guides: Some(n), .. })) if n.get() == 1);
+        test!(tree_guides_n: Mode <- ["--tree", "--ascii-depth-guides=4"], None;         Both => like Ok(Mode::Details(details::Options { depth_guides: Some(n), .. })) if n.get() == 4);
+        test!(long_guides:   Mode <- ["--long", "--ascii-depth-guides=4"], None;         Last => like Ok(Mode::Details(details::Options { depth_guides: None, .. })));
+
+        // Lining up recursive listings
+        test!(long_aligned:  Mode <- ["--long", "--recurse", "--align-sections"], None;  Both => like Ok(Mode::Details(details::Options { align_sections: true, .. })));
+        test!(long_unaligned: Mode <- ["--long", "--recurse"], None;                     Both => like Ok(Mode::Details(details::Options { align_sections: false, .. })));
+        test!(tree_aligned:  Mode <- ["--long", "--tree", "--align-sections"], None;     Last => like Ok(Mode::Details(details::Options { align_sections: false, .. })));
+
+        // Sections
+        test!(group_by:      Mode <- ["--long", "--group-by=mtime-bucket"], None;        Both => like Ok(Mode::Details(details::Options { group_by: Some(group::Options { buckets: _ }), .. })));
+        test!(group_none:    Mode <- ["--long", "--group-by=none"], None;                Both => like Ok(Mode::Details(details::Options { group_by: None, .. })));
+        test!(group_tree:    Mode <- ["--long", "--tree", "--group-by=mtime-bucket"], None; Complain => err OptionsError::Useless(&flags::GROUP_BY, true, &flags::TREE));
+        test!(group_short:   Mode <- ["--group-by=mtime-bucket"], None;                  Complain => err OptionsError::Useless(&flags::GROUP_BY, false, &flags::LONG));
+        test!(buckets_alone: Mode <- ["--long", "--time-buckets=week"], None;            Complain => err OptionsError::Useless(&flags::TIME_BUCKETS, false, &flags::GROUP_BY));
+        test!(bad_bucket:    Mode <- ["--long", "--group-by=mtime-bucket", "--time-buckets=decade"], None; Both => err OptionsError::BadArgument(&flags::TIME_BUCKETS, OsString::from("decade")));
+        test!(unrecursed:    View <- ["--long", "--align-sections"], None;               Complain => err OptionsError::Useless(&flags::ALIGN_SECTIONS, false, &flags::RECURSE));
+        test!(tree_useless:  View <- ["--long", "--recurse", "--tree", "--align-sections"], None; Complain => err OptionsError::Useless(&flags::ALIGN_SECTIONS, true, &flags::TREE));
+
+        // Picking columns
+        test!(columns_bad:   Mode <- ["-l", "--columns=size,colour"], None;      Both => err OptionsError::BadArgument(&flags::COLUMNS, OsString::from("colour")));
+        test!(columns_name:  Mode <- ["-l", "--columns=name,size"], None;        Both => err OptionsError::Unsupported(String::from("The name column must come last in --columns")));
 
         // Grid-details views
         test!(lid:           Mode <- ["--long", "--grid"], None;  Both => like Ok(Mode::GridDetails(_)));
@@ -763,7 +1830,9 @@ mod test {
         #[cfg(feature = "git")]
         test!(just_git:      Mode <- ["--git"],       None;  Last => like Ok(Mode::Grid(_)));
 
+        test!(just_header_units: Mode <- ["--header-units"], None; Complain => err OptionsError::Useless(&flags::HEADER_UNITS, false, &flags::LONG));
         test!(just_header_2: Mode <- ["--header"],    None;  Complain => err OptionsError::Useless(&flags::HEADER,  false, &flags::LONG));
+        test!(just_mode:     Mode <- ["--header-mode=repeat"], None; Complain => err OptionsError::Useless(&flags::HEADER_MODE, false, &flags::LONG));
         test!(just_group_2:  Mode <- ["--group"],     None;  Complain => err OptionsError::Useless(&flags::GROUP,   false, &flags::LONG));
         test!(just_inode_2:  Mode <- ["--inode"],     None;  Complain => err OptionsError::Useless(&flags::INODE,   false, &flags::LONG));
         test!(just_links_2:  Mode <- ["--links"],     None;  Complain => err OptionsError::Useless(&flags::LINKS,   false, &flags::LONG));
@@ -771,12 +1840,16 @@ mod test {
         test!(just_binary_2: Mode <- ["--binary"],    None;  Complain => err OptionsError::Useless(&flags::BINARY,  false, &flags::LONG));
         test!(just_bytes_2:  Mode <- ["--bytes"],     None;  Complain => err OptionsError::Useless(&flags::BYTES,   false, &flags::LONG));
         test!(just_numeric2: Mode <- ["--numeric"],   None;  Complain => err OptionsError::Useless(&flags::NUMERIC, false, &flags::LONG));
+        test!(just_ids:      Mode <- ["--numeric-ids=uid"], None; Complain => err OptionsError::Useless(&flags::NUMERIC_IDS, false, &flags::LONG));
+        test!(just_columns:  Mode <- ["--columns=size"], None;  Complain => err OptionsError::Useless(&flags::COLUMNS, false, &flags::LONG));
 
         #[cfg(feature = "git")]
         test!(just_git_2:    Mode <- ["--git"],    None;  Complain => err OptionsError::Useless(&flags::GIT,    false, &flags::LONG));
 
         // Contradictions and combinations
         test!(lgo:           Mode <- ["--long", "--grid", "--oneline"], None;  Both => like Ok(Mode::Lines));
+        test!(null_long:     Mode <- ["--null", "--long"], None;               Last => like Ok(Mode::Details(_)));
+        test!(null_long_2:   Mode <- ["--null", "--long"], None;               Complain => err OptionsError::Useless(&flags::NULL, true, &flags::LONG));
         test!(lgt:           Mode <- ["--long", "--grid", "--tree"],    None;  Both => like Ok(Mode::Details(_)));
         test!(tgl:           Mode <- ["--tree", "--grid", "--long"],    None;  Both => like Ok(Mode::GridDetails(_)));
         test!(tlg:           Mode <- ["--tree", "--long", "--grid"],    None;  Both => like Ok(Mode::GridDetails(_)));
@@ -784,4 +1857,225 @@ mod test {
         test!(og:            Mode <- ["--oneline", "--grid"],           None;  Both => like Ok(Mode::Grid(_)));
         test!(tg:            Mode <- ["--tree", "--grid"],              None;  Both => like Ok(Mode::Grid(_)));
     }
+
+    mod terminal_heights {
+        use super::*;
+
+        #[test]
+        fn from_lines() {
+            let vars = Some(OsString::from("30"));
+            assert_eq!(TerminalHeight::deduce(&vars), Ok(TerminalHeight::Set(30)));
+        }
+
+        #[test]
+        fn automatic() {
+            let vars: Option<OsString> = None;
+            assert_eq!(TerminalHeight::deduce(&vars), Ok(TerminalHeight::Automatic));
+        }
+
+        #[test]
+        fn bad_lines() {
+            let vars = Some(OsString::from("tall"));
+            assert!(matches!(
+                TerminalHeight::deduce(&vars),
+                Err(OptionsError::FailedParse(_, NumberSource::Env("LINES"), _))
+            ));
+        }
+    }
+    mod columns {
+        use super::*;
+
+        fn collect(inputs: &[&str]) -> Vec<Column> {
+            let vars: Option<OsString> = None;
+            let results = parse_for_test(inputs, TEST_ARGS, Last, |mf| Columns::deduce(mf, &vars));
+            results[0].as_ref().unwrap().collect(true, true, false)
+        }
+
+        #[test]
+        fn in_given_order() {
+            assert_eq!(
+                collect(&["--columns=size,perms,modified,name"]),
+                vec![
+                    Column::FileSize,
+                    Column::Permissions,
+                    Column::Timestamp(TimeType::Modified)
+                ]
+            );
+        }
+
+        #[test]
+        fn time_follows_time_flags() {
+            assert_eq!(
+                collect(&["--columns=time,size", "--accessed", "--modified"]),
+                vec![
+                    Column::Timestamp(TimeType::Modified),
+                    Column::Timestamp(TimeType::Accessed),
+                    Column::FileSize
+                ]
+            );
+        }
+
+        #[test]
+        fn overrides_column_flags() {
+            assert_eq!(
+                collect(&["--columns=perms", "--inode", "--links"]),
+                vec![Column::Permissions]
+            );
+        }
+
+        #[test]
+        fn git_since_after_the_rest() {
+            assert_eq!(
+                collect(&["--git-age-since=v1.0"]).last(),
+                Some(&Column::GitSince)
+            );
+        }
+
+        #[test]
+        fn git_since_in_given_order() {
+            assert_eq!(
+                collect(&["--columns=git-since,size", "--git-age-since", "v1.0"]),
+                vec![Column::GitSince, Column::FileSize]
+            );
+        }
+
+        #[test]
+        fn git_since_needs_a_reference() {
+            assert_eq!(
+                collect(&["--columns=size,git-since"]),
+                vec![Column::FileSize]
+            );
+        }
+
+        #[test]
+        fn git_since_without_git() {
+            assert!(!collect(&["--git-age-since=v1.0", "--no-git"]).contains(&Column::GitSince));
+        }
+    }
+
+    mod colour_scales {
+        use super::*;
+
+        test!(relative:      ColorScaleOptions <- ["--color-scale=size"], None;                            Both => like Ok(ColorScaleOptions { size: true, size_thresholds: None, .. }));
+        test!(no_floor:      ColorScaleOptions <- ["--color-scale=size", "--no-color-scale-floor"], None;  Both => like Ok(ColorScaleOptions { size_thresholds: Some(SizeThresholds([1024, ..])), .. }));
+        test!(no_floor_u:    ColorScaleOptions <- ["--colour-scale", "--no-colour-scale-floor"], None;     Both => like Ok(ColorScaleOptions { size_thresholds: Some(_), .. }));
+        test!(custom_sizes:  ColorScaleOptions <- ["--color-scale", "--no-color-scale-floor"], Some("1k, 1M, 1G, 1T".into()); Both => like Ok(ColorScaleOptions { size_thresholds: Some(SizeThresholds([1000, 1_000_000, 1_000_000_000, 1_000_000_000_000])), .. }));
+        test!(bad_sizes:     ColorScaleOptions <- ["--color-scale", "--no-color-scale-floor"], Some("1G,1M".into());          Both => like Ok(ColorScaleOptions { size_thresholds: Some(SizeThresholds([1024, ..])), .. }));
+        test!(just_age:      ColorScaleOptions <- ["--color-scale=age", "--no-color-scale-floor"], None;   Last => like Ok(ColorScaleOptions { size_thresholds: None, .. }));
+        test!(useless:       ColorScaleOptions <- ["--no-color-scale-floor"], None;                        Complain => err OptionsError::Useless(&flags::NO_COLOR_SCALE_FLOOR, false, &flags::COLOR_SCALE));
+        test!(own_time:      ColorScaleOptions <- ["--color-scale=age"], None;                             Both => like Ok(ColorScaleOptions { time: None, .. }));
+        test!(ctime:         ColorScaleOptions <- ["--color-scale=age", "--color-scale-time=ctime"], None; Both => like Ok(ColorScaleOptions { time: Some(TimeType::Changed), .. }));
+        test!(accessed:      ColorScaleOptions <- ["--colour-scale", "--colour-scale-time", "accessed"], None; Both => like Ok(ColorScaleOptions { time: Some(TimeType::Accessed), .. }));
+        test!(bad_time:      ColorScaleOptions <- ["--color-scale", "--color-scale-time=yesterday"], None; Both => err OptionsError::BadArgument(&flags::COLOR_SCALE_TIME, OsString::from("yesterday")));
+        test!(depth_8:       ColorScaleOptions <- ["--color-depth=8"], None;                               Both => like Ok(ColorScaleOptions { depth: ColorDepth::Eight, .. }));
+        test!(depth_256:     ColorScaleOptions <- ["--colour-depth", "256"], Some("truecolor".into());     Both => like Ok(ColorScaleOptions { depth: ColorDepth::Palette, .. }));
+        test!(depth_24:      ColorScaleOptions <- ["--color-depth=truecolor"], None;                       Both => like Ok(ColorScaleOptions { depth: ColorDepth::TrueColor, .. }));
+        test!(bad_depth:     ColorScaleOptions <- ["--color-depth=16"], None;                              Both => err OptionsError::BadArgument(&flags::COLOR_DEPTH, OsString::from("16")));
+        test!(colorterm:     ColorScaleOptions <- [], Some("truecolor".into());                            Both => like Ok(ColorScaleOptions { depth: ColorDepth::TrueColor, .. }));
+        test!(console:       ColorScaleOptions <- [], Some("linux".into());                                Both => like Ok(ColorScaleOptions { depth: ColorDepth::Eight, .. }));
+        test!(no_term:       ColorScaleOptions <- [], None;                                                Both => like Ok(ColorScaleOptions { depth: ColorDepth::Palette, .. }));
+        test!(time_no_age:   ColorScaleOptions <- ["--color-scale=size", "--color-scale-time=btime"], None; Complain => err OptionsError::Useless(&flags::COLOR_SCALE_TIME, false, &flags::COLOR_SCALE));
+    }
+
+    mod comparisons {
+        use super::*;
+
+        test!(compare:       View <- ["--compare"], None;               Both => like Ok(View { compare: true, .. }));
+        test!(no_compare:    View <- [], None;                          Both => like Ok(View { compare: false, .. }));
+        test!(recurse:       View <- ["--compare", "--recurse"], None;  Complain => err OptionsError::Useless(&flags::RECURSE, true, &flags::COMPARE));
+        test!(tree:          View <- ["--tree", "--compare"], None;     Complain => err OptionsError::Useless(&flags::TREE, true, &flags::COMPARE));
+    }
+
+    mod formats {
+        use super::*;
+
+        test!(empty:         OutputFormat <- [];                         Both => like Ok(OutputFormat::Text));
+        test!(jsonl:         OutputFormat <- ["--format=jsonl"];         Both => like Ok(OutputFormat::JsonLines));
+        test!(text:          OutputFormat <- ["--format", "text"];       Both => like Ok(OutputFormat::Text));
+        test!(html:          OutputFormat <- ["--format=html"];          Both => like Ok(OutputFormat::Html));
+        test!(overridden:    OutputFormat <- ["--format=jsonl", "--format=text"]; Last => like Ok(OutputFormat::Text));
+        test!(bad:           OutputFormat <- ["--format=xml"];           Both => err OptionsError::BadArgument(&flags::FORMAT, OsString::from("xml")));
+        test!(template:      OutputFormat <- ["--format-template={name}"];  Both => like Ok(OutputFormat::Template));
+        test!(both:          OutputFormat <- ["--format=jsonl", "--format-template={name}"];  Both => err OptionsError::Conflict(&flags::FORMAT_TEMPLATE, &flags::FORMAT));
+        test!(bad_template:  View <- ["--format-template={colour}"], None;  Both => err OptionsError::FailedFormatTemplate(template::TemplateError::UnknownField("colour".into())));
+    }
+
+    mod nulls {
+        use super::*;
+
+        test!(null:          View <- ["-0"], None;                      Both => like Ok(View { null_terminated: true, mode: Mode::Lines, .. }));
+        test!(not_null:      View <- ["-1"], None;                      Both => like Ok(View { null_terminated: false, .. }));
+    }
+
+    mod sections {
+        use super::*;
+        use crate::output::section::{Options as Sections, TemplateError};
+
+        test!(empty:         Sections <- [], None;                             Both => like Ok(Sections { header: None, footer: None }));
+        test!(header:        Sections <- ["--dir-header={path}"], None;        Both => like Ok(Sections { header: Some(_), footer: None }));
+        test!(footer:        Sections <- ["--dir-footer", "{count}"], None;    Both => like Ok(Sections { header: None, footer: Some(_) }));
+        test!(from_env:      Sections <- [], Some("== {name} ==".into());      Both => like Ok(Sections { header: Some(_), footer: Some(_) }));
+        test!(empty_env:     Sections <- [], Some(OsString::new());            Both => like Ok(Sections { header: None, footer: None }));
+        test!(bad_field:     Sections <- ["--dir-header={size}"], None;        Both => err OptionsError::FailedTemplate(NumberSource::Arg(&flags::DIR_HEADER), TemplateError::UnknownField("size".into())));
+        test!(bad_env:       Sections <- [], Some("{path".into());             Both => err OptionsError::FailedTemplate(NumberSource::Env("EZA_DIR_HEADER"), TemplateError::Unclosed));
+        test!(twice:         Sections <- ["--dir-header=a", "--dir-header=b"], None; Complain => err OptionsError::Duplicate(Flag::Long("dir-header"), Flag::Long("dir-header")));
+    }
+
+    mod json_fields {
+        use super::*;
+        use crate::output::json::{Field, Options as Json};
+
+        test!(all:           Json <- [];                                          Both => Ok(Json::default()));
+        test!(picked:        Json <- ["--format=jsonl", "--fields=name,size,mtime,git"]; Both => Ok(Json { fields: vec![Field::Name, Field::Size, Field::Modified, Field::Git] }));
+        test!(repeated:      Json <- ["--format=jsonl", "--fields", "size,size"]; Both => Ok(Json { fields: vec![Field::Size] }));
+        test!(unknown:       Json <- ["--format=jsonl", "--fields=name,colour"];  Both => err OptionsError::BadArgument(&flags::FIELDS, OsString::from("colour")));
+        test!(empty:         Json <- ["--format=jsonl", "--fields", ""];         Both => err OptionsError::BadArgument(&flags::FIELDS, OsString::from("")));
+        test!(useless:       Json <- ["--fields=name"];                           Complain => err OptionsError::Useless(&flags::FIELDS, false, &flags::FORMAT));
+        test!(ignored:       Json <- ["--fields=name"];                           Last => Ok(Json { fields: vec![Field::Name] }));
+    }
+
+    mod summaries {
+        use super::*;
+        use crate::output::summary::Options as Summary;
+
+        test!(none:          Summary <- [];                                      Both => Ok(None));
+        test!(ext:           Summary <- ["--summary=ext"];                       Both => Ok(Some(Summary { sort: SummarySort::Size })));
+        test!(by_count:      Summary <- ["--summary=ext", "--summary-sort=count"]; Both => Ok(Some(Summary { sort: SummarySort::Count })));
+        test!(by_ext:        Summary <- ["--summary", "ext", "--summary-sort", "ext"]; Both => Ok(Some(Summary { sort: SummarySort::Extension })));
+        test!(bad:           Summary <- ["--summary=dir"];                       Both => err OptionsError::BadArgument(&flags::SUMMARY, OsString::from("dir")));
+        test!(bad_sort:      Summary <- ["--summary=ext", "--summary-sort=name"]; Both => err OptionsError::BadArgument(&flags::SUMMARY_SORT, OsString::from("name")));
+        test!(useless:       Summary <- ["--summary-sort=size"];                 Complain => err OptionsError::Useless(&flags::SUMMARY_SORT, false, &flags::SUMMARY));
+        test!(ignored:       Summary <- ["--summary-sort=size"];                 Last => Ok(None));
+        test!(tree:          Summary <- ["--summary=ext", "--tree"];             Complain => err OptionsError::Useless(&flags::TREE, true, &flags::SUMMARY));
+    }
+
+    mod link_owners {
+        use super::*;
+
+        test!(deref:         View <- ["-X"], None;                            Both => like Ok(View { deref_links: true, keep_link_owners: false, .. }));
+        test!(keep:          View <- ["-X", "--no-dereference-owner"], None;  Both => like Ok(View { deref_links: true, keep_link_owners: true, .. }));
+        test!(no_deref:      View <- ["--no-dereference-owner"], None;        Last => like Ok(View { deref_links: false, .. }));
+        test!(useless:       View <- ["--no-dereference-owner"], None;        Complain => err OptionsError::Useless(&flags::NO_DEREF_OWNER, false, &flags::DEREF_LINKS));
+    }
+
+    mod stats {
+        use super::*;
+
+        test!(stat:          View <- ["--stat"], None;                  Both => like Ok(View { stat: true, .. }));
+        test!(no_stat:       View <- [], None;                          Both => like Ok(View { stat: false, .. }));
+        test!(recurse:       View <- ["--stat", "--recurse"], None;     Complain => err OptionsError::Useless(&flags::RECURSE, true, &flags::STAT));
+        test!(compare:       View <- ["--compare", "--stat"], None;     Complain => err OptionsError::Useless(&flags::COMPARE, true, &flags::STAT));
+    }
+
+    mod pagings {
+        use super::*;
+
+        test!(empty:         Paging <- [], None;                          Both => like Ok(Paging::Never));
+        test!(bare:          Paging <- ["--paging"], None;                Both => like Ok(Paging::Auto(TerminalHeight::Automatic)));
+        test!(always:        Paging <- ["--paging=always"], None;         Both => like Ok(Paging::Always));
+        test!(never:         Paging <- ["--paging", "never"], None;       Both => like Ok(Paging::Never));
+        test!(lines:         Paging <- ["--paging=auto"], Some("40".into()); Both => like Ok(Paging::Auto(TerminalHeight::Set(40))));
+        test!(overridden:    Paging <- ["--paging=always", "--paging=never"], None; Last => like Ok(Paging::Never));
+        test!(bad:           Paging <- ["--paging=sometimes"], None;      Both => err OptionsError::BadArgument(&flags::PAGING, OsString::from("sometimes")));
+    }
 }
@@ -4,7 +4,8 @@ use std::iter::Sum;
 use std::ops::{Add, Deref, DerefMut};
 
 use nu_ansi_term::{AnsiString as ANSIString, AnsiStrings as ANSIStrings, Style};
-use unicode_width::UnicodeWidthStr;
+
+use crate::output::width::str_width;
 
 /// An individual cell that holds text in a table, used in the details and
 /// lines views to store ANSI-terminal-formatted data before it is printed.
@@ -190,7 +191,7 @@ pub struct DisplayWidth(usize);
 
 impl<'a> From<&'a str> for DisplayWidth {
     fn from(input: &'a str) -> Self {
-        Self(UnicodeWidthStr::width(input))
+        Self(str_width(input))
     }
 }
 
@@ -1,5 +1,6 @@
 use log::trace;
 use nu_ansi_term::{Color as Colour, Style};
+use number_prefix::Prefix;
 use palette::{FromColor, LinSrgb, Oklab, Srgb};
 
 use crate::{
@@ -14,6 +15,73 @@ pub struct ColorScaleOptions {
 
     pub size: bool,
     pub age: bool,
+
+    /// The time field that every timestamp column gets shaded by, rather
+    /// than by the time it shows.
+    pub time: Option<TimeType>,
+
+    /// The fixed thresholds that sizes get placed between, if they aren’t
+    /// scaled relative to the other files in the listing.
+    pub size_thresholds: Option<SizeThresholds>,
+
+    /// How many colours the terminal can show, which is how smooth the
+    /// gradients can be.
+    pub depth: ColorDepth,
+}
+
+/// How many colours a terminal can show.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum ColorDepth {
+    /// Just the eight standard colours and their bright versions, which
+    /// aren’t enough to draw a gradient with, so everything keeps the
+    /// colour its theme gives it.
+    Eight,
+
+    /// The 256-colour palette, which gradients get rounded to.
+    Palette,
+
+    /// Any 24-bit colour.
+    TrueColor,
+}
+
+/// The sizes, in bytes, at which the size colour scale moves from bytes to
+/// kilobytes, kilobytes to megabytes, and so on up to terabytes.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct SizeThresholds(pub [u64; 4]);
+
+impl Default for SizeThresholds {
+    fn default() -> Self {
+        Self([1 << 10, 1 << 20, 1 << 30, 1 << 40])
+    }
+}
+
+impl SizeThresholds {
+    /// The prefix whose colour a size gets, and how far it is between the
+    /// thresholds either side of it, on a logarithmic scale.
+    pub fn place(self, size: u64) -> (Option<Prefix>, f32) {
+        const PREFIXES: [Option<Prefix>; 5] = [
+            None,
+            Some(Prefix::Kibi),
+            Some(Prefix::Mebi),
+            Some(Prefix::Gibi),
+            Some(Prefix::Tebi),
+        ];
+
+        let band = self
+            .0
+            .iter()
+            .take_while(|threshold| size >= **threshold)
+            .count();
+        let Some(&upper) = self.0.get(band) else {
+            return (PREFIXES[band], 1.0);
+        };
+
+        let lower = if band == 0 { 0 } else { self.0[band - 1] };
+        let log = |bytes: u64| (bytes as f32 + 1.0).ln();
+        let ratio = (log(size) - log(lower)) / (log(upper) - log(lower));
+
+        (PREFIXES[band], ratio.clamp(0.0, 1.0))
+    }
 }
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
@@ -69,24 +137,41 @@ impl ColorScaleInformation {
         }
     }
 
-    pub fn adjust_style(&self, mut style: Style, value: f32, range: Option<Extremes>) -> Style {
-        if let (Some(fg), Some(range)) = (style.foreground, range) {
-            let mut ratio = ((value - range.min) / (range.max - range.min)).clamp(0.0, 1.0);
-            if ratio.is_nan() {
-                ratio = 1.0;
-            }
+    pub fn adjust_style(&self, style: Style, value: f32, range: Option<Extremes>) -> Style {
+        let Some(range) = range else {
+            return style;
+        };
 
-            style.foreground = Some(adjust_luminance(
-                fg,
-                ratio,
-                self.options.min_luminance as f32 / 100.0,
-            ));
+        let mut ratio = ((value - range.min) / (range.max - range.min)).clamp(0.0, 1.0);
+        if ratio.is_nan() {
+            ratio = 1.0;
+        }
+
+        self.adjust_style_by_ratio(style, ratio)
+    }
+
+    /// Dim the style’s colour by how far along the scale something is, from
+    /// 0 for the dimmest to 1 for the brightest.
+    pub fn adjust_style_by_ratio(&self, mut style: Style, ratio: f32) -> Style {
+        if self.options.depth == ColorDepth::Eight {
+            return style;
+        }
+
+        if let Some(fg) = style.foreground {
+            let adjusted = adjust_luminance(fg, ratio, self.options.min_luminance as f32 / 100.0);
+            style.foreground = Some(match (self.options.depth, adjusted) {
+                (ColorDepth::Palette, Colour::Rgb(r, g, b)) => {
+                    Colour::Fixed(nearest_in_palette(r, g, b))
+                }
+                _ => adjusted,
+            });
         }
 
         style
     }
 
     pub fn apply_time_gradient(&self, style: Style, file: &File<'_>, time_type: TimeType) -> Style {
+        let time_type = self.options.time.unwrap_or(time_type);
         let range = match time_type {
             TimeType::Modified => self.modified,
             TimeType::Changed => self.changed,
@@ -135,7 +220,7 @@ fn update_information_recursively(
             );
         }
 
-        if information.options.size {
+        if information.options.size && information.options.size_thresholds.is_none() {
             let size = match file.size() {
                 Size::Some(size) => Some(size as f32),
                 _ => None,
@@ -232,7 +317,20 @@ fn adjust_luminance(color: Colour, x: f32, min_l: f32) -> Colour {
 
         Colour::LightPurple | Colour::Purple => LinSrgb::new(0.5, 0.0, 0.5),
 
-        _ => LinSrgb::new(1.0, 1.0, 1.0),
+        Colour::Fixed(index @ 0..=15) => {
+            return adjust_luminance(STANDARD_COLOURS[usize::from(index)], x, min_l)
+        }
+
+        Colour::Fixed(index) => {
+            let (r, g, b) = palette_rgb(index);
+            LinSrgb::new(
+                f32::from(r) / 255.0,
+                f32::from(g) / 255.0,
+                f32::from(b) / 255.0,
+            )
+        }
+
+        Colour::Default => LinSrgb::new(1.0, 1.0, 1.0),
     };
 
     let mut lab: Oklab = Oklab::from_color(rgb_color);
@@ -245,3 +343,125 @@ fn adjust_luminance(color: Colour, x: f32, min_l: f32) -> Colour {
         (adjusted_rgb.blue * 255.0).round() as u8,
     )
 }
+
+/// The first sixteen colours of the 256-colour palette, which are the
+/// standard colours and their bright versions, in order.
+const STANDARD_COLOURS: [Colour; 16] = [
+    Colour::Black,
+    Colour::Red,
+    Colour::Green,
+    Colour::Yellow,
+    Colour::Blue,
+    Colour::Purple,
+    Colour::Cyan,
+    Colour::LightGray,
+    Colour::DarkGray,
+    Colour::LightRed,
+    Colour::LightGreen,
+    Colour::LightYellow,
+    Colour::LightBlue,
+    Colour::LightPurple,
+    Colour::LightCyan,
+    Colour::White,
+];
+
+/// The levels each channel of the palette’s 6×6×6 colour cube can be at.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The colour of one of the palette’s colours from the colour cube or the
+/// greyscale ramp after it.
+fn palette_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        16..=231 => {
+            let index = index - 16;
+            let level = |n: u8| CUBE_LEVELS[usize::from(n)];
+            (level(index / 36), level(index / 6 % 6), level(index % 6))
+        }
+        232..=255 => {
+            let grey = 8 + (index - 232) * 10;
+            (grey, grey, grey)
+        }
+        _ => (255, 255, 255),
+    }
+}
+
+/// The colour from the palette’s colour cube or greyscale ramp that’s
+/// closest to the given one. The first sixteen get left out, as terminals
+/// tend to have their own ideas about what those look like.
+fn nearest_in_palette(r: u8, g: u8, b: u8) -> u8 {
+    let nearest_level = |channel: u8| {
+        (0..6)
+            .min_by_key(|level| CUBE_LEVELS[*level].abs_diff(channel))
+            .unwrap_or(0) as u8
+    };
+    let cube = 16 + 36 * nearest_level(r) + 6 * nearest_level(g) + nearest_level(b);
+
+    let average = (u16::from(r) + u16::from(g) + u16::from(b)) / 3;
+    let grey = 232 + (average.saturating_sub(3) / 10).min(23) as u8;
+
+    let distance = |index: u8| {
+        let (pr, pg, pb) = palette_rgb(index);
+        let square = |a: u8, b: u8| u32::from(a.abs_diff(b)).pow(2);
+        square(r, pr) + square(g, pg) + square(b, pb)
+    };
+
+    if distance(grey) < distance(cube) {
+        grey
+    } else {
+        cube
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn small_sizes() {
+        assert_eq!(SizeThresholds::default().place(0), (None, 0.0));
+        assert_eq!(SizeThresholds::default().place(1023).0, None);
+    }
+
+    #[test]
+    fn on_a_threshold() {
+        assert_eq!(
+            SizeThresholds::default().place(1 << 20),
+            (Some(Prefix::Mebi), 0.0)
+        );
+    }
+
+    #[test]
+    fn between_thresholds() {
+        let (prefix, ratio) = SizeThresholds::default().place(2 << 30);
+        assert_eq!(prefix, Some(Prefix::Gibi));
+        assert!(ratio > 0.0 && ratio < 0.2);
+    }
+
+    #[test]
+    fn palette_colours() {
+        assert_eq!(palette_rgb(16), (0, 0, 0));
+        assert_eq!(palette_rgb(196), (255, 0, 0));
+        assert_eq!(palette_rgb(244), (128, 128, 128));
+    }
+
+    #[test]
+    fn nearest_cube_colour() {
+        assert_eq!(nearest_in_palette(255, 0, 0), 196);
+        assert_eq!(nearest_in_palette(0, 100, 200), 26);
+    }
+
+    #[test]
+    fn nearest_grey() {
+        assert_eq!(nearest_in_palette(128, 128, 128), 244);
+        assert_eq!(nearest_in_palette(0, 0, 0), 16);
+        assert_eq!(nearest_in_palette(255, 255, 255), 231);
+    }
+
+    #[test]
+    fn past_the_last_threshold() {
+        assert_eq!(
+            SizeThresholds::default().place(u64::MAX),
+            (Some(Prefix::Tebi), 1.0)
+        );
+    }
+}
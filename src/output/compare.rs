@@ -0,0 +1,244 @@
+//! Listing two directories against each other, for `--compare`.
+//!
+//! Every name found in either directory gets one line, with a marker in
+//! front of it saying how the two sides differ: `A` for files that are only
+//! in the first directory, `B` for files that are only in the second, `±`
+//! for files that differ, `~` for files with the same contents but different
+//! modification times, and `=` for files that are the same.
+//!
+//! Regular files of the same size have their contents compared byte by
+//! byte, so two files are never called the same just because their sizes
+//! and timestamps happen to match.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+use log::*;
+use nu_ansi_term::{AnsiStrings as ANSIStrings, Style};
+
+use crate::fs::filter::FileFilter;
+use crate::fs::metadata::Kind;
+use crate::fs::File;
+use crate::output::file_name::Options as FileStyle;
+use crate::theme::Theme;
+
+/// How a file in one directory compares to the file with the same name in
+/// the other.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Comparison {
+    /// The file is only in the first directory.
+    OnlyLeft,
+
+    /// The file is only in the second directory.
+    OnlyRight,
+
+    /// Both files exist, but they’re of different kinds, have different
+    /// contents, or are links to different places.
+    Differ,
+
+    /// Both files have the same contents, but were modified at different
+    /// times.
+    Touched,
+
+    /// Both files are the same.
+    Same,
+}
+
+impl Comparison {
+    /// Compare two files with the same name. Directories are only compared
+    /// by kind, not by what’s inside them.
+    pub fn between(left: &File<'_>, right: &File<'_>) -> Self {
+        let kind = left.metadata.kind();
+        if kind != right.metadata.kind() {
+            return Self::Differ;
+        }
+
+        if kind == Kind::Symlink {
+            return if fs::read_link(&left.path).ok() == fs::read_link(&right.path).ok() {
+                Self::Same
+            } else {
+                Self::Differ
+            };
+        }
+
+        if kind != Kind::File {
+            return Self::Same;
+        }
+
+        if left.metadata.len() != right.metadata.len() {
+            return Self::Differ;
+        }
+
+        match same_contents(&left.path, &right.path) {
+            Ok(true) if left.modified_time() == right.modified_time() => Self::Same,
+            Ok(true) => Self::Touched,
+            Ok(false) => Self::Differ,
+            Err(e) => {
+                debug!(
+                    "Error comparing {:?} with {:?}: {}",
+                    left.path, right.path, e
+                );
+                Self::Differ
+            }
+        }
+    }
+
+    /// The marker shown at the start of the line.
+    fn marker(self) -> &'static str {
+        match self {
+            Self::OnlyLeft => "A",
+            Self::OnlyRight => "B",
+            Self::Differ => "±",
+            Self::Touched => "~",
+            Self::Same => "=",
+        }
+    }
+
+    /// The style to paint the marker in. These borrow the Git colours, with
+    /// the first directory playing the part of the old version.
+    fn style(self, theme: &Theme) -> Style {
+        match self {
+            Self::OnlyLeft => theme.ui.git.deleted,
+            Self::OnlyRight => theme.ui.git.new,
+            Self::Differ => theme.ui.git.modified,
+            Self::Touched => theme.ui.git.typechange,
+            Self::Same => theme.ui.punctuation,
+        }
+    }
+}
+
+/// Whether two files have exactly the same contents.
+fn same_contents(left: &Path, right: &Path) -> io::Result<bool> {
+    same_bytes(fs::File::open(left)?, fs::File::open(right)?)
+}
+
+/// Whether two readers produce exactly the same bytes.
+fn same_bytes<L: Read, R: Read>(mut left: L, mut right: R) -> io::Result<bool> {
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    let mut left_chunk = vec![0; CHUNK_SIZE];
+    let mut right_chunk = vec![0; CHUNK_SIZE];
+
+    loop {
+        let left_len = fill(&mut left, &mut left_chunk)?;
+        let right_len = fill(&mut right, &mut right_chunk)?;
+
+        if left_chunk[..left_len] != right_chunk[..right_len] {
+            return Ok(false);
+        }
+
+        if left_len == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Read into the buffer until it’s full or the reader runs out, returning
+/// how much was read.
+fn fill<R: Read>(reader: &mut R, buffer: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match reader.read(&mut buffer[filled..])? {
+            0 => break,
+            read => filled += read,
+        }
+    }
+
+    Ok(filled)
+}
+
+/// One line of the listing: the file to show, taken from the first
+/// directory if it’s in both, and how it compares.
+struct Entry<'a> {
+    file: File<'a>,
+    comparison: Comparison,
+}
+
+impl<'a> AsRef<File<'a>> for Entry<'a> {
+    fn as_ref(&self) -> &File<'a> {
+        &self.file
+    }
+}
+
+/// The comparison view lists the union of two directories’ contents, one
+/// file per line, with a marker for each.
+pub struct Render<'a> {
+    pub left: Vec<File<'a>>,
+    pub right: Vec<File<'a>>,
+    pub theme: &'a Theme,
+    pub file_style: &'a FileStyle,
+    pub filter: &'a FileFilter,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(self, w: &mut W) -> io::Result<()> {
+        let mut right = self
+            .right
+            .into_iter()
+            .map(|file| (file.name.clone(), file))
+            .collect::<HashMap<_, _>>();
+
+        let mut entries = Vec::new();
+        for file in self.left {
+            let comparison = match right.remove(&file.name) {
+                Some(other) => Comparison::between(&file, &other),
+                None => Comparison::OnlyLeft,
+            };
+            entries.push(Entry { file, comparison });
+        }
+
+        entries.extend(right.into_values().map(|file| Entry {
+            file,
+            comparison: Comparison::OnlyRight,
+        }));
+
+        self.filter.sort_files(&mut entries);
+        for entry in &entries {
+            let marker = entry
+                .comparison
+                .style(self.theme)
+                .paint(entry.comparison.marker());
+
+            let name = self
+                .file_style
+                .for_file(&entry.file, self.theme)
+                .with_link_paths()
+                .paint();
+
+            writeln!(w, "{} {}", marker, ANSIStrings(&name))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn same_bytes_in_different_chunks() {
+        let left = io::Read::chain(&b"ab"[..], &b"cd"[..]);
+        assert!(same_bytes(left, &b"abcd"[..]).unwrap());
+    }
+
+    #[test]
+    fn different_bytes() {
+        assert!(!same_bytes(&b"abcd"[..], &b"abce"[..]).unwrap());
+    }
+
+    #[test]
+    fn one_is_longer() {
+        assert!(!same_bytes(&b"abc"[..], &b"abcd"[..]).unwrap());
+    }
+
+    #[test]
+    fn fills_across_short_reads() {
+        let mut reader = io::Read::chain(&b"ab"[..], &b"cd"[..]);
+        let mut buffer = [0; 3];
+        assert_eq!(fill(&mut reader, &mut buffer).unwrap(), 3);
+        assert_eq!(&buffer, b"abc");
+    }
+}
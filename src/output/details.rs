@@ -58,8 +58,15 @@
 //! Each column in the table needs to be resized to fit its widest argument. This
 //! means that we must wait until every row has been added to the table before it
 //! can be displayed, in order to make sure that every column is wide enough.
+//!
+//! A tree without a table has no columns to measure, so its rows get written
+//! out as soon as they’re made instead, one directory at a time on the way
+//! down. This means a huge tree starts appearing straight away, and only the
+//! directories leading to the row being written have to be held on to.
 
 use std::io::{self, Write};
+use std::mem;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::vec::IntoIter as VecIntoIter;
 
@@ -70,15 +77,24 @@ use log::*;
 
 use crate::fs::dir_action::RecurseOptions;
 use crate::fs::feature::git::GitCache;
-use crate::fs::feature::xattr::Attribute;
+use crate::fs::feature::trash;
+use crate::fs::feature::xattr::{self, Attribute};
+use crate::fs::fields as f;
 use crate::fs::fields::SecurityContextType;
 use crate::fs::filter::FileFilter;
 use crate::fs::{Dir, File};
 use crate::output::cell::TextCell;
 use crate::output::color_scale::{ColorScaleInformation, ColorScaleOptions};
-use crate::output::file_name::Options as FileStyle;
-use crate::output::table::{Options as TableOptions, Row as TableRow, Table};
-use crate::output::tree::{TreeDepth, TreeParams, TreeTrunk};
+use crate::output::file_name::{HardLinkGroups, Options as FileStyle};
+use crate::output::group::{self, Group};
+use crate::output::table::{
+    Options as TableOptions, Row as TableRow, SharedWidths, SizeFormat, Table,
+};
+use crate::output::total::Totals;
+use crate::output::tree::{TreeDepth, TreeParams, TreePart, TreeStyle, TreeTrunk};
+use crate::output::width::str_width;
+use crate::output::{TerminalHeight, TerminalWidth};
+use crate::progress;
 use crate::theme::Theme;
 
 /// With the **Details** view, the output gets formatted into columns, with
@@ -105,9 +121,21 @@ pub struct Options {
     /// Whether to show a header line or not.
     pub header: bool,
 
+    /// Whether to show a second header line, with the units each column is
+    /// in and which one the files are sorted by.
+    pub header_units: bool,
+
+    /// Whether to show the header line again each time a screenful of rows
+    /// has gone by, and how tall that screen is.
+    pub header_repeat: Option<TerminalHeight>,
+
     /// Whether to show each file’s extended attributes.
     pub xattr: bool,
 
+    /// How many characters of each extended attribute’s value to preview,
+    /// or `None` to show them the usual way.
+    pub xattr_values: Option<usize>,
+
     /// Whether to show each file's security attribute.
     pub secattr: bool,
 
@@ -115,6 +143,43 @@ pub struct Options {
     pub mounts: bool,
 
     pub color_scale: ColorScaleOptions,
+
+    /// Whether to annotate each directory in a tree with the total size of
+    /// everything inside it, and which size to use. This is only done when
+    /// there’s no table, as otherwise the size column already shows it.
+    pub subtree_size: Option<SubtreeSize>,
+
+    /// Whether to annotate each directory in a tree with how many files are
+    /// inside it, at any depth, and their total size.
+    pub show_counts: bool,
+
+    /// Whether to leave directories out of a tree when nothing inside them,
+    /// at any depth, makes it through the filters.
+    pub prune: bool,
+
+    /// Which characters to draw a tree’s branches with.
+    pub tree_style: TreeStyle,
+
+    /// How many levels apart a tree’s branch lines get their depths written
+    /// in place of them, to help keep track of where a deep tree is.
+    pub depth_guides: Option<NonZeroUsize>,
+
+    /// Whether every directory in a recursive listing gets its columns made
+    /// as wide as the widest directory needs, so they all line up.
+    pub align_sections: bool,
+
+    /// How to split each listing into sections with headings, if at all.
+    pub group_by: Option<group::Options>,
+}
+
+/// Which total gets shown next to directories in a tree.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum SubtreeSize {
+    /// The sum of the sizes of all the files inside.
+    Apparent,
+
+    /// The space everything inside takes up on disk, like `du` reports.
+    OnDisk,
 }
 
 pub struct Render<'a> {
@@ -138,6 +203,29 @@ pub struct Render<'a> {
     pub git: Option<&'a GitCache>,
 
     pub git_repos: bool,
+
+    /// The widths that the table’s columns should be widened to, so that
+    /// they line up with the other directories’ tables.
+    pub shared_widths: Option<&'a SharedWidths>,
+
+    /// Whether to write a footer with the files’ totals after them, for
+    /// `--total`.
+    pub total: bool,
+
+    /// How to format the numbers in the footer and in the sizes added after
+    /// directory names, which is loaded once for the whole listing rather
+    /// than for each directory in it.
+    pub numerics: &'a locale::Numeric,
+}
+
+/// What every row gets rendered with that only has to be worked out once
+/// for the whole listing, rather than once for each directory in it.
+#[derive(Copy, Clone)]
+struct RowContext<'n> {
+    color_scale_info: Option<ColorScaleInformation>,
+
+    /// How to format the numbers in the sizes added after directory names.
+    numerics: &'n locale::Numeric,
 }
 
 #[rustfmt::skip]
@@ -146,6 +234,7 @@ struct Egg<'a> {
     xattrs:    &'a [Attribute],
     errors:    Vec<(io::Error, Option<PathBuf>)>,
     dir:       Option<Dir>,
+    is_loop:   bool,
     file:      &'a File<'a>,
 }
 
@@ -157,76 +246,345 @@ impl<'a> AsRef<File<'a>> for Egg<'a> {
 
 impl<'a> Render<'a> {
     pub fn render<W: Write>(mut self, w: &mut W) -> io::Result<()> {
-        let mut rows = Vec::new();
+        let context = RowContext {
+            color_scale_info: ColorScaleInformation::from_color_scale(
+                self.opts.color_scale,
+                &self.files,
+                self.filter.dot_filter,
+                self.git,
+                self.git_ignoring,
+                self.recurse,
+            ),
+            numerics: self.numerics,
+        };
 
-        let color_scale_info = ColorScaleInformation::from_color_scale(
-            self.opts.color_scale,
-            &self.files,
-            self.filter.dot_filter,
-            self.git,
-            self.git_ignoring,
-            self.recurse,
-        );
+        // Everything that gets listed is counted up for the footer, which in
+        // a tree includes the files inside the directories given.
+        let mut listed = Totals::default();
 
         if let Some(ref table) = self.opts.table {
-            match (self.git, self.dir) {
-                (Some(g), Some(d)) => {
-                    if !g.has_anything_for(&d.path) {
-                        self.git = None;
+            self.check_git_column();
+            let groups = self.arrange_groups();
+            let groups = groups.as_deref();
+            let (lines, totals) = self.table_lines(table, context, groups);
+            listed = totals;
+
+            if let Some(git) = self.git.filter(|g| g.is_pending()) {
+                // With --git=async, the table gets printed with placeholders
+                // in the Git column, then printed again over the top of itself
+                // once the statuses are in. Moving the cursor back up only
+                // lands in the right place when every line takes up exactly
+                // one row of a terminal and the whole table is still on the
+                // screen, so otherwise, just wait.
+                let fits_width = TerminalWidth::Automatic
+                    .actual_terminal_width()
+                    .is_some_and(|width| lines.iter().all(|line| *line.width < width));
+                let on_screen = fits_width
+                    && TerminalHeight::Automatic
+                        .actual_terminal_height()
+                        .is_some_and(|height| lines.len() < height);
+
+                if on_screen {
+                    for line in &lines {
+                        writeln!(w, "{}", line.strings())?;
                     }
-                }
-                (Some(g), None) => {
-                    if !self.files.iter().any(|f| g.has_anything_for(&f.path)) {
-                        self.git = None;
+                    w.flush()?;
+
+                    git.wait();
+                    write!(w, "\x1B[{}A", lines.len())?;
+                    for line in self.table_lines(table, context, groups).0 {
+                        writeln!(w, "\r\x1B[2K{}", line.strings())?;
+                    }
+                } else {
+                    git.wait();
+                    for line in self.table_lines(table, context, groups).0 {
+                        writeln!(w, "{}", line.strings())?;
                     }
                 }
-                (None, _) => { /* Keep Git how it is */ }
+            } else {
+                for line in lines {
+                    writeln!(w, "{}", line.strings())?;
+                }
             }
+        } else {
+            let mut stream = Stream {
+                writer: w,
+                branches: self.branches(),
+                error: None,
+            };
 
-            let mut table = Table::new(table, self.git, self.theme, self.git_repos);
-
-            if self.opts.header {
-                let header = table.header_row();
-                table.add_widths(&header);
-                rows.push(self.render_header(header));
+            // Hard links can only be marked once every row is in, so they
+            // get held back until then.
+            if self.file_style.hard_link_groups {
+                let mut rows = Vec::new();
+                self.add_files_to_table(
+                    &mut None,
+                    &mut rows,
+                    &mut listed,
+                    &self.files,
+                    TreeDepth::root(),
+                    context,
+                );
+
+                self.mark_hard_links(&mut rows);
+                for row in rows {
+                    stream.add(row);
+                }
+            } else {
+                self.add_files_to_table(
+                    &mut None,
+                    &mut stream,
+                    &mut listed,
+                    &self.files,
+                    TreeDepth::root(),
+                    context,
+                );
             }
 
-            // This is weird, but I can’t find a way around it:
-            // https://internals.rust-lang.org/t/should-option-mut-t-implement-copy/3715/6
-            let mut table = Some(table);
-            self.add_files_to_table(
-                &mut table,
-                &mut rows,
+            stream.finish()?;
+        }
+
+        if self.total {
+            let size_format = self
+                .opts
+                .table
+                .as_ref()
+                .map_or_else(SizeFormat::default, |t| t.size_format);
+            listed.write(w, self.theme, size_format, self.numerics)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds the widths of the table’s columns to the shared widths, without
+    /// printing anything, so that every directory’s table can be drawn with
+    /// the same widths.
+    pub fn measure(mut self, shared: &mut SharedWidths) {
+        let Some(ref options) = self.opts.table else {
+            return;
+        };
+
+        self.check_git_column();
+        let context = RowContext {
+            color_scale_info: ColorScaleInformation::from_color_scale(
+                self.opts.color_scale,
                 &self.files,
-                TreeDepth::root(),
-                color_scale_info,
-            );
+                self.filter.dot_filter,
+                self.git,
+                self.git_ignoring,
+                self.recurse,
+            ),
+            numerics: self.numerics,
+        };
+
+        let groups = self.arrange_groups();
+        let (table, _, _) = self.table_rows(options, context, groups.as_deref());
+        table.share_widths(shared);
+    }
+
+    /// Puts the files in order of the sections they’re shown in, if they’re
+    /// being split into any, returning where each section’s files are.
+    fn arrange_groups(&mut self) -> Option<Vec<Group>> {
+        let options = self.opts.group_by.as_ref()?;
+        Some(options.arrange(&mut self.files))
+    }
+
+    /// Leaves out the Git column when none of the files are in a repository.
+    /// A tree can have repositories nested anywhere inside it, which only
+    /// get found as it’s being listed, so it keeps the column until then,
+    /// and `drop_unused_git_column` takes it out afterwards.
+    fn check_git_column(&mut self) {
+        let is_tree = self.recurse.is_some_and(|r| r.tree);
+
+        match (self.git, self.dir) {
+            (Some(g), Some(d)) => {
+                g.discover_nested(&d.path);
+                if !is_tree && !g.has_anything_for(&d.path) {
+                    self.git = None;
+                }
+            }
+            (Some(g), None) => {
+                if !is_tree && !self.files.iter().any(|f| g.has_anything_for(&f.path)) {
+                    self.git = None;
+                }
+            }
+            (None, _) => { /* Keep Git how it is */ }
+        }
+    }
+
+    /// Takes the Git column back out of a tree’s table once it has been
+    /// listed, if no repository turned up in it after all, as otherwise the
+    /// column would only be full of placeholders.
+    fn drop_unused_git_column(&self, table: &mut Table<'_>, rows: &mut [Row]) {
+        let Some(git) = self.git else {
+            return;
+        };
+
+        if !self.recurse.is_some_and(|r| r.tree) {
+            return;
+        }
+
+        let found = match self.dir {
+            Some(dir) => git.has_anything_under(&dir.path),
+            None => self.files.iter().any(|f| git.has_anything_under(&f.path)),
+        };
+
+        if !found {
+            table.remove_git_columns(rows.iter_mut().filter_map(|row| row.cells.as_mut()));
+        }
+    }
+
+    /// Marks the files that are hard links to the same file as another one
+    /// in the listing. This waits until all of the listing’s rows have been
+    /// made, as in a tree, the other links can be anywhere further down.
+    fn mark_hard_links(&self, rows: &mut [Row]) {
+        if !self.file_style.hard_link_groups {
+            return;
+        }
 
-            for row in self.iterate_with_table(table.unwrap(), rows) {
-                writeln!(w, "{}", row.strings())?;
+        let groups = HardLinkGroups::new(rows.iter().filter_map(|row| row.hard_link));
+        for row in rows {
+            let Some(marker) = row.hard_link.and_then(|link| groups.marker(link, self.theme))
+            else {
+                continue;
+            };
+
+            let width = str_width(marker.as_str());
+            row.name.push(Style::default().paint(" "), 1);
+            row.name.push(marker, width);
+        }
+    }
+
+    /// Renders the files into the lines of a table, with the header at the
+    /// top (and again every so often, if it should be repeated), along with
+    /// the totals of the files that got a row.
+    fn table_lines(
+        &self,
+        options: &TableOptions,
+        context: RowContext<'_>,
+        groups: Option<&[Group]>,
+    ) -> (Vec<TextCell>, Totals) {
+        let (mut table, rows, totals) = self.table_rows(options, context, groups);
+        if let Some(shared) = self.shared_widths {
+            table.widen_to(shared);
+        }
+
+        // The header takes up a line of the screen itself, or two with its
+        // units, so it gets printed again after one screen’s worth of rows,
+        // less those.
+        let header_lines = if self.opts.header_units { 2 } else { 1 };
+        let repeat_every = self
+            .opts
+            .header_repeat
+            .and_then(TerminalHeight::actual_terminal_height)
+            .filter(|_| self.opts.header)
+            .map(|height| height.saturating_sub(header_lines).max(1));
+
+        let mut lines: Vec<TextCell> = Vec::new();
+        for (index, row) in self.iterate_with_table(table, rows).enumerate() {
+            if let Some(every) = repeat_every {
+                if index > header_lines && (index - header_lines) % every == 0 {
+                    lines.extend_from_within(..header_lines);
+                }
+            }
+
+            lines.push(row);
+        }
+
+        (lines, totals)
+    }
+
+    /// Fills a table with the files’ rows, and the header rows if there
+    /// are any, measuring how wide each column has to be. Files split into
+    /// sections get a heading before each one.
+    fn table_rows<'t>(
+        &'t self,
+        options: &'t TableOptions,
+        context: RowContext<'_>,
+        groups: Option<&[Group]>,
+    ) -> (Table<'t>, Vec<Row>, Totals) {
+        let mut rows = Vec::new();
+        let mut totals = Totals::default();
+        let trash = self.dir.is_some_and(|dir| trash::is_trash_dir(&dir.path));
+        let mut table = Table::new(options, self.git, self.theme, self.git_repos, trash);
+
+        // Like the Git column, a tree keeps the group column, as the files
+        // further down it aren’t known yet.
+        if !self.recurse.is_some_and(|r| r.tree) {
+            table.hide_redundant_groups(&self.files);
+        }
+
+        // This is weird, but I can’t find a way around it:
+        // https://internals.rust-lang.org/t/should-option-mut-t-implement-copy/3715/6
+        let mut table = Some(table);
+        if let Some(groups) = groups {
+            for group in groups {
+                rows.push(self.render_group_heading(group.heading));
+                self.add_files_to_table(
+                    &mut table,
+                    &mut rows,
+                    &mut totals,
+                    &self.files[group.range.clone()],
+                    TreeDepth::root(),
+                    context,
+                );
             }
         } else {
             self.add_files_to_table(
-                &mut None,
+                &mut table,
                 &mut rows,
+                &mut totals,
                 &self.files,
                 TreeDepth::root(),
-                color_scale_info,
+                context,
             );
+        }
+
+        // The header rows go at the top, but get made last, as which header
+        // each column gets can depend on how wide the files’ rows made it.
+        let mut table = table.unwrap();
+        self.drop_unused_git_column(&mut table, &mut rows);
+        self.mark_hard_links(&mut rows);
+        if self.opts.header {
+            let units = self.opts.header_units.then(|| table.units_row(self.filter));
+            if let Some(units) = &units {
+                table.add_widths(units);
+            }
 
-            for row in self.iterate(rows) {
-                writeln!(w, "{}", row.strings())?;
+            let header = table.header_row();
+            table.add_widths(&header);
+            let mut header_rows = vec![Row {
+                tree: TreeParams::new(TreeDepth::root(), false),
+                cells: Some(header),
+                name: table.name_header(),
+                hard_link: None,
+            }];
+
+            if let Some(units) = units {
+                header_rows.push(Row {
+                    tree: TreeParams::new(TreeDepth::root(), false),
+                    cells: Some(units),
+                    name: table.name_units(self.filter),
+                    hard_link: None,
+                });
             }
+
+            rows.splice(0..0, header_rows);
         }
 
-        Ok(())
+        (table, rows, totals)
     }
 
     /// Whether to show the extended attribute hint
     pub fn show_xattr_hint(&self, file: &File<'_>) -> bool {
         // Do not show the hint '@' if the only extended attribute is the security
-        // attribute and the security attribute column is active.
-        let xattr_count = file.extended_attributes().len();
+        // attribute and the security attribute column is active. Access
+        // control lists get a hint of their own.
+        let xattr_count = file
+            .extended_attributes()
+            .iter()
+            .filter(|attribute| !xattr::is_acl(&attribute.name))
+            .count();
         let selinux_ctx_shown = self.opts.secattr
             && match file.security_context().context {
                 SecurityContextType::SELinux(_) => true,
@@ -235,17 +593,103 @@ impl<'a> Render<'a> {
         xattr_count > 1 || (xattr_count == 1 && !selinux_ctx_shown)
     }
 
-    /// Adds files to the table, possibly recursively. This is easily
+    /// Add the total size of a directory’s subtree after its name, such as
+    /// `src [24k]`. The total was already worked out when the file was read,
+    /// in a walk that caches the total of every directory it passes through,
+    /// so the directories further down the tree don’t get walked again.
+    fn annotate_subtree_size(
+        &self,
+        name: &mut TextCell,
+        file: &File<'_>,
+        kind: SubtreeSize,
+        numerics: &locale::Numeric,
+    ) {
+        let bytes = match kind {
+            SubtreeSize::Apparent => match file.size() {
+                f::Size::Some(bytes) => bytes,
+                _ => return,
+            },
+            #[cfg(unix)]
+            SubtreeSize::OnDisk => match file.blocksize() {
+                f::Blocksize::Some(bytes) => bytes,
+                f::Blocksize::None => return,
+            },
+            #[cfg(not(unix))]
+            SubtreeSize::OnDisk => return,
+        };
+
+        let size = f::Size::Some(bytes).render(self.theme, SizeFormat::default(), numerics, None);
+
+        name.push(self.theme.ui.punctuation.paint(" ["), 2);
+        name.append(size);
+        name.push(self.theme.ui.punctuation.paint("]"), 1);
+    }
+
+    /// Add the number of files under a directory and their total size after
+    /// its name, such as `src (42 files, 3.1M)`. This counts everything,
+    /// not just the files that the filters would let through, so it still
+    /// works when only directories are being listed.
+    fn annotate_counts(&self, name: &mut TextCell, file: &File<'_>, numerics: &locale::Numeric) {
+        let Some(totals) = file.subtree_totals() else {
+            return;
+        };
+
+        let size =
+            f::Size::Some(totals.size).render(self.theme, SizeFormat::default(), numerics, None);
+        let files = match totals.files {
+            1 => String::from(" (1 file, "),
+            n => format!(" ({n} files, "),
+        };
+
+        let width = files.len();
+        name.push(self.theme.ui.punctuation.paint(files), width);
+        name.append(size);
+        name.push(self.theme.ui.punctuation.paint(")"), 1);
+    }
+
+    /// Where a file was renamed from, if Git has tracked a rename for it and
+    /// the Git column is being shown. Nothing gets looked up while the
+    /// statuses are still being queried in the background, as the table
+    /// gets drawn again once they’re in.
+    fn git_rename(&self, file: &File<'_>) -> Option<f::GitRename> {
+        let git = self.git?;
+        let has_column = self.opts.table.as_ref().is_some_and(|t| t.columns.git);
+        if !has_column || !git.is_ready(&file.path) {
+            return None;
+        }
+        git.rename(&file.path)
+    }
+
+    /// Shows where a file was renamed from and to, inside its repository,
+    /// the way `git status` does.
+    fn annotate_rename(&self, name: &mut TextCell, rename: &f::GitRename) {
+        let from = rename.from.display().to_string();
+        let to = rename.to.display().to_string();
+        let (from_width, to_width) = (str_width(&from), str_width(&to));
+
+        name.push(self.theme.ui.punctuation.paint(" ("), 2);
+        name.push(self.theme.ui.git.renamed.paint("R "), 2);
+        name.push(self.theme.ui.symlink_path.paint(from), from_width);
+        name.push(self.theme.ui.punctuation.paint(" → "), 3);
+        name.push(self.theme.ui.symlink_path.paint(to), to_width);
+        name.push(self.theme.ui.punctuation.paint(")"), 1);
+    }
+
+    /// Adds files to the table, possibly recursively, counting each one
+    /// that gets a row, and returning whether any did. This is easily
     /// parallelisable, and uses a pool of threads.
-    fn add_files_to_table<'dir>(
+    fn add_files_to_table<'dir, R: Rows>(
         &self,
         table: &mut Option<Table<'a>>,
-        rows: &mut Vec<Row>,
+        rows: &mut R,
+        totals: &mut Totals,
         src: &[File<'dir>],
         depth: TreeDepth,
-        color_scale_info: Option<ColorScaleInformation>,
-    ) {
-        use crate::fs::feature::xattr;
+        context: RowContext<'_>,
+    ) -> bool {
+        if let Some(table) = table {
+            table.prefetch_owners(src);
+        }
 
         let mut file_eggs: Vec<_> = src
             .par_iter()
@@ -280,20 +724,31 @@ impl<'a> Render<'a> {
                     &[]
                 };
 
-                let table_row = table
-                    .as_ref()
-                    .map(|t| t.row_for_file(file, self.show_xattr_hint(file), color_scale_info));
+                let table_row = table.as_ref().map(|t| {
+                    t.row_for_file(file, self.show_xattr_hint(file), context.color_scale_info)
+                });
 
                 let mut dir = None;
+                let mut is_loop = false;
                 if let Some(r) = self.recurse {
-                    if file.is_directory() && r.tree && !r.is_too_deep(depth.0) {
-                        trace!("matching on to_dir");
-                        match file.to_dir() {
-                            Ok(d) => {
-                                dir = Some(d);
-                            }
-                            Err(e) => {
-                                errors.push((e, None));
+                    let descends = file.is_directory()
+                        || (r.follow_symlinks && file.is_link() && file.points_to_directory());
+
+                    if descends && r.tree && !r.is_too_deep(depth.0) {
+                        if file.is_loop() {
+                            is_loop = true;
+                        } else {
+                            trace!("matching on to_dir");
+                            match file.to_dir() {
+                                Ok(d) => {
+                                    if let Some(git) = self.git {
+                                        git.discover_nested(&d.path);
+                                    }
+                                    dir = Some(d);
+                                }
+                                Err(e) => {
+                                    errors.push((e, None));
+                                }
                             }
                         }
                     }
@@ -304,23 +759,56 @@ impl<'a> Render<'a> {
                     xattrs,
                     errors,
                     dir,
+                    is_loop,
                     file,
                 }
             })
             .collect();
 
         // this is safe because all entries have been initialized above
-        self.filter.sort_files(&mut file_eggs);
+        if self.recurse.is_some_and(|r| r.tree) {
+            // The files here are one level deeper when they’re the contents
+            // of a directory rather than the arguments themselves.
+            let level = depth.0 + usize::from(self.dir.is_some());
+            self.filter.sort_files_at_level(&mut file_eggs, level);
+        } else {
+            self.filter.sort_files(&mut file_eggs);
+        }
 
-        for (tree_params, egg) in depth.iterate_over(file_eggs.into_iter()) {
-            let mut files = Vec::new();
-            let mut errors = egg.errors;
+        // With --prune, a directory only gets listed if something inside it
+        // does, so the contents of each directory get listed first, into rows
+        // of their own that go after it once it’s known to be staying. This
+        // works its way up from the bottom of the tree, reading each
+        // directory once.
+        let mut contents = Vec::new();
+        if self.opts.prune {
+            let mut kept = Vec::with_capacity(file_eggs.len());
+            for mut egg in file_eggs {
+                let mut inside = Vec::new();
+                let listed =
+                    self.add_contents(table, &mut inside, totals, &mut egg, depth, context);
+
+                if egg.dir.is_none() || listed {
+                    kept.push(egg);
+                    contents.push(inside);
+                }
+            }
+            file_eggs = kept;
+        }
+        let mut contents = contents.into_iter();
+
+        let mut listed = false;
+        for (tree_params, mut egg) in depth.iterate_over(file_eggs.into_iter()) {
+            // Nothing more can be written, so there’s no point reading on.
+            if rows.is_closed() {
+                return listed;
+            }
 
             if let (Some(ref mut t), Some(row)) = (table.as_mut(), egg.table_row.as_ref()) {
                 t.add_widths(row);
             }
 
-            let file_name = self
+            let mut file_name = self
                 .file_style
                 .for_file(egg.file, self.theme)
                 .with_link_paths()
@@ -328,74 +816,146 @@ impl<'a> Render<'a> {
                 .paint()
                 .promote();
 
+            if let Some(subtree_size) = self.opts.subtree_size {
+                if table.is_none() && egg.file.is_directory() {
+                    self.annotate_subtree_size(
+                        &mut file_name,
+                        egg.file,
+                        subtree_size,
+                        context.numerics,
+                    );
+                }
+            }
+
+            if self.opts.show_counts && egg.file.is_directory() {
+                self.annotate_counts(&mut file_name, egg.file, context.numerics);
+            }
+
+            if let Some(rename) = self.git_rename(egg.file) {
+                self.annotate_rename(&mut file_name, &rename);
+            }
+
+            if egg.is_loop {
+                file_name.push(self.theme.ui.broken_symlink.paint(" [loop]"), 7);
+            }
+
             let row = Row {
                 tree: tree_params,
-                cells: egg.table_row,
+                cells: egg.table_row.take(),
                 name: file_name,
+                hard_link: egg
+                    .file
+                    .hard_link()
+                    .filter(|_| self.file_style.hard_link_groups),
             };
 
-            rows.push(row);
-
-            if let Some(ref dir) = egg.dir {
-                for file_to_add in dir.files(
-                    self.filter.dot_filter,
-                    self.git,
-                    self.git_ignoring,
-                    egg.file.deref_links,
-                    egg.file.is_recursive_size(),
-                ) {
-                    match file_to_add {
-                        Ok(f) => {
-                            files.push(f);
-                        }
-                        Err((path, e)) => {
-                            errors.push((e, Some(path)));
-                        }
-                    }
+            rows.add(row);
+            listed = true;
+
+            // A directory given to a tree is where it starts rather than
+            // something in it, so it isn’t counted, the same as tree(1).
+            let is_tree_root = egg.dir.is_some() && self.dir.is_none() && depth.0 == 0;
+            if !is_tree_root {
+                totals.add(egg.file);
+            }
+
+            if let Some(inside) = contents.next() {
+                for row in inside {
+                    rows.add(row);
                 }
+            } else {
+                self.add_contents(table, rows, totals, &mut egg, depth, context);
+            }
+        }
 
-                self.filter.filter_child_files(&mut files);
+        listed
+    }
 
-                if !files.is_empty() {
-                    for xattr in egg.xattrs {
-                        rows.push(self.render_xattr(xattr, TreeParams::new(depth.deeper(), false)));
+    /// Adds the rows that go underneath a file: the contents of its
+    /// directory, if the tree goes inside it, along with its extended
+    /// attributes and any errors. Returns whether anything inside the
+    /// directory got listed, with errors counting as something.
+    fn add_contents<R: Rows>(
+        &self,
+        table: &mut Option<Table<'a>>,
+        rows: &mut R,
+        totals: &mut Totals,
+        egg: &mut Egg<'_>,
+        depth: TreeDepth,
+        context: RowContext<'_>,
+    ) -> bool {
+        let mut errors = mem::take(&mut egg.errors);
+        if let Some(ref dir) = egg.dir {
+            if let Some(snapshots) = &self.file_style.highlight_new {
+                snapshots.record(dir);
+            }
+            let mut files = Vec::new();
+            for file_to_add in dir.files(
+                self.filter.dot_filter,
+                self.git,
+                self.git_ignoring,
+                egg.file.deref_links,
+                egg.file.is_recursive_size(),
+            ) {
+                match file_to_add {
+                    Ok(f) => {
+                        files.push(f);
                     }
-
-                    for (error, path) in errors {
-                        rows.push(self.render_error(
-                            &error,
-                            TreeParams::new(depth.deeper(), false),
-                            path,
-                        ));
+                    Err((path, e)) => {
+                        errors.push((e, Some(path)));
                     }
-
-                    self.add_files_to_table(table, rows, &files, depth.deeper(), color_scale_info);
-                    continue;
                 }
             }
 
-            let count = egg.xattrs.len();
-            for (index, xattr) in egg.xattrs.iter().enumerate() {
-                let params =
-                    TreeParams::new(depth.deeper(), errors.is_empty() && index == count - 1);
-                let r = self.render_xattr(xattr, params);
-                rows.push(r);
-            }
+            self.filter.filter_child_files(&mut files, self.git);
+            progress::add_directory(files.len());
+
+            if !files.is_empty() {
+                for xattr in egg.xattrs {
+                    rows.add(self.render_xattr(xattr, TreeParams::new(depth.deeper(), false)));
+                }
 
-            let count = errors.len();
-            for (index, (error, path)) in errors.into_iter().enumerate() {
-                let params = TreeParams::new(depth.deeper(), index == count - 1);
-                let r = self.render_error(&error, params, path);
-                rows.push(r);
+                let has_errors = !errors.is_empty();
+                for (error, path) in errors {
+                    rows.add(self.render_error(
+                        &error,
+                        TreeParams::new(depth.deeper(), false),
+                        path,
+                    ));
+                }
+
+                let listed =
+                    self.add_files_to_table(table, rows, totals, &files, depth.deeper(), context);
+                return has_errors || listed;
             }
         }
+
+        let count = egg.xattrs.len();
+        for (index, xattr) in egg.xattrs.iter().enumerate() {
+            let params = TreeParams::new(depth.deeper(), errors.is_empty() && index == count - 1);
+            let r = self.render_xattr(xattr, params);
+            rows.add(r);
+        }
+
+        let has_errors = !errors.is_empty();
+        let count = errors.len();
+        for (index, (error, path)) in errors.into_iter().enumerate() {
+            let params = TreeParams::new(depth.deeper(), index == count - 1);
+            let r = self.render_error(&error, params, path);
+            rows.add(r);
+        }
+
+        has_errors
     }
 
-    pub fn render_header(&self, header: TableRow) -> Row {
+    /// The heading above a section of files, which has no cells and starts
+    /// at the very left of the line.
+    fn render_group_heading(&self, heading: &'static str) -> Row {
         Row {
             tree: TreeParams::new(TreeDepth::root(), false),
-            cells: Some(header),
-            name: TextCell::paint_str(self.theme.ui.header, "Name"),
+            cells: None,
+            name: TextCell::paint_str(self.theme.ui.header, heading),
+            hard_link: None,
         }
     }
 
@@ -415,33 +975,40 @@ impl<'a> Render<'a> {
             cells: None,
             name,
             tree,
+            hard_link: None,
         }
     }
 
     fn render_xattr(&self, xattr: &Attribute, tree: TreeParams) -> Row {
-        let name = TextCell::paint(self.theme.ui.perms.attribute, format!("{xattr}"));
+        let text = match self.opts.xattr_values {
+            Some(limit) => xattr.preview(limit),
+            None => xattr.to_string(),
+        };
+
+        let name = TextCell::paint(self.theme.ui.perms.attribute, text);
         Row {
             cells: None,
             name,
             tree,
+            hard_link: None,
         }
     }
 
     pub fn iterate_with_table(&'a self, table: Table<'a>, rows: Vec<Row>) -> TableIter<'a> {
         TableIter {
-            tree_trunk: TreeTrunk::default(),
             total_width: table.widths().total(),
             table,
             inner: rows.into_iter(),
-            tree_style: self.theme.ui.punctuation,
+            branches: self.branches(),
         }
     }
 
-    pub fn iterate(&'a self, rows: Vec<Row>) -> Iter {
-        Iter {
-            tree_trunk: TreeTrunk::default(),
-            inner: rows.into_iter(),
-            tree_style: self.theme.ui.punctuation,
+    fn branches(&self) -> Branches {
+        Branches {
+            trunk: TreeTrunk::default(),
+            colour: self.theme.ui.tree.unwrap_or(self.theme.ui.punctuation),
+            style: self.opts.tree_style,
+            depth_guides: self.opts.depth_guides,
         }
     }
 }
@@ -461,6 +1028,104 @@ pub struct Row {
 
     /// Information used to determine which symbols to display in a tree.
     pub tree: TreeParams,
+
+    /// Where the file is kept, if it has other hard links and they’re
+    /// getting marked, which can only be done once the whole listing is in.
+    pub hard_link: Option<f::HardLink>,
+}
+
+/// Somewhere for rows to go as they’re made.
+trait Rows {
+    fn add(&mut self, row: Row);
+
+    /// Whether the rows have stopped going anywhere, so there’s no point
+    /// making any more of them.
+    fn is_closed(&self) -> bool {
+        false
+    }
+}
+
+/// A table’s rows get kept until the columns have all been measured.
+impl Rows for Vec<Row> {
+    fn add(&mut self, row: Row) {
+        self.push(row);
+    }
+}
+
+/// Writes each row of a tree without a table as soon as it’s made.
+///
+/// The writer blocks whenever whatever’s reading the output falls behind,
+/// which holds up reading the rest of the tree along with it, so a slow
+/// pager never has more than the current directories’ worth of files
+/// waiting on it. Once writing fails, such as when the pager has been
+/// closed, the rest of the tree is left unread.
+struct Stream<'w, W> {
+    writer: &'w mut W,
+    branches: Branches,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Rows for Stream<'_, W> {
+    fn add(&mut self, row: Row) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let mut cell = TextCell::default();
+        self.branches.draw(&mut cell, row.tree);
+        cell.append(row.name);
+
+        if let Err(e) = writeln!(self.writer, "{}", cell.strings()) {
+            self.error = Some(e);
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.error.is_some()
+    }
+}
+
+impl<W> Stream<'_, W> {
+    /// Returns the error that stopped the rows from being written, if any.
+    fn finish(self) -> io::Result<()> {
+        self.error.map_or(Ok(()), Err)
+    }
+}
+
+/// Draws the branches of a tree in front of each row’s name.
+struct Branches {
+    trunk: TreeTrunk,
+    colour: Style,
+    style: TreeStyle,
+    depth_guides: Option<NonZeroUsize>,
+}
+
+impl Branches {
+    fn draw(&mut self, cell: &mut TextCell, params: TreeParams) {
+        for (index, tree_part) in self.trunk.new_row(params).iter().enumerate() {
+            // The parts start at the first level down from the top, and
+            // only the lines passing by other rows get numbered, as the
+            // rightmost part leads to the row itself.
+            let depth = index + 1;
+            match self.depth_guides {
+                Some(every)
+                    if depth % every == 0
+                        && matches!(tree_part, TreePart::Line | TreePart::Blank) =>
+                {
+                    let guide = format!("{depth:<3}");
+                    let width = guide.len() + 1;
+                    cell.push(self.colour.paint(guide), width);
+                }
+                _ => cell.push(self.colour.paint(tree_part.art(self.style)), 4),
+            }
+        }
+
+        // If any tree characters have been printed, then add an extra
+        // space, which makes the output look much better.
+        if !params.is_at_root() {
+            cell.add_spaces(1);
+        }
+    }
 }
 
 #[rustfmt::skip]
@@ -469,8 +1134,7 @@ pub struct TableIter<'a> {
     table: Table<'a>,
 
     total_width: usize,
-    tree_style:  Style,
-    tree_trunk:  TreeTrunk,
+    branches:    Branches,
 }
 
 impl<'a> Iterator for TableIter<'a> {
@@ -480,53 +1144,105 @@ impl<'a> Iterator for TableIter<'a> {
         self.inner.next().map(|row| {
             let mut cell = if let Some(cells) = row.cells {
                 self.table.render(cells)
+            } else if row.tree.is_at_root() {
+                // Only section headings have neither cells nor any depth,
+                // as errors and attributes go underneath their files.
+                TextCell::default()
             } else {
                 let mut cell = TextCell::default();
                 cell.add_spaces(self.total_width);
                 cell
             };
 
-            for tree_part in self.tree_trunk.new_row(row.tree) {
-                cell.push(self.tree_style.paint(tree_part.ascii_art()), 4);
-            }
-
-            // If any tree characters have been printed, then add an extra
-            // space, which makes the output look much better.
-            if !row.tree.is_at_root() {
-                cell.add_spaces(1);
-            }
-
+            self.branches.draw(&mut cell, row.tree);
             cell.append(row.name);
             cell
         })
     }
 }
 
-pub struct Iter {
-    tree_trunk: TreeTrunk,
-    tree_style: Style,
-    inner: VecIntoIter<Row>,
-}
+#[cfg(test)]
+mod test {
+    use super::*;
 
-impl Iterator for Iter {
-    type Item = TextCell;
+    fn row(depth: TreeDepth, last: bool, name: &'static str) -> Row {
+        Row {
+            cells: None,
+            name: TextCell::paint_str(Style::default(), name),
+            tree: TreeParams::new(depth, last),
+            hard_link: None,
+        }
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        self.inner.next().map(|row| {
-            let mut cell = TextCell::default();
+    fn stream<W>(writer: &mut W) -> Stream<'_, W> {
+        Stream {
+            writer,
+            branches: Branches {
+                trunk: TreeTrunk::default(),
+                colour: Style::default(),
+                style: TreeStyle::Ascii,
+                depth_guides: None,
+            },
+            error: None,
+        }
+    }
 
-            for tree_part in self.tree_trunk.new_row(row.tree) {
-                cell.push(self.tree_style.paint(tree_part.ascii_art()), 4);
+    #[test]
+    fn streams_rows() {
+        let mut output = Vec::new();
+        let mut rows = stream(&mut output);
+
+        rows.add(row(TreeDepth::root(), false, "dir"));
+        rows.add(row(TreeDepth::root().deeper(), false, "one"));
+        rows.add(row(TreeDepth::root().deeper().deeper(), true, "two"));
+        rows.add(row(TreeDepth::root().deeper(), true, "three"));
+        assert!(!rows.is_closed());
+        rows.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "dir\n|-- one\n|  `-- two\n`-- three\n"
+        );
+    }
+
+    #[test]
+    fn numbers_depth_guides() {
+        let mut output = Vec::new();
+        let mut rows = stream(&mut output);
+        rows.branches.depth_guides = NonZeroUsize::new(2);
+
+        let mut depth = TreeDepth::root();
+        for name in ["dir", "one", "two", "three"] {
+            rows.add(row(depth, false, name));
+            depth = depth.deeper();
+        }
+        rows.add(row(TreeDepth::root().deeper(), true, "four"));
+        rows.finish().unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "dir\n|-- one\n|  |-- two\n|  2  |-- three\n`-- four\n"
+        );
+    }
+
+    #[test]
+    fn stops_when_closed() {
+        struct Closed;
+
+        impl Write for Closed {
+            fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+                Err(io::ErrorKind::BrokenPipe.into())
             }
 
-            // If any tree characters have been printed, then add an extra
-            // space, which makes the output look much better.
-            if !row.tree.is_at_root() {
-                cell.add_spaces(1);
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
             }
+        }
 
-            cell.append(row.name);
-            cell
-        })
+        let mut closed = Closed;
+        let mut rows = stream(&mut closed);
+        rows.add(row(TreeDepth::root(), false, "dir"));
+        assert!(rows.is_closed());
+        assert_eq!(rows.finish().unwrap_err().kind(), io::ErrorKind::BrokenPipe);
     }
 }
@@ -1,7 +1,40 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
+
 use super::file_name::QuoteStyle;
 use nu_ansi_term::{AnsiString as ANSIString, Style};
 
 pub fn escape(
+    name: impl AsRef<OsStr>,
+    bits: &mut Vec<ANSIString<'_>>,
+    good: Style,
+    bad: Style,
+    quote_style: QuoteStyle,
+) {
+    let name = name.as_ref();
+
+    match quote_style {
+        QuoteStyle::NoQuotes | QuoteStyle::QuoteSpaces => {
+            escape_default(
+                name.to_string_lossy().into_owned(),
+                bits,
+                good,
+                bad,
+                quote_style,
+            );
+        }
+        QuoteStyle::Literal => bits.push(good.paint(name.to_string_lossy().into_owned())),
+        QuoteStyle::Shell => escape_shell(&name_bytes(name), bits, good, bad),
+        QuoteStyle::C => {
+            bits.push(good.paint("\""));
+            escape_c(&name_bytes(name), bits, good, bad, quote_style);
+            bits.push(good.paint("\""));
+        }
+        QuoteStyle::Escape => escape_c(&name_bytes(name), bits, good, bad, quote_style),
+    }
+}
+
+fn escape_default(
     string: String,
     bits: &mut Vec<ANSIString<'_>>,
     good: Style,
@@ -37,3 +70,283 @@ pub fn escape(
         bits.push(quote_bit);
     }
 }
+
+/// The bytes of a name, so that ones that aren’t valid UTF-8 can be escaped
+/// rather than replaced. Names on Windows are always turned into UTF-8.
+fn name_bytes(name: &OsStr) -> Cow<'_, [u8]> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Cow::Borrowed(name.as_bytes())
+    }
+
+    #[cfg(not(unix))]
+    match name.to_string_lossy() {
+        Cow::Borrowed(string) => Cow::Borrowed(string.as_bytes()),
+        Cow::Owned(string) => Cow::Owned(string.into_bytes()),
+    }
+}
+
+/// A piece of a name: either a character, or a byte that isn’t part of any
+/// valid UTF-8 character.
+#[derive(PartialEq, Debug, Copy, Clone)]
+enum Unit {
+    Char(char),
+    Byte(u8),
+}
+
+impl Unit {
+    /// Whether this needs a backslash escape to be shown, rather than being
+    /// printed as it is.
+    fn is_unprintable(self) -> bool {
+        match self {
+            Self::Char(c) => c.is_control(),
+            Self::Byte(_) => true,
+        }
+    }
+}
+
+fn units(mut bytes: &[u8]) -> Vec<Unit> {
+    let mut units = Vec::with_capacity(bytes.len());
+
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid) => {
+                units.extend(valid.chars().map(Unit::Char));
+                return units;
+            }
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                let valid = std::str::from_utf8(valid).unwrap_or_default();
+                units.extend(valid.chars().map(Unit::Char));
+
+                let invalid = e.error_len().unwrap_or(rest.len());
+                units.extend(rest[..invalid].iter().copied().map(Unit::Byte));
+                bytes = &rest[invalid..];
+            }
+        }
+    }
+}
+
+/// The backslash escape for a character or byte that can’t be printed, the
+/// way C writes it in a string: a letter for the common ones, and three
+/// octal digits for everything else.
+fn c_escape(unit: Unit) -> String {
+    match unit {
+        Unit::Char('\x07') => String::from("\\a"),
+        Unit::Char('\x08') => String::from("\\b"),
+        Unit::Char('\x0c') => String::from("\\f"),
+        Unit::Char('\n') => String::from("\\n"),
+        Unit::Char('\r') => String::from("\\r"),
+        Unit::Char('\t') => String::from("\\t"),
+        Unit::Char('\x0b') => String::from("\\v"),
+        Unit::Char(c) => {
+            let mut buffer = [0; 4];
+            c.encode_utf8(&mut buffer)
+                .bytes()
+                .map(|byte| format!("\\{byte:03o}"))
+                .collect()
+        }
+        Unit::Byte(byte) => format!("\\{byte:03o}"),
+    }
+}
+
+/// Escapes a name the way GNU `ls` does with `--quoting-style=c` or
+/// `--quoting-style=escape`: unprintable characters and backslashes get
+/// backslash escapes, and so do double quotes for C strings, or spaces
+/// otherwise. The C style’s surrounding quotes aren’t added here.
+fn escape_c(
+    bytes: &[u8],
+    bits: &mut Vec<ANSIString<'_>>,
+    good: Style,
+    bad: Style,
+    quote_style: QuoteStyle,
+) {
+    let mut run = String::new();
+
+    for unit in units(bytes) {
+        let escaped = match unit {
+            Unit::Char('\\') => Some(String::from("\\\\")),
+            Unit::Char('"') if quote_style == QuoteStyle::C => Some(String::from("\\\"")),
+            Unit::Char(' ') if quote_style == QuoteStyle::Escape => Some(String::from("\\ ")),
+            unit if unit.is_unprintable() => Some(c_escape(unit)),
+            _ => None,
+        };
+
+        match (escaped, unit) {
+            (Some(escaped), _) => {
+                if !run.is_empty() {
+                    bits.push(good.paint(std::mem::take(&mut run)));
+                }
+                bits.push(bad.paint(escaped));
+            }
+            (None, Unit::Char(c)) => run.push(c),
+            (None, Unit::Byte(_)) => unreachable!("bytes are always unprintable"),
+        }
+    }
+
+    if !run.is_empty() {
+        bits.push(good.paint(run));
+    }
+}
+
+/// Whether a character can be left unquoted by the shell.
+fn is_shell_safe(c: char) -> bool {
+    c.is_alphanumeric() || "%+,-./:=@_".contains(c)
+}
+
+/// Quotes a name so that it can be pasted into, or `eval`’d by, a POSIX
+/// shell, the way GNU `ls` does with `--quoting-style=shell-escape`. Names
+/// made only of safe characters are left as they are; anything else goes
+/// in single quotes, with single quotes themselves escaped outside of them,
+/// and unprintable characters in `$'…'` strings.
+fn escape_shell(bytes: &[u8], bits: &mut Vec<ANSIString<'_>>, good: Style, bad: Style) {
+    #[derive(PartialEq)]
+    enum Quoting {
+        None,
+        Single,
+        Dollar,
+    }
+
+    let units = units(bytes);
+    if !units.is_empty()
+        && units
+            .iter()
+            .all(|unit| matches!(unit, Unit::Char(c) if is_shell_safe(*c)))
+    {
+        let name = units
+            .iter()
+            .filter_map(|unit| match unit {
+                Unit::Char(c) => Some(*c),
+                Unit::Byte(_) => None,
+            })
+            .collect::<String>();
+        bits.push(good.paint(name));
+        return;
+    }
+
+    let mut quoting = Quoting::None;
+    let mut run = String::new();
+
+    for unit in units {
+        let wanted = match unit {
+            Unit::Char('\'') => Quoting::None,
+            unit if unit.is_unprintable() => Quoting::Dollar,
+            _ => Quoting::Single,
+        };
+
+        if wanted != quoting {
+            if quoting != Quoting::None {
+                run.push('\'');
+            }
+            if !run.is_empty() {
+                bits.push(good.paint(std::mem::take(&mut run)));
+            }
+
+            match wanted {
+                Quoting::Single => run.push('\''),
+                Quoting::Dollar => bits.push(good.paint("$'")),
+                Quoting::None => {}
+            }
+            quoting = wanted;
+        }
+
+        match unit {
+            Unit::Char('\'') => bits.push(bad.paint("\\'")),
+            unit if unit.is_unprintable() => bits.push(bad.paint(c_escape(unit))),
+            Unit::Char(c) => run.push(c),
+            Unit::Byte(_) => unreachable!("bytes are always unprintable"),
+        }
+    }
+
+    if quoting != Quoting::None {
+        run.push('\'');
+    }
+    if !run.is_empty() {
+        bits.push(good.paint(run));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use nu_ansi_term::AnsiStrings as ANSIStrings;
+
+    fn escaped(bytes: &[u8], quote_style: QuoteStyle) -> String {
+        #[cfg(unix)]
+        let name = {
+            use std::os::unix::ffi::OsStrExt;
+            OsStr::from_bytes(bytes)
+        };
+
+        #[cfg(not(unix))]
+        let name = OsStr::new(std::str::from_utf8(bytes).unwrap());
+
+        let mut bits = Vec::new();
+        escape(
+            name,
+            &mut bits,
+            Style::default(),
+            Style::default(),
+            quote_style,
+        );
+        ANSIStrings(&bits).to_string()
+    }
+
+    #[test]
+    fn literal() {
+        assert_eq!(escaped(b"a b\n", QuoteStyle::Literal), "a b\n");
+    }
+
+    #[test]
+    fn shell_safe() {
+        assert_eq!(escaped(b"file-1.txt", QuoteStyle::Shell), "file-1.txt");
+        assert_eq!(escaped("héllo".as_bytes(), QuoteStyle::Shell), "héllo");
+    }
+
+    #[test]
+    fn shell_spaces() {
+        assert_eq!(escaped(b"a b", QuoteStyle::Shell), "'a b'");
+        assert_eq!(escaped(b"$HOME", QuoteStyle::Shell), "'$HOME'");
+    }
+
+    #[test]
+    fn shell_quotes() {
+        assert_eq!(escaped(b"it's", QuoteStyle::Shell), "'it'\\''s'");
+        assert_eq!(escaped(b"say \"hi\"", QuoteStyle::Shell), "'say \"hi\"'");
+    }
+
+    #[test]
+    fn shell_newline() {
+        assert_eq!(escaped(b"a\nb", QuoteStyle::Shell), "'a'$'\\n''b'");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn shell_invalid_utf8() {
+        assert_eq!(escaped(b"a\xffb", QuoteStyle::Shell), "'a'$'\\377''b'");
+    }
+
+    #[test]
+    fn c_quotes() {
+        assert_eq!(escaped(b"say \"hi\"", QuoteStyle::C), "\"say \\\"hi\\\"\"");
+        assert_eq!(escaped(b"back\\slash", QuoteStyle::C), "\"back\\\\slash\"");
+    }
+
+    #[test]
+    fn c_control_chars() {
+        assert_eq!(escaped(b"a\nb\tc\x01", QuoteStyle::C), "\"a\\nb\\tc\\001\"");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn c_invalid_utf8() {
+        assert_eq!(escaped(b"\xc3(", QuoteStyle::C), "\"\\303(\"");
+    }
+
+    #[test]
+    fn escape_spaces() {
+        assert_eq!(escaped(b"a b\n", QuoteStyle::Escape), "a\\ b\\n");
+        assert_eq!(escaped(b"it's \"x\"", QuoteStyle::Escape), "it's\\ \"x\"");
+    }
+}
@@ -1,25 +1,47 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ffi::OsString;
 use std::fmt::Debug;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
 
 use nu_ansi_term::{AnsiString as ANSIString, Style};
 use path_clean;
-use unicode_width::UnicodeWidthStr;
-
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::duplicates::CommonNames;
+use crate::fs::feature::audit::{Audit, Severity};
+use crate::fs::feature::in_use::{self, OpenFiles};
+use crate::fs::feature::snapshot::{Change, Snapshots};
+use crate::fs::feature::special_dirs::{SpecialDir, UserDirs};
+use crate::fs::feature::xattr::TagColour;
+use crate::fs::fields::HardLink;
+use crate::fs::metadata::Field;
 use crate::fs::{File, FileTarget};
 use crate::output::cell::TextCellContents;
 use crate::output::escape;
-use crate::output::icons::{icon_for_file, iconify_style};
+use crate::output::icons::{icon_for_file, iconify_style, IconPack};
 use crate::output::render::FiletypeColours;
+use crate::output::width::{cluster_width, str_width};
+use crate::which::Duplicates;
 
 /// Basically a file name factory.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Options {
     /// Whether to append file class characters to file names.
     pub classify: Classify,
 
+    /// Which set of characters to classify files with.
+    pub indicators: Indicators,
+
     /// Whether to prepend icon characters before file names.
     pub show_icons: ShowIcons,
 
+    /// The icons to show instead of the default ones, if others were
+    /// picked.
+    pub icon_pack: Option<Arc<IconPack>>,
+
     /// How to display file names with spaces (with or without quotes).
     pub quote_style: QuoteStyle,
 
@@ -29,15 +51,55 @@ pub struct Options {
     /// Whether to display files with their absolute path.
     pub absolute: Absolute,
 
+    /// The directory to display files’ paths relative to, if any.
+    pub relative_to: Option<Arc<Path>>,
+
     /// Whether we are in a console or redirecting the output
     pub is_a_tty: bool,
+
+    /// Whether to mark files that are hard links to the same file as
+    /// another one in the listing, colouring each group of them alike.
+    pub hard_link_groups: bool,
+
+    /// Whether to mark files that share data blocks with a clone.
+    pub clones: bool,
+
+    /// The snapshots to mark files that have appeared or changed since
+    /// their directory was last listed against, if they’re being marked.
+    pub highlight_new: Option<Arc<Snapshots>>,
+
+    /// Whether to look for a file with a similar name to suggest when a
+    /// link’s target is missing.
+    pub suggest_broken_links: bool,
+
+    /// What to check files’ permissions against, if files with risky
+    /// permissions are being flagged.
+    pub security_audit: Option<Arc<Audit>>,
+
+    /// The files that processes have open, if they’re being marked.
+    pub in_use: Option<Arc<OpenFiles>>,
+
+    /// The files that have the same name as a file under another argument,
+    /// if they’re being marked.
+    pub common_names: Option<Arc<CommonNames>>,
+
+    /// The programs found by `--which` that are the same file as an earlier
+    /// one, if they were looked for.
+    pub which_duplicates: Option<Arc<Duplicates>>,
+
+    /// How to shorten names that are too long, if at all.
+    pub truncate_names: Option<Truncation>,
+
+    /// The user’s special directories, which get icons and colours of their
+    /// own.
+    pub user_dirs: Arc<UserDirs>,
 }
 
 impl Options {
     /// Create a new `FileName` that prints the given file’s name, painting it
     /// with the remaining arguments.
     pub fn for_file<'a, 'dir, C>(
-        self,
+        &self,
         file: &'a File<'dir>,
         colours: &'a C,
     ) -> FileName<'a, 'dir, C> {
@@ -45,15 +107,64 @@ impl Options {
             file,
             colours,
             link_style: LinkStyle::JustFilenames,
-            options: self,
+            options: self.clone(),
             target: if file.is_link() {
                 Some(file.link_target())
             } else {
                 None
             },
             mount_style: MountStyle::JustDirectoryNames,
+            hard_link_groups: None,
         }
     }
+
+    /// The groups of hard links among a listing’s files, if they’re getting
+    /// marked.
+    pub fn hard_link_groups_of(&self, files: &[File<'_>]) -> Option<HardLinkGroups> {
+        self.hard_link_groups.then(|| HardLinkGroups::of(files))
+    }
+}
+
+/// Which of the files in a listing are hard links to the same file as
+/// another one in it. Each group of them gets a number, in the order it
+/// first turns up, that picks the colour its markers get painted in.
+#[derive(Debug, Default)]
+pub struct HardLinkGroups(HashMap<(u64, u64), usize>);
+
+impl HardLinkGroups {
+    /// Groups the links that turn up more than once among the files of a
+    /// listing, given in the order they’re listed.
+    pub fn new(links: impl IntoIterator<Item = HardLink>) -> Self {
+        let mut counts = HashMap::new();
+        let mut order = Vec::new();
+        for link in links {
+            let count = counts.entry(link.id).or_insert(0);
+            if *count == 0 {
+                order.push(link.id);
+            }
+            *count += 1;
+        }
+
+        let shared = order.into_iter().filter(|id| counts[id] > 1);
+        Self(shared.enumerate().map(|(group, id)| (id, group)).collect())
+    }
+
+    /// Groups the links among the given files.
+    pub fn of(files: &[File<'_>]) -> Self {
+        Self::new(files.iter().filter_map(File::hard_link))
+    }
+
+    /// The marker for a file with the given link, if another file in the
+    /// listing is a link to the same file: a `⇶` and how many links there
+    /// are to it, painted in its group’s colour.
+    pub fn marker<C: Colours>(&self, link: HardLink, colours: &C) -> Option<ANSIString<'static>> {
+        let group = *self.0.get(&link.id)?;
+        Some(
+            colours
+                .hard_link_group(group)
+                .paint(format!("⇶ {}", link.count)),
+        )
+    }
 }
 
 /// When displaying a file name, there needs to be some way to handle broken
@@ -85,6 +196,101 @@ pub enum Classify {
     AutomaticAddFileIndicators,
 }
 
+/// Which characters to classify files with.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub enum Indicators {
+    /// The characters `ls -F` uses: `/` for directories, `*` for executable
+    /// files, `@` for links, `|` for pipes, and `=` for sockets.
+    #[default]
+    Basic,
+
+    /// The basic characters, plus `!` for links that point to nothing, `^`
+    /// and `&` instead of `*` for setuid and setgid files, and `>` for doors.
+    /// Setgid files don’t get a `+`, as that marks access control lists in
+    /// the permissions column.
+    Extended,
+}
+
+/// How to shorten a file name that’s too wide, for `--truncate-names`.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct Truncation {
+    /// Which part of the name to cut out.
+    pub position: TruncatePosition,
+
+    /// The widest a name can be, in terminal columns.
+    pub width: usize,
+}
+
+/// Which part of a name to cut out when it’s too wide. Either way, the
+/// extension stays visible.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum TruncatePosition {
+    /// Keep the start and end of the name, and cut out the middle.
+    Middle,
+
+    /// Keep the start of the name, and cut out everything before the
+    /// extension.
+    End,
+}
+
+impl Truncation {
+    /// The widest a name can be when no width is given.
+    pub const DEFAULT_WIDTH: usize = 40;
+
+    /// Shortens the name to fit in the width, if it’s too wide, putting an
+    /// ellipsis where the cut is. Names are only ever cut between grapheme
+    /// clusters, so accents and emoji sequences stay in one piece.
+    pub fn truncate(self, name: &str) -> Option<String> {
+        const ELLIPSIS: &str = "…";
+
+        if str_width(name) <= self.width {
+            return None;
+        }
+
+        let budget = self.width.saturating_sub(str_width(ELLIPSIS));
+        let clusters = name.graphemes(true).collect::<Vec<_>>();
+
+        // Keep the extension, as long as it leaves room for some of the
+        // rest of the name.
+        let extension_width = name
+            .rfind('.')
+            .filter(|index| *index > 0)
+            .map_or(0, |index| str_width(&name[index..]))
+            .min(budget / 2);
+
+        let tail_width = match self.position {
+            TruncatePosition::Middle => budget / 2,
+            TruncatePosition::End => 0,
+        }
+        .max(extension_width);
+
+        let head = take_width(clusters.iter().copied(), budget - tail_width);
+        let tail = take_width(clusters.iter().rev().copied(), tail_width);
+
+        let mut truncated = String::new();
+        truncated.extend(head);
+        truncated.push_str(ELLIPSIS);
+        truncated.extend(tail.into_iter().rev());
+        Some(truncated)
+    }
+}
+
+/// Takes clusters until the next one wouldn’t fit in the width.
+fn take_width<'a>(clusters: impl Iterator<Item = &'a str>, width: usize) -> Vec<&'a str> {
+    let mut taken = Vec::new();
+    let mut used = 0;
+
+    for cluster in clusters {
+        used += cluster_width(cluster);
+        if used > width {
+            break;
+        }
+        taken.push(cluster);
+    }
+
+    taken
+}
+
 /// When displaying a directory name, there needs to be some way to handle
 /// mount details, depending on how long the resulting Cell can be.
 #[derive(PartialEq, Debug, Copy, Clone)]
@@ -135,6 +341,18 @@ pub enum QuoteStyle {
     /// Use single quotes for file names that contain spaces and no single quotes
     /// Use double quotes for file names that contain single quotes.
     QuoteSpaces,
+
+    /// Print file names exactly as they are, without escaping anything.
+    Literal,
+
+    /// Quote file names so they can be pasted into a shell, or `eval`’d.
+    Shell,
+
+    /// Show file names as C strings, in double quotes with backslash escapes.
+    C,
+
+    /// Like C strings, but without the quotes, and with spaces escaped.
+    Escape,
 }
 
 /// A **file name** holds all the information necessary to display the name
@@ -156,6 +374,10 @@ pub struct FileName<'a, 'dir, C> {
 
     /// How to handle displaying a mounted filesystem.
     mount_style: MountStyle,
+
+    /// The groups of hard links in the listing this file is part of, if
+    /// they’re getting marked.
+    hard_link_groups: Option<&'a HardLinkGroups>,
 }
 
 impl<'a, 'dir, C> FileName<'a, 'dir, C> {
@@ -176,6 +398,13 @@ impl<'a, 'dir, C> FileName<'a, 'dir, C> {
         };
         self
     }
+
+    /// Marks the file if it’s a hard link to the same file as another one
+    /// in the listing, according to the groups of links in it.
+    pub fn with_hard_link_groups(mut self, groups: Option<&'a HardLinkGroups>) -> Self {
+        self.hard_link_groups = groups;
+        self
+    }
 }
 
 impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
@@ -202,14 +431,15 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
 
         if let Some(spaces_count) = spaces_count_opt {
             let style = iconify_style(self.style());
-            let file_icon = icon_for_file(self.file).to_string();
+            let pack = self.options.icon_pack.as_deref();
+            let file_icon = icon_for_file(self.file, pack, &self.options.user_dirs).to_string();
             bits.push(style.paint(file_icon));
             bits.push(style.paint(" ".repeat(spaces_count as usize)));
         }
 
-        if self.file.parent_dir.is_none() {
+        if self.file.parent_dir.is_none() && !self.shows_whole_path() {
             if let Some(parent) = self.file.path.parent() {
-                self.add_parent_bits(&mut bits, parent);
+                self.add_parent_bits(&mut bits, &self.parent_to_show(parent));
             }
         }
 
@@ -239,11 +469,24 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
                     if !target.name.is_empty() {
                         let target_options = Options {
                             classify: Classify::JustFilenames,
-                            quote_style: QuoteStyle::QuoteSpaces,
+                            indicators: Indicators::Basic,
+                            quote_style: self.options.quote_style,
                             show_icons: ShowIcons::Never,
+                            icon_pack: None,
                             embed_hyperlinks: EmbedHyperlinks::Off,
                             is_a_tty: self.options.is_a_tty,
                             absolute: Absolute::Off,
+                            relative_to: None,
+                            hard_link_groups: false,
+                            clones: false,
+                            highlight_new: None,
+                            suggest_broken_links: false,
+                            security_audit: None,
+                            in_use: None,
+                            common_names: None,
+                            which_duplicates: None,
+                            truncate_names: None,
+                            user_dirs: Arc::clone(&self.options.user_dirs),
                         };
 
                         let target_name = FileName {
@@ -253,6 +496,7 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
                             link_style: LinkStyle::FullLinkPaths,
                             options: target_options,
                             mount_style: MountStyle::JustDirectoryNames,
+                            hard_link_groups: None,
                         };
 
                         for bit in target_name.escaped_file_name() {
@@ -279,6 +523,24 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
                         self.colours.broken_control_char(),
                         self.options.quote_style,
                     );
+
+                    if should_add_classify_char && self.options.indicators == Indicators::Extended {
+                        bits.push(Style::default().paint("!"));
+                    }
+
+                    if self.options.suggest_broken_links {
+                        if let Some(suggestion) = self.file.suggest_link_target() {
+                            bits.push(Style::default().paint(" (did you mean "));
+                            escape(
+                                suggestion,
+                                &mut bits,
+                                self.colours.symlink_path(),
+                                self.colours.control_char(),
+                                self.options.quote_style,
+                            );
+                            bits.push(Style::default().paint("?)"));
+                        }
+                    }
                 }
 
                 FileTarget::Err(_) => {
@@ -291,6 +553,79 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
             }
         }
 
+        if let Some(groups) = self.hard_link_groups {
+            let link = self.file.hard_link();
+            if let Some(marker) = link.and_then(|link| groups.marker(link, self.colours)) {
+                bits.push(Style::default().paint(" "));
+                bits.push(marker);
+            }
+        }
+
+        if self.options.clones && self.file.is_clone() {
+            bits.push(Style::default().paint(" "));
+            bits.push(self.colours.clone_marker().paint("⧉"));
+        }
+
+        if let Some(snapshots) = &self.options.highlight_new {
+            if let Some(change) = snapshots.change(self.file) {
+                let (style, marker) = match change {
+                    Change::Added => (self.colours.added_since_last(), "+"),
+                    Change::Modified => (self.colours.modified_since_last(), "~"),
+                };
+                bits.push(Style::default().paint(" "));
+                bits.push(style.paint(marker));
+            }
+        }
+
+        if let Some(common_names) = &self.options.common_names {
+            if common_names.contains(&self.file.path) {
+                bits.push(Style::default().paint(" "));
+                bits.push(self.colours.common_name().paint("≡"));
+            }
+        }
+
+        for tag in self.file.tags() {
+            bits.push(Style::default().paint(" "));
+            bits.push(self.colours.tag(tag.colour).paint(format!("●{}", tag.name)));
+        }
+
+        if let Some(audit) = &self.options.security_audit {
+            let findings = audit.check(self.file);
+            for (i, finding) in findings.iter().enumerate() {
+                bits.push(Style::default().paint(if i == 0 { " [" } else { ", " }));
+                bits.push(
+                    self.colours
+                        .audit_finding(finding.severity())
+                        .paint(finding.label()),
+                );
+            }
+            if !findings.is_empty() {
+                bits.push(Style::default().paint("]"));
+            }
+        }
+
+        if let Some(open_files) = &self.options.in_use {
+            if let Some(names) = open_files.users_of(self.file) {
+                bits.push(Style::default().paint(" ["));
+                bits.push(self.colours.in_use().paint(in_use::label(names)));
+                bits.push(Style::default().paint("]"));
+            }
+        }
+
+        if let (Some(duplicates), None) = (&self.options.which_duplicates, self.file.parent_dir) {
+            if let Some(original) = duplicates.original_of(&self.file.path) {
+                bits.push(Style::default().paint(" (same as "));
+                escape(
+                    original.display().to_string(),
+                    &mut bits,
+                    self.colours.symlink_path(),
+                    self.colours.control_char(),
+                    self.options.quote_style,
+                );
+                bits.push(Style::default().paint(")"));
+            }
+        }
+
         if self.mount_style == MountStyle::MountInfo {
             if let Some(mount_details) = self.file.mount_point_info() {
                 // This is a filesystem mounted on the directory, output its details
@@ -305,6 +640,25 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
         bits.into()
     }
 
+    /// The directory that a file given as an argument is in, as it gets
+    /// shown in front of its name, which is a URL if the file isn’t on this
+    /// computer.
+    fn parent_to_show<'p>(&self, parent: &'p Path) -> Cow<'p, Path> {
+        #[cfg(unix)]
+        if let Some(source) = self.file.metadata.source() {
+            // The home directory itself gets named `~`, so it goes straight
+            // after the host.
+            let url = if parent.as_os_str().is_empty() {
+                source.display(Path::new("/"))
+            } else {
+                source.display(parent)
+            };
+            return Cow::Owned(PathBuf::from(url.trim_end_matches('/')));
+        }
+
+        Cow::Borrowed(parent)
+    }
+
     /// Adds the bits of the parent path to the given bits vector.
     /// The path gets its characters escaped based on the colours.
     fn add_parent_bits(&self, bits: &mut Vec<ANSIString<'_>>, parent: &Path) {
@@ -336,6 +690,12 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
     /// the file’s type has one associated with it.
     #[cfg(unix)]
     pub(crate) fn classify_char(&self, file: &File<'_>) -> Option<&'static str> {
+        if self.options.indicators == Indicators::Extended {
+            if let Some(class) = Self::extended_classify_char(file) {
+                return Some(class);
+            }
+        }
+
         if file.is_executable_file() {
             Some("*")
         } else if file.is_directory() {
@@ -351,8 +711,36 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
         }
     }
 
+    /// The character to be displayed after a file when the extended set of
+    /// indicators is in use, if it’s one of the kinds of file only that set
+    /// has a character for.
+    #[cfg(unix)]
+    fn extended_classify_char(file: &File<'_>) -> Option<&'static str> {
+        #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+        if file.is_door() {
+            return Some(">");
+        }
+
+        if file.is_link() && matches!(file.link_target(), FileTarget::Broken(_)) {
+            Some("!")
+        } else if file.is_setuid_file() {
+            Some("^")
+        } else if file.is_setgid_file() {
+            Some("&")
+        } else {
+            None
+        }
+    }
+
     #[cfg(windows)]
     pub(crate) fn classify_char(&self, file: &File<'_>) -> Option<&'static str> {
+        if self.options.indicators == Indicators::Extended
+            && file.is_link()
+            && matches!(file.link_target(), FileTarget::Broken(_))
+        {
+            return Some("!");
+        }
+
         if file.is_directory() {
             Some("/")
         } else if file.is_link() {
@@ -404,8 +792,16 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
             }
         }
 
+        let name = self.display_name();
+        let name = match self.options.truncate_names {
+            Some(truncation) => truncation
+                .truncate(&name.to_string_lossy())
+                .map_or(name, OsString::from),
+            None => name,
+        };
+
         escape(
-            self.display_name(),
+            name,
             &mut bits,
             file_style,
             self.colours.control_char(),
@@ -422,7 +818,14 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
     }
 
     /// Returns the string that should be displayed as the file's name.
-    fn display_name(&self) -> String {
+    fn display_name(&self) -> OsString {
+        if let Some(base) = &self.options.relative_to {
+            if let Ok(cwd) = std::env::current_dir() {
+                let path = path_clean::clean(cwd.join(&self.file.path));
+                return relative_path(&path, base).into_os_string();
+            }
+        }
+
         match self.options.absolute {
             Absolute::On => std::env::current_dir().ok().and_then(|p| {
                 path_clean::clean(p.join(&self.file.path))
@@ -436,7 +839,42 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
                 .map(std::borrow::ToOwned::to_owned),
             Absolute::Off => None,
         }
-        .unwrap_or(self.file.name.clone())
+        .map_or_else(|| self.raw_name(), OsString::from)
+    }
+
+    /// The file’s name with nothing added to it and nothing escaped, for
+    /// output that other programs are going to read: the path it was given
+    /// as if it came from the command line, and its absolute path if one
+    /// was asked for.
+    pub fn plain_name(&self) -> OsString {
+        if !self.shows_whole_path() && self.file.parent_dir.is_none() {
+            #[cfg(unix)]
+            if let Some(source) = self.file.metadata.source() {
+                return source.display(&self.file.path).into();
+            }
+
+            self.file.path.clone().into_os_string()
+        } else {
+            self.display_name()
+        }
+    }
+
+    /// Whether the displayed name is a path worked out from the file’s own
+    /// path, which already has any parent directories in it.
+    fn shows_whole_path(&self) -> bool {
+        self.options.absolute != Absolute::Off || self.options.relative_to.is_some()
+    }
+
+    /// The file’s name as it is on the filesystem, which is only different
+    /// from its `name` when it isn’t valid UTF-8, so that the quoting styles
+    /// that escape the invalid bytes can see them.
+    fn raw_name(&self) -> OsString {
+        match self.file.path.components().next_back() {
+            Some(last) if last.as_os_str().to_string_lossy() == self.file.name => {
+                last.as_os_str().to_os_string()
+            }
+            _ => OsString::from(&self.file.name),
+        }
     }
 
     /// Figures out which colour to paint the filename part of the output,
@@ -455,7 +893,9 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
         #[rustfmt::skip]
         return match self.file {
             f if f.is_mount_point()      => self.colours.mount_point(),
-            f if f.is_directory()        => self.colours.directory(),
+            f if f.is_directory()        => f.special_dir(&self.options.user_dirs)
+                                                .and_then(|dir| self.colours.special_dir(dir))
+                                                .unwrap_or_else(|| self.colours.directory()),
             #[cfg(unix)]
             f if f.is_executable_file()  => self.colours.executable_file(),
             f if f.is_link()             => self.colours.symlink(),
@@ -467,14 +907,17 @@ impl<'a, 'dir, C: Colours> FileName<'a, 'dir, C> {
             f if f.is_char_device()      => self.colours.char_device(),
             #[cfg(unix)]
             f if f.is_socket()           => self.colours.socket(),
-            f if ! f.is_file()           => self.colours.special(),
+            #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+            f if f.is_door()             => self.colours.door(),
+            f if ! f.is_file()
+              && f.metadata.has(Field::Mode) => self.colours.special(),
             _                            => self.colours.colour_file(self.file),
         };
     }
 
     /// For grid's use, to cover the case of hyperlink escape sequences
     pub fn bare_utf8_width(&self) -> usize {
-        UnicodeWidthStr::width(self.file.name.as_str())
+        str_width(&self.file.name)
     }
 }
 
@@ -508,5 +951,371 @@ pub trait Colours: FiletypeColours {
     /// The style to paint a directory that has a filesystem mounted on it.
     fn mount_point(&self) -> Style;
 
+    /// The style to paint the marker of a file that’s a hard link to the
+    /// same file as others in the listing, picked by the number of their
+    /// group.
+    fn hard_link_group(&self, group: usize) -> Style;
+
+    /// The style to paint the marker of a file that shares its data blocks
+    /// with a clone.
+    fn clone_marker(&self) -> Style;
+
+    /// The style to paint the marker of a file that wasn’t there the last
+    /// time its directory was listed.
+    fn added_since_last(&self) -> Style;
+
+    /// The style to paint the marker of a file that has been modified since
+    /// its directory was last listed.
+    fn modified_since_last(&self) -> Style;
+
+    /// The style to paint one of the user’s special directories, if it
+    /// should look different from other directories.
+    fn special_dir(&self, dir: SpecialDir) -> Option<Style>;
+
+    /// The style to paint a risky permission that `--security-audit` found,
+    /// by how serious it is.
+    fn audit_finding(&self, severity: Severity) -> Style;
+
+    /// The style to paint the processes that have a file open.
+    fn in_use(&self) -> Style;
+
+    /// The style to paint the marker of a file that has the same name as a
+    /// file under another argument.
+    fn common_name(&self) -> Style;
+
+    /// The style to paint one of the file’s tags, in the tag’s own colour.
+    fn tag(&self, colour: Option<TagColour>) -> Style;
+
     fn colour_file(&self, file: &File<'_>) -> Style;
 }
+
+/// The path that leads from the `base` directory to `path`, going up out of
+/// it with `..` as far as necessary, as `realpath --relative-to` prints.
+/// Both paths should be absolute and clean. If they have nothing in common,
+/// such as when they’re on different drives, `path` is returned as it is.
+fn relative_path(path: &Path, base: &Path) -> PathBuf {
+    let mut path_components = path.components().peekable();
+    let mut base_components = base.components().peekable();
+
+    let mut shared = 0;
+    while let (Some(a), Some(b)) = (path_components.peek(), base_components.peek()) {
+        if a != b {
+            break;
+        }
+        path_components.next();
+        base_components.next();
+        shared += 1;
+    }
+
+    if shared == 0 {
+        return path.to_path_buf();
+    }
+
+    let relative: PathBuf = base_components
+        .map(|_| Component::ParentDir)
+        .chain(path_components)
+        .collect();
+
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn truncate(position: TruncatePosition, width: usize, name: &str) -> Option<String> {
+        Truncation { position, width }.truncate(name)
+    }
+
+    #[test]
+    fn short_names_are_left_alone() {
+        assert_eq!(truncate(TruncatePosition::Middle, 10, "short.txt"), None);
+    }
+
+    #[test]
+    fn middle() {
+        assert_eq!(
+            truncate(TruncatePosition::Middle, 11, "abcdefghijklmnop"),
+            Some(String::from("abcde…lmnop"))
+        );
+    }
+
+    #[test]
+    fn middle_keeps_extension() {
+        assert_eq!(
+            truncate(TruncatePosition::Middle, 12, "a_very_long_file_name.tar"),
+            Some(String::from("a_very…e.tar"))
+        );
+    }
+
+    #[test]
+    fn end_keeps_extension() {
+        assert_eq!(
+            truncate(TruncatePosition::End, 12, "a_very_long_file_name.txt"),
+            Some(String::from("a_very_….txt"))
+        );
+    }
+
+    #[test]
+    fn end_without_extension() {
+        assert_eq!(
+            truncate(TruncatePosition::End, 6, "abcdefghij"),
+            Some(String::from("abcde…"))
+        );
+    }
+
+    #[test]
+    fn wide_characters() {
+        let truncated = truncate(TruncatePosition::End, 6, "日本語のファイル").unwrap();
+        assert_eq!(truncated, "日本…");
+        assert!(str_width(&truncated) <= 6);
+    }
+
+    #[test]
+    fn combining_characters_stay_together() {
+        assert_eq!(
+            truncate(TruncatePosition::End, 3, "e\u{301}e\u{301}e\u{301}e\u{301}"),
+            Some(String::from("e\u{301}e\u{301}…"))
+        );
+    }
+
+    #[test]
+    fn joined_emoji_stay_together() {
+        let family = "👩\u{200D}👧";
+        assert_eq!(
+            truncate(TruncatePosition::End, 3, &format!("{family}{family}")),
+            Some(format!("{family}…"))
+        );
+    }
+
+    #[test]
+    fn relative_paths() {
+        let relative = |path, base| relative_path(Path::new(path), Path::new(base));
+        assert_eq!(relative("/a/b/c", "/a"), PathBuf::from("b/c"));
+        assert_eq!(relative("/a/b", "/a/c/d"), PathBuf::from("../../b"));
+        assert_eq!(relative("/a", "/a/b"), PathBuf::from(".."));
+        assert_eq!(relative("/a/b", "/a/b"), PathBuf::from("."));
+        assert_eq!(relative("/a", "/b"), PathBuf::from("../a"));
+    }
+
+    #[cfg(unix)]
+    fn plain_theme() -> crate::theme::Theme {
+        use crate::output::color_scale::{ColorDepth, ColorScaleMode, ColorScaleOptions};
+        use crate::theme::{
+            Background, Definitions, GitSymbols, Options as ThemeOptions, UseColours,
+        };
+
+        ThemeOptions {
+            use_colours: UseColours::Never,
+            colour_scale: ColorScaleOptions {
+                mode: ColorScaleMode::Fixed,
+                min_luminance: 40,
+                size: false,
+                age: false,
+                time: None,
+                size_thresholds: None,
+                depth: ColorDepth::TrueColor,
+            },
+            background: Background::Dark,
+            definitions: Definitions::default(),
+            git_symbols: GitSymbols::default(),
+        }
+        .to_theme(false)
+    }
+
+    #[cfg(unix)]
+    fn options(classify: Classify, indicators: Indicators, quote_style: QuoteStyle) -> Options {
+        Options {
+            classify,
+            indicators,
+            show_icons: ShowIcons::Never,
+            icon_pack: None,
+            quote_style,
+            embed_hyperlinks: EmbedHyperlinks::Off,
+            absolute: Absolute::Off,
+            relative_to: None,
+            is_a_tty: false,
+            hard_link_groups: false,
+            clones: false,
+            highlight_new: None,
+            suggest_broken_links: false,
+            security_audit: None,
+            in_use: None,
+            common_names: None,
+            which_duplicates: None,
+            truncate_names: None,
+            user_dirs: Arc::default(),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn link_targets_use_the_quoting_style() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("it's here"), "").unwrap();
+        std::os::unix::fs::symlink("it's here", dir.path().join("link")).unwrap();
+        let link = File::from_args(dir.path().join("link"), None, None, false, false).unwrap();
+
+        let theme = plain_theme();
+        let name = |quote_style| {
+            let options = options(Classify::JustFilenames, Indicators::Basic, quote_style);
+            let painted = options.for_file(&link, &theme).with_link_paths().paint();
+            painted.strings().to_string()
+        };
+
+        let target = |quote_style| name(quote_style).split_once(" -> ").unwrap().1.to_owned();
+        assert_eq!(target(QuoteStyle::QuoteSpaces), "\"it's here\"");
+        assert_eq!(target(QuoteStyle::Literal), "it's here");
+        assert_eq!(target(QuoteStyle::C), "\"it's here\"");
+        assert_eq!(target(QuoteStyle::Shell), "'it'\\''s here'");
+        assert_eq!(target(QuoteStyle::Escape), "it's\\ here");
+    }
+
+    /// The indicator a file at the given path gets with `-F`, using the
+    /// given set of indicators.
+    #[cfg(unix)]
+    fn indicator(path: &Path, indicators: Indicators) -> Option<&'static str> {
+        let file = File::from_args(path.to_path_buf(), None, None, false, false).unwrap();
+        let theme = plain_theme();
+        let options = options(
+            Classify::AddFileIndicators,
+            indicators,
+            QuoteStyle::QuoteSpaces,
+        );
+        let name = options.for_file(&file, &theme);
+        name.classify_char(&file)
+    }
+
+    /// Makes an executable file with the given extra permission bits.
+    #[cfg(unix)]
+    fn executable(dir: &Path, name: &str, mode: u32) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = dir.join(name);
+        std::fs::write(&path, "").unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755 | mode)).unwrap();
+        path
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn extended_broken_link() {
+        let dir = tempfile::tempdir().unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink("nowhere", &link).unwrap();
+
+        assert_eq!(indicator(&link, Indicators::Extended), Some("!"));
+        assert_eq!(indicator(&link, Indicators::Basic), Some("@"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn extended_working_link() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("target"), "").unwrap();
+        let link = dir.path().join("link");
+        std::os::unix::fs::symlink("target", &link).unwrap();
+
+        assert_eq!(indicator(&link, Indicators::Extended), Some("@"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn extended_setuid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = executable(dir.path(), "setuid", 0o4000);
+
+        assert_eq!(indicator(&path, Indicators::Extended), Some("^"));
+        assert_eq!(indicator(&path, Indicators::Basic), Some("*"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn extended_setgid() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = executable(dir.path(), "setgid", 0o2000);
+
+        assert_eq!(indicator(&path, Indicators::Extended), Some("&"));
+        assert_eq!(indicator(&path, Indicators::Basic), Some("*"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn extended_plain_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = executable(dir.path(), "plain", 0);
+
+        assert_eq!(indicator(&path, Indicators::Extended), Some("*"));
+    }
+
+    #[test]
+    #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+    fn extended_door() {
+        // The name service cache daemon listens on a door that’s always
+        // there on a running system.
+        let door = Path::new("/var/run/name_service_door");
+
+        assert_eq!(indicator(door, Indicators::Extended), Some(">"));
+        assert_eq!(indicator(door, Indicators::Basic), None);
+    }
+
+    fn link(inode: u64, count: u64) -> HardLink {
+        HardLink {
+            id: (1, inode),
+            count,
+        }
+    }
+
+    #[test]
+    fn hard_link_groups_need_two_listed_links() {
+        let links = [
+            link(10, 3),
+            link(11, 2),
+            link(10, 3),
+            link(12, 2),
+            link(11, 2),
+        ];
+        let groups = HardLinkGroups::new(links);
+
+        assert_eq!(groups.0.get(&(1, 10)), Some(&0));
+        assert_eq!(groups.0.get(&(1, 11)), Some(&1));
+        assert_eq!(groups.0.get(&(1, 12)), None);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn hard_link_markers() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        std::fs::write(dir.join("one"), "").unwrap();
+        std::fs::hard_link(dir.join("one"), dir.join("same")).unwrap();
+        std::fs::write(dir.join("two"), "").unwrap();
+        std::fs::create_dir(dir.join("elsewhere")).unwrap();
+        std::fs::hard_link(dir.join("two"), dir.join("elsewhere/two")).unwrap();
+
+        let files = ["one", "same", "two"]
+            .map(|name| File::from_args(dir.join(name), None, None, false, false).unwrap());
+        let theme = plain_theme();
+        let mut options = options(
+            Classify::JustFilenames,
+            Indicators::Basic,
+            QuoteStyle::NoQuotes,
+        );
+        options.hard_link_groups = true;
+        let groups = options.hard_link_groups_of(&files);
+
+        let names = files.iter().map(|file| {
+            let name = options
+                .for_file(file, &theme)
+                .with_hard_link_groups(groups.as_ref());
+            name.paint().strings().to_string()
+        });
+        let marked =
+            ["one ⇶ 2", "same ⇶ 2", "two"].map(|name| dir.join(name).display().to_string());
+        assert_eq!(names.collect::<Vec<_>>(), marked);
+    }
+}
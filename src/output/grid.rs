@@ -5,6 +5,8 @@ use term_grid::{Direction, Filling, Grid, GridOptions};
 use crate::fs::filter::FileFilter;
 use crate::fs::File;
 use crate::output::file_name::Options as FileStyle;
+use crate::output::table::SizeFormat;
+use crate::output::total::Totals;
 use crate::theme::Theme;
 
 #[derive(PartialEq, Eq, Debug, Copy, Clone)]
@@ -29,11 +31,20 @@ pub struct Render<'a> {
     pub opts: &'a Options,
     pub console_width: usize,
     pub filter: &'a FileFilter,
+
+    /// Whether to write a footer with the files’ totals after them, for
+    /// `--total`.
+    pub total: bool,
+
+    /// How to format the numbers in the footer, which is loaded once for the
+    /// whole listing rather than for each directory in it.
+    pub numerics: &'a locale::Numeric,
 }
 
 impl<'a> Render<'a> {
     pub fn render<W: Write>(mut self, w: &mut W) -> io::Result<()> {
         self.filter.sort_files(&mut self.files);
+        let hard_links = self.file_style.hard_link_groups_of(&self.files);
 
         let cells = self
             .files
@@ -41,6 +52,7 @@ impl<'a> Render<'a> {
             .map(|file| {
                 self.file_style
                     .for_file(file, self.theme)
+                    .with_hard_link_groups(hard_links.as_ref())
                     .paint()
                     .strings()
                     .to_string()
@@ -56,6 +68,12 @@ impl<'a> Render<'a> {
             },
         );
 
-        write!(w, "{grid}")
+        write!(w, "{grid}")?;
+
+        if self.total {
+            Totals::of(&self.files).write(w, self.theme, SizeFormat::default(), self.numerics)?;
+        }
+
+        Ok(())
     }
 }
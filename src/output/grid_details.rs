@@ -3,23 +3,28 @@
 use std::io::{self, Write};
 
 use ansi_width;
-use grid::{Direction, Filling, Grid, GridOptions};
+use grid::{Filling, Grid};
 use term_grid as grid;
 
 use crate::fs::feature::git::GitCache;
+use crate::fs::feature::trash;
 use crate::fs::filter::FileFilter;
 use crate::fs::{Dir, File};
-use crate::output::cell::TextCell;
 use crate::output::color_scale::ColorScaleInformation;
 use crate::output::details::{Options as DetailsOptions, Render as DetailsRender};
 use crate::output::file_name::Options as FileStyle;
+use crate::output::grid::Options as GridOptions;
 use crate::output::table::{Options as TableOptions, Table};
+use crate::output::total::Totals;
 use crate::theme::Theme;
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct Options {
     pub details: DetailsOptions,
     pub row_threshold: RowThreshold,
+
+    /// Which way the details views get laid out.
+    pub grid: GridOptions,
 }
 
 impl Options {
@@ -72,6 +77,9 @@ pub struct Render<'a> {
     /// mode is activated.
     pub row_threshold: RowThreshold,
 
+    /// Which way the details views get laid out.
+    pub grid: GridOptions,
+
     /// Whether we are skipping Git-ignored files.
     pub git_ignoring: bool,
 
@@ -80,6 +88,14 @@ pub struct Render<'a> {
     pub console_width: usize,
 
     pub git_repos: bool,
+
+    /// Whether to write a footer with the files’ totals after them, for
+    /// `--total`.
+    pub total: bool,
+
+    /// How to format the numbers in the footer, which is loaded once for the
+    /// whole listing rather than for each directory in it.
+    pub numerics: &'a locale::Numeric,
 }
 
 impl<'a> Render<'a> {
@@ -102,6 +118,9 @@ impl<'a> Render<'a> {
             git_ignoring:  self.git_ignoring,
             git:           self.git,
             git_repos:     self.git_repos,
+            shared_widths: None,
+            total:         false,
+            numerics:      self.numerics,
         };
     }
 
@@ -115,6 +134,12 @@ impl<'a> Render<'a> {
             .as_ref()
             .expect("Details table options not given!");
 
+        // Cells in a grid can’t be updated after they’ve been printed, so
+        // there’s no use for Git placeholders here.
+        if let Some(git) = self.git {
+            git.wait();
+        }
+
         let drender = self.details_for_column();
 
         let color_scale_info = ColorScaleInformation::from_color_scale(
@@ -127,6 +152,7 @@ impl<'a> Render<'a> {
         );
 
         let mut table = self.make_table(options);
+        table.prefetch_owners(&self.files);
 
         // It is important to collect all these rows _before_ turning them into
         // cells, because the width calculations need to consider all rows
@@ -141,6 +167,23 @@ impl<'a> Render<'a> {
             })
             .collect();
 
+        // The header rows will be printed separately, but they should be
+        // considered for the width calculations too, once the files’ rows
+        // have been, as which header each column gets depends on those.
+        if self.details.header {
+            if self.details.header_units {
+                let row = table.units_row(self.filter);
+                table.add_widths(&row);
+            }
+
+            let row = table.header_row();
+            table.add_widths(&row);
+        }
+
+        let name_header = table.name_header();
+        let totals = self.total.then(|| Totals::of(&self.files));
+        let hard_links = self.file_style.hard_link_groups_of(&self.files);
+
         let cells = rows
             .into_iter()
             .zip(self.files)
@@ -148,6 +191,7 @@ impl<'a> Render<'a> {
                 let filename = self
                     .file_style
                     .for_file(&file, self.theme)
+                    .with_hard_link_groups(hard_links.as_ref())
                     .paint()
                     .strings()
                     .to_string();
@@ -155,12 +199,14 @@ impl<'a> Render<'a> {
 
                 // This bit fixes a strange corner case. If there is a header,
                 // then "Name" will be added to the header row. That means that
-                // the filename column, should be at least 4 characters wide.
+                // the filename column, should be at least that wide.
                 // Therefore we pad the filenames with some spaces. We have to
                 // use ansi_width here, because the filename might contain some
                 // styling.
                 let padding = " ".repeat(if self.details.header {
-                    4usize.saturating_sub(ansi_width::ansi_width(&filename))
+                    name_header
+                        .width
+                        .saturating_sub(ansi_width::ansi_width(&filename))
                 } else {
                     0
                 });
@@ -171,36 +217,55 @@ impl<'a> Render<'a> {
 
         let grid = Grid::new(
             cells,
-            GridOptions {
+            grid::GridOptions {
                 filling: Filling::Spaces(4),
-                direction: Direction::TopToBottom,
+                direction: self.grid.direction(),
                 width: self.console_width,
             },
         );
 
         if self.details.header {
             let row = table.header_row();
-            let name = TextCell::paint_str(self.theme.ui.header, "Name")
-                .strings()
-                .to_string();
+            let name = name_header.strings().to_string();
             let s = table.render(row).strings().to_string();
             let combined_header = format!("{s} {name}");
-            let header_width = ansi_width::ansi_width(&combined_header);
-            for column_width in grid.column_widths() {
-                let padding = " ".repeat((column_width + 4).saturating_sub(header_width));
-                write!(w, "{combined_header}{padding}")?;
+            Self::write_header_line(w, grid.column_widths(), &combined_header)?;
+
+            if self.details.header_units {
+                let row = table.units_row(self.filter);
+                let name = table.name_units(self.filter).strings().to_string();
+                let s = table.render(row).strings().to_string();
+                Self::write_header_line(w, grid.column_widths(), &format!("{s} {name}"))?;
             }
-            writeln!(w)?;
         }
 
         write!(w, "{grid}")?;
 
+        if let Some(totals) = totals {
+            totals.write(w, self.theme, options.size_format, self.numerics)?;
+        }
+
         Ok(())
     }
 
+    /// Writes a line of the header once above each column of the grid.
+    fn write_header_line<W: Write>(
+        w: &mut W,
+        column_widths: &[usize],
+        line: &str,
+    ) -> io::Result<()> {
+        let header_width = ansi_width::ansi_width(line);
+        for column_width in column_widths {
+            let padding = " ".repeat((column_width + 4).saturating_sub(header_width));
+            write!(w, "{line}{padding}")?;
+        }
+        writeln!(w)
+    }
+
     fn make_table(&mut self, options: &'a TableOptions) -> Table<'a> {
         match (self.git, self.dir) {
             (Some(g), Some(d)) => {
+                g.discover_nested(&d.path);
                 if !g.has_anything_for(&d.path) {
                     self.git = None;
                 }
@@ -213,15 +278,7 @@ impl<'a> Render<'a> {
             (None, _) => { /* Keep Git how it is */ }
         }
 
-        let mut table = Table::new(options, self.git, self.theme, self.git_repos);
-
-        // The header row will be printed separately, but it should be
-        // considered for the width calculations.
-        if self.details.header {
-            let row = table.header_row();
-            table.add_widths(&row);
-        }
-
-        table
+        let trash = self.dir.is_some_and(|dir| trash::is_trash_dir(&dir.path));
+        Table::new(options, self.git, self.theme, self.git_repos, trash)
     }
 }
@@ -0,0 +1,195 @@
+//! Splitting a long listing into sections by how recently each file was
+//! modified, for `--group-by=mtime-bucket`.
+//!
+//! Each section gets a heading, such as “Today” or “Last week”, like the
+//! recent files in a graphical file manager. Which buckets there are can be
+//! picked with `--time-buckets`, and a file goes in the first one that it
+//! fits, in order from the most recent, with anything that doesn’t fit any
+//! of them going under “Older”. The files within a section are sorted as
+//! they would be otherwise, and every section is drawn with the same column
+//! widths, so they all line up.
+
+use std::ops::Range;
+
+use chrono::prelude::*;
+
+use crate::fs::File;
+
+/// Which buckets to group a listing’s files into.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Options {
+    /// The buckets, in order from the most recent.
+    pub buckets: Vec<Bucket>,
+}
+
+/// A span of time before today that files can have been modified in.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Copy, Clone)]
+pub enum Bucket {
+    /// Today, or at some point in the future.
+    Today,
+
+    /// The day before today.
+    Yesterday,
+
+    /// The last seven days.
+    Week,
+
+    /// The last thirty days.
+    Month,
+
+    /// The last year.
+    Year,
+}
+
+/// The buckets used when none are given.
+pub const DEFAULT_BUCKETS: &[Bucket] = &[Bucket::Today, Bucket::Yesterday, Bucket::Week];
+
+impl Bucket {
+    const NAMES: &'static [(&'static str, Self)] = &[
+        ("today", Self::Today),
+        ("yesterday", Self::Yesterday),
+        ("week", Self::Week),
+        ("month", Self::Month),
+        ("year", Self::Year),
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, bucket)| *bucket)
+    }
+
+    /// The heading shown above the files in this bucket.
+    fn heading(self) -> &'static str {
+        match self {
+            Self::Today => "Today",
+            Self::Yesterday => "Yesterday",
+            Self::Week => "Last week",
+            Self::Month => "Last month",
+            Self::Year => "Last year",
+        }
+    }
+
+    /// Whether a file modified the given number of days before today
+    /// belongs in this bucket.
+    fn contains(self, days_ago: i64) -> bool {
+        match self {
+            Self::Today => days_ago <= 0,
+            Self::Yesterday => days_ago <= 1,
+            Self::Week => days_ago < 7,
+            Self::Month => days_ago < 30,
+            Self::Year => days_ago < 365,
+        }
+    }
+}
+
+/// The heading shown above the files that don’t fit in any bucket.
+const OLDER: &str = "Older";
+
+/// A run of files in one bucket, once they’ve been put in order.
+#[derive(PartialEq, Eq, Debug)]
+pub struct Group {
+    pub heading: &'static str,
+    pub range: Range<usize>,
+}
+
+impl Options {
+    /// Puts the files in order of which bucket they’re in, keeping their
+    /// order within each one, and returns where each bucket’s files are.
+    /// Buckets without any files are left out.
+    pub fn arrange(&self, files: &mut Vec<File<'_>>) -> Vec<Group> {
+        let today = Local::now().date_naive();
+        let mut keyed: Vec<_> = files
+            .drain(..)
+            .map(|file| (self.index(today, file.modified_time()), file))
+            .collect();
+        keyed.sort_by_key(|(index, _)| *index);
+
+        let mut groups: Vec<Group> = Vec::new();
+        let mut last_index = None;
+        for (index, file) in keyed {
+            if last_index != Some(index) {
+                last_index = Some(index);
+                groups.push(Group {
+                    heading: self.heading(index),
+                    range: files.len()..files.len(),
+                });
+            }
+
+            files.push(file);
+            if let Some(group) = groups.last_mut() {
+                group.range.end = files.len();
+            }
+        }
+
+        groups
+    }
+
+    /// The position of the first bucket that a file modified at the given
+    /// time fits in, or one past the last bucket if it fits in none of them.
+    fn index(&self, today: NaiveDate, modified: Option<NaiveDateTime>) -> usize {
+        let Some(modified) = modified else {
+            return self.buckets.len();
+        };
+
+        let date = Local.from_utc_datetime(&modified).date_naive();
+        let days_ago = (today - date).num_days();
+        self.buckets
+            .iter()
+            .position(|bucket| bucket.contains(days_ago))
+            .unwrap_or(self.buckets.len())
+    }
+
+    fn heading(&self, index: usize) -> &'static str {
+        self.buckets
+            .get(index)
+            .map_or(OLDER, |bucket| bucket.heading())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn options(buckets: &[Bucket]) -> Options {
+        Options {
+            buckets: buckets.to_vec(),
+        }
+    }
+
+    fn days_before(today: NaiveDate, days: i64) -> NaiveDateTime {
+        let date = today - chrono::Duration::days(days);
+        let local = Local
+            .from_local_datetime(&date.and_hms_opt(12, 0, 0).unwrap())
+            .unwrap();
+        local.naive_utc()
+    }
+
+    #[test]
+    fn default_buckets() {
+        let options = options(DEFAULT_BUCKETS);
+        let today = Local::now().date_naive();
+
+        assert_eq!(options.index(today, Some(days_before(today, 0))), 0);
+        assert_eq!(options.index(today, Some(days_before(today, -3))), 0);
+        assert_eq!(options.index(today, Some(days_before(today, 1))), 1);
+        assert_eq!(options.index(today, Some(days_before(today, 2))), 2);
+        assert_eq!(options.index(today, Some(days_before(today, 6))), 2);
+        assert_eq!(options.index(today, Some(days_before(today, 7))), 3);
+        assert_eq!(options.index(today, None), 3);
+    }
+
+    #[test]
+    fn fewer_buckets() {
+        let options = options(&[Bucket::Week, Bucket::Year]);
+        let today = Local::now().date_naive();
+
+        assert_eq!(options.index(today, Some(days_before(today, 0))), 0);
+        assert_eq!(options.index(today, Some(days_before(today, 100))), 1);
+        assert_eq!(options.index(today, Some(days_before(today, 400))), 2);
+
+        assert_eq!(options.heading(0), "Last week");
+        assert_eq!(options.heading(2), "Older");
+    }
+}
@@ -0,0 +1,329 @@
+//! Turning a coloured listing into a standalone HTML page, for
+//! `--format=html`.
+//!
+//! Rather than teaching every view to write HTML, the listing gets drawn the
+//! usual way, with colours on, and the escape sequences in it get turned
+//! into `<span>` elements with inline styles afterwards. The page keeps the
+//! listing’s layout by putting it all in a `<pre>`, and has no stylesheet of
+//! its own, so it can be pasted into a report or a wiki as it is.
+//!
+//! Colours from the basic and 256-colour palettes become the colours that
+//! xterm uses for them, and 24-bit colours are kept as they are. Hyperlinks
+//! from `--hyperlink` become links, and any other escape sequences get
+//! dropped.
+
+use std::fmt::Write;
+
+/// What the page looks like where the listing doesn’t set a colour.
+const FOREGROUND: &str = "#e5e5e5";
+const BACKGROUND: &str = "#000000";
+
+/// The colours that xterm uses for the first sixteen palette entries.
+const BASIC_COLOURS: [&str; 16] = [
+    "#000000", "#cd0000", "#00cd00", "#cdcd00", "#0000ee", "#cd00cd", "#00cdcd", "#e5e5e5",
+    "#7f7f7f", "#ff0000", "#00ff00", "#ffff00", "#5c5cff", "#ff00ff", "#00ffff", "#ffffff",
+];
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum Colour {
+    Palette(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Colour {
+    fn css(self) -> String {
+        match self {
+            Self::Palette(n) if n < 16 => BASIC_COLOURS[usize::from(n)].to_string(),
+            Self::Palette(n) if n < 232 => {
+                // The 6×6×6 colour cube.
+                let level = |i: u8| if i == 0 { 0 } else { 55 + i * 40 };
+                let n = n - 16;
+                format!(
+                    "#{:02x}{:02x}{:02x}",
+                    level(n / 36),
+                    level(n / 6 % 6),
+                    level(n % 6)
+                )
+            }
+            Self::Palette(n) => {
+                let grey = 8 + (n - 232) * 10;
+                format!("#{grey:02x}{grey:02x}{grey:02x}")
+            }
+            Self::Rgb(r, g, b) => format!("#{r:02x}{g:02x}{b:02x}"),
+        }
+    }
+}
+
+/// The text attributes that have been turned on so far.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+struct Attributes {
+    foreground: Option<Colour>,
+    background: Option<Colour>,
+    bold: bool,
+    dimmed: bool,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+    hidden: bool,
+    strikethrough: bool,
+}
+
+impl Attributes {
+    /// Applies the parameters of one `SGR` escape sequence.
+    fn apply(&mut self, params: &str) {
+        let mut codes = params
+            .split(';')
+            .map(|code| code.parse::<u8>().unwrap_or(0));
+
+        while let Some(code) = codes.next() {
+            match code {
+                0 => *self = Self::default(),
+                1 => self.bold = true,
+                2 => self.dimmed = true,
+                3 => self.italic = true,
+                4 => self.underline = true,
+                7 => self.reverse = true,
+                8 => self.hidden = true,
+                9 => self.strikethrough = true,
+                22 => (self.bold, self.dimmed) = (false, false),
+                23 => self.italic = false,
+                24 => self.underline = false,
+                27 => self.reverse = false,
+                28 => self.hidden = false,
+                29 => self.strikethrough = false,
+                30..=37 => self.foreground = Some(Colour::Palette(code - 30)),
+                38 => self.foreground = extended_colour(&mut codes),
+                39 => self.foreground = None,
+                40..=47 => self.background = Some(Colour::Palette(code - 40)),
+                48 => self.background = extended_colour(&mut codes),
+                49 => self.background = None,
+                90..=97 => self.foreground = Some(Colour::Palette(code - 90 + 8)),
+                100..=107 => self.background = Some(Colour::Palette(code - 100 + 8)),
+                _ => {}
+            }
+        }
+    }
+
+    /// The inline style for text with these attributes, or nothing if the
+    /// text looks the same as the rest of the page.
+    fn css(&self) -> Option<String> {
+        let (mut foreground, mut background) = (
+            self.foreground.map(Colour::css),
+            self.background.map(Colour::css),
+        );
+        if self.reverse {
+            (foreground, background) = (
+                Some(background.unwrap_or_else(|| BACKGROUND.into())),
+                Some(foreground.unwrap_or_else(|| FOREGROUND.into())),
+            );
+        }
+
+        let mut css = String::new();
+        if let Some(colour) = foreground {
+            let _ = write!(css, "color:{colour};");
+        }
+        if let Some(colour) = background {
+            let _ = write!(css, "background-color:{colour};");
+        }
+        if self.bold {
+            css.push_str("font-weight:bold;");
+        }
+        if self.dimmed {
+            css.push_str("opacity:0.7;");
+        }
+        if self.italic {
+            css.push_str("font-style:italic;");
+        }
+        match (self.underline, self.strikethrough) {
+            (true, true) => css.push_str("text-decoration:underline line-through;"),
+            (true, false) => css.push_str("text-decoration:underline;"),
+            (false, true) => css.push_str("text-decoration:line-through;"),
+            (false, false) => {}
+        }
+        if self.hidden {
+            css.push_str("visibility:hidden;");
+        }
+
+        css.pop();
+        (!css.is_empty()).then_some(css)
+    }
+}
+
+/// Reads the rest of a `38` or `48` code, which is either `5;n` for a
+/// palette colour or `2;r;g;b` for a 24-bit one.
+fn extended_colour(codes: &mut impl Iterator<Item = u8>) -> Option<Colour> {
+    match codes.next()? {
+        5 => Some(Colour::Palette(codes.next()?)),
+        2 => Some(Colour::Rgb(codes.next()?, codes.next()?, codes.next()?)),
+        _ => None,
+    }
+}
+
+/// Turns the coloured text into the body of a `<pre>` element.
+fn convert(input: &str) -> String {
+    let mut html = String::new();
+    let mut attributes = Attributes::default();
+    let mut open_span = None;
+    let mut in_link = false;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            match chars.next() {
+                // A control sequence: parameters, then a final letter.
+                Some('[') => {
+                    let mut params = String::new();
+                    for c in chars.by_ref() {
+                        if c.is_ascii_alphabetic() || c == '@' || c == '~' {
+                            if c == 'm' {
+                                attributes.apply(&params);
+                            }
+                            break;
+                        }
+                        params.push(c);
+                    }
+                }
+
+                // An operating system command, ending in either a bell or
+                // an escape and a backslash.
+                Some(']') => {
+                    let mut command = String::new();
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        } else if c == '\x1b' {
+                            chars.next_if_eq(&'\\');
+                            break;
+                        }
+                        command.push(c);
+                    }
+
+                    if let Some(link) = command.strip_prefix("8;") {
+                        let url = link.split_once(';').map_or("", |(_, url)| url);
+                        close_span(&mut html, &mut open_span);
+                        if in_link {
+                            html.push_str("</a>");
+                        }
+                        in_link = !url.is_empty();
+                        if in_link {
+                            html.push_str("<a href=\"");
+                            escape(&mut html, url);
+                            html.push_str("\" style=\"color:inherit\">");
+                        }
+                    }
+                }
+
+                _ => {}
+            }
+            continue;
+        }
+
+        let css = attributes.css();
+        if css != open_span {
+            close_span(&mut html, &mut open_span);
+            if let Some(css) = css {
+                let _ = write!(html, "<span style=\"{css}\">");
+                open_span = Some(css);
+            }
+        }
+
+        let mut buf = [0; 4];
+        escape(&mut html, c.encode_utf8(&mut buf));
+    }
+
+    close_span(&mut html, &mut open_span);
+    if in_link {
+        html.push_str("</a>");
+    }
+    html
+}
+
+fn close_span(html: &mut String, open_span: &mut Option<String>) {
+    if open_span.take().is_some() {
+        html.push_str("</span>");
+    }
+}
+
+fn escape(html: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => html.push_str("&amp;"),
+            '<' => html.push_str("&lt;"),
+            '>' => html.push_str("&gt;"),
+            '"' => html.push_str("&quot;"),
+            c => html.push(c),
+        }
+    }
+}
+
+/// Wraps the coloured listing up as a whole HTML page.
+pub fn page(listing: &[u8]) -> String {
+    let body = convert(&String::from_utf8_lossy(listing));
+    format!(
+        "<!DOCTYPE html>\n\
+         <html>\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>eza</title>\n\
+         </head>\n\
+         <body>\n\
+         <pre style=\"color:{FOREGROUND};background-color:{BACKGROUND};padding:1em;\">{body}</pre>\n\
+         </body>\n\
+         </html>\n"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plain_text() {
+        assert_eq!(
+            convert("a <b> & \"c\"\n"),
+            "a &lt;b&gt; &amp; &quot;c&quot;\n"
+        );
+    }
+
+    #[test]
+    fn colours() {
+        assert_eq!(
+            convert("\x1b[1;34mdir\x1b[0m file"),
+            "<span style=\"color:#0000ee;font-weight:bold\">dir</span> file"
+        );
+        assert_eq!(
+            convert("\x1b[38;5;196mx\x1b[38;2;1;2;3my\x1b[39mz"),
+            "<span style=\"color:#ff0000\">x</span><span style=\"color:#010203\">y</span>z"
+        );
+    }
+
+    #[test]
+    fn palette() {
+        assert_eq!(Colour::Palette(9).css(), "#ff0000");
+        assert_eq!(Colour::Palette(16).css(), "#000000");
+        assert_eq!(Colour::Palette(231).css(), "#ffffff");
+        assert_eq!(Colour::Palette(244).css(), "#808080");
+    }
+
+    #[test]
+    fn reversed() {
+        assert_eq!(
+            convert("\x1b[7mx"),
+            "<span style=\"color:#000000;background-color:#e5e5e5\">x</span>"
+        );
+    }
+
+    #[test]
+    fn hyperlinks() {
+        assert_eq!(
+            convert("\x1b]8;;file:///tmp/a&b\x1b\\a&b\x1b]8;;\x1b\\"),
+            "<a href=\"file:///tmp/a&amp;b\" style=\"color:inherit\">a&amp;b</a>"
+        );
+    }
+
+    #[test]
+    fn other_sequences_are_dropped() {
+        assert_eq!(convert("\x1b[2Ka\x1b[1Ab"), "ab");
+    }
+}
@@ -0,0 +1,432 @@
+//! Machine-readable output, for `--format=jsonl`.
+//!
+//! Each file gets a JSON object on a line of its own, and each directory’s
+//! lines are written as soon as it’s been read, so a program reading a huge
+//! recursive listing can start on it straight away, and nothing has to hold
+//! the whole listing in memory. There’s no array around the objects.
+//!
+//! Every object starts with a `schema_version`, so scripts can check that
+//! the fields mean what they expect them to. Which of the other fields get
+//! written, and in what order, can be picked with `--fields`.
+//!
+//! The objects are written by hand rather than through a serialisation
+//! library, as every value is a string, a number, or null.
+
+use std::io::{self, Write};
+use std::path::Path;
+
+use chrono::{NaiveDateTime, SecondsFormat};
+
+use crate::fs::feature::git::GitCache;
+use crate::fs::fields as f;
+use crate::fs::filter::FileFilter;
+#[cfg(unix)]
+use crate::fs::metadata;
+use crate::fs::File;
+
+/// The version of the objects’ layout. This goes up whenever a field gets
+/// renamed or removed, or starts holding something different, but not when
+/// a new field is added.
+pub const SCHEMA_VERSION: u64 = 1;
+
+/// Which fields to write for each file.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Options {
+    pub fields: Vec<Field>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            fields: Field::ALL.to_vec(),
+        }
+    }
+}
+
+/// Something about a file that can be written out.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Field {
+    Path,
+    Name,
+    Type,
+    Size,
+    Inode,
+    Links,
+    Blocks,
+    Mode,
+    Uid,
+    User,
+    Gid,
+    Group,
+    Modified,
+    Accessed,
+    Changed,
+    Created,
+
+    /// Where a symlink points. Only written for symlinks.
+    Target,
+
+    /// The staged and unstaged Git statuses, as `git_staged` and
+    /// `git_unstaged`. Only written for files in a repository.
+    Git,
+}
+
+impl Field {
+    /// Every field, in the order they get written by default.
+    pub const ALL: [Self; 18] = [
+        Self::Path,
+        Self::Name,
+        Self::Type,
+        Self::Size,
+        Self::Inode,
+        Self::Links,
+        Self::Blocks,
+        Self::Mode,
+        Self::Uid,
+        Self::User,
+        Self::Gid,
+        Self::Group,
+        Self::Modified,
+        Self::Accessed,
+        Self::Changed,
+        Self::Created,
+        Self::Target,
+        Self::Git,
+    ];
+
+    /// The field with the given name, which is the same as its key in the
+    /// objects. The timestamps can also be picked by their `stat` names.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "path" => Self::Path,
+            "name" => Self::Name,
+            "type" => Self::Type,
+            "size" => Self::Size,
+            "inode" => Self::Inode,
+            "links" => Self::Links,
+            "blocks" => Self::Blocks,
+            "mode" => Self::Mode,
+            "uid" => Self::Uid,
+            "user" => Self::User,
+            "gid" => Self::Gid,
+            "group" => Self::Group,
+            "modified" | "mtime" => Self::Modified,
+            "accessed" | "atime" => Self::Accessed,
+            "changed" | "ctime" => Self::Changed,
+            "created" | "btime" => Self::Created,
+            "target" => Self::Target,
+            "git" => Self::Git,
+            _ => return None,
+        })
+    }
+}
+
+/// The JSON Lines view writes one object per file.
+pub struct Render<'a> {
+    pub files: Vec<File<'a>>,
+    pub filter: &'a FileFilter,
+    pub git: Option<&'a GitCache>,
+    pub opts: &'a Options,
+
+    /// Whether links that are being dereferenced still show their own
+    /// owner, group, and permissions.
+    pub keep_link_owners: bool,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(mut self, w: &mut W) -> io::Result<()> {
+        self.filter.sort_files(&mut self.files);
+        for file in &self.files {
+            writeln!(w, "{}", self.entry(file))?;
+        }
+
+        Ok(())
+    }
+
+    /// The chosen fields of the file, as a single-line JSON object.
+    fn entry(&self, file: &File<'_>) -> String {
+        let mut object = Object::new();
+        object.number("schema_version", SCHEMA_VERSION);
+
+        for field in &self.opts.fields {
+            self.add_field(&mut object, file, *field);
+        }
+
+        object.finish()
+    }
+
+    fn add_field(&self, object: &mut Object, file: &File<'_>, field: Field) {
+        match field {
+            Field::Path => object.string("path", &path_string(&file.path)),
+            Field::Name => object.string("name", &file.name),
+            Field::Type => object.string("type", type_name(file)),
+            Field::Size => match file.size() {
+                f::Size::Some(bytes) => object.number("size", bytes),
+                _ => object.null("size"),
+            },
+
+            #[cfg(unix)]
+            Field::Inode if file.metadata.has(metadata::Field::Inode) => {
+                object.number("inode", file.metadata.ino());
+            }
+            #[cfg(unix)]
+            Field::Inode => object.null("inode"),
+            #[cfg(unix)]
+            Field::Links if file.metadata.has(metadata::Field::Links) => {
+                object.number("links", file.metadata.nlink());
+            }
+            #[cfg(unix)]
+            Field::Links => object.null("links"),
+            #[cfg(unix)]
+            Field::Blocks if file.metadata.has(metadata::Field::Blocks) => {
+                object.number("blocks", file.metadata.blocks());
+            }
+            #[cfg(unix)]
+            Field::Blocks => object.null("blocks"),
+            #[cfg(unix)]
+            Field::Mode => match file.owner_metadata(self.keep_link_owners) {
+                Some(metadata) if metadata.has(metadata::Field::Mode) => {
+                    object.string("mode", &format!("{:04o}", metadata.mode() & 0o7777));
+                }
+                _ => object.null("mode"),
+            },
+            #[cfg(unix)]
+            Field::Uid => match file.user(self.keep_link_owners) {
+                Some(user) => object.number("uid", u64::from(user.0)),
+                None => object.null("uid"),
+            },
+            #[cfg(unix)]
+            Field::User => {
+                let name = file
+                    .user(self.keep_link_owners)
+                    .and_then(|user| user_name(file, user.0));
+                object.optional_string("user", name.as_deref());
+            }
+            #[cfg(unix)]
+            Field::Gid => match file.group(self.keep_link_owners) {
+                Some(group) => object.number("gid", u64::from(group.0)),
+                None => object.null("gid"),
+            },
+            #[cfg(unix)]
+            Field::Group => {
+                let name = file
+                    .group(self.keep_link_owners)
+                    .and_then(|group| group_name(file, group.0));
+                object.optional_string("group", name.as_deref());
+            }
+            #[cfg(not(unix))]
+            Field::Inode
+            | Field::Links
+            | Field::Blocks
+            | Field::Mode
+            | Field::Uid
+            | Field::User
+            | Field::Gid
+            | Field::Group => {}
+
+            Field::Modified => object.time("modified", file.modified_time()),
+            Field::Accessed => object.time("accessed", file.accessed_time()),
+            Field::Changed => object.time("changed", file.changed_time()),
+            Field::Created => object.time("created", file.created_time()),
+
+            Field::Target => {
+                if file.is_link() {
+                    let target = file.read_link().ok();
+                    object.optional_string("target", target.as_deref().map(path_string).as_deref());
+                }
+            }
+
+            Field::Git => {
+                if let Some(git) = self
+                    .git
+                    .filter(|git| file.is_local() && git.has_anything_for(&file.path))
+                {
+                    let status = git.get(&file.path, file.is_directory());
+                    object.string("git_staged", git_status_name(status.staged));
+                    object.string("git_unstaged", git_status_name(status.unstaged));
+                }
+            }
+        }
+    }
+}
+
+/// A JSON object that’s having its fields written one at a time.
+struct Object {
+    json: String,
+}
+
+impl Object {
+    fn new() -> Self {
+        Self {
+            json: String::from("{"),
+        }
+    }
+
+    fn key(&mut self, key: &str) {
+        if self.json.len() > 1 {
+            self.json.push(',');
+        }
+
+        escape_into(&mut self.json, key);
+        self.json.push(':');
+    }
+
+    fn string(&mut self, key: &str, value: &str) {
+        self.key(key);
+        escape_into(&mut self.json, value);
+    }
+
+    fn optional_string(&mut self, key: &str, value: Option<&str>) {
+        match value {
+            Some(value) => self.string(key, value),
+            None => self.null(key),
+        }
+    }
+
+    fn number(&mut self, key: &str, value: u64) {
+        self.key(key);
+        self.json.push_str(&value.to_string());
+    }
+
+    fn null(&mut self, key: &str) {
+        self.key(key);
+        self.json.push_str("null");
+    }
+
+    /// A timestamp in RFC 3339 format, in UTC.
+    fn time(&mut self, key: &str, time: Option<NaiveDateTime>) {
+        let time = time.map(|time| time.and_utc().to_rfc3339_opts(SecondsFormat::AutoSi, true));
+        self.optional_string(key, time.as_deref());
+    }
+
+    fn finish(mut self) -> String {
+        self.json.push('}');
+        self.json
+    }
+}
+
+/// Writes the string as a quoted JSON string, escaping the characters that
+/// can’t appear in one as they are.
+fn escape_into(json: &mut String, string: &str) {
+    json.push('"');
+
+    for c in string.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if c < ' ' => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+
+    json.push('"');
+}
+
+/// The path as a string. Parts that aren’t valid Unicode are replaced, as
+/// JSON strings can’t hold arbitrary bytes.
+fn path_string(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+/// What kind of file this is, as a single word, which `--format` templates
+/// use too.
+pub(crate) fn type_name(file: &File<'_>) -> &'static str {
+    #[rustfmt::skip]
+    return match file {
+        f if f.is_link()          => "symlink",
+        f if f.is_directory()     => "directory",
+        #[cfg(unix)]
+        f if f.is_pipe()          => "pipe",
+        #[cfg(unix)]
+        f if f.is_socket()        => "socket",
+        #[cfg(unix)]
+        f if f.is_char_device()   => "char_device",
+        #[cfg(unix)]
+        f if f.is_block_device()  => "block_device",
+        f if f.is_file()          => "file",
+        #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+        f if f.is_door()          => "door",
+        _                         => "unknown",
+    };
+}
+
+fn git_status_name(status: f::GitStatus) -> &'static str {
+    match status {
+        f::GitStatus::NotModified => "unmodified",
+        f::GitStatus::New => "new",
+        f::GitStatus::Modified => "modified",
+        f::GitStatus::Deleted => "deleted",
+        f::GitStatus::Renamed => "renamed",
+        f::GitStatus::TypeChange => "typechange",
+        f::GitStatus::Ignored => "ignored",
+        f::GitStatus::Conflicted => "conflicted",
+    }
+}
+
+/// The name of the user with the ID, from wherever the file came from.
+#[cfg(unix)]
+pub(crate) fn user_name(file: &File<'_>, uid: u32) -> Option<String> {
+    match file.metadata.source() {
+        Some(source) => source.user_name(uid),
+        None => uzers::get_user_by_uid(uid).map(|user| user.name().to_string_lossy().into_owned()),
+    }
+}
+
+/// The name of the group with the ID, from wherever the file came from.
+#[cfg(unix)]
+pub(crate) fn group_name(file: &File<'_>, gid: u32) -> Option<String> {
+    match file.metadata.source() {
+        Some(source) => source.group_name(gid),
+        None => {
+            uzers::get_group_by_gid(gid).map(|group| group.name().to_string_lossy().into_owned())
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn escaped(string: &str) -> String {
+        let mut json = String::new();
+        escape_into(&mut json, string);
+        json
+    }
+
+    #[test]
+    fn plain() {
+        assert_eq!(escaped("file.txt"), "\"file.txt\"");
+    }
+
+    #[test]
+    fn quotes_and_backslashes() {
+        assert_eq!(escaped("a \"b\" \\c"), "\"a \\\"b\\\" \\\\c\"");
+    }
+
+    #[test]
+    fn control_characters() {
+        assert_eq!(escaped("a\nb\x01"), "\"a\\nb\\u0001\"");
+    }
+
+    #[test]
+    fn field_names() {
+        assert_eq!(Field::from_name("mtime"), Some(Field::Modified));
+        assert_eq!(Field::from_name("git"), Some(Field::Git));
+        assert_eq!(Field::from_name("git_staged"), None);
+    }
+
+    #[test]
+    fn objects() {
+        let mut object = Object::new();
+        object.string("name", "ä");
+        object.number("size", 12);
+        object.null("target");
+        assert_eq!(
+            object.finish(),
+            "{\"name\":\"ä\",\"size\":12,\"target\":null}"
+        );
+    }
+}
@@ -1,3 +1,4 @@
+use std::ffi::OsStr;
 use std::io::{self, Write};
 
 use nu_ansi_term::AnsiStrings as ANSIStrings;
@@ -5,7 +6,9 @@ use nu_ansi_term::AnsiStrings as ANSIStrings;
 use crate::fs::filter::FileFilter;
 use crate::fs::File;
 use crate::output::cell::TextCellContents;
-use crate::output::file_name::Options as FileStyle;
+use crate::output::file_name::{HardLinkGroups, Options as FileStyle};
+use crate::output::table::SizeFormat;
+use crate::output::total::Totals;
 use crate::theme::Theme;
 
 /// The lines view literally just displays each file, line-by-line.
@@ -14,24 +17,80 @@ pub struct Render<'a> {
     pub theme: &'a Theme,
     pub file_style: &'a FileStyle,
     pub filter: &'a FileFilter,
+
+    /// Whether to end each name with a NUL byte rather than a newline, and
+    /// leave out the colours, escapes, and everything else around it, so
+    /// the names can be passed straight to `xargs -0`.
+    pub null_terminated: bool,
+
+    /// Whether to write each file’s whole path, rather than just its name,
+    /// for null-terminated lists that don’t have headings for directories.
+    pub full_paths: bool,
+
+    /// Whether to write a footer with the files’ totals after them, for
+    /// `--total`.
+    pub total: bool,
+
+    /// How to format the numbers in the footer, which is loaded once for the
+    /// whole listing rather than for each directory in it.
+    pub numerics: &'a locale::Numeric,
 }
 
 impl<'a> Render<'a> {
     pub fn render<W: Write>(mut self, w: &mut W) -> io::Result<()> {
         self.filter.sort_files(&mut self.files);
+        let hard_links = self.file_style.hard_link_groups_of(&self.files);
         for file in &self.files {
-            let name_cell = self.render_file(file);
+            if self.null_terminated {
+                self.write_plain_name(w, file)?;
+                continue;
+            }
+
+            let name_cell = self.render_file(file, hard_links.as_ref());
             writeln!(w, "{}", ANSIStrings(&name_cell))?;
         }
 
+        if self.total {
+            Totals::of(&self.files).write(w, self.theme, SizeFormat::default(), self.numerics)?;
+        }
+
         Ok(())
     }
 
-    fn render_file<'f>(&self, file: &'f File<'a>) -> TextCellContents {
+    fn render_file<'f>(
+        &self,
+        file: &'f File<'a>,
+        hard_links: Option<&HardLinkGroups>,
+    ) -> TextCellContents {
         self.file_style
             .for_file(file, self.theme)
             .with_link_paths()
+            .with_hard_link_groups(hard_links)
             .with_mount_details(false)
             .paint()
     }
+
+    fn write_plain_name<W: Write>(&self, w: &mut W, file: &File<'a>) -> io::Result<()> {
+        let name = if self.full_paths {
+            file.path.clone().into_os_string()
+        } else {
+            self.file_style.for_file(file, self.theme).plain_name()
+        };
+
+        w.write_all(&name_bytes(&name))?;
+        w.write_all(b"\0")
+    }
+}
+
+/// The bytes of a name, exactly as they are on Unix, where names don’t have
+/// to be valid UTF-8.
+fn name_bytes(name: &OsStr) -> Vec<u8> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        name.as_bytes().to_vec()
+    }
+
+    #[cfg(not(unix))]
+    name.to_string_lossy().into_owned().into_bytes()
 }
@@ -4,28 +4,81 @@ pub use self::cell::{DisplayWidth, TextCell, TextCellContents};
 pub use self::escape::escape;
 
 pub mod color_scale;
+pub mod compare;
 pub mod details;
 pub mod file_name;
 pub mod grid;
 pub mod grid_details;
+pub mod group;
+pub mod html;
 pub mod icons;
+pub mod json;
 pub mod lines;
 pub mod render;
+pub mod section;
+pub mod stat_view;
+pub mod summary;
 pub mod table;
+pub mod template;
+pub mod terminal;
+pub mod thumbnails;
 pub mod time;
+pub mod total;
+pub mod tree;
 
 mod cell;
 mod escape;
-mod tree;
+#[cfg(unix)]
+mod user_cache;
+pub mod width;
 
 /// The **view** contains all information about how to format output.
 #[derive(Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct View {
     pub mode: Mode,
     pub width: TerminalWidth,
     pub file_style: file_name::Options,
     pub deref_links: bool,
+    pub keep_link_owners: bool,
     pub total_size: bool,
+    pub total: bool,
+    pub compare: bool,
+    pub stat: bool,
+    pub paging: Paging,
+    pub format: OutputFormat,
+    pub json: json::Options,
+    pub template: Option<template::Template>,
+    pub null_terminated: bool,
+    pub sections: section::Options,
+    pub summary: Option<summary::Options>,
+}
+
+/// Whether the listing is for people to read or for programs to parse.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum OutputFormat {
+    /// Laid out in whichever view was picked, for reading in a terminal.
+    Text,
+
+    /// One JSON object per file, one per line, written as each directory
+    /// is read.
+    JsonLines,
+
+    /// Laid out and coloured the same as text, then written as a whole HTML
+    /// page once the listing is done.
+    Html,
+
+    /// One line per file, filled in from a template, written as each
+    /// directory is read.
+    Template,
+}
+
+impl OutputFormat {
+    /// Whether each file gets written on its own line with its full path,
+    /// so there are no headings or trees.
+    pub fn is_per_file(self) -> bool {
+        matches!(self, Self::JsonLines | Self::Template)
+    }
 }
 
 /// The **mode** is the “type” of output.
@@ -36,6 +89,7 @@ pub enum Mode {
     Details(details::Options),
     GridDetails(grid_details::Options),
     Lines,
+    Thumbnails(thumbnails::Options),
 }
 
 /// The width of the terminal requested by the user.
@@ -50,30 +104,67 @@ pub enum TerminalWidth {
 
 impl TerminalWidth {
     pub fn actual_terminal_width(self) -> Option<usize> {
-        // All of stdin, stdout, and stderr could not be connected to a
-        // terminal, but we’re only interested in stdout because it’s
-        // where the output goes.
-
-        #[cfg(unix)]
-        let stdout_term_width = {
-            use std::os::fd::AsRawFd;
-            terminal_size::terminal_size_using_fd(std::io::stdout().as_raw_fd())
-                .map(|(w, _h)| w.0 as _)
-        };
-        #[cfg(windows)]
-        let stdout_term_width = {
-            use std::os::windows::io::RawHandle;
-            use windows_sys::Win32::System::Console::{GetStdHandle, STD_OUTPUT_HANDLE};
-            terminal_size::terminal_size_using_handle(unsafe {
-                GetStdHandle(STD_OUTPUT_HANDLE) as RawHandle
-            })
-            .map(|(w, _h)| w.0 as _)
+        #[rustfmt::skip]
+        return match self {
+            Self::Set(width)  => Some(width),
+            Self::Automatic   => stdout_terminal_size().map(|(w, _h)| w),
         };
+    }
+}
+
+/// The height of the terminal requested by the user.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum TerminalHeight {
+    /// The user requested this specific number of lines.
+    Set(usize),
+
+    /// Look up the terminal size at runtime.
+    Automatic,
+}
 
+impl TerminalHeight {
+    pub fn actual_terminal_height(self) -> Option<usize> {
         #[rustfmt::skip]
         return match self {
-            Self::Set(width)  => Some(width),
-            Self::Automatic   => stdout_term_width,
+            Self::Set(height)  => Some(height),
+            Self::Automatic    => stdout_terminal_size().map(|(_w, h)| h),
         };
     }
 }
+
+/// Whether to send the output through a pager.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Paging {
+    /// Write straight to stdout.
+    Never,
+
+    /// Use a pager when stdout is a terminal and the output is longer than
+    /// the terminal is tall.
+    Auto(TerminalHeight),
+
+    /// Always use a pager.
+    Always,
+}
+
+/// The width and height of the terminal that stdout is connected to, if any.
+fn stdout_terminal_size() -> Option<(usize, usize)> {
+    // All of stdin, stdout, and stderr could not be connected to a
+    // terminal, but we’re only interested in stdout because it’s
+    // where the output goes.
+
+    #[cfg(unix)]
+    let size = {
+        use std::os::fd::AsRawFd;
+        terminal_size::terminal_size_using_fd(std::io::stdout().as_raw_fd())
+    };
+    #[cfg(windows)]
+    let size = {
+        use std::os::windows::io::RawHandle;
+        use windows_sys::Win32::System::Console::{GetStdHandle, STD_OUTPUT_HANDLE};
+        terminal_size::terminal_size_using_handle(unsafe {
+            GetStdHandle(STD_OUTPUT_HANDLE) as RawHandle
+        })
+    };
+
+    size.map(|(w, h)| (w.0 as _, h.0 as _))
+}
@@ -13,6 +13,7 @@ impl f::Type {
             Self::BlockDevice  => colours.block_device().paint("b"),
             Self::CharDevice   => colours.char_device().paint("c"),
             Self::Socket       => colours.socket().paint("s"),
+            Self::Door         => colours.door().paint("D"),
             Self::Special      => colours.special().paint("?"),
         };
     }
@@ -26,5 +27,6 @@ pub trait Colours {
     fn block_device(&self) -> Style;
     fn char_device(&self) -> Style;
     fn socket(&self) -> Style;
+    fn door(&self) -> Style;
     fn special(&self) -> Style;
 }
@@ -0,0 +1,194 @@
+use crate::fs::fields as f;
+use crate::output::table::FlagsFormat;
+use crate::output::TextCell;
+use nu_ansi_term::Style;
+
+// See ioctl_iflags(2), and chattr(1) for what each of these means.
+const FS_SECRM_FL: u32 = 0x0000_0001; // s
+const FS_UNRM_FL: u32 = 0x0000_0002; // u
+const FS_COMPR_FL: u32 = 0x0000_0004; // c
+const FS_SYNC_FL: u32 = 0x0000_0008; // S
+const FS_IMMUTABLE_FL: u32 = 0x0000_0010; // i
+const FS_APPEND_FL: u32 = 0x0000_0020; // a
+const FS_NODUMP_FL: u32 = 0x0000_0040; // d
+const FS_NOATIME_FL: u32 = 0x0000_0080; // A
+const FS_NOCOMP_FL: u32 = 0x0000_0400; // m
+const FS_ENCRYPT_FL: u32 = 0x0000_0800; // E
+const FS_JOURNAL_DATA_FL: u32 = 0x0000_4000; // j
+const FS_NOTAIL_FL: u32 = 0x0000_8000; // t
+const FS_DIRSYNC_FL: u32 = 0x0001_0000; // D
+const FS_TOPDIR_FL: u32 = 0x0002_0000; // T
+const FS_VERITY_FL: u32 = 0x0010_0000; // V
+const FS_NOCOW_FL: u32 = 0x0080_0000; // C
+const FS_DAX_FL: u32 = 0x0200_0000; // x
+const FS_PROJINHERIT_FL: u32 = 0x2000_0000; // P
+const FS_CASEFOLD_FL: u32 = 0x4000_0000; // F
+
+struct Attribute {
+    flag: u32,
+    name: &'static str,
+    abbr: char,
+}
+
+/// The attributes, in the order `lsattr` lists them.
+const ATTRIBUTES: [Attribute; 19] = [
+    Attribute {
+        flag: FS_SECRM_FL,
+        name: "secure-delete",
+        abbr: 's',
+    },
+    Attribute {
+        flag: FS_UNRM_FL,
+        name: "undeletable",
+        abbr: 'u',
+    },
+    Attribute {
+        flag: FS_SYNC_FL,
+        name: "sync",
+        abbr: 'S',
+    },
+    Attribute {
+        flag: FS_DIRSYNC_FL,
+        name: "dirsync",
+        abbr: 'D',
+    },
+    Attribute {
+        flag: FS_IMMUTABLE_FL,
+        name: "immutable",
+        abbr: 'i',
+    },
+    Attribute {
+        flag: FS_APPEND_FL,
+        name: "append-only",
+        abbr: 'a',
+    },
+    Attribute {
+        flag: FS_NODUMP_FL,
+        name: "nodump",
+        abbr: 'd',
+    },
+    Attribute {
+        flag: FS_NOATIME_FL,
+        name: "noatime",
+        abbr: 'A',
+    },
+    Attribute {
+        flag: FS_COMPR_FL,
+        name: "compressed",
+        abbr: 'c',
+    },
+    Attribute {
+        flag: FS_ENCRYPT_FL,
+        name: "encrypted",
+        abbr: 'E',
+    },
+    Attribute {
+        flag: FS_JOURNAL_DATA_FL,
+        name: "journalled",
+        abbr: 'j',
+    },
+    Attribute {
+        flag: FS_NOTAIL_FL,
+        name: "notail",
+        abbr: 't',
+    },
+    Attribute {
+        flag: FS_TOPDIR_FL,
+        name: "topdir",
+        abbr: 'T',
+    },
+    Attribute {
+        flag: FS_NOCOW_FL,
+        name: "nocow",
+        abbr: 'C',
+    },
+    Attribute {
+        flag: FS_DAX_FL,
+        name: "dax",
+        abbr: 'x',
+    },
+    Attribute {
+        flag: FS_CASEFOLD_FL,
+        name: "casefold",
+        abbr: 'F',
+    },
+    Attribute {
+        flag: FS_PROJINHERIT_FL,
+        name: "projinherit",
+        abbr: 'P',
+    },
+    Attribute {
+        flag: FS_VERITY_FL,
+        name: "verity",
+        abbr: 'V',
+    },
+    Attribute {
+        flag: FS_NOCOMP_FL,
+        name: "nocompress",
+        abbr: 'm',
+    },
+];
+
+fn flags_to_long_string(flags: f::flag_t) -> String {
+    let names = ATTRIBUTES
+        .iter()
+        .filter(|attribute| attribute.flag & flags != 0)
+        .map(|attribute| attribute.name)
+        .collect::<Vec<_>>();
+
+    if names.is_empty() {
+        "-".to_string()
+    } else {
+        names.join(",")
+    }
+}
+
+fn flags_to_short_string(flags: f::flag_t) -> String {
+    let letters = ATTRIBUTES
+        .iter()
+        .filter(|attribute| attribute.flag & flags != 0)
+        .map(|attribute| attribute.abbr)
+        .collect::<String>();
+
+    if letters.is_empty() {
+        "-".to_string()
+    } else {
+        letters
+    }
+}
+
+impl f::Flags {
+    pub fn render(self, style: Style, format: FlagsFormat) -> TextCell {
+        TextCell::paint(
+            style,
+            if format == FlagsFormat::Short {
+                flags_to_short_string(self.0)
+            } else {
+                flags_to_long_string(self.0)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn no_flags() {
+        assert_eq!(flags_to_long_string(0), "-");
+        assert_eq!(flags_to_short_string(0), "-");
+    }
+
+    #[test]
+    fn unknown_flags_are_ignored() {
+        assert_eq!(flags_to_long_string(0x0008_0000), "-");
+    }
+
+    #[test]
+    fn lsattr_order() {
+        let flags = FS_APPEND_FL | FS_IMMUTABLE_FL | FS_NOCOW_FL;
+        assert_eq!(flags_to_long_string(flags), "immutable,append-only,nocow");
+        assert_eq!(flags_to_short_string(flags), "iaC");
+    }
+}
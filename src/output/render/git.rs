@@ -2,12 +2,99 @@ use nu_ansi_term::{AnsiString as ANSIString, Style};
 
 use crate::fs::fields as f;
 use crate::output::cell::{DisplayWidth, TextCell};
+use crate::theme::GitSymbols;
 
 impl f::Git {
-    pub fn render(self, colours: &dyn Colours) -> TextCell {
+    pub fn render(self, colours: &dyn Colours, symbols: GitSymbols) -> TextCell {
+        let contents = match symbols {
+            GitSymbols::Eza => vec![self.staged.render(colours), self.unstaged.render(colours)],
+            GitSymbols::Porcelain => self.render_porcelain(colours),
+        };
+
         TextCell {
             width: DisplayWidth::from(2),
-            contents: vec![self.staged.render(colours), self.unstaged.render(colours)].into(),
+            contents: contents.into(),
+        }
+    }
+
+    /// The status written the way `git status --short` writes it, where
+    /// untracked and ignored files have the same symbol in both columns.
+    fn render_porcelain(self, colours: &dyn Colours) -> Vec<ANSIString<'static>> {
+        let column = |status| match status {
+            f::GitStatus::NotModified => colours.not_modified().paint(" "),
+            f::GitStatus::New => colours.new().paint("A"),
+            f::GitStatus::Modified => colours.modified().paint("M"),
+            f::GitStatus::Deleted => colours.deleted().paint("D"),
+            f::GitStatus::Renamed => colours.renamed().paint("R"),
+            f::GitStatus::TypeChange => colours.type_change().paint("T"),
+            f::GitStatus::Ignored => colours.ignored().paint("!"),
+            f::GitStatus::Conflicted => colours.conflicted().paint("U"),
+        };
+
+        match self.unstaged {
+            f::GitStatus::New => vec![colours.new().paint("?"), colours.new().paint("?")],
+            f::GitStatus::Ignored => {
+                vec![colours.ignored().paint("!"), colours.ignored().paint("!")]
+            }
+            unstaged => vec![column(self.staged), column(unstaged)],
+        }
+    }
+
+    /// The status with the staged and unstaged columns apart, for
+    /// `--git-detail`, with each rename followed by how similar the file is
+    /// to what it was renamed from, the way `git diff -M` writes it, such as
+    /// `R086`.
+    pub fn render_detailed(
+        self,
+        rename: Option<&f::GitRename>,
+        colours: &dyn Colours,
+        symbols: GitSymbols,
+    ) -> TextCell {
+        let columns = match symbols {
+            GitSymbols::Eza => vec![self.staged.render(colours), self.unstaged.render(colours)],
+            GitSymbols::Porcelain => self.render_porcelain(colours),
+        };
+        let scores = [
+            rename.and_then(|rename| rename.staged_similarity),
+            rename.and_then(|rename| rename.unstaged_similarity),
+        ];
+
+        let mut cell = TextCell::default();
+        for (i, (column, score)) in columns.into_iter().zip(scores).enumerate() {
+            if i > 0 {
+                cell.push(Style::default().paint(" "), 1);
+            }
+            cell.push(column, 1);
+            if let Some(score) = score {
+                cell.push(colours.renamed().paint(format!("{score:03}")), 3);
+            }
+        }
+        cell
+    }
+
+    /// The placeholder shown while the statuses are still being queried in
+    /// the background.
+    pub fn render_pending(colours: &dyn Colours) -> TextCell {
+        TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![
+                colours.not_modified().paint("…"),
+                colours.not_modified().paint("…"),
+            ]
+            .into(),
+        }
+    }
+
+    /// The placeholder shown when querying the statuses took longer than
+    /// `--io-timeout` allows, so they aren’t known.
+    pub fn render_timed_out(colours: &dyn Colours) -> TextCell {
+        TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![
+                colours.not_modified().paint("?"),
+                colours.not_modified().paint("?"),
+            ]
+            .into(),
         }
     }
 }
@@ -55,7 +142,7 @@ impl f::SubdirGitRepo {
             None => colours.no_repo().paint("-"),
         };
 
-        if let Some(status) = self.status {
+        let mut cell = if let Some(status) = self.status {
             TextCell {
                 width: DisplayWidth::from(2) + DisplayWidth::from(branch_name.as_str()),
                 contents: vec![
@@ -70,7 +157,16 @@ impl f::SubdirGitRepo {
                 width: DisplayWidth::from(branch_name.as_str()),
                 contents: vec![branch_name].into(),
             }
+        };
+
+        if let Some(behind) = self.behind.filter(|behind| *behind > 0) {
+            let behind = format!("⇣{behind}");
+            let width = behind.chars().count();
+            cell.push(Style::default().paint(" "), 1);
+            cell.push(colours.git_behind().paint(behind), width);
         }
+
+        cell
     }
 }
 
@@ -90,6 +186,7 @@ pub trait RepoColours {
     fn no_repo(&self) -> Style;
     fn git_clean(&self) -> Style;
     fn git_dirty(&self) -> Style;
+    fn git_behind(&self) -> Style;
 }
 
 #[cfg(test)]
@@ -97,6 +194,7 @@ pub mod test {
     use super::Colours;
     use crate::fs::fields as f;
     use crate::output::cell::{DisplayWidth, TextCell};
+    use crate::theme::GitSymbols;
 
     use nu_ansi_term::Color::*;
     use nu_ansi_term::Style;
@@ -142,7 +240,7 @@ pub mod test {
             contents: vec![Fixed(90).paint("-"), Fixed(90).paint("-")].into(),
         };
 
-        assert_eq!(expected, stati.render(&TestColours));
+        assert_eq!(expected, stati.render(&TestColours, GitSymbols::Eza));
     }
 
     #[test]
@@ -157,6 +255,81 @@ pub mod test {
             contents: vec![Fixed(91).paint("N"), Fixed(92).paint("M")].into(),
         };
 
-        assert_eq!(expected, stati.render(&TestColours));
+        assert_eq!(expected, stati.render(&TestColours, GitSymbols::Eza));
+    }
+
+    #[test]
+    fn detailed_rename() {
+        let stati = f::Git {
+            staged: f::GitStatus::Renamed,
+            unstaged: f::GitStatus::Modified,
+        };
+        let rename = f::GitRename {
+            from: "old.rs".into(),
+            to: "new.rs".into(),
+            staged_similarity: Some(86),
+            unstaged_similarity: None,
+        };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(6),
+            contents: vec![
+                Fixed(94).paint("R"),
+                Fixed(94).paint("086"),
+                Style::default().paint(" "),
+                Fixed(92).paint("M"),
+            ]
+            .into(),
+        };
+
+        assert_eq!(
+            expected,
+            stati.render_detailed(Some(&rename), &TestColours, GitSymbols::Eza)
+        );
+    }
+
+    #[test]
+    fn porcelain_added_changed() {
+        let stati = f::Git {
+            staged: f::GitStatus::New,
+            unstaged: f::GitStatus::Modified,
+        };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![Fixed(91).paint("A"), Fixed(92).paint("M")].into(),
+        };
+
+        assert_eq!(expected, stati.render(&TestColours, GitSymbols::Porcelain));
+    }
+
+    #[test]
+    fn porcelain_untracked() {
+        let stati = f::Git {
+            staged: f::GitStatus::NotModified,
+            unstaged: f::GitStatus::New,
+        };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![Fixed(91).paint("?"), Fixed(91).paint("?")].into(),
+        };
+
+        assert_eq!(expected, stati.render(&TestColours, GitSymbols::Porcelain));
+    }
+
+    #[test]
+    fn porcelain_unmodified() {
+        let stati = f::Git {
+            staged: f::GitStatus::NotModified,
+            unstaged: f::GitStatus::Modified,
+        };
+
+        let expected = TextCell {
+            width: DisplayWidth::from(2),
+            contents: vec![Fixed(90).paint(" "), Fixed(92).paint("M")].into(),
+        };
+
+        assert_eq!(expected, stati.render(&TestColours, GitSymbols::Porcelain));
     }
 }
@@ -11,7 +11,7 @@ pub trait Render {
         self,
         colours: &C,
         users: &U,
-        user_format: UserFormat,
+        id_format: UserFormat,
         group_format: GroupFormat,
         file_user: Option<User>,
     ) -> TextCell;
@@ -22,7 +22,7 @@ impl Render for Option<f::Group> {
         self,
         colours: &C,
         users: &U,
-        user_format: UserFormat,
+        id_format: UserFormat,
         group_format: GroupFormat,
         file_user: Option<User>,
     ) -> TextCell {
@@ -51,9 +51,15 @@ impl Render for Option<f::Group> {
             style = colours.root_group();
         }
 
-        let mut group_name = match user_format {
+        // A colour given to the group by name wins over the others.
+        if let Some(named) = colours.named_group(&group.name().to_string_lossy()) {
+            style = named;
+        }
+
+        let mut group_name = match id_format {
             UserFormat::Name => group.name().to_string_lossy().into(),
             UserFormat::Numeric => group.gid().to_string(),
+            UserFormat::NameAndId => format!("{}({})", group.name().to_string_lossy(), group.gid()),
         };
 
         if let GroupFormat::Smart = group_format {
@@ -75,6 +81,9 @@ pub trait Colours {
     fn not_yours(&self) -> Style;
     fn no_group(&self) -> Style;
     fn root_group(&self) -> Style;
+
+    /// The colour given to the group with this name, if there is one.
+    fn named_group(&self, name: &str) -> Option<Style>;
 }
 
 #[cfg(test)]
@@ -99,6 +108,10 @@ pub mod test {
         fn not_yours(&self) -> Style { Fixed(81).normal() }
         fn no_group(&self)   -> Style { Black.italic() }
         fn root_group(&self) -> Style { Fixed(82).normal() }
+
+        fn named_group(&self, name: &str) -> Option<Style> {
+            (name == "wheel").then(|| Fixed(83).normal())
+        }
     }
 
     #[test]
@@ -131,6 +144,38 @@ pub mod test {
                 file_user
             )
         );
+
+        let expected = TextCell::paint_str(TestColours.not_yours(), "folk(100)");
+        assert_eq!(
+            expected,
+            group.render(
+                &TestColours,
+                &users,
+                UserFormat::NameAndId,
+                GroupFormat::Regular,
+                file_user
+            )
+        );
+    }
+
+    #[test]
+    fn coloured_by_name() {
+        let mut users = MockUsers::with_current_uid(1000);
+        users.add_user(User::new(1000, "enoch", 100));
+        users.add_group(Group::new(10, "wheel").add_member("enoch"));
+
+        let group = Some(f::Group(10));
+        let expected = TextCell::paint_str(Fixed(83).normal(), "wheel");
+        assert_eq!(
+            expected,
+            group.render(
+                &TestColours,
+                &users,
+                UserFormat::Name,
+                GroupFormat::Regular,
+                Some(f::User(1000))
+            )
+        );
     }
 
     #[test]
@@ -180,7 +225,7 @@ pub mod test {
                 GroupFormat::Regular,
                 file_user
             )
-        )
+        );
     }
 
     #[test]
@@ -203,7 +248,7 @@ pub mod test {
                 GroupFormat::Regular,
                 file_user
             )
-        )
+        );
     }
 
     #[test]
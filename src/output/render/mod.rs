@@ -25,17 +25,25 @@ pub use self::links::Colours as LinksColours;
 mod permissions;
 pub use self::permissions::{Colours as PermissionsColours, PermissionsPlusRender};
 
+#[cfg(unix)]
+mod quota;
+#[cfg(unix)]
+pub use self::quota::Colours as QuotaColours;
+
 mod size;
 pub use self::size::Colours as SizeColours;
 
+mod target;
+pub use self::target::Colours as TargetColours;
+
 mod times;
 pub use self::times::Render as TimeRender;
 // times does too
 
-#[cfg(unix)]
 mod users;
-#[cfg(unix)]
 pub use self::users::Colours as UserColours;
+#[cfg(windows)]
+pub use self::users::OwnerRender;
 #[cfg(unix)]
 pub use self::users::Render as UserRender;
 
@@ -58,12 +66,17 @@ mod flags_bsd;
 #[cfg(windows)]
 mod flags_windows;
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+mod flags_linux;
+
 #[cfg(not(any(
     target_os = "macos",
     target_os = "freebsd",
     target_os = "netbsd",
     target_os = "openbsd",
     target_os = "dragonfly",
-    target_os = "windows"
+    target_os = "windows",
+    target_os = "linux",
+    target_os = "android"
 )))]
 mod flags;
@@ -2,6 +2,8 @@ use std::iter;
 
 use nu_ansi_term::{AnsiString as ANSIString, Style};
 
+#[cfg(unix)]
+use crate::fs::feature::acl::AclKind;
 use crate::fs::fields as f;
 use crate::output::cell::{DisplayWidth, TextCell};
 use crate::output::render::FiletypeColours;
@@ -19,10 +21,26 @@ impl PermissionsPlusRender for Option<f::PermissionsPlus> {
                 let permissions = p.permissions;
                 chars.extend(Some(permissions).render(colours, p.file_type.is_regular_file()));
 
+                // Files with access control lists get marked with which
+                // flavour they have, as NFSv4 ACLs can’t be read the same way
+                // as POSIX ones.
+                match p.acl {
+                    Some(AclKind::Posix) => chars.push(colours.attribute().paint("+")),
+                    Some(AclKind::Nfs4) => chars.push(colours.attribute().paint("#")),
+                    None => {}
+                }
+
                 if p.xattrs {
                     chars.push(colours.attribute().paint("@"));
                 }
 
+                // Files the Finder hides get marked the same way Windows
+                // marks its hidden files.
+                #[cfg(target_os = "macos")]
+                if p.finder_hidden {
+                    chars.push(colours.special_user_file().paint("h"));
+                }
+
                 // As these are all ASCII characters, we can guarantee that they’re
                 // all going to be one character wide, and don’t need to compute the
                 // cell’s display width.
@@ -48,6 +66,10 @@ impl PermissionsPlusRender for Option<f::PermissionsPlus> {
                 let mut chars = vec![p.attributes.render_type(colours)];
                 chars.extend(p.attributes.render(colours));
 
+                if let Some(access) = p.access {
+                    chars.extend(access.render(colours));
+                }
+
                 TextCell {
                     width: DisplayWidth::from(chars.len()),
                     contents: chars.into(),
@@ -151,13 +173,39 @@ impl f::Attributes {
         ]
     }
 
+    /// Renders the file’s type, with links, junctions, and app execution
+    /// aliases each getting a letter of their own. Other reparse points
+    /// stand in for ordinary files and directories, so they get shown as
+    /// those.
     pub fn render_type<C: Colours + FiletypeColours>(self, colours: &C) -> ANSIString<'static> {
-        if self.reparse_point {
-            return colours.pipe().paint("l");
-        } else if self.directory {
-            return colours.directory().paint("d");
+        match self.reparse {
+            Some(f::ReparseKind::Symlink) => colours.symlink().paint("l"),
+            Some(f::ReparseKind::Junction) => colours.symlink().paint("j"),
+            Some(f::ReparseKind::AppExecLink) => colours.symlink().paint("x"),
+            _ if self.directory => colours.directory().paint("d"),
+            _ => colours.dash().paint("-"),
         }
-        colours.dash().paint("-")
+    }
+}
+
+#[cfg(windows)]
+impl f::AccessRights {
+    /// Render the current user’s effective rights in the same style as the
+    /// user triplet of Unix permissions.
+    pub fn render<C: Colours>(self, colours: &C) -> Vec<ANSIString<'static>> {
+        let bit = |bit, chr: &'static str, style: Style| {
+            if bit {
+                style.paint(chr)
+            } else {
+                colours.dash().paint("-")
+            }
+        };
+
+        vec![
+            bit(self.read, "r", colours.user_read()),
+            bit(self.write, "w", colours.user_write()),
+            bit(self.execute, "x", colours.user_execute_file()),
+        ]
     }
 }
 
@@ -0,0 +1,137 @@
+use locale::Numeric as NumericLocale;
+use nu_ansi_term::Style;
+
+use crate::fs::fields as f;
+use crate::output::cell::TextCell;
+use crate::output::render::SizeColours;
+use crate::output::table::SizeFormat;
+
+impl f::Quota {
+    /// Renders how much the user is using, followed by their limit if they
+    /// have one, and a `*` if they’re over it, as `quota` marks them.
+    pub fn render<C: Colours>(
+        self,
+        colours: &C,
+        size_format: SizeFormat,
+        numerics: &NumericLocale,
+    ) -> TextCell {
+        let (used, limit) = match self {
+            Self::None => return TextCell::blank(colours.no_quota()),
+            Self::Usage { used, limit } => (used, limit),
+        };
+
+        let mut cell = f::Size::Some(used).render(colours, size_format, numerics, None);
+        if let Some(limit) = limit {
+            cell.push(colours.separator().paint("/"), 1);
+            cell.append(f::Size::Some(limit).render(colours, size_format, numerics, None));
+            if used > limit {
+                cell.push(colours.over_quota().paint("*"), 1);
+            }
+        }
+        cell
+    }
+}
+
+#[rustfmt::skip]
+pub trait Colours: SizeColours {
+    fn no_quota(&self)   -> Style;
+    fn separator(&self)  -> Style;
+    fn over_quota(&self) -> Style;
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::Colours;
+    use crate::fs::fields as f;
+    use crate::output::cell::TextCell;
+    use crate::output::render::SizeColours;
+    use crate::output::table::SizeFormat;
+
+    use locale::Numeric as NumericLocale;
+    use nu_ansi_term::Color::*;
+    use nu_ansi_term::Style;
+    use number_prefix::Prefix;
+
+    struct TestColours;
+
+    #[rustfmt::skip]
+    impl SizeColours for TestColours {
+        fn size(&self, _prefix: Option<Prefix>) -> Style { Fixed(66).normal() }
+        fn unit(&self, _prefix: Option<Prefix>) -> Style { Fixed(77).bold() }
+        fn no_size(&self)                       -> Style { Black.italic() }
+
+        fn major(&self) -> Style { Blue.on(Red) }
+        fn comma(&self) -> Style { Green.italic() }
+        fn minor(&self) -> Style { Cyan.on(Yellow) }
+    }
+
+    #[rustfmt::skip]
+    impl Colours for TestColours {
+        fn no_quota(&self)   -> Style { Black.italic() }
+        fn separator(&self)  -> Style { Black.bold() }
+        fn over_quota(&self) -> Style { Red.bold() }
+    }
+
+    fn render(quota: f::Quota) -> TextCell {
+        quota.render(
+            &TestColours,
+            SizeFormat::JustBytes,
+            &NumericLocale::english(),
+        )
+    }
+
+    fn cell(parts: &[(Style, &str)]) -> TextCell {
+        let mut cell = TextCell::default();
+        for (style, text) in parts {
+            cell.append(TextCell::paint(*style, (*text).to_string()));
+        }
+        cell
+    }
+
+    #[test]
+    fn no_quota() {
+        assert_eq!(render(f::Quota::None), TextCell::blank(Black.italic()));
+    }
+
+    #[test]
+    fn no_limit() {
+        let quota = f::Quota::Usage {
+            used: 512,
+            limit: None,
+        };
+        assert_eq!(render(quota), cell(&[(Fixed(66).normal(), "512")]));
+    }
+
+    #[test]
+    fn under_limit() {
+        let quota = f::Quota::Usage {
+            used: 512,
+            limit: Some(1024),
+        };
+        assert_eq!(
+            render(quota),
+            cell(&[
+                (Fixed(66).normal(), "512"),
+                (Black.bold(), "/"),
+                (Fixed(66).normal(), "1,024")
+            ])
+        );
+    }
+
+    #[test]
+    fn over_limit() {
+        let quota = f::Quota::Usage {
+            used: 2048,
+            limit: Some(1024),
+        };
+        assert_eq!(
+            render(quota),
+            cell(&[
+                (Fixed(66).normal(), "2,048"),
+                (Black.bold(), "/"),
+                (Fixed(66).normal(), "1,024"),
+                (Red.bold(), "*")
+            ])
+        );
+    }
+}
@@ -23,8 +23,20 @@ impl f::Size {
             Self::DeviceIDs(ref ids) => return ids.render(colours),
         };
 
-        let is_gradient_mode =
-            color_scale_info.is_some_and(|csi| csi.options.mode == ColorScaleMode::Gradient);
+        // The style to use for the size with the given prefix, if it’s on a
+        // colour scale. Fixed thresholds pick the prefix colour themselves.
+        let scaled_style = |prefix: Option<Prefix>| {
+            let csi =
+                color_scale_info.filter(|csi| csi.options.mode == ColorScaleMode::Gradient)?;
+
+            Some(match csi.options.size_thresholds {
+                Some(thresholds) => {
+                    let (prefix, ratio) = thresholds.place(size);
+                    csi.adjust_style_by_ratio(colours.size(prefix), ratio)
+                }
+                None => csi.adjust_style(colours.size(prefix), size as f32, csi.size),
+            })
+        };
 
         #[rustfmt::skip]
         let result = match size_format {
@@ -40,30 +52,16 @@ impl f::Size {
                 // But format the number directly using the locale.
                 let string = numerics.format_int(size);
 
-                return if is_gradient_mode {
-                    let csi = color_scale_info.unwrap();
-                    TextCell::paint(
-                        csi.adjust_style(colours.size(prefix), size as f32, csi.size),
-                        string,
-                    )
-                } else {
-                    TextCell::paint(colours.size(prefix), string)
-                }
+                let style = scaled_style(prefix).unwrap_or_else(|| colours.size(prefix));
+                return TextCell::paint(style, string);
             }
         };
 
         #[rustfmt::skip]
         let (prefix, n) = match result {
             NumberPrefix::Standalone(b) => {
-                return if is_gradient_mode {
-                    let csi = color_scale_info.unwrap();
-                    TextCell::paint(
-                        csi.adjust_style(colours.size(None), size as f32, csi.size),
-                        numerics.format_int(b),
-                    )
-                } else {
-                    TextCell::paint(colours.size(None), numerics.format_int(b))
-                }
+                let style = scaled_style(None).unwrap_or_else(|| colours.size(None));
+                return TextCell::paint(style, numerics.format_int(b));
             }
             NumberPrefix::Prefixed(p, n)  => (p, n),
         };
@@ -78,14 +76,8 @@ impl f::Size {
         TextCell {
             // symbol is guaranteed to be ASCII since unit prefixes are hardcoded.
             width: DisplayWidth::from(&*number) + symbol.len(),
-            contents: if is_gradient_mode {
-                let csi = color_scale_info.unwrap();
-                vec![
-                    csi.adjust_style(colours.size(Some(prefix)), size as f32, csi.size)
-                        .paint(number),
-                    csi.adjust_style(colours.size(Some(prefix)), size as f32, csi.size)
-                        .paint(symbol),
-                ]
+            contents: if let Some(style) = scaled_style(Some(prefix)) {
+                vec![style.paint(number), style.paint(symbol)]
             } else {
                 vec![
                     colours.size(Some(prefix)).paint(number),
@@ -0,0 +1,121 @@
+use nu_ansi_term::Style;
+
+use crate::fs::fields as f;
+use crate::output::cell::TextCell;
+
+impl f::LinkChain {
+    pub fn render<C: Colours>(&self, colours: &C) -> TextCell {
+        let (mut cell, links) = match self {
+            Self::NotALink => return TextCell::blank(colours.no_link()),
+            Self::Resolved {
+                path,
+                links,
+                exists,
+            } => {
+                let path = path.display().to_string();
+                if *exists {
+                    (TextCell::paint(colours.target(), path), *links)
+                } else {
+                    let mut cell = TextCell::paint(colours.broken(), path);
+                    cell.append(TextCell::paint_str(colours.broken(), " [broken]"));
+                    (cell, *links)
+                }
+            }
+            Self::Loop { links } => (TextCell::paint_str(colours.broken(), "[loop]"), *links),
+        };
+
+        let unit = if links == 1 { "link" } else { "links" };
+        cell.append(TextCell::paint(
+            colours.chain_length(),
+            format!(" ({links} {unit})"),
+        ));
+        cell
+    }
+}
+
+#[rustfmt::skip]
+pub trait Colours {
+    fn no_link(&self)      -> Style;
+    fn target(&self)       -> Style;
+    fn broken(&self)       -> Style;
+    fn chain_length(&self) -> Style;
+}
+
+#[cfg(test)]
+pub mod test {
+    use super::Colours;
+    use crate::fs::fields as f;
+    use crate::output::cell::TextCell;
+
+    use nu_ansi_term::Color::*;
+    use nu_ansi_term::Style;
+    use std::path::PathBuf;
+
+    struct TestColours;
+
+    #[rustfmt::skip]
+    impl Colours for TestColours {
+        fn no_link(&self)      -> Style { Black.italic() }
+        fn target(&self)       -> Style { Cyan.normal() }
+        fn broken(&self)       -> Style { Red.normal() }
+        fn chain_length(&self) -> Style { Black.bold() }
+    }
+
+    fn cell(parts: &[(Style, &str)]) -> TextCell {
+        let mut cell = TextCell::default();
+        for (style, text) in parts {
+            cell.append(TextCell::paint(*style, (*text).to_string()));
+        }
+        cell
+    }
+
+    #[test]
+    fn not_a_link() {
+        assert_eq!(
+            f::LinkChain::NotALink.render(&TestColours),
+            TextCell::blank(Black.italic())
+        );
+    }
+
+    #[test]
+    fn chain() {
+        let chain = f::LinkChain::Resolved {
+            path: PathBuf::from("/usr/bin/python3.11"),
+            links: 2,
+            exists: true,
+        };
+        assert_eq!(
+            chain.render(&TestColours),
+            cell(&[
+                (Cyan.normal(), "/usr/bin/python3.11"),
+                (Black.bold(), " (2 links)")
+            ])
+        );
+    }
+
+    #[test]
+    fn broken() {
+        let chain = f::LinkChain::Resolved {
+            path: PathBuf::from("/nowhere"),
+            links: 1,
+            exists: false,
+        };
+        assert_eq!(
+            chain.render(&TestColours),
+            cell(&[
+                (Red.normal(), "/nowhere"),
+                (Red.normal(), " [broken]"),
+                (Black.bold(), " (1 link)")
+            ])
+        );
+    }
+
+    #[test]
+    fn looped() {
+        let chain = f::LinkChain::Loop { links: 3 };
+        assert_eq!(
+            chain.render(&TestColours),
+            cell(&[(Red.normal(), "[loop]"), (Black.bold(), " (3 links)")])
+        );
+    }
+}
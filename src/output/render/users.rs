@@ -1,14 +1,18 @@
 use nu_ansi_term::Style;
+#[cfg(unix)]
 use uzers::Users;
 
 use crate::fs::fields as f;
 use crate::output::cell::TextCell;
+#[cfg(unix)]
 use crate::output::table::UserFormat;
 
+#[cfg(unix)]
 pub trait Render {
     fn render<C: Colours, U: Users>(self, colours: &C, users: &U, format: UserFormat) -> TextCell;
 }
 
+#[cfg(unix)]
 impl Render for Option<f::User> {
     fn render<C: Colours, U: Users>(self, colours: &C, users: &U, format: UserFormat) -> TextCell {
         #[rustfmt::skip]
@@ -16,14 +20,20 @@ impl Render for Option<f::User> {
             Some(u) => u.0,
             None    => return TextCell::blank(colours.no_user()),
         };
+        let user = users.get_user_by_uid(uid);
         #[rustfmt::skip]
-        let user_name = match (format, users.get_user_by_uid(uid)) {
+        let user_name = match (format, &user) {
             (_, None)                      => uid.to_string(),
             (UserFormat::Numeric, _)       => uid.to_string(),
             (UserFormat::Name, Some(user)) => user.name().to_string_lossy().into(),
+            (UserFormat::NameAndId, Some(user)) => format!("{}({uid})", user.name().to_string_lossy()),
         };
 
-        let style = if users.get_current_uid() == uid {
+        // A colour given to the user by name wins over the others.
+        let named = user.and_then(|user| colours.named_user(&user.name().to_string_lossy()));
+        let style = if let Some(style) = named {
+            style
+        } else if users.get_current_uid() == uid {
             colours.you()
         } else if uid == 0 {
             colours.root()
@@ -34,14 +44,44 @@ impl Render for Option<f::User> {
     }
 }
 
+/// Windows has no numeric user IDs to look up, so a file’s owner arrives
+/// with its account name already resolved.
+#[cfg(windows)]
+pub trait OwnerRender {
+    fn render<C: Colours>(self, colours: &C) -> TextCell;
+}
+
+#[cfg(windows)]
+impl OwnerRender for Option<&f::Owner> {
+    fn render<C: Colours>(self, colours: &C) -> TextCell {
+        let Some(owner) = self else {
+            return TextCell::blank(colours.no_user());
+        };
+
+        let style = if let Some(style) = colours.named_user(&owner.name) {
+            style
+        } else if owner.is_you {
+            colours.you()
+        } else if owner.is_admin {
+            colours.root()
+        } else {
+            colours.other()
+        };
+        TextCell::paint(style, owner.name.clone())
+    }
+}
+
 pub trait Colours {
     fn you(&self) -> Style;
     fn other(&self) -> Style;
     fn root(&self) -> Style;
     fn no_user(&self) -> Style;
+
+    /// The colour given to the user with this name, if there is one.
+    fn named_user(&self, name: &str) -> Option<Style>;
 }
 
-#[cfg(test)]
+#[cfg(all(test, unix))]
 #[allow(unused_results)]
 pub mod test {
     use super::{Colours, Render};
@@ -62,6 +102,10 @@ pub mod test {
         fn other(&self) -> Style { Blue.underline() }
         fn root(&self)         -> Style { Blue.underline() }
         fn no_user(&self)      -> Style { Black.italic() }
+
+        fn named_user(&self, name: &str) -> Option<Style> {
+            (name == "www-data").then(|| Cyan.normal())
+        }
     }
 
     #[test]
@@ -77,6 +121,10 @@ pub mod test {
         let expected = TextCell::paint_str(Red.bold(), "1000");
         #[rustfmt::skip]
         assert_eq!(expected, user.render(&TestColours, &users, UserFormat::Numeric));
+
+        let expected = TextCell::paint_str(Red.bold(), "enoch(1000)");
+        #[rustfmt::skip]
+        assert_eq!(expected, user.render(&TestColours, &users, UserFormat::NameAndId));
     }
 
     #[test]
@@ -89,6 +137,8 @@ pub mod test {
         assert_eq!(expected, user.render(&TestColours, &users, UserFormat::Name));
         #[rustfmt::skip]
         assert_eq!(expected, user.render(&TestColours, &users, UserFormat::Numeric));
+        #[rustfmt::skip]
+        assert_eq!(expected, user.render(&TestColours, &users, UserFormat::NameAndId));
     }
 
     #[test]
@@ -104,6 +154,25 @@ pub mod test {
         );
     }
 
+    #[test]
+    fn coloured_by_name() {
+        let mut users = MockUsers::with_current_uid(33);
+        users.add_user(User::new(33, "www-data", 33));
+
+        let user = Some(f::User(33));
+        let expected = TextCell::paint_str(Cyan.normal(), "www-data");
+        assert_eq!(
+            expected,
+            user.render(&TestColours, &users, UserFormat::Name)
+        );
+
+        let expected = TextCell::paint_str(Cyan.normal(), "33");
+        assert_eq!(
+            expected,
+            user.render(&TestColours, &users, UserFormat::Numeric)
+        );
+    }
+
     #[test]
     fn different_unnamed() {
         let user = Some(f::User(1000));
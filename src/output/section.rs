@@ -0,0 +1,320 @@
+//! Custom headers and footers for each directory in a listing, for
+//! `--dir-header` and `--dir-footer`.
+//!
+//! A template is ordinary text with fields in braces, such as
+//! `== {path} ({count} entries, {total_size}) ==`, which get filled in for
+//! each directory once its files have been read and filtered. Braces that
+//! should appear as they are get doubled up, as `{{` and `}}`.
+
+use std::fmt;
+use std::path::Path;
+
+use nu_ansi_term::Style;
+
+use crate::fs::fields as f;
+use crate::output::cell::TextCell;
+use crate::output::escape;
+use crate::output::file_name::QuoteStyle;
+use crate::output::table::SizeFormat;
+use crate::output::total::Totals;
+use crate::theme::Theme;
+
+/// The templates to use around each directory’s files, if any.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct Options {
+    /// Printed in place of the usual `path:` line.
+    pub header: Option<Template>,
+
+    /// Printed after the directory’s files.
+    pub footer: Option<Template>,
+}
+
+/// A line of text with fields to fill in.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum Part {
+    Text(String),
+    Field(Field),
+}
+
+/// Something about a directory that a template can show.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum Field {
+    /// The directory’s path, as it would be shown in the usual header.
+    Path,
+
+    /// The last component of the directory’s path.
+    Name,
+
+    /// How many entries are being listed.
+    Count,
+
+    /// How many of them are regular files.
+    Files,
+
+    /// How many of them are directories.
+    Dirs,
+
+    /// Their combined size.
+    TotalSize,
+}
+
+impl Field {
+    const NAMES: &'static [(&'static str, Self)] = &[
+        ("path", Self::Path),
+        ("name", Self::Name),
+        ("count", Self::Count),
+        ("files", Self::Files),
+        ("dirs", Self::Dirs),
+        ("total_size", Self::TotalSize),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, field)| *field)
+    }
+}
+
+/// Something wrong with a template.
+#[derive(PartialEq, Eq, Debug)]
+pub enum TemplateError {
+    /// A field that doesn’t exist.
+    UnknownField(String),
+
+    /// A `{` with no `}` after it.
+    Unclosed,
+
+    /// A `}` with no `{` before it.
+    Unopened,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField(name) => {
+                let names = Field::NAMES
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "unknown field {{{name}}} (choices: {names})")
+            }
+            Self::Unclosed => write!(f, "'{{' is never closed (write '{{{{' for a brace)"),
+            Self::Unopened => write!(f, "'}}' was never opened (write '}}}}' for a brace)"),
+        }
+    }
+}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, TemplateError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = input.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(TemplateError::Unclosed),
+                        }
+                    }
+
+                    let field =
+                        Field::from_name(name.trim()).ok_or(TemplateError::UnknownField(name))?;
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => return Err(TemplateError::Unopened),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+}
+
+/// Everything needed to fill in a template for one directory.
+pub struct Section<'a> {
+    pub path: &'a Path,
+    pub totals: Totals,
+    pub theme: &'a Theme,
+    pub size_format: SizeFormat,
+    pub numerics: &'a locale::Numeric,
+    pub quote_style: QuoteStyle,
+}
+
+impl<'a> Section<'a> {
+    pub fn render(&self, template: &Template) -> TextCell {
+        let mut cell = TextCell::default();
+
+        for part in &template.parts {
+            match part {
+                Part::Text(text) => cell.append(TextCell::paint(Style::default(), text.clone())),
+                Part::Field(Field::Path) => {
+                    cell.append(self.escaped(self.path.display().to_string()));
+                }
+                Part::Field(Field::Name) => {
+                    let name = self
+                        .path
+                        .file_name()
+                        .unwrap_or(self.path.as_os_str())
+                        .to_string_lossy()
+                        .into_owned();
+                    cell.append(self.escaped(name));
+                }
+                Part::Field(Field::Count) => cell.append(number(self.count())),
+                Part::Field(Field::Files) => cell.append(number(self.totals.files)),
+                Part::Field(Field::Dirs) => cell.append(number(self.totals.directories)),
+                Part::Field(Field::TotalSize) => {
+                    cell.append(f::Size::Some(self.totals.size).render(
+                        self.theme,
+                        self.size_format,
+                        self.numerics,
+                        None,
+                    ));
+                }
+            }
+        }
+
+        cell
+    }
+
+    fn count(&self) -> usize {
+        self.totals.files + self.totals.directories + self.totals.links + self.totals.others
+    }
+
+    fn escaped(&self, text: String) -> TextCell {
+        let mut bits = Vec::new();
+        escape(
+            text,
+            &mut bits,
+            Style::default(),
+            Style::default(),
+            self.quote_style,
+        );
+        bits.into_iter()
+            .map(|bit| TextCell::paint(*bit.style_ref(), bit.as_str().to_owned()))
+            .fold(TextCell::default(), |mut cell, bit| {
+                cell.append(bit);
+                cell
+            })
+    }
+}
+
+fn number(number: usize) -> TextCell {
+    TextCell::paint(Style::default(), number.to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output::color_scale::{ColorDepth, ColorScaleMode, ColorScaleOptions};
+    use crate::theme::{Background, Definitions, GitSymbols, Options as ThemeOptions, UseColours};
+
+    fn render(template: &str, path: &str, totals: Totals) -> String {
+        let theme = ThemeOptions {
+            use_colours: UseColours::Never,
+            colour_scale: ColorScaleOptions {
+                mode: ColorScaleMode::Fixed,
+                min_luminance: 40,
+                size: false,
+                age: false,
+                time: None,
+                size_thresholds: None,
+                depth: ColorDepth::TrueColor,
+            },
+            background: Background::Dark,
+            definitions: Definitions::default(),
+            git_symbols: GitSymbols::default(),
+        }
+        .to_theme(false);
+
+        let section = Section {
+            path: Path::new(path),
+            totals,
+            theme: &theme,
+            size_format: SizeFormat::JustBytes,
+            numerics: &locale::Numeric::english(),
+            quote_style: QuoteStyle::NoQuotes,
+        };
+
+        section
+            .render(&Template::parse(template).unwrap())
+            .strings()
+            .to_string()
+    }
+
+    #[test]
+    fn just_text() {
+        assert_eq!(render("files:", "/tmp", Totals::default()), "files:");
+    }
+
+    #[test]
+    fn fields() {
+        let totals = Totals {
+            files: 3,
+            directories: 2,
+            size: 999,
+            ..Totals::default()
+        };
+        assert_eq!(
+            render(
+                "== {path} ({count} entries, {total_size}) ==",
+                "/tmp/stuff",
+                totals
+            ),
+            "== /tmp/stuff (5 entries, 999) =="
+        );
+        assert_eq!(
+            render("{name}: {files} files, {dirs} dirs", "/tmp/stuff", totals),
+            "stuff: 3 files, 2 dirs"
+        );
+    }
+
+    #[test]
+    fn doubled_braces() {
+        assert_eq!(render("{{{name}}}", "src", Totals::default()), "{src}");
+    }
+
+    #[test]
+    fn unknown_field() {
+        assert_eq!(
+            Template::parse("{size}"),
+            Err(TemplateError::UnknownField("size".into()))
+        );
+    }
+
+    #[test]
+    fn unclosed() {
+        assert_eq!(Template::parse("{path"), Err(TemplateError::Unclosed));
+    }
+
+    #[test]
+    fn unopened() {
+        assert_eq!(Template::parse("path}"), Err(TemplateError::Unopened));
+    }
+}
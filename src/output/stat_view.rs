@@ -0,0 +1,341 @@
+//! Showing everything that’s known about each file, for `--stat`.
+//!
+//! Rather than one row per file, each file gets a block of lines, one for
+//! each of its fields, like the `stat` command prints. The values are
+//! rendered by the same code as the columns of the details view, so they
+//! get the same colours, and every timestamp is shown in full.
+
+use std::io::{self, Write};
+
+use crate::fs::feature::git::GitCache;
+use crate::fs::feature::xattr::{self, AclEntry, AclTag};
+use crate::fs::fields as f;
+use crate::fs::File;
+use crate::output::cell::TextCell;
+use crate::output::file_name::Options as FileStyle;
+use crate::output::table::{
+    Column, Columns, FlagsFormat, GroupFormat, HeaderLabels, Options as TableOptions, SizeFormat,
+    Table, TimeType, TimeTypes, UserFormat,
+};
+use crate::output::time::TimeFormat;
+use crate::theme::Theme;
+
+/// How long an extended attribute’s value can be before it gets cut short.
+const XATTR_PREVIEW_LIMIT: usize = 32;
+
+/// The stat view shows a block of lines for each file, with a blank line
+/// between them.
+pub struct Render<'a> {
+    pub files: Vec<File<'a>>,
+    pub theme: &'a Theme,
+    pub file_style: &'a FileStyle,
+    pub git: Option<&'a GitCache>,
+
+    /// Whether links that are being dereferenced still show their own
+    /// owner, group, and permissions.
+    pub keep_link_owners: bool,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(self, w: &mut W) -> io::Result<()> {
+        let options = table_options(self.keep_link_owners);
+        let table = Table::new(&options, self.git, self.theme, false, false);
+
+        for (index, file) in self.files.iter().enumerate() {
+            if index > 0 {
+                writeln!(w)?;
+            }
+
+            let fields = self.fields(&table, file);
+            let label_width = fields.iter().map(|(label, _)| label.len()).max();
+
+            for (label, value) in &fields {
+                let label = if label.is_empty() {
+                    String::new()
+                } else {
+                    format!("{label}:")
+                };
+
+                writeln!(
+                    w,
+                    "{:>width$} {}",
+                    label,
+                    value.strings(),
+                    width = label_width.unwrap_or(0) + 1
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Every field that’s worth showing for the file, in order, as labels
+    /// and rendered values. Values that take up more than one line have an
+    /// empty label on every line after the first.
+    fn fields(&self, table: &Table<'_>, file: &File<'_>) -> Vec<(&'static str, TextCell)> {
+        let mut fields = Vec::new();
+
+        let name = self
+            .file_style
+            .for_file(file, self.theme)
+            .with_link_paths()
+            .paint();
+        fields.push(("File", name.promote()));
+
+        fields.push((
+            "Type",
+            TextCell::paint_str(self.theme.ui.punctuation, type_name(file)),
+        ));
+
+        let mut size = table.cell_for_file(file, Column::FileSize);
+        if file.is_file() && file.metadata.len() >= 1000 {
+            size.append(TextCell::paint(
+                self.theme.ui.punctuation,
+                format!(" ({} bytes)", file.metadata.len()),
+            ));
+        }
+        fields.push(("Size", size));
+
+        #[cfg(unix)]
+        {
+            let device = file.device();
+            fields.push(("Blocks", table.cell_for_file(file, Column::Blocksize)));
+            fields.push((
+                "Device",
+                TextCell::paint(
+                    self.theme.ui.size.major,
+                    format!("{},{}", device.major, device.minor),
+                ),
+            ));
+            fields.push(("Inode", table.cell_for_file(file, Column::Inode)));
+            fields.push(("Links", table.cell_for_file(file, Column::HardLinks)));
+        }
+
+        #[cfg_attr(not(unix), allow(unused_mut))]
+        let mut permissions = table.cell_for_file(file, Column::Permissions);
+        #[cfg(unix)]
+        {
+            permissions.add_spaces(1);
+            permissions.append(table.cell_for_file(file, Column::Octal));
+        }
+        fields.push(("Permissions", permissions));
+
+        fields.push(("User", table.cell_for_file(file, Column::User)));
+        #[cfg(unix)]
+        fields.push(("Group", table.cell_for_file(file, Column::Group)));
+
+        for (label, time_type) in [
+            ("Accessed", TimeType::Accessed),
+            ("Modified", TimeType::Modified),
+            ("Changed", TimeType::Changed),
+            ("Created", TimeType::Created),
+        ] {
+            if time_type.get_corresponding_time(file).is_some() {
+                fields.push((
+                    label,
+                    table.cell_for_file(file, Column::Timestamp(time_type)),
+                ));
+            }
+        }
+
+        if self.git.is_some_and(|git| git.has_anything_for(&file.path)) {
+            fields.push(("Git", table.cell_for_file(file, Column::GitStatus)));
+        }
+
+        #[cfg(unix)]
+        if let f::SecurityContextType::SELinux(_) = file.security_context().context {
+            fields.push((
+                "Security context",
+                table.cell_for_file(file, Column::SecurityContext),
+            ));
+        }
+
+        if file.flags().0 != 0 {
+            fields.push(("Flags", table.cell_for_file(file, Column::FileFlags)));
+        }
+
+        if file.is_link() {
+            fields.push(("Target", table.cell_for_file(file, Column::Target)));
+        }
+
+        let mut label = "Attributes";
+        for attribute in file.extended_attributes() {
+            if xattr::is_acl(&attribute.name) {
+                continue;
+            }
+
+            let text = attribute.preview(XATTR_PREVIEW_LIMIT);
+            fields.push((label, TextCell::paint(self.theme.ui.perms.attribute, text)));
+            label = "";
+        }
+
+        let mut label = "ACL";
+        for attribute in file.extended_attributes() {
+            let prefix = match attribute.name.as_str() {
+                xattr::POSIX_ACL_ACCESS => "",
+                xattr::POSIX_ACL_DEFAULT => "default:",
+                _ => continue,
+            };
+
+            let entries = attribute.value.as_deref().and_then(xattr::parse_posix_acl);
+            for entry in entries.unwrap_or_default() {
+                let text = format!("{prefix}{}", acl_entry(entry));
+                fields.push((label, TextCell::paint(self.theme.ui.perms.attribute, text)));
+                label = "";
+            }
+        }
+
+        #[cfg(unix)]
+        for text in file.nfs4_acl() {
+            fields.push((label, TextCell::paint(self.theme.ui.perms.attribute, text)));
+            label = "";
+        }
+
+        fields
+    }
+}
+
+/// The details view’s table options, but with every timestamp in full, and
+/// users and groups shown with both their names and IDs.
+fn table_options(keep_link_owners: bool) -> TableOptions {
+    TableOptions {
+        size_format: SizeFormat::default(),
+        time_format: TimeFormat::FullISO,
+        user_format: UserFormat::NameAndId,
+        group_id_format: UserFormat::NameAndId,
+        group_format: GroupFormat::Regular,
+        flags_format: FlagsFormat::default(),
+        columns: Columns {
+            time_types: TimeTypes::default(),
+            inode: false,
+            links: false,
+            blocksize: false,
+            group: false,
+            git: false,
+            subdir_git_repos: false,
+            subdir_git_repos_no_stat: false,
+            git_since: None,
+            octal: false,
+            security_context: false,
+            file_flags: false,
+            target: false,
+            quota: false,
+            permissions: false,
+            filesize: false,
+            user: false,
+            order: None,
+        },
+        git_async: false,
+        git_submodules: f::SubmoduleIgnore::default(),
+        git_repos_behind: false,
+        git_detail: false,
+        headers: HeaderLabels::default(),
+        keep_link_owners,
+    }
+}
+
+/// What kind of file this is, in words.
+fn type_name(file: &File<'_>) -> &'static str {
+    #[rustfmt::skip]
+    return match file {
+        f if f.is_link()          => "symbolic link",
+        f if f.is_directory()     => "directory",
+        #[cfg(unix)]
+        f if f.is_pipe()          => "named pipe",
+        #[cfg(unix)]
+        f if f.is_socket()        => "socket",
+        #[cfg(unix)]
+        f if f.is_char_device()   => "character device",
+        #[cfg(unix)]
+        f if f.is_block_device()  => "block device",
+        f if f.is_file()          => "regular file",
+        #[cfg(any(target_os = "solaris", target_os = "illumos"))]
+        f if f.is_door()          => "door",
+        _                         => "unknown",
+    };
+}
+
+/// An access control list entry, written the way `getfacl` writes it, such
+/// as `user:alice:r-x`.
+fn acl_entry(entry: AclEntry) -> String {
+    let (kind, who) = match entry.tag {
+        AclTag::Owner => ("user", String::new()),
+        AclTag::User(uid) => ("user", user_name(uid)),
+        AclTag::OwningGroup => ("group", String::new()),
+        AclTag::Group(gid) => ("group", group_name(gid)),
+        AclTag::Mask => ("mask", String::new()),
+        AclTag::Other => ("other", String::new()),
+    };
+
+    let bit = |mask: u16, c: char| {
+        if entry.permissions & mask == 0 {
+            '-'
+        } else {
+            c
+        }
+    };
+    format!(
+        "{kind}:{who}:{}{}{}",
+        bit(0o4, 'r'),
+        bit(0o2, 'w'),
+        bit(0o1, 'x')
+    )
+}
+
+#[cfg(unix)]
+fn user_name(uid: u32) -> String {
+    uzers::get_user_by_uid(uid).map_or_else(
+        || uid.to_string(),
+        |user| user.name().to_string_lossy().into_owned(),
+    )
+}
+
+#[cfg(unix)]
+fn group_name(gid: u32) -> String {
+    uzers::get_group_by_gid(gid).map_or_else(
+        || gid.to_string(),
+        |group| group.name().to_string_lossy().into_owned(),
+    )
+}
+
+#[cfg(not(unix))]
+fn user_name(uid: u32) -> String {
+    uid.to_string()
+}
+
+#[cfg(not(unix))]
+fn group_name(gid: u32) -> String {
+    gid.to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn owner_entry() {
+        let entry = AclEntry {
+            tag: AclTag::Owner,
+            permissions: 0o6,
+        };
+        assert_eq!(acl_entry(entry), "user::rw-");
+    }
+
+    #[test]
+    fn other_entry() {
+        let entry = AclEntry {
+            tag: AclTag::Other,
+            permissions: 0o5,
+        };
+        assert_eq!(acl_entry(entry), "other::r-x");
+    }
+
+    #[test]
+    fn unknown_group_entry() {
+        let entry = AclEntry {
+            tag: AclTag::Group(0xDEAD_BEEF),
+            permissions: 0,
+        };
+        assert_eq!(acl_entry(entry), "group:3735928559:---");
+    }
+}
@@ -0,0 +1,271 @@
+//! Adding up a listing by file extension, for `--summary=ext`.
+//!
+//! Rather than one line per file, each extension gets one line, with how
+//! many files have it, how big they are altogether, and which of them is the
+//! biggest. Directories aren’t counted, as they have no size of their own to
+//! add up. Like the `--total` footer, each listing gets a summary of its
+//! own, so in a recursive listing, each directory is added up separately.
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use nu_ansi_term::Style;
+
+use crate::fs::fields as f;
+use crate::fs::filter::{FileFilter, FileFilterFlags};
+use crate::fs::File;
+use crate::output::cell::TextCell;
+use crate::output::file_name::Options as FileStyle;
+use crate::output::table::SizeFormat;
+use crate::output::total::Totals;
+use crate::theme::Theme;
+
+/// What a summary is grouped and sorted by.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub struct Options {
+    pub sort: SummarySort,
+}
+
+/// Which column the rows of a summary are sorted by.
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Default)]
+pub enum SummarySort {
+    /// Alphabetically by extension.
+    Extension,
+
+    /// Most files first.
+    Count,
+
+    /// Biggest combined size first.
+    #[default]
+    Size,
+
+    /// Biggest single file first.
+    Largest,
+}
+
+/// The files with one extension.
+#[derive(PartialEq, Eq, Debug)]
+struct Row {
+    /// The extension, or nothing for files without one.
+    ext: Option<String>,
+
+    count: usize,
+
+    /// The combined size of every file.
+    size: u64,
+
+    /// The size of the biggest file, and its position in the listing.
+    largest: (u64, usize),
+}
+
+impl SummarySort {
+    fn compare(self, a: &Row, b: &Row) -> Ordering {
+        let by_ext = || a.ext.cmp(&b.ext);
+
+        match self {
+            Self::Extension => by_ext(),
+            Self::Count => b.count.cmp(&a.count).then_with(by_ext),
+            Self::Size => b.size.cmp(&a.size).then_with(by_ext),
+            Self::Largest => b.largest.0.cmp(&a.largest.0).then_with(by_ext),
+        }
+    }
+}
+
+/// Groups the files by extension, in the order the options ask for.
+fn rows(files: &[File<'_>], sort: SummarySort, reverse: bool) -> Vec<Row> {
+    let mut rows: HashMap<Option<String>, Row> = HashMap::new();
+
+    for (index, file) in files.iter().enumerate() {
+        if file.is_directory() {
+            continue;
+        }
+
+        let size = match file.size() {
+            f::Size::Some(size) => size,
+            _ => 0,
+        };
+
+        let row = rows.entry(file.ext.clone()).or_insert_with(|| Row {
+            ext: file.ext.clone(),
+            count: 0,
+            size: 0,
+            largest: (size, index),
+        });
+
+        row.count += 1;
+        row.size += size;
+        if size > row.largest.0 {
+            row.largest = (size, index);
+        }
+    }
+
+    let mut rows = rows.into_values().collect::<Vec<_>>();
+    sort_rows(&mut rows, sort, reverse);
+    rows
+}
+
+fn sort_rows(rows: &mut [Row], sort: SummarySort, reverse: bool) {
+    rows.sort_by(|a, b| sort.compare(a, b));
+    if reverse {
+        rows.reverse();
+    }
+}
+
+/// The summary view shows a line for each extension, under a header.
+pub struct Render<'a> {
+    pub files: Vec<File<'a>>,
+    pub theme: &'a Theme,
+    pub file_style: &'a FileStyle,
+    pub filter: &'a FileFilter,
+    pub size_format: SizeFormat,
+    pub opts: &'a Options,
+
+    /// Whether to write a footer with the files’ totals after them, for
+    /// `--total`.
+    pub total: bool,
+
+    /// How to format the numbers in the footer, which is loaded once for the
+    /// whole listing rather than for each directory in it.
+    pub numerics: &'a locale::Numeric,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(self, w: &mut W) -> io::Result<()> {
+        let reverse = self.filter.flags.contains(&FileFilterFlags::Reverse);
+        let rows = rows(&self.files, self.opts.sort, reverse);
+        if rows.is_empty() {
+            return Ok(());
+        }
+
+        let header = self.theme.ui.header;
+        let size =
+            |bytes| f::Size::Some(bytes).render(self.theme, self.size_format, self.numerics, None);
+
+        let mut lines = vec![[
+            TextCell::paint_str(header, "Extension"),
+            TextCell::paint_str(header, "Count"),
+            TextCell::paint_str(header, "Size"),
+            TextCell::paint_str(header, "Largest"),
+        ]];
+
+        let mut names = vec![TextCell::default()];
+        for row in &rows {
+            let ext = match &row.ext {
+                Some(ext) => TextCell::paint(Style::default(), format!(".{ext}")),
+                None => TextCell::paint_str(self.theme.ui.punctuation, "(none)"),
+            };
+
+            lines.push([
+                ext,
+                TextCell::paint(Style::default(), row.count.to_string()),
+                size(row.size),
+                size(row.largest.0),
+            ]);
+
+            let largest = &self.files[row.largest.1];
+            names.push(
+                self.file_style
+                    .for_file(largest, self.theme)
+                    .paint()
+                    .promote(),
+            );
+        }
+
+        let mut widths = [0; 4];
+        for line in &lines {
+            for (width, cell) in widths.iter_mut().zip(line) {
+                *width = (*width).max(*cell.width);
+            }
+        }
+
+        for (line, name) in lines.into_iter().zip(names) {
+            let mut cell = TextCell::default();
+            for (column, (part, width)) in line.into_iter().zip(widths).enumerate() {
+                let padding = width - *part.width;
+
+                // The extensions line up on the left, and the numbers on
+                // the right.
+                if column == 0 {
+                    cell.append(part);
+                    cell.add_spaces(padding);
+                } else {
+                    cell.add_spaces(padding + 1);
+                    cell.append(part);
+                }
+            }
+
+            if *name.width > 0 {
+                cell.add_spaces(1);
+                cell.append(name);
+            }
+            writeln!(w, "{}", cell.strings())?;
+        }
+
+        if self.total {
+            Totals::of(&self.files).write(w, self.theme, self.size_format, self.numerics)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn row(ext: Option<&str>, count: usize, size: u64, largest: u64) -> Row {
+        Row {
+            ext: ext.map(String::from),
+            count,
+            size,
+            largest: (largest, 0),
+        }
+    }
+
+    fn sorted(sort: SummarySort, reverse: bool) -> Vec<Option<String>> {
+        let mut rows = vec![
+            row(Some("mp4"), 2, 3000, 2000),
+            row(Some("jpg"), 10, 500, 100),
+            row(None, 1, 800, 800),
+        ];
+        sort_rows(&mut rows, sort, reverse);
+        rows.into_iter().map(|row| row.ext).collect()
+    }
+
+    fn exts(exts: &[Option<&str>]) -> Vec<Option<String>> {
+        exts.iter().map(|ext| ext.map(String::from)).collect()
+    }
+
+    #[test]
+    fn by_size() {
+        assert_eq!(
+            sorted(SummarySort::Size, false),
+            exts(&[Some("mp4"), None, Some("jpg")])
+        );
+    }
+
+    #[test]
+    fn by_count() {
+        assert_eq!(
+            sorted(SummarySort::Count, false),
+            exts(&[Some("jpg"), Some("mp4"), None])
+        );
+    }
+
+    #[test]
+    fn by_largest_reversed() {
+        assert_eq!(
+            sorted(SummarySort::Largest, true),
+            exts(&[Some("jpg"), None, Some("mp4")])
+        );
+    }
+
+    #[test]
+    fn by_extension() {
+        assert_eq!(
+            sorted(SummarySort::Extension, false),
+            exts(&[None, Some("jpg"), Some("mp4")])
+        );
+    }
+}
@@ -1,43 +1,104 @@
 use std::cmp::max;
 use std::ops::Deref;
-#[cfg(unix)]
-use std::sync::{Mutex, MutexGuard};
 
 use chrono::prelude::*;
 
 use log::*;
+use nu_ansi_term::Style;
 use once_cell::sync::Lazy;
-#[cfg(unix)]
-use uzers::UsersCache;
 
 use crate::fs::feature::git::GitCache;
+use crate::fs::filter::{FileFilter, FileFilterFlags, SortField};
+use crate::fs::metadata::Field;
 use crate::fs::{fields as f, File};
 use crate::options::vars::EZA_WINDOWS_ATTRIBUTES;
 use crate::options::Vars;
-use crate::output::cell::TextCell;
+use crate::output::cell::{DisplayWidth, TextCell};
 use crate::output::color_scale::ColorScaleInformation;
+#[cfg(windows)]
+use crate::output::render::OwnerRender;
 #[cfg(unix)]
 use crate::output::render::{GroupRender, OctalPermissionsRender, UserRender};
 use crate::output::render::{PermissionsPlusRender, TimeRender};
 use crate::output::time::TimeFormat;
+#[cfg(unix)]
+use crate::output::user_cache::{SourceUsers, UserCache};
 use crate::theme::Theme;
 
 use super::color_scale::ColorScaleMode;
 
 /// Options for displaying a table.
 #[derive(PartialEq, Eq, Debug)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Options {
     pub size_format: SizeFormat,
     pub time_format: TimeFormat,
     pub user_format: UserFormat,
+    pub group_id_format: UserFormat,
     pub group_format: GroupFormat,
     pub flags_format: FlagsFormat,
     pub columns: Columns,
+
+    /// Whether to query Git in the background, showing placeholders in the
+    /// Git column until it’s done.
+    pub git_async: bool,
+
+    /// Which changes inside Git submodules count towards their statuses.
+    pub git_submodules: f::SubmoduleIgnore,
+
+    /// Whether the repository column also shows how many commits each
+    /// branch is behind its upstream.
+    pub git_repos_behind: bool,
+
+    /// Whether the Git column shows the staged and unstaged statuses apart,
+    /// with how similar each renamed file is to what it was renamed from.
+    pub git_detail: bool,
+
+    /// The text to use in the header row in place of the usual headers.
+    pub headers: HeaderLabels,
+
+    /// Whether links that are being dereferenced still show their own
+    /// owner, group, and permissions, for `--no-dereference-owner`.
+    pub keep_link_owners: bool,
+}
+
+/// Replacements for the headers at the top of each column, such as ones in
+/// another language, read from the configuration file. Each column can also
+/// have a shorter header, which gets used instead when the full one would
+/// make the column any wider than the values in it.
+#[derive(PartialEq, Eq, Debug, Default, Clone)]
+pub struct HeaderLabels {
+    /// The header to use for each column that has been given one.
+    pub full: Vec<(Column, String)>,
+
+    /// The shorter header to use for each column that has been given one.
+    pub short: Vec<(Column, String)>,
+
+    /// The header to use above the file names, if not “Name”.
+    pub name: Option<String>,
+}
+
+impl HeaderLabels {
+    /// The header for a column whose values are, at most, the given width.
+    fn label(&self, column: Column, values_width: usize) -> &str {
+        let full = Self::find(&self.full, column).unwrap_or_else(|| column.header());
+        match Self::find(&self.short, column) {
+            Some(short) if *DisplayWidth::from(full) > values_width => short,
+            _ => full,
+        }
+    }
+
+    fn find(labels: &[(Column, String)], column: Column) -> Option<&str> {
+        labels
+            .iter()
+            .find(|(c, _)| *c == column)
+            .map(|(_, label)| label.as_str())
+    }
 }
 
 /// Extra columns to display in the table.
 #[allow(clippy::struct_excessive_bools)]
-#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Columns {
     /// At least one of these timestamps will be shown.
     pub time_types: TimeTypes,
@@ -53,15 +114,38 @@ pub struct Columns {
     pub octal: bool,
     pub security_context: bool,
     pub file_flags: bool,
+    pub target: bool,
+    pub quota: bool,
+
+    /// The Git reference to show whether each file has changed since, with
+    /// `--git-age-since`.
+    pub git_since: Option<String>,
 
     // Defaults to true:
     pub permissions: bool,
     pub filesize: bool,
     pub user: bool,
+
+    /// The exact columns to show and the order to show them in, if the user
+    /// picked them with `--columns` rather than with the flags above.
+    pub order: Option<Vec<Column>>,
 }
 
 impl Columns {
-    pub fn collect(&self, actually_enable_git: bool, git_repos: bool) -> Vec<Column> {
+    pub fn collect(&self, actually_enable_git: bool, git_repos: bool, trash: bool) -> Vec<Column> {
+        if let Some(order) = &self.order {
+            return order
+                .iter()
+                .filter(|column| match column {
+                    Column::GitStatus => actually_enable_git,
+                    Column::GitSince => actually_enable_git && self.git_since.is_some(),
+                    Column::SubdirGitRepo(_) => git_repos,
+                    _ => true,
+                })
+                .copied()
+                .collect();
+        }
+
         let mut columns = Vec::with_capacity(4);
 
         if self.inode {
@@ -93,7 +177,6 @@ impl Columns {
         }
 
         if self.user {
-            #[cfg(unix)]
             columns.push(Column::User);
         }
 
@@ -102,6 +185,11 @@ impl Columns {
             columns.push(Column::Group);
         }
 
+        if self.quota {
+            #[cfg(unix)]
+            columns.push(Column::Quota);
+        }
+
         if self.file_flags {
             columns.push(Column::FileFlags);
         }
@@ -131,6 +219,10 @@ impl Columns {
             columns.push(Column::GitStatus);
         }
 
+        if self.git_since.is_some() && actually_enable_git {
+            columns.push(Column::GitSince);
+        }
+
         if self.subdir_git_repos && git_repos {
             columns.push(Column::SubdirGitRepo(true));
         }
@@ -139,19 +231,27 @@ impl Columns {
             columns.push(Column::SubdirGitRepo(false));
         }
 
+        if self.target {
+            columns.push(Column::Target);
+        }
+
+        if trash {
+            columns.push(Column::DeletionDate);
+            columns.push(Column::OriginalPath);
+        }
+
         columns
     }
 }
 
 /// A table contains these.
-#[derive(Debug, Copy, Clone)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
 pub enum Column {
     Permissions,
     FileSize,
     Timestamp(TimeType),
     #[cfg(unix)]
     Blocksize,
-    #[cfg(unix)]
     User,
     #[cfg(unix)]
     Group,
@@ -160,12 +260,18 @@ pub enum Column {
     #[cfg(unix)]
     Inode,
     GitStatus,
+    GitSince,
     SubdirGitRepo(bool),
     #[cfg(unix)]
     Octal,
     #[cfg(unix)]
     SecurityContext,
     FileFlags,
+    DeletionDate,
+    OriginalPath,
+    Target,
+    #[cfg(unix)]
+    Quota,
 }
 
 /// Each column can pick its own **Alignment**. Usually, numbers are
@@ -182,9 +288,12 @@ impl Column {
     pub fn alignment(self) -> Alignment {
         #[allow(clippy::wildcard_in_or_patterns)]
         match self {
-            Self::FileSize | Self::HardLinks | Self::Inode | Self::Blocksize | Self::GitStatus => {
-                Alignment::Right
-            }
+            Self::FileSize
+            | Self::HardLinks
+            | Self::Inode
+            | Self::Blocksize
+            | Self::Quota
+            | Self::GitStatus => Alignment::Right,
             Self::Timestamp(_) | _ => Alignment::Left,
         }
     }
@@ -197,6 +306,48 @@ impl Column {
         }
     }
 
+    /// Whether sorting by the given field puts this column in order, and if
+    /// so, whether it puts it in ascending order.
+    fn sorted_by(self, field: SortField) -> Option<bool> {
+        match (self, field) {
+            (Self::FileSize, SortField::Size)
+            | (Self::Timestamp(TimeType::Modified), SortField::ModifiedDate)
+            | (Self::Timestamp(TimeType::Accessed), SortField::AccessedDate)
+            | (Self::Timestamp(TimeType::Changed), SortField::ChangedDate)
+            | (Self::Timestamp(TimeType::Created), SortField::CreatedDate) => Some(true),
+            #[cfg(unix)]
+            (Self::Inode, SortField::FileInode)
+            | (Self::Blocksize, SortField::Blocks)
+            | (Self::HardLinks, SortField::Links) => Some(true),
+            (Self::Timestamp(TimeType::Modified), SortField::ModifiedAge) => Some(false),
+            _ => None,
+        }
+    }
+
+    /// The field of a file’s metadata that this column shows, if it shows
+    /// one that not every file might have.
+    fn field(self) -> Option<Field> {
+        match self {
+            Self::Permissions => Some(Field::Mode),
+            Self::FileSize => Some(Field::Size),
+            Self::Timestamp(TimeType::Modified) => Some(Field::Modified),
+            Self::Timestamp(TimeType::Changed) => Some(Field::Changed),
+            Self::Timestamp(TimeType::Created) => Some(Field::Created),
+            Self::Timestamp(TimeType::Accessed) => Some(Field::Accessed),
+            #[cfg(unix)]
+            Self::Octal => Some(Field::Mode),
+            #[cfg(unix)]
+            Self::Blocksize => Some(Field::Blocks),
+            #[cfg(unix)]
+            Self::User | Self::Group => Some(Field::Owner),
+            #[cfg(unix)]
+            Self::HardLinks => Some(Field::Links),
+            #[cfg(unix)]
+            Self::Inode => Some(Field::Inode),
+            _ => None,
+        }
+    }
+
     /// Get the text that should be printed at the top, when the user elects
     /// to have a header row printed.
     pub fn header(self) -> &'static str {
@@ -211,6 +362,8 @@ impl Column {
             Self::Blocksize => "Blocksize",
             #[cfg(unix)]
             Self::User => "User",
+            #[cfg(windows)]
+            Self::User => "Owner",
             #[cfg(unix)]
             Self::Group => "Group",
             #[cfg(unix)]
@@ -218,16 +371,33 @@ impl Column {
             #[cfg(unix)]
             Self::Inode => "inode",
             Self::GitStatus => "Git",
+            Self::GitSince => "Changed Since",
             Self::SubdirGitRepo(_) => "Repo",
             #[cfg(unix)]
             Self::Octal => "Octal",
             #[cfg(unix)]
             Self::SecurityContext => "Security Context",
             Self::FileFlags => "Flags",
+            Self::DeletionDate => "Date Deleted",
+            Self::OriginalPath => "Original Path",
+            Self::Target => "Target",
+            #[cfg(unix)]
+            Self::Quota => "Quota",
         }
     }
 }
 
+/// The arrow shown under the header of the column the files are sorted by:
+/// pointing up if its values go up going down the list, and down if they
+/// go down.
+fn sort_arrow(ascending: bool, filter: &FileFilter) -> &'static str {
+    if ascending == filter.flags.contains(&FileFilterFlags::Reverse) {
+        "▼"
+    } else {
+        "▲"
+    }
+}
+
 /// Formatting options for file sizes.
 #[allow(clippy::enum_variant_names)]
 #[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
@@ -252,6 +422,8 @@ pub enum UserFormat {
     Numeric,
     /// Show the name
     Name,
+    /// Show the name, followed by the UID / GID in brackets, like `alice(1000)`
+    NameAndId,
 }
 
 /// Formatting options for group only.
@@ -365,15 +537,10 @@ pub struct Environment {
 
     /// Mapping cache of user IDs to usernames.
     #[cfg(unix)]
-    users: Mutex<UsersCache>,
+    users: UserCache,
 }
 
 impl Environment {
-    #[cfg(unix)]
-    pub fn lock_users(&self) -> MutexGuard<'_, UsersCache> {
-        self.users.lock().unwrap()
-    }
-
     fn load_all() -> Self {
         let time_offset = *Local::now().offset();
 
@@ -381,7 +548,7 @@ impl Environment {
             locale::Numeric::load_user_locale().unwrap_or_else(|_| locale::Numeric::english());
 
         #[cfg(unix)]
-        let users = Mutex::new(UsersCache::new());
+        let users = UserCache::new();
 
         Self {
             time_offset,
@@ -404,9 +571,15 @@ pub struct Table<'a> {
     #[cfg(unix)]
     user_format: UserFormat,
     #[cfg(unix)]
+    group_id_format: UserFormat,
+    #[cfg(unix)]
     group_format: GroupFormat,
     flags_format: FlagsFormat,
     git: Option<&'a GitCache>,
+    git_repos_behind: bool,
+    git_detail: bool,
+    headers: &'a HeaderLabels,
+    keep_link_owners: bool,
 }
 
 #[derive(Clone)]
@@ -420,8 +593,9 @@ impl<'a> Table<'a> {
         git: Option<&'a GitCache>,
         theme: &'a Theme,
         git_repos: bool,
+        trash: bool,
     ) -> Table<'a> {
-        let columns = options.columns.collect(git.is_some(), git_repos);
+        let columns = options.columns.collect(git.is_some(), git_repos, trash);
         let widths = TableWidths::zero(columns.len());
         let env = &*ENVIRONMENT;
 
@@ -438,25 +612,250 @@ impl<'a> Table<'a> {
             #[cfg(unix)]
             user_format: options.user_format,
             #[cfg(unix)]
+            group_id_format: options.group_id_format,
+            #[cfg(unix)]
             group_format: options.group_format,
             flags_format: options.flags_format,
+            git_repos_behind: options.git_repos_behind,
+            git_detail: options.git_detail,
+            headers: &options.headers,
+            keep_link_owners: options.keep_link_owners,
         }
     }
 
+    /// Looks up the owners of all the given files in one go, so that rendering
+    /// their rows in parallel doesn’t have to wait on any lookups.
+    #[cfg(unix)]
+    pub fn prefetch_owners(&self, files: &[File<'_>]) {
+        let users = self.columns.contains(&Column::User);
+        let groups = self.columns.contains(&Column::Group);
+        if !users && !groups {
+            return;
+        }
+
+        // The owners of files from elsewhere don’t get looked up here.
+        let local = || files.iter().filter(|file| file.is_local());
+        self.env.users.prefetch(
+            local()
+                .filter(|_| users)
+                .filter_map(|file| file.user(self.keep_link_owners))
+                .map(|u| u.0),
+            local()
+                .filter(|_| groups)
+                .filter_map(|file| file.group(self.keep_link_owners))
+                .map(|g| g.0),
+        );
+    }
+
+    #[cfg(not(unix))]
+    pub fn prefetch_owners(&self, _files: &[File<'_>]) {}
+
+    /// With `--smart-group`, leaves out the group column altogether when
+    /// every file’s group has the same name as its owner, as the column
+    /// would only be full of placeholders. The column stays if the user
+    /// column isn’t there to show whose name that is.
+    #[cfg(unix)]
+    pub fn hide_redundant_groups(&mut self, files: &[File<'_>]) {
+        use uzers::{Groups, Users};
+
+        let users = &self.env.users;
+        let keep = self.keep_link_owners;
+        self.hide_groups_matching_owners(files.iter().map(|file| {
+            let (user, group) = match file.metadata.source() {
+                Some(source) => (
+                    file.user(keep).and_then(|u| source.user_name(u.0)),
+                    file.group(keep).and_then(|g| source.group_name(g.0)),
+                ),
+                None => (
+                    file.user(keep)
+                        .and_then(|u| users.get_user_by_uid(u.0))
+                        .map(|user| user.name().to_string_lossy().into_owned()),
+                    file.group(keep)
+                        .and_then(|g| users.get_group_by_gid(g.0))
+                        .map(|group| group.name().to_string_lossy().into_owned()),
+                ),
+            };
+            match (user, group) {
+                (Some(user), Some(group)) => user == group,
+                _ => false,
+            }
+        }));
+    }
+
+    /// Leaves out the group column if every one of the files, given as
+    /// whether its group has the same name as its owner, does. Nothing gets
+    /// looked up unless the column could go.
+    #[cfg(unix)]
+    fn hide_groups_matching_owners<I>(&mut self, files: I)
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        if self.group_format != GroupFormat::Smart || !self.columns.contains(&Column::User) {
+            return;
+        }
+
+        let Some(index) = self.columns.iter().position(|c| *c == Column::Group) else {
+            return;
+        };
+
+        let mut files = files.into_iter().peekable();
+        if files.peek().is_some() && files.all(|matches| matches) {
+            self.columns.remove(index);
+            self.widths = TableWidths::zero(self.columns.len());
+        }
+    }
+
+    #[cfg(not(unix))]
+    pub fn hide_redundant_groups(&mut self, _files: &[File<'_>]) {}
+
+    /// Leaves out the Git columns, taking them out of the rows that have
+    /// already been made for the table too, for when the files turned out
+    /// not to be in any repository.
+    pub fn remove_git_columns<'r>(&mut self, rows: impl IntoIterator<Item = &'r mut Row>) {
+        let is_git = |c: &Column| matches!(c, Column::GitStatus | Column::GitSince);
+        let keep = self.columns.iter().map(|c| !is_git(c)).collect::<Vec<_>>();
+        if keep.iter().all(|k| *k) {
+            return;
+        }
+
+        for row in rows {
+            let mut index = 0;
+            row.cells.retain(|_| {
+                index += 1;
+                keep[index - 1]
+            });
+        }
+
+        let mut index = 0;
+        self.widths.0.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+        self.columns.retain(|c| !is_git(c));
+    }
+
     pub fn widths(&self) -> &TableWidths {
         &self.widths
     }
 
+    /// Records how wide each of this table’s columns is, so other tables can
+    /// be lined up with it.
+    pub fn share_widths(&self, shared: &mut SharedWidths) {
+        for (column, width) in self.columns.iter().zip(self.widths.iter()) {
+            shared.add(*column, *width);
+        }
+    }
+
+    /// Widens this table’s columns to the widest that any of the measured
+    /// tables had.
+    pub fn widen_to(&mut self, shared: &SharedWidths) {
+        for (column, width) in self.columns.iter().zip(self.widths.0.iter_mut()) {
+            *width = max(*width, shared.get(*column));
+        }
+    }
+
+    /// The row of headers at the top of the table. Columns with a short
+    /// header get it when their full one is wider than anything in them, so
+    /// this should be called once the widths of the files’ rows are known.
     pub fn header_row(&self) -> Row {
         let cells = self
             .columns
             .iter()
-            .map(|c| TextCell::paint_str(self.theme.ui.header, c.header()))
+            .zip(self.widths.iter())
+            .map(|(c, width)| {
+                let label = self.headers.label(*c, *width);
+                TextCell::paint(self.theme.ui.header, label.to_string())
+            })
+            .collect();
+
+        Row { cells }
+    }
+
+    /// The header above the file names, which aren’t part of the table.
+    pub fn name_header(&self) -> TextCell {
+        match &self.headers.name {
+            Some(name) => TextCell::paint(self.theme.ui.header, name.clone()),
+            None => TextCell::paint_str(self.theme.ui.header, "Name"),
+        }
+    }
+
+    /// The row that goes under the header when `--header-units` is given,
+    /// saying what units each column is in, with an arrow under the column
+    /// the files are sorted by.
+    pub fn units_row(&self, filter: &FileFilter) -> Row {
+        let cells = self
+            .columns
+            .iter()
+            .map(|c| {
+                let mut units = self.units(*c);
+                if let Some(ascending) = c.sorted_by(filter.sort_field) {
+                    if !units.is_empty() {
+                        units.push(' ');
+                    }
+                    units.push_str(sort_arrow(ascending, filter));
+                }
+
+                TextCell::paint(self.theme.ui.header, units)
+            })
             .collect();
 
         Row { cells }
     }
 
+    /// The cell that goes under the “Name” header when `--header-units` is
+    /// given, which only ever has the sort arrow in it.
+    pub fn name_units(&self, filter: &FileFilter) -> TextCell {
+        match filter.sort_field {
+            SortField::Name(_) | SortField::NameMixHidden(_) | SortField::NameNatural(_) => {
+                TextCell::paint_str(self.theme.ui.header, sort_arrow(true, filter))
+            }
+            _ => TextCell::default(),
+        }
+    }
+
+    /// The units that the values in a column are in, for columns where that
+    /// isn’t obvious from the values themselves.
+    fn units(&self, column: Column) -> String {
+        let units = match column {
+            Column::FileSize => match self.size_format {
+                SizeFormat::DecimalBytes => "SI",
+                SizeFormat::BinaryBytes => "IEC",
+                SizeFormat::JustBytes => "bytes",
+            },
+            #[cfg(unix)]
+            Column::Blocksize => match self.size_format {
+                SizeFormat::DecimalBytes => "SI blocks",
+                SizeFormat::BinaryBytes => "IEC blocks",
+                SizeFormat::JustBytes => "blocks",
+            },
+            Column::Timestamp(_) | Column::DeletionDate | Column::GitSince => {
+                match &self.time_format {
+                    TimeFormat::DefaultFormat | TimeFormat::LocaleFormat => "local",
+                    TimeFormat::ISOFormat | TimeFormat::LongISO | TimeFormat::FullISO => "ISO",
+                    TimeFormat::Relative => "relative",
+                    TimeFormat::Custom { non_recent, .. } => return non_recent.clone(),
+                }
+            }
+            #[cfg(unix)]
+            Column::User => match self.user_format {
+                UserFormat::Numeric => "uid",
+                UserFormat::Name => "name",
+                UserFormat::NameAndId => "name(uid)",
+            },
+            #[cfg(unix)]
+            Column::Group => match self.group_id_format {
+                UserFormat::Numeric => "gid",
+                UserFormat::Name => "name",
+                UserFormat::NameAndId => "name(gid)",
+            },
+            #[cfg(unix)]
+            Column::HardLinks => "count",
+            _ => "",
+        };
+
+        String::from(units)
+    }
+
     pub fn row_for_file(
         &self,
         file: &File<'_>,
@@ -478,11 +877,15 @@ impl<'a> Table<'a> {
 
     #[cfg(unix)]
     fn permissions_plus(&self, file: &File<'_>, xattrs: bool) -> Option<f::PermissionsPlus> {
-        file.permissions().map(|p| f::PermissionsPlus {
-            file_type: file.type_char(),
-            permissions: p,
-            xattrs,
-        })
+        file.permissions(self.keep_link_owners)
+            .map(|p| f::PermissionsPlus {
+                file_type: file.type_char(),
+                permissions: p,
+                xattrs,
+                acl: file.acl_kind(),
+                #[cfg(target_os = "macos")]
+                finder_hidden: file.is_finder_hidden(),
+            })
     }
 
     #[allow(clippy::unnecessary_wraps)] // Needs to match Unix function
@@ -492,16 +895,25 @@ impl<'a> Table<'a> {
             file_type: file.type_char(),
             #[cfg(windows)]
             attributes: file.attributes(),
+            #[cfg(windows)]
+            access: file.access_rights(),
             xattrs,
         })
     }
 
     #[cfg(unix)]
     fn octal_permissions(&self, file: &File<'_>) -> Option<f::OctalPermissions> {
-        file.permissions()
+        file.permissions(self.keep_link_owners)
             .map(|p| f::OctalPermissions { permissions: p })
     }
 
+    /// Renders the one cell of this column for the file, outside of any row,
+    /// for views that lay out each file’s details by themselves.
+    pub fn cell_for_file(&self, file: &File<'_>, column: Column) -> TextCell {
+        let xattrs = !file.extended_attributes().is_empty();
+        self.display(file, column, xattrs, None)
+    }
+
     fn display(
         &self,
         file: &File<'_>,
@@ -509,6 +921,13 @@ impl<'a> Table<'a> {
         xattrs: bool,
         color_scale_info: Option<ColorScaleInformation>,
     ) -> TextCell {
+        if column
+            .field()
+            .is_some_and(|field| !file.metadata.has(field))
+        {
+            return TextCell::paint_str(self.theme.ui.punctuation, "?");
+        }
+
         match column {
             Column::Permissions => self.permissions_plus(file, xattrs).render(self.theme),
             Column::FileSize => file.size().render(
@@ -527,25 +946,88 @@ impl<'a> Table<'a> {
                     .render(self.theme, self.size_format, &self.env.numeric)
             }
             #[cfg(unix)]
-            Column::User => {
-                file.user()
-                    .render(self.theme, &*self.env.lock_users(), self.user_format)
-            }
+            Column::User => match file.metadata.source() {
+                Some(source) => file.user(self.keep_link_owners).render(
+                    self.theme,
+                    &SourceUsers(source.as_ref()),
+                    self.user_format,
+                ),
+                None => file.user(self.keep_link_owners).render(
+                    self.theme,
+                    &self.env.users,
+                    self.user_format,
+                ),
+            },
+            #[cfg(windows)]
+            Column::User => file.owner().render(self.theme),
             #[cfg(unix)]
-            Column::Group => file.group().render(
-                self.theme,
-                &*self.env.lock_users(),
-                self.user_format,
-                self.group_format,
-                file.user(),
-            ),
+            Column::Group => match file.metadata.source() {
+                Some(source) => file.group(self.keep_link_owners).render(
+                    self.theme,
+                    &SourceUsers(source.as_ref()),
+                    self.group_id_format,
+                    self.group_format,
+                    file.user(self.keep_link_owners),
+                ),
+                None => file.group(self.keep_link_owners).render(
+                    self.theme,
+                    &self.env.users,
+                    self.group_id_format,
+                    self.group_format,
+                    file.user(self.keep_link_owners),
+                ),
+            },
             #[cfg(unix)]
             Column::SecurityContext => file.security_context().render(self.theme),
             Column::FileFlags => file.flags().render(self.theme.ui.flags, self.flags_format),
-            Column::GitStatus => self.git_status(file).render(self.theme),
+            Column::GitStatus => match self.git {
+                Some(git) if !git.is_ready(&file.path) => f::Git::render_pending(self.theme),
+                git => {
+                    let status = self.git_status(file);
+                    if git.is_some_and(|git| git.has_timed_out(&file.path)) {
+                        f::Git::render_timed_out(self.theme)
+                    } else if self.git_detail {
+                        let rename = git.and_then(|git| git.rename(&file.path));
+                        status.render_detailed(rename.as_ref(), self.theme, self.theme.git_symbols)
+                    } else {
+                        status.render(self.theme, self.theme.git_symbols)
+                    }
+                }
+            },
+            Column::GitSince => match self.git {
+                Some(git) if !git.is_ready(&file.path) => f::Git::render_pending(self.theme),
+                Some(git) => match git.changed_since(&file.path, file.is_directory()) {
+                    f::ChangedSince::Changed(modified) => modified.render(
+                        self.theme.ui.git.modified,
+                        self.env.time_offset,
+                        self.time_format.clone(),
+                    ),
+                    f::ChangedSince::Unchanged => TextCell::blank(self.theme.ui.punctuation),
+                    f::ChangedSince::Unknown => TextCell::default(),
+                },
+                None => TextCell::default(),
+            },
             Column::SubdirGitRepo(status) => self.subdir_git_repo(file, status).render(self.theme),
             #[cfg(unix)]
             Column::Octal => self.octal_permissions(file).render(self.theme.ui.octal),
+            Column::DeletionDate => file.trash_info().and_then(|info| info.deleted).render(
+                self.theme.ui.date,
+                self.env.time_offset,
+                self.time_format.clone(),
+            ),
+            Column::OriginalPath => match file.trash_info() {
+                Some(info) => {
+                    TextCell::paint(Style::default(), info.original_path.display().to_string())
+                }
+                None => TextCell::blank(self.theme.ui.punctuation),
+            },
+            Column::Target => file.link_chain().render(self.theme),
+            #[cfg(unix)]
+            Column::Quota => file.quota(self.keep_link_owners).render(
+                self.theme,
+                self.size_format,
+                &self.env.numeric,
+            ),
 
             Column::Timestamp(time_type) => time_type.get_corresponding_time(file).render(
                 if color_scale_info.is_some_and(|csi| csi.options.mode == ColorScaleMode::Gradient)
@@ -576,7 +1058,7 @@ impl<'a> Table<'a> {
         debug!("Getting subdir repo status for path {:?}", file.path);
 
         if file.is_directory() {
-            return f::SubdirGitRepo::from_path(&file.path, status);
+            return f::SubdirGitRepo::from_path(&file.path, status, self.git_repos_behind);
         }
         f::SubdirGitRepo::default()
     }
@@ -632,3 +1114,138 @@ impl TableWidths {
         self.0.len() + self.0.iter().sum::<usize>()
     }
 }
+
+/// The widest each column has been across several tables, for
+/// `--align-sections`. Tables can have different columns, such as when only
+/// some directories are in a Git repository, so the widths are kept by
+/// column rather than by position.
+#[derive(Debug, Default)]
+pub struct SharedWidths(Vec<(Column, usize)>);
+
+impl SharedWidths {
+    fn get(&self, column: Column) -> usize {
+        self.0
+            .iter()
+            .find(|(c, _)| *c == column)
+            .map_or(0, |(_, width)| *width)
+    }
+
+    fn add(&mut self, column: Column, width: usize) {
+        match self.0.iter_mut().find(|(c, _)| *c == column) {
+            Some((_, old_width)) => *old_width = max(*old_width, width),
+            None => self.0.push((column, width)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(unix)]
+mod test {
+    use super::*;
+    use crate::output::color_scale::{ColorDepth, ColorScaleOptions};
+    use crate::theme::{Background, Definitions, GitSymbols, Options as ThemeOptions, UseColours};
+
+    fn theme() -> Theme {
+        ThemeOptions {
+            use_colours: UseColours::Never,
+            colour_scale: ColorScaleOptions {
+                mode: ColorScaleMode::Fixed,
+                min_luminance: 40,
+                size: false,
+                age: false,
+                time: None,
+                size_thresholds: None,
+                depth: ColorDepth::TrueColor,
+            },
+            background: Background::Dark,
+            definitions: Definitions::default(),
+            git_symbols: GitSymbols::default(),
+        }
+        .to_theme(false)
+    }
+
+    fn options(group_format: GroupFormat, columns: Vec<Column>) -> Options {
+        Options {
+            size_format: SizeFormat::default(),
+            time_format: TimeFormat::DefaultFormat,
+            user_format: UserFormat::Name,
+            group_id_format: UserFormat::Name,
+            group_format,
+            flags_format: FlagsFormat::default(),
+            columns: Columns {
+                time_types: TimeTypes::default(),
+                inode: false,
+                links: false,
+                blocksize: false,
+                group: true,
+                git: false,
+                subdir_git_repos: false,
+                subdir_git_repos_no_stat: false,
+                octal: false,
+                security_context: false,
+                file_flags: false,
+                target: false,
+                quota: false,
+                git_since: None,
+                permissions: false,
+                filesize: false,
+                user: true,
+                order: Some(columns),
+            },
+            git_async: false,
+            git_submodules: f::SubmoduleIgnore::default(),
+            git_repos_behind: false,
+            git_detail: false,
+            headers: HeaderLabels::default(),
+            keep_link_owners: false,
+        }
+    }
+
+    /// The columns left after hiding the group column for files whose
+    /// groups do or don’t match their owners.
+    fn columns_left(options: &Options, files: &[bool]) -> Vec<Column> {
+        let theme = theme();
+        let mut table = Table::new(options, None, &theme, false, false);
+        table.hide_groups_matching_owners(files.iter().copied());
+        table.columns
+    }
+
+    #[test]
+    fn smart_group_drops_matching_groups() {
+        let options = options(GroupFormat::Smart, vec![Column::User, Column::Group]);
+        assert_eq!(columns_left(&options, &[true, true]), vec![Column::User]);
+    }
+
+    #[test]
+    fn smart_group_keeps_a_different_group() {
+        let options = options(GroupFormat::Smart, vec![Column::User, Column::Group]);
+        assert_eq!(
+            columns_left(&options, &[true, false]),
+            vec![Column::User, Column::Group]
+        );
+    }
+
+    #[test]
+    fn smart_group_keeps_the_group_of_nothing() {
+        let options = options(GroupFormat::Smart, vec![Column::User, Column::Group]);
+        assert_eq!(
+            columns_left(&options, &[]),
+            vec![Column::User, Column::Group]
+        );
+    }
+
+    #[test]
+    fn smart_group_keeps_groups_without_users() {
+        let options = options(GroupFormat::Smart, vec![Column::Group]);
+        assert_eq!(columns_left(&options, &[true]), vec![Column::Group]);
+    }
+
+    #[test]
+    fn regular_group_keeps_matching_groups() {
+        let options = options(GroupFormat::Regular, vec![Column::User, Column::Group]);
+        assert_eq!(
+            columns_left(&options, &[true]),
+            vec![Column::User, Column::Group]
+        );
+    }
+}
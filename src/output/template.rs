@@ -0,0 +1,625 @@
+//! Writing each file as a line filled in from a template, for
+//! `--format-template`.
+//!
+//! A template is ordinary text with fields in braces, such as
+//! `{name}\t{size}\t{mtime}`, which get filled in for each file, so scripts
+//! can get exactly the columns they want without having to parse JSON. Like
+//! JSON Lines, each directory’s lines are written as soon as it’s been read,
+//! and there are no headings or colours.
+//!
+//! A field can be given a width after a colon, optionally starting with `<`,
+//! `>`, or `^` to align it to the left, right, or centre, as in `{size:>8}`.
+//! Numbers are aligned to the right and everything else to the left unless
+//! told otherwise. After the width, or straight after the name, a format can
+//! be given after a `|`, as in `{size|binary}` or `{mtime:20|%Y-%m-%d}`.
+//! The format goes last because a time’s format can have colons in it.
+//!
+//! Braces that should appear as they are get doubled up, as `{{` and `}}`,
+//! and `\t`, `\n`, `\0`, and `\\` stand for a tab, a newline, a null byte,
+//! and a backslash, as shells don’t turn them into those inside quotes.
+
+use std::fmt;
+use std::io::{self, Write};
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::prelude::*;
+use chrono::SecondsFormat;
+use number_prefix::NumberPrefix;
+
+use crate::fs::feature::git::GitCache;
+use crate::fs::fields as f;
+use crate::fs::filter::FileFilter;
+#[cfg(unix)]
+use crate::fs::metadata;
+use crate::fs::File;
+use crate::output::json::type_name;
+#[cfg(unix)]
+use crate::output::json::{group_name, user_name};
+use crate::output::width::str_width;
+
+/// What gets written for a field that has no value for a file.
+const MISSING: &str = "-";
+
+/// A line of text with fields to fill in for each file.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum Part {
+    Text(String),
+    Field(Placeholder),
+}
+
+/// A field, along with how to lay it out.
+#[derive(PartialEq, Eq, Debug, Clone)]
+struct Placeholder {
+    field: Field,
+    align: Option<Align>,
+    width: usize,
+    format: Option<Format>,
+}
+
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum Align {
+    Left,
+    Right,
+    Centre,
+}
+
+/// Something about a file that a template can show.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum Field {
+    Path,
+    Name,
+    Extension,
+    Type,
+    Size,
+    Inode,
+    Links,
+    Blocks,
+    Mode,
+    Uid,
+    User,
+    Gid,
+    Group,
+    Modified,
+    Accessed,
+    Changed,
+    Created,
+
+    /// Where a symlink points.
+    Target,
+
+    /// The staged and unstaged Git statuses, as two letters.
+    Git,
+}
+
+impl Field {
+    /// The same names that `--fields` takes, along with `ext`.
+    const NAMES: &'static [(&'static str, Self)] = &[
+        ("path", Self::Path),
+        ("name", Self::Name),
+        ("ext", Self::Extension),
+        ("type", Self::Type),
+        ("size", Self::Size),
+        ("inode", Self::Inode),
+        ("links", Self::Links),
+        ("blocks", Self::Blocks),
+        ("mode", Self::Mode),
+        ("uid", Self::Uid),
+        ("user", Self::User),
+        ("gid", Self::Gid),
+        ("group", Self::Group),
+        ("modified", Self::Modified),
+        ("mtime", Self::Modified),
+        ("accessed", Self::Accessed),
+        ("atime", Self::Accessed),
+        ("changed", Self::Changed),
+        ("ctime", Self::Changed),
+        ("created", Self::Created),
+        ("btime", Self::Created),
+        ("target", Self::Target),
+        ("git", Self::Git),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, field)| *field)
+    }
+
+    fn is_number(self) -> bool {
+        matches!(
+            self,
+            Self::Size | Self::Inode | Self::Links | Self::Blocks | Self::Uid | Self::Gid
+        )
+    }
+
+    fn is_time(self) -> bool {
+        matches!(
+            self,
+            Self::Modified | Self::Accessed | Self::Changed | Self::Created
+        )
+    }
+}
+
+/// How to write a size or a time.
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum Format {
+    /// A size with a decimal prefix, such as `1.2k`.
+    Decimal,
+
+    /// A size with a binary prefix, such as `1.2Ki`.
+    Binary,
+
+    /// A size as a number of bytes, which is the default.
+    Bytes,
+
+    /// A time in RFC 3339 format, in UTC, which is the default.
+    Iso,
+
+    /// A time as the number of seconds since the Unix epoch.
+    Unix,
+
+    /// A time in the local time zone, in a `strftime` format.
+    Strftime(String),
+}
+
+impl Format {
+    /// The format with the given name, if the field can be written in it.
+    fn parse(field: Field, name: &str) -> Option<Self> {
+        if field == Field::Size {
+            match name {
+                "decimal" => Some(Self::Decimal),
+                "binary" => Some(Self::Binary),
+                "bytes" => Some(Self::Bytes),
+                _ => None,
+            }
+        } else if field.is_time() {
+            match name {
+                "iso" => Some(Self::Iso),
+                "unix" => Some(Self::Unix),
+                _ if name.contains('%') && is_valid_strftime(name) => {
+                    Some(Self::Strftime(name.to_owned()))
+                }
+                _ => None,
+            }
+        } else {
+            None
+        }
+    }
+}
+
+fn is_valid_strftime(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| item == Item::Error)
+}
+
+/// Something wrong with a template.
+#[derive(PartialEq, Eq, Debug)]
+pub enum TemplateError {
+    /// A field that doesn’t exist.
+    UnknownField(String),
+
+    /// A width that isn’t a number.
+    BadWidth(String),
+
+    /// A format that the field can’t be written in.
+    BadFormat(String, String),
+
+    /// A `{` with no `}` after it.
+    Unclosed,
+
+    /// A `}` with no `{` before it.
+    Unopened,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownField(name) => {
+                let names = Field::NAMES
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "unknown field {{{name}}} (choices: {names})")
+            }
+            Self::BadWidth(width) => write!(f, "{width:?} is not a width"),
+            Self::BadFormat(name, format) => {
+                write!(f, "field {{{name}}} can't be written as {format:?}")
+            }
+            Self::Unclosed => write!(f, "'{{' is never closed (write '{{{{' for a brace)"),
+            Self::Unopened => write!(f, "'}}' was never opened (write '}}}}' for a brace)"),
+        }
+    }
+}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, TemplateError> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut chars = input.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '\\' => match chars.peek() {
+                    Some(&e @ ('t' | 'n' | '0' | '\\')) => {
+                        chars.next();
+                        text.push(match e {
+                            't' => '\t',
+                            'n' => '\n',
+                            '0' => '\0',
+                            _ => '\\',
+                        });
+                    }
+                    _ => text.push('\\'),
+                },
+                '{' => {
+                    let mut inside = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => inside.push(c),
+                            None => return Err(TemplateError::Unclosed),
+                        }
+                    }
+
+                    if !text.is_empty() {
+                        parts.push(Part::Text(std::mem::take(&mut text)));
+                    }
+                    parts.push(Part::Field(Placeholder::parse(&inside)?));
+                }
+                '}' => return Err(TemplateError::Unopened),
+                c => text.push(c),
+            }
+        }
+
+        if !text.is_empty() {
+            parts.push(Part::Text(text));
+        }
+
+        Ok(Self { parts })
+    }
+
+    /// Whether any of the fields need Git statuses.
+    pub fn uses_git(&self) -> bool {
+        self.parts
+            .iter()
+            .any(|part| matches!(part, Part::Field(p) if p.field == Field::Git))
+    }
+}
+
+impl Placeholder {
+    /// Parses what’s between the braces: a field name, then optionally a
+    /// colon and a width, then optionally a bar and a format.
+    fn parse(inside: &str) -> Result<Self, TemplateError> {
+        let (rest, format) = match inside.split_once('|') {
+            Some((rest, format)) => (rest, Some(format)),
+            None => (inside, None),
+        };
+        let (name, width) = match rest.split_once(':') {
+            Some((name, width)) => (name.trim(), Some(width)),
+            None => (rest.trim(), None),
+        };
+
+        let field =
+            Field::from_name(name).ok_or_else(|| TemplateError::UnknownField(name.into()))?;
+
+        let (align, width) = match width {
+            Some(width) => parse_width(width)?,
+            None => (None, 0),
+        };
+
+        let format = match format {
+            Some(format) => Some(
+                Format::parse(field, format)
+                    .ok_or_else(|| TemplateError::BadFormat(name.into(), format.into()))?,
+            ),
+            None => None,
+        };
+
+        Ok(Self {
+            field,
+            align,
+            width,
+            format,
+        })
+    }
+
+    /// Pads the value out to the width, if it isn’t that wide already.
+    fn pad(&self, value: &str) -> String {
+        let gap = self.width.saturating_sub(str_width(value));
+        if gap == 0 {
+            return value.to_owned();
+        }
+
+        let align = self.align.unwrap_or(if self.field.is_number() {
+            Align::Right
+        } else {
+            Align::Left
+        });
+
+        let (before, after) = match align {
+            Align::Left => (0, gap),
+            Align::Right => (gap, 0),
+            Align::Centre => (gap / 2, gap - gap / 2),
+        };
+
+        format!("{}{value}{}", " ".repeat(before), " ".repeat(after))
+    }
+}
+
+fn parse_width(width: &str) -> Result<(Option<Align>, usize), TemplateError> {
+    let (align, digits) = match width.chars().next() {
+        Some('<') => (Some(Align::Left), &width[1..]),
+        Some('>') => (Some(Align::Right), &width[1..]),
+        Some('^') => (Some(Align::Centre), &width[1..]),
+        _ => (None, width),
+    };
+
+    match digits.parse() {
+        Ok(width) => Ok((align, width)),
+        Err(_) => Err(TemplateError::BadWidth(width.into())),
+    }
+}
+
+/// The template view writes one filled-in line per file.
+pub struct Render<'a> {
+    pub files: Vec<File<'a>>,
+    pub filter: &'a FileFilter,
+    pub git: Option<&'a GitCache>,
+    pub template: &'a Template,
+
+    /// Whether links that are being dereferenced still show their own
+    /// owner, group, and permissions.
+    pub keep_link_owners: bool,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(mut self, w: &mut W) -> io::Result<()> {
+        self.filter.sort_files(&mut self.files);
+        for file in &self.files {
+            writeln!(w, "{}", self.line(file))?;
+        }
+
+        Ok(())
+    }
+
+    fn line(&self, file: &File<'_>) -> String {
+        let mut line = String::new();
+
+        for part in &self.template.parts {
+            match part {
+                Part::Text(text) => line.push_str(text),
+                Part::Field(placeholder) => {
+                    let value = self.value(file, placeholder);
+                    line.push_str(&placeholder.pad(value.as_deref().unwrap_or(MISSING)));
+                }
+            }
+        }
+
+        line
+    }
+
+    fn value(&self, file: &File<'_>, placeholder: &Placeholder) -> Option<String> {
+        let format = placeholder.format.as_ref();
+
+        match placeholder.field {
+            Field::Path => Some(file.path.to_string_lossy().into_owned()),
+            Field::Name => Some(file.name.clone()),
+            Field::Extension => file.ext.clone(),
+            Field::Type => Some(type_name(file).into()),
+            Field::Size => match file.size() {
+                f::Size::Some(bytes) => Some(size(bytes, format)),
+                _ => None,
+            },
+
+            #[cfg(unix)]
+            Field::Inode => known(file, metadata::Field::Inode, file.metadata.ino()),
+            #[cfg(unix)]
+            Field::Links => known(file, metadata::Field::Links, file.metadata.nlink()),
+            #[cfg(unix)]
+            Field::Blocks => known(file, metadata::Field::Blocks, file.metadata.blocks()),
+            #[cfg(unix)]
+            Field::Mode => file
+                .owner_metadata(self.keep_link_owners)
+                .filter(|metadata| metadata.has(metadata::Field::Mode))
+                .map(|metadata| format!("{:04o}", metadata.mode() & 0o7777)),
+            #[cfg(unix)]
+            Field::Uid => file
+                .user(self.keep_link_owners)
+                .map(|user| user.0.to_string()),
+            #[cfg(unix)]
+            Field::User => file
+                .user(self.keep_link_owners)
+                .and_then(|user| user_name(file, user.0)),
+            #[cfg(unix)]
+            Field::Gid => file
+                .group(self.keep_link_owners)
+                .map(|group| group.0.to_string()),
+            #[cfg(unix)]
+            Field::Group => file
+                .group(self.keep_link_owners)
+                .and_then(|group| group_name(file, group.0)),
+            #[cfg(not(unix))]
+            Field::Inode
+            | Field::Links
+            | Field::Blocks
+            | Field::Mode
+            | Field::Uid
+            | Field::User
+            | Field::Gid
+            | Field::Group => None,
+
+            Field::Modified => file.modified_time().map(|time| self::time(time, format)),
+            Field::Accessed => file.accessed_time().map(|time| self::time(time, format)),
+            Field::Changed => file.changed_time().map(|time| self::time(time, format)),
+            Field::Created => file.created_time().map(|time| self::time(time, format)),
+
+            Field::Target => file
+                .is_link()
+                .then(|| file.read_link().ok())
+                .flatten()
+                .map(|target| target.to_string_lossy().into_owned()),
+
+            Field::Git => self
+                .git
+                .filter(|git| git.has_anything_for(&file.path))
+                .map(|git| {
+                    let status = git.get(&file.path, file.is_directory());
+                    format!(
+                        "{}{}",
+                        git_status_char(status.staged),
+                        git_status_char(status.unstaged)
+                    )
+                }),
+        }
+    }
+}
+
+/// A number from the file’s metadata, if it’s known.
+#[cfg(unix)]
+fn known(file: &File<'_>, field: metadata::Field, value: u64) -> Option<String> {
+    file.metadata.has(field).then(|| value.to_string())
+}
+
+fn size(bytes: u64, format: Option<&Format>) -> String {
+    let prefixed = match format {
+        Some(Format::Decimal) => NumberPrefix::decimal(bytes as f64),
+        Some(Format::Binary) => NumberPrefix::binary(bytes as f64),
+        _ => return bytes.to_string(),
+    };
+
+    match prefixed {
+        NumberPrefix::Standalone(_) => bytes.to_string(),
+        NumberPrefix::Prefixed(prefix, n) if n < 10_f64 => format!("{n:.1}{prefix}"),
+        NumberPrefix::Prefixed(prefix, n) => format!("{:.0}{prefix}", n.round()),
+    }
+}
+
+fn time(time: NaiveDateTime, format: Option<&Format>) -> String {
+    match format {
+        Some(Format::Unix) => time.and_utc().timestamp().to_string(),
+        Some(Format::Strftime(format)) => Local.from_utc_datetime(&time).format(format).to_string(),
+        _ => time.and_utc().to_rfc3339_opts(SecondsFormat::AutoSi, true),
+    }
+}
+
+/// The letter that the Git column shows for the status.
+fn git_status_char(status: f::GitStatus) -> char {
+    match status {
+        f::GitStatus::NotModified => '-',
+        f::GitStatus::New => 'N',
+        f::GitStatus::Modified => 'M',
+        f::GitStatus::Deleted => 'D',
+        f::GitStatus::Renamed => 'R',
+        f::GitStatus::TypeChange => 'T',
+        f::GitStatus::Ignored => 'I',
+        f::GitStatus::Conflicted => 'U',
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn placeholder(template: &str) -> Placeholder {
+        match Template::parse(template).unwrap().parts.as_slice() {
+            [Part::Field(placeholder)] => placeholder.clone(),
+            parts => panic!("Expected one field, got {parts:?}"),
+        }
+    }
+
+    #[test]
+    fn text_and_fields() {
+        let template = Template::parse("{name}\\t{size}\\n").unwrap();
+        assert_eq!(template.parts.len(), 4);
+        assert_eq!(template.parts[1], Part::Text(String::from("\t")));
+        assert_eq!(template.parts[3], Part::Text(String::from("\n")));
+    }
+
+    #[test]
+    fn escapes_and_braces() {
+        let template = Template::parse("{{\\\\\\q}}").unwrap();
+        assert_eq!(template.parts, vec![Part::Text(String::from("{\\\\q}"))]);
+    }
+
+    #[test]
+    fn widths() {
+        let size = placeholder("{size:8}");
+        assert_eq!(size.pad("123"), "     123");
+
+        let name = placeholder("{name:8}");
+        assert_eq!(name.pad("abc"), "abc     ");
+
+        let centred = placeholder("{name:^7}");
+        assert_eq!(centred.pad("abc"), "  abc  ");
+
+        let right = placeholder("{name:>2}");
+        assert_eq!(right.pad("abc"), "abc");
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(placeholder("{size|binary}").format, Some(Format::Binary));
+        assert_eq!(
+            placeholder("{mtime:20|%H:%M}").format,
+            Some(Format::Strftime(String::from("%H:%M")))
+        );
+        assert_eq!(placeholder("{mtime:20|%H:%M}").width, 20);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(size(999, None), "999");
+        assert_eq!(size(1_234_567, Some(&Format::Decimal)), "1.2M");
+        assert_eq!(size(20_480, Some(&Format::Binary)), "20Ki");
+        assert_eq!(size(512, Some(&Format::Binary)), "512");
+    }
+
+    #[test]
+    fn times() {
+        let time = NaiveDateTime::from_timestamp_opt(1_700_000_000, 0).unwrap();
+        assert_eq!(self::time(time, None), "2023-11-14T22:13:20Z");
+        assert_eq!(self::time(time, Some(&Format::Unix)), "1700000000");
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Template::parse("{colour}"),
+            Err(TemplateError::UnknownField("colour".into()))
+        );
+        assert_eq!(
+            Template::parse("{size:wide}"),
+            Err(TemplateError::BadWidth("wide".into()))
+        );
+        assert_eq!(
+            Template::parse("{name|binary}"),
+            Err(TemplateError::BadFormat("name".into(), "binary".into()))
+        );
+        assert_eq!(
+            Template::parse("{mtime|%Q}"),
+            Err(TemplateError::BadFormat("mtime".into(), "%Q".into()))
+        );
+        assert_eq!(Template::parse("{name"), Err(TemplateError::Unclosed));
+        assert_eq!(Template::parse("name}"), Err(TemplateError::Unopened));
+    }
+
+    #[test]
+    fn git() {
+        assert!(Template::parse("{git} {name}").unwrap().uses_git());
+        assert!(!Template::parse("{name}").unwrap().uses_git());
+    }
+}
@@ -0,0 +1,107 @@
+//! Asking the terminal about itself, by writing escape sequences to it and
+//! reading back what it answers with.
+//!
+//! The answers arrive as input, so the terminal gets switched out of line
+//! buffering and echoing while it’s being asked, and put back afterwards.
+//! Terminals that don’t understand a query never answer it, so every read
+//! gives up after a tenth of a second.
+//!
+//! Only a process in the terminal’s foreground gets to ask, as changing the
+//! settings from the background stops it with `SIGTTOU`. Anything left to
+//! read once the asking is done gets thrown away, so an answer that turns
+//! up late doesn’t end up typed into the shell’s prompt.
+
+/// Opens the controlling terminal, and runs the given function with it in
+/// raw mode, as long as the output is going to a terminal in the first
+/// place. Returns `None` if it isn’t, if eza is running in the background,
+/// or if the terminal couldn’t be set up.
+#[cfg(unix)]
+pub fn query<T>(ask: impl FnOnce(&mut std::fs::File) -> Option<T>) -> Option<T> {
+    use std::fs::OpenOptions;
+    use std::io::{IsTerminal, Read};
+    use std::os::fd::AsRawFd;
+
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+
+    let mut tty = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open("/dev/tty")
+        .ok()?;
+    let fd = tty.as_raw_fd();
+
+    // SAFETY: the file descriptor stays open for as long as `tty` does, and
+    // getpgrp can’t fail.
+    if unsafe { libc::tcgetpgrp(fd) != libc::getpgrp() } {
+        return None;
+    }
+
+    // SAFETY: an all-zero termios is valid, and gets filled in by tcgetattr.
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    // SAFETY: as above.
+    if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+        return None;
+    }
+
+    // Turn off echoing and line buffering so the terminal’s responses can
+    // be read as soon as they arrive, giving up after a tenth of a second.
+    let mut raw = original;
+    raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+    raw.c_cc[libc::VMIN] = 0;
+    raw.c_cc[libc::VTIME] = 1;
+    // SAFETY: as above.
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &raw) } != 0 {
+        return None;
+    }
+
+    let answer = ask(&mut tty);
+
+    // A terminal that was too slow to answer might still be about to, so
+    // wait for it to go quiet before throwing away what’s left.
+    if answer.is_none() {
+        let mut late = [0_u8; 64];
+        for _ in 0..16 {
+            if !matches!(tty.read(&mut late), Ok(n) if n > 0) {
+                break;
+            }
+        }
+    }
+
+    // SAFETY: as above.
+    unsafe {
+        libc::tcflush(fd, libc::TCIFLUSH);
+        libc::tcsetattr(fd, libc::TCSANOW, &original);
+    }
+    answer
+}
+
+/// Terminals other than Unix ones can’t be asked anything.
+#[cfg(not(unix))]
+pub fn query<T>(_ask: impl FnOnce(&mut std::fs::File) -> Option<T>) -> Option<T> {
+    None
+}
+
+/// Reads the terminal’s response a byte at a time until it ends the way
+/// the given function says it should, or until it gets longer than the
+/// limit. Returns `None` if the terminal stops answering first.
+#[cfg(unix)]
+pub fn read_until(
+    tty: &mut std::fs::File,
+    limit: usize,
+    finished: impl Fn(&[u8]) -> bool,
+) -> Option<Vec<u8>> {
+    use std::io::Read;
+
+    let mut response = Vec::new();
+    let mut byte = [0_u8];
+    while !finished(&response) {
+        if response.len() > limit || tty.read(&mut byte).ok()? == 0 {
+            return None;
+        }
+        response.push(byte[0]);
+    }
+
+    Some(response)
+}
@@ -0,0 +1,345 @@
+//! The thumbnails view, for `--thumbnails`, which lays a directory out as a
+//! grid of small previews of its images, with each file’s name beneath.
+//!
+//! eza doesn’t decode images itself: the previews get drawn by `chafa`,
+//! which can write them using the kitty graphics protocol, as sixels, or as
+//! coloured block characters for terminals that can’t show images at all.
+//! Drawing a preview is slow next to listing a directory, so each one gets
+//! kept in eza’s cache directory, named after a hash of the file’s path,
+//! modification time, and size, and only gets drawn again when the file
+//! changes.
+//!
+//! Files that aren’t images get an empty space where their preview would
+//! be. If `chafa` isn’t installed, a warning gets printed, and the files get
+//! laid out as an ordinary grid instead.
+
+use std::fs;
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::UNIX_EPOCH;
+
+use log::*;
+use rayon::prelude::*;
+
+use crate::cache;
+use crate::fs::filter::FileFilter;
+use crate::fs::File;
+use crate::info::filetype::FileType;
+use crate::output::file_name::{Options as FileStyle, TruncatePosition, Truncation};
+use crate::output::grid;
+use crate::output::table::SizeFormat;
+use crate::output::total::Totals;
+use crate::theme::Theme;
+
+/// How wide each preview is, in terminal columns.
+const WIDTH: usize = 16;
+
+/// How tall each preview is, in terminal rows.
+const HEIGHT: usize = 8;
+
+/// How many columns go between one preview and the next.
+const GAP: usize = 2;
+
+/// Set once `chafa` turns out not to be installed, so it isn’t tried again
+/// for every other image, and the warning only gets printed once.
+static CHAFA_MISSING: AtomicBool = AtomicBool::new(false);
+
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Options {
+    /// How the previews get drawn.
+    pub protocol: Protocol,
+
+    /// Where drawn previews get kept, if there’s anywhere to keep them.
+    pub cache: Option<PathBuf>,
+}
+
+/// How the terminal can be made to show an image.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Protocol {
+    /// The kitty graphics protocol, which kitty, WezTerm, and Ghostty
+    /// understand.
+    Kitty,
+
+    /// Sixel graphics, which terminals descended from the DEC VT340 use.
+    Sixel,
+
+    /// Coloured block characters, which work anywhere that has colours.
+    Symbols,
+}
+
+impl Protocol {
+    /// What `chafa` calls this protocol.
+    fn chafa_format(self) -> &'static str {
+        match self {
+            Self::Kitty => "kitty",
+            Self::Sixel => "sixels",
+            Self::Symbols => "symbols",
+        }
+    }
+}
+
+pub struct Render<'a> {
+    pub files: Vec<File<'a>>,
+    pub theme: &'a Theme,
+    pub file_style: &'a FileStyle,
+    pub opts: &'a Options,
+    pub console_width: usize,
+    pub filter: &'a FileFilter,
+
+    /// Whether to write a footer with the files’ totals after them, for
+    /// `--total`.
+    pub total: bool,
+
+    /// How to format the numbers in the footer, which is loaded once for the
+    /// whole listing rather than for each directory in it.
+    pub numerics: &'a locale::Numeric,
+}
+
+impl<'a> Render<'a> {
+    pub fn render<W: Write>(mut self, w: &mut W) -> io::Result<()> {
+        self.filter.sort_files(&mut self.files);
+
+        let previews = self
+            .files
+            .par_iter()
+            .map(|file| preview(file, self.opts))
+            .collect::<Vec<_>>();
+
+        if CHAFA_MISSING.load(Ordering::Relaxed) {
+            let r = grid::Render {
+                files: self.files,
+                theme: self.theme,
+                file_style: self.file_style,
+                opts: &grid::Options { across: true },
+                console_width: self.console_width,
+                filter: self.filter,
+                total: self.total,
+                numerics: self.numerics,
+            };
+            return r.render(w);
+        }
+
+        // Names get shortened to fit underneath their preview.
+        let file_style = FileStyle {
+            truncate_names: Some(Truncation {
+                position: TruncatePosition::Middle,
+                width: WIDTH,
+            }),
+            ..self.file_style.clone()
+        };
+
+        let columns = ((self.console_width + GAP) / (WIDTH + GAP)).max(1);
+        let rows = self.files.chunks(columns).zip(previews.chunks(columns));
+
+        for (files, previews) in rows {
+            if previews.iter().any(Option::is_some) {
+                match self.opts.protocol {
+                    Protocol::Symbols => write_symbols(w, previews)?,
+                    Protocol::Kitty | Protocol::Sixel => write_images(w, previews)?,
+                }
+            }
+
+            let names = files
+                .iter()
+                .map(|file| {
+                    let name = file_style.for_file(file, self.theme).paint();
+                    name.strings().to_string()
+                })
+                .collect::<Vec<_>>();
+            writeln!(w, "{}", join_cells(&names))?;
+        }
+
+        if self.total {
+            Totals::of(&self.files).write(w, self.theme, SizeFormat::default(), self.numerics)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Puts each piece of text at the start of its own cell, leaving at least
+/// one space after any that are too wide for theirs.
+fn join_cells<S: AsRef<str>>(parts: &[S]) -> String {
+    let mut line = String::new();
+    let mut width = 0;
+
+    for (index, part) in parts.iter().enumerate() {
+        if index > 0 {
+            let padding = (index * (WIDTH + GAP)).saturating_sub(width).max(1);
+            line.extend(std::iter::repeat(' ').take(padding));
+            width += padding;
+        }
+        line.push_str(part.as_ref());
+        width += ansi_width::ansi_width(part.as_ref());
+    }
+
+    line.trim_end().to_string()
+}
+
+/// Writes a row of previews made of characters by interleaving their lines.
+fn write_symbols<W: Write>(w: &mut W, previews: &[Option<Vec<u8>>]) -> io::Result<()> {
+    let previews = previews
+        .iter()
+        .map(|preview| {
+            preview.as_ref().map_or_else(Vec::new, |preview| {
+                String::from_utf8_lossy(preview)
+                    .lines()
+                    .map(String::from)
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let height = previews.iter().map(Vec::len).max().unwrap_or(0);
+    for row in 0..height {
+        let parts = previews
+            .iter()
+            .map(|preview| preview.get(row).map_or("", String::as_str))
+            .collect::<Vec<_>>();
+        writeln!(w, "{}", join_cells(&parts))?;
+    }
+
+    Ok(())
+}
+
+/// Writes a row of images by moving the cursor to where each one goes. Space
+/// for the row gets made first, so the screen doesn’t scroll part of the way
+/// through and leave the images out of line.
+fn write_images<W: Write>(w: &mut W, previews: &[Option<Vec<u8>>]) -> io::Result<()> {
+    write!(w, "{}\x1b[{HEIGHT}A", "\n".repeat(HEIGHT))?;
+
+    for (index, preview) in previews.iter().enumerate() {
+        if let Some(preview) = preview {
+            write!(w, "\x1b[{}G\x1b7", index * (WIDTH + GAP) + 1)?;
+            w.write_all(preview)?;
+            write!(w, "\x1b8")?;
+        }
+    }
+
+    write!(w, "\r\x1b[{HEIGHT}B")
+}
+
+/// The file’s preview, from the cache if it’s there, or drawn by `chafa` if
+/// it isn’t. Only images get previews.
+fn preview(file: &File<'_>, opts: &Options) -> Option<Vec<u8>> {
+    if file.points_to_directory() || !matches!(FileType::get_file_type(file), Some(FileType::Image))
+    {
+        return None;
+    }
+
+    let cached = opts
+        .cache
+        .as_ref()
+        .and_then(|cache| cache_path(cache, file, opts.protocol));
+    if let Some(bytes) = cached.as_ref().and_then(|path| fs::read(path).ok()) {
+        return Some(bytes);
+    }
+
+    let bytes = draw(&file.path, opts.protocol)?;
+    if let Some(path) = cached {
+        if let Err(e) = cache::save(&path, &bytes) {
+            warn!("Failed to save thumbnail {path:?}: {e}");
+        }
+    }
+    Some(bytes)
+}
+
+/// Asks `chafa` to draw the image at the size of a preview.
+fn draw(path: &Path, protocol: Protocol) -> Option<Vec<u8>> {
+    if CHAFA_MISSING.load(Ordering::Relaxed) {
+        return None;
+    }
+
+    let output = Command::new("chafa")
+        .arg("--format")
+        .arg(protocol.chafa_format())
+        .arg("--size")
+        .arg(format!("{WIDTH}x{HEIGHT}"))
+        .args(["--animate", "off", "--polite", "on", "--"])
+        .arg(path)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output();
+
+    match output {
+        Ok(output) if output.status.success() && !output.stdout.is_empty() => Some(output.stdout),
+        Ok(_) => {
+            debug!("chafa couldn’t draw {path:?}");
+            None
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            if !CHAFA_MISSING.swap(true, Ordering::Relaxed) {
+                eprintln!(
+                    "eza: Couldn't find chafa to draw thumbnails with, so showing a grid instead"
+                );
+            }
+            None
+        }
+        Err(e) => {
+            debug!("Failed to run chafa: {e}");
+            None
+        }
+    }
+}
+
+/// Where the file’s preview gets kept, which changes along with the file.
+fn cache_path(cache: &Path, file: &File<'_>, protocol: Protocol) -> Option<PathBuf> {
+    let canonical = fs::canonicalize(&file.path).ok()?;
+    let modified = file
+        .metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .unwrap_or_default();
+
+    let key = format!(
+        "{}\0{}.{}\0{}\0{}\0{WIDTH}x{HEIGHT}",
+        canonical.display(),
+        modified.as_secs(),
+        modified.subsec_nanos(),
+        file.metadata.len(),
+        protocol.chafa_format(),
+    );
+    Some(cache.join(cache::file_name(key.as_bytes())))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn symbols_get_interleaved() {
+        let previews = [
+            Some(b"ab\ncd\n".to_vec()),
+            None,
+            Some(b"\x1b[31mef\x1b[0m\n".to_vec()),
+        ];
+        let mut out = Vec::new();
+        write_symbols(&mut out, &previews).unwrap();
+
+        let gap = " ".repeat(WIDTH + GAP - 2);
+        let empty = " ".repeat(WIDTH + GAP);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("ab{gap}{empty}\x1b[31mef\x1b[0m\ncd\n")
+        );
+    }
+
+    #[test]
+    fn cells_line_up() {
+        let cell = WIDTH + GAP;
+        assert_eq!(
+            join_cells(&["a", "", "b"]),
+            format!("a{}b", " ".repeat(2 * cell - 1))
+        );
+        assert_eq!(
+            join_cells(&["\x1b[1ma\x1b[0m", "b"]),
+            format!("\x1b[1ma\x1b[0m{}b", " ".repeat(cell - 1))
+        );
+
+        let wide = "x".repeat(cell + 3);
+        assert_eq!(join_cells(&[wide.as_str(), "b"]), format!("{wide} b"));
+    }
+}
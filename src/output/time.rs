@@ -3,9 +3,12 @@
 use chrono::prelude::*;
 use core::cmp::max;
 use once_cell::sync::Lazy;
+use std::env;
 use std::time::Duration;
 use unicode_width::UnicodeWidthStr;
 
+use crate::options::vars;
+
 /// Every timestamp in exa needs to be rendered by a **time format**.
 /// Formatting times is tricky, because how a timestamp is rendered can
 /// depend on one or more of the following:
@@ -26,9 +29,14 @@ use unicode_width::UnicodeWidthStr;
 pub enum TimeFormat {
     /// The **default format** uses the user’s locale to print month names,
     /// and specifies the timestamp down to the minute for recent times, and
-    /// day for older times.
+    /// day for older times. The day always goes before the month.
     DefaultFormat,
 
+    /// Use the **locale format**, which is the same as the default format,
+    /// except that the day and the month go in the order the user’s locale
+    /// writes them in, so the month comes first in US English or Japanese.
+    LocaleFormat,
+
     /// Use the **ISO format**, which specifies the timestamp down to the
     /// minute for recent times, and day for older times. It uses a number
     /// for the month so it doesn’t use the locale.
@@ -60,6 +68,7 @@ impl TimeFormat {
         #[rustfmt::skip]
         return match self {
             Self::DefaultFormat                 => default(time),
+            Self::LocaleFormat                  => locale_order(time),
             Self::ISOFormat                     => iso(time),
             Self::LongISO                       => long(time),
             Self::FullISO                       => full(time),
@@ -72,14 +81,43 @@ impl TimeFormat {
 }
 
 fn default(time: &DateTime<FixedOffset>) -> String {
-    let month = &*LOCALE.short_month_name(time.month0() as usize);
-    let month_width = short_month_padding(*MAX_MONTH_WIDTH, month);
-    let format = if time.year() == *CURRENT_YEAR {
-        format!("%_d {month:<month_width$} %H:%M")
-    } else {
-        format!("%_d {month:<month_width$}  %Y")
+    default_in_locale(&LOCALE, *MAX_MONTH_WIDTH, DateOrder::DayMonth, time)
+}
+
+fn locale_order(time: &DateTime<FixedOffset>) -> String {
+    default_in_locale(&LOCALE, *MAX_MONTH_WIDTH, LOCALE.order, time)
+}
+
+fn default_in_locale(
+    locale: &DateLocale,
+    max_month_width: usize,
+    order: DateOrder,
+    time: &DateTime<FixedOffset>,
+) -> String {
+    let month = locale.short_month_name(time.month0() as usize);
+    let month_width = short_month_padding(max_month_width, month);
+    let recent = time.year() == *CURRENT_YEAR;
+
+    #[rustfmt::skip]
+    let format = match (order, recent) {
+        (DateOrder::DayMonth, true)   => format!("%_d {month:<month_width$} %H:%M"),
+        (DateOrder::DayMonth, false)  => format!("%_d {month:<month_width$}  %Y"),
+        (DateOrder::MonthDay, true)   => format!("{month:<month_width$} %_d %H:%M"),
+        (DateOrder::MonthDay, false)  => format!("{month:<month_width$} %_d  %Y"),
     };
-    time.format(format.as_str()).to_string()
+
+    let formatted = time.format(format.as_str()).to_string();
+
+    // In a right-to-left locale, a terminal that implements the Unicode
+    // bidirectional algorithm would move the month name to the other side of
+    // the numbers around it, so the columns would no longer line up. Wrapping
+    // the timestamp in a left-to-right isolate keeps its parts in the same
+    // order as every other row, and the isolate characters have no width.
+    if locale.right_to_left {
+        format!("\u{2066}{formatted}\u{2069}")
+    } else {
+        formatted
+    }
 }
 
 /// Convert between Unicode width and width in chars to use in format!.
@@ -136,58 +174,277 @@ fn custom(time: &DateTime<FixedOffset>, non_recent_fmt: &str, recent_fmt: Option
 
 static CURRENT_YEAR: Lazy<i32> = Lazy::new(|| Local::now().year());
 
-static LOCALE: Lazy<locale::Time> =
-    Lazy::new(|| locale::Time::load_user_locale().unwrap_or_else(|_| locale::Time::english()));
+/// Which way round the day and the month go in the locale format.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+enum DateOrder {
+    /// “29 Jun”, used by most European languages.
+    DayMonth,
+
+    /// “6月 29”, used by the East Asian languages, and by US English.
+    MonthDay,
+}
+
+/// The language code at the start of a locale name such as `de_DE.UTF-8`
+/// or `fr_FR@euro`.
+fn language(name: &str) -> &str {
+    name.split(|c| c == '_' || c == '-' || c == '.' || c == '@')
+        .next()
+        .unwrap_or_default()
+}
+
+/// The territory code after the language in a locale name, such as the
+/// `US` in `en_US.UTF-8`, if there is one.
+fn territory(name: &str) -> Option<&str> {
+    let rest = name.split(|c| c == '.' || c == '@').next()?;
+    rest.split(|c| c == '_' || c == '-').nth(1)
+}
+
+/// The parts of the user’s time locale that the default format needs: the
+/// abbreviated month names, which way round the day and month go, and
+/// whether the language is written right-to-left.
+#[derive(PartialEq, Eq, Debug)]
+struct DateLocale {
+    short_months: Vec<String>,
+    order: DateOrder,
+    right_to_left: bool,
+}
+
+impl DateLocale {
+    /// Picks the locale from the environment, using the same precedence as
+    /// the C library: `LC_ALL`, then `LC_TIME`, then `LANG`. The month names
+    /// come from the system’s locale data, falling back to English when it
+    /// has none for that locale; the date order and writing direction go by
+    /// the locale’s name.
+    fn from_env() -> Self {
+        let name = [vars::LC_ALL, vars::LC_TIME, vars::LANG]
+            .iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+
+        let time = locale::Time::load_user_locale().unwrap_or_else(|_| locale::Time::english());
+        Self::from_months((0..12).map(|i| time.short_month_name(i)).collect(), &name)
+    }
+
+    fn from_months(short_months: Vec<String>, name: &str) -> Self {
+        Self {
+            short_months,
+            order: DateOrder::of_locale(name),
+            right_to_left: is_right_to_left(name),
+        }
+    }
+
+    fn short_month_name(&self, month0: usize) -> &str {
+        &self.short_months[month0]
+    }
+
+    /// Some locales use a three-character wide month name (Jan to Dec);
+    /// others vary between three to four (1月 to 12月, juil.). We check each
+    /// month width to detect the longest and set the output format
+    /// accordingly.
+    fn max_month_width(&self) -> usize {
+        self.short_months
+            .iter()
+            .map(|month| UnicodeWidthStr::width(month.as_str()))
+            .max()
+            .unwrap()
+    }
+}
+
+impl DateOrder {
+    /// Looks up which way round a locale name such as `en_US.UTF-8` puts
+    /// the day and the month, going by its language code, and by its
+    /// territory where that changes the order.
+    fn of_locale(name: &str) -> Self {
+        match language(name) {
+            "en" if territory(name) == Some("US") => Self::MonthDay,
+            "ja" | "zh" | "ko" => Self::MonthDay,
+            _ => Self::DayMonth,
+        }
+    }
+}
+
+/// Whether the locale’s language is written right-to-left.
+fn is_right_to_left(name: &str) -> bool {
+    matches!(language(name), "ar" | "fa" | "he" | "ur" | "yi")
+}
+
+static LOCALE: Lazy<DateLocale> = Lazy::new(DateLocale::from_env);
 
-static MAX_MONTH_WIDTH: Lazy<usize> = Lazy::new(|| {
-    // Some locales use a three-character wide month name (Jan to Dec);
-    // others vary between three to four (1月 to 12月, juil.). We check each month width
-    // to detect the longest and set the output format accordingly.
-    (0..11)
-        .map(|i| UnicodeWidthStr::width(&*LOCALE.short_month_name(i)))
-        .max()
-        .unwrap()
-});
+static MAX_MONTH_WIDTH: Lazy<usize> = Lazy::new(|| LOCALE.max_month_width());
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    fn old_date(month: u32, day: u32) -> DateTime<FixedOffset> {
+        let offset = FixedOffset::east_opt(0).unwrap();
+        let naive = NaiveDate::from_ymd_opt(2001, month, day)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap();
+        DateTime::from_naive_utc_and_offset(naive, offset)
+    }
+
+    #[rustfmt::skip]
+    const ENGLISH: [&str; 12] = ["Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"];
+    #[rustfmt::skip]
+    const GERMAN: [&str; 12] = ["Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez"];
+    #[rustfmt::skip]
+    const FRENCH: [&str; 12] = ["janv.", "févr.", "mars", "avril", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.", "déc."];
+    #[rustfmt::skip]
+    const JAPANESE: [&str; 12] = ["1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月", "12月"];
+    #[rustfmt::skip]
+    const HEBREW: [&str; 12] = ["ינו", "פבר", "מרץ", "אפר", "מאי", "יונ", "יול", "אוג", "ספט", "אוק", "נוב", "דצמ"];
+
+    fn locale(locale_name: &str, months: [&str; 12]) -> DateLocale {
+        DateLocale::from_months(
+            months.iter().map(ToString::to_string).collect(),
+            locale_name,
+        )
+    }
+
+    fn default_for(locale_name: &str, months: [&str; 12], time: &DateTime<FixedOffset>) -> String {
+        let locale = locale(locale_name, months);
+        default_in_locale(&locale, locale.max_month_width(), DateOrder::DayMonth, time)
+    }
+
+    fn locale_order_for(
+        locale_name: &str,
+        months: [&str; 12],
+        time: &DateTime<FixedOffset>,
+    ) -> String {
+        let locale = locale(locale_name, months);
+        default_in_locale(&locale, locale.max_month_width(), locale.order, time)
+    }
+
+    #[test]
+    fn languages() {
+        assert_eq!(language("de_DE.UTF-8"), "de");
+        assert_eq!(language("fr_FR@euro"), "fr");
+        assert_eq!(language("C"), "C");
+    }
+
+    #[test]
+    fn territories() {
+        assert_eq!(territory("en_US.UTF-8"), Some("US"));
+        assert_eq!(territory("fr_FR@euro"), Some("FR"));
+        assert_eq!(territory("de"), None);
+    }
+
+    #[test]
+    fn date_orders() {
+        assert_eq!(DateOrder::of_locale("en_US.UTF-8"), DateOrder::MonthDay);
+        assert_eq!(DateOrder::of_locale("en_GB.UTF-8"), DateOrder::DayMonth);
+        assert_eq!(DateOrder::of_locale("de_DE.UTF-8"), DateOrder::DayMonth);
+        assert_eq!(DateOrder::of_locale("ja_JP.UTF-8"), DateOrder::MonthDay);
+        assert_eq!(DateOrder::of_locale("fi_FI.UTF-8"), DateOrder::DayMonth);
+        assert_eq!(DateOrder::of_locale(""), DateOrder::DayMonth);
+    }
+
+    #[test]
+    fn writing_directions() {
+        assert!(is_right_to_left("he_IL.UTF-8"));
+        assert!(is_right_to_left("ar_EG"));
+        assert!(!is_right_to_left("tr_TR.UTF-8"));
+        assert!(!is_right_to_left("C"));
+    }
+
+    #[test]
+    fn english_default() {
+        assert_eq!(
+            default_for("en_GB.UTF-8", ENGLISH, &old_date(6, 9)),
+            " 9 Jun  2001"
+        );
+    }
+
+    #[test]
+    fn german_default() {
+        assert_eq!(
+            default_for("de_DE.UTF-8", GERMAN, &old_date(3, 29)),
+            "29 Mär  2001"
+        );
+    }
+
+    #[test]
+    fn french_default_pads_to_longest_month() {
+        assert_eq!(
+            default_for("fr_FR.UTF-8", FRENCH, &old_date(5, 1)),
+            " 1 mai    2001"
+        );
+    }
+
+    #[test]
+    fn us_english_default_keeps_day_first() {
+        assert_eq!(
+            default_for("en_US.UTF-8", ENGLISH, &old_date(6, 9)),
+            " 9 Jun  2001"
+        );
+    }
+
+    #[test]
+    fn us_english_locale_order() {
+        assert_eq!(
+            locale_order_for("en_US.UTF-8", ENGLISH, &old_date(6, 9)),
+            "Jun  9  2001"
+        );
+    }
+
+    #[test]
+    fn japanese_locale_order_puts_month_first() {
+        assert_eq!(
+            locale_order_for("ja_JP.UTF-8", JAPANESE, &old_date(6, 29)),
+            "6月  29  2001"
+        );
+    }
+
+    #[test]
+    fn german_locale_order_keeps_day_first() {
+        assert_eq!(
+            locale_order_for("de_DE.UTF-8", GERMAN, &old_date(3, 29)),
+            "29 Mär  2001"
+        );
+    }
+
+    #[test]
+    fn hebrew_default_is_isolated() {
+        let date = default_for("he_IL.UTF-8", HEBREW, &old_date(6, 29));
+        assert_eq!(date, "\u{2066}29 יונ  2001\u{2069}");
+        assert_eq!(UnicodeWidthStr::width(date.as_str()), 12);
+    }
+
     #[test]
     fn short_month_width_japanese() {
         let max_month_width = 4;
         let month = "1\u{2F49}"; // 1月
         let padding = short_month_padding(max_month_width, month);
-        let final_str = format!("{:<width$}", month, width = padding);
+        let final_str = format!("{month:<padding$}");
         assert_eq!(max_month_width, UnicodeWidthStr::width(final_str.as_str()));
     }
 
     #[test]
     fn short_month_width_hindi() {
         let max_month_width = 4;
-        assert_eq!(
-            true,
-            [
-                "\u{091C}\u{0928}\u{0970}",                         // जन॰
-                "\u{092B}\u{093C}\u{0930}\u{0970}",                 // फ़र॰
-                "\u{092E}\u{093E}\u{0930}\u{094D}\u{091A}",         // मार्च
-                "\u{0905}\u{092A}\u{094D}\u{0930}\u{0948}\u{0932}", // अप्रैल
-                "\u{092E}\u{0908}",                                 // मई
-                "\u{091C}\u{0942}\u{0928}",                         // जून
-                "\u{091C}\u{0941}\u{0932}\u{0970}",                 // जुल॰
-                "\u{0905}\u{0917}\u{0970}",                         // अग॰
-                "\u{0938}\u{093F}\u{0924}\u{0970}",                 // सित॰
-                "\u{0905}\u{0915}\u{094D}\u{0924}\u{0942}\u{0970}", // अक्तू॰
-                "\u{0928}\u{0935}\u{0970}",                         // नव॰
-                "\u{0926}\u{093F}\u{0938}\u{0970}",                 // दिस॰
-            ]
-            .iter()
-            .map(|month| format!(
-                "{:<width$}",
-                month,
-                width = short_month_padding(max_month_width, month)
-            ))
-            .all(|string| UnicodeWidthStr::width(string.as_str()) == max_month_width)
-        );
+        assert!([
+            "\u{091C}\u{0928}\u{0970}",                         // जन॰
+            "\u{092B}\u{093C}\u{0930}\u{0970}",                 // फ़र॰
+            "\u{092E}\u{093E}\u{0930}\u{094D}\u{091A}",         // मार्च
+            "\u{0905}\u{092A}\u{094D}\u{0930}\u{0948}\u{0932}", // अप्रैल
+            "\u{092E}\u{0908}",                                 // मई
+            "\u{091C}\u{0942}\u{0928}",                         // जून
+            "\u{091C}\u{0941}\u{0932}\u{0970}",                 // जुल॰
+            "\u{0905}\u{0917}\u{0970}",                         // अग॰
+            "\u{0938}\u{093F}\u{0924}\u{0970}",                 // सित॰
+            "\u{0905}\u{0915}\u{094D}\u{0924}\u{0942}\u{0970}", // अक्तू॰
+            "\u{0928}\u{0935}\u{0970}",                         // नव॰
+            "\u{0926}\u{093F}\u{0938}\u{0970}",                 // दिस॰
+        ]
+        .iter()
+        .map(|month| format!(
+            "{:<width$}",
+            month,
+            width = short_month_padding(max_month_width, month)
+        ))
+        .all(|string| UnicodeWidthStr::width(string.as_str()) == max_month_width));
     }
 }
@@ -0,0 +1,150 @@
+//! The summary footer printed after a listing when `--total` is given.
+//!
+//! This counts up the files that were passed in to be displayed, so in a
+//! recursive listing, each directory gets a footer of its own. A tree counts
+//! every row it lists instead, at whatever depth. Each view
+//! writes it after its own output, with sizes formatted the way the view
+//! formats them.
+
+use std::io::{self, Write};
+
+use nu_ansi_term::Style;
+
+use crate::fs::fields as f;
+use crate::fs::File;
+use crate::output::cell::TextCell;
+use crate::output::table::SizeFormat;
+use crate::theme::Theme;
+
+/// The counts and sizes of everything in a listing.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub struct Totals {
+    pub files: usize,
+    pub directories: usize,
+    pub links: usize,
+
+    /// Pipes, sockets, devices, and anything else that isn’t one of the
+    /// three kinds above.
+    pub others: usize,
+
+    /// The combined apparent size of everything that has one.
+    pub size: u64,
+
+    /// The combined space everything takes up on disk, if it can be known
+    /// on this platform.
+    pub disk_usage: Option<u64>,
+}
+
+impl Totals {
+    pub fn of(files: &[File<'_>]) -> Self {
+        let mut totals = Self::default();
+        for file in files {
+            totals.add(file);
+        }
+        totals
+    }
+
+    /// Counts one more file, such as a row of a tree as it gets listed.
+    pub fn add(&mut self, file: &File<'_>) {
+        if file.is_link() {
+            self.links += 1;
+        } else if file.is_directory() {
+            self.directories += 1;
+        } else if file.is_file() {
+            self.files += 1;
+        } else {
+            self.others += 1;
+        }
+
+        if let f::Size::Some(size) = file.size() {
+            self.size += size;
+        }
+
+        #[cfg(unix)]
+        if let f::Blocksize::Some(blocks) = file.blocksize() {
+            *self.disk_usage.get_or_insert(0) += blocks;
+        }
+    }
+
+    /// The counts part of the footer, such as “3 files, 1 directory”. Kinds
+    /// with nothing in them are left out.
+    fn counts(&self) -> String {
+        let counts = [
+            (self.files, "file", "files"),
+            (self.directories, "directory", "directories"),
+            (self.links, "link", "links"),
+            (self.others, "other", "others"),
+        ];
+
+        counts
+            .iter()
+            .filter(|(count, _, _)| *count > 0)
+            .map(|(count, one, many)| format!("{count} {}", if *count == 1 { one } else { many }))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn render(
+        &self,
+        theme: &Theme,
+        size_format: SizeFormat,
+        numerics: &locale::Numeric,
+    ) -> TextCell {
+        let mut cell = TextCell::paint(Style::default(), self.counts());
+        cell.push(Style::default().paint("; size "), 7);
+        cell.append(f::Size::Some(self.size).render(theme, size_format, numerics, None));
+
+        if let Some(disk_usage) = self.disk_usage {
+            cell.push(Style::default().paint(", disk usage "), 13);
+            cell.append(f::Size::Some(disk_usage).render(theme, size_format, numerics, None));
+        }
+
+        cell
+    }
+
+    /// Writes the footer as a line of its own.
+    pub fn write<W: Write>(
+        &self,
+        w: &mut W,
+        theme: &Theme,
+        size_format: SizeFormat,
+        numerics: &locale::Numeric,
+    ) -> io::Result<()> {
+        writeln!(w, "{}", self.render(theme, size_format, numerics).strings())
+    }
+}
+
+/// Reads the user’s rules for formatting numbers, which the sizes in
+/// footers and summaries get written with. This only needs doing once for
+/// the whole listing, however many directories are in it.
+pub fn load_numerics() -> locale::Numeric {
+    locale::Numeric::load_user_locale().unwrap_or_else(|_| locale::Numeric::english())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn plurals() {
+        let totals = Totals {
+            files: 3,
+            directories: 1,
+            links: 0,
+            others: 2,
+            ..Totals::default()
+        };
+
+        assert_eq!(totals.counts(), "3 files, 1 directory, 2 others");
+    }
+
+    #[test]
+    fn just_links() {
+        let totals = Totals {
+            links: 1,
+            ..Totals::default()
+        };
+
+        assert_eq!(totals.counts(), "1 link");
+    }
+}
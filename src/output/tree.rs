@@ -54,19 +54,60 @@ pub enum TreePart {
 }
 
 impl TreePart {
-    /// Turn this tree part into ASCII-licious box drawing characters!
-    /// (Warning: not actually ASCII)
-    pub fn ascii_art(self) -> &'static str {
+    /// Turn this tree part into box drawing characters, of the given style.
+    pub fn art(self, style: TreeStyle) -> &'static str {
         #[rustfmt::skip]
-        return match self {
-            Self::Edge    => "├──",
-            Self::Line    => "│  ",
-            Self::Corner  => "└──",
-            Self::Blank   => "   ",
+        return match (style, self) {
+            (_,                  Self::Blank)  => "   ",
+            (TreeStyle::None,    _)            => "   ",
+
+            (TreeStyle::Unicode, Self::Edge)   => "├──",
+            (TreeStyle::Unicode, Self::Line)   => "│  ",
+            (TreeStyle::Unicode, Self::Corner) => "└──",
+
+            (TreeStyle::Ascii,   Self::Edge)   => "|--",
+            (TreeStyle::Ascii,   Self::Line)   => "|  ",
+            (TreeStyle::Ascii,   Self::Corner) => "`--",
+
+            (TreeStyle::Rounded, Self::Edge)   => "├──",
+            (TreeStyle::Rounded, Self::Line)   => "│  ",
+            (TreeStyle::Rounded, Self::Corner) => "╰──",
+
+            (TreeStyle::Bold,    Self::Edge)   => "┣━━",
+            (TreeStyle::Bold,    Self::Line)   => "┃  ",
+            (TreeStyle::Bold,    Self::Corner) => "┗━━",
+
+            (TreeStyle::Double,  Self::Edge)   => "╠══",
+            (TreeStyle::Double,  Self::Line)   => "║  ",
+            (TreeStyle::Double,  Self::Corner) => "╚══",
         };
     }
 }
 
+/// Which characters a tree’s branches get drawn with.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub enum TreeStyle {
+    /// Box drawing lines, such as `├──`.
+    #[default]
+    Unicode,
+
+    /// Plain ASCII characters, such as `|--`, for terminals or fonts that
+    /// can’t show box drawing characters.
+    Ascii,
+
+    /// Box drawing lines with rounded corners, such as `╰──`.
+    Rounded,
+
+    /// Heavy box drawing lines, such as `┣━━`.
+    Bold,
+
+    /// Double box drawing lines, such as `╠══`.
+    Double,
+
+    /// No lines at all, only the indentation.
+    None,
+}
+
 /// A **tree trunk** builds up arrays of tree parts over multiple depths.
 #[derive(Debug, Default)]
 pub struct TreeTrunk {
@@ -283,3 +324,36 @@ mod iter_test {
         assert!(iter.next().is_none());
     }
 }
+
+#[cfg(test)]
+mod art_test {
+    use super::*;
+
+    #[test]
+    fn every_part_is_as_wide() {
+        for style in [
+            TreeStyle::Unicode,
+            TreeStyle::Ascii,
+            TreeStyle::Rounded,
+            TreeStyle::Bold,
+            TreeStyle::Double,
+            TreeStyle::None,
+        ] {
+            for part in [
+                TreePart::Edge,
+                TreePart::Line,
+                TreePart::Corner,
+                TreePart::Blank,
+            ] {
+                assert_eq!(part.art(style).chars().count(), 3);
+            }
+        }
+    }
+
+    #[test]
+    fn ascii_is_ascii() {
+        for part in [TreePart::Edge, TreePart::Line, TreePart::Corner] {
+            assert!(part.art(TreeStyle::Ascii).is_ascii());
+        }
+    }
+}
@@ -0,0 +1,269 @@
+//! A cache of users and groups that the threads rendering a table’s rows
+//! can share.
+//!
+//! `uzers::UsersCache` remembers every lookup, but it isn’t `Sync`, so it
+//! had to sit behind a mutex that every row took in turn. A slow lookup,
+//! such as one that goes out to an LDAP server, would then hold up every
+//! other thread, even ones after names that were already known. This cache
+//! only takes its write lock to store a result, never while looking one up.
+//!
+//! Before the rows of a table are rendered, `prefetch` looks up every user
+//! and group that isn’t known yet in one batch, in parallel, so rendering
+//! the rows only ever has to read from the cache. Lookups still go through
+//! the C library, rather than reading `/etc/passwd` directly, so users from
+//! NSS modules such as LDAP or SSSD get their names too.
+
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::hash::Hash;
+use std::sync::{Arc, RwLock};
+
+use libc::{gid_t, uid_t};
+use rayon::prelude::*;
+use uzers::{Group, Groups, User, Users};
+
+use crate::fs::metadata::Source;
+
+/// A thread-safe map of user and group IDs to what they were found to be,
+/// including IDs that don’t belong to anyone.
+pub struct UserCache {
+    users: RwLock<HashMap<uid_t, Option<Arc<User>>>>,
+    groups: RwLock<HashMap<gid_t, Option<Arc<Group>>>>,
+
+    uid: uid_t,
+    euid: uid_t,
+    gid: gid_t,
+    egid: gid_t,
+}
+
+impl UserCache {
+    pub fn new() -> Self {
+        Self {
+            users: RwLock::default(),
+            groups: RwLock::default(),
+            uid: uzers::get_current_uid(),
+            euid: uzers::get_effective_uid(),
+            gid: uzers::get_current_gid(),
+            egid: uzers::get_effective_gid(),
+        }
+    }
+
+    /// Looks up all the given users and groups that haven’t been looked up
+    /// already, in parallel.
+    pub fn prefetch<U, G>(&self, user_ids: U, group_ids: G)
+    where
+        U: IntoIterator<Item = uid_t>,
+        G: IntoIterator<Item = gid_t>,
+    {
+        fetch_all(&self.users, user_ids, uzers::get_user_by_uid);
+        fetch_all(&self.groups, group_ids, uzers::get_group_by_gid);
+    }
+}
+
+/// Returns the cached value for the key, looking it up and storing it if
+/// it hasn’t been seen before.
+fn fetch<K, V>(
+    cache: &RwLock<HashMap<K, Option<Arc<V>>>>,
+    key: K,
+    look_up: fn(K) -> Option<V>,
+) -> Option<Arc<V>>
+where
+    K: Copy + Eq + Hash,
+{
+    if let Some(value) = cache.read().unwrap().get(&key) {
+        return value.clone();
+    }
+
+    let value = look_up(key).map(Arc::new);
+    cache.write().unwrap().entry(key).or_insert(value).clone()
+}
+
+fn fetch_all<K, V, I>(
+    cache: &RwLock<HashMap<K, Option<Arc<V>>>>,
+    keys: I,
+    look_up: fn(K) -> Option<V>,
+) where
+    K: Copy + Eq + Hash + Send + Sync,
+    V: Send + Sync,
+    I: IntoIterator<Item = K>,
+{
+    let missing = {
+        let cache = cache.read().unwrap();
+        keys.into_iter()
+            .filter(|key| !cache.contains_key(key))
+            .collect::<HashSet<K>>()
+    };
+
+    if missing.is_empty() {
+        return;
+    }
+
+    let found = missing
+        .into_par_iter()
+        .map(|key| (key, look_up(key).map(Arc::new)))
+        .collect::<Vec<_>>();
+
+    let mut cache = cache.write().unwrap();
+    for (key, value) in found {
+        cache.entry(key).or_insert(value);
+    }
+}
+
+impl Users for UserCache {
+    fn get_user_by_uid(&self, uid: uid_t) -> Option<Arc<User>> {
+        fetch(&self.users, uid, uzers::get_user_by_uid)
+    }
+
+    fn get_user_by_name<S: AsRef<OsStr> + ?Sized>(&self, username: &S) -> Option<Arc<User>> {
+        let user = uzers::get_user_by_name(username)?;
+        self.get_user_by_uid(user.uid())
+    }
+
+    fn get_current_uid(&self) -> uid_t {
+        self.uid
+    }
+
+    fn get_current_username(&self) -> Option<Arc<OsStr>> {
+        self.get_user_by_uid(self.uid)
+            .map(|user| Arc::from(user.name()))
+    }
+
+    fn get_effective_uid(&self) -> uid_t {
+        self.euid
+    }
+
+    fn get_effective_username(&self) -> Option<Arc<OsStr>> {
+        self.get_user_by_uid(self.euid)
+            .map(|user| Arc::from(user.name()))
+    }
+}
+
+impl Groups for UserCache {
+    fn get_group_by_gid(&self, gid: gid_t) -> Option<Arc<Group>> {
+        fetch(&self.groups, gid, uzers::get_group_by_gid)
+    }
+
+    fn get_group_by_name<S: AsRef<OsStr> + ?Sized>(&self, group_name: &S) -> Option<Arc<Group>> {
+        let group = uzers::get_group_by_name(group_name)?;
+        self.get_group_by_gid(group.gid())
+    }
+
+    fn get_current_gid(&self) -> gid_t {
+        self.gid
+    }
+
+    fn get_current_groupname(&self) -> Option<Arc<OsStr>> {
+        self.get_group_by_gid(self.gid)
+            .map(|group| Arc::from(group.name()))
+    }
+
+    fn get_effective_gid(&self) -> gid_t {
+        self.egid
+    }
+
+    fn get_effective_groupname(&self) -> Option<Arc<OsStr>> {
+        self.get_group_by_gid(self.egid)
+            .map(|group| Arc::from(group.name()))
+    }
+}
+
+/// The users and groups of somewhere other than this computer, which only
+/// have the names that came along with the files that were read from there.
+pub struct SourceUsers<'a>(pub &'a dyn Source);
+
+impl SourceUsers<'_> {
+    fn current_ids(&self) -> (uid_t, gid_t) {
+        self.0.current_ids().unwrap_or((uid_t::MAX, gid_t::MAX))
+    }
+}
+
+impl Users for SourceUsers<'_> {
+    fn get_user_by_uid(&self, uid: uid_t) -> Option<Arc<User>> {
+        let name = self.0.user_name(uid)?;
+        let (current_user, current_group) = self.current_ids();
+        let primary_group = if uid == current_user {
+            current_group
+        } else {
+            gid_t::MAX
+        };
+        Some(Arc::new(User::new(uid, &name, primary_group)))
+    }
+
+    fn get_user_by_name<S: AsRef<OsStr> + ?Sized>(&self, _username: &S) -> Option<Arc<User>> {
+        None
+    }
+
+    fn get_current_uid(&self) -> uid_t {
+        self.current_ids().0
+    }
+
+    fn get_current_username(&self) -> Option<Arc<OsStr>> {
+        self.get_user_by_uid(self.get_current_uid())
+            .map(|user| Arc::from(user.name()))
+    }
+
+    fn get_effective_uid(&self) -> uid_t {
+        self.get_current_uid()
+    }
+
+    fn get_effective_username(&self) -> Option<Arc<OsStr>> {
+        self.get_current_username()
+    }
+}
+
+impl Groups for SourceUsers<'_> {
+    fn get_group_by_gid(&self, gid: gid_t) -> Option<Arc<Group>> {
+        let name = self.0.group_name(gid)?;
+        Some(Arc::new(Group::new(gid, &name)))
+    }
+
+    fn get_group_by_name<S: AsRef<OsStr> + ?Sized>(&self, _group_name: &S) -> Option<Arc<Group>> {
+        None
+    }
+
+    fn get_current_gid(&self) -> gid_t {
+        self.current_ids().1
+    }
+
+    fn get_current_groupname(&self) -> Option<Arc<OsStr>> {
+        self.get_group_by_gid(self.get_current_gid())
+            .map(|group| Arc::from(group.name()))
+    }
+
+    fn get_effective_gid(&self) -> gid_t {
+        self.get_current_gid()
+    }
+
+    fn get_effective_groupname(&self) -> Option<Arc<OsStr>> {
+        self.get_current_groupname()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn lookups_are_shared() {
+        let cache = UserCache::new();
+        let uid = cache.get_current_uid();
+
+        cache.prefetch([uid], []);
+        let first = cache.get_user_by_uid(uid);
+        let second = cache.get_user_by_uid(uid);
+
+        assert_eq!(first.is_some(), uzers::get_user_by_uid(uid).is_some());
+        if let (Some(first), Some(second)) = (first, second) {
+            assert!(Arc::ptr_eq(&first, &second));
+        }
+    }
+
+    #[test]
+    fn missing_ids_are_remembered() {
+        let cache = UserCache::new();
+        let nobody = 0xDEAD_BEEF;
+
+        assert!(cache.get_group_by_gid(nobody).is_none());
+        assert!(cache.groups.read().unwrap().contains_key(&nobody));
+    }
+}
@@ -0,0 +1,68 @@
+//! Working out how many terminal columns text takes up.
+//!
+//! Counting the width of each character on its own gets file names with
+//! emoji and accents wrong. A family emoji is several people joined together
+//! with zero-width joiners, and a flag is a pair of regional indicator
+//! letters, but either gets drawn as a single two-column picture. So text
+//! gets split into grapheme clusters — what a reader would call one
+//! character — first, and each cluster is measured as a whole.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// How many columns the text takes up.
+pub fn str_width(string: &str) -> usize {
+    string.graphemes(true).map(cluster_width).sum()
+}
+
+/// How many columns one grapheme cluster takes up. Characters with an
+/// ambiguous East Asian width are taken to be one column wide, as they are
+/// outside of CJK terminals.
+pub fn cluster_width(cluster: &str) -> usize {
+    cluster.width()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ascii() {
+        assert_eq!(str_width("Cargo.toml"), 10);
+    }
+
+    #[test]
+    fn east_asian() {
+        assert_eq!(str_width("日本語.txt"), 10);
+        assert_eq!(str_width("ｶﾀｶﾅ"), 4);
+        assert_eq!(str_width("한글"), 4);
+    }
+
+    #[test]
+    fn combining_marks() {
+        assert_eq!(str_width("cafe\u{301}"), 4);
+    }
+
+    #[test]
+    fn zwj_sequences() {
+        let family = "👨\u{200D}👩\u{200D}👧";
+        assert_eq!(str_width(family), 2);
+        assert_eq!(str_width(&format!("{family}.png")), 6);
+    }
+
+    #[test]
+    fn skin_tones() {
+        assert_eq!(str_width("👍🏽"), 2);
+    }
+
+    #[test]
+    fn flags() {
+        assert_eq!(str_width("🇯🇵🇫🇷"), 4);
+    }
+
+    #[test]
+    fn presentation_selectors() {
+        assert_eq!(str_width("\u{2764}"), 1);
+        assert_eq!(str_width("\u{2764}\u{FE0F}"), 2);
+    }
+}
@@ -0,0 +1,107 @@
+//! Showing how far a slow recursive listing has got.
+//!
+//! A `--recurse` or `--tree` listing of a huge directory can take a long
+//! time before anything gets printed, especially as a long tree has to be
+//! read all the way down before its first line can be drawn. So the recursion
+//! adds up how many directories and entries it’s been through, and once
+//! a listing has taken longer than a second, a line on stderr keeps count.
+//!
+//! The line is only shown when stderr is a terminal, and gets cleared as
+//! soon as the listing starts being written out, so it never ends up mixed
+//! in with the output.
+
+use std::io::{self, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+/// How long a listing has to take before the line gets shown.
+const DELAY: Duration = Duration::from_secs(1);
+
+/// How often the line gets redrawn once it’s shown.
+const INTERVAL: Duration = Duration::from_millis(100);
+
+static DIRECTORIES: AtomicU64 = AtomicU64::new(0);
+static ENTRIES: AtomicU64 = AtomicU64::new(0);
+
+/// Whether the listing has started being written, so the line should go.
+static FINISHED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the line is on the screen, which is only changed while holding
+/// the lock so the line doesn’t get drawn again just after being cleared.
+static SHOWN: Mutex<bool> = Mutex::new(false);
+
+/// Starts showing the counts once the delay has passed, if stderr is a
+/// terminal to show them on.
+pub fn start() {
+    if !io::stderr().is_terminal() {
+        return;
+    }
+
+    thread::spawn(|| {
+        thread::sleep(DELAY);
+        while !FINISHED.load(Ordering::Relaxed) {
+            draw();
+            thread::sleep(INTERVAL);
+        }
+    });
+}
+
+/// Adds a directory that’s been read, with how many entries were in it.
+pub fn add_directory(entries: usize) {
+    DIRECTORIES.fetch_add(1, Ordering::Relaxed);
+    ENTRIES.fetch_add(entries as u64, Ordering::Relaxed);
+}
+
+/// Clears the line, and stops it from being drawn again. This gets run
+/// before anything is written out, so it has to be cheap to call again.
+pub fn finish() {
+    if FINISHED.swap(true, Ordering::Relaxed) {
+        return;
+    }
+
+    let mut shown = SHOWN.lock().unwrap();
+    if *shown {
+        let _ = write!(io::stderr(), "\r\x1b[K");
+        *shown = false;
+    }
+}
+
+fn draw() {
+    let mut shown = SHOWN.lock().unwrap();
+    if FINISHED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let line = progress_line(
+        DIRECTORIES.load(Ordering::Relaxed),
+        ENTRIES.load(Ordering::Relaxed),
+    );
+    let _ = write!(io::stderr(), "\r\x1b[K{line}");
+    *shown = true;
+}
+
+fn progress_line(directories: u64, entries: u64) -> String {
+    let directories_unit = if directories == 1 {
+        "directory"
+    } else {
+        "directories"
+    };
+    let entries_unit = if entries == 1 { "entry" } else { "entries" };
+    format!("eza: scanned {directories} {directories_unit}, {entries} {entries_unit}…")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn line() {
+        assert_eq!(progress_line(1, 1), "eza: scanned 1 directory, 1 entry…");
+        assert_eq!(
+            progress_line(120, 45000),
+            "eza: scanned 120 directories, 45000 entries…"
+        );
+    }
+}
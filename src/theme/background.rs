@@ -0,0 +1,146 @@
+//! Working out whether the terminal has a dark or a light background, so
+//! the default colours can be picked to be readable on it.
+//!
+//! Some terminals say which colours they use in the `COLORFGBG` environment
+//! variable, which gets checked first. Otherwise, the terminal gets asked
+//! for its background colour with an OSC 11 query, followed by a Primary
+//! Device Attributes query that every terminal answers, so there’s no need
+//! to wait around for terminals that don’t understand the first one.
+
+use log::*;
+
+#[cfg(unix)]
+use crate::output::terminal;
+
+/// Whether the terminal’s background is dark or light.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub enum Background {
+    /// Light text on a dark background, which the default colours were
+    /// originally picked for.
+    #[default]
+    Dark,
+
+    /// Dark text on a light background, which some of the default colours,
+    /// such as yellow, are too faint to read on.
+    Light,
+
+    /// Ask the terminal which it is, once the theme gets made.
+    Automatic,
+}
+
+impl Background {
+    /// The background given in a `COLORFGBG` value, which holds the numbers
+    /// of the foreground and background colours, separated by semicolons,
+    /// with the background coming last. Of the 16 standard colours, white,
+    /// grey, and the bright ones other than bright black are light.
+    pub fn from_colorfgbg(value: &str) -> Option<Self> {
+        let background = value.rsplit(';').next()?.trim().parse::<u8>().ok()?;
+        match background {
+            0..=6 | 8 => Some(Self::Dark),
+            7 | 9..=15 => Some(Self::Light),
+            _ => None,
+        }
+    }
+
+    /// Asks the terminal for its background colour, falling back to a dark
+    /// background if it doesn’t say.
+    pub fn detect() -> Self {
+        let background = query_terminal();
+        debug!("Terminal background is {:?}", background);
+        background.unwrap_or(Self::Dark)
+    }
+}
+
+/// Sends the background colour query, and reads the answer out of whatever
+/// the terminal sends back before it answers the device attributes query.
+#[cfg(unix)]
+fn query_terminal() -> Option<Background> {
+    use std::io::Write;
+
+    terminal::query(|tty| {
+        tty.write_all(b"\x1b]11;?\x1b\\\x1b[c").ok()?;
+        tty.flush().ok()?;
+
+        let response = terminal::read_until(tty, 256, |response| {
+            response.last() == Some(&b'c') && find(response, b"\x1b[?").is_some()
+        })?;
+        from_osc_response(&response)
+    })
+}
+
+/// Terminals other than Unix ones can’t be asked.
+#[cfg(not(unix))]
+fn query_terminal() -> Option<Background> {
+    None
+}
+
+/// Parse the background colour out of an OSC 11 response, which looks like
+/// `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` followed by a terminator, where each
+/// component has between one and four hex digits. The background counts as
+/// light when its relative luminance is over a half.
+fn from_osc_response(response: &[u8]) -> Option<Background> {
+    let start = find(response, b"]11;rgb:")? + b"]11;rgb:".len();
+    let colour = std::str::from_utf8(&response[start..]).ok()?;
+
+    let mut components = colour.splitn(3, '/').map(|component| {
+        let digits = component
+            .split(|c: char| !c.is_ascii_hexdigit())
+            .next()
+            .filter(|digits| (1..=4).contains(&digits.len()))?;
+        let value = u16::from_str_radix(digits, 16).ok()?;
+        let max = (1_u32 << (4 * digits.len())) - 1;
+        Some(f64::from(value) / f64::from(max))
+    });
+
+    let red = components.next()??;
+    let green = components.next()??;
+    let blue = components.next()??;
+
+    let luminance = 0.2126 * red + 0.7152 * green + 0.0722 * blue;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+/// Where the given bytes first appear in the response.
+fn find(response: &[u8], needle: &[u8]) -> Option<usize> {
+    response
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn colorfgbg() {
+        assert_eq!(Background::from_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(Background::from_colorfgbg("0;15"), Some(Background::Light));
+        assert_eq!(
+            Background::from_colorfgbg("0;default;7"),
+            Some(Background::Light)
+        );
+        assert_eq!(Background::from_colorfgbg("7;8"), Some(Background::Dark));
+        assert_eq!(Background::from_colorfgbg("15;default"), None);
+        assert_eq!(Background::from_colorfgbg(""), None);
+    }
+
+    #[test]
+    fn osc_responses() {
+        let dark = b"\x1b]11;rgb:1e1e/1e1e/2e2e\x1b\\\x1b[?62;22c";
+        let light = b"\x1b]11;rgb:ffff/fafa/f0f0\x07\x1b[?1;2c";
+        let short = b"\x1b]11;rgb:f/f/f\x07";
+        assert_eq!(from_osc_response(dark), Some(Background::Dark));
+        assert_eq!(from_osc_response(light), Some(Background::Light));
+        assert_eq!(from_osc_response(short), Some(Background::Light));
+    }
+
+    #[test]
+    fn no_osc_response() {
+        assert_eq!(from_osc_response(b"\x1b[?62;22c"), None);
+        assert_eq!(from_osc_response(b"\x1b]11;rgb:ffff/ffff\x07"), None);
+    }
+}
@@ -1,12 +1,46 @@
-use nu_ansi_term::Color::*;
+use nu_ansi_term::Color::{self, *};
 use nu_ansi_term::Style;
+use std::collections::HashMap;
 use std::default::Default;
 
 use crate::output::color_scale::{ColorScaleMode, ColorScaleOptions};
 use crate::theme::ui_styles::*;
+use crate::theme::Background;
+
+/// The colours that stand in for the ones that are too faint to read on a
+/// light background: yellow becomes a dark gold, cyan a dark teal, and white
+/// a mid grey.
+struct Palette {
+    yellow: Color,
+    cyan: Color,
+    white: Color,
+}
+
+impl Palette {
+    fn for_background(background: Background) -> Self {
+        match background {
+            Background::Light => Self {
+                yellow: Fixed(136),
+                cyan: Fixed(30),
+                white: Fixed(244),
+            },
+            Background::Dark | Background::Automatic => Self {
+                yellow: Yellow,
+                cyan: Cyan,
+                white: White,
+            },
+        }
+    }
+}
 
 impl UiStyles {
-    pub fn default_theme(scale: ColorScaleOptions) -> Self {
+    pub fn default_theme(scale: ColorScaleOptions, background: Background) -> Self {
+        let Palette {
+            yellow,
+            cyan,
+            white,
+        } = Palette::for_background(background);
+
         Self {
             colourful: true,
 
@@ -14,28 +48,32 @@ impl UiStyles {
             filekinds: FileKinds {
                 normal:       Style::default(),
                 directory:    Blue.bold(),
-                symlink:      Cyan.normal(),
-                pipe:         Yellow.normal(),
-                block_device: Yellow.bold(),
-                char_device:  Yellow.bold(),
+                symlink:      cyan.normal(),
+                pipe:         yellow.normal(),
+                block_device: yellow.bold(),
+                char_device:  yellow.bold(),
                 socket:       Red.bold(),
-                special:      Yellow.normal(),
+                door:         Red.bold(),
+                special:      yellow.normal(),
                 executable:   Green.bold(),
                 mount_point:  Blue.bold().underline(),
+                home:         Some(Purple.bold()),
+                user_dir:     Some(cyan.bold()),
+                project:      Some(Blue.bold().italic()),
             },
 
             #[rustfmt::skip]
             perms: Permissions {
-                user_read:           Yellow.bold(),
+                user_read:           yellow.bold(),
                 user_write:          Red.bold(),
                 user_execute_file:   Green.bold().underline(),
                 user_execute_other:  Green.bold(),
 
-                group_read:          Yellow.normal(),
+                group_read:          yellow.normal(),
                 group_write:         Red.normal(),
                 group_execute:       Green.normal(),
 
-                other_read:          Yellow.normal(),
+                other_read:          yellow.normal(),
                 other_write:         Red.normal(),
                 other_execute:       Green.normal(),
 
@@ -45,14 +83,14 @@ impl UiStyles {
                 attribute:           Style::default(),
             },
 
-            size: Size::colourful(scale),
+            size: Size::colourful(scale, yellow),
 
             #[rustfmt::skip]
             users: Users {
-                user_you:                       Yellow.bold(),
+                user_you:                       yellow.bold(),
                 user_other:                     Style::default(),
                 user_root:                      Style::default(),
-                group_yours:                    Yellow.bold(),
+                group_yours:                    yellow.bold(),
                 group_other:                    Style::default(),
                 group_root:                     Style::default(),
             },
@@ -61,6 +99,7 @@ impl UiStyles {
             links: Links {
                 normal:          Red.bold(),
                 multi_link_file: Red.on(Yellow),
+                groups:          [Cyan.bold(), Purple.bold(), yellow.bold(), Green.bold(), Blue.bold(), Red.bold()],
             },
 
             #[rustfmt::skip]
@@ -68,7 +107,7 @@ impl UiStyles {
                 new:         Green.normal(),
                 modified:    Blue.normal(),
                 deleted:     Red.normal(),
-                renamed:     Yellow.normal(),
+                renamed:     yellow.normal(),
                 typechange:  Purple.normal(),
                 ignored:     Style::default().dimmed(),
                 conflicted:  Red.normal(),
@@ -76,9 +115,10 @@ impl UiStyles {
 
             git_repo: GitRepo {
                 branch_main: Green.normal(),
-                branch_other: Yellow.normal(),
+                branch_other: yellow.normal(),
                 git_clean: Green.normal(),
-                git_dirty: Yellow.bold(),
+                git_dirty: yellow.bold(),
+                git_behind: Red.normal(),
             },
 
             security_context: SecurityContext {
@@ -88,8 +128,8 @@ impl UiStyles {
                     colon: Style::default().dimmed(),
                     user:  Blue.normal(),
                     role:  Green.normal(),
-                    typ:   Yellow.normal(),
-                    range: Cyan.normal(),
+                    typ:   yellow.normal(),
+                    range: cyan.normal(),
                 },
             },
 
@@ -97,39 +137,51 @@ impl UiStyles {
             file_type: FileType {
                 image:      Purple.normal(),
                 video:      Purple.bold(),
-                music:      Cyan.normal(),
-                lossless:   Cyan.bold(),
+                music:      cyan.normal(),
+                lossless:   cyan.bold(),
                 crypto:     Green.bold(),
                 document:   Green.normal(),
                 compressed: Red.normal(),
-                temp:       White.normal(),
-                compiled:   Yellow.normal(),
-                build:      Yellow.bold().underline(),
-                source:     Yellow.bold(), // Need to discuss color
+                temp:       white.normal(),
+                compiled:   yellow.normal(),
+                build:      yellow.bold().underline(),
+                source:     yellow.bold(), // Need to discuss color
+            },
+
+            #[rustfmt::skip]
+            audit: Audit {
+                warning:  yellow.bold(),
+                critical: Red.bold().reverse(),
             },
 
             punctuation: DarkGray.bold(),
             date: Blue.normal(),
             inode: Purple.normal(),
-            blocks: Cyan.normal(),
+            blocks: cyan.normal(),
             octal: Purple.normal(),
             flags: Style::default(),
+            tree: None,
             header: Style::default().underline(),
 
-            symlink_path: Cyan.normal(),
+            symlink_path: cyan.normal(),
             control_char: Red.normal(),
             broken_symlink: Red.normal(),
             broken_path_overlay: Style::default().underline(),
+            in_use: Purple.bold(),
+            common_name: Blue.bold(),
+
+            user_rules: HashMap::new(),
+            group_rules: HashMap::new(),
         }
     }
 }
 
 impl Size {
-    pub fn colourful(scale: ColorScaleOptions) -> Self {
+    pub fn colourful(scale: ColorScaleOptions, yellow: Color) -> Self {
         if scale.size && scale.mode == ColorScaleMode::Fixed {
             Self::colourful_fixed()
         } else {
-            Self::colourful_gradient()
+            Self::colourful_gradient(yellow)
         }
     }
 
@@ -152,20 +204,20 @@ impl Size {
         }
     }
 
-    fn colourful_gradient() -> Self {
+    fn colourful_gradient(yellow: Color) -> Self {
         Self {
             major: Green.bold(),
             minor: Green.normal(),
 
             number_byte: Green.normal(),
             number_kilo: Green.bold(),
-            number_mega: Yellow.normal(),
+            number_mega: yellow.normal(),
             number_giga: Red.normal(),
             number_huge: Purple.normal(),
 
             unit_byte: Green.normal(),
             unit_kilo: Green.bold(),
-            unit_mega: Yellow.normal(),
+            unit_mega: yellow.normal(),
             unit_giga: Red.normal(),
             unit_huge: Purple.normal(),
         }
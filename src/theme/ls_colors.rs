@@ -0,0 +1,130 @@
+//! Writing a theme back out as an `LS_COLORS` string, for
+//! `--print-ls-colors`.
+//!
+//! Other programs that colour file names, such as `fd`, `tree`, or `dircolors`
+//! itself, understand `LS_COLORS` but not `EZA_COLORS` or eza’s theme files,
+//! so this gives them as much of the theme as the format can hold: the
+//! colours for each kind of file, followed by one entry for every file name
+//! suffix that eza has a colour for. Globs that can’t be written as a suffix,
+//! and the parts of the theme that have nothing to do with file names, get
+//! left out.
+
+use std::collections::BTreeMap;
+
+use nu_ansi_term::Style;
+
+use super::Theme;
+
+impl Theme {
+    /// The file name colours of this theme, as an `LS_COLORS` string.
+    pub fn to_ls_colors(&self) -> String {
+        let kinds = &self.ui.filekinds;
+
+        #[rustfmt::skip]
+        let keys = [
+            ("di", kinds.directory),
+            ("ex", kinds.executable),
+            ("fi", kinds.normal),
+            ("pi", kinds.pipe),
+            ("so", kinds.socket),
+            ("do", kinds.door),
+            ("bd", kinds.block_device),
+            ("cd", kinds.char_device),
+            ("ln", kinds.symlink),
+            ("or", self.ui.broken_symlink),
+        ];
+
+        let mut suffixes = Vec::new();
+        self.exts.ls_colors_suffixes(self, &mut suffixes);
+
+        // Later suffixes override earlier ones, and a map keeps the output
+        // in the same order each time.
+        let suffixes = suffixes.into_iter().collect::<BTreeMap<_, _>>();
+
+        keys.into_iter()
+            .map(|(key, style)| (key.to_string(), style))
+            .chain(
+                suffixes
+                    .into_iter()
+                    .map(|(suffix, style)| (format!("*{suffix}"), style)),
+            )
+            .filter_map(|(key, style)| sgr(style).map(|sgr| format!("{key}={sgr}")))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+}
+
+/// The numbers of the escape sequence that turns on a style, or nothing if
+/// the style doesn’t change anything.
+fn sgr(style: Style) -> Option<String> {
+    if style.is_plain() {
+        return None;
+    }
+
+    let prefix = style.prefix().to_string();
+    prefix
+        .strip_prefix("\x1b[")
+        .and_then(|codes| codes.strip_suffix('m'))
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::output::color_scale::{ColorDepth, ColorScaleMode, ColorScaleOptions};
+    use crate::theme::{Background, Definitions, GitSymbols, Options, UseColours};
+
+    fn ls_colors(use_colours: UseColours, definitions: Definitions) -> String {
+        Options {
+            use_colours,
+            colour_scale: ColorScaleOptions {
+                mode: ColorScaleMode::Fixed,
+                min_luminance: 40,
+                size: false,
+                age: false,
+                time: None,
+                size_thresholds: None,
+                depth: ColorDepth::TrueColor,
+            },
+            background: Background::Dark,
+            definitions,
+            git_symbols: GitSymbols::default(),
+        }
+        .to_theme(true)
+        .to_ls_colors()
+    }
+
+    #[test]
+    fn no_colours() {
+        assert_eq!(ls_colors(UseColours::Never, Definitions::default()), "");
+    }
+
+    #[test]
+    fn reset_theme() {
+        let definitions = Definitions {
+            exa: Some("reset:di=34:ln=36:*.rs=1;33:**/build/*=31:*.md=4".into()),
+            ..Definitions::default()
+        };
+        assert_eq!(
+            ls_colors(UseColours::Always, definitions),
+            "di=34:ex=1;32:pi=33:so=1;31:do=1;31:bd=1;33:cd=1;33:ln=36:or=31:*.md=4:*.rs=1;33"
+        );
+    }
+
+    #[test]
+    fn later_globs_win() {
+        let definitions = Definitions {
+            ls: Some("*.txt=31".into()),
+            exa: Some("reset:*.txt=32".into()),
+            ..Definitions::default()
+        };
+        assert!(ls_colors(UseColours::Always, definitions).ends_with(":*.txt=32"));
+    }
+
+    #[test]
+    fn default_file_types() {
+        let colours = ls_colors(UseColours::Always, Definitions::default());
+        assert!(colours.starts_with("di=1;34:"));
+        assert!(colours.contains(":*.png="));
+        assert!(colours.contains(":*Makefile="));
+    }
+}
@@ -1,5 +1,10 @@
+use std::path::{Path, PathBuf};
+
 use nu_ansi_term::Style;
 
+use crate::fs::feature::audit::Severity;
+use crate::fs::feature::special_dirs::SpecialDir;
+use crate::fs::feature::xattr::TagColour;
 use crate::fs::File;
 use crate::info::filetype::FileType;
 use crate::output::color_scale::ColorScaleOptions;
@@ -12,7 +17,11 @@ pub use self::ui_styles::UiStyles;
 mod lsc;
 pub use self::lsc::LSColors;
 
+mod background;
+pub use self::background::Background;
+
 mod default_theme;
+mod ls_colors;
 
 #[derive(PartialEq, Eq, Debug)]
 pub struct Options {
@@ -20,7 +29,12 @@ pub struct Options {
 
     pub colour_scale: ColorScaleOptions,
 
+    /// Which background the default colours should be readable on.
+    pub background: Background,
+
     pub definitions: Definitions,
+
+    pub git_symbols: GitSymbols,
 }
 
 /// Under what circumstances we should display coloured, rather than plain,
@@ -42,15 +56,35 @@ pub enum UseColours {
     Never,
 }
 
+/// Which letters the Git column uses for each file’s status.
+#[derive(PartialEq, Eq, Debug, Default, Copy, Clone)]
+pub enum GitSymbols {
+    /// eza’s own letters, with `N` for new files, `I` for ignored ones, and
+    /// `-` for files with no changes.
+    #[default]
+    Eza,
+
+    /// The letters `git status --short` uses, with `A` for files added to
+    /// the index, `??` for untracked files, `!!` for ignored ones, and a
+    /// space for files with no changes.
+    Porcelain,
+}
+
 #[derive(PartialEq, Eq, Debug, Default)]
 pub struct Definitions {
     pub ls: Option<String>,
+
+    /// The definitions from the theme file, in the same format as
+    /// `EZA_COLORS`, with any themes it extends merged in.
+    pub theme: Option<String>,
+
     pub exa: Option<String>,
 }
 
 pub struct Theme {
     pub ui: UiStyles,
     pub exts: Box<dyn FileStyle>,
+    pub git_symbols: GitSymbols,
 }
 
 impl Options {
@@ -60,11 +94,22 @@ impl Options {
         {
             let ui = UiStyles::plain();
             let exts = Box::new(NoFileStyle);
-            return Theme { ui, exts };
+            return Theme {
+                ui,
+                exts,
+                git_symbols: self.git_symbols,
+            };
         }
 
+        // Only ask the terminal about its background once it’s known that
+        // the colours are going to be used.
+        let background = match self.background {
+            Background::Automatic => Background::detect(),
+            background => background,
+        };
+
         // Parse the environment variables into colours and extension mappings
-        let mut ui = UiStyles::default_theme(self.colour_scale);
+        let mut ui = UiStyles::default_theme(self.colour_scale, background);
         let (exts, use_default_filetypes) = self.definitions.parse_color_vars(&mut ui);
 
         // Use between 0 and 2 file name highlighters
@@ -75,7 +120,11 @@ impl Options {
             (true, true) => Box::new((exts, FileTypes)),
         };
 
-        Theme { ui, exts }
+        Theme {
+            ui,
+            exts,
+            git_symbols: self.git_symbols,
+        }
     }
 }
 
@@ -84,59 +133,97 @@ impl Definitions {
     /// colours into the `ExtensionMappings` that gets returned, and using the
     /// two-character UI codes to modify the mutable `Colours`.
     ///
-    /// Also returns if the `EZA_COLORS` variable should reset the existing file
-    /// type mappings or not. The `reset` code needs to be the first one.
+    /// Also returns if the `EZA_COLORS` variable or the theme file should reset
+    /// the existing file type mappings or not. The `reset` code needs to be
+    /// the first one.
+    ///
+    /// A glob can be given a priority by ending it with `#` and a number, as
+    /// in `**/build/*#10=38;5;240`. Globs with a higher priority win over
+    /// those with a lower one, and globs without one have a priority of 0.
+    /// Between globs of the same priority, the one specified last wins.
     fn parse_color_vars(&self, colours: &mut UiStyles) -> (ExtensionMappings, bool) {
         use log::*;
 
-        let mut exts = ExtensionMappings::default();
+        let mut globs = Vec::new();
+        let mut add_glob = |key: &str, style: Style| {
+            let (key, priority) = split_priority(key);
+            match glob::Pattern::new(key) {
+                Ok(pat) => {
+                    globs.push((priority, pat, style));
+                }
+                Err(e) => {
+                    warn!("Couldn't parse glob pattern {:?}: {}", key, e);
+                }
+            }
+        };
 
         if let Some(lsc) = &self.ls {
             LSColors(lsc).each_pair(|pair| {
-                if !colours.set_ls(&pair) {
-                    match glob::Pattern::new(pair.key) {
-                        Ok(pat) => {
-                            exts.add(pat, pair.to_style());
-                        }
-                        Err(e) => {
-                            warn!("Couldn't parse glob pattern {:?}: {}", pair.key, e);
-                        }
-                    }
+                if !colours.set_ls(&pair) && !colours.set_ls_only(&pair) {
+                    add_glob(pair.key, pair.to_style());
                 }
             });
         }
 
         let mut use_default_filetypes = true;
 
-        if let Some(exa) = &self.exa {
+        // The theme file goes first, so the environment can override it.
+        for exa in [&self.theme, &self.exa].into_iter().flatten() {
             // Is this hacky? Yes.
             if exa == "reset" || exa.starts_with("reset:") {
                 use_default_filetypes = false;
             }
 
             LSColors(exa).each_pair(|pair| {
-                if !colours.set_ls(&pair) && !colours.set_exa(&pair) {
-                    match glob::Pattern::new(pair.key) {
-                        Ok(pat) => {
-                            exts.add(pat, pair.to_style());
-                        }
-                        Err(e) => {
-                            warn!("Couldn't parse glob pattern {:?}: {}", pair.key, e);
-                        }
-                    }
+                if !colours.set_ls(&pair) && !colours.set_exa(&pair) && !colours.set_owner(&pair) {
+                    add_glob(pair.key, pair.to_style());
                 };
             });
         }
 
+        // The mappings get searched from the end, so the highest priority
+        // needs to go last. The sort is stable, which keeps globs of the same
+        // priority in the order they were given.
+        globs.sort_by_key(|glob| glob.0);
+
+        let mut exts = ExtensionMappings::default();
+        for (_, pat, style) in globs {
+            exts.add(pat, style);
+        }
+
+        if exts
+            .mappings
+            .iter()
+            .any(|(pat, _)| pat.as_str().contains('/'))
+        {
+            exts.cwd = std::env::current_dir().ok();
+        }
+
         (exts, use_default_filetypes)
     }
 }
 
+/// Splits a `#N` priority off the end of a glob key, if it has one.
+fn split_priority(key: &str) -> (&str, i32) {
+    if let Some((glob, priority)) = key.rsplit_once('#') {
+        if let Ok(priority) = priority.parse() {
+            return (glob, priority);
+        }
+    }
+
+    (key, 0)
+}
+
 /// Determine the style to paint the text for the filename part of the output.
 pub trait FileStyle: Sync {
     /// Return the style to paint the filename text for `file` from the given
     /// `theme`.
     fn get_style(&self, file: &File<'_>, theme: &Theme) -> Option<Style>;
+
+    /// Add the file name suffixes that this styles, and their styles, as
+    /// far as they can be written in `LS_COLORS`. Later suffixes take
+    /// precedence over earlier ones.
+    fn ls_colors_suffixes(&self, _theme: &Theme, _suffixes: &mut Vec<(String, Style)>) {}
 }
 
 #[derive(PartialEq, Debug)]
@@ -162,11 +249,21 @@ where
             .get_style(file, theme)
             .or_else(|| self.1.get_style(file, theme))
     }
+
+    fn ls_colors_suffixes(&self, theme: &Theme, suffixes: &mut Vec<(String, Style)>) {
+        self.1.ls_colors_suffixes(theme, suffixes);
+        self.0.ls_colors_suffixes(theme, suffixes);
+    }
 }
 
 #[derive(PartialEq, Debug, Default)]
 struct ExtensionMappings {
     mappings: Vec<(glob::Pattern, Style)>,
+
+    /// The directory that relative paths are listed from, which globs with
+    /// a slash in them need to match against. It only gets looked up when
+    /// there are some.
+    cwd: Option<PathBuf>,
 }
 
 impl ExtensionMappings {
@@ -187,9 +284,35 @@ impl FileStyle for ExtensionMappings {
         self.mappings
             .iter()
             .rev()
-            .find(|t| t.0.matches(&file.name))
+            .find(|t| glob_matches(&t.0, file, self.cwd.as_deref()))
             .map(|t| t.1)
     }
+
+    /// Only globs that are a `*` followed by a plain suffix, such as
+    /// `*.txt`, mean the same thing in `LS_COLORS`.
+    fn ls_colors_suffixes(&self, _theme: &Theme, suffixes: &mut Vec<(String, Style)>) {
+        for (pattern, style) in &self.mappings {
+            if let Some(suffix) = pattern.as_str().strip_prefix('*') {
+                if !suffix.is_empty() && !suffix.contains(['*', '?', '[', ']', '/']) {
+                    suffixes.push((suffix.to_string(), *style));
+                }
+            }
+        }
+    }
+}
+
+/// Globs with a slash in them, such as `**/build/*`, are matched against the
+/// file’s whole path as it was listed, without following any links; all
+/// others are matched against just its name.
+fn glob_matches(pattern: &glob::Pattern, file: &File<'_>, cwd: Option<&Path>) -> bool {
+    if pattern.as_str().contains('/') {
+        match cwd {
+            Some(cwd) => pattern.matches_path(&path_clean::clean(cwd.join(&file.path))),
+            None => pattern.matches_path(&file.path),
+        }
+    } else {
+        pattern.matches(&file.name)
+    }
 }
 
 #[derive(Debug)]
@@ -197,24 +320,35 @@ struct FileTypes;
 
 impl FileStyle for FileTypes {
     fn get_style(&self, file: &File<'_>, theme: &Theme) -> Option<Style> {
-        #[rustfmt::skip]
-        return match FileType::get_file_type(file) {
-            Some(FileType::Image)      => Some(theme.ui.file_type.image),
-            Some(FileType::Video)      => Some(theme.ui.file_type.video),
-            Some(FileType::Music)      => Some(theme.ui.file_type.music),
-            Some(FileType::Lossless)   => Some(theme.ui.file_type.lossless),
-            Some(FileType::Crypto)     => Some(theme.ui.file_type.crypto),
-            Some(FileType::Document)   => Some(theme.ui.file_type.document),
-            Some(FileType::Compressed) => Some(theme.ui.file_type.compressed),
-            Some(FileType::Temp)       => Some(theme.ui.file_type.temp),
-            Some(FileType::Compiled)   => Some(theme.ui.file_type.compiled),
-            Some(FileType::Build)      => Some(theme.ui.file_type.build),
-            Some(FileType::Source)     => Some(theme.ui.file_type.source),
-            None                       => None
-        };
+        FileType::get_file_type(file).map(|file_type| file_type_style(&file_type, theme))
+    }
+
+    fn ls_colors_suffixes(&self, theme: &Theme, suffixes: &mut Vec<(String, Style)>) {
+        let mut names = FileType::all_names().collect::<Vec<_>>();
+        names.sort_by(|a, b| a.0.cmp(&b.0));
+        for (name, file_type) in names {
+            suffixes.push((name, file_type_style(&file_type, theme)));
+        }
     }
 }
 
+fn file_type_style(file_type: &FileType, theme: &Theme) -> Style {
+    #[rustfmt::skip]
+    return match file_type {
+        FileType::Image      => theme.ui.file_type.image,
+        FileType::Video      => theme.ui.file_type.video,
+        FileType::Music      => theme.ui.file_type.music,
+        FileType::Lossless   => theme.ui.file_type.lossless,
+        FileType::Crypto     => theme.ui.file_type.crypto,
+        FileType::Document   => theme.ui.file_type.document,
+        FileType::Compressed => theme.ui.file_type.compressed,
+        FileType::Temp       => theme.ui.file_type.temp,
+        FileType::Compiled   => theme.ui.file_type.compiled,
+        FileType::Build      => theme.ui.file_type.build,
+        FileType::Source     => theme.ui.file_type.source,
+    };
+}
+
 #[cfg(unix)]
 impl render::BlocksColours for Theme {
     fn blocksize(&self, prefix: Option<number_prefix::Prefix>) -> Style {
@@ -257,6 +391,7 @@ impl render::FiletypeColours for Theme {
     fn block_device(&self) -> Style { self.ui.filekinds.block_device }
     fn char_device(&self)  -> Style { self.ui.filekinds.char_device }
     fn socket(&self)       -> Style { self.ui.filekinds.socket }
+    fn door(&self)         -> Style { self.ui.filekinds.door }
     fn special(&self)      -> Style { self.ui.filekinds.special }
 }
 
@@ -280,6 +415,7 @@ impl render::GitRepoColours for Theme {
     fn no_repo(&self)      -> Style { self.ui.punctuation }
     fn git_clean(&self)    -> Style { self.ui.git_repo.git_clean }
     fn git_dirty(&self)    -> Style { self.ui.git_repo.git_dirty }
+    fn git_behind(&self)   -> Style { self.ui.git_repo.git_behind }
 }
 
 #[rustfmt::skip]
@@ -289,6 +425,7 @@ impl render::GroupColours for Theme {
     fn not_yours(&self)  -> Style { self.ui.users.group_other }
     fn root_group(&self) -> Style { self.ui.users.group_root }
     fn no_group(&self)   -> Style { self.ui.punctuation }
+    fn named_group(&self, name: &str) -> Option<Style> { self.ui.group_rules.get(name).copied() }
 }
 
 #[rustfmt::skip]
@@ -353,12 +490,12 @@ impl render::SizeColours for Theme {
 }
 
 #[rustfmt::skip]
-#[cfg(unix)]
 impl render::UserColours for Theme {
     fn you(&self)           -> Style { self.ui.users.user_you }
     fn other(&self)         -> Style { self.ui.users.user_other }
     fn root(&self)          -> Style { self.ui.users.user_root }
     fn no_user(&self)       -> Style { self.ui.punctuation }
+    fn named_user(&self, name: &str) -> Option<Style> { self.ui.user_rules.get(name).copied() }
 }
 
 #[rustfmt::skip]
@@ -371,6 +508,50 @@ impl FileNameColours for Theme {
     fn broken_control_char(&self) -> Style { apply_overlay(self.ui.control_char,   self.ui.broken_path_overlay) }
     fn executable_file(&self)     -> Style { self.ui.filekinds.executable }
     fn mount_point(&self)         -> Style { self.ui.filekinds.mount_point }
+    fn clone_marker(&self)        -> Style { self.ui.links.multi_link_file }
+    fn added_since_last(&self)    -> Style { self.ui.git.new }
+    fn modified_since_last(&self) -> Style { self.ui.git.modified }
+    fn in_use(&self)              -> Style { self.ui.in_use }
+    fn common_name(&self)         -> Style { self.ui.common_name }
+
+    fn special_dir(&self, dir: SpecialDir) -> Option<Style> {
+        match dir {
+            SpecialDir::Home    => self.ui.filekinds.home,
+            SpecialDir::Project => self.ui.filekinds.project,
+            _                   => self.ui.filekinds.user_dir,
+        }
+    }
+
+    fn hard_link_group(&self, group: usize) -> Style {
+        let groups = &self.ui.links.groups;
+        groups[group % groups.len()]
+    }
+
+    fn audit_finding(&self, severity: Severity) -> Style {
+        match severity {
+            Severity::Warning => self.ui.audit.warning,
+            Severity::Critical => self.ui.audit.critical,
+        }
+    }
+
+    fn tag(&self, colour: Option<TagColour>) -> Style {
+        use nu_ansi_term::Color::*;
+
+        if !self.ui.colourful {
+            return Style::default();
+        }
+
+        match colour {
+            Some(TagColour::Grey) => Fixed(244).normal(),
+            Some(TagColour::Green) => Green.normal(),
+            Some(TagColour::Purple) => Purple.normal(),
+            Some(TagColour::Blue) => Blue.normal(),
+            Some(TagColour::Yellow) => Yellow.normal(),
+            Some(TagColour::Red) => Red.normal(),
+            Some(TagColour::Orange) => Fixed(208).normal(),
+            None => Style::default(),
+        }
+    }
 
     fn colour_file(&self, file: &File<'_>) -> Style {
         self.exts
@@ -379,6 +560,22 @@ impl FileNameColours for Theme {
     }
 }
 
+#[cfg(unix)]
+#[rustfmt::skip]
+impl render::QuotaColours for Theme {
+    fn no_quota(&self)   -> Style { self.ui.punctuation }
+    fn separator(&self)  -> Style { self.ui.punctuation }
+    fn over_quota(&self) -> Style { self.ui.broken_symlink }
+}
+
+#[rustfmt::skip]
+impl render::TargetColours for Theme {
+    fn no_link(&self)      -> Style { self.ui.punctuation }
+    fn target(&self)       -> Style { self.ui.symlink_path }
+    fn broken(&self)       -> Style { self.ui.broken_symlink }
+    fn chain_length(&self) -> Style { self.ui.punctuation }
+}
+
 #[rustfmt::skip]
 impl render::SecurityCtxColours for Theme {
     fn none(&self)          -> Style { self.ui.security_context.none }
@@ -436,6 +633,7 @@ mod customs_test {
 
                 let definitions = Definitions {
                     ls: Some($ls.into()),
+                    theme: None,
                     exa: Some($exa.into()),
                 };
 
@@ -454,11 +652,12 @@ mod customs_test {
 
                 let definitions = Definitions {
                     ls: Some($ls.into()),
+                    theme: None,
                     exa: Some($exa.into()),
                 };
 
                 let (result, _) = definitions.parse_color_vars(&mut UiStyles::default());
-                assert_eq!(ExtensionMappings { mappings }, result);
+                assert_eq!(mappings, result.mappings);
             }
         };
         ($name:ident:  ls $ls:expr, exa $exa:expr  =>  colours $expected:ident -> $process_expected:expr, exts $mappings:expr) => {
@@ -474,12 +673,13 @@ mod customs_test {
 
                 let definitions = Definitions {
                     ls: Some($ls.into()),
+                    theme: None,
                     exa: Some($exa.into()),
                 };
 
                 let mut result = UiStyles::default();
                 let (exts, _) = definitions.parse_color_vars(&mut result);
-                assert_eq!(ExtensionMappings { mappings }, exts);
+                assert_eq!(mappings, exts.mappings);
                 assert_eq!($expected, result);
             }
         };
@@ -494,6 +694,7 @@ mod customs_test {
     test!(ls_bd:   ls "bd=36", exa ""  =>  colours c -> { c.filekinds.block_device = Cyan.normal();   });
     test!(ls_cd:   ls "cd=35", exa ""  =>  colours c -> { c.filekinds.char_device  = Purple.normal(); });
     test!(ls_ln:   ls "ln=34", exa ""  =>  colours c -> { c.filekinds.symlink      = Blue.normal();   });
+    test!(ls_door:  ls "do=31", exa ""  =>  colours c -> { c.filekinds.door         = Red.normal();    });
     test!(ls_or:   ls "or=33", exa ""  =>  colours c -> { c.broken_symlink         = Yellow.normal(); });
 
     // EZA_COLORS can affect all those colours too:
@@ -512,6 +713,9 @@ mod customs_test {
     test!(ls_exa_ex: ls "ex=32", exa "ex=33"  =>  colours c -> { c.filekinds.executable = Yellow.normal(); });
     test!(ls_exa_fi: ls "fi=33", exa "fi=34"  =>  colours c -> { c.filekinds.normal     = Blue.normal();   });
 
+    // Except for doors, as `do` means documents in EZA_COLORS:
+    test!(ls_exa_do: ls "do=31", exa "do=32"  =>  colours c -> { c.filekinds.door = Red.normal(); c.file_type.document = Green.normal(); });
+
     // But more importantly, EZA_COLORS has its own, special list of colours:
     test!(exa_ur:  ls "", exa "ur=38;5;100"  =>  colours c -> { c.perms.user_read           = Fixed(100).normal(); });
     test!(exa_uw:  ls "", exa "uw=38;5;101"  =>  colours c -> { c.perms.user_write          = Fixed(101).normal(); });
@@ -564,6 +768,8 @@ mod customs_test {
 
     test!(exa_lc:  ls "", exa "lc=38;5;121"  =>  colours c -> { c.links.normal                          = Fixed(121).normal(); });
     test!(exa_lm:  ls "", exa "lm=38;5;122"  =>  colours c -> { c.links.multi_link_file                 = Fixed(122).normal(); });
+    test!(exa_l1:  ls "", exa "l1=38;5;123"  =>  colours c -> { c.links.groups[0]                       = Fixed(123).normal(); });
+    test!(exa_l6:  ls "", exa "l6=38;5;124"  =>  colours c -> { c.links.groups[5]                       = Fixed(124).normal(); });
 
     test!(exa_ga:  ls "", exa "ga=38;5;123"  =>  colours c -> { c.git.new                               = Fixed(123).normal(); });
     test!(exa_gm:  ls "", exa "gm=38;5;124"  =>  colours c -> { c.git.modified                          = Fixed(124).normal(); });
@@ -574,6 +780,7 @@ mod customs_test {
     test!(exa_gc:  ls "", exa "gc=38;5;129"  =>  colours c -> { c.git.conflicted                        = Fixed(129).normal(); });
 
     test!(exa_xx:  ls "", exa "xx=38;5;128"  =>  colours c -> { c.punctuation                           = Fixed(128).normal(); });
+    test!(exa_tc:  ls "", exa "tc=38;5;128"  =>  colours c -> { c.tree                                  = Some(Fixed(128).normal()); });
     test!(exa_da:  ls "", exa "da=38;5;129"  =>  colours c -> { c.date                                  = Fixed(129).normal(); });
     test!(exa_in:  ls "", exa "in=38;5;130"  =>  colours c -> { c.inode                                 = Fixed(130).normal(); });
     test!(exa_bl:  ls "", exa "bl=38;5;131"  =>  colours c -> { c.blocks                                = Fixed(131).normal(); });
@@ -583,9 +790,14 @@ mod customs_test {
     test!(exa_oc:  ls "", exa "oc=38;5;135"  =>  colours c -> { c.octal                                 = Fixed(135).normal(); });
     test!(exa_ff:  ls "", exa "ff=38;5;136"  =>  colours c -> { c.flags                                 = Fixed(136).normal(); });
     test!(exa_bo:  ls "", exa "bO=4"         =>  colours c -> { c.broken_path_overlay                   = Style::default().underline(); });
+    test!(exa_iu:  ls "", exa "iu=38;5;137"  =>  colours c -> { c.in_use                                = Fixed(137).normal(); });
+    test!(exa_cn:  ls "", exa "cn=38;5;138"  =>  colours c -> { c.common_name                           = Fixed(138).normal(); });
 
     test!(exa_mp:  ls "", exa "mp=1;34;4"    =>  colours c -> { c.filekinds.mount_point                 = Blue.bold().underline(); });
     test!(exa_sp:  ls "", exa "sp=1;35;4"    =>  colours c -> { c.filekinds.special                     = Purple.bold().underline(); });
+    test!(exa_dh:  ls "", exa "dh=35"        =>  colours c -> { c.filekinds.home                        = Some(Purple.normal()); });
+    test!(exa_du:  ls "", exa "du=36"        =>  colours c -> { c.filekinds.user_dir                    = Some(Cyan.normal()); });
+    test!(exa_dp:  ls "", exa "dp=4"         =>  colours c -> { c.filekinds.project                     = Some(Style::default().underline()); });
 
     test!(exa_im:  ls "", exa "im=38;5;128"  =>  colours c -> { c.file_type.image                       = Fixed(128).normal(); });
     test!(exa_vi:  ls "", exa "vi=38;5;129"  =>  colours c -> { c.file_type.video                       = Fixed(129).normal(); });
@@ -606,6 +818,13 @@ mod customs_test {
     test!(exa_St:  ls "", exa "St=38;5;131"  =>  colours c -> { c.security_context.selinux.typ          = Fixed(131).normal(); });
     test!(exa_Sl:  ls "", exa "Sl=38;5;132"  =>  colours c -> { c.security_context.selinux.range        = Fixed(132).normal(); });
 
+    test!(exa_Aw:  ls "", exa "Aw=38;5;133"  =>  colours c -> { c.audit.warning                          = Fixed(133).normal(); });
+    test!(exa_Ac:  ls "", exa "Ac=38;5;134"  =>  colours c -> { c.audit.critical                         = Fixed(134).normal(); });
+
+    // Users and groups can be given colours by name:
+    test!(exa_user:   ls "", exa "user.root=31"        =>  colours c -> { c.user_rules.insert("root".into(), Red.normal()); });
+    test!(exa_group:  ls "", exa "group.www-data=36"   =>  colours c -> { c.group_rules.insert("www-data".into(), Cyan.normal()); });
+
     // All the while, LS_COLORS treats them as filenames:
     test!(ls_uu:   ls "uu=38;5;117", exa ""  =>  exts [ ("uu", Fixed(117).normal()) ]);
     test!(ls_un:   ls "un=38;5;118", exa ""  =>  exts [ ("un", Fixed(118).normal()) ]);
@@ -632,6 +851,12 @@ mod customs_test {
         ("1*1", Red.normal()), ("2*2", Green.normal()), ("3*3", Yellow.bold()), ("4*4", Blue.bold()), ("5*5", Purple.underline())
     ]);
 
+    // Globs can be given a priority, with higher priorities sorted last:
+    test!(exa_prio:      ls "", exa "*.log#2=31:*.log=32"        => exts [ ("*.log", Green.normal()), ("*.log", Red.normal()) ]);
+    test!(ls_exa_prio:   ls "*.o#1=31", exa "**/build/*=32"      => exts [ ("**/build/*", Green.normal()), ("*.o", Red.normal()) ]);
+    test!(exa_neg_prio:  ls "", exa "*.c=31:*#-1=32"             => exts [ ("*", Green.normal()), ("*.c", Red.normal()) ]);
+    test!(exa_not_prio:  ls "", exa "#*#=31"                     => exts [ ("#*#", Red.normal()) ]);
+
     // Finally, colours get applied right-to-left:
     test!(ls_overwrite:  ls "pi=31:pi=32:pi=33", exa ""  =>  colours c -> { c.filekinds.pipe = Yellow.normal(); });
     test!(exa_overwrite: ls "", exa "da=36:da=35:da=34"  =>  colours c -> { c.date = Blue.normal(); });
@@ -641,4 +866,89 @@ mod customs_test {
     test!(ls_fi_exa_txt:  ls "fi=33", exa "*.txt=31"  => colours c -> { c.filekinds.normal = Yellow.normal(); }, exts [ ("*.txt", Red.normal()) ]);
     test!(ls_txt_exa_fi:  ls "*.txt=31", exa "fi=33"  => colours c -> { c.filekinds.normal = Yellow.normal(); }, exts [ ("*.txt", Red.normal()) ]);
     test!(eza_fi_exa_txt: ls "", exa "fi=33:*.txt=31" => colours c -> { c.filekinds.normal = Yellow.normal(); }, exts [ ("*.txt", Red.normal()) ]);
+
+    /// Runs the test with `build/main.o` and `src/main.o` made in a
+    /// temporary directory, given a way to get them as files.
+    fn with_build_files(test: impl FnOnce(&dyn Fn(&str) -> File<'static>)) {
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["build", "src"] {
+            std::fs::create_dir_all(dir.path().join(sub)).unwrap();
+            std::fs::write(dir.path().join(sub).join("main.o"), "").unwrap();
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("build", dir.path().join("out")).unwrap();
+
+        test(&|path| File::from_args(dir.path().join(path), None, None, false, false).unwrap());
+    }
+
+    #[test]
+    fn path_globs() {
+        let glob = |pattern| glob::Pattern::new(pattern).unwrap();
+        let cwd = std::env::current_dir().unwrap();
+        let glob_matches =
+            |pattern: &glob::Pattern, file: &File<'_>| glob_matches(pattern, file, Some(&cwd));
+
+        with_build_files(|file| {
+            // Globs with a slash get matched against the whole path
+            assert!(glob_matches(&glob("**/build/*"), &file("build/main.o")));
+            assert!(!glob_matches(&glob("**/build/*"), &file("src/main.o")));
+            assert!(!glob_matches(&glob("build/*"), &file("build/main.o")));
+
+            // Without following the links on the way there
+            #[cfg(unix)]
+            {
+                assert!(glob_matches(&glob("**/out/*"), &file("out/main.o")));
+                assert!(!glob_matches(&glob("**/build/*"), &file("out/main.o")));
+            }
+
+            // And others only against the name
+            assert!(glob_matches(&glob("*.o"), &file("src/main.o")));
+            assert!(!glob_matches(&glob("src*"), &file("src/main.o")));
+        });
+    }
+
+    #[test]
+    fn glob_priorities() {
+        let style_for = |exa: &str, file: &File<'_>| {
+            let definitions = Definitions {
+                ls: None,
+                theme: None,
+                exa: Some(exa.into()),
+            };
+            let (exts, _) = definitions.parse_color_vars(&mut UiStyles::default());
+            let theme = Theme {
+                ui: UiStyles::default(),
+                exts: Box::new(NoFileStyle),
+                git_symbols: GitSymbols::default(),
+            };
+            exts.get_style(file, &theme)
+        };
+
+        with_build_files(|file| {
+            let object = file("build/main.o");
+
+            // The glob given last wins, unless an earlier one has a higher
+            // priority
+            assert_eq!(
+                style_for("*.o=31:**/build/*=32", &object),
+                Some(Green.normal())
+            );
+            assert_eq!(
+                style_for("*.o#1=31:**/build/*=32", &object),
+                Some(Red.normal())
+            );
+            assert_eq!(
+                style_for("*.o=31:**/build/*#-1=32", &object),
+                Some(Red.normal())
+            );
+            assert_eq!(
+                style_for("*.o#1=31:**/build/*#2=32", &object),
+                Some(Green.normal())
+            );
+            assert_eq!(
+                style_for("*.o#1=31:**/build/*=32", &file("src/main.o")),
+                Some(Red.normal())
+            );
+        });
+    }
 }
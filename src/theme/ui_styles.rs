@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use nu_ansi_term::Style;
 
 use crate::theme::lsc::Pair;
@@ -16,6 +18,7 @@ pub struct UiStyles {
     pub git_repo:         GitRepo,
     pub security_context: SecurityContext,
     pub file_type:        FileType,
+    pub audit:            Audit,
 
     pub punctuation:  Style,          // xx
     pub date:         Style,          // da
@@ -24,11 +27,17 @@ pub struct UiStyles {
     pub header:       Style,          // hd
     pub octal:        Style,          // oc
     pub flags:        Style,          // ff
+    pub tree:         Option<Style>,  // tc
 
     pub symlink_path:         Style,  // lp
     pub control_char:         Style,  // cc
     pub broken_symlink:       Style,  // or
     pub broken_path_overlay:  Style,  // bO
+    pub in_use:               Style,  // iu
+    pub common_name:          Style,  // cn
+
+    pub user_rules:   HashMap<String, Style>,  // user.NAME
+    pub group_rules:  HashMap<String, Style>,  // group.NAME
 }
 
 #[rustfmt::skip]
@@ -41,9 +50,13 @@ pub struct FileKinds {
     pub block_device: Style,  // bd
     pub char_device: Style,   // cd
     pub socket: Style,        // so
+    pub door: Style,          // do
     pub special: Style,       // sp
     pub executable: Style,    // ex
     pub mount_point: Style,   // mp
+    pub home: Option<Style>,      // dh
+    pub user_dir: Option<Style>,  // du
+    pub project: Option<Style>,   // dp
 }
 
 #[rustfmt::skip]
@@ -103,6 +116,7 @@ pub struct Users {
 pub struct Links {
     pub normal: Style,           // lc
     pub multi_link_file: Style,  // lm
+    pub groups: [Style; 6],      // l1 to l6
 }
 
 #[rustfmt::skip]
@@ -124,6 +138,7 @@ pub struct GitRepo {
     pub branch_other: Style, //Go
     pub git_clean: Style,    //Gc
     pub git_dirty: Style,    //Gd
+    pub git_behind: Style,   //Gb
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -142,6 +157,14 @@ pub struct SecurityContext {
     pub selinux: SELinuxContext,
 }
 
+/// Drawing styles for the risky permissions that `--security-audit` finds
+#[rustfmt::skip]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Audit {
+    pub warning:  Style,  // Aw
+    pub critical: Style,  // Ac
+}
+
 /// Drawing styles based on the type of file (video, image, compressed, etc)
 #[rustfmt::skip]
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
@@ -183,12 +206,23 @@ impl UiStyles {
             "or" => self.broken_symlink         = pair.to_style(),  // ORPHAN
              _   => return false,
              // Codes we don’t do anything with:
-             // MULTIHARDLINK, DOOR, SETUID, SETGID, CAPABILITY,
+             // MULTIHARDLINK, SETUID, SETGID, CAPABILITY,
              // STICKY_OTHER_WRITABLE, OTHER_WRITABLE, STICKY, MISSING
         };
         true
     }
 
+    /// Sets a value on this set of colours using one of the keys that only
+    /// `LS_COLORS` reads this way, because `EZA_COLORS` uses the same key
+    /// for something else: `do` is for doors there, but documents here.
+    pub fn set_ls_only(&mut self, pair: &Pair<'_>) -> bool {
+        match pair.key {
+            "do" => self.filekinds.door = pair.to_style(), // DOOR
+            _ => return false,
+        };
+        true
+    }
+
     /// Sets a value on this set of colours using one of the keys understood
     /// by the `EZA_COLORS` environment variable. Invalid keys set nothing,
     /// but return false. This doesn’t take the `LS_COLORS` keys into account,
@@ -234,6 +268,12 @@ impl UiStyles {
 
             "lc" => self.links.normal                   = pair.to_style(),
             "lm" => self.links.multi_link_file          = pair.to_style(),
+            "l1" => self.links.groups[0]                = pair.to_style(),
+            "l2" => self.links.groups[1]                = pair.to_style(),
+            "l3" => self.links.groups[2]                = pair.to_style(),
+            "l4" => self.links.groups[3]                = pair.to_style(),
+            "l5" => self.links.groups[4]                = pair.to_style(),
+            "l6" => self.links.groups[5]                = pair.to_style(),
 
             "ga" => self.git.new                        = pair.to_style(),
             "gm" => self.git.modified                   = pair.to_style(),
@@ -247,6 +287,7 @@ impl UiStyles {
             "Go" => self.git_repo.branch_other          = pair.to_style(),
             "Gc" => self.git_repo.git_clean             = pair.to_style(),
             "Gd" => self.git_repo.git_dirty             = pair.to_style(),
+            "Gb" => self.git_repo.git_behind            = pair.to_style(),
 
             "xx" => self.punctuation                    = pair.to_style(),
             "da" => self.date                           = pair.to_style(),
@@ -255,11 +296,17 @@ impl UiStyles {
             "hd" => self.header                         = pair.to_style(),
             "oc" => self.octal                          = pair.to_style(),
             "ff" => self.flags                          = pair.to_style(),
+            "tc" => self.tree                           = Some(pair.to_style()),
             "lp" => self.symlink_path                   = pair.to_style(),
             "cc" => self.control_char                   = pair.to_style(),
             "bO" => self.broken_path_overlay            = pair.to_style(),
+            "iu" => self.in_use                         = pair.to_style(),
+            "cn" => self.common_name                    = pair.to_style(),
 
             "mp" => self.filekinds.mount_point          = pair.to_style(),
+            "dh" => self.filekinds.home                 = Some(pair.to_style()),
+            "du" => self.filekinds.user_dir             = Some(pair.to_style()),
+            "dp" => self.filekinds.project              = Some(pair.to_style()),
             "sp" => self.filekinds.special              = pair.to_style(),  // Catch-all for unrecognized file kind
 
             "im" => self.file_type.image                = pair.to_style(),
@@ -280,12 +327,30 @@ impl UiStyles {
             "St" => self.security_context.selinux.typ   = pair.to_style(),
             "Sl" => self.security_context.selinux.range = pair.to_style(),
 
+            "Aw" => self.audit.warning                  = pair.to_style(),
+            "Ac" => self.audit.critical                 = pair.to_style(),
+
              _   => return false,
         };
 
         true
     }
 
+    /// Sets the colour of the user or group with a name, using a key such
+    /// as `user.root` or `group.www-data`. Other keys set nothing, but
+    /// return false.
+    pub fn set_owner(&mut self, pair: &Pair<'_>) -> bool {
+        if let Some(name) = pair.key.strip_prefix("user.") {
+            self.user_rules.insert(name.to_string(), pair.to_style());
+        } else if let Some(name) = pair.key.strip_prefix("group.") {
+            self.group_rules.insert(name.to_string(), pair.to_style());
+        } else {
+            return false;
+        }
+
+        true
+    }
+
     pub fn set_number_style(&mut self, style: Style) {
         self.size.number_byte = style;
         self.size.number_kilo = style;
@@ -0,0 +1,311 @@
+//! Giving up on filesystem calls that take too long, for `--io-timeout`.
+//!
+//! A network filesystem whose server has gone away can leave `stat` and
+//! friends blocked for minutes, and with them the whole listing. Once a
+//! limit has been set, a pool of worker threads gets started, and the calls
+//! that go through a `Deadline` get handed to it while the caller waits for
+//! an answer. The time counts from when a worker starts on the call, so
+//! calls waiting their turn behind others don’t get given up on. If no
+//! answer comes in time, the caller carries on as though the call had
+//! failed. A thread stuck in the kernel can’t be stopped from outside, so
+//! the worker running it gets left behind, and another one gets started to
+//! take its place.
+//!
+//! The calls for one directory’s entries share a deadline, so when its
+//! mount hangs, it costs one limit’s worth of waiting, rather than one for
+//! every file in it.
+//!
+//! Without a limit, the calls run on the thread that made them, as they
+//! would have anyway.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+#[cfg(feature = "git")]
+use std::thread::JoinHandle;
+use std::time::Duration;
+#[cfg(feature = "git")]
+use std::time::Instant;
+
+use log::*;
+
+/// The limit in milliseconds, or zero for no limit.
+static LIMIT: AtomicU64 = AtomicU64::new(0);
+
+/// The workers that calls get run on, once a limit has been set.
+static POOL: OnceLock<Pool> = OnceLock::new();
+
+/// How many calls can be waited on at once. They spend their time blocked
+/// on the filesystem rather than using the CPU, so this doesn’t depend on
+/// how many cores there are.
+const WORKERS: usize = 8;
+
+/// How often to check whether a thread that can only be joined has finished.
+#[cfg(feature = "git")]
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Gives up on calls that take longer than the given time from now on,
+/// starting the threads they get run on. Fails if none of them could be
+/// started.
+pub fn set(limit: Duration) -> io::Result<()> {
+    if POOL.get().is_none() {
+        let pool = Pool::start(WORKERS)?;
+        let _ = POOL.set(pool);
+    }
+
+    let millis = u64::try_from(limit.as_millis()).unwrap_or(u64::MAX).max(1);
+    LIMIT.store(millis, Ordering::Relaxed);
+    Ok(())
+}
+
+fn limit() -> Option<Duration> {
+    match LIMIT.load(Ordering::Relaxed) {
+        0 => None,
+        millis => Some(Duration::from_millis(millis)),
+    }
+}
+
+/// A deadline shared by a group of calls, such as the ones for a
+/// directory’s entries. Each call waits up to the limit for an answer, but
+/// as soon as one of them doesn’t get one, the rest give up straight away.
+#[derive(Debug, Clone, Default)]
+pub struct Deadline {
+    passed: Arc<AtomicBool>,
+}
+
+impl Deadline {
+    /// Runs the function, returning what it returned, or `None` if it took
+    /// longer than the limit, or the deadline has already passed.
+    pub fn run<T: Send + 'static>(&self, f: impl FnOnce() -> T + Send + 'static) -> Option<T> {
+        match (limit(), POOL.get()) {
+            (Some(limit), Some(pool)) => self.run_on(pool, limit, f),
+            _ => Some(f()),
+        }
+    }
+
+    fn run_on<T: Send + 'static>(
+        &self,
+        pool: &Pool,
+        limit: Duration,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> Option<T> {
+        if self.has_passed() {
+            return None;
+        }
+
+        let result = pool.run(limit, f);
+        if result.is_none() {
+            warn!("Gave up on a call after {:?}", limit);
+            self.passed.store(true, Ordering::Relaxed);
+        }
+        result
+    }
+
+    /// Runs a function that does some IO, turning it taking too long into an
+    /// error of its own.
+    pub fn io<T: Send + 'static>(
+        &self,
+        f: impl FnOnce() -> io::Result<T> + Send + 'static,
+    ) -> io::Result<T> {
+        self.run(f)
+            .unwrap_or_else(|| Err(io::Error::new(io::ErrorKind::TimedOut, "timed out")))
+    }
+
+    /// Whether one of the calls sharing this deadline has taken too long.
+    pub fn has_passed(&self) -> bool {
+        self.passed.load(Ordering::Relaxed)
+    }
+}
+
+/// Waits for a thread that’s already been started, returning `None` if it
+/// didn’t finish in time.
+#[cfg(feature = "git")]
+pub fn join<T>(thread: JoinHandle<T>) -> Option<T> {
+    let Some(limit) = limit() else {
+        return Some(thread.join().expect("Worker thread panicked"));
+    };
+
+    let deadline = Instant::now() + limit;
+    while !thread.is_finished() {
+        if Instant::now() >= deadline {
+            warn!("Gave up waiting for a thread after {:?}", limit);
+            return None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    Some(thread.join().expect("Worker thread panicked"))
+}
+
+/// A call for a worker to run, which returns whether the worker should
+/// carry on taking calls afterwards.
+type Job = Box<dyn FnOnce() -> bool + Send>;
+
+/// Worker threads that take calls from a queue, one at a time.
+struct Pool {
+    sender: Mutex<Sender<Job>>,
+    receiver: Arc<Mutex<Receiver<Job>>>,
+}
+
+/// How far a call that a worker has started on has got, so that it’s known
+/// whether giving up on it leaves the worker stuck.
+const RUNNING: u8 = 0;
+const FINISHED: u8 = 1;
+const ABANDONED: u8 = 2;
+
+impl Pool {
+    fn start(workers: usize) -> io::Result<Self> {
+        let (sender, receiver) = mpsc::channel();
+        let pool = Self {
+            sender: Mutex::new(sender),
+            receiver: Arc::new(Mutex::new(receiver)),
+        };
+
+        for _ in 0..workers {
+            pool.add_worker()?;
+        }
+        Ok(pool)
+    }
+
+    fn add_worker(&self) -> io::Result<()> {
+        let receiver = Arc::clone(&self.receiver);
+        thread::Builder::new()
+            .name(String::from("eza-io"))
+            .spawn(move || loop {
+                // The lock only gets held while waiting for the next call,
+                // not while running it.
+                let job = receiver.lock().unwrap().recv();
+                let Ok(job) = job else { return };
+                if !job() {
+                    return;
+                }
+            })?;
+        Ok(())
+    }
+
+    fn run<T: Send + 'static>(
+        &self,
+        limit: Duration,
+        f: impl FnOnce() -> T + Send + 'static,
+    ) -> Option<T> {
+        let (started_sender, started_receiver) = mpsc::channel();
+        let (sender, receiver) = mpsc::channel();
+        let state = Arc::new(AtomicU8::new(RUNNING));
+
+        let job_state = Arc::clone(&state);
+        let job = Box::new(move || {
+            let _ = started_sender.send(());
+            let result = f();
+
+            // The receiver is gone if this took too long, and nobody is
+            // waiting for the result any more. Another worker has been
+            // started in place of this one by then, so this one stops.
+            let finished = job_state
+                .compare_exchange(RUNNING, FINISHED, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok();
+            let _ = sender.send(result);
+            finished
+        });
+
+        // The pool holds on to the receiving end, so this can’t fail.
+        self.sender
+            .lock()
+            .unwrap()
+            .send(job)
+            .expect("Pool lost its queue");
+
+        // The limit is for running the call, not for waiting in the queue
+        // behind others, so the clock only starts once a worker has it.
+        started_receiver.recv().ok()?;
+        if let Ok(result) = receiver.recv_timeout(limit) {
+            return Some(result);
+        }
+
+        // The worker is stuck on the call, so there needs to be another to
+        // keep the pool the same size, unless it finished just now.
+        let abandoned =
+            state.compare_exchange(RUNNING, ABANDONED, Ordering::AcqRel, Ordering::Acquire);
+        if abandoned.is_ok() {
+            if let Err(e) = self.add_worker() {
+                error!("Couldn't start a worker thread to replace a stuck one: {e}");
+            }
+            return None;
+        }
+        receiver.recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn in_time() {
+        let pool = Pool::start(1).unwrap();
+        assert_eq!(pool.run(Duration::from_secs(10), || 2 + 2), Some(4));
+    }
+
+    #[test]
+    fn too_slow() {
+        let pool = Pool::start(1).unwrap();
+        let result = pool.run(Duration::from_millis(10), || {
+            thread::sleep(Duration::from_secs(1));
+            4
+        });
+        assert_eq!(result, None);
+
+        // The stuck worker got replaced, so there’s still one free.
+        assert_eq!(pool.run(Duration::from_secs(10), || 2 + 2), Some(4));
+    }
+
+    #[test]
+    fn queued_calls_wait_their_turn() {
+        // More calls than workers, which together take longer than the
+        // limit, though none of them does on its own.
+        let pool = Pool::start(2).unwrap();
+        let results = thread::scope(|scope| {
+            let calls = (0..8)
+                .map(|n| {
+                    let pool = &pool;
+                    scope.spawn(move || {
+                        pool.run(Duration::from_millis(250), move || {
+                            thread::sleep(Duration::from_millis(100));
+                            n
+                        })
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            calls
+                .into_iter()
+                .map(|call| call.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(results, (0..8).map(Some).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn shared_deadline() {
+        let pool = Pool::start(2).unwrap();
+        let deadline = Deadline::default();
+
+        let slow = deadline.run_on(&pool, Duration::from_millis(10), || {
+            thread::sleep(Duration::from_secs(1));
+        });
+        assert_eq!(slow, None);
+        assert!(deadline.has_passed());
+
+        // Once one call has taken too long, the rest don’t get tried.
+        assert_eq!(
+            deadline.run_on(&pool, Duration::from_secs(10), || 2 + 2),
+            None
+        );
+        assert_eq!(
+            Deadline::default().run_on(&pool, Duration::from_secs(10), || 2 + 2),
+            Some(4)
+        );
+    }
+}
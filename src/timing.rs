@@ -0,0 +1,171 @@
+//! Timing how long each part of a listing takes, for `--debug-timing`.
+//!
+//! When a listing is slow, it’s rarely obvious why: it could be a network
+//! filesystem taking its time over `stat`, a huge Git repository, or
+//! extended attributes that have to be fetched one file at a time. The
+//! parts of eza that touch the filesystem or Git run inside `time`, which
+//! adds up how long each phase took and how many times it ran, and the
+//! totals get printed to stderr once the listing is done.
+//!
+//! Nothing gets measured unless timing has been turned on, so the only cost
+//! the rest of the time is checking one flag. Phases can run on several
+//! threads at once, and inside one another — files get statted while the
+//! directory they’re in is being read, for example — so the times are the
+//! sum of every run, rather than slices of the wall-clock time.
+
+use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// A part of a listing that gets timed.
+#[derive(PartialEq, Eq, Debug, Copy, Clone)]
+pub enum Phase {
+    /// Reading the entries of a directory.
+    ReadDir,
+
+    /// Getting the metadata of a file.
+    Stat,
+
+    /// Finding Git repositories, and looking up files’ statuses in them.
+    Git,
+
+    /// Reading the extended attributes of a file.
+    Xattr,
+
+    /// Sorting a list of files.
+    Sort,
+
+    /// Writing out a view of some files, which includes any of the other
+    /// phases that happen while doing so.
+    Render,
+}
+
+impl Phase {
+    const ALL: [Self; 6] = [
+        Self::ReadDir,
+        Self::Stat,
+        Self::Git,
+        Self::Xattr,
+        Self::Sort,
+        Self::Render,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::ReadDir => "directory read",
+            Self::Stat => "stat",
+            Self::Git => "git status",
+            Self::Xattr => "xattr",
+            Self::Sort => "sort",
+            Self::Render => "render",
+        }
+    }
+
+    /// What gets counted each time this phase runs, in the singular and
+    /// the plural.
+    fn unit(self) -> (&'static str, &'static str) {
+        match self {
+            Self::ReadDir => ("directory", "directories"),
+            Self::Stat | Self::Xattr => ("file", "files"),
+            Self::Git => ("lookup", "lookups"),
+            Self::Sort => ("sort", "sorts"),
+            Self::Render => ("view", "views"),
+        }
+    }
+}
+
+/// The running totals for one phase.
+struct Counter {
+    nanos: AtomicU64,
+    count: AtomicU64,
+}
+
+impl Counter {
+    const fn new() -> Self {
+        Self {
+            nanos: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+}
+
+static COUNTERS: [Counter; 6] = [
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+    Counter::new(),
+];
+
+/// Starts timing every phase from now on.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+}
+
+/// Runs the function, adding how long it took to the totals for the phase
+/// if timing is turned on.
+pub fn time<T>(phase: Phase, f: impl FnOnce() -> T) -> T {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return f();
+    }
+
+    let start = Instant::now();
+    let result = f();
+    let elapsed = u64::try_from(start.elapsed().as_nanos()).unwrap_or(u64::MAX);
+
+    let counter = &COUNTERS[phase as usize];
+    counter.nanos.fetch_add(elapsed, Ordering::Relaxed);
+    counter.count.fetch_add(1, Ordering::Relaxed);
+    result
+}
+
+/// Prints the totals for every phase to stderr, if timing is turned on.
+pub fn report() {
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    let mut stderr = io::stderr().lock();
+    let _ = writeln!(stderr, "eza: timings (summed across threads):");
+    for phase in Phase::ALL {
+        let counter = &COUNTERS[phase as usize];
+        let elapsed = Duration::from_nanos(counter.nanos.load(Ordering::Relaxed));
+        let count = counter.count.load(Ordering::Relaxed);
+        let _ = writeln!(stderr, "{}", report_line(phase, elapsed, count));
+    }
+}
+
+fn report_line(phase: Phase, elapsed: Duration, count: u64) -> String {
+    let (one, many) = phase.unit();
+    format!(
+        "  {:<15}{:>10.3} ms  {count} {}",
+        format!("{}:", phase.name()),
+        elapsed.as_secs_f64() * 1000.0,
+        if count == 1 { one } else { many }
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn disabled_still_runs() {
+        assert_eq!(time(Phase::Sort, || 2 + 2), 4);
+    }
+
+    #[test]
+    fn lines() {
+        assert_eq!(
+            report_line(Phase::ReadDir, Duration::from_micros(1500), 3),
+            "  directory read:     1.500 ms  3 directories"
+        );
+        assert_eq!(
+            report_line(Phase::Render, Duration::ZERO, 1),
+            "  render:             0.000 ms  1 view"
+        );
+    }
+}
@@ -0,0 +1,202 @@
+//! Listing the same paths again whenever something in them changes, for
+//! `--watch`, and running a command each time, for `--exec`.
+//!
+//! There’s no portable way to be told when files change, so the paths get
+//! looked at again every second, down to as many levels as the listing goes,
+//! noting each file’s modification time and size. When anything has been
+//! added, removed, or changed, the screen gets cleared and the listing gets
+//! shown again, followed by the output of the command, if there is one.
+//!
+//! The command gets run by the shell, with `{}` replaced by the paths that
+//! changed, each quoted, so `--exec 'cargo fmt -- {}'` only touches those.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+use log::*;
+
+use crate::fs::walk::{is_hidden, walk};
+
+/// How long to wait between looks at the paths.
+pub const INTERVAL: Duration = Duration::from_secs(1);
+
+/// What to do when something changes.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Options {
+    /// The command to run after listing the paths again, if any.
+    pub exec: Option<String>,
+}
+
+/// When each file was last modified, and how big it was.
+#[derive(PartialEq, Eq, Debug, Default)]
+pub struct Snapshot {
+    files: BTreeMap<PathBuf, (Option<SystemTime>, u64)>,
+}
+
+impl Snapshot {
+    /// Looks at the given paths, and the files in them down to the given
+    /// number of levels, or all the way down if there’s no limit. Files
+    /// starting with a dot get left out unless they’re wanted.
+    pub fn take(paths: &[&OsStr], levels: Option<usize>, dotfiles: bool) -> Self {
+        let mut snapshot = Self::default();
+        for path in paths.iter().map(Path::new) {
+            let Ok(metadata) = fs::symlink_metadata(path) else {
+                continue;
+            };
+
+            snapshot.add(path.to_path_buf(), &metadata);
+            if !metadata.is_dir() || levels == Some(0) {
+                continue;
+            }
+
+            walk(path, levels, dotfiles, &mut |_, entries| {
+                let entries = entries.into_iter().flatten();
+                for entry in entries.filter(|entry| dotfiles || !is_hidden(entry)) {
+                    if let Ok(metadata) = entry.metadata() {
+                        snapshot.add(entry.path(), &metadata);
+                    }
+                }
+            });
+        }
+        snapshot
+    }
+
+    fn add(&mut self, path: PathBuf, metadata: &fs::Metadata) {
+        let stamp = (metadata.modified().ok(), metadata.len());
+        self.files.insert(path, stamp);
+    }
+
+    /// The paths that were added, removed, or changed between this snapshot
+    /// and a later one, in order.
+    pub fn changes(&self, later: &Self) -> Vec<PathBuf> {
+        let paths: BTreeSet<&PathBuf> = self.files.keys().chain(later.files.keys()).collect();
+
+        paths
+            .into_iter()
+            .filter(|path| self.files.get(*path) != later.files.get(*path))
+            .cloned()
+            .collect()
+    }
+}
+
+/// The command with `{}` replaced by the changed paths.
+fn fill_in(command: &str, paths: &[PathBuf]) -> String {
+    let quoted = paths
+        .iter()
+        .map(|path| quote(&path.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    command.replace("{}", &quoted)
+}
+
+/// Runs the command through the shell, waiting for it to finish.
+pub fn exec(command: &str, paths: &[PathBuf]) {
+    let command = fill_in(command, paths);
+    debug!("Running {:?}", command);
+
+    match shell(&command).status() {
+        Ok(status) if !status.success() => eprintln!("eza: {command}: {status}"),
+        Ok(_) => {}
+        Err(e) => eprintln!("eza: {command}: {e}"),
+    }
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// `cmd` gets the command as it is, rather than quoted the way other
+/// programs expect their arguments, and `/S` has it take off only the
+/// outermost quotes.
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut shell = Command::new("cmd");
+    shell.arg("/S").arg("/C").raw_arg(format!("\"{command}\""));
+    shell
+}
+
+/// Quotes a path so that the shell passes it on as it is.
+#[cfg(unix)]
+fn quote(path: &str) -> String {
+    format!("'{}'", path.replace('\'', r"'\''"))
+}
+
+/// `cmd` expands `%VAR%` even inside double quotes, so the quotes around
+/// the path and everything special to `cmd` get escaped with a `^`, which
+/// `cmd` takes off again after it’s done expanding. The program then sees
+/// the path in quotes, with any backslashes at the end doubled so they don’t
+/// escape the closing quote.
+#[cfg(windows)]
+fn quote(path: &str) -> String {
+    let trailing = path.len() - path.trim_end_matches('\\').len();
+    let quoted = format!("\"{path}{}\"", "\\".repeat(trailing));
+
+    let mut escaped = String::with_capacity(quoted.len() * 2);
+    for c in quoted.chars() {
+        if "()[]%!^\"`<>&|;, *?".contains(c) {
+            escaped.push('^');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    fn stamp(secs: u64, size: u64) -> (Option<SystemTime>, u64) {
+        (
+            Some(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)),
+            size,
+        )
+    }
+
+    fn snapshot(files: &[(&str, (Option<SystemTime>, u64))]) -> Snapshot {
+        Snapshot {
+            files: files
+                .iter()
+                .map(|(path, stamp)| (PathBuf::from(path), *stamp))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn changes() {
+        let before = snapshot(&[
+            ("same", stamp(1, 10)),
+            ("touched", stamp(1, 10)),
+            ("grown", stamp(1, 10)),
+            ("removed", stamp(1, 10)),
+        ]);
+        let after = snapshot(&[
+            ("same", stamp(1, 10)),
+            ("touched", stamp(2, 10)),
+            ("grown", stamp(1, 20)),
+            ("added", stamp(2, 10)),
+        ]);
+
+        assert_eq!(
+            before.changes(&after),
+            ["added", "grown", "removed", "touched"].map(PathBuf::from)
+        );
+        assert_eq!(after.changes(&after), Vec::<PathBuf>::new());
+    }
+
+    #[test]
+    fn placeholders() {
+        let paths = [PathBuf::from("a b"), PathBuf::from("it's")];
+        assert_eq!(fill_in("wc -l {}", &paths), r"wc -l 'a b' 'it'\''s'");
+        assert_eq!(fill_in("make", &paths), "make");
+    }
+}
@@ -0,0 +1,178 @@
+//! Finding every program with a name along `$PATH`, for `--which`.
+//!
+//! `--which NAME` lists each executable called `NAME` in the directories in
+//! `$PATH`, in the order the shell would find them, so the first one listed
+//! is the one that runs. Directories often end up in `$PATH` under more than
+//! one name, or hold links to the same program as another, so matches that
+//! turn out to be the same file as an earlier one get marked with the path
+//! of that one once the listing is shown.
+
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::*;
+
+/// The matches that are the same file as an earlier match, along with the
+/// path of the earliest one.
+#[derive(Debug, Default)]
+pub struct Duplicates(HashMap<PathBuf, PathBuf>);
+
+impl Duplicates {
+    /// The path of an earlier match that the given one is the same file
+    /// as, if there is one.
+    pub fn original_of(&self, path: &Path) -> Option<&Path> {
+        self.0.get(path).map(PathBuf::as_path)
+    }
+}
+
+/// Finds every executable file with the given name in the directories in
+/// the `$PATH` value, in order, along with which of them are the same file
+/// as one before them.
+pub fn find_all(name: &OsStr, path: &OsStr) -> (Vec<OsString>, Duplicates) {
+    let matches = search(name, path);
+    let duplicates = Duplicates(duplicates(&matches));
+    let matches = matches.into_iter().map(PathBuf::into_os_string).collect();
+    (matches, duplicates)
+}
+
+fn search(name: &OsStr, path: &OsStr) -> Vec<PathBuf> {
+    let mut matches: Vec<PathBuf> = Vec::new();
+
+    for dir in std::env::split_paths(path) {
+        // An empty entry means the current directory, as it does to shells.
+        let dir = if dir.as_os_str().is_empty() {
+            PathBuf::from(".")
+        } else {
+            dir
+        };
+
+        for candidate in candidates(&dir, name) {
+            if is_executable(&candidate) && !matches.contains(&candidate) {
+                debug!("Found {:?}", candidate);
+                matches.push(candidate);
+            }
+        }
+    }
+
+    matches
+}
+
+/// Which matches are the same file as an earlier one, once links have been
+/// followed.
+#[cfg(unix)]
+fn duplicates(matches: &[PathBuf]) -> HashMap<PathBuf, PathBuf> {
+    use std::os::unix::fs::MetadataExt;
+
+    let mut originals: HashMap<(u64, u64), &PathBuf> = HashMap::new();
+    let mut duplicates = HashMap::new();
+
+    for path in matches {
+        let Ok(metadata) = fs::metadata(path) else {
+            continue;
+        };
+
+        let original = *originals
+            .entry((metadata.dev(), metadata.ino()))
+            .or_insert(path);
+        if original != path {
+            duplicates.insert(path.clone(), original.clone());
+        }
+    }
+
+    duplicates
+}
+
+/// Without inode numbers, files can only be told apart by where their
+/// links lead.
+#[cfg(not(unix))]
+fn duplicates(matches: &[PathBuf]) -> HashMap<PathBuf, PathBuf> {
+    let mut originals: HashMap<PathBuf, &PathBuf> = HashMap::new();
+    let mut duplicates = HashMap::new();
+
+    for path in matches {
+        let Ok(canonical) = fs::canonicalize(path) else {
+            continue;
+        };
+
+        let original = *originals.entry(canonical).or_insert(path);
+        if original != path {
+            duplicates.insert(path.clone(), original.clone());
+        }
+    }
+
+    duplicates
+}
+
+/// The paths in the directory that a program with the name could be at.
+#[cfg(unix)]
+fn candidates(dir: &Path, name: &OsStr) -> Vec<PathBuf> {
+    vec![dir.join(name)]
+}
+
+/// On Windows, programs get found by their name without an extension, as
+/// long as the extension is one of the ones in `%PATHEXT%`.
+#[cfg(not(unix))]
+fn candidates(dir: &Path, name: &OsStr) -> Vec<PathBuf> {
+    let extensions =
+        std::env::var_os("PATHEXT").unwrap_or_else(|| OsString::from(".COM;.EXE;.BAT;.CMD"));
+
+    let mut candidates = vec![dir.join(name)];
+    for extension in extensions.to_string_lossy().split(';') {
+        if !extension.is_empty() {
+            let mut with_extension = name.to_os_string();
+            with_extension.push(extension);
+            candidates.push(dir.join(with_extension));
+        }
+    }
+    candidates
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.is_file())
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    use std::os::unix::fs::{symlink, PermissionsExt};
+
+    #[test]
+    fn finds_programs_in_order() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        let (first, second, third) = (root.join("a"), root.join("b"), root.join("c"));
+        for dir in [&first, &second, &third] {
+            fs::create_dir_all(dir).unwrap();
+        }
+
+        fs::write(first.join("tool"), "").unwrap();
+        fs::set_permissions(first.join("tool"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::write(second.join("tool"), "").unwrap();
+        symlink(first.join("tool"), third.join("tool")).unwrap();
+
+        let path = std::env::join_paths([&first, &second, &third, &first]).unwrap();
+        let matches = search(OsStr::new("tool"), &path);
+
+        // The file in the second directory isn’t executable, and the first
+        // directory only counts once.
+        assert_eq!(matches, vec![first.join("tool"), third.join("tool")]);
+
+        let duplicates = duplicates(&matches);
+        assert_eq!(
+            duplicates.get(&third.join("tool")),
+            Some(&first.join("tool"))
+        );
+        assert_eq!(duplicates.get(&first.join("tool")), None);
+    }
+}